@@ -2,21 +2,42 @@ mod starwars;
 
 use async_graphql::{
     http::{playground_source, GraphQLPlaygroundConfig},
-    EmptyMutation, EmptySubscription, Request, Response, Schema,
+    EmptyMutation, Request, Response, Schema,
 };
+use futures_util::{stream, StreamExt};
 use poem::{
     get, handler,
     listener::TcpListener,
-    web::{Data, Html, Json},
+    post,
+    web::{
+        sse::{Event, SSE},
+        Data, Html, Json,
+    },
     EndpointExt, IntoResponse, Route, Server,
 };
-use starwars::{QueryRoot, StarWars, StarWarsSchema};
+use starwars::{QueryRoot, StarWars, StarWarsSchema, SubscriptionRoot};
 
 #[handler]
 async fn graphql_handler(schema: Data<&StarWarsSchema>, req: Json<Request>) -> Json<Response> {
     Json(schema.execute(req.0).await)
 }
 
+/// Executes the operation with the "distinct connections mode" of the
+/// [GraphQL over SSE](https://github.com/enisdenjo/graphql-sse/blob/master/PROTOCOL.md)
+/// protocol, which can be used for the subscriptions where websockets are
+/// blocked.
+///
+/// Each result is sent as a `next` event, followed by a `complete` event.
+#[handler]
+fn graphql_sse_handler(schema: Data<&StarWarsSchema>, req: Json<Request>) -> SSE {
+    let results = schema
+        .execute_stream(req.0)
+        .map(|resp| Event::json(&resp).unwrap().event_type("next"));
+    SSE::new(results.chain(stream::once(async {
+        Event::message("").event_type("complete")
+    })))
+}
+
 #[handler]
 fn graphql_playground() -> impl IntoResponse {
     Html(playground_source(GraphQLPlaygroundConfig::new("/")))
@@ -29,15 +50,17 @@ async fn main() -> Result<(), std::io::Error> {
     }
     tracing_subscriber::fmt::init();
 
-    let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+    let schema = Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
         .data(StarWars::new())
         .finish();
 
     let app = Route::new()
         .at("/", get(graphql_playground).post(graphql_handler))
+        .at("/stream", post(graphql_sse_handler))
         .data(schema);
 
     println!("Playground: http://localhost:3000");
+    println!("GraphQL over SSE: http://localhost:3000/stream");
 
     Server::new(TcpListener::bind("0.0.0.0:3000"))
         .run(app)
@@ -2,11 +2,11 @@ mod model;
 
 use std::collections::HashMap;
 
-use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+use async_graphql::{EmptyMutation, Schema};
 use model::Episode;
-pub use model::QueryRoot;
+pub use model::{QueryRoot, SubscriptionRoot};
 use slab::Slab;
-pub type StarWarsSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+pub type StarWarsSchema = Schema<QueryRoot, EmptyMutation, SubscriptionRoot>;
 
 pub struct StarWarsChar {
     id: &'static str,
@@ -2,8 +2,10 @@
 
 use async_graphql::{
     connection::{query, Connection, Edge},
-    Context, Enum, Error, Interface, Object, OutputType, Result,
+    Context, Enum, Error, Interface, Object, OutputType, Result, Subscription,
 };
+use futures_util::{stream, Stream};
+use tokio::time::Duration;
 
 use super::StarWars;
 use crate::starwars::StarWarsChar;
@@ -171,6 +173,22 @@ impl QueryRoot {
     }
 }
 
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Counts from zero, one number per second.
+    async fn interval(&self, #[graphql(default = 10)] count: i32) -> impl Stream<Item = i32> {
+        stream::unfold(0, move |n| async move {
+            if n >= count {
+                return None;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Some((n, n + 1))
+        })
+    }
+}
+
 #[derive(Interface)]
 #[graphql(
     field(name = "id", type = "&str"),
@@ -0,0 +1,105 @@
+mod store;
+
+use std::sync::Arc;
+
+use poem::{
+    error::{BadRequest, NotFoundError},
+    get, handler,
+    http::{StatusCode, Uri},
+    listener::TcpListener,
+    post,
+    web::{Data, Json, Path, Redirect},
+    EndpointExt, Error, IntoResponse, Result, Route, Server,
+};
+use serde::{Deserialize, Serialize};
+use store::{LinkStore, MemoryStore};
+
+type Store = Arc<dyn LinkStore>;
+
+#[derive(Deserialize)]
+struct CreateLink {
+    url: String,
+    code: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LinkInfo {
+    code: String,
+    url: String,
+    hits: u64,
+}
+
+#[handler]
+async fn create(store: Data<&Store>, Json(req): Json<CreateLink>) -> Result<impl IntoResponse> {
+    let uri = req.url.parse::<Uri>().map_err(BadRequest)?;
+    if !matches!(uri.scheme_str(), Some("http" | "https")) {
+        return Err(Error::from_string(
+            "only http and https links are allowed",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    let code = match req.code {
+        Some(code) => {
+            if code.is_empty() || !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(Error::from_string("invalid code", StatusCode::BAD_REQUEST));
+            }
+            if !store.insert(&code, &req.url).await {
+                return Err(Error::from_string(
+                    "the code is already used",
+                    StatusCode::CONFLICT,
+                ));
+            }
+            code
+        }
+        None => loop {
+            let code = store.generate_code().await;
+            if store.insert(&code, &req.url).await {
+                break code;
+            }
+        },
+    };
+
+    Ok(Json(LinkInfo {
+        code,
+        url: req.url,
+        hits: 0,
+    })
+    .with_status(StatusCode::CREATED))
+}
+
+#[handler]
+async fn stats(store: Data<&Store>, Path(code): Path<String>) -> Result<Json<LinkInfo>> {
+    let link = store.get(&code).await.ok_or(NotFoundError)?;
+    Ok(Json(LinkInfo {
+        code,
+        url: link.url,
+        hits: link.hits,
+    }))
+}
+
+#[handler]
+async fn visit(store: Data<&Store>, Path(code): Path<String>) -> Result<Redirect> {
+    let url = store.visit(&code).await.ok_or(NotFoundError)?;
+    Ok(Redirect::found(url))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), std::io::Error> {
+    if std::env::var_os("RUST_LOG").is_none() {
+        std::env::set_var("RUST_LOG", "poem=debug");
+    }
+    tracing_subscriber::fmt::init();
+
+    let store: Store = Arc::new(MemoryStore::default());
+    let app = Route::new()
+        .at("/api/links", post(create))
+        .at("/api/links/:code", get(stats))
+        .at("/:code", get(visit))
+        .data(store);
+
+    println!("Create links: POST http://localhost:3000/api/links");
+    Server::new(TcpListener::bind("0.0.0.0:3000"))
+        .run(app)
+        .await
+}
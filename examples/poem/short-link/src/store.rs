@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use poem::async_trait;
+
+/// A short link and the number of times it has been visited.
+#[derive(Debug, Clone)]
+pub struct Link {
+    pub url: String,
+    pub hits: u64,
+}
+
+/// The storage of the short links, implement this trait to keep the links in
+/// a database instead of the memory.
+#[async_trait]
+pub trait LinkStore: Send + Sync + 'static {
+    /// Saves the link with the code, returns `false` if the code is already
+    /// used.
+    async fn insert(&self, code: &str, url: &str) -> bool;
+
+    /// Returns a code that is not used yet.
+    async fn generate_code(&self) -> String;
+
+    /// Returns the target of the link and increases the hit count.
+    async fn visit(&self, code: &str) -> Option<String>;
+
+    /// Returns the link without increasing the hit count.
+    async fn get(&self, code: &str) -> Option<Link>;
+}
+
+const ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+fn base62(mut n: u64) -> String {
+    let mut code = Vec::new();
+    loop {
+        code.push(ALPHABET[(n % 62) as usize]);
+        n /= 62;
+        if n == 0 {
+            break;
+        }
+    }
+    code.reverse();
+    String::from_utf8(code).unwrap()
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    links: Mutex<HashMap<String, Link>>,
+    next_id: AtomicU64,
+}
+
+#[async_trait]
+impl LinkStore for MemoryStore {
+    async fn insert(&self, code: &str, url: &str) -> bool {
+        let mut links = self.links.lock().unwrap();
+        if links.contains_key(code) {
+            return false;
+        }
+        links.insert(
+            code.to_string(),
+            Link {
+                url: url.to_string(),
+                hits: 0,
+            },
+        );
+        true
+    }
+
+    async fn generate_code(&self) -> String {
+        loop {
+            let code = base62(self.next_id.fetch_add(1, Ordering::Relaxed) + 10_000);
+            if !self.links.lock().unwrap().contains_key(&code) {
+                return code;
+            }
+        }
+    }
+
+    async fn visit(&self, code: &str) -> Option<String> {
+        let mut links = self.links.lock().unwrap();
+        let link = links.get_mut(code)?;
+        link.hits += 1;
+        Some(link.url.clone())
+    }
+
+    async fn get(&self, code: &str) -> Option<Link> {
+        self.links.lock().unwrap().get(code).cloned()
+    }
+}
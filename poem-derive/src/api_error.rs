@@ -0,0 +1,142 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Error, Fields, LitInt, Result};
+
+use crate::utils::get_crate_name;
+
+#[derive(Default)]
+struct Args {
+    internal: bool,
+    status: Option<u16>,
+    json: bool,
+}
+
+fn parse_args(attrs: &[Attribute], allow_internal: bool) -> Result<Args> {
+    let mut args = Args::default();
+    for attr in attrs {
+        if attr.path().is_ident("api_error") {
+            attr.parse_nested_meta(|meta| {
+                if allow_internal && meta.path.is_ident("internal") {
+                    args.internal = true;
+                } else if meta.path.is_ident("status") {
+                    let lit = meta.value()?.parse::<LitInt>()?;
+                    let status = lit.base10_parse::<u16>()?;
+                    if !(100..1000).contains(&status) {
+                        return Err(Error::new_spanned(lit, "invalid status code"));
+                    }
+                    args.status = Some(status);
+                } else if meta.path.is_ident("json") {
+                    args.json = true;
+                } else {
+                    return Err(meta.error("unsupported api_error attribute"));
+                }
+                Ok(())
+            })?;
+        }
+    }
+    Ok(args)
+}
+
+pub(crate) fn generate(input: DeriveInput) -> Result<TokenStream> {
+    let args = parse_args(&input.attrs, true)?;
+    let crate_name = get_crate_name(args.internal);
+    let ident = &input.ident;
+    let status_code = |status: Option<u16>| {
+        let status = status.unwrap_or(500);
+        quote! {
+            match #crate_name::http::StatusCode::from_u16(#status) {
+                ::std::result::Result::Ok(status) => status,
+                ::std::result::Result::Err(_) => ::std::unreachable!(),
+            }
+        }
+    };
+
+    let mut status_arms = Vec::new();
+    let mut json_arms = Vec::new();
+
+    match &input.data {
+        Data::Enum(data) => {
+            for variant in &data.variants {
+                let variant_args = parse_args(&variant.attrs, false)?;
+                let variant_ident = &variant.ident;
+                let status = status_code(variant_args.status.or(args.status));
+                status_arms.push(quote! { Self::#variant_ident { .. } => #status, });
+
+                if variant_args.json || args.json {
+                    let pattern = match &variant.fields {
+                        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                            quote! { Self::#variant_ident(value) }
+                        }
+                        _ => {
+                            return Err(Error::new_spanned(
+                                variant_ident,
+                                "the `json` attribute requires a variant with exactly one unnamed field",
+                            ))
+                        }
+                    };
+                    json_arms.push(quote! {
+                        #pattern => #crate_name::IntoResponse::into_response(#crate_name::web::Json(value)),
+                    });
+                }
+            }
+        }
+        Data::Struct(_) => {
+            if args.json {
+                return Err(Error::new_spanned(
+                    ident,
+                    "the `json` attribute can only be used on enum variants",
+                ));
+            }
+            let status = status_code(args.status);
+            status_arms.push(quote! { _ => #status, });
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ident,
+                "ApiError can only be derived for enums and structs",
+            ))
+        }
+    }
+
+    let as_response = if json_arms.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            #[allow(unreachable_patterns)]
+            fn as_response(&self) -> #crate_name::Response
+            where
+                Self: ::std::error::Error + ::std::marker::Send + ::std::marker::Sync + 'static,
+            {
+                let mut resp = match self {
+                    #(#json_arms)*
+                    _ => #crate_name::Response::builder().body(::std::string::ToString::to_string(self)),
+                };
+                if resp.status() == #crate_name::http::StatusCode::OK {
+                    resp.set_status(#crate_name::error::ResponseError::status(self));
+                }
+                resp
+            }
+        }
+    };
+
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let expanded = quote! {
+        impl #impl_generics #crate_name::error::ResponseError for #ident #type_generics #where_clause {
+            fn status(&self) -> #crate_name::http::StatusCode {
+                match self {
+                    #(#status_arms)*
+                }
+            }
+
+            #as_response
+        }
+
+        impl #impl_generics #crate_name::IntoResponse for #ident #type_generics #where_clause {
+            fn into_response(self) -> #crate_name::Response {
+                #crate_name::Error::from(self).into_response()
+            }
+        }
+    };
+
+    Ok(expanded)
+}
@@ -7,11 +7,13 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![warn(missing_docs)]
 
+mod api_error;
+mod multipart;
 mod utils;
 
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, FnArg, GenericParam, ItemFn, Member, Result};
+use syn::{parse_macro_input, DeriveInput, FnArg, GenericParam, ItemFn, Member, Result};
 
 /// Wrap an asynchronous function as an `Endpoint`.
 ///
@@ -108,8 +110,10 @@ fn generate_handler(internal: bool, input: TokenStream) -> Result<TokenStream> {
             async fn call(&self, mut req: #crate_name::Request) -> #crate_name::Result<Self::Output> {
                 let (req, mut body) = req.split();
                 #(#extractors)*
+                req.lifecycle().emit(#crate_name::web::LifecycleStage::Extracted);
                 #item_fn
                 let res = #ident(#(#args),*)#call_await;
+                req.lifecycle().emit(#crate_name::web::LifecycleStage::Handled);
                 let res = #crate_name::error::IntoResult::into_result(res);
                 std::result::Result::map(res, #crate_name::IntoResponse::into_response)
             }
@@ -119,6 +123,74 @@ fn generate_handler(internal: bool, input: TokenStream) -> Result<TokenStream> {
     Ok(expanded.into())
 }
 
+/// Implement `FromRequest` for a struct by parsing a `multipart/form-data`
+/// request into its fields.
+///
+/// # Field attributes
+///
+/// - `rename = "name"`: Use a different name for the form field.
+/// - `limit = 1024`: Maximum size of the field data in bytes.
+/// - `default`: Use `Default::default()` if the field is missing.
+/// - `from_str`: Parse the field text with `FromStr`.
+/// - `json`: Deserialize the field text as JSON.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Multipart)]
+/// struct UploadForm {
+///     title: String,
+///     #[multipart(limit = 1048576)]
+///     file: Upload,
+/// }
+/// ```
+#[proc_macro_derive(Multipart, attributes(multipart))]
+pub fn derive_multipart(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match multipart::generate(input) {
+        Ok(stream) => stream.into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+/// Implement `ResponseError` and `IntoResponse` for an error type, so that
+/// each variant of an error enum is mapped to a status code.
+///
+/// The body of the response is the `Display` output of the error, which is
+/// usually derived with `thiserror`.
+///
+/// # Type attributes
+///
+/// - `status = 500`: The default status code of the variants.
+/// - `json`: Apply `json` to all variants.
+///
+/// # Variant attributes
+///
+/// - `status = 404`: The status code of the variant.
+/// - `json`: Serialize the single field of the variant as the JSON body.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Debug, thiserror::Error, ApiError)]
+/// enum UserError {
+///     #[error("user not found")]
+///     #[api_error(status = 404)]
+///     NotFound,
+///     #[error("invalid user")]
+///     #[api_error(status = 400, json)]
+///     Invalid(ValidationErrors),
+/// }
+/// ```
+#[proc_macro_derive(ApiError, attributes(api_error))]
+pub fn derive_api_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match api_error::generate(input) {
+        Ok(stream) => stream.into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
 #[doc(hidden)]
 #[proc_macro]
 pub fn generate_implement_middlewares(_: TokenStream) -> TokenStream {
@@ -0,0 +1,181 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{ext::IdentExt, Data, DeriveInput, Error, Fields, LitInt, LitStr, Result};
+
+use crate::utils::get_crate_name;
+
+#[derive(Default)]
+enum FieldParser {
+    #[default]
+    Default,
+    FromStr,
+    Json,
+}
+
+#[derive(Default)]
+struct FieldArgs {
+    rename: Option<String>,
+    limit: Option<usize>,
+    default: bool,
+    parser: FieldParser,
+}
+
+fn parse_struct_args(input: &DeriveInput) -> Result<bool> {
+    let mut internal = false;
+    for attr in &input.attrs {
+        if attr.path().is_ident("multipart") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("internal") {
+                    internal = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported multipart attribute"))
+                }
+            })?;
+        }
+    }
+    Ok(internal)
+}
+
+fn parse_field_args(field: &syn::Field) -> Result<FieldArgs> {
+    let mut args = FieldArgs::default();
+    for attr in &field.attrs {
+        if attr.path().is_ident("multipart") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    args.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("limit") {
+                    args.limit = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                } else if meta.path.is_ident("default") {
+                    args.default = true;
+                } else if meta.path.is_ident("from_str") {
+                    args.parser = FieldParser::FromStr;
+                } else if meta.path.is_ident("json") {
+                    args.parser = FieldParser::Json;
+                } else {
+                    return Err(meta.error("unsupported multipart attribute"));
+                }
+                Ok(())
+            })?;
+        }
+    }
+    Ok(args)
+}
+
+pub(crate) fn generate(input: DeriveInput) -> Result<TokenStream> {
+    let internal = parse_struct_args(&input)?;
+    let crate_name = get_crate_name(internal);
+    let ident = &input.ident;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    ident,
+                    "Multipart can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                ident,
+                "Multipart can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut decls = Vec::new();
+    let mut arms = Vec::new();
+    let mut inits = Vec::new();
+
+    for field in fields {
+        let args = parse_field_args(field)?;
+        let field_ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let name = args
+            .rename
+            .unwrap_or_else(|| field_ident.unraw().to_string());
+        let var = format_ident!("__{}", field_ident.unraw());
+        let limit = match args.limit {
+            Some(limit) => quote!(::std::option::Option::Some(#limit)),
+            None => quote!(::std::option::Option::None),
+        };
+
+        match args.parser {
+            FieldParser::Default => {
+                decls.push(quote! { let mut #var: ::std::option::Option<#ty> = ::std::option::Option::None; });
+                arms.push(quote! {
+                    ::std::option::Option::Some(#name) => {
+                        #var = ::std::option::Option::Some(match #var.take() {
+                            ::std::option::Option::Some(value) => <#ty as #crate_name::web::MultipartField>::parse_repeated_field(value, field, #limit).await?,
+                            ::std::option::Option::None => <#ty as #crate_name::web::MultipartField>::parse_field(field, #limit).await?,
+                        });
+                    }
+                });
+            }
+            FieldParser::FromStr | FieldParser::Json => {
+                let parse = match args.parser {
+                    FieldParser::FromStr => quote!(__parse_from_str),
+                    _ => quote!(__parse_json),
+                };
+                decls.push(quote! { let mut #var: ::std::option::Option<#ty> = ::std::option::Option::None; });
+                arms.push(quote! {
+                    ::std::option::Option::Some(#name) => {
+                        if #var.is_some() {
+                            return ::std::result::Result::Err(#crate_name::error::ParseMultipartError::DuplicateField(::std::string::ToString::to_string(#name)).into());
+                        }
+                        #var = ::std::option::Option::Some(field.#parse::<#ty>(#limit).await?);
+                    }
+                });
+            }
+        }
+
+        let missing = if args.default {
+            quote!(::std::default::Default::default())
+        } else if matches!(args.parser, FieldParser::Default) {
+            quote! {
+                match <#ty as #crate_name::web::MultipartField>::missing_value() {
+                    ::std::option::Option::Some(value) => value,
+                    ::std::option::Option::None => return ::std::result::Result::Err(#crate_name::error::ParseMultipartError::FieldRequired(::std::string::ToString::to_string(#name)).into()),
+                }
+            }
+        } else {
+            quote! {
+                return ::std::result::Result::Err(#crate_name::error::ParseMultipartError::FieldRequired(::std::string::ToString::to_string(#name)).into())
+            }
+        };
+        inits.push(quote! {
+            #field_ident: match #var {
+                ::std::option::Option::Some(value) => value,
+                ::std::option::Option::None => #missing,
+            }
+        });
+    }
+
+    let params = &input.generics.params;
+    let (_, type_generics, where_clause) = input.generics.split_for_impl();
+    let expanded = quote! {
+        #[#crate_name::async_trait]
+        impl<'__request, #params> #crate_name::FromRequest<'__request> for #ident #type_generics #where_clause {
+            async fn from_request(req: &'__request #crate_name::Request, body: &mut #crate_name::RequestBody) -> #crate_name::Result<Self> {
+                let mut multipart = <#crate_name::web::Multipart as #crate_name::FromRequest>::from_request(req, body).await?;
+                #(#decls)*
+
+                while let ::std::option::Option::Some(field) = multipart.next_field().await? {
+                    let name = field.name().map(::std::string::ToString::to_string);
+                    match name.as_deref() {
+                        #(#arms)*
+                        _ => {}
+                    }
+                }
+
+                ::std::result::Result::Ok(Self {
+                    #(#inits),*
+                })
+            }
+        }
+    };
+
+    Ok(expanded)
+}
@@ -464,7 +464,9 @@ fn generate_operation(
                         async move {
                             let (request, mut body) = request.split();
                             #(#parse_args)*
+                            request.lifecycle().emit(#crate_name::__private::poem::web::LifecycleStage::Extracted);
                             let res = api_obj.#fn_ident(#(#use_args),*).await;
+                            request.lifecycle().emit(#crate_name::__private::poem::web::LifecycleStage::Handled);
                             let res = #crate_name::__private::poem::error::IntoResult::into_result(res);
                             match ::std::result::Result::map(res, #crate_name::__private::poem::IntoResponse::into_response) {
                                 ::std::result::Result::Ok(mut resp) => {
@@ -1,6 +1,8 @@
-use std::fmt::Write;
-
-use poem::{http::header::CONTENT_DISPOSITION, Body, IntoResponse, Response};
+use poem::{
+    http::{header::CONTENT_DISPOSITION, HeaderValue},
+    web::{ContentDisposition, DispositionType},
+    Body, IntoResponse, Response,
+};
 
 use crate::{
     payload::{Binary, Payload},
@@ -21,12 +23,11 @@ pub enum AttachmentType {
     Attachment,
 }
 
-impl AttachmentType {
-    #[inline]
-    fn as_str(&self) -> &'static str {
-        match self {
-            AttachmentType::Inline => "inline",
-            AttachmentType::Attachment => "attachment",
+impl From<AttachmentType> for DispositionType {
+    fn from(ty: AttachmentType) -> Self {
+        match ty {
+            AttachmentType::Inline => DispositionType::Inline,
+            AttachmentType::Attachment => DispositionType::Attachment,
         }
     }
 }
@@ -56,6 +57,10 @@ impl<T: Into<Body> + Send> Attachment<T> {
     }
 
     /// Specify the file name.
+    ///
+    /// The file name is sanitized with
+    /// [`sanitize_filename`](poem::web::sanitize_filename) when the response
+    /// is generated.
     #[must_use]
     pub fn filename(self, filename: impl Into<String>) -> Self {
         Self {
@@ -64,20 +69,12 @@ impl<T: Into<Body> + Send> Attachment<T> {
         }
     }
 
-    fn content_disposition(&self) -> String {
-        let mut content_disposition = self.ty.as_str().to_string();
-
-        if let Some(legal_filename) = self.filename.as_ref().map(|filename| {
-            filename
-                .replace('\\', "\\\\")
-                .replace('\"', "\\\"")
-                .replace('\r', "\\\r")
-                .replace('\n', "\\\n")
-        }) {
-            _ = write!(content_disposition, "; filename=\"{legal_filename}\"");
+    fn content_disposition(&self) -> HeaderValue {
+        let mut content_disposition = ContentDisposition::new(self.ty.into());
+        if let Some(filename) = &self.filename {
+            content_disposition = content_disposition.filename(filename);
         }
-
-        content_disposition
+        content_disposition.to_header_value()
     }
 }
 
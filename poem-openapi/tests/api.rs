@@ -996,3 +996,40 @@ async fn issue_489() {
         .await
         .assert_status(StatusCode::METHOD_NOT_ALLOWED);
 }
+
+#[tokio::test]
+async fn lifecycle() {
+    use poem::web::{LifecycleStage, RequestLifecycle};
+
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/", method = "get")]
+        async fn test(&self, n: Query<i32>) -> PlainText<String> {
+            PlainText(n.0.to_string())
+        }
+    }
+
+    let ep = OpenApiService::new(Api, "test", "1.0").around(|ep, req: poem::Request| async move {
+        req.lifecycle().subscribe(|_| {});
+        ep.call(req).await
+    });
+    let req = poem::Request::builder().uri_str("/?n=1").finish();
+    let lifecycle: RequestLifecycle = req.lifecycle().clone();
+    ep.call(req).await.unwrap();
+    let stages = lifecycle
+        .events()
+        .into_iter()
+        .map(|event| event.stage)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stages,
+        [
+            LifecycleStage::Received,
+            LifecycleStage::Routed,
+            LifecycleStage::Extracted,
+            LifecycleStage::Handled
+        ]
+    );
+}
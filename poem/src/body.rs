@@ -1,8 +1,9 @@
 use std::{
+    error::Error as StdError,
     fmt::{Debug, Formatter},
     io::{Error as IoError, ErrorKind},
     pin::Pin,
-    task::Poll,
+    task::{Context, Poll},
 };
 
 use bytes::{Bytes, BytesMut};
@@ -10,7 +11,7 @@ use futures_util::{Stream, TryStreamExt};
 use http_body_util::BodyExt;
 use hyper::body::{Body as _, Frame};
 use serde::{de::DeserializeOwned, Serialize};
-use sync_wrapper::SyncStream;
+use sync_wrapper::{SyncStream, SyncWrapper};
 use tokio::io::{AsyncRead, AsyncReadExt};
 
 use crate::{
@@ -38,6 +39,85 @@ impl From<BoxBody> for Body {
     }
 }
 
+/// An observer of the data of a [`Body`], which is attached with
+/// [`Body::tee`].
+///
+/// It is implemented for the closures `FnMut(&[u8])`, which are called with
+/// each chunk of data.
+pub trait BodyObserver: Send + 'static {
+    /// Called with each chunk of data, before it is passed on.
+    fn on_data(&mut self, data: &[u8]);
+
+    /// Called once when the body is finished, `complete` is `false` if an
+    /// error occurred or the body was dropped before all data was read, for
+    /// example because the client disconnected.
+    fn on_finish(&mut self, complete: bool) {
+        let _ = complete;
+    }
+}
+
+impl<F> BodyObserver for F
+where
+    F: FnMut(&[u8]) + Send + 'static,
+{
+    fn on_data(&mut self, data: &[u8]) {
+        self(data)
+    }
+}
+
+struct TeeBody {
+    inner: BoxBody,
+    observer: Option<SyncWrapper<Box<dyn BodyObserver>>>,
+}
+
+impl TeeBody {
+    fn finish(&mut self, complete: bool) {
+        if let Some(mut observer) = self.observer.take() {
+            observer.get_mut().on_finish(complete);
+        }
+    }
+}
+
+impl hyper::body::Body for TeeBody {
+    type Data = Bytes;
+    type Error = IoError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let res = Pin::new(&mut this.inner).poll_frame(cx);
+        match &res {
+            Poll::Ready(Some(Ok(frame))) => {
+                if let (Some(data), Some(observer)) = (frame.data_ref(), &mut this.observer) {
+                    observer.get_mut().on_data(data);
+                }
+            }
+            Poll::Ready(Some(Err(_))) => this.finish(false),
+            Poll::Ready(None) => this.finish(true),
+            Poll::Pending => {}
+        }
+        res
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> hyper::body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for TeeBody {
+    fn drop(&mut self) {
+        // an empty body may be never polled
+        let complete = self.inner.is_end_stream();
+        self.finish(complete);
+    }
+}
+
 impl Debug for Body {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Body").finish()
@@ -98,6 +178,17 @@ impl From<()> for Body {
     }
 }
 
+/// Returns `true` if the error is produced by a reader that limits the size of
+/// the body, such as the ones used by the `Decompression` and
+/// `RequestBodyLimit` middlewares.
+pub(crate) fn payload_too_large(err: &IoError) -> bool {
+    matches!(
+        err.get_ref()
+            .and_then(|err| err.downcast_ref::<ReadBodyError>()),
+        Some(ReadBodyError::PayloadTooLarge)
+    )
+}
+
 impl Body {
     /// Create a body object from [`Bytes`].
     #[inline]
@@ -118,6 +209,21 @@ impl Body {
     }
 
     /// Create a body object from reader.
+    ///
+    /// The reader is read in chunks as the body is sent, so it can be used to
+    /// stream a [`tokio::fs::File`] or the output of a subprocess without
+    /// loading it into memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::Body;
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let body = Body::from_async_read(&b"hello"[..]);
+    /// assert_eq!(body.into_string().await.unwrap(), "hello");
+    /// # });
+    /// ```
     #[inline]
     pub fn from_async_read(reader: impl AsyncRead + Send + 'static) -> Self {
         Self(BoxBody::new(http_body_util::StreamBody::new(
@@ -141,6 +247,37 @@ impl Body {
         )))
     }
 
+    /// Create a body object from a stream of chunks, which is sent to the
+    /// client as it is produced without being buffered.
+    ///
+    /// Unlike [`Body::from_bytes_stream`], the error type can be any error
+    /// that can be converted into `Box<dyn Error + Send + Sync>`, such as the
+    /// errors of database drivers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use futures_util::stream;
+    /// use poem::Body;
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let body = Body::from_stream(stream::iter(vec![
+    ///     Ok::<_, std::fmt::Error>(Bytes::from("hello ")),
+    ///     Ok(Bytes::from("world")),
+    /// ]));
+    /// assert_eq!(body.into_string().await.unwrap(), "hello world");
+    /// # });
+    /// ```
+    pub fn from_stream<S, O, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'static,
+        O: Into<Bytes> + 'static,
+        E: Into<Box<dyn StdError + Send + Sync>> + 'static,
+    {
+        Self::from_bytes_stream(stream.map_err(IoError::other))
+    }
+
     /// Create a body object from JSON.
     pub fn from_json(body: impl Serialize) -> serde_json::Result<Self> {
         Ok(serde_json::to_vec(&body)?.into())
@@ -156,6 +293,42 @@ impl Body {
         )
     }
 
+    /// Consumes this body object to return a body that passes the data to
+    /// `observer` as it is read, for example to count the bytes sent to the
+    /// client, compute a hash, or log a sample of a streamed response.
+    ///
+    /// The data is not buffered and the size hint of the body is kept, so the
+    /// `Content-Length` header is still set for the sized bodies.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::{
+    ///     atomic::{AtomicUsize, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// use poem::Body;
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let size = Arc::new(AtomicUsize::new(0));
+    /// let body = Body::from("hello").tee({
+    ///     let size = size.clone();
+    ///     move |data: &[u8]| {
+    ///         size.fetch_add(data.len(), Ordering::Relaxed);
+    ///     }
+    /// });
+    /// assert_eq!(body.into_string().await.unwrap(), "hello");
+    /// assert_eq!(size.load(Ordering::Relaxed), 5);
+    /// # });
+    /// ```
+    pub fn tee(self, observer: impl BodyObserver) -> Self {
+        Self(BoxBody::new(TeeBody {
+            inner: self.0,
+            observer: Some(SyncWrapper::new(Box::new(observer))),
+        }))
+    }
+
     /// Returns `true` if this body is empty.
     pub fn is_empty(&self) -> bool {
         let size_hint = hyper::body::Body::size_hint(&self.0);
@@ -168,7 +341,13 @@ impl Body {
             .0
             .collect()
             .await
-            .map_err(|err| ReadBodyError::Io(IoError::new(ErrorKind::Other, err)))?
+            .map_err(|err| {
+                if payload_too_large(&err) {
+                    ReadBodyError::PayloadTooLarge
+                } else {
+                    ReadBodyError::Io(IoError::new(ErrorKind::Other, err))
+                }
+            })?
             .to_bytes())
     }
 
@@ -209,7 +388,13 @@ impl Body {
         let mut data = BytesMut::new();
 
         loop {
-            let sz = reader.read(&mut buf).await?;
+            let sz = reader.read(&mut buf).await.map_err(|err| {
+                if payload_too_large(&err) {
+                    ReadBodyError::PayloadTooLarge
+                } else {
+                    ReadBodyError::Io(err)
+                }
+            })?;
             if sz == 0 {
                 break;
             }
@@ -250,6 +435,23 @@ impl Body {
     }
 
     /// Consumes this body object to return a reader.
+    ///
+    /// It can be used to pipe the request body to a [`tokio::fs::File`] or
+    /// the input of a subprocess with [`tokio::io::copy`], without buffering
+    /// the whole body in memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::Body;
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let mut reader = Body::from("hello").into_async_read();
+    /// let mut output = Vec::new();
+    /// tokio::io::copy(&mut reader, &mut output).await.unwrap();
+    /// assert_eq!(output, b"hello");
+    /// # });
+    /// ```
     pub fn into_async_read(self) -> impl AsyncRead + Unpin + Send + 'static {
         tokio_util::io::StreamReader::new(self.into_bytes_stream())
     }
@@ -317,4 +519,51 @@ mod tests {
         let body = Body::from_json("abc").unwrap();
         assert_eq!(body.into_json::<String>().await.unwrap(), "abc");
     }
+
+    #[tokio::test]
+    async fn tee() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<(Vec<u8>, Option<bool>)>>);
+
+        impl BodyObserver for Recorder {
+            fn on_data(&mut self, data: &[u8]) {
+                self.0.lock().unwrap().0.extend_from_slice(data);
+            }
+
+            fn on_finish(&mut self, complete: bool) {
+                let mut state = self.0.lock().unwrap();
+                assert!(state.1.is_none());
+                state.1 = Some(complete);
+            }
+        }
+
+        let recorder = Recorder::default();
+        let body = Body::from("hello").tee(recorder.clone());
+        assert_eq!(hyper::body::Body::size_hint(&body.0).exact(), Some(5));
+        assert_eq!(body.into_string().await.unwrap(), "hello");
+        assert_eq!(*recorder.0.lock().unwrap(), (b"hello".to_vec(), Some(true)));
+
+        let recorder = Recorder::default();
+        let body = Body::from_bytes_stream(futures_util::stream::iter(vec![
+            Ok(Bytes::from_static(b"abc")),
+            Ok(Bytes::from_static(b"def")),
+            Err(IoError::new(ErrorKind::Other, "failed")),
+        ]))
+        .tee(recorder.clone());
+        assert!(body.into_bytes().await.is_err());
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            (b"abcdef".to_vec(), Some(false))
+        );
+
+        let recorder = Recorder::default();
+        drop(Body::from("hello").tee(recorder.clone()));
+        assert_eq!(*recorder.0.lock().unwrap(), (Vec::new(), Some(false)));
+
+        let recorder = Recorder::default();
+        drop(Body::empty().tee(recorder.clone()));
+        assert_eq!(*recorder.0.lock().unwrap(), (Vec::new(), Some(true)));
+    }
 }
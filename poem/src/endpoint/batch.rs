@@ -0,0 +1,316 @@
+use std::{
+    fmt::Write,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use mime::Mime;
+
+use crate::{
+    error::BatchError,
+    http::{header, HeaderName, HeaderValue, Method, StatusCode, Uri},
+    Endpoint, Request, Response, Result,
+};
+
+const DEFAULT_MAX_REQUESTS: usize = 100;
+
+/// An endpoint that handles `multipart/mixed` batch requests.
+///
+/// Each part of the request must have the content type `application/http`
+/// and contain an HTTP/1.1 request, which is dispatched through the inner
+/// endpoint in order. The response is a `multipart/mixed` body with one
+/// `application/http` part for each sub-request, and the `Content-ID` of a
+/// part is echoed back as `response-<Content-ID>`, as used by OData and
+/// Google batch APIs.
+///
+/// # Errors
+///
+/// - [`BatchError`]
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use poem::{endpoint::BatchEndpoint, handler, post, test::TestClient, Route};
+///
+/// #[handler]
+/// fn hello() -> &'static str {
+///     "hello"
+/// }
+///
+/// let api = Arc::new(Route::new().at("/hello", hello));
+/// let app = Route::new()
+///     .at("/batch", post(BatchEndpoint::new(api.clone())))
+///     .nest("/", api);
+///
+/// let body = "--XYZ\r\nContent-Type: application/http\r\nContent-ID: 1\r\n\r\nGET /hello HTTP/1.1\r\n\r\n\r\n--XYZ--\r\n";
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/batch")
+///     .content_type("multipart/mixed; boundary=XYZ")
+///     .body(body)
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+pub struct BatchEndpoint<E> {
+    inner: E,
+    max_requests: usize,
+}
+
+impl<E: Endpoint> BatchEndpoint<E> {
+    /// Create a `BatchEndpoint` that dispatches the sub-requests to `ep`.
+    pub fn new(ep: E) -> Self {
+        Self {
+            inner: ep,
+            max_requests: DEFAULT_MAX_REQUESTS,
+        }
+    }
+
+    /// Sets the maximum number of sub-requests in a batch, the default value
+    /// is `100`.
+    #[must_use]
+    pub fn max_requests(self, max_requests: usize) -> Self {
+        Self {
+            max_requests,
+            ..self
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for BatchEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| Mime::from_str(value).ok())
+            .ok_or(BatchError::ContentTypeRequired)?;
+        if content_type.type_() != mime::MULTIPART || content_type.subtype() != "mixed" {
+            return Err(
+                BatchError::InvalidContentType(content_type.essence_str().to_string()).into(),
+            );
+        }
+        // `multer::parse_boundary` only accepts `multipart/form-data`
+        let boundary = content_type
+            .get_param(mime::BOUNDARY)
+            .map(|boundary| boundary.as_str().to_string())
+            .ok_or(BatchError::Multipart(multer::Error::NoBoundary))?;
+
+        let mut multipart = multer::Multipart::new(
+            tokio_util::io::ReaderStream::new(req.take_body().into_async_read()),
+            boundary,
+        );
+        let mut parts = Vec::new();
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(BatchError::Multipart)?
+        {
+            if parts.len() >= self.max_requests {
+                return Err(BatchError::TooManyRequests {
+                    limit: self.max_requests,
+                }
+                .into());
+            }
+
+            let is_http = field
+                .content_type()
+                .map(|mime| mime.essence_str() == "application/http")
+                .unwrap_or_default();
+            if !is_http {
+                return Err(BatchError::InvalidRequest(
+                    "the content type of the part must be `application/http`".to_string(),
+                )
+                .into());
+            }
+
+            let content_id = field
+                .headers()
+                .get("content-id")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim_matches(|ch| ch == '<' || ch == '>').to_string());
+            let data = field.bytes().await.map_err(BatchError::Multipart)?;
+            parts.push((content_id, parse_http_request(&data)?));
+        }
+
+        let boundary = format!(
+            "batch_{:x}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default()
+        );
+        let mut body = Vec::new();
+
+        for (content_id, sub_req) in parts {
+            let resp = self.inner.get_response(sub_req).await;
+            let mut head = format!("--{boundary}\r\nContent-Type: application/http\r\n");
+            if let Some(content_id) = content_id {
+                _ = write!(head, "Content-ID: <response-{content_id}>\r\n");
+            }
+            let status = resp.status();
+            _ = write!(
+                head,
+                "\r\nHTTP/1.1 {} {}\r\n",
+                status.as_u16(),
+                status.canonical_reason().unwrap_or_default()
+            );
+            let (parts, resp_body) = resp.into_parts();
+            for (name, value) in &parts.headers {
+                _ = write!(head, "{}: {}\r\n", name, String::from_utf8_lossy(value.as_bytes()));
+            }
+            head.push_str("\r\n");
+
+            body.extend_from_slice(head.as_bytes());
+            body.extend_from_slice(&resp_body.into_vec().await?);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .content_type(format!("multipart/mixed; boundary={boundary}"))
+            .body(body))
+    }
+}
+
+/// Parses an HTTP/1.1 request message embedded in a batch part.
+fn parse_http_request(data: &[u8]) -> Result<Request, BatchError> {
+    let invalid = |msg: &str| BatchError::InvalidRequest(msg.to_string());
+
+    let (head, body) = match find_subsequence(data, b"\r\n\r\n") {
+        Some(pos) => (&data[..pos], &data[pos + 4..]),
+        None => match find_subsequence(data, b"\n\n") {
+            Some(pos) => (&data[..pos], &data[pos + 2..]),
+            None => (data, &[][..]),
+        },
+    };
+    let head = std::str::from_utf8(head).map_err(|_| invalid("invalid request head"))?;
+    let mut lines = head.lines().map(|line| line.trim_end_matches('\r'));
+
+    let request_line = lines.next().ok_or_else(|| invalid("missing request line"))?;
+    let mut request_line = request_line.split_whitespace();
+    let method = request_line
+        .next()
+        .and_then(|method| Method::from_str(method).ok())
+        .ok_or_else(|| invalid("invalid method"))?;
+    let uri = request_line
+        .next()
+        .and_then(|uri| Uri::from_str(uri).ok())
+        .ok_or_else(|| invalid("invalid uri"))?;
+
+    let mut builder = Request::builder().method(method).uri(uri);
+    for line in lines {
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| invalid("invalid header"))?;
+        let name = HeaderName::from_str(name.trim()).map_err(|_| invalid("invalid header"))?;
+        let value =
+            HeaderValue::from_str(value.trim()).map_err(|_| invalid("invalid header"))?;
+        builder = builder.header(name, value);
+    }
+
+    Ok(builder.body(body.to_vec()))
+}
+
+fn find_subsequence(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{handler, post, test::TestClient, web::Path, Route};
+
+    #[test]
+    fn test_parse_http_request() {
+        let req = parse_http_request(
+            b"POST /a?b=1 HTTP/1.1\r\nContent-Type: text/plain\r\nX-A: 1\r\n\r\nhello",
+        )
+        .unwrap();
+        assert_eq!(req.method(), Method::POST);
+        assert_eq!(req.uri().path(), "/a");
+        assert_eq!(req.uri().query(), Some("b=1"));
+        assert_eq!(req.header("x-a"), Some("1"));
+        assert_eq!(req.content_type(), Some("text/plain"));
+
+        assert!(parse_http_request(b"GET\r\n\r\n").is_err());
+        assert!(parse_http_request(b"GET / HTTP/1.1\r\nX-A\r\n\r\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn batch() {
+        #[handler(internal)]
+        fn hello(Path(name): Path<String>) -> String {
+            format!("hello {name}")
+        }
+
+        #[handler(internal)]
+        fn echo(body: String) -> String {
+            body
+        }
+
+        let api = Arc::new(Route::new().at("/hello/:name", hello).at("/echo", echo));
+        let cli = TestClient::new(BatchEndpoint::new(api));
+
+        let body = "--XYZ\r\n\
+            Content-Type: application/http\r\n\
+            Content-ID: <a>\r\n\
+            \r\n\
+            GET /hello/sunli HTTP/1.1\r\n\
+            \r\n\
+            \r\n\
+            --XYZ\r\n\
+            Content-Type: application/http\r\n\
+            \r\n\
+            POST /echo HTTP/1.1\r\n\
+            Content-Type: text/plain\r\n\
+            \r\n\
+            abc\r\n\
+            --XYZ\r\n\
+            Content-Type: application/http\r\n\
+            \r\n\
+            GET /404 HTTP/1.1\r\n\
+            \r\n\
+            \r\n\
+            --XYZ--\r\n";
+        let resp = cli
+            .post("/")
+            .content_type("multipart/mixed; boundary=XYZ")
+            .body(body)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        let text = resp.0.into_body().into_string().await.unwrap();
+        assert!(text.contains("Content-ID: <response-a>"));
+        assert!(text.contains("HTTP/1.1 200 OK"));
+        assert!(text.contains("hello sunli"));
+        assert!(text.contains("\r\n\r\nabc\r\n"));
+        assert!(text.contains("HTTP/1.1 404 Not Found"));
+
+        let cli = TestClient::new(post(BatchEndpoint::new(echo).max_requests(1)));
+        cli.post("/")
+            .content_type("multipart/mixed; boundary=XYZ")
+            .body(body)
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        cli.post("/")
+            .content_type("application/json")
+            .body("{}")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
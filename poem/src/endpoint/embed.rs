@@ -4,6 +4,7 @@ use async_trait::async_trait;
 use rust_embed::RustEmbed;
 
 use crate::{
+    endpoint::SriManifest,
     http::{header, Method, StatusCode},
     Endpoint, Error, Request, Response,
 };
@@ -79,6 +80,17 @@ impl<E: RustEmbed + Send + Sync> EmbeddedFilesEndpoint<E> {
             _embed: PhantomData,
         }
     }
+
+    /// Computes the Subresource Integrity hashes of all files in the bundle.
+    pub fn sri_manifest() -> SriManifest {
+        let mut manifest = SriManifest::new();
+        for path in E::iter() {
+            if let Some(content) = E::get(&path) {
+                manifest.insert(path.as_ref(), content.data);
+            }
+        }
+        manifest
+    }
 }
 
 #[async_trait]
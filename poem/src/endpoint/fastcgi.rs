@@ -0,0 +1,654 @@
+use std::{
+    io::{Error as IoError, ErrorKind},
+    path::{Component, Path, PathBuf},
+};
+
+use bytes::Bytes;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter},
+    net::TcpStream,
+};
+
+use crate::{
+    error::FastCgiError,
+    http::{header, uri::Scheme, HeaderName, HeaderValue, StatusCode, Version},
+    Body, Endpoint, Request, Response, Result,
+};
+
+const FCGI_VERSION_1: u8 = 1;
+const FCGI_BEGIN_REQUEST: u8 = 1;
+const FCGI_END_REQUEST: u8 = 3;
+const FCGI_PARAMS: u8 = 4;
+const FCGI_STDIN: u8 = 5;
+const FCGI_STDOUT: u8 = 6;
+const FCGI_STDERR: u8 = 7;
+const FCGI_RESPONDER: u16 = 1;
+const REQUEST_ID: u16 = 1;
+const MAX_CONTENT_LENGTH: usize = 0xffff;
+
+trait Connection: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Connection for T {}
+
+enum Address {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// An endpoint that forwards the requests to a
+/// [FastCGI](https://fastcgi-archives.github.io/FastCGI_Specification.html)
+/// backend, such as `php-fpm`.
+///
+/// The request body is streamed to the backend, and the response body is
+/// streamed to the client as the backend produces it. A new connection is
+/// opened for each request.
+///
+/// By default, the request path is mapped to a script in the
+/// [document root](FastCgiEndpoint::document_root), use
+/// [`FastCgiEndpoint::script_name`] to send all requests to a front
+/// controller instead, in which case the request path is passed as
+/// `PATH_INFO`. The request headers are passed as the `HTTP_*` parameters,
+/// except `Proxy`.
+///
+/// # Errors
+///
+/// - [`FastCgiError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{endpoint::FastCgiEndpoint, Route};
+///
+/// let app = Route::new().nest(
+///     "/legacy",
+///     FastCgiEndpoint::tcp("127.0.0.1:9000")
+///         .document_root("/var/www/legacy")
+///         .script_name("/index.php"),
+/// );
+/// ```
+pub struct FastCgiEndpoint {
+    address: Address,
+    document_root: PathBuf,
+    script_name: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+impl FastCgiEndpoint {
+    fn new(address: Address) -> Self {
+        Self {
+            address,
+            document_root: PathBuf::new(),
+            script_name: None,
+            params: Vec::new(),
+        }
+    }
+
+    /// Create a `FastCgiEndpoint` that connects to the backend with TCP, such
+    /// as `127.0.0.1:9000`.
+    pub fn tcp(addr: impl Into<String>) -> Self {
+        Self::new(Address::Tcp(addr.into()))
+    }
+
+    /// Create a `FastCgiEndpoint` that connects to the backend with a Unix
+    /// domain socket, such as `/run/php/php-fpm.sock`.
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self::new(Address::Unix(path.into()))
+    }
+
+    /// Sets the document root on the backend, which is passed as
+    /// `DOCUMENT_ROOT` and used to build `SCRIPT_FILENAME`.
+    #[must_use]
+    pub fn document_root(self, path: impl Into<PathBuf>) -> Self {
+        Self {
+            document_root: path.into(),
+            ..self
+        }
+    }
+
+    /// Sends all requests to this script, relative to the document root, such
+    /// as `/index.php`.
+    #[must_use]
+    pub fn script_name(self, name: impl Into<String>) -> Self {
+        Self {
+            script_name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Adds a parameter that is passed to the backend with each request, the
+    /// parameters added here override the ones set by this endpoint.
+    #[must_use]
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((name.into(), value.into()));
+        self
+    }
+
+    async fn connect(&self) -> Result<Box<dyn Connection>, IoError> {
+        Ok(match &self.address {
+            Address::Tcp(addr) => Box::new(TcpStream::connect(addr).await?),
+            #[cfg(unix)]
+            Address::Unix(path) => Box::new(tokio::net::UnixStream::connect(path).await?),
+        })
+    }
+
+    fn build_params(&self, req: &Request) -> Result<Vec<(String, String)>, FastCgiError> {
+        let path = percent_encoding::percent_decode_str(req.uri().path())
+            .decode_utf8()
+            .map_err(|_| FastCgiError::InvalidPath)?;
+        if Path::new(&*path)
+            .components()
+            .any(|component| component == Component::ParentDir)
+        {
+            return Err(FastCgiError::InvalidPath);
+        }
+        let (script_name, path_info) = match &self.script_name {
+            Some(script_name) => (script_name.clone(), path.into_owned()),
+            None => (path.into_owned(), String::new()),
+        };
+        let script_filename = self.document_root.join(script_name.trim_start_matches('/'));
+
+        let mut params = vec![
+            ("GATEWAY_INTERFACE".to_string(), "CGI/1.1".to_string()),
+            ("SERVER_SOFTWARE".to_string(), "poem".to_string()),
+            (
+                "SERVER_PROTOCOL".to_string(),
+                server_protocol(req.version()).to_string(),
+            ),
+            ("REQUEST_METHOD".to_string(), req.method().to_string()),
+            (
+                "REQUEST_URI".to_string(),
+                req.original_uri()
+                    .path_and_query()
+                    .map(|path_and_query| path_and_query.to_string())
+                    .unwrap_or_else(|| "/".to_string()),
+            ),
+            (
+                "QUERY_STRING".to_string(),
+                req.uri().query().unwrap_or_default().to_string(),
+            ),
+            (
+                "DOCUMENT_ROOT".to_string(),
+                self.document_root.display().to_string(),
+            ),
+            (
+                "SCRIPT_FILENAME".to_string(),
+                script_filename.display().to_string(),
+            ),
+            ("SCRIPT_NAME".to_string(), script_name),
+            ("PATH_INFO".to_string(), path_info),
+        ];
+
+        if req.scheme() == &Scheme::HTTPS {
+            params.push(("HTTPS".to_string(), "on".to_string()));
+        }
+        if let Some(addr) = req.remote_addr().as_socket_addr() {
+            params.push(("REMOTE_ADDR".to_string(), addr.ip().to_string()));
+            params.push(("REMOTE_PORT".to_string(), addr.port().to_string()));
+        }
+        if let Some(addr) = req.local_addr().as_socket_addr() {
+            params.push(("SERVER_ADDR".to_string(), addr.ip().to_string()));
+            params.push(("SERVER_PORT".to_string(), addr.port().to_string()));
+        }
+        if let Some(host) = req.uri().host().or_else(|| req.header(header::HOST)) {
+            let name = match host.rsplit_once(':') {
+                Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+                _ => host,
+            };
+            params.push(("SERVER_NAME".to_string(), name.to_string()));
+        }
+
+        for name in req.headers().keys() {
+            let key = if name == header::CONTENT_TYPE {
+                "CONTENT_TYPE".to_string()
+            } else if name == header::CONTENT_LENGTH {
+                "CONTENT_LENGTH".to_string()
+            } else if name.as_str() == "proxy" {
+                // https://httpoxy.org
+                continue;
+            } else {
+                format!(
+                    "HTTP_{}",
+                    name.as_str().to_ascii_uppercase().replace('-', "_")
+                )
+            };
+            let value = req
+                .headers()
+                .get_all(name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .collect::<Vec<_>>()
+                .join(", ");
+            params.push((key, value));
+        }
+
+        for (name, value) in &self.params {
+            params.retain(|(key, _)| key != name);
+            params.push((name.clone(), value.clone()));
+        }
+
+        Ok(params)
+    }
+}
+
+fn server_protocol(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_2 => "HTTP/2.0",
+        Version::HTTP_3 => "HTTP/3.0",
+        _ => "HTTP/1.1",
+    }
+}
+
+fn encode_length(buf: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        buf.push(len as u8);
+    } else {
+        buf.extend_from_slice(&(len as u32 | 0x8000_0000).to_be_bytes());
+    }
+}
+
+fn encode_params(params: &[(String, String)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for (name, value) in params {
+        encode_length(&mut buf, name.len());
+        encode_length(&mut buf, value.len());
+        buf.extend_from_slice(name.as_bytes());
+        buf.extend_from_slice(value.as_bytes());
+    }
+    buf
+}
+
+async fn write_record<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    ty: u8,
+    content: &[u8],
+) -> Result<(), IoError> {
+    let [id_hi, id_lo] = REQUEST_ID.to_be_bytes();
+    let [len_hi, len_lo] = (content.len() as u16).to_be_bytes();
+    writer
+        .write_all(&[FCGI_VERSION_1, ty, id_hi, id_lo, len_hi, len_lo, 0, 0])
+        .await?;
+    writer.write_all(content).await
+}
+
+/// Writes the content as a stream of records, without the empty record that
+/// ends the stream.
+async fn write_stream<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    ty: u8,
+    content: &[u8],
+) -> Result<(), IoError> {
+    for chunk in content.chunks(MAX_CONTENT_LENGTH) {
+        write_record(writer, ty, chunk).await?;
+    }
+    Ok(())
+}
+
+async fn read_record<R: AsyncRead + Unpin>(reader: &mut R) -> Result<(u8, Bytes), IoError> {
+    let mut header = [0; 8];
+    reader.read_exact(&mut header).await?;
+    if header[0] != FCGI_VERSION_1 {
+        return Err(IoError::new(
+            ErrorKind::InvalidData,
+            "unsupported FastCGI version",
+        ));
+    }
+    let len = u16::from_be_bytes([header[4], header[5]]) as usize;
+    let padding = header[6] as usize;
+    let mut content = vec![0; len + padding];
+    reader.read_exact(&mut content).await?;
+    content.truncate(len);
+    Ok((header[1], content.into()))
+}
+
+/// Reads the records until the next `FCGI_STDOUT` data, returns `None` if the
+/// request is ended.
+async fn read_stdout<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Bytes>, IoError> {
+    loop {
+        let (ty, content) = read_record(reader).await?;
+        match ty {
+            FCGI_STDOUT if !content.is_empty() => return Ok(Some(content)),
+            FCGI_STDERR if !content.is_empty() => {
+                tracing::warn!(
+                    stderr = %String::from_utf8_lossy(&content).trim_end(),
+                    "fastcgi stderr"
+                );
+            }
+            FCGI_END_REQUEST => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Parses the CGI response headers, returns the response and the rest of the
+/// data, or `None` if the headers are incomplete.
+fn parse_headers(data: &[u8]) -> Result<Option<(Response, Bytes)>, FastCgiError> {
+    let (end, body_start) = match (
+        find(data, b"\r\n\r\n").map(|pos| (pos, pos + 4)),
+        find(data, b"\n\n").map(|pos| (pos, pos + 2)),
+    ) {
+        (Some(a), Some(b)) => a.min(b),
+        (Some(pos), None) | (None, Some(pos)) => pos,
+        (None, None) => return Ok(None),
+    };
+
+    let headers = std::str::from_utf8(&data[..end])
+        .map_err(|_| FastCgiError::InvalidResponse("invalid headers".to_string()))?;
+    let mut resp = Response::default();
+    let mut status = None;
+
+    for line in headers.lines().filter(|line| !line.is_empty()) {
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| FastCgiError::InvalidResponse(format!("invalid header `{line}`")))?;
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("status") {
+            let code = value.split(' ').next().unwrap_or_default();
+            status = Some(
+                code.parse::<u16>()
+                    .ok()
+                    .and_then(|code| StatusCode::from_u16(code).ok())
+                    .ok_or_else(|| {
+                        FastCgiError::InvalidResponse(format!("invalid status `{value}`"))
+                    })?,
+            );
+        } else {
+            let name = HeaderName::try_from(name.trim()).map_err(|_| {
+                FastCgiError::InvalidResponse(format!("invalid header name `{name}`"))
+            })?;
+            let value = HeaderValue::try_from(value).map_err(|_| {
+                FastCgiError::InvalidResponse(format!("invalid header value `{value}`"))
+            })?;
+            resp.headers_mut().append(name, value);
+        }
+    }
+
+    resp.set_status(match status {
+        Some(status) => status,
+        None if resp.headers().contains_key(header::LOCATION) => StatusCode::FOUND,
+        None => StatusCode::OK,
+    });
+    Ok(Some((resp, Bytes::copy_from_slice(&data[body_start..]))))
+}
+
+fn find(data: &[u8], pattern: &[u8]) -> Option<usize> {
+    data.windows(pattern.len())
+        .position(|window| window == pattern)
+}
+
+#[async_trait::async_trait]
+impl Endpoint for FastCgiEndpoint {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let params = self.build_params(&req)?;
+        let mut conn = self.connect().await.map_err(FastCgiError::Io)?;
+
+        // send the request
+        let mut writer = BufWriter::new(&mut conn);
+        let [role_hi, role_lo] = FCGI_RESPONDER.to_be_bytes();
+        write_record(
+            &mut writer,
+            FCGI_BEGIN_REQUEST,
+            &[role_hi, role_lo, 0, 0, 0, 0, 0, 0],
+        )
+        .await
+        .map_err(FastCgiError::Io)?;
+        write_stream(&mut writer, FCGI_PARAMS, &encode_params(&params))
+            .await
+            .map_err(FastCgiError::Io)?;
+        write_record(&mut writer, FCGI_PARAMS, &[])
+            .await
+            .map_err(FastCgiError::Io)?;
+
+        let mut body = req.take_body().into_bytes_stream();
+        while let Some(data) = body.try_next().await.map_err(FastCgiError::Io)? {
+            write_stream(&mut writer, FCGI_STDIN, &data)
+                .await
+                .map_err(FastCgiError::Io)?;
+        }
+        write_record(&mut writer, FCGI_STDIN, &[])
+            .await
+            .map_err(FastCgiError::Io)?;
+        writer.flush().await.map_err(FastCgiError::Io)?;
+
+        // read the headers of the response
+        let mut data = Vec::new();
+        let (mut resp, rest) = loop {
+            match read_stdout(&mut conn).await.map_err(FastCgiError::Io)? {
+                Some(content) => {
+                    data.extend_from_slice(&content);
+                    if let Some(res) = parse_headers(&data)? {
+                        break res;
+                    }
+                }
+                None => {
+                    return Err(
+                        FastCgiError::InvalidResponse("incomplete headers".to_string()).into(),
+                    )
+                }
+            }
+        };
+
+        // stream the rest of the response
+        let first = stream::iter((!rest.is_empty()).then_some(Ok(rest)));
+        let rest = stream::try_unfold(conn, |mut conn| async move {
+            Ok::<_, IoError>(read_stdout(&mut conn).await?.map(|data| (data, conn)))
+        });
+        resp.set_body(Body::from_bytes_stream(first.chain(rest)));
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::test::TestClient;
+
+    fn decode_params(mut data: &[u8]) -> Vec<(String, String)> {
+        fn decode_length(data: &mut &[u8]) -> usize {
+            if data[0] < 0x80 {
+                let len = data[0] as usize;
+                *data = &data[1..];
+                len
+            } else {
+                let len = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) & 0x7fff_ffff;
+                *data = &data[4..];
+                len as usize
+            }
+        }
+
+        let mut params = Vec::new();
+        while !data.is_empty() {
+            let name_len = decode_length(&mut data);
+            let value_len = decode_length(&mut data);
+            let name = String::from_utf8(data[..name_len].to_vec()).unwrap();
+            let value = String::from_utf8(data[name_len..name_len + value_len].to_vec()).unwrap();
+            data = &data[name_len + value_len..];
+            params.push((name, value));
+        }
+        params
+    }
+
+    /// A backend that responds with the parameters and the body of the
+    /// request.
+    async fn backend() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut conn, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut params = Vec::new();
+                    let mut stdin = Vec::new();
+                    loop {
+                        let (ty, content) = read_record(&mut conn).await.unwrap();
+                        match ty {
+                            FCGI_PARAMS => params.extend_from_slice(&content),
+                            FCGI_STDIN if content.is_empty() => break,
+                            FCGI_STDIN => stdin.extend_from_slice(&content),
+                            _ => {}
+                        }
+                    }
+                    let params = decode_params(&params);
+                    let param = |name: &str| {
+                        params
+                            .iter()
+                            .find(|(key, _)| key == name)
+                            .map(|(_, value)| value.clone())
+                            .unwrap_or_default()
+                    };
+
+                    write_record(&mut conn, FCGI_STDERR, b"notice\n")
+                        .await
+                        .unwrap();
+                    write_record(
+                        &mut conn,
+                        FCGI_STDOUT,
+                        b"Status: 201 Created\r\nContent-Type: text/plain\r\nX-Script: ",
+                    )
+                    .await
+                    .unwrap();
+                    let rest = format!(
+                        "{}\r\nX-Path-Info: {}\r\nX-Request-Uri: {}\r\nX-Query: {}\r\nX-Token: {}\r\n\r\n",
+                        param("SCRIPT_FILENAME"),
+                        param("PATH_INFO"),
+                        param("REQUEST_URI"),
+                        param("QUERY_STRING"),
+                        param("HTTP_X_TOKEN"),
+                    );
+                    write_record(&mut conn, FCGI_STDOUT, rest.as_bytes())
+                        .await
+                        .unwrap();
+                    write_stream(&mut conn, FCGI_STDOUT, &stdin).await.unwrap();
+                    write_record(&mut conn, FCGI_STDOUT, &[]).await.unwrap();
+                    write_record(&mut conn, FCGI_END_REQUEST, &[0; 8])
+                        .await
+                        .unwrap();
+                });
+            }
+        });
+
+        addr
+    }
+
+    #[test]
+    fn params() {
+        let long = "a".repeat(200);
+        let params = vec![
+            ("A".to_string(), "1".to_string()),
+            ("LONG".to_string(), long.clone()),
+        ];
+        let data = encode_params(&params);
+        assert_eq!(&data[..5], b"\x01\x01A1\x04");
+        assert_eq!(&data[5..9], &(200u32 | 0x8000_0000).to_be_bytes());
+        assert_eq!(decode_params(&data), params);
+
+        let req = Request::builder()
+            .uri_str("http://example.com:8080/a/b.php?x=1")
+            .header(header::CONTENT_TYPE, "text/plain")
+            .header("x-token", "abc")
+            .header("proxy", "http://evil")
+            .finish();
+        let params = FastCgiEndpoint::tcp("127.0.0.1:9000")
+            .document_root("/var/www")
+            .param("SERVER_NAME", "localhost")
+            .build_params(&req)
+            .unwrap();
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(param("SCRIPT_FILENAME"), Some("/var/www/a/b.php"));
+        assert_eq!(param("SCRIPT_NAME"), Some("/a/b.php"));
+        assert_eq!(param("PATH_INFO"), Some(""));
+        assert_eq!(param("QUERY_STRING"), Some("x=1"));
+        assert_eq!(param("CONTENT_TYPE"), Some("text/plain"));
+        assert_eq!(param("HTTP_X_TOKEN"), Some("abc"));
+        assert_eq!(param("HTTP_PROXY"), None);
+        assert_eq!(param("SERVER_NAME"), Some("localhost"));
+
+        let req = Request::builder().uri_str("/a/../../etc/passwd").finish();
+        assert!(matches!(
+            FastCgiEndpoint::tcp("127.0.0.1:9000").build_params(&req),
+            Err(FastCgiError::InvalidPath)
+        ));
+    }
+
+    #[test]
+    fn headers() {
+        assert!(parse_headers(b"Content-Type: text/html\r\n")
+            .unwrap()
+            .is_none());
+
+        let (resp, rest) = parse_headers(b"Content-Type: text/html\r\n\r\nhello")
+            .unwrap()
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.content_type(), Some("text/html"));
+        assert_eq!(rest, "hello");
+
+        let (resp, rest) = parse_headers(b"Status: 404 Not Found\nX-A: 1\nX-A: 2\n\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.headers().get_all("x-a").iter().count(), 2);
+        assert!(rest.is_empty());
+
+        let (resp, _) = parse_headers(b"Location: /login\r\n\r\n").unwrap().unwrap();
+        assert_eq!(resp.status(), StatusCode::FOUND);
+
+        assert!(parse_headers(b"Status: abc\r\n\r\n").is_err());
+        assert!(parse_headers(b"invalid\r\n\r\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn forward() {
+        let addr = backend().await;
+        let cli = TestClient::new(
+            FastCgiEndpoint::tcp(addr)
+                .document_root("/var/www")
+                .script_name("/index.php"),
+        );
+
+        let body = "x".repeat(100_000);
+        let resp = cli
+            .post("/users/1")
+            .query("a", &1)
+            .header("x-token", "abc")
+            .body(body.clone())
+            .send()
+            .await;
+        resp.assert_status(StatusCode::CREATED);
+        resp.assert_content_type("text/plain");
+        resp.assert_header("x-script", "/var/www/index.php");
+        resp.assert_header("x-path-info", "/users/1");
+        resp.assert_header("x-request-uri", "/users/1?a=1");
+        resp.assert_header("x-query", "a=1");
+        resp.assert_header("x-token", "abc");
+        resp.assert_text(body).await;
+    }
+
+    #[tokio::test]
+    async fn backend_unavailable() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        TestClient::new(FastCgiEndpoint::tcp(addr))
+            .get("/index.php")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_GATEWAY);
+    }
+}
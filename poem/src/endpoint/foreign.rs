@@ -0,0 +1,444 @@
+use std::{
+    io::{Error as IoError, ErrorKind},
+    sync::Arc,
+};
+
+use bytes::Bytes;
+use futures_util::{stream, TryStreamExt};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{
+    error::ReadBodyError,
+    http::{HeaderName, HeaderValue, Method, StatusCode, Uri},
+    Body, Endpoint, Error, Request, Response, Result,
+};
+
+/// The status code and the headers of a response.
+type Head = (u16, Vec<(String, Vec<u8>)>);
+
+/// A request in a plain representation, which only consists of the standard
+/// types, so that it is easy to convert to the values of a scripting runtime
+/// or to pass through a FFI boundary.
+///
+/// The header values are bytes because they are not always valid UTF-8.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ForeignRequest {
+    /// The method, such as `GET`.
+    pub method: String,
+    /// The path and the query string, such as `/users?page=1`.
+    pub uri: String,
+    /// The headers, in the order they are received.
+    pub headers: Vec<(String, Vec<u8>)>,
+    /// The body.
+    pub body: Vec<u8>,
+}
+
+impl ForeignRequest {
+    /// Convert a [`Request`] to a `ForeignRequest`, the body is read into
+    /// memory.
+    pub async fn from_request(mut req: Request) -> Result<Self, ReadBodyError> {
+        let body = req.take_body().into_vec().await?;
+        Ok(Self {
+            method: req.method().to_string(),
+            uri: req
+                .uri()
+                .path_and_query()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| "/".to_string()),
+            headers: req
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+                .collect(),
+            body,
+        })
+    }
+
+    fn into_request(self) -> Result<Request> {
+        let method = Method::from_bytes(self.method.as_bytes())
+            .map_err(|_| bad_request("invalid method"))?;
+        let uri = self
+            .uri
+            .parse::<Uri>()
+            .map_err(|_| bad_request("invalid uri"))?;
+        let mut req = Request::builder().method(method).uri(uri).body(self.body);
+        for (name, value) in self.headers {
+            let name =
+                HeaderName::try_from(name).map_err(|_| bad_request("invalid header name"))?;
+            let value =
+                HeaderValue::from_bytes(&value).map_err(|_| bad_request("invalid header value"))?;
+            req.headers_mut().append(name, value);
+        }
+        Ok(req)
+    }
+
+    /// Calls the endpoint with this request and streams the response to
+    /// `sink`, which is the entry point for the hosts that embed a poem
+    /// application.
+    ///
+    /// If this request is invalid, a `400 Bad Request` response is sent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     endpoint::{ForeignRequest, ResponseSink},
+    ///     handler,
+    /// };
+    ///
+    /// #[handler]
+    /// fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Collect {
+    ///     status: u16,
+    ///     body: Vec<u8>,
+    /// }
+    ///
+    /// impl ResponseSink for Collect {
+    ///     fn start(&mut self, status: u16, _headers: Vec<(String, Vec<u8>)>) {
+    ///         self.status = status;
+    ///     }
+    ///
+    ///     fn write(&mut self, data: &[u8]) {
+    ///         self.body.extend_from_slice(data);
+    ///     }
+    /// }
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let req = ForeignRequest {
+    ///     method: "GET".to_string(),
+    ///     uri: "/".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let mut sink = Collect::default();
+    /// req.dispatch(&index, &mut sink).await;
+    /// assert_eq!(sink.status, 200);
+    /// assert_eq!(sink.body, b"hello");
+    /// # });
+    /// ```
+    pub async fn dispatch<E: Endpoint>(self, ep: &E, sink: &mut impl ResponseSink) {
+        let resp = match self.into_request() {
+            Ok(req) => ep.get_response(req).await,
+            Err(err) => err.into_response(),
+        };
+
+        let (parts, body) = resp.into_parts();
+        sink.start(
+            parts.status.as_u16(),
+            parts
+                .headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+                .collect(),
+        );
+
+        let mut body = body.into_bytes_stream();
+        loop {
+            match body.try_next().await {
+                Ok(Some(data)) => sink.write(&data),
+                Ok(None) => return sink.finish(Ok(())),
+                Err(err) => return sink.finish(Err(err)),
+            }
+        }
+    }
+}
+
+/// The receiver of a response streamed by [`ForeignRequest::dispatch`].
+pub trait ResponseSink {
+    /// Called once with the status code and the headers, before the body.
+    fn start(&mut self, status: u16, headers: Vec<(String, Vec<u8>)>);
+
+    /// Called with each chunk of the body.
+    fn write(&mut self, data: &[u8]);
+
+    /// Called once after the body, or if an error occurred while reading the
+    /// body.
+    fn finish(&mut self, result: std::io::Result<()>) {
+        let _ = result;
+    }
+}
+
+/// The writer of the response of a [`ForeignEndpoint`], which is similar to
+/// the `start_response` and `write` callables of WSGI.
+///
+/// The response is finished when the writer is dropped, and a `200 OK`
+/// response without headers is sent if [`ResponseWriter::start_response`] is
+/// not called.
+pub struct ResponseWriter {
+    head: Option<oneshot::Sender<Head>>,
+    body: mpsc::Sender<Bytes>,
+}
+
+impl ResponseWriter {
+    /// Starts the response with the status code and the headers.
+    ///
+    /// Returns an error if the response is already started.
+    pub fn start_response(
+        &mut self,
+        status: u16,
+        headers: Vec<(String, Vec<u8>)>,
+    ) -> std::io::Result<()> {
+        match self.head.take() {
+            Some(head) => {
+                let _ = head.send((status, headers));
+                Ok(())
+            }
+            None => Err(IoError::new(
+                ErrorKind::Other,
+                "the response is already started",
+            )),
+        }
+    }
+
+    /// Writes a chunk of the body, starting the response with `200 OK` if it
+    /// is not started. It blocks while the client is slower than the handler.
+    ///
+    /// Returns an error if the client is disconnected.
+    pub fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        if self.head.is_some() {
+            self.start_response(200, Vec::new())?;
+        }
+        self.body
+            .blocking_send(Bytes::copy_from_slice(data))
+            .map_err(|_| IoError::new(ErrorKind::BrokenPipe, "the client is disconnected"))
+    }
+}
+
+impl Drop for ResponseWriter {
+    fn drop(&mut self) {
+        // the endpoint responds with an error if the handler panicked
+        if !std::thread::panicking() {
+            if let Some(head) = self.head.take() {
+                let _ = head.send((200, Vec::new()));
+            }
+        }
+    }
+}
+
+type ForeignHandler = Arc<dyn Fn(ForeignRequest, ResponseWriter) + Send + Sync>;
+
+/// An endpoint that calls a blocking handler with plain types, which is used
+/// to call the handlers of a scripting runtime, such as Python or JavaScript,
+/// through FFI bindings.
+///
+/// The handler runs on the blocking thread pool of Tokio, so it can wait for
+/// a lock of the runtime, such as the GIL of Python. The request body is read
+/// into memory before the handler is called, and the response body is
+/// streamed to the client as the handler [writes](ResponseWriter::write) it.
+///
+/// If the handler panics before starting the response, or starts it with an
+/// invalid status code or header, the endpoint responds with
+/// `500 Internal Server Error`.
+///
+/// # Example
+///
+/// ```
+/// use poem::{endpoint::ForeignEndpoint, test::TestClient};
+///
+/// let ep = ForeignEndpoint::new(|req, mut writer| {
+///     let _ = writer.start_response(
+///         200,
+///         vec![("content-type".to_string(), b"text/plain".to_vec())],
+///     );
+///     let _ = writer.write(b"method: ");
+///     let _ = writer.write(req.method.as_bytes());
+/// });
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = TestClient::new(ep).post("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("method: POST").await;
+/// # });
+/// ```
+pub struct ForeignEndpoint {
+    handler: ForeignHandler,
+}
+
+impl ForeignEndpoint {
+    /// Create a `ForeignEndpoint` with the handler.
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(ForeignRequest, ResponseWriter) + Send + Sync + 'static,
+    {
+        Self {
+            handler: Arc::new(handler),
+        }
+    }
+}
+
+fn bad_request(msg: impl Into<String>) -> Error {
+    Error::from_string(msg, StatusCode::BAD_REQUEST)
+}
+
+fn internal_error(msg: impl Into<String>) -> Error {
+    Error::from_string(msg, StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[async_trait::async_trait]
+impl Endpoint for ForeignEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let req = ForeignRequest::from_request(req).await?;
+        let (head_tx, head_rx) = oneshot::channel();
+        let (body_tx, mut body_rx) = mpsc::channel(16);
+        let writer = ResponseWriter {
+            head: Some(head_tx),
+            body: body_tx,
+        };
+
+        let handler = self.handler.clone();
+        let task = tokio::task::spawn_blocking(move || handler(req, writer));
+        let (status, headers) = match head_rx.await {
+            Ok(head) => head,
+            Err(_) => {
+                let _ = task.await;
+                return Err(internal_error("the handler panicked"));
+            }
+        };
+
+        let mut resp = Response::default();
+        resp.set_status(
+            StatusCode::from_u16(status).map_err(|_| internal_error("invalid status code"))?,
+        );
+        for (name, value) in headers {
+            let name =
+                HeaderName::try_from(name).map_err(|_| internal_error("invalid header name"))?;
+            let value = HeaderValue::from_bytes(&value)
+                .map_err(|_| internal_error("invalid header value"))?;
+            resp.headers_mut().append(name, value);
+        }
+        resp.set_body(Body::from_bytes_stream(stream::poll_fn(move |cx| {
+            body_rx.poll_recv(cx).map(|data| data.map(Ok::<_, IoError>))
+        })));
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{handler, http::header, test::TestClient, IntoResponse};
+
+    #[derive(Default)]
+    struct Collect {
+        status: u16,
+        headers: Vec<(String, Vec<u8>)>,
+        body: Vec<u8>,
+        finished: bool,
+    }
+
+    impl ResponseSink for Collect {
+        fn start(&mut self, status: u16, headers: Vec<(String, Vec<u8>)>) {
+            self.status = status;
+            self.headers = headers;
+        }
+
+        fn write(&mut self, data: &[u8]) {
+            self.body.extend_from_slice(data);
+        }
+
+        fn finish(&mut self, result: std::io::Result<()>) {
+            assert!(result.is_ok());
+            self.finished = true;
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch() {
+        #[handler(internal)]
+        async fn index(req: &Request, body: String) -> impl IntoResponse {
+            format!("{} {} {}", req.method(), req.uri(), body).with_header("x-a", "1")
+        }
+
+        let mut sink = Collect::default();
+        ForeignRequest {
+            method: "POST".to_string(),
+            uri: "/users?page=1".to_string(),
+            headers: vec![("content-type".to_string(), b"text/plain".to_vec())],
+            body: b"hello".to_vec(),
+        }
+        .dispatch(&index, &mut sink)
+        .await;
+        assert_eq!(sink.status, 200);
+        assert!(sink.headers.contains(&("x-a".to_string(), b"1".to_vec())));
+        assert_eq!(sink.body, b"POST /users?page=1 hello");
+        assert!(sink.finished);
+
+        let mut sink = Collect::default();
+        ForeignRequest {
+            method: "GET".to_string(),
+            uri: "not a uri".to_string(),
+            ..Default::default()
+        }
+        .dispatch(&index, &mut sink)
+        .await;
+        assert_eq!(sink.status, 400);
+    }
+
+    #[tokio::test]
+    async fn from_request() {
+        let req = Request::builder()
+            .method(Method::PUT)
+            .uri(Uri::from_static("http://localhost/a?b=1"))
+            .header("x-a", "1")
+            .header("x-a", "2")
+            .body("data");
+        assert_eq!(
+            ForeignRequest::from_request(req).await.unwrap(),
+            ForeignRequest {
+                method: "PUT".to_string(),
+                uri: "/a?b=1".to_string(),
+                headers: vec![
+                    ("x-a".to_string(), b"1".to_vec()),
+                    ("x-a".to_string(), b"2".to_vec())
+                ],
+                body: b"data".to_vec(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn foreign_endpoint() {
+        let cli = TestClient::new(ForeignEndpoint::new(|req, mut writer| {
+            writer
+                .start_response(
+                    201,
+                    vec![("content-type".to_string(), b"text/plain".to_vec())],
+                )
+                .unwrap();
+            assert!(writer.start_response(200, Vec::new()).is_err());
+            for _ in 0..100 {
+                writer.write(&req.body).unwrap();
+            }
+        }));
+        let resp = cli.post("/").body("ab").send().await;
+        resp.assert_status(StatusCode::CREATED);
+        resp.assert_header(header::CONTENT_TYPE, "text/plain");
+        resp.assert_text("ab".repeat(100)).await;
+
+        let resp = TestClient::new(ForeignEndpoint::new(|_, _| {}))
+            .get("/")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("").await;
+
+        let resp = TestClient::new(ForeignEndpoint::new(|_, _| panic!("failed")))
+            .get("/")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+        let resp = TestClient::new(ForeignEndpoint::new(|_, mut writer| {
+            let _ = writer.start_response(1000, Vec::new());
+        }))
+        .get("/")
+        .send()
+        .await;
+        resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,491 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    future::Future,
+    sync::Arc,
+};
+
+use futures_util::{future::BoxFuture, stream, FutureExt, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    error::MethodNotAllowedError,
+    http::{Method, StatusCode},
+    Endpoint, Request, Response, Result,
+};
+
+type MethodFn = Arc<dyn Fn(Value) -> BoxFuture<'static, Result<Value, JsonRpcError>> + Send + Sync>;
+
+/// An error object of the JSON-RPC 2.0 protocol, which is returned by the
+/// methods of [`JsonRpc`].
+///
+/// Reference: <https://www.jsonrpc.org/specification#error_object>
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonRpcError {
+    /// The error code.
+    pub code: i64,
+    /// A short description of the error.
+    pub message: String,
+    /// Additional information about the error.
+    pub data: Option<Value>,
+}
+
+impl Display for JsonRpcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
+impl JsonRpcError {
+    /// Invalid JSON was received by the server.
+    pub const PARSE_ERROR: i64 = -32700;
+    /// The JSON sent is not a valid request object.
+    pub const INVALID_REQUEST: i64 = -32600;
+    /// The method does not exist.
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    /// Invalid method parameters.
+    pub const INVALID_PARAMS: i64 = -32602;
+    /// Internal JSON-RPC error.
+    pub const INTERNAL_ERROR: i64 = -32603;
+
+    /// Create an error with the code and message.
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Sets the additional information of the error.
+    #[must_use]
+    pub fn with_data(self, data: impl Into<Value>) -> Self {
+        Self {
+            data: Some(data.into()),
+            ..self
+        }
+    }
+
+    /// Create a `Parse error` error.
+    pub fn parse_error() -> Self {
+        Self::new(Self::PARSE_ERROR, "Parse error")
+    }
+
+    /// Create an `Invalid Request` error.
+    pub fn invalid_request() -> Self {
+        Self::new(Self::INVALID_REQUEST, "Invalid Request")
+    }
+
+    /// Create a `Method not found` error.
+    pub fn method_not_found() -> Self {
+        Self::new(Self::METHOD_NOT_FOUND, "Method not found")
+    }
+
+    /// Create an `Invalid params` error with the reason as the data.
+    pub fn invalid_params(reason: impl Display) -> Self {
+        Self::new(Self::INVALID_PARAMS, "Invalid params").with_data(reason.to_string())
+    }
+
+    /// Create an `Internal error` error with the reason as the data.
+    pub fn internal_error(reason: impl Display) -> Self {
+        Self::new(Self::INTERNAL_ERROR, "Internal error").with_data(reason.to_string())
+    }
+
+    fn to_value(&self) -> Value {
+        let mut obj = Map::new();
+        obj.insert("code".to_string(), self.code.into());
+        obj.insert("message".to_string(), self.message.clone().into());
+        if let Some(data) = &self.data {
+            obj.insert("data".to_string(), data.clone());
+        }
+        Value::Object(obj)
+    }
+}
+
+/// An endpoint that dispatches the [JSON-RPC 2.0](https://www.jsonrpc.org/specification)
+/// requests posted to it to the registered methods.
+///
+/// The parameters of a method are deserialized from the `params` member of
+/// the request, which may be an array or an object, and a missing `params` is
+/// deserialized from `null`, so the methods without parameters can take `()`
+/// or an `Option`. The result is serialized to the `result` member of the
+/// response.
+///
+/// Batch requests are executed concurrently, at most
+/// [`max_batch_concurrency`](JsonRpc::max_batch_concurrency) at a time, and
+/// the notifications, the requests without an `id`, are executed without a
+/// response. If there is nothing to respond, the status of the response is
+/// `204 No Content`. A batch with more than
+/// [`max_batch_size`](JsonRpc::max_batch_size) requests is rejected with an
+/// `Invalid Request` error.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     endpoint::{JsonRpc, JsonRpcError},
+///     test::TestClient,
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Params {
+///     a: i32,
+///     b: i32,
+/// }
+///
+/// let rpc = JsonRpc::new()
+///     .method("add", |params: Params| async move {
+///         Ok::<_, JsonRpcError>(params.a + params.b)
+///     })
+///     .method("div", |(a, b): (i32, i32)| async move {
+///         a.checked_div(b)
+///             .ok_or_else(|| JsonRpcError::new(1, "division by zero"))
+///     });
+/// let cli = TestClient::new(rpc);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/")
+///     .body(r#"{"jsonrpc":"2.0","method":"add","params":{"a":1,"b":2},"id":1}"#)
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_json(serde_json::json!({"jsonrpc": "2.0", "result": 3, "id": 1}))
+///     .await;
+/// # });
+/// ```
+pub struct JsonRpc {
+    methods: HashMap<String, MethodFn>,
+    max_batch_size: usize,
+    max_batch_concurrency: usize,
+}
+
+impl Default for JsonRpc {
+    fn default() -> Self {
+        Self {
+            methods: HashMap::new(),
+            max_batch_size: 100,
+            max_batch_concurrency: 16,
+        }
+    }
+}
+
+impl JsonRpc {
+    /// Create a `JsonRpc` endpoint without methods.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the maximum number of requests in a batch, default is `100`.
+    #[must_use]
+    pub fn max_batch_size(self, max_batch_size: usize) -> Self {
+        Self {
+            max_batch_size,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of requests of a batch that are executed
+    /// concurrently, default is `16`.
+    #[must_use]
+    pub fn max_batch_concurrency(self, max_batch_concurrency: usize) -> Self {
+        Self {
+            max_batch_concurrency: max_batch_concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Registers a method.
+    #[must_use]
+    pub fn method<F, Fut, P, R>(mut self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, JsonRpcError>> + Send + 'static,
+        P: DeserializeOwned + Send + 'static,
+        R: Serialize,
+    {
+        let f = Arc::new(f);
+        self.methods.insert(
+            name.into(),
+            Arc::new(move |params| {
+                let params = serde_json::from_value::<P>(params);
+                let f = f.clone();
+                async move {
+                    let result = f(params.map_err(JsonRpcError::invalid_params)?).await?;
+                    serde_json::to_value(result).map_err(JsonRpcError::internal_error)
+                }
+                .boxed()
+            }),
+        );
+        self
+    }
+
+    /// Executes a request object, returns `None` for the notifications.
+    async fn call_one(&self, req: Value) -> Option<Value> {
+        let mut req = match req {
+            Value::Object(req) => req,
+            _ => {
+                return Some(error_response(
+                    Value::Null,
+                    &JsonRpcError::invalid_request(),
+                ))
+            }
+        };
+
+        let id = match req.remove("id") {
+            Some(id @ (Value::Null | Value::Number(_) | Value::String(_))) => Some(id),
+            Some(_) => {
+                return Some(error_response(
+                    Value::Null,
+                    &JsonRpcError::invalid_request(),
+                ))
+            }
+            None => None,
+        };
+        let method = match (req.get("jsonrpc"), req.get("method")) {
+            (Some(Value::String(version)), Some(Value::String(method))) if version == "2.0" => {
+                method.clone()
+            }
+            _ => {
+                return Some(error_response(
+                    id.unwrap_or_default(),
+                    &JsonRpcError::invalid_request(),
+                ))
+            }
+        };
+        let params = match req.remove("params") {
+            Some(params @ (Value::Array(_) | Value::Object(_))) => params,
+            None => Value::Null,
+            Some(_) => {
+                return Some(error_response(
+                    id.unwrap_or_default(),
+                    &JsonRpcError::invalid_request(),
+                ))
+            }
+        };
+
+        let result = match self.methods.get(&method) {
+            Some(f) => f(params).await,
+            None => Err(JsonRpcError::method_not_found()),
+        };
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(err) => error_response(id, &err),
+        })
+    }
+}
+
+fn error_response(id: Value, err: &JsonRpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "error": err.to_value(), "id": id })
+}
+
+fn json_response(value: Option<Value>) -> Response {
+    match value {
+        Some(value) => Response::builder()
+            .content_type("application/json")
+            .body(value.to_string()),
+        None => StatusCode::NO_CONTENT.into(),
+    }
+}
+
+#[async_trait::async_trait]
+impl Endpoint for JsonRpc {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if req.method() != Method::POST {
+            return Err(MethodNotAllowedError.into());
+        }
+
+        let data = req.into_body().into_bytes().await?;
+        let value = match serde_json::from_slice::<Value>(&data) {
+            Ok(value) => value,
+            Err(_) => {
+                return Ok(json_response(Some(error_response(
+                    Value::Null,
+                    &JsonRpcError::parse_error(),
+                ))))
+            }
+        };
+
+        Ok(match value {
+            Value::Array(batch) if batch.is_empty() || batch.len() > self.max_batch_size => {
+                json_response(Some(error_response(
+                    Value::Null,
+                    &JsonRpcError::invalid_request(),
+                )))
+            }
+            Value::Array(batch) => {
+                let responses = stream::iter(batch.into_iter().map(|req| self.call_one(req)))
+                    .buffered(self.max_batch_concurrency)
+                    .filter_map(|resp| async move { resp })
+                    .collect::<Vec<_>>()
+                    .await;
+                json_response((!responses.is_empty()).then_some(Value::Array(responses)))
+            }
+            req => json_response(self.call_one(req).await),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::test::TestClient;
+
+    fn rpc() -> JsonRpc {
+        #[derive(Deserialize)]
+        struct Params {
+            a: i32,
+            b: i32,
+        }
+
+        JsonRpc::new()
+            .method("add", |params: Params| async move {
+                Ok::<_, JsonRpcError>(params.a + params.b)
+            })
+            .method("sub", |(a, b): (i32, i32)| async move {
+                Ok::<_, JsonRpcError>(a - b)
+            })
+            .method("ping", |_: ()| async move { Ok::<_, JsonRpcError>("pong") })
+            .method("fail", |_: Option<Value>| async move {
+                Err::<(), _>(JsonRpcError::new(100, "failed").with_data("reason"))
+            })
+    }
+
+    async fn call(body: &'static str) -> Value {
+        let resp = TestClient::new(rpc()).post("/").body(body).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("application/json");
+        let json = resp.json().await;
+        json.value().deserialize()
+    }
+
+    #[tokio::test]
+    async fn single() {
+        assert_eq!(
+            call(r#"{"jsonrpc":"2.0","method":"add","params":{"a":1,"b":2},"id":1}"#).await,
+            json!({"jsonrpc": "2.0", "result": 3, "id": 1})
+        );
+        assert_eq!(
+            call(r#"{"jsonrpc":"2.0","method":"sub","params":[5,2],"id":"a"}"#).await,
+            json!({"jsonrpc": "2.0", "result": 3, "id": "a"})
+        );
+        assert_eq!(
+            call(r#"{"jsonrpc":"2.0","method":"ping","id":null}"#).await,
+            json!({"jsonrpc": "2.0", "result": "pong", "id": null})
+        );
+        assert_eq!(
+            call(r#"{"jsonrpc":"2.0","method":"fail","id":2}"#).await,
+            json!({
+                "jsonrpc": "2.0",
+                "error": {"code": 100, "message": "failed", "data": "reason"},
+                "id": 2
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let code = |value: Value| value["error"]["code"].as_i64().unwrap();
+
+        let resp = call(r#"{"jsonrpc":"2.0","method":"add","params":"#).await;
+        assert_eq!(code(resp), JsonRpcError::PARSE_ERROR);
+        let resp = call(r#"{"jsonrpc":"1.0","method":"add","id":1}"#).await;
+        assert_eq!(code(resp), JsonRpcError::INVALID_REQUEST);
+        let resp = call(r#"{"jsonrpc":"2.0","method":"add","params":1,"id":1}"#).await;
+        assert_eq!(code(resp), JsonRpcError::INVALID_REQUEST);
+        let resp = call("[]").await;
+        assert_eq!(code(resp), JsonRpcError::INVALID_REQUEST);
+        let resp = call(r#"{"jsonrpc":"2.0","method":"mul","id":1}"#).await;
+        assert_eq!(code(resp), JsonRpcError::METHOD_NOT_FOUND);
+        let resp = call(r#"{"jsonrpc":"2.0","method":"add","params":[1],"id":1}"#).await;
+        assert_eq!(code(resp), JsonRpcError::INVALID_PARAMS);
+
+        TestClient::new(rpc())
+            .get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn batch() {
+        assert_eq!(
+            call(
+                r#"[
+                    {"jsonrpc":"2.0","method":"add","params":{"a":1,"b":2},"id":1},
+                    {"jsonrpc":"2.0","method":"ping"},
+                    1,
+                    {"jsonrpc":"2.0","method":"sub","params":[1,2],"id":2}
+                ]"#
+            )
+            .await,
+            json!([
+                {"jsonrpc": "2.0", "result": 3, "id": 1},
+                {
+                    "jsonrpc": "2.0",
+                    "error": {"code": -32600, "message": "Invalid Request"},
+                    "id": null
+                },
+                {"jsonrpc": "2.0", "result": -1, "id": 2},
+            ])
+        );
+
+        TestClient::new(rpc())
+            .post("/")
+            .body(r#"[{"jsonrpc":"2.0","method":"ping"},{"jsonrpc":"2.0","method":"add"}]"#)
+            .send()
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn batch_limits() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let rpc = JsonRpc::new()
+            .max_batch_size(4)
+            .max_batch_concurrency(2)
+            .method("sleep", {
+                let running = running.clone();
+                let max_running = max_running.clone();
+                move |_: ()| {
+                    let running = running.clone();
+                    let max_running = max_running.clone();
+                    async move {
+                        let n = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_running.fetch_max(n, Ordering::SeqCst);
+                        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        Ok::<_, JsonRpcError>(())
+                    }
+                }
+            });
+        let cli = TestClient::new(rpc);
+
+        let req = json!({"jsonrpc": "2.0", "method": "sleep", "id": 1});
+        let resp = cli.post("/").body_json(&vec![req.clone(); 4]).send().await;
+        resp.assert_status_is_ok();
+        let value: Value = resp.json().await.value().deserialize();
+        assert_eq!(value.as_array().unwrap().len(), 4);
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+
+        let resp = cli.post("/").body_json(&vec![req; 5]).send().await;
+        resp.assert_json(json!({
+            "jsonrpc": "2.0",
+            "error": {"code": -32600, "message": "Invalid Request"},
+            "id": null
+        }))
+        .await;
+    }
+}
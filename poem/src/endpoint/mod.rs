@@ -3,6 +3,8 @@
 mod after;
 mod and_then;
 mod around;
+#[cfg(feature = "multipart")]
+mod batch;
 mod before;
 mod catch_all_error;
 mod catch_error;
@@ -10,35 +12,61 @@ mod catch_error;
 mod embed;
 #[allow(clippy::module_inception)]
 mod endpoint;
+mod fastcgi;
+mod foreign;
 mod inspect_all_err;
 mod inspect_err;
+mod json_rpc;
 mod map;
 mod map_to_response;
+#[cfg(feature = "websocket")]
+mod mqtt_bridge;
 #[cfg(feature = "prometheus")]
 mod prometheus_exporter;
+mod resource;
+#[cfg(feature = "xml")]
+mod soap;
+#[cfg(any(feature = "static-files", feature = "embed"))]
+mod sri;
 #[cfg(feature = "static-files")]
 mod static_files;
 mod to_response;
 #[cfg(feature = "tower-compat")]
 mod tower_compat;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 pub use after::After;
 pub use and_then::AndThen;
 pub use around::Around;
+#[cfg(feature = "multipart")]
+pub use batch::BatchEndpoint;
 pub use before::Before;
 pub use catch_all_error::CatchAllError;
 pub use catch_error::CatchError;
 #[cfg(feature = "embed")]
 pub use embed::{EmbeddedFileEndpoint, EmbeddedFilesEndpoint};
 pub use endpoint::{make, make_sync, BoxEndpoint, Endpoint, EndpointExt, IntoEndpoint};
+pub use fastcgi::FastCgiEndpoint;
+pub use foreign::{ForeignEndpoint, ForeignRequest, ResponseSink, ResponseWriter};
 pub use inspect_all_err::InspectAllError;
 pub use inspect_err::InspectError;
+pub use json_rpc::{JsonRpc, JsonRpcError};
 pub use map::Map;
 pub use map_to_response::MapToResponse;
+#[cfg(feature = "websocket")]
+pub use mqtt_bridge::MqttBridge;
 #[cfg(feature = "prometheus")]
 pub use prometheus_exporter::PrometheusExporter;
+pub use resource::{ListParams, Page, Resource, ResourceEndpoint};
+#[cfg(feature = "xml")]
+pub use soap::{Soap, SoapFault};
+#[cfg(any(feature = "static-files", feature = "embed"))]
+pub use sri::{sri_hash, SriManifest};
 #[cfg(feature = "static-files")]
 pub use static_files::{StaticFileEndpoint, StaticFilesEndpoint};
 pub use to_response::ToResponse;
 #[cfg(feature = "tower-compat")]
-pub use tower_compat::TowerCompatExt;
+pub use tower_compat::{EndpointService, TowerCompatExt};
+#[cfg(feature = "wasm")]
+pub use wasm::WasmEndpoint;
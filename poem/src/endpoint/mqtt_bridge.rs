@@ -0,0 +1,468 @@
+use std::{
+    io::{Error as IoError, ErrorKind, Result as IoResult},
+    sync::Arc,
+};
+
+use bytes::{Buf, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    web::websocket::{Message, WebSocket, WebSocketStream},
+    Endpoint, FromRequest, IntoResponse, Request, Response, Result,
+};
+
+type AuthFn = Arc<dyn Fn(&Request) -> Result<()> + Send + Sync>;
+
+/// An endpoint that bridges the MQTT-over-WebSocket connections of browsers
+/// to an MQTT broker over TCP.
+///
+/// Each WebSocket connection that negotiates the `mqtt` subprotocol is
+/// relayed to a new TCP connection to the broker. The MQTT packets are
+/// forwarded as they are, except for the topics when a topic prefix is set,
+/// so the broker does not need to accept WebSocket connections itself.
+///
+/// # Topic mapping
+///
+/// With [`MqttBridge::topic_prefix`], the topics of the `PUBLISH` packets and
+/// the will messages, and the topic filters of the `SUBSCRIBE` and
+/// `UNSUBSCRIBE` packets sent by the client are prefixed, and the prefix is
+/// removed from the topics of the `PUBLISH` packets sent by the broker. This
+/// confines the clients to a subtree of the topics of the broker, for example
+/// the topics of a tenant. Both MQTT 3.1.1 and MQTT 5 are supported.
+///
+/// # Example
+///
+/// ```
+/// use poem::{endpoint::MqttBridge, http::StatusCode, Route};
+///
+/// let app = Route::new().at(
+///     "/mqtt",
+///     MqttBridge::new("127.0.0.1:1883")
+///         .topic_prefix("dashboard/")
+///         .auth(|req| match req.header("x-api-key") {
+///             Some("secret") => Ok(()),
+///             _ => Err(StatusCode::UNAUTHORIZED.into()),
+///         }),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "websocket")))]
+pub struct MqttBridge {
+    broker: String,
+    topic_prefix: Arc<[u8]>,
+    auth: Option<AuthFn>,
+}
+
+impl MqttBridge {
+    /// Create an `MqttBridge` endpoint that connects to the broker at the
+    /// address, such as `127.0.0.1:1883`.
+    pub fn new(broker: impl Into<String>) -> Self {
+        Self {
+            broker: broker.into(),
+            topic_prefix: Arc::from(&[][..]),
+            auth: None,
+        }
+    }
+
+    /// Sets the prefix that is added to the topics of the client, see
+    /// [Topic mapping](#topic-mapping).
+    #[must_use]
+    pub fn topic_prefix(self, prefix: impl AsRef<str>) -> Self {
+        Self {
+            topic_prefix: Arc::from(prefix.as_ref().as_bytes()),
+            ..self
+        }
+    }
+
+    /// Sets a function to authorize the WebSocket handshake requests, the
+    /// connection is rejected with the returned error.
+    #[must_use]
+    pub fn auth<F>(self, f: F) -> Self
+    where
+        F: Fn(&Request) -> Result<()> + Send + Sync + 'static,
+    {
+        Self {
+            auth: Some(Arc::new(f)),
+            ..self
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Endpoint for MqttBridge {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if let Some(auth) = &self.auth {
+            auth(&req)?;
+        }
+
+        let ws = WebSocket::from_request_without_body(&req).await?;
+        let broker = self.broker.clone();
+        let topic_prefix = self.topic_prefix.clone();
+        Ok(ws
+            .protocols(["mqtt"])
+            .on_upgrade(move |socket| async move {
+                match TcpStream::connect(&broker).await {
+                    Ok(stream) => {
+                        if let Err(err) = bridge(socket, stream, &topic_prefix).await {
+                            tracing::debug!(error = %err, "mqtt bridge closed");
+                        }
+                    }
+                    Err(err) => {
+                        tracing::error!(
+                            broker = %broker,
+                            error = %err,
+                            "failed to connect to mqtt broker"
+                        );
+                    }
+                }
+            })
+            .into_response())
+    }
+}
+
+async fn bridge(socket: WebSocketStream, stream: TcpStream, prefix: &[u8]) -> IoResult<()> {
+    let (mut ws_sink, mut ws_stream) = socket.split();
+    let (mut tcp_reader, mut tcp_writer) = stream.into_split();
+
+    let client_to_broker = async {
+        let mut buf = BytesMut::new();
+        let mut protocol_level = 4;
+        while let Some(msg) = ws_stream.next().await {
+            match msg? {
+                Message::Binary(data) => buf.extend_from_slice(&data),
+                Message::Close(_) => break,
+                _ => continue,
+            }
+            while let Some(packet) = next_packet(&mut buf)? {
+                let packet = map_client_packet(packet, prefix, &mut protocol_level)?;
+                tcp_writer.write_all(&packet).await?;
+            }
+        }
+        Ok::<_, IoError>(())
+    };
+
+    let broker_to_client = async {
+        let mut buf = BytesMut::new();
+        while tcp_reader.read_buf(&mut buf).await? > 0 {
+            while let Some(packet) = next_packet(&mut buf)? {
+                let packet = map_broker_packet(packet, prefix)?;
+                ws_sink.send(Message::Binary(packet)).await?;
+            }
+        }
+        ws_sink.close().await
+    };
+
+    tokio::select! {
+        res = client_to_broker => res,
+        res = broker_to_client => res,
+    }
+}
+
+fn malformed_packet() -> IoError {
+    IoError::new(ErrorKind::InvalidData, "malformed mqtt packet")
+}
+
+const CONNECT: u8 = 1;
+const PUBLISH: u8 = 3;
+const SUBSCRIBE: u8 = 8;
+const UNSUBSCRIBE: u8 = 10;
+
+/// Splits the first complete packet off the buffer.
+fn next_packet(buf: &mut BytesMut) -> IoResult<Option<Vec<u8>>> {
+    let mut remaining_length = 0;
+    for i in 0..4 {
+        let byte = match buf.get(i + 1) {
+            Some(byte) => *byte,
+            None => return Ok(None),
+        };
+        remaining_length |= ((byte & 0x7f) as usize) << (7 * i);
+        if byte & 0x80 == 0 {
+            let len = i + 2 + remaining_length;
+            if buf.len() < len {
+                return Ok(None);
+            }
+            let packet = buf[..len].to_vec();
+            buf.advance(len);
+            return Ok(Some(packet));
+        }
+    }
+    Err(malformed_packet())
+}
+
+struct PacketReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PacketReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, n: usize) -> IoResult<&'a [u8]> {
+        let data = self
+            .data
+            .get(self.pos..self.pos + n)
+            .ok_or_else(malformed_packet)?;
+        self.pos += n;
+        Ok(data)
+    }
+
+    fn u8(&mut self) -> IoResult<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn string(&mut self) -> IoResult<&'a [u8]> {
+        let len = self.bytes(2)?;
+        self.bytes(u16::from_be_bytes([len[0], len[1]]) as usize)
+    }
+
+    fn varint(&mut self) -> IoResult<usize> {
+        let mut value = 0;
+        for i in 0..4 {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as usize) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(malformed_packet())
+    }
+
+    /// Skips the properties of MQTT 5.
+    fn skip_properties(&mut self) -> IoResult<()> {
+        let len = self.varint()?;
+        self.bytes(len).map(|_| ())
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        &self.data[self.pos..]
+    }
+}
+
+fn put_string(buf: &mut Vec<u8>, data: &[u8]) -> IoResult<()> {
+    let len = u16::try_from(data.len()).map_err(|_| malformed_packet())?;
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(data);
+    Ok(())
+}
+
+/// Builds a packet from the first byte of the fixed header and the body.
+fn build_packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(body.len() + 5);
+    packet.push(header);
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Returns the body of the packet, which follows the fixed header.
+fn packet_body(packet: &[u8]) -> &[u8] {
+    let len_bytes = packet[1..].iter().take_while(|b| *b & 0x80 != 0).count() + 1;
+    &packet[1 + len_bytes..]
+}
+
+fn add_prefix(prefix: &[u8], topic: &[u8]) -> Vec<u8> {
+    // insert the prefix after the group name of the shared subscriptions
+    let split = match topic.strip_prefix(b"$share/") {
+        Some(rest) => match rest.iter().position(|b| *b == b'/') {
+            Some(pos) => b"$share/".len() + pos + 1,
+            None => 0,
+        },
+        None => 0,
+    };
+    [&topic[..split], prefix, &topic[split..]].concat()
+}
+
+fn map_client_packet(packet: Vec<u8>, prefix: &[u8], protocol_level: &mut u8) -> IoResult<Vec<u8>> {
+    let header = packet[0];
+    let packet_type = header >> 4;
+    if packet_type == CONNECT {
+        let mut reader = PacketReader::new(packet_body(&packet));
+        reader.string()?;
+        *protocol_level = reader.u8()?;
+    }
+    if prefix.is_empty() {
+        return Ok(packet);
+    }
+
+    let body = packet_body(&packet);
+    let mut reader = PacketReader::new(body);
+    let mut new_body = Vec::with_capacity(body.len() + prefix.len());
+    match packet_type {
+        CONNECT => {
+            reader.string()?;
+            reader.u8()?;
+            let flags = reader.u8()?;
+            reader.bytes(2)?;
+            if *protocol_level >= 5 {
+                reader.skip_properties()?;
+            }
+            reader.string()?;
+            if flags & 0x04 == 0 {
+                return Ok(packet);
+            }
+            if *protocol_level >= 5 {
+                reader.skip_properties()?;
+            }
+            new_body.extend_from_slice(&body[..reader.pos]);
+            let topic = reader.string()?;
+            put_string(&mut new_body, &add_prefix(prefix, topic))?;
+        }
+        PUBLISH => {
+            let topic = reader.string()?;
+            if topic.is_empty() {
+                // a topic alias of MQTT 5
+                return Ok(packet);
+            }
+            put_string(&mut new_body, &add_prefix(prefix, topic))?;
+        }
+        SUBSCRIBE | UNSUBSCRIBE => {
+            new_body.extend_from_slice(reader.bytes(2)?);
+            if *protocol_level >= 5 {
+                let start = reader.pos;
+                reader.skip_properties()?;
+                new_body.extend_from_slice(&body[start..reader.pos]);
+            }
+            while !reader.rest().is_empty() {
+                let filter = reader.string()?;
+                put_string(&mut new_body, &add_prefix(prefix, filter))?;
+                if packet_type == SUBSCRIBE {
+                    new_body.push(reader.u8()?);
+                }
+            }
+        }
+        _ => return Ok(packet),
+    }
+    new_body.extend_from_slice(reader.rest());
+    Ok(build_packet(header, &new_body))
+}
+
+fn map_broker_packet(packet: Vec<u8>, prefix: &[u8]) -> IoResult<Vec<u8>> {
+    if prefix.is_empty() || packet[0] >> 4 != PUBLISH {
+        return Ok(packet);
+    }
+
+    let body = packet_body(&packet);
+    let mut reader = PacketReader::new(body);
+    let topic = match reader.string()?.strip_prefix(prefix) {
+        Some(topic) => topic,
+        None => return Ok(packet),
+    };
+    let mut new_body = Vec::with_capacity(body.len());
+    put_string(&mut new_body, topic)?;
+    new_body.extend_from_slice(reader.rest());
+    Ok(build_packet(packet[0], &new_body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        listener::{Acceptor, Listener, TcpListener},
+        Server,
+    };
+
+    fn publish(topic: &str, payload: &[u8]) -> Vec<u8> {
+        let mut body = Vec::new();
+        put_string(&mut body, topic.as_bytes()).unwrap();
+        body.extend_from_slice(payload);
+        build_packet(PUBLISH << 4, &body)
+    }
+
+    #[test]
+    fn split_packets() {
+        let packet = publish("a/b", &[0; 200]);
+        let mut buf = BytesMut::from(&packet[..10]);
+        assert_eq!(next_packet(&mut buf).unwrap(), None);
+        buf.extend_from_slice(&packet[10..]);
+        buf.extend_from_slice(&[0xc0, 0x00]);
+        assert_eq!(next_packet(&mut buf).unwrap(), Some(packet));
+        assert_eq!(next_packet(&mut buf).unwrap(), Some(vec![0xc0, 0x00]));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn map_topics() {
+        let mut level = 4;
+        assert_eq!(
+            map_client_packet(publish("a/b", b"1"), b"t1/", &mut level).unwrap(),
+            publish("t1/a/b", b"1")
+        );
+        assert_eq!(
+            map_broker_packet(publish("t1/a/b", b"1"), b"t1/").unwrap(),
+            publish("a/b", b"1")
+        );
+
+        let mut body = vec![0, 1];
+        put_string(&mut body, b"a/#").unwrap();
+        body.push(1);
+        put_string(&mut body, b"$share/g/c").unwrap();
+        body.push(0);
+        let mut expected = vec![0, 1];
+        put_string(&mut expected, b"t1/a/#").unwrap();
+        expected.push(1);
+        put_string(&mut expected, b"$share/g/t1/c").unwrap();
+        expected.push(0);
+        assert_eq!(
+            map_client_packet(build_packet(0x82, &body), b"t1/", &mut level).unwrap(),
+            build_packet(0x82, &expected)
+        );
+    }
+
+    #[tokio::test]
+    async fn bridge_connection() {
+        let broker = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let broker_addr = broker.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = broker.accept().await.unwrap();
+            let mut buf = vec![0; publish("t1/a", b"hi").len()];
+            stream.read_exact(&mut buf).await.unwrap();
+            assert_eq!(buf, publish("t1/a", b"hi"));
+            stream.write_all(&publish("t1/b", b"ok")).await.unwrap();
+        });
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+        tokio::spawn(
+            Server::new_with_acceptor(acceptor)
+                .run(MqttBridge::new(broker_addr.to_string()).topic_prefix("t1/")),
+        );
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+            .await
+            .unwrap();
+        client
+            .send(tokio_tungstenite::tungstenite::Message::Binary(publish(
+                "a", b"hi",
+            )))
+            .await
+            .unwrap();
+        let msg = client.next().await.unwrap().unwrap();
+        assert_eq!(msg.into_data(), publish("b", b"ok"));
+    }
+}
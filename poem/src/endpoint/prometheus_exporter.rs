@@ -16,6 +16,9 @@ use crate::{
 /// let registry = Registry::new();
 /// let app = Route::new().nest("/metrics", PrometheusExporter::new(registry));
 /// ```
+///
+/// See also [`PrometheusMetrics`](crate::middleware::PrometheusMetrics) for
+/// the metrics of the requests.
 #[cfg_attr(docsrs, doc(cfg(feature = "prometheus")))]
 pub struct PrometheusExporter {
     registry: Registry,
@@ -56,7 +59,9 @@ impl Endpoint for PrometheusExporterEndpoint {
         let metric_families = self.registry.gather();
         let mut result = Vec::new();
         match encoder.encode(&metric_families, &mut result) {
-            Ok(()) => Ok(Response::builder().content_type("text/plain").body(result)),
+            Ok(()) => Ok(Response::builder()
+                .content_type(encoder.format_type())
+                .body(result)),
             Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
@@ -0,0 +1,544 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    endpoint::make,
+    error::{NotFoundError, ValidationError},
+    get,
+    http::{header, HeaderValue, StatusCode},
+    post,
+    web::Json,
+    Endpoint, Error, IntoResponse, Request, Response, Result, Route,
+};
+
+/// The parameters of listing the items of a [`Resource`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListParams {
+    /// The number of items to skip.
+    pub offset: u64,
+    /// The maximum number of items to return.
+    pub limit: u64,
+    /// Whether the soft-deleted items are included.
+    pub include_deleted: bool,
+}
+
+/// A page of the items of a [`Resource`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// The items of this page.
+    pub items: Vec<T>,
+    /// The total number of items.
+    pub total: u64,
+    /// The number of skipped items.
+    pub offset: u64,
+    /// The maximum number of items of this page.
+    pub limit: u64,
+}
+
+/// A storage of the items that can be served as a REST resource by
+/// [`ResourceEndpoint`].
+///
+/// The items are expected to be soft-deleted, [`Resource::delete`] marks the
+/// item as deleted, and [`Resource::restore`] brings it back. The deleted
+/// items are not returned by [`Resource::get`], and only returned by
+/// [`Resource::list`] if [`ListParams::include_deleted`] is `true`.
+#[async_trait::async_trait]
+pub trait Resource: Send + Sync + 'static {
+    /// The identifier of the items, which is parsed from the path.
+    type Id: FromStr + Display + Send + Sync;
+
+    /// The representation of the items.
+    type Item: Serialize + Send + Sync;
+
+    /// The input for creating an item.
+    type Create: DeserializeOwned + Send + Sync;
+
+    /// The input for updating an item.
+    type Update: DeserializeOwned + Send + Sync;
+
+    /// The page size used if the request does not specify the `limit`.
+    const DEFAULT_PAGE_SIZE: u64 = 20;
+
+    /// The maximum page size.
+    const MAX_PAGE_SIZE: u64 = 100;
+
+    /// Returns a page of the items.
+    async fn list(&self, params: ListParams) -> Result<Page<Self::Item>>;
+
+    /// Returns the item, or `None` if the item does not exist or has been
+    /// deleted.
+    async fn get(&self, id: &Self::Id) -> Result<Option<Self::Item>>;
+
+    /// Creates an item, returns the identifier and the created item.
+    async fn create(&self, input: Self::Create) -> Result<(Self::Id, Self::Item)>;
+
+    /// Updates the item, returns `None` if the item does not exist or has been
+    /// deleted.
+    async fn update(&self, id: &Self::Id, input: Self::Update) -> Result<Option<Self::Item>>;
+
+    /// Marks the item as deleted, returns `false` if the item does not exist
+    /// or has been deleted.
+    async fn delete(&self, id: &Self::Id) -> Result<bool>;
+
+    /// Restores the deleted item, returns `None` if the item does not exist
+    /// or has not been deleted.
+    ///
+    /// The default implementation fails with `501 Not Implemented`.
+    async fn restore(&self, _id: &Self::Id) -> Result<Option<Self::Item>> {
+        Err(Error::from_status(StatusCode::NOT_IMPLEMENTED))
+    }
+
+    /// Validates the input for creating an item.
+    fn validate_create(&self, _input: &Self::Create) -> Result<(), ValidationError> {
+        Ok(())
+    }
+
+    /// Validates the input for updating the item.
+    fn validate_update(
+        &self,
+        _id: &Self::Id,
+        _input: &Self::Update,
+    ) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    #[serde(default)]
+    offset: u64,
+    limit: Option<u64>,
+    #[serde(default)]
+    include_deleted: bool,
+}
+
+/// An endpoint that serves the standard CRUD routes of a [`Resource`].
+///
+/// | Method | Path           | Description                                   |
+/// |--------|----------------|-----------------------------------------------|
+/// | GET    | `/`            | List the items with `offset`, `limit` and `include_deleted` query parameters |
+/// | POST   | `/`            | Create an item, responds `201 Created` with the `Location` header |
+/// | GET    | `/:id`         | Get the item                                  |
+/// | PUT    | `/:id`         | Update the item                               |
+/// | DELETE | `/:id`         | Delete the item, responds `204 No Content`    |
+/// | POST   | `/:id/restore` | Restore the deleted item                      |
+///
+/// The request and response bodies are JSON, the invalid inputs are rejected
+/// with [`ValidationError`], and the missing items with [`NotFoundError`].
+///
+/// # Example
+///
+/// ```
+/// use std::{collections::BTreeMap, sync::Mutex};
+///
+/// use poem::{
+///     endpoint::{ListParams, Page, Resource, ResourceEndpoint},
+///     error::ValidationError,
+///     http::StatusCode,
+///     test::TestClient,
+///     Result, Route,
+/// };
+/// use serde::{Deserialize, Serialize};
+/// use serde_json::json;
+///
+/// #[derive(Clone, Serialize)]
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct UserInput {
+///     name: String,
+/// }
+///
+/// #[derive(Default)]
+/// struct Users(Mutex<BTreeMap<u64, User>>);
+///
+/// #[poem::async_trait]
+/// impl Resource for Users {
+///     type Id = u64;
+///     type Item = User;
+///     type Create = UserInput;
+///     type Update = UserInput;
+///
+///     async fn list(&self, params: ListParams) -> Result<Page<User>> {
+///         let users = self.0.lock().unwrap();
+///         Ok(Page {
+///             items: users
+///                 .values()
+///                 .skip(params.offset as usize)
+///                 .take(params.limit as usize)
+///                 .cloned()
+///                 .collect(),
+///             total: users.len() as u64,
+///             offset: params.offset,
+///             limit: params.limit,
+///         })
+///     }
+///
+///     async fn get(&self, id: &u64) -> Result<Option<User>> {
+///         Ok(self.0.lock().unwrap().get(id).cloned())
+///     }
+///
+///     async fn create(&self, input: UserInput) -> Result<(u64, User)> {
+///         let mut users = self.0.lock().unwrap();
+///         let id = users.len() as u64 + 1;
+///         let user = User { id, name: input.name };
+///         users.insert(id, user.clone());
+///         Ok((id, user))
+///     }
+///
+///     async fn update(&self, id: &u64, input: UserInput) -> Result<Option<User>> {
+///         let mut users = self.0.lock().unwrap();
+///         Ok(users.get_mut(id).map(|user| {
+///             user.name = input.name;
+///             user.clone()
+///         }))
+///     }
+///
+///     async fn delete(&self, id: &u64) -> Result<bool> {
+///         Ok(self.0.lock().unwrap().remove(id).is_some())
+///     }
+///
+///     fn validate_create(&self, input: &UserInput) -> Result<(), ValidationError> {
+///         let mut err = ValidationError::new();
+///         if input.name.is_empty() {
+///             err.add("name", "must not be empty");
+///         }
+///         err.into_result()
+///     }
+/// }
+///
+/// let app = Route::new().nest("/users", ResourceEndpoint::new(Users::default()));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/users")
+///     .body_json(&json!({ "name": "alice" }))
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::CREATED);
+/// resp.assert_header("location", "/users/1");
+///
+/// let resp = cli.get("/users/1").send().await;
+/// resp.assert_json(json!({ "id": 1, "name": "alice" })).await;
+///
+/// let resp = cli
+///     .post("/users")
+///     .body_json(&json!({ "name": "" }))
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+/// # });
+/// ```
+pub struct ResourceEndpoint {
+    route: Route,
+}
+
+impl ResourceEndpoint {
+    /// Create a `ResourceEndpoint` that serves the resource.
+    pub fn new<R: Resource>(resource: R) -> Self {
+        let resource = Arc::new(resource);
+        let route = Route::new()
+            .at(
+                "/",
+                get(make({
+                    let resource = resource.clone();
+                    move |req| list(resource.clone(), req)
+                }))
+                .post(make({
+                    let resource = resource.clone();
+                    move |req| create(resource.clone(), req)
+                })),
+            )
+            .at(
+                "/:id",
+                get(make({
+                    let resource = resource.clone();
+                    move |req| get_item(resource.clone(), req)
+                }))
+                .put(make({
+                    let resource = resource.clone();
+                    move |req| update(resource.clone(), req)
+                }))
+                .delete(make({
+                    let resource = resource.clone();
+                    move |req| delete(resource.clone(), req)
+                })),
+            )
+            .at(
+                "/:id/restore",
+                post(make(move |req| restore(resource.clone(), req))),
+            );
+        Self { route }
+    }
+}
+
+#[async_trait::async_trait]
+impl Endpoint for ResourceEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        self.route.call(req).await
+    }
+}
+
+fn item_id<R: Resource>(req: &Request) -> Result<R::Id> {
+    Ok(req
+        .raw_path_param("id")
+        .and_then(|id| id.parse().ok())
+        .ok_or(NotFoundError)?)
+}
+
+async fn list<R: Resource>(resource: Arc<R>, req: Request) -> Result<Response> {
+    let query = req.params::<ListQuery>()?;
+    let params = ListParams {
+        offset: query.offset,
+        limit: query
+            .limit
+            .unwrap_or(R::DEFAULT_PAGE_SIZE)
+            .clamp(1, R::MAX_PAGE_SIZE),
+        include_deleted: query.include_deleted,
+    };
+    Ok(Json(resource.list(params).await?).into_response())
+}
+
+async fn create<R: Resource>(resource: Arc<R>, mut req: Request) -> Result<Response> {
+    let input = req.take_body().into_json::<R::Create>().await?;
+    resource.validate_create(&input)?;
+    let (id, item) = resource.create(input).await?;
+
+    let mut resp = Json(item).with_status(StatusCode::CREATED).into_response();
+    let location = format!("{}/{}", req.original_uri().path().trim_end_matches('/'), id);
+    if let Ok(location) = HeaderValue::try_from(location) {
+        resp.headers_mut().insert(header::LOCATION, location);
+    }
+    Ok(resp)
+}
+
+async fn get_item<R: Resource>(resource: Arc<R>, req: Request) -> Result<Response> {
+    let id = item_id::<R>(&req)?;
+    let item = resource.get(&id).await?.ok_or(NotFoundError)?;
+    Ok(Json(item).into_response())
+}
+
+async fn update<R: Resource>(resource: Arc<R>, mut req: Request) -> Result<Response> {
+    let id = item_id::<R>(&req)?;
+    let input = req.take_body().into_json::<R::Update>().await?;
+    resource.validate_update(&id, &input)?;
+    let item = resource.update(&id, input).await?.ok_or(NotFoundError)?;
+    Ok(Json(item).into_response())
+}
+
+async fn delete<R: Resource>(resource: Arc<R>, req: Request) -> Result<Response> {
+    let id = item_id::<R>(&req)?;
+    if !resource.delete(&id).await? {
+        return Err(NotFoundError.into());
+    }
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn restore<R: Resource>(resource: Arc<R>, req: Request) -> Result<Response> {
+    let id = item_id::<R>(&req)?;
+    let item = resource.restore(&id).await?.ok_or(NotFoundError)?;
+    Ok(Json(item).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use parking_lot::Mutex;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::TestClient;
+
+    #[derive(Debug, Clone, Serialize)]
+    struct Todo {
+        id: u32,
+        title: String,
+        #[serde(skip)]
+        deleted: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct TodoInput {
+        title: String,
+    }
+
+    #[derive(Default)]
+    struct Todos(Mutex<BTreeMap<u32, Todo>>);
+
+    #[async_trait::async_trait]
+    impl Resource for Todos {
+        type Id = u32;
+        type Item = Todo;
+        type Create = TodoInput;
+        type Update = TodoInput;
+
+        const DEFAULT_PAGE_SIZE: u64 = 2;
+
+        async fn list(&self, params: ListParams) -> Result<Page<Todo>> {
+            let todos = self.0.lock();
+            let items = todos
+                .values()
+                .filter(|todo| params.include_deleted || !todo.deleted)
+                .cloned()
+                .collect::<Vec<_>>();
+            Ok(Page {
+                total: items.len() as u64,
+                items: items
+                    .into_iter()
+                    .skip(params.offset as usize)
+                    .take(params.limit as usize)
+                    .collect(),
+                offset: params.offset,
+                limit: params.limit,
+            })
+        }
+
+        async fn get(&self, id: &u32) -> Result<Option<Todo>> {
+            Ok(self.0.lock().get(id).filter(|todo| !todo.deleted).cloned())
+        }
+
+        async fn create(&self, input: TodoInput) -> Result<(u32, Todo)> {
+            let mut todos = self.0.lock();
+            let id = todos.len() as u32 + 1;
+            let todo = Todo {
+                id,
+                title: input.title,
+                deleted: false,
+            };
+            todos.insert(id, todo.clone());
+            Ok((id, todo))
+        }
+
+        async fn update(&self, id: &u32, input: TodoInput) -> Result<Option<Todo>> {
+            let mut todos = self.0.lock();
+            Ok(todos.get_mut(id).filter(|todo| !todo.deleted).map(|todo| {
+                todo.title = input.title;
+                todo.clone()
+            }))
+        }
+
+        async fn delete(&self, id: &u32) -> Result<bool> {
+            let mut todos = self.0.lock();
+            match todos.get_mut(id).filter(|todo| !todo.deleted) {
+                Some(todo) => {
+                    todo.deleted = true;
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+
+        async fn restore(&self, id: &u32) -> Result<Option<Todo>> {
+            let mut todos = self.0.lock();
+            Ok(todos.get_mut(id).filter(|todo| todo.deleted).map(|todo| {
+                todo.deleted = false;
+                todo.clone()
+            }))
+        }
+
+        fn validate_create(&self, input: &TodoInput) -> Result<(), ValidationError> {
+            let mut err = ValidationError::new();
+            if input.title.trim().is_empty() {
+                err.add("title", "must not be empty");
+            }
+            err.into_result()
+        }
+    }
+
+    #[tokio::test]
+    async fn crud() {
+        let cli =
+            TestClient::new(Route::new().nest("/todos", ResourceEndpoint::new(Todos::default())));
+
+        for title in ["a", "b", "c"] {
+            let resp = cli
+                .post("/todos")
+                .body_json(&json!({ "title": title }))
+                .send()
+                .await;
+            resp.assert_status(StatusCode::CREATED);
+        }
+
+        let resp = cli
+            .post("/todos")
+            .body_json(&json!({ "title": " " }))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        resp.assert_json(json!({
+            "type": "about:blank",
+            "title": "Unprocessable Entity",
+            "status": 422,
+            "detail": "validation failed",
+            "errors": { "title": ["must not be empty"] },
+        }))
+        .await;
+
+        let resp = cli.get("/todos").send().await;
+        resp.assert_json(json!({
+            "items": [{ "id": 1, "title": "a" }, { "id": 2, "title": "b" }],
+            "total": 3,
+            "offset": 0,
+            "limit": 2,
+        }))
+        .await;
+
+        let resp = cli
+            .put("/todos/2")
+            .body_json(&json!({ "title": "bb" }))
+            .send()
+            .await;
+        resp.assert_json(json!({ "id": 2, "title": "bb" })).await;
+
+        cli.delete("/todos/1")
+            .send()
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+        cli.get("/todos/1")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        cli.delete("/todos/1")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+
+        let resp = cli.get("/todos").query("limit", &10).send().await;
+        resp.assert_json(json!({
+            "items": [{ "id": 2, "title": "bb" }, { "id": 3, "title": "c" }],
+            "total": 2,
+            "offset": 0,
+            "limit": 10,
+        }))
+        .await;
+        let resp = cli
+            .get("/todos")
+            .query("offset", &2)
+            .query("include_deleted", &true)
+            .send()
+            .await;
+        resp.assert_json(json!({
+            "items": [{ "id": 3, "title": "c" }],
+            "total": 3,
+            "offset": 2,
+            "limit": 2,
+        }))
+        .await;
+
+        let resp = cli.post("/todos/1/restore").send().await;
+        resp.assert_json(json!({ "id": 1, "title": "a" })).await;
+        cli.get("/todos/1").send().await.assert_status_is_ok();
+        cli.get("/todos/abc")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,360 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    future::Future,
+    sync::Arc,
+};
+
+use futures_util::{future::BoxFuture, FutureExt};
+use quick_xml::{escape::escape, events::Event, Reader};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    error::MethodNotAllowedError,
+    http::{Method, StatusCode},
+    Endpoint, Request, Response, Result,
+};
+
+type OperationFn =
+    Arc<dyn Fn(String) -> BoxFuture<'static, Result<String, SoapFault>> + Send + Sync>;
+
+const ENVELOPE_NS: &str = "http://schemas.xmlsoap.org/soap/envelope/";
+
+/// A SOAP 1.1 fault, which is returned by the operations of [`Soap`].
+///
+/// Reference: <https://www.w3.org/TR/2000/NOTE-SOAP-20000508/#_Toc478383507>
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SoapFault {
+    /// The fault code, such as `soap:Client` or `soap:Server`.
+    pub code: String,
+    /// A human readable explanation of the fault.
+    pub string: String,
+    /// The application specific information, which is escaped as text.
+    pub detail: Option<String>,
+}
+
+impl Display for SoapFault {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.string)
+    }
+}
+
+impl std::error::Error for SoapFault {}
+
+impl SoapFault {
+    /// Create a fault with the code and the explanation.
+    pub fn new(code: impl Into<String>, string: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            string: string.into(),
+            detail: None,
+        }
+    }
+
+    /// Create a `soap:Client` fault, which indicates that the message is
+    /// incorrectly formed or contains incorrect information.
+    pub fn client(string: impl Into<String>) -> Self {
+        Self::new("soap:Client", string)
+    }
+
+    /// Create a `soap:Server` fault, which indicates that the message could
+    /// not be processed for reasons not directly attributable to its
+    /// contents.
+    pub fn server(string: impl Into<String>) -> Self {
+        Self::new("soap:Server", string)
+    }
+
+    /// Sets the detail of the fault.
+    #[must_use]
+    pub fn with_detail(self, detail: impl Into<String>) -> Self {
+        Self {
+            detail: Some(detail.into()),
+            ..self
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = format!(
+            "<soap:Fault><faultcode>{}</faultcode><faultstring>{}</faultstring>",
+            escape(&self.code),
+            escape(&self.string)
+        );
+        if let Some(detail) = &self.detail {
+            xml.push_str(&format!("<detail>{}</detail>", escape(detail)));
+        }
+        xml.push_str("</soap:Fault>");
+        xml
+    }
+}
+
+/// A minimal [SOAP 1.1](https://www.w3.org/TR/2000/NOTE-SOAP-20000508/)
+/// endpoint, which dispatches the messages posted to it to the registered
+/// operations.
+///
+/// The operation is selected by the local name of the first element in the
+/// `Body` of the envelope, which is deserialized to the request type of the
+/// operation with [`quick-xml`](https://crates.io/crates/quick-xml). The
+/// result is serialized as the content of the `Body` of the response
+/// envelope, using the name of the type as the name of the element, which can
+/// be changed with `#[serde(rename = "...")]`.
+///
+/// The errors are responded as SOAP faults with the status
+/// `500 Internal Server Error`, as required by SOAP 1.1. The `Header` of the
+/// envelope, the encoding styles and the attachments are not supported.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     endpoint::{Soap, SoapFault},
+///     test::TestClient,
+/// };
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize)]
+/// struct GetPrice {
+///     #[serde(rename = "Item")]
+///     item: String,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct GetPriceResponse {
+///     #[serde(rename = "Price")]
+///     price: f64,
+/// }
+///
+/// let soap = Soap::new().operation("GetPrice", |req: GetPrice| async move {
+///     match req.item.as_str() {
+///         "Apples" => Ok(GetPriceResponse { price: 1.9 }),
+///         _ => Err(SoapFault::client("unknown item")),
+///     }
+/// });
+/// let cli = TestClient::new(soap);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/")
+///     .content_type("text/xml; charset=utf-8")
+///     .body(
+///         r#"<?xml version="1.0"?>
+/// <soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+///   <soap:Body>
+///     <GetPrice><Item>Apples</Item></GetPrice>
+///   </soap:Body>
+/// </soap:Envelope>"#,
+///     )
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("text/xml; charset=utf-8");
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "xml")))]
+#[derive(Default)]
+pub struct Soap {
+    operations: HashMap<String, OperationFn>,
+}
+
+impl Soap {
+    /// Create a `Soap` endpoint without operations.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers an operation, the name is the local name of the request
+    /// element.
+    #[must_use]
+    pub fn operation<F, Fut, Req, Resp>(mut self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(Req) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Resp, SoapFault>> + Send + 'static,
+        Req: DeserializeOwned + Send + 'static,
+        Resp: Serialize,
+    {
+        let f = Arc::new(f);
+        self.operations.insert(
+            name.into(),
+            Arc::new(move |xml| {
+                let req = quick_xml::de::from_str::<Req>(&xml);
+                let f = f.clone();
+                async move {
+                    let req = req.map_err(|err| SoapFault::client(err.to_string()))?;
+                    let resp = f(req).await?;
+                    quick_xml::se::to_string(&resp)
+                        .map_err(|err| SoapFault::server(err.to_string()))
+                }
+                .boxed()
+            }),
+        );
+        self
+    }
+
+    async fn dispatch(&self, xml: &str) -> Result<String, SoapFault> {
+        let (name, element) = parse_envelope(xml).map_err(SoapFault::client)?;
+        match self.operations.get(&name) {
+            Some(f) => f(element).await,
+            None => Err(SoapFault::client(format!("unknown operation `{name}`"))),
+        }
+    }
+}
+
+/// Returns the local name and the XML of the first element in the `Body` of
+/// the envelope.
+fn parse_envelope(xml: &str) -> Result<(String, String), String> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut depth = 0;
+
+    loop {
+        let start = reader.buffer_position();
+        match reader.read_event().map_err(|err| err.to_string())? {
+            Event::Start(e) => {
+                let local_name = e.local_name();
+                match (depth, local_name.as_ref()) {
+                    (0, b"Envelope") | (1, b"Body") => depth += 1,
+                    (0, _) => return Err("missing `Envelope` element".to_string()),
+                    (1, _) => {
+                        reader
+                            .read_to_end(e.name())
+                            .map_err(|err| err.to_string())?;
+                    }
+                    _ => {
+                        let name = String::from_utf8_lossy(local_name.as_ref()).into_owned();
+                        reader
+                            .read_to_end(e.name())
+                            .map_err(|err| err.to_string())?;
+                        return Ok((name, xml[start..reader.buffer_position()].to_string()));
+                    }
+                }
+            }
+            Event::Empty(e) if depth == 2 => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                return Ok((name, xml[start..reader.buffer_position()].to_string()));
+            }
+            Event::End(_) | Event::Eof => return Err("missing operation element".to_string()),
+            _ => {}
+        }
+    }
+}
+
+fn envelope(status: StatusCode, body: &str) -> Response {
+    Response::builder()
+        .status(status)
+        .content_type("text/xml; charset=utf-8")
+        .body(format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="{ENVELOPE_NS}"><soap:Body>{body}</soap:Body></soap:Envelope>"#
+        ))
+}
+
+#[async_trait::async_trait]
+impl Endpoint for Soap {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if req.method() != Method::POST {
+            return Err(MethodNotAllowedError.into());
+        }
+
+        let xml = req.into_body().into_string().await?;
+        Ok(match self.dispatch(&xml).await {
+            Ok(body) => envelope(StatusCode::OK, &body),
+            Err(fault) => envelope(StatusCode::INTERNAL_SERVER_ERROR, &fault.to_xml()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::test::TestClient;
+
+    #[derive(Deserialize)]
+    struct Add {
+        a: i32,
+        b: i32,
+    }
+
+    #[derive(Serialize)]
+    struct AddResponse {
+        result: i32,
+    }
+
+    fn soap() -> Soap {
+        Soap::new().operation("Add", |req: Add| async move {
+            req.a
+                .checked_add(req.b)
+                .map(|result| AddResponse { result })
+                .ok_or_else(|| SoapFault::client("overflow").with_detail("a <= b"))
+        })
+    }
+
+    fn request(body: &str) -> String {
+        format!(
+            r#"<soap:Envelope xmlns:soap="{ENVELOPE_NS}"><soap:Header/><soap:Body>{body}</soap:Body></soap:Envelope>"#
+        )
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_envelope(&request(r#"<m:Add xmlns:m="urn:calc"><a>1</a></m:Add>"#)),
+            Ok((
+                "Add".to_string(),
+                r#"<m:Add xmlns:m="urn:calc"><a>1</a></m:Add>"#.to_string()
+            ))
+        );
+        assert_eq!(
+            parse_envelope(&request("<Ping/>")),
+            Ok(("Ping".to_string(), "<Ping/>".to_string()))
+        );
+        assert!(parse_envelope(&request("")).is_err());
+        assert!(parse_envelope("<Add><a>1</a></Add>").is_err());
+        assert!(parse_envelope("<soap:Envelope").is_err());
+    }
+
+    #[tokio::test]
+    async fn call() {
+        let cli = TestClient::new(soap());
+
+        let resp = cli
+            .post("/")
+            .body(request("<Add><a>1</a><b>2</b></Add>"))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("text/xml; charset=utf-8");
+        resp.assert_text(format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="{ENVELOPE_NS}"><soap:Body><AddResponse><result>3</result></AddResponse></soap:Body></soap:Envelope>"#
+        ))
+        .await;
+
+        let resp = cli
+            .post("/")
+            .body(request("<Add><a>2147483647</a><b>1</b></Add>"))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        let text = resp.0.into_body().into_string().await.unwrap();
+        assert!(text.contains(
+            "<soap:Fault><faultcode>soap:Client</faultcode><faultstring>overflow</faultstring><detail>a &lt;= b</detail></soap:Fault>"
+        ));
+
+        for body in [
+            request("<Sub><a>1</a><b>2</b></Sub>"),
+            request("<Add><a>x</a></Add>"),
+            "not xml".to_string(),
+        ] {
+            let resp = cli.post("/").body(body).send().await;
+            resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+            let text = resp.0.into_body().into_string().await.unwrap();
+            assert!(text.contains("<faultcode>soap:Client</faultcode>"));
+        }
+
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use base64::engine::{general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use sha2::{Digest, Sha384};
+
+/// Computes the [Subresource Integrity](https://www.w3.org/TR/SRI/) hash of
+/// the data with SHA-384, which can be used as the value of the `integrity`
+/// attribute.
+///
+/// # Example
+///
+/// ```
+/// use poem::endpoint::sri_hash;
+///
+/// assert_eq!(
+///     sri_hash(b"alert('Hello, world.');"),
+///     "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO"
+/// );
+/// ```
+pub fn sri_hash(data: impl AsRef<[u8]>) -> String {
+    format!("sha384-{}", STANDARD.encode(Sha384::digest(data.as_ref())))
+}
+
+/// A manifest of the [Subresource Integrity](https://www.w3.org/TR/SRI/)
+/// hashes of the served assets, keyed by the path relative to the root of the
+/// assets, using `/` as the separator.
+///
+/// It can be created with
+/// [`StaticFilesEndpoint::sri_manifest`](crate::endpoint::StaticFilesEndpoint::sri_manifest)
+/// or
+/// [`EmbeddedFilesEndpoint::sri_manifest`](crate::endpoint::EmbeddedFilesEndpoint::sri_manifest),
+/// and passed to templates to emit the `integrity` attributes. It serializes
+/// as a JSON object from paths to hashes.
+///
+/// # Example
+///
+/// ```
+/// use poem::endpoint::SriManifest;
+///
+/// let mut manifest = SriManifest::new();
+/// manifest.insert("js/app.js", b"alert('Hello, world.');");
+///
+/// assert_eq!(
+///     manifest.script_tag("js/app.js", "/static/js/app.js").unwrap(),
+///     r#"<script src="/static/js/app.js" integrity="sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO" crossorigin="anonymous"></script>"#
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(any(feature = "static-files", feature = "embed"))))]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct SriManifest {
+    hashes: BTreeMap<String, String>,
+}
+
+impl SriManifest {
+    /// Create an empty `SriManifest`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Computes the hash of the data and inserts it for the specified path.
+    pub fn insert(&mut self, path: impl Into<String>, data: impl AsRef<[u8]>) {
+        self.hashes
+            .insert(normalize_path(path.into()), sri_hash(data));
+    }
+
+    /// Returns the hash of the specified path, which is the value of the
+    /// `integrity` attribute.
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.hashes
+            .get(path.trim_start_matches('/'))
+            .map(String::as_str)
+    }
+
+    /// Returns an iterator over the paths and hashes.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.hashes
+            .iter()
+            .map(|(path, hash)| (path.as_str(), hash.as_str()))
+    }
+
+    /// Returns the number of assets.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Returns `true` if the manifest contains no assets.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Returns a `<script>` tag for the asset of the specified path, with
+    /// `src` as the URL.
+    pub fn script_tag(&self, path: &str, src: &str) -> Option<String> {
+        let integrity = self.get(path)?;
+        Some(format!(
+            r#"<script src="{}" integrity="{integrity}" crossorigin="anonymous"></script>"#,
+            escape_attr(src)
+        ))
+    }
+
+    /// Returns a `<link rel="stylesheet">` tag for the asset of the specified
+    /// path, with `href` as the URL.
+    pub fn stylesheet_tag(&self, path: &str, href: &str) -> Option<String> {
+        let integrity = self.get(path)?;
+        Some(format!(
+            r#"<link rel="stylesheet" href="{}" integrity="{integrity}" crossorigin="anonymous">"#,
+            escape_attr(href)
+        ))
+    }
+}
+
+fn normalize_path(path: String) -> String {
+    let path = path.replace('\\', "/");
+    path.trim_start_matches('/').to_string()
+}
+
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash() {
+        assert_eq!(
+            sri_hash(b""),
+            "sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb"
+        );
+    }
+
+    #[test]
+    fn manifest() {
+        let mut manifest = SriManifest::new();
+        manifest.insert("/a.css", b"");
+        manifest.insert("js\\b.js", b"");
+        assert_eq!(manifest.len(), 2);
+        assert!(manifest.get("a.css").is_some());
+        assert!(manifest.get("/js/b.js").is_some());
+        assert!(manifest.get("c.js").is_none());
+        assert_eq!(
+            manifest.stylesheet_tag("a.css", "/a.css?v=\"1\"").unwrap(),
+            r#"<link rel="stylesheet" href="/a.css?v=&quot;1&quot;" integrity="sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb" crossorigin="anonymous">"#
+        );
+        assert_eq!(
+            serde_json::to_value(&manifest).unwrap(),
+            serde_json::json!({
+                "a.css": "sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb",
+                "js/b.js": "sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb",
+            })
+        );
+    }
+}
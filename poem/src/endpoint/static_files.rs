@@ -5,11 +5,16 @@ use std::{
 };
 
 use http::header::LOCATION;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC};
 
 use crate::{
+    endpoint::SriManifest,
     error::StaticFileError,
-    http::{header, Method, StatusCode},
-    web::StaticFileRequest,
+    http::{header, HeaderValue, Method, StatusCode},
+    web::{
+        guess_content_type, metadata_etag, negotiate_encoding, vary_accept_encoding,
+        StaticFileRequest,
+    },
     Body, Endpoint, FromRequest, IntoResponse, Request, Response, Result,
 };
 
@@ -77,6 +82,8 @@ pub struct StaticFilesEndpoint {
     fallback_to_index: bool,
     prefer_utf8: bool,
     redirect_to_slash: bool,
+    precompressed: bool,
+    webdav: bool,
 }
 
 impl StaticFilesEndpoint {
@@ -102,6 +109,8 @@ impl StaticFilesEndpoint {
             fallback_to_index: false,
             prefer_utf8: true,
             redirect_to_slash: false,
+            precompressed: false,
+            webdav: false,
         }
     }
 
@@ -151,6 +160,39 @@ impl StaticFilesEndpoint {
         }
     }
 
+    /// Serves the pre-compressed variants of the files if the client accepts
+    /// them, which are the files with the same name and the `.br` or `.gz`
+    /// extension, such as `app.js.br` for `app.js`.
+    ///
+    /// The `Content-Encoding` and `Vary` headers are set accordingly, and the
+    /// content type is guessed from the original file.
+    #[must_use]
+    pub fn precompressed(self) -> Self {
+        Self {
+            precompressed: true,
+            ..self
+        }
+    }
+
+    /// Enables the read-only [WebDAV](https://www.rfc-editor.org/rfc/rfc4918)
+    /// mode, so that the files can be browsed by the WebDAV clients, such as
+    /// the file managers and the office applications.
+    ///
+    /// `OPTIONS` responds with the `DAV: 1` header, and `PROPFIND` responds
+    /// with the properties of the file or the directory, and of its children
+    /// unless the `Depth` header is `0`. Listing the children requires
+    /// [`show_files_listing`](Self::show_files_listing), otherwise it is
+    /// rejected with `403 Forbidden`. The requested properties are ignored
+    /// and all supported properties are returned. Other WebDAV methods are
+    /// rejected with `405 Method Not Allowed`.
+    #[must_use]
+    pub fn webdav(self) -> Self {
+        Self {
+            webdav: true,
+            ..self
+        }
+    }
+
     /// Fall back to the configured index file if any, if the file is not found
     #[must_use]
     pub fn fallback_to_index(self) -> Self {
@@ -159,6 +201,209 @@ impl StaticFilesEndpoint {
             ..self
         }
     }
+
+    /// Computes the Subresource Integrity hashes of all files in the base
+    /// directory.
+    ///
+    /// Symbolic links to directories are not followed, so that a link cycle
+    /// cannot recurse forever.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use poem::endpoint::StaticFilesEndpoint;
+    ///
+    /// let files = StaticFilesEndpoint::new("/etc/www");
+    /// let manifest = files.sri_manifest().unwrap();
+    /// let tag = manifest.script_tag("js/app.js", "/files/js/app.js");
+    /// ```
+    pub fn sri_manifest(&self) -> std::io::Result<SriManifest> {
+        fn walk(manifest: &mut SriManifest, base: &Path, dir: &Path) -> std::io::Result<()> {
+            for entry in std::fs::read_dir(dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    walk(manifest, base, &path)?;
+                } else if file_type.is_symlink() && path.is_dir() {
+                    continue;
+                } else if let Ok(relative) = path.strip_prefix(base) {
+                    let relative = relative
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    manifest.insert(relative, std::fs::read(&path)?);
+                }
+            }
+            Ok(())
+        }
+
+        let mut manifest = SriManifest::new();
+        walk(&mut manifest, &self.path, &self.path)?;
+        Ok(manifest)
+    }
+}
+
+/// The content codings of the pre-compressed files and their extensions, in
+/// the order of preference.
+const PRECOMPRESSED_EXTENSIONS: &[(&str, &str)] = &[("br", "br"), ("gzip", "gz")];
+
+async fn file_response(
+    req: &Request,
+    path: &Path,
+    prefer_utf8: bool,
+    precompressed: bool,
+) -> Result<Response> {
+    let static_req = StaticFileRequest::from_request_without_body(req).await?;
+    if !precompressed {
+        return Ok(static_req
+            .create_response(path, prefer_utf8)?
+            .into_response());
+    }
+
+    let mut variants = PRECOMPRESSED_EXTENSIONS
+        .iter()
+        .filter_map(|(coding, ext)| {
+            let mut encoded_path = path.as_os_str().to_owned();
+            encoded_path.push(".");
+            encoded_path.push(ext);
+            let encoded_path = PathBuf::from(encoded_path);
+            encoded_path.is_file().then_some((*coding, encoded_path))
+        })
+        .collect::<Vec<_>>();
+    let codings = variants
+        .iter()
+        .map(|(coding, _)| *coding)
+        .collect::<Vec<_>>();
+    let variant = negotiate_encoding(req.headers(), &codings).and_then(|coding| {
+        variants
+            .iter()
+            .position(|(c, _)| *c == coding)
+            .map(|idx| variants.swap_remove(idx))
+    });
+
+    let mut resp = match variant {
+        Some((coding, encoded_path)) => {
+            let mut resp = static_req
+                .create_encoded_response(path, &encoded_path, prefer_utf8)?
+                .into_response();
+            if resp.status() != StatusCode::NOT_MODIFIED {
+                resp.headers_mut()
+                    .insert(header::CONTENT_ENCODING, HeaderValue::from_static(coding));
+            }
+            resp
+        }
+        None => static_req
+            .create_response(path, prefer_utf8)?
+            .into_response(),
+    };
+    vary_accept_encoding(resp.headers_mut());
+    Ok(resp)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_dav_response(xml: &mut String, href: &str, path: &Path, prefer_utf8: bool) -> Result<()> {
+    let metadata = path.metadata().map_err(StaticFileError::Io)?;
+    let displayname = path.file_name().and_then(OsStr::to_str).unwrap_or_default();
+
+    let _ = write!(
+        xml,
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop><D:displayname>{}</D:displayname>",
+        xml_escape(href),
+        xml_escape(displayname)
+    );
+    if metadata.is_dir() {
+        xml.push_str("<D:resourcetype><D:collection/></D:resourcetype>");
+    } else {
+        let _ = write!(
+            xml,
+            "<D:resourcetype/><D:getcontentlength>{}</D:getcontentlength>",
+            metadata.len()
+        );
+        if let Some(content_type) = guess_content_type(path, prefer_utf8) {
+            let _ = write!(
+                xml,
+                "<D:getcontenttype>{}</D:getcontenttype>",
+                xml_escape(&content_type)
+            );
+        }
+        if let Some(etag) = metadata_etag(&metadata) {
+            let _ = write!(xml, "<D:getetag>{}</D:getetag>", xml_escape(&etag));
+        }
+    }
+    if let Ok(modified) = metadata.modified() {
+        let _ = write!(
+            xml,
+            "<D:getlastmodified>{}</D:getlastmodified>",
+            httpdate::fmt_http_date(modified)
+        );
+    }
+    xml.push_str("</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>");
+    Ok(())
+}
+
+/// The characters to percent-encode in the `href` of the WebDAV responses,
+/// which are all but the unreserved characters.
+const DAV_HREF: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+fn propfind_response(
+    req: &Request,
+    file_path: &Path,
+    prefer_utf8: bool,
+    show_files_listing: bool,
+) -> Result<Response> {
+    let depth = req
+        .headers()
+        .get("depth")
+        .and_then(|value| value.to_str().ok());
+    let list_children = file_path.is_dir() && depth != Some("0");
+    if list_children && !show_files_listing {
+        return Err(StaticFileError::Forbidden(file_path.display().to_string()).into());
+    }
+
+    let href = req.original_uri().path();
+    let mut xml =
+        String::from(r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#);
+    write_dav_response(&mut xml, href, file_path, prefer_utf8)?;
+
+    if list_children {
+        let mut base_url = href.to_string();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+
+        for res in file_path.read_dir().map_err(StaticFileError::Io)? {
+            let entry = res.map_err(StaticFileError::Io)?;
+            if let Some(filename) = entry.file_name().to_str() {
+                let mut url = format!(
+                    "{base_url}{}",
+                    percent_encoding::percent_encode(filename.as_bytes(), DAV_HREF)
+                );
+                let path = entry.path();
+                if path.is_dir() {
+                    url.push('/');
+                }
+                write_dav_response(&mut xml, &url, &path, prefer_utf8)?;
+            }
+        }
+    }
+
+    xml.push_str("</D:multistatus>");
+    Ok(Response::builder()
+        .status(StatusCode::MULTI_STATUS)
+        .content_type("application/xml; charset=utf-8")
+        .body(xml))
 }
 
 #[async_trait::async_trait]
@@ -166,7 +411,13 @@ impl Endpoint for StaticFilesEndpoint {
     type Output = Response;
 
     async fn call(&self, req: Request) -> Result<Self::Output> {
-        if req.method() != Method::GET {
+        let is_propfind = self.webdav && req.method().as_str() == "PROPFIND";
+        if self.webdav && req.method() == Method::OPTIONS {
+            return Ok(Response::builder()
+                .header("dav", "1")
+                .header(header::ALLOW, "OPTIONS, GET, PROPFIND")
+                .finish());
+        } else if req.method() != Method::GET && !is_propfind {
             return Err(StaticFileError::MethodNotAllowed(req.method().clone()).into());
         }
 
@@ -195,15 +446,25 @@ impl Endpoint for StaticFilesEndpoint {
             return Err(StaticFileError::Forbidden(file_path.display().to_string()).into());
         }
 
+        if is_propfind {
+            if !file_path.exists() {
+                return Err(StaticFileError::NotFound.into());
+            }
+            return propfind_response(&req, &file_path, self.prefer_utf8, self.show_files_listing);
+        }
+
         if !file_path.exists() {
             if self.fallback_to_index {
                 if let Some(index_file) = &self.index_file {
                     let index_path = self.path.join(index_file);
                     if index_path.is_file() {
-                        return Ok(StaticFileRequest::from_request_without_body(&req)
-                            .await?
-                            .create_response(&index_path, self.prefer_utf8)?
-                            .into_response());
+                        return file_response(
+                            &req,
+                            &index_path,
+                            self.prefer_utf8,
+                            self.precompressed,
+                        )
+                        .await;
                     }
                 }
             }
@@ -211,10 +472,7 @@ impl Endpoint for StaticFilesEndpoint {
         }
 
         if file_path.is_file() {
-            return Ok(StaticFileRequest::from_request_without_body(&req)
-                .await?
-                .create_response(&file_path, self.prefer_utf8)?
-                .into_response());
+            return file_response(&req, &file_path, self.prefer_utf8, self.precompressed).await;
         } else {
             if self.redirect_to_slash
                 && !req.original_uri().path().ends_with('/')
@@ -230,10 +488,8 @@ impl Endpoint for StaticFilesEndpoint {
             if let Some(index_file) = &self.index_file {
                 let index_path = file_path.join(index_file);
                 if index_path.is_file() {
-                    return Ok(StaticFileRequest::from_request_without_body(&req)
-                        .await?
-                        .create_response(&index_path, self.prefer_utf8)?
-                        .into_response());
+                    return file_response(&req, &index_path, self.prefer_utf8, self.precompressed)
+                        .await;
                 }
             }
 
@@ -284,6 +540,7 @@ impl Endpoint for StaticFilesEndpoint {
 pub struct StaticFileEndpoint {
     path: PathBuf,
     prefer_utf8: bool,
+    precompressed: bool,
 }
 
 impl StaticFileEndpoint {
@@ -300,6 +557,7 @@ impl StaticFileEndpoint {
         Self {
             path: path.into(),
             prefer_utf8: true,
+            precompressed: false,
         }
     }
 
@@ -313,6 +571,20 @@ impl StaticFileEndpoint {
             ..self
         }
     }
+
+    /// Serves the pre-compressed variants of the file if the client accepts
+    /// them, which are the files with the same name and the `.br` or `.gz`
+    /// extension, such as `app.js.br` for `app.js`.
+    ///
+    /// The `Content-Encoding` and `Vary` headers are set accordingly, and the
+    /// content type is guessed from the original file.
+    #[must_use]
+    pub fn precompressed(self) -> Self {
+        Self {
+            precompressed: true,
+            ..self
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -320,9 +592,166 @@ impl Endpoint for StaticFileEndpoint {
     type Output = Response;
 
     async fn call(&self, req: Request) -> Result<Self::Output> {
-        Ok(StaticFileRequest::from_request_without_body(&req)
-            .await?
-            .create_response(&self.path, self.prefer_utf8)?
-            .into_response())
+        file_response(&req, &self.path, self.prefer_utf8, self.precompressed).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestClient;
+
+    #[tokio::test]
+    async fn precompressed() {
+        let dir = std::env::temp_dir().join(format!("poem-precompressed-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("app.js"), "plain").unwrap();
+        std::fs::write(dir.join("app.js.gz"), "gzip").unwrap();
+
+        let cli = TestClient::new(StaticFilesEndpoint::new(&dir).precompressed());
+
+        let resp = cli
+            .get("/app.js")
+            .header(header::ACCEPT_ENCODING, "gzip, br")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header(header::CONTENT_ENCODING, "gzip");
+        resp.assert_header(header::VARY, "accept-encoding");
+        assert!(resp.0.content_type().unwrap().contains("javascript"));
+        resp.assert_text("gzip").await;
+
+        let resp = cli
+            .get("/app.js")
+            .header(header::ACCEPT_ENCODING, "br")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header_is_not_exist(header::CONTENT_ENCODING);
+        resp.assert_header(header::VARY, "accept-encoding");
+        resp.assert_text("plain").await;
+
+        let resp = TestClient::new(StaticFilesEndpoint::new(&dir))
+            .get("/app.js")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .send()
+            .await;
+        resp.assert_header_is_not_exist(header::CONTENT_ENCODING);
+        resp.assert_text("plain").await;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn webdav() {
+        let dir = std::env::temp_dir().join(format!("poem-webdav-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub dir")).unwrap();
+        std::fs::write(dir.join("a.txt"), "hello").unwrap();
+
+        let propfind = Method::from_bytes(b"PROPFIND").unwrap();
+        let cli = TestClient::new(StaticFilesEndpoint::new(&dir).show_files_listing().webdav());
+
+        let resp = cli.request(Method::OPTIONS, "/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header("dav", "1");
+        resp.assert_header(header::ALLOW, "OPTIONS, GET, PROPFIND");
+
+        let resp = cli
+            .request(propfind.clone(), "/a.txt")
+            .header("depth", "0")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::MULTI_STATUS);
+        resp.assert_content_type("application/xml; charset=utf-8");
+        let xml = resp.0.into_body().into_string().await.unwrap();
+        assert!(xml.contains("<D:href>/a.txt</D:href>"));
+        assert!(xml.contains("<D:displayname>a.txt</D:displayname>"));
+        assert!(xml.contains("<D:resourcetype/><D:getcontentlength>5</D:getcontentlength>"));
+        assert!(xml.contains("<D:getcontenttype>text/plain; charset=utf-8</D:getcontenttype>"));
+        assert!(xml.contains("<D:getlastmodified>"));
+
+        let resp = cli.request(propfind.clone(), "/").send().await;
+        resp.assert_status(StatusCode::MULTI_STATUS);
+        let xml = resp.0.into_body().into_string().await.unwrap();
+        assert_eq!(xml.matches("<D:response>").count(), 3);
+        assert!(xml.contains("<D:href>/a.txt</D:href>"));
+        assert!(xml.contains(
+            "<D:href>/sub%20dir/</D:href><D:propstat><D:prop><D:displayname>sub dir</D:displayname><D:resourcetype><D:collection/></D:resourcetype>"
+        ));
+
+        let resp = cli
+            .request(propfind.clone(), "/")
+            .header("depth", "0")
+            .send()
+            .await;
+        let xml = resp.0.into_body().into_string().await.unwrap();
+        assert_eq!(xml.matches("<D:response>").count(), 1);
+
+        cli.request(propfind.clone(), "/missing")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        cli.request(Method::from_bytes(b"MKCOL").unwrap(), "/new")
+            .send()
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        cli.get("/a.txt").send().await.assert_text("hello").await;
+
+        TestClient::new(StaticFilesEndpoint::new(&dir))
+            .request(propfind, "/")
+            .send()
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn webdav_without_files_listing() {
+        let dir = std::env::temp_dir().join(format!("poem-webdav-listing-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.txt"), "hello").unwrap();
+
+        let propfind = Method::from_bytes(b"PROPFIND").unwrap();
+        let cli = TestClient::new(StaticFilesEndpoint::new(&dir).webdav());
+
+        cli.request(propfind.clone(), "/")
+            .header("depth", "1")
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        cli.request(propfind.clone(), "/")
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        cli.request(propfind.clone(), "/")
+            .header("depth", "0")
+            .send()
+            .await
+            .assert_status(StatusCode::MULTI_STATUS);
+        cli.request(propfind, "/a.txt")
+            .header("depth", "1")
+            .send()
+            .await
+            .assert_status(StatusCode::MULTI_STATUS);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sri_manifest_skips_symlinked_dirs() {
+        let dir = std::env::temp_dir().join(format!("poem-sri-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("js")).unwrap();
+        std::fs::write(dir.join("js/app.js"), "app").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("js/loop")).unwrap();
+        std::os::unix::fs::symlink(dir.join("js/app.js"), dir.join("main.js")).unwrap();
+
+        let manifest = StaticFilesEndpoint::new(&dir).sri_manifest().unwrap();
+        assert_eq!(manifest.len(), 2);
+        assert!(manifest.get("js/app.js").is_some());
+        assert_eq!(manifest.get("main.js"), manifest.get("js/app.js"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -1,10 +1,17 @@
-use std::{error::Error as StdError, future::Future};
+use std::{
+    convert::Infallible,
+    error::Error as StdError,
+    future::Future,
+    sync::Arc,
+    task::{Context, Poll},
+};
 
 use bytes::Bytes;
+use futures_util::{future::BoxFuture, FutureExt};
 use http_body_util::BodyExt;
 use tower::{Service, ServiceExt};
 
-use crate::{body::BoxBody, Endpoint, Error, Request, Response, Result};
+use crate::{body::BoxBody, Endpoint, Error, IntoResponse, Request, Response, Result};
 
 /// Extension trait for tower service compat.
 #[cfg_attr(docsrs, doc(cfg(feature = "tower-compat")))]
@@ -73,14 +80,111 @@ where
     }
 }
 
+/// An adapter that converts a poem endpoint to a `tower::Service` and a
+/// `hyper::service::Service`, which can be used to mount the poem routes in
+/// existing hyper or tonic servers.
+///
+/// The errors returned by the endpoint are converted to responses, so the
+/// service never fails.
+///
+/// # Example
+///
+/// ```
+/// use poem::{endpoint::EndpointService, handler, http, Route};
+/// use tower::ServiceExt;
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let svc = EndpointService::new(Route::new().at("/", index));
+/// let resp = svc
+///     .oneshot(http::Request::new(String::new()))
+///     .await
+///     .unwrap();
+/// assert_eq!(resp.status(), http::StatusCode::OK);
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "tower-compat")))]
+pub struct EndpointService<E>(Arc<E>);
+
+impl<E> Clone for EndpointService<E> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<E: Endpoint + 'static> EndpointService<E> {
+    /// Create an `EndpointService` from the endpoint.
+    pub fn new(ep: E) -> Self {
+        Self(Arc::new(ep))
+    }
+
+    fn serve<B>(
+        &self,
+        req: http::Request<B>,
+    ) -> BoxFuture<'static, Result<http::Response<BoxBody>, Infallible>>
+    where
+        B: hyper::body::Body + Send + Sync + 'static,
+        B::Data: Into<Bytes>,
+        B::Error: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        let ep = self.0.clone();
+        let req = Request::from(req);
+        async move {
+            let lifecycle = req.lifecycle().clone();
+            let resp = match ep.call(req).await {
+                Ok(resp) => resp.into_response(),
+                Err(err) => err.into_response(),
+            };
+            Ok(lifecycle.finish(resp))
+        }
+        .boxed()
+    }
+}
+
+impl<E, B> Service<http::Request<B>> for EndpointService<E>
+where
+    E: Endpoint + 'static,
+    B: hyper::body::Body + Send + Sync + 'static,
+    B::Data: Into<Bytes>,
+    B::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        self.serve(req)
+    }
+}
+
+impl<E, B> hyper::service::Service<http::Request<B>> for EndpointService<E>
+where
+    E: Endpoint + 'static,
+    B: hyper::body::Body + Send + Sync + 'static,
+    B::Data: Into<Bytes>,
+    B::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn call(&self, req: http::Request<B>) -> Self::Future {
+        self.serve(req)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{
-        convert::Infallible,
-        task::{Context, Poll},
-    };
-
     use futures_util::future::Ready;
+    use http::StatusCode;
 
     use super::*;
     use crate::test::TestClient;
@@ -109,4 +213,44 @@ mod tests {
         resp.assert_status_is_ok();
         resp.assert_text("abc").await;
     }
+
+    #[tokio::test]
+    async fn test_endpoint_service() {
+        use crate::{endpoint::make_sync, handler, web::Path, Route};
+
+        #[handler(internal)]
+        fn hello(Path(name): Path<String>, body: String) -> String {
+            format!("hello {name} {body}")
+        }
+
+        let svc = EndpointService::new(Route::new().at("/hello/:name", hello).at(
+            "/error",
+            make_sync(|_| Err::<(), _>(Error::from_status(StatusCode::CONFLICT))),
+        ));
+
+        let resp = svc
+            .clone()
+            .oneshot(
+                http::Request::builder()
+                    .uri("/hello/poem")
+                    .body("abc".to_string())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "hello poem abc");
+
+        let resp = hyper::service::Service::call(
+            &svc,
+            http::Request::builder()
+                .uri("/error")
+                .body(String::new())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
 }
@@ -0,0 +1,373 @@
+use std::{borrow::Cow, path::Path, sync::RwLock};
+
+use serde::{Deserialize, Serialize};
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+
+use crate::{
+    endpoint::ForeignRequest,
+    error::WasmError,
+    http::{HeaderName, HeaderValue, StatusCode},
+    Endpoint, Request, Response, Result,
+};
+
+#[derive(Serialize)]
+struct RequestHead<'a> {
+    method: &'a str,
+    uri: &'a str,
+    headers: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+#[derive(Deserialize)]
+struct ResponseHead {
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+}
+
+/// The default amount of fuel for each request.
+const DEFAULT_FUEL: u64 = 1_000_000_000;
+
+/// The default maximum size of the memory of the module.
+const DEFAULT_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+struct State {
+    limits: StoreLimits,
+}
+
+fn call_error(err: wasmtime::Error) -> WasmError {
+    match err.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => WasmError::FuelExhausted,
+        _ => WasmError::Handler(err.to_string()),
+    }
+}
+
+/// An endpoint that runs a handler compiled to a WebAssembly module, which is
+/// used to run the plugins provided by the users in a sandbox.
+///
+/// The module is instantiated for each request, without any imports, so it
+/// can only access its own memory. The instructions it executes and the size
+/// of its memory are limited, see [`WasmEndpoint::fuel`] and
+/// [`WasmEndpoint::memory_limit`], and it can be replaced without restarting
+/// the server with [`WasmEndpoint::reload`].
+///
+/// The handler interface is modeled after `wasi:http/incoming-handler`, but it
+/// is not an implementation of wasi-http: the handler is a core module with
+/// the exports below instead of a component, WASI is not provided, and the
+/// request and the response bodies are passed as a whole instead of as
+/// streams.
+///
+/// # Handler interface
+///
+/// The module must export:
+///
+/// - `memory`: The memory used to exchange the request and the response.
+/// - `poem_alloc(size: i32) -> i32`: Allocates `size` bytes and returns the
+///   pointer.
+/// - `poem_handle(head_ptr: i32, head_len: i32, body_ptr: i32, body_len: i32)
+///   -> i64`: Handles a request, the head is a JSON object such as
+///   `{"method":"GET","uri":"/?a=1","headers":[["host","localhost"]]}`. Returns
+///   the pointer of the response in the high 32 bits and the length in the low
+///   32 bits.
+///
+/// The response starts with the length of the head as a little-endian `u32`,
+/// followed by the head, which is a JSON object such as
+/// `{"status":200,"headers":[["content-type","text/plain"]]}`, and the body.
+///
+/// # Errors
+///
+/// - [`WasmError`]
+///
+/// # Example
+///
+/// ```no_run
+/// use poem::{endpoint::WasmEndpoint, Route};
+///
+/// let plugin = WasmEndpoint::from_file("plugins/hello.wasm")
+///     .unwrap()
+///     .fuel(10_000_000)
+///     .memory_limit(16 * 1024 * 1024);
+/// let app = Route::new().nest("/plugins/hello", plugin);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+pub struct WasmEndpoint {
+    engine: Engine,
+    module: RwLock<Module>,
+    fuel: u64,
+    memory_limit: usize,
+}
+
+impl WasmEndpoint {
+    /// Create a `WasmEndpoint` from the binary or text format of a module.
+    pub fn new(wasm: impl AsRef<[u8]>) -> Result<Self, WasmError> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|err| WasmError::Load(err.to_string()))?;
+        let module = Module::new(&engine, wasm).map_err(|err| WasmError::Load(err.to_string()))?;
+        Ok(Self {
+            engine,
+            module: RwLock::new(module),
+            fuel: DEFAULT_FUEL,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        })
+    }
+
+    /// Create a `WasmEndpoint` from a module file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, WasmError> {
+        Self::new(std::fs::read(path).map_err(|err| WasmError::Load(err.to_string()))?)
+    }
+
+    /// Sets the amount of fuel for each request, which is consumed by the
+    /// executed instructions.
+    ///
+    /// Default is `1000000000`.
+    #[must_use]
+    pub fn fuel(self, fuel: u64) -> Self {
+        Self { fuel, ..self }
+    }
+
+    /// Sets the maximum size of the memory of the module in bytes.
+    ///
+    /// Default is 64 MiB.
+    #[must_use]
+    pub fn memory_limit(self, bytes: usize) -> Self {
+        Self {
+            memory_limit: bytes,
+            ..self
+        }
+    }
+
+    /// Replaces the module, the requests that are being handled are not
+    /// affected.
+    pub fn reload(&self, wasm: impl AsRef<[u8]>) -> Result<(), WasmError> {
+        let module =
+            Module::new(&self.engine, wasm).map_err(|err| WasmError::Load(err.to_string()))?;
+        *self.module.write().unwrap() = module;
+        Ok(())
+    }
+
+    fn run(
+        engine: &Engine,
+        module: &Module,
+        fuel: u64,
+        memory_limit: usize,
+        req: ForeignRequest,
+    ) -> Result<(ResponseHead, Vec<u8>), WasmError> {
+        let mut store = Store::new(
+            engine,
+            State {
+                limits: StoreLimitsBuilder::new().memory_size(memory_limit).build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(fuel).map_err(call_error)?;
+
+        let instance = Linker::new(engine)
+            .instantiate(&mut store, module)
+            .map_err(call_error)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| WasmError::Handler("missing `memory` export".to_string()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "poem_alloc")
+            .map_err(call_error)?;
+        let handle = instance
+            .get_typed_func::<(i32, i32, i32, i32), i64>(&mut store, "poem_handle")
+            .map_err(call_error)?;
+
+        let head = serde_json::to_vec(&RequestHead {
+            method: &req.method,
+            uri: &req.uri,
+            headers: req
+                .headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), String::from_utf8_lossy(value)))
+                .collect(),
+        })
+        .map_err(|err| WasmError::Handler(err.to_string()))?;
+
+        let mut args = Vec::new();
+        for data in [&head, &req.body] {
+            let ptr = alloc
+                .call(&mut store, data.len() as i32)
+                .map_err(call_error)?;
+            memory
+                .write(&mut store, ptr as u32 as usize, data)
+                .map_err(|err| WasmError::Handler(err.to_string()))?;
+            args.push(ptr);
+            args.push(data.len() as i32);
+        }
+        let res = handle
+            .call(&mut store, (args[0], args[1], args[2], args[3]))
+            .map_err(call_error)?;
+
+        let ptr = (res as u64 >> 32) as usize;
+        let len = (res as u64 & 0xffff_ffff) as usize;
+        let data = memory
+            .data(&store)
+            .get(ptr..ptr + len)
+            .ok_or_else(|| WasmError::Handler("response out of bounds".to_string()))?;
+        let head_len = data
+            .get(..4)
+            .map(|len| u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize)
+            .ok_or_else(|| WasmError::Handler("invalid response".to_string()))?;
+        let head = data
+            .get(4..4 + head_len)
+            .ok_or_else(|| WasmError::Handler("invalid response".to_string()))?;
+        let head = serde_json::from_slice::<ResponseHead>(head)
+            .map_err(|err| WasmError::Handler(err.to_string()))?;
+        Ok((head, data[4 + head_len..].to_vec()))
+    }
+}
+
+#[async_trait::async_trait]
+impl Endpoint for WasmEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let req = ForeignRequest::from_request(req).await?;
+        let engine = self.engine.clone();
+        let module = self.module.read().unwrap().clone();
+        let (fuel, memory_limit) = (self.fuel, self.memory_limit);
+
+        let (head, body) = tokio::task::spawn_blocking(move || {
+            Self::run(&engine, &module, fuel, memory_limit, req)
+        })
+        .await
+        .map_err(|err| WasmError::Handler(err.to_string()))??;
+
+        let mut resp = Response::builder().body(body);
+        resp.set_status(
+            StatusCode::from_u16(head.status)
+                .map_err(|_| WasmError::Handler("invalid status code".to_string()))?,
+        );
+        for (name, value) in head.headers {
+            let name = HeaderName::try_from(name)
+                .map_err(|_| WasmError::Handler("invalid header name".to_string()))?;
+            let value = HeaderValue::try_from(value)
+                .map_err(|_| WasmError::Handler("invalid header value".to_string()))?;
+            resp.headers_mut().append(name, value);
+        }
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestClient;
+
+    /// A module that responds with the head and echoes the body.
+    fn echo_module(head: &str) -> String {
+        let mut data = (head.len() as u32)
+            .to_le_bytes()
+            .iter()
+            .map(|b| format!("\\{b:02x}"))
+            .collect::<String>();
+        data.push_str(&head.replace('"', "\\\""));
+        let total = head.len() + 4;
+
+        format!(
+            r#"(module
+                (memory (export "memory") 2)
+                (global $next (mut i32) (i32.const 1024))
+                (data (i32.const 0) "{data}")
+                (func (export "poem_alloc") (param $size i32) (result i32)
+                    (local $ptr i32)
+                    (local.set $ptr (global.get $next))
+                    (global.set $next (i32.add (global.get $next) (local.get $size)))
+                    (local.get $ptr))
+                (func (export "poem_handle")
+                    (param $hp i32) (param $hl i32) (param $bp i32) (param $bl i32) (result i64)
+                    (memory.copy (i32.const 65536) (i32.const 0) (i32.const {total}))
+                    (memory.copy (i32.const {body}) (local.get $bp) (local.get $bl))
+                    (i64.or
+                        (i64.shl (i64.const 65536) (i64.const 32))
+                        (i64.extend_i32_u (i32.add (i32.const {total}) (local.get $bl))))))"#,
+            body = 65536 + total,
+        )
+    }
+
+    #[tokio::test]
+    async fn handle() {
+        let ep = WasmEndpoint::new(echo_module(
+            r#"{"status":201,"headers":[["x-plugin","echo"]]}"#,
+        ))
+        .unwrap();
+        let cli = TestClient::new(ep);
+
+        let resp = cli.post("/").body("hello").send().await;
+        resp.assert_status(StatusCode::CREATED);
+        resp.assert_header("x-plugin", "echo");
+        resp.assert_text("hello").await;
+    }
+
+    #[tokio::test]
+    async fn reload() {
+        let ep = WasmEndpoint::new(echo_module(r#"{"status":200}"#)).unwrap();
+        ep.reload(echo_module(r#"{"status":202}"#)).unwrap();
+        TestClient::new(ep)
+            .get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::ACCEPTED);
+
+        assert!(matches!(
+            WasmEndpoint::new("(module").map(|_| ()),
+            Err(WasmError::Load(_))
+        ));
+    }
+
+    const LOOP_MODULE: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "poem_alloc") (param i32) (result i32) (i32.const 0))
+        (func (export "poem_handle")
+            (param i32 i32 i32 i32) (result i64)
+            (loop $l (br $l))
+            (unreachable)))"#;
+
+    #[tokio::test]
+    async fn limits() {
+        let ep = WasmEndpoint::new(LOOP_MODULE).unwrap().fuel(10_000);
+        TestClient::new(ep)
+            .get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+        let ep = WasmEndpoint::new(echo_module(r#"{"status":200}"#))
+            .unwrap()
+            .memory_limit(65536);
+        TestClient::new(ep)
+            .get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn default_limits() {
+        let ep = WasmEndpoint::new(LOOP_MODULE).unwrap();
+        TestClient::new(ep)
+            .get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+        let ep = WasmEndpoint::new(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "poem_alloc") (param i32) (result i32) (i32.const 0))
+                (func (export "poem_handle")
+                    (param i32 i32 i32 i32) (result i64)
+                    (if (i32.eq (memory.grow (i32.const 1024)) (i32.const -1))
+                        (then (unreachable)))
+                    (i64.const 0)))"#,
+        )
+        .unwrap();
+        TestClient::new(ep)
+            .get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
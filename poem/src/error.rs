@@ -1,6 +1,7 @@
 //! Some common error types.
 
 use std::{
+    collections::BTreeMap,
     convert::Infallible,
     error::Error as StdError,
     fmt::{self, Debug, Display, Formatter},
@@ -12,6 +13,8 @@ use http::{Extensions, Method};
 
 use crate::{http::StatusCode, IntoResponse, Response};
 
+pub use poem_derive::ApiError;
+
 macro_rules! define_http_error {
     ($($(#[$docs:meta])* ($name:ident, $status:ident);)*) => {
         $(
@@ -641,8 +644,66 @@ define_simple_errors!(
 
     /// Error occurred in the router.
     (MethodNotAllowedError, METHOD_NOT_ALLOWED, "method not allowed");
+
+    /// None of the media types in the `Accept` header are supported by
+    /// [`Negotiate`](crate::web::Negotiate).
+    (NotAcceptableError, NOT_ACCEPTABLE, "not acceptable");
 );
 
+/// A possible error value when parsing a path parameter.
+///
+/// The default response is `400 Bad Request` with the error message, use
+/// [`EndpointExt::catch_error`](crate::EndpointExt::catch_error) to render it
+/// differently.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     error::PathError, handler, http::StatusCode, test::TestClient, web::Path, EndpointExt,
+///     IntoResponse, Route,
+/// };
+///
+/// #[handler]
+/// fn index(Path(id): Path<i32>) -> String {
+///     id.to_string()
+/// }
+///
+/// let app = Route::new()
+///     .at("/:id", index)
+///     .catch_error(|err: PathError| async move {
+///         format!("bad `{}`: {}", err.name, err.value).with_status(StatusCode::BAD_REQUEST)
+///     });
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/abc").send().await;
+/// resp.assert_status(StatusCode::BAD_REQUEST);
+/// resp.assert_text("bad `id`: abc").await;
+/// # });
+/// ```
+#[derive(Debug, thiserror::Error, Clone, Eq, PartialEq)]
+#[error("invalid path parameter `{name}`: {reason}")]
+pub struct PathError {
+    /// The name of the parameter.
+    pub name: String,
+
+    /// The value of the parameter.
+    pub value: String,
+
+    /// The expected type of the parameter, if known.
+    pub expected_type: Option<String>,
+
+    /// The reason for the error.
+    pub reason: String,
+}
+
+impl ResponseError for PathError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
 /// A possible error value when reading the body.
 #[derive(Debug, thiserror::Error)]
 pub enum ReadBodyError {
@@ -668,6 +729,9 @@ impl ResponseError for ReadBodyError {
         match self {
             ReadBodyError::BodyHasBeenTaken => StatusCode::INTERNAL_SERVER_ERROR,
             ReadBodyError::Utf8(_) => StatusCode::BAD_REQUEST,
+            ReadBodyError::Io(err) if crate::body::payload_too_large(err) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
             ReadBodyError::Io(_) => StatusCode::BAD_REQUEST,
             ReadBodyError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
         }
@@ -853,6 +917,32 @@ pub enum ParseMultipartError {
     /// Io error
     #[error("io: {0}")]
     Io(#[from] std::io::Error),
+
+    /// A specified field is required.
+    #[error("field `{0}` is required")]
+    FieldRequired(String),
+
+    /// A field that can only appear once was repeated.
+    #[error("field `{0}` is repeated")]
+    DuplicateField(String),
+
+    /// The field data exceeds the configured limit.
+    #[error("field `{name}` exceeds the limit of {limit} bytes")]
+    FieldTooLarge {
+        /// Field name
+        name: String,
+        /// The configured limit
+        limit: usize,
+    },
+
+    /// Failed to parse the field value.
+    #[error("failed to parse field `{name}`: {reason}")]
+    ParseField {
+        /// Field name
+        name: String,
+        /// The reason for the error
+        reason: String,
+    },
 }
 
 #[cfg(feature = "multipart")]
@@ -861,9 +951,66 @@ impl ResponseError for ParseMultipartError {
         match self {
             ParseMultipartError::InvalidContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             ParseMultipartError::ContentTypeRequired => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ParseMultipartError::Multipart(multer::Error::StreamReadFailed(err))
+                if err
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(crate::body::payload_too_large) =>
+            {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
             ParseMultipartError::Multipart(_) => StatusCode::BAD_REQUEST,
             ParseMultipartError::Utf8(_) => StatusCode::BAD_REQUEST,
+            ParseMultipartError::Io(err) if crate::body::payload_too_large(err) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
             ParseMultipartError::Io(_) => StatusCode::BAD_REQUEST,
+            ParseMultipartError::FieldRequired(_) => StatusCode::BAD_REQUEST,
+            ParseMultipartError::DuplicateField(_) => StatusCode::BAD_REQUEST,
+            ParseMultipartError::FieldTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            ParseMultipartError::ParseField { .. } => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// A possible error value occurred in the
+/// [`BatchEndpoint`](crate::endpoint::BatchEndpoint).
+#[cfg(feature = "multipart")]
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+#[derive(Debug, thiserror::Error)]
+pub enum BatchError {
+    /// Invalid content type.
+    #[error("invalid content type `{0}`, expect: `multipart/mixed`")]
+    InvalidContentType(String),
+
+    /// `Content-Type` header is required.
+    #[error("expect content type `multipart/mixed`")]
+    ContentTypeRequired,
+
+    /// Parse error.
+    #[error("parse: {0}")]
+    Multipart(#[from] multer::Error),
+
+    /// Invalid sub-request.
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// Too many sub-requests.
+    #[error("too many requests in the batch, the limit is {limit}")]
+    TooManyRequests {
+        /// The maximum number of sub-requests
+        limit: usize,
+    },
+}
+
+#[cfg(feature = "multipart")]
+impl ResponseError for BatchError {
+    fn status(&self) -> StatusCode {
+        match self {
+            BatchError::InvalidContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            BatchError::ContentTypeRequired => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            BatchError::Multipart(_) => StatusCode::BAD_REQUEST,
+            BatchError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            BatchError::TooManyRequests { .. } => StatusCode::PAYLOAD_TOO_LARGE,
         }
     }
 }
@@ -886,6 +1033,165 @@ impl ResponseError for ParseTypedHeaderError {
     }
 }
 
+/// A possible error value when the client certificate is required but not
+/// presented.
+#[cfg(feature = "rustls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+#[error("client certificate is required")]
+pub struct ClientCertRequiredError;
+
+#[cfg(feature = "rustls")]
+impl ResponseError for ClientCertRequiredError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+}
+
+/// A possible error value when verifying the HTTP Message Signatures of the
+/// request.
+#[cfg(feature = "signing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+#[derive(Debug, thiserror::Error, Clone, Eq, PartialEq)]
+pub enum SignatureVerificationError {
+    /// The request is not signed.
+    #[error("missing signature")]
+    MissingSignature,
+
+    /// The `Signature-Input` or `Signature` header is invalid.
+    #[error("invalid `{0}` header")]
+    InvalidHeader(String),
+
+    /// The signature does not cover a required component.
+    #[error("signature does not cover the required component `{0}`")]
+    MissingRequiredComponent(String),
+
+    /// A covered component is not present in the request, or is not
+    /// supported.
+    #[error("missing component `{0}`")]
+    MissingComponent(String),
+
+    /// The key ID is missing or unknown.
+    #[error("unknown key")]
+    UnknownKey,
+
+    /// The signature has expired.
+    #[error("signature has expired")]
+    Expired,
+
+    /// The signature is invalid.
+    #[error("invalid signature")]
+    InvalidSignature,
+}
+
+#[cfg(feature = "signing")]
+impl ResponseError for SignatureVerificationError {
+    fn status(&self) -> StatusCode {
+        match self {
+            SignatureVerificationError::InvalidHeader(_)
+            | SignatureVerificationError::MissingComponent(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+/// A possible error value when extracting the SPIFFE identity of the client.
+#[cfg(feature = "rustls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+#[derive(Debug, thiserror::Error, Clone, Eq, PartialEq)]
+pub enum PeerIdentityError {
+    /// The client certificate does not contain a SPIFFE ID.
+    #[error("missing spiffe id")]
+    MissingSpiffeId,
+
+    /// The client certificate contains more than one URI subject alternative
+    /// name.
+    #[error("multiple uri subject alternative names")]
+    MultipleUris,
+
+    /// The SPIFFE ID is invalid.
+    #[error("invalid spiffe id: {0}")]
+    InvalidSpiffeId(String),
+
+    /// The trust domain is not allowed.
+    #[error("trust domain not allowed: {0}")]
+    TrustDomainNotAllowed(String),
+}
+
+#[cfg(feature = "rustls")]
+impl ResponseError for PeerIdentityError {
+    fn status(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+}
+
+/// A possible error value when validating the bearer token with the
+/// [`TokenIntrospection`](crate::middleware::TokenIntrospection) middleware.
+#[cfg(feature = "introspection")]
+#[cfg_attr(docsrs, doc(cfg(feature = "introspection")))]
+#[derive(Debug, thiserror::Error, Clone, Eq, PartialEq)]
+pub enum IntrospectionError {
+    /// The request does not contain a bearer token.
+    #[error("missing bearer token")]
+    MissingToken,
+
+    /// The token is not active.
+    #[error("invalid token")]
+    InvalidToken,
+
+    /// The token does not have the required scope.
+    #[error("insufficient scope: {0}")]
+    InsufficientScope(String),
+
+    /// Failed to call the introspection endpoint.
+    #[error("introspection request failed: {0}")]
+    Request(String),
+}
+
+#[cfg(feature = "introspection")]
+impl ResponseError for IntrospectionError {
+    fn status(&self) -> StatusCode {
+        match self {
+            IntrospectionError::MissingToken | IntrospectionError::InvalidToken => {
+                StatusCode::UNAUTHORIZED
+            }
+            IntrospectionError::InsufficientScope(_) => StatusCode::FORBIDDEN,
+            IntrospectionError::Request(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    fn as_response(&self) -> Response {
+        let challenge = match self {
+            IntrospectionError::MissingToken => Some("Bearer".to_string()),
+            IntrospectionError::InvalidToken => Some("Bearer error=\"invalid_token\"".to_string()),
+            IntrospectionError::InsufficientScope(scope) => Some(format!(
+                "Bearer error=\"insufficient_scope\", scope=\"{scope}\""
+            )),
+            IntrospectionError::Request(_) => None,
+        };
+
+        let mut resp = Response::builder()
+            .status(self.status())
+            .body(self.to_string());
+        if let Some(value) = challenge.and_then(|value| http::HeaderValue::try_from(value).ok()) {
+            resp.headers_mut()
+                .insert(http::header::WWW_AUTHENTICATE, value);
+        }
+        resp
+    }
+}
+
+/// A possible error value when parsing the `Forwarded` header.
+#[derive(Debug, thiserror::Error)]
+#[error("invalid forwarded header: {0}")]
+pub struct ParseForwardedError(pub String);
+
+impl ResponseError for ParseForwardedError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
 /// A possible error value when handling websocket.
 #[cfg(feature = "websocket")]
 #[cfg_attr(docsrs, doc(cfg(feature = "websocket")))]
@@ -895,6 +1201,10 @@ pub enum WebSocketError {
     #[error("invalid protocol")]
     InvalidProtocol,
 
+    /// The `Origin` header is not allowed
+    #[error("origin not allowed")]
+    OriginNotAllowed,
+
     /// Upgrade Error
     #[error(transparent)]
     UpgradeError(#[from] UpgradeError),
@@ -905,11 +1215,405 @@ impl ResponseError for WebSocketError {
     fn status(&self) -> StatusCode {
         match self {
             WebSocketError::InvalidProtocol => StatusCode::BAD_REQUEST,
+            WebSocketError::OriginNotAllowed => StatusCode::FORBIDDEN,
             WebSocketError::UpgradeError(err) => err.status(),
         }
     }
 }
 
+/// A possible error value occurred in the `SocketIo` endpoint.
+#[cfg(feature = "socketio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "socketio")))]
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum SocketIoError {
+    /// The version of the Engine.IO protocol is not supported
+    #[error("unsupported protocol version")]
+    UnsupportedProtocolVersion,
+
+    /// Unknown transport
+    #[error("transport unknown")]
+    UnknownTransport,
+
+    /// Unknown session id
+    #[error("session id unknown")]
+    UnknownSession,
+
+    /// Bad request
+    #[error("bad request")]
+    BadRequest,
+}
+
+#[cfg(feature = "socketio")]
+impl ResponseError for SocketIoError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// A possible error value occurred in the `FastCgiEndpoint`.
+#[derive(Debug, thiserror::Error)]
+pub enum FastCgiError {
+    /// Invalid path
+    #[error("invalid path")]
+    InvalidPath,
+
+    /// Failed to communicate with the backend
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The backend responded with an invalid response
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+impl ResponseError for FastCgiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            FastCgiError::InvalidPath => StatusCode::BAD_REQUEST,
+            FastCgiError::Io(_) | FastCgiError::InvalidResponse(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+/// A possible error value occurred in the `WasmEndpoint`.
+#[cfg(feature = "wasm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+#[derive(Debug, thiserror::Error)]
+pub enum WasmError {
+    /// Failed to load the module
+    #[error("failed to load the module: {0}")]
+    Load(String),
+
+    /// The handler exhausted its fuel
+    #[error("fuel exhausted")]
+    FuelExhausted,
+
+    /// The handler trapped or returned an invalid response
+    #[error("handler failed: {0}")]
+    Handler(String),
+}
+
+#[cfg(feature = "wasm")]
+impl ResponseError for WasmError {
+    fn status(&self) -> StatusCode {
+        match self {
+            WasmError::FuelExhausted => StatusCode::SERVICE_UNAVAILABLE,
+            WasmError::Load(_) | WasmError::Handler(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// A possible error value occurred in the `RhaiScript` middleware.
+#[cfg(feature = "rhai")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rhai")))]
+#[derive(Debug, thiserror::Error)]
+pub enum ScriptError {
+    /// Failed to compile the script
+    #[error("failed to compile the script: {0}")]
+    Compile(String),
+
+    /// Failed to evaluate the script
+    #[error("failed to evaluate the script: {0}")]
+    Eval(String),
+}
+
+#[cfg(feature = "rhai")]
+impl ResponseError for ScriptError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// A possible error value occurred in the `RedirectRules` middleware.
+#[derive(Debug, thiserror::Error)]
+pub enum RedirectRuleError {
+    /// Failed to load the rules
+    #[error("failed to load the redirect rules: {0}")]
+    Load(String),
+
+    /// The pattern is not a valid regular expression
+    #[error("invalid pattern `{pattern}`: {reason}")]
+    InvalidPattern {
+        /// Pattern
+        pattern: String,
+
+        /// Reason
+        reason: String,
+    },
+
+    /// The status code is not a redirect status code
+    #[error("invalid redirect status code: {0}")]
+    InvalidStatus(u16),
+
+    /// The header name of a condition is invalid
+    #[error("invalid header name: {0}")]
+    InvalidHeaderName(String),
+
+    /// The rewritten path is not a valid URI
+    #[error("invalid rewrite target: {0}")]
+    InvalidTarget(String),
+}
+
+impl ResponseError for RedirectRuleError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// A possible error value occurred in the `Decompression` middleware.
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, thiserror::Error)]
+#[error("unsupported content encoding: {0}")]
+pub struct UnsupportedEncodingError(pub String);
+
+#[cfg(feature = "compression")]
+impl ResponseError for UnsupportedEncodingError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    }
+}
+
+/// A possible error value occurred when resolving the tenant of the request.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum TenantError {
+    /// The request does not identify a tenant.
+    #[error("the tenant of the request is missing")]
+    Missing,
+
+    /// The tenant does not exist.
+    #[error("unknown tenant: {0}")]
+    Unknown(String),
+}
+
+impl ResponseError for TenantError {
+    fn status(&self) -> StatusCode {
+        match self {
+            TenantError::Missing => StatusCode::BAD_REQUEST,
+            TenantError::Unknown(_) => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+/// A possible error value occurred in the `Timeout` middleware.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[error("the request timed out")]
+pub struct TimeoutError(pub StatusCode);
+
+impl ResponseError for TimeoutError {
+    fn status(&self) -> StatusCode {
+        self.0
+    }
+}
+
+/// A possible error value when validating the input of a
+/// [`Resource`](crate::endpoint::Resource).
+///
+/// It is converted into a problem details response with the
+/// `422 Unprocessable Entity` status, the messages of the invalid fields are
+/// in the `errors` member.
+#[derive(Debug, Default, Clone, thiserror::Error, Eq, PartialEq)]
+#[error("validation failed")]
+pub struct ValidationError {
+    errors: BTreeMap<String, Vec<String>>,
+}
+
+impl ValidationError {
+    /// Create an empty `ValidationError`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an error message of the field.
+    #[must_use]
+    pub fn field(mut self, name: impl Into<String>, message: impl Into<String>) -> Self {
+        self.add(name, message);
+        self
+    }
+
+    /// Adds an error message of the field.
+    pub fn add(&mut self, name: impl Into<String>, message: impl Into<String>) {
+        self.errors
+            .entry(name.into())
+            .or_default()
+            .push(message.into());
+    }
+
+    /// Returns `true` if there are no errors.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the error messages of the fields.
+    #[inline]
+    pub fn errors(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.errors
+    }
+
+    /// Returns `Ok(())` if there are no errors, otherwise returns `Err(self)`.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl ResponseError for ValidationError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+
+    fn as_response(&self) -> Response {
+        crate::web::ProblemDetails::new(self.status())
+            .detail(self.to_string())
+            .extension("errors", &self.errors)
+            .into_response()
+    }
+}
+
+/// A possible error value when parsing the OData query options.
+#[derive(Debug, thiserror::Error, Clone, Eq, PartialEq)]
+pub enum ParseODataError {
+    /// Invalid query string.
+    #[error("invalid query string: {0}")]
+    InvalidQuery(String),
+
+    /// The query option is not supported.
+    #[error("unsupported query option `{0}`")]
+    UnsupportedOption(String),
+
+    /// The query option is specified more than once.
+    #[error("duplicate query option `{0}`")]
+    DuplicateOption(String),
+
+    /// The value of the query option is invalid.
+    #[error("invalid value of `{option}`: {message}")]
+    InvalidValue {
+        /// The name of the query option.
+        option: &'static str,
+        /// The error message.
+        message: String,
+    },
+
+    /// The field is not allowed.
+    #[error("field `{0}` is not allowed")]
+    FieldNotAllowed(String),
+
+    /// The value of `$top` exceeds the maximum.
+    #[error("`$top` must not exceed {0}")]
+    TopTooLarge(u64),
+}
+
+impl ResponseError for ParseODataError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// A possible error value when fetching the status or the output of a job.
+#[cfg(feature = "jobs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jobs")))]
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+pub enum JobError {
+    /// The job does not exist or has expired.
+    #[error("job not found")]
+    NotFound,
+
+    /// The job is not completed yet.
+    #[error("job is not completed")]
+    NotReady,
+
+    /// The job failed and has no output.
+    #[error("job failed")]
+    Failed,
+}
+
+#[cfg(feature = "jobs")]
+impl ResponseError for JobError {
+    fn status(&self) -> StatusCode {
+        match self {
+            JobError::NotFound => StatusCode::NOT_FOUND,
+            JobError::NotReady => StatusCode::CONFLICT,
+            JobError::Failed => StatusCode::GONE,
+        }
+    }
+}
+
+/// A possible error value when verifying the CSRF token.
+#[cfg(feature = "csrf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "csrf")))]
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+pub enum CsrfError {
+    /// The request has no CSRF token.
+    #[error("missing csrf token")]
+    MissingToken,
+
+    /// The CSRF token is invalid or expired.
+    #[error("invalid csrf token")]
+    InvalidToken,
+}
+
+#[cfg(feature = "csrf")]
+impl ResponseError for CsrfError {
+    fn status(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+}
+
+/// A possible error value when sending a subrequest.
+#[derive(Debug, thiserror::Error, Clone, Eq, PartialEq)]
+pub enum SubrequestError {
+    /// The `Subrequests` middleware is not applied.
+    #[error("the `Subrequests` middleware is required")]
+    NotEnabled,
+
+    /// The uri of the subrequest is invalid.
+    #[error("invalid subrequest uri `{0}`")]
+    InvalidUri(String),
+
+    /// The subrequests are nested deeper than the maximum depth.
+    #[error("subrequests are nested deeper than {0}")]
+    TooDeep(usize),
+}
+
+impl ResponseError for SubrequestError {
+    fn status(&self) -> StatusCode {
+        match self {
+            SubrequestError::NotEnabled | SubrequestError::InvalidUri(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            SubrequestError::TooDeep(_) => StatusCode::LOOP_DETECTED,
+        }
+    }
+}
+
+/// A possible error value when processing the Edge-Side Includes.
+#[derive(Debug, thiserror::Error, Clone, Eq, PartialEq)]
+pub enum EsiError {
+    /// The subrequest of an `<esi:include>` tag failed.
+    #[error("failed to include `{src}`: {status}")]
+    IncludeFailed {
+        /// The uri of the subrequest.
+        src: String,
+        /// The status of the response.
+        status: StatusCode,
+    },
+
+    /// The body of the response or of an included fragment exceeds the
+    /// maximum size.
+    #[error("the body is larger than {0} bytes")]
+    BodyTooLarge(usize),
+}
+
+impl ResponseError for EsiError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
 /// A possible error value when upgrading connection.
 #[derive(Debug, thiserror::Error)]
 pub enum UpgradeError {
@@ -991,6 +1695,37 @@ impl ResponseError for StaticFileError {
     }
 }
 
+/// A possible error value occurred when evaluating the conditional request
+/// headers with [`Preconditions`](crate::web::Preconditions).
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+pub enum PreconditionError {
+    /// The resource has not been modified
+    #[error("not modified")]
+    NotModified,
+
+    /// Precondition failed
+    #[error("precondition failed")]
+    PreconditionFailed,
+}
+
+impl ResponseError for PreconditionError {
+    fn status(&self) -> StatusCode {
+        match self {
+            PreconditionError::NotModified => StatusCode::NOT_MODIFIED,
+            PreconditionError::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+        }
+    }
+
+    fn as_response(&self) -> Response {
+        match self {
+            PreconditionError::NotModified => Response::builder().status(self.status()).finish(),
+            PreconditionError::PreconditionFailed => Response::builder()
+                .status(self.status())
+                .body(self.to_string()),
+        }
+    }
+}
+
 /// A possible error value occurred in the `SizeLimit` middleware.
 #[derive(Debug, thiserror::Error, Eq, PartialEq)]
 pub enum SizedLimitError {
@@ -1012,6 +1747,65 @@ impl ResponseError for SizedLimitError {
     }
 }
 
+/// A possible error value occurred in the `RequestLimits` middleware.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum RequestLimitError {
+    /// Request headers too large
+    #[error("request header fields too large")]
+    HeadersTooLarge,
+
+    /// Request URI too long
+    #[error("uri too long")]
+    UriTooLong,
+}
+
+impl ResponseError for RequestLimitError {
+    fn status(&self) -> StatusCode {
+        match self {
+            RequestLimitError::HeadersTooLarge => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+            RequestLimitError::UriTooLong => StatusCode::URI_TOO_LONG,
+        }
+    }
+}
+
+/// A possible error value when creating a header value from a string, for
+/// example, the location of a redirect or the attributes of a cookie.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum InvalidHeaderValueError {
+    /// The value contains a CR or LF character.
+    #[error("the value of header `{0}` contains a line break")]
+    LineBreak(String),
+
+    /// The value contains a character that is not allowed in a header value.
+    #[error("the value of header `{0}` contains an invalid character")]
+    InvalidCharacter(String),
+}
+
+impl ResponseError for InvalidHeaderValueError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// A possible error value when rendering a template, see
+/// [`Template`](crate::web::Template).
+#[derive(Debug, thiserror::Error)]
+#[error("failed to render template: {0}")]
+pub struct RenderTemplateError(pub Box<dyn StdError + Send + Sync>);
+
+impl RenderTemplateError {
+    /// Create a `RenderTemplateError` from the error of a template engine.
+    pub fn new(err: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self(err.into())
+    }
+}
+
+impl ResponseError for RenderTemplateError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
 /// A possible error value occurred when adding a route.
 #[derive(Debug, thiserror::Error, Eq, PartialEq)]
 pub enum RouteError {
@@ -1117,6 +1911,38 @@ impl ResponseError for RedisSessionError {
     }
 }
 
+/// A possible error value occurred when deal with redis rate limit store.
+#[cfg(feature = "redis-rate-limit")]
+#[derive(Debug, thiserror::Error)]
+pub enum RedisRateLimitError {
+    /// Redis error.
+    #[error("redis: {0}")]
+    Redis(redis::RedisError),
+}
+
+#[cfg(feature = "redis-rate-limit")]
+impl ResponseError for RedisRateLimitError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// A possible error value occurred when deal with redis job store.
+#[cfg(feature = "redis-jobs")]
+#[derive(Debug, thiserror::Error)]
+pub enum RedisJobError {
+    /// Redis error.
+    #[error("redis: {0}")]
+    Redis(redis::RedisError),
+}
+
+#[cfg(feature = "redis-jobs")]
+impl ResponseError for RedisJobError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::{Error as IoError, ErrorKind};
@@ -1190,6 +2016,55 @@ mod tests {
         assert_eq!(err.into_response().status(), StatusCode::BAD_GATEWAY);
     }
 
+    #[tokio::test]
+    async fn test_derive_api_error() {
+        #[derive(Debug, serde::Serialize)]
+        struct Details {
+            field: &'static str,
+        }
+
+        #[derive(Debug, thiserror::Error, ApiError)]
+        #[api_error(internal, status = 400)]
+        enum MyError {
+            #[error("not found")]
+            #[api_error(status = 404)]
+            NotFound,
+            #[error("invalid {0}")]
+            Invalid(String),
+            #[error("conflict")]
+            #[api_error(status = 409, json)]
+            Conflict(Details),
+        }
+
+        #[derive(Debug, thiserror::Error, ApiError)]
+        #[error("too many requests")]
+        #[api_error(internal, status = 429)]
+        struct TooManyRequests;
+
+        let resp = MyError::NotFound.into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "not found");
+
+        let err = Error::from(MyError::Invalid("name".to_string()));
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        let resp = err.into_response();
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            "invalid name"
+        );
+
+        let resp = MyError::Conflict(Details { field: "email" }).into_response();
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        assert_eq!(resp.content_type(), Some("application/json; charset=utf-8"));
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            r#"{"field":"email"}"#
+        );
+
+        let resp = TooManyRequests.into_response();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
     #[tokio::test]
     async fn test_custom_as_response() {
         #[derive(Debug, thiserror::Error)]
@@ -0,0 +1,383 @@
+//! The `Upgrade: h2c` mechanism of `HTTP/1.1`, see
+//! [RFC 7540](https://www.rfc-editor.org/rfc/rfc7540#section-3.2).
+//!
+//! The request that carries the upgrade is answered with `101 Switching
+//! Protocols`, and the connection is then served with `HTTP/2`. The request
+//! itself becomes the stream `1` of the `HTTP/2` connection, which is done by
+//! injecting a `HEADERS` frame for it after the connection preface of the
+//! client, so that it is handled like any other `HTTP/2` request.
+
+use std::{
+    io::Result as IoResult,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use base64::engine::{general_purpose::URL_SAFE_NO_PAD, Engine};
+use bytes::{Buf, Bytes, BytesMut};
+use http::{header, HeaderMap, HeaderName, Method, StatusCode, Version};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{body::BoxBody, Response};
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const FRAME_HEADER_LEN: usize = 9;
+/// The default `SETTINGS_MAX_FRAME_SIZE`.
+const MAX_FRAME_SIZE: usize = 16384;
+
+const FRAME_HEADERS: u8 = 0x1;
+const FRAME_SETTINGS: u8 = 0x4;
+const FRAME_CONTINUATION: u8 = 0x9;
+const FLAG_END_STREAM: u8 = 0x1;
+const FLAG_ACK: u8 = 0x1;
+const FLAG_END_HEADERS: u8 = 0x4;
+
+/// The headers that are specific to an `HTTP/1.1` connection, which are not
+/// allowed in `HTTP/2`.
+const CONNECTION_HEADERS: &[&str] = &[
+    "connection",
+    "upgrade",
+    "host",
+    "transfer-encoding",
+    "te",
+    "http2-settings",
+    "keep-alive",
+    "proxy-connection",
+];
+
+/// An accepted `Upgrade: h2c` request.
+pub(crate) struct H2cUpgrade {
+    /// The payload of the `HTTP2-Settings` header.
+    settings: Vec<u8>,
+    /// The `HEADERS` and `CONTINUATION` frames of the request on stream `1`.
+    headers: Vec<u8>,
+}
+
+fn has_token(headers: &HeaderMap, name: HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|item| item.trim().eq_ignore_ascii_case(token))
+}
+
+impl H2cUpgrade {
+    /// Returns the upgrade if the request asks for `h2c`.
+    ///
+    /// Requests with a body are not upgraded, because the body would have to
+    /// be sent on stream `1` as well, and are answered with `HTTP/1.1`, as
+    /// the server is allowed to ignore the upgrade.
+    pub(crate) fn from_request<B>(req: &http::Request<B>) -> Option<Self> {
+        let headers = req.headers();
+        if req.version() != Version::HTTP_11
+            || !has_token(headers, header::UPGRADE, "h2c")
+            || !has_token(headers, header::CONNECTION, "upgrade")
+            || !has_token(headers, header::CONNECTION, "http2-settings")
+            || headers.contains_key(header::TRANSFER_ENCODING)
+            || headers
+                .get(header::CONTENT_LENGTH)
+                .is_some_and(|value| value.as_bytes() != b"0")
+        {
+            return None;
+        }
+
+        let mut values = headers.get_all("http2-settings").iter();
+        let settings = URL_SAFE_NO_PAD.decode(values.next()?.as_bytes()).ok()?;
+        if values.next().is_some() || settings.len() % 6 != 0 {
+            return None;
+        }
+
+        let mut block = Vec::new();
+        encode_header(&mut block, b":method", req.method().as_str().as_bytes());
+        encode_header(&mut block, b":scheme", b"http");
+        if let Some(host) = headers.get(header::HOST) {
+            encode_header(&mut block, b":authority", host.as_bytes());
+        }
+        if req.method() != Method::CONNECT {
+            let path = req
+                .uri()
+                .path_and_query()
+                .map(|path| path.as_str())
+                .unwrap_or("/");
+            encode_header(&mut block, b":path", path.as_bytes());
+        }
+        for (name, value) in headers {
+            if !CONNECTION_HEADERS.contains(&name.as_str()) {
+                encode_header(&mut block, name.as_str().as_bytes(), value.as_bytes());
+            }
+        }
+
+        let mut frames = Vec::with_capacity(block.len() + FRAME_HEADER_LEN);
+        let mut chunks = block.chunks(MAX_FRAME_SIZE).peekable();
+        let mut frame_type = FRAME_HEADERS;
+        let mut flags = FLAG_END_STREAM;
+        while let Some(chunk) = chunks.next() {
+            if chunks.peek().is_none() {
+                flags |= FLAG_END_HEADERS;
+            }
+            write_frame_header(&mut frames, chunk.len(), frame_type, flags, 1);
+            frames.extend_from_slice(chunk);
+            frame_type = FRAME_CONTINUATION;
+            flags = 0;
+        }
+        if block.is_empty() {
+            write_frame_header(
+                &mut frames,
+                0,
+                FRAME_HEADERS,
+                FLAG_END_STREAM | FLAG_END_HEADERS,
+                1,
+            );
+        }
+
+        Some(Self {
+            settings,
+            headers: frames,
+        })
+    }
+
+    /// The response that accepts the upgrade.
+    pub(crate) fn switching_protocols() -> http::Response<BoxBody> {
+        Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(header::CONNECTION, "Upgrade")
+            .header(header::UPGRADE, "h2c")
+            .finish()
+            .into()
+    }
+
+    /// Wraps the upgraded connection.
+    pub(crate) fn wrap<T>(self, io: T) -> H2cUpgradeIo<T> {
+        H2cUpgradeIo {
+            inner: io,
+            preface: Some((BytesMut::new(), self)),
+            pending: Bytes::new(),
+        }
+    }
+}
+
+fn write_frame_header(buf: &mut Vec<u8>, len: usize, frame_type: u8, flags: u8, stream_id: u32) {
+    buf.extend_from_slice(&(len as u32).to_be_bytes()[1..]);
+    buf.push(frame_type);
+    buf.push(flags);
+    buf.extend_from_slice(&stream_id.to_be_bytes());
+}
+
+/// Encodes an integer with an `N`-bit prefix, see
+/// [RFC 7541](https://www.rfc-editor.org/rfc/rfc7541#section-5.1).
+fn encode_integer(buf: &mut Vec<u8>, mut value: usize, prefix_bits: u8, first_byte: u8) {
+    let max = (1 << prefix_bits) - 1;
+    if value < max {
+        buf.push(first_byte | value as u8);
+        return;
+    }
+    buf.push(first_byte | max as u8);
+    value -= max;
+    while value >= 128 {
+        buf.push((value % 128 + 128) as u8);
+        value /= 128;
+    }
+    buf.push(value as u8);
+}
+
+/// Encodes a header field as a literal without indexing, so that the dynamic
+/// table of the decoder is not changed.
+fn encode_header(buf: &mut Vec<u8>, name: &[u8], value: &[u8]) {
+    buf.push(0);
+    encode_integer(buf, name.len(), 7, 0);
+    buf.extend_from_slice(name);
+    encode_integer(buf, value.len(), 7, 0);
+    buf.extend_from_slice(value);
+}
+
+/// The upgraded connection, which merges the `HTTP2-Settings` into the first
+/// `SETTINGS` frame of the client, and injects the request after it.
+pub(crate) struct H2cUpgradeIo<T> {
+    inner: T,
+    preface: Option<(BytesMut, H2cUpgrade)>,
+    pending: Bytes,
+}
+
+/// Returns the rewritten connection preface of the client, or `None` if more
+/// data is required.
+fn rewrite_preface(buf: &mut BytesMut, upgrade: &H2cUpgrade) -> Option<Bytes> {
+    let head_len = PREFACE.len() + FRAME_HEADER_LEN;
+    if !buf.starts_with(PREFACE) && !PREFACE.starts_with(buf) {
+        // not an `HTTP/2` client, which is rejected by the server
+        return Some(buf.split().freeze());
+    }
+    if buf.len() < head_len {
+        return None;
+    }
+
+    let frame = &buf[PREFACE.len()..head_len];
+    let len = u32::from_be_bytes([0, frame[0], frame[1], frame[2]]) as usize;
+    if frame[3] != FRAME_SETTINGS || frame[4] & FLAG_ACK != 0 {
+        return Some(buf.split().freeze());
+    }
+    if buf.len() < head_len + len {
+        return None;
+    }
+
+    let mut output = Vec::with_capacity(buf.len() + upgrade.settings.len() + upgrade.headers.len());
+    output.extend_from_slice(PREFACE);
+    // the settings of the frame are applied after the ones of the header, so
+    // they take precedence
+    write_frame_header(
+        &mut output,
+        upgrade.settings.len() + len,
+        FRAME_SETTINGS,
+        0,
+        0,
+    );
+    output.extend_from_slice(&upgrade.settings);
+    buf.advance(head_len);
+    output.extend_from_slice(&buf.split_to(len));
+    output.extend_from_slice(&upgrade.headers);
+    output.extend_from_slice(&buf.split());
+    Some(output.into())
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for H2cUpgradeIo<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let this = self.get_mut();
+
+        loop {
+            if !this.pending.is_empty() {
+                let len = this.pending.len().min(buf.remaining());
+                buf.put_slice(&this.pending.split_to(len));
+                return Poll::Ready(Ok(()));
+            }
+
+            let Some((data, upgrade)) = &mut this.preface else {
+                return Pin::new(&mut this.inner).poll_read(cx, buf);
+            };
+
+            let mut chunk = [0; 1024];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            futures_util::ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+            if chunk_buf.filled().is_empty() {
+                this.pending = data.split().freeze();
+                this.preface = None;
+                if this.pending.is_empty() {
+                    return Poll::Ready(Ok(()));
+                }
+                continue;
+            }
+
+            data.extend_from_slice(chunk_buf.filled());
+            if let Some(output) = rewrite_preface(data, upgrade) {
+                this.pending = output;
+                this.preface = None;
+            }
+        }
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for H2cUpgradeIo<T> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer() {
+        let mut buf = Vec::new();
+        encode_integer(&mut buf, 10, 5, 0);
+        assert_eq!(buf, [10]);
+
+        // the example of RFC 7541, C.1.2
+        let mut buf = Vec::new();
+        encode_integer(&mut buf, 1337, 5, 0);
+        assert_eq!(buf, [31, 154, 10]);
+    }
+
+    #[test]
+    fn upgrade_request() {
+        let req = http::Request::builder()
+            .uri("/a?b=1")
+            .header(header::HOST, "localhost")
+            .header(header::CONNECTION, "Upgrade, HTTP2-Settings")
+            .header(header::UPGRADE, "h2c")
+            .header("http2-settings", "AAMAAABkAAQAAP__")
+            .header("x-a", "1")
+            .body(())
+            .unwrap();
+        let upgrade = H2cUpgrade::from_request(&req).unwrap();
+        assert_eq!(upgrade.settings, [0, 3, 0, 0, 0, 100, 0, 4, 0, 0, 255, 255]);
+
+        let frame = &upgrade.headers[..FRAME_HEADER_LEN];
+        assert_eq!(frame[3], FRAME_HEADERS);
+        assert_eq!(frame[4], FLAG_END_STREAM | FLAG_END_HEADERS);
+        assert_eq!(&frame[5..], [0, 0, 0, 1]);
+        let block = &upgrade.headers[FRAME_HEADER_LEN..];
+        assert!(block.starts_with(b"\x00\x07:method\x03GET\x00\x07:scheme\x04http"));
+        assert!(block.ends_with(b"\x00\x03x-a\x011"));
+        assert!(!block.windows(7).any(|w| w == b"upgrade"));
+
+        let req = http::Request::builder()
+            .method(Method::POST)
+            .header(header::CONNECTION, "Upgrade, HTTP2-Settings")
+            .header(header::UPGRADE, "h2c")
+            .header(header::CONTENT_LENGTH, "5")
+            .header("http2-settings", "")
+            .body(())
+            .unwrap();
+        assert!(H2cUpgrade::from_request(&req).is_none());
+
+        let req = http::Request::builder()
+            .header(header::CONNECTION, "Upgrade")
+            .header(header::UPGRADE, "h2c")
+            .header("http2-settings", "")
+            .body(())
+            .unwrap();
+        assert!(H2cUpgrade::from_request(&req).is_none());
+    }
+
+    #[test]
+    fn preface() {
+        let upgrade = H2cUpgrade {
+            settings: vec![0, 3, 0, 0, 0, 100],
+            headers: vec![1, 2, 3],
+        };
+
+        let mut buf = BytesMut::from(&PREFACE[..10]);
+        assert!(rewrite_preface(&mut buf, &upgrade).is_none());
+
+        buf.extend_from_slice(&PREFACE[10..]);
+        buf.extend_from_slice(&[0, 0, 6, FRAME_SETTINGS, 0, 0, 0, 0, 0]);
+        buf.extend_from_slice(&[0, 4, 0, 0]);
+        assert!(rewrite_preface(&mut buf, &upgrade).is_none());
+
+        buf.extend_from_slice(&[255, 255, 9, 9]);
+        let output = rewrite_preface(&mut buf, &upgrade).unwrap();
+        let mut expected = PREFACE.to_vec();
+        expected.extend_from_slice(&[0, 0, 12, FRAME_SETTINGS, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[0, 3, 0, 0, 0, 100, 0, 4, 0, 0, 255, 255]);
+        expected.extend_from_slice(&[1, 2, 3, 9, 9]);
+        assert_eq!(output, expected);
+
+        let mut buf = BytesMut::from(&b"GET / HTTP/1.1\r\n"[..]);
+        assert_eq!(
+            rewrite_preface(&mut buf, &upgrade).unwrap(),
+            &b"GET / HTTP/1.1\r\n"[..]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use chrono::{Datelike, Timelike};
+use unic_langid::LanguageIdentifier;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+#[derive(Debug)]
+struct Conventions {
+    decimal: &'static str,
+    group: &'static str,
+    date_order: DateOrder,
+    date_separator: &'static str,
+    zero_pad: bool,
+    hour12: bool,
+}
+
+const fn conventions(
+    decimal: &'static str,
+    group: &'static str,
+    date_order: DateOrder,
+    date_separator: &'static str,
+    zero_pad: bool,
+    hour12: bool,
+) -> Conventions {
+    Conventions {
+        decimal,
+        group,
+        date_order,
+        date_separator,
+        zero_pad,
+        hour12,
+    }
+}
+
+const ISO: Conventions = conventions(".", ",", DateOrder::YearMonthDay, "-", true, false);
+const EN_US: Conventions = conventions(".", ",", DateOrder::MonthDayYear, "/", false, true);
+const EN: Conventions = conventions(".", ",", DateOrder::DayMonthYear, "/", true, false);
+const DE: Conventions = conventions(",", ".", DateOrder::DayMonthYear, ".", true, false);
+const FR: Conventions = conventions(",", "\u{202f}", DateOrder::DayMonthYear, "/", true, false);
+const ROMANCE: Conventions = conventions(",", ".", DateOrder::DayMonthYear, "/", true, false);
+const NL: Conventions = conventions(",", ".", DateOrder::DayMonthYear, "-", true, false);
+const SLAVIC: Conventions = conventions(",", "\u{a0}", DateOrder::DayMonthYear, ".", true, false);
+const SV: Conventions = conventions(",", "\u{a0}", DateOrder::YearMonthDay, "-", true, false);
+const JA: Conventions = conventions(".", ",", DateOrder::YearMonthDay, "/", true, false);
+const ZH: Conventions = conventions(".", ",", DateOrder::YearMonthDay, "/", false, false);
+
+fn lookup(language: &LanguageIdentifier) -> &'static Conventions {
+    match language.language.as_str() {
+        "en" => match language.region.as_ref().map(|region| region.as_str()) {
+            None | Some("US") => &EN_US,
+            Some(_) => &EN,
+        },
+        "de" => &DE,
+        "fr" => &FR,
+        "es" | "it" | "pt" => &ROMANCE,
+        "nl" => &NL,
+        "ru" | "pl" | "uk" | "cs" => &SLAVIC,
+        "sv" => &SV,
+        "ja" => &JA,
+        "zh" => &ZH,
+        _ => &ISO,
+    }
+}
+
+/// Locale-aware formatting of numbers, dates and times.
+///
+/// The formatter uses the separators and the date order of the common
+/// languages, such as `1,234.5` and `2/29/2024` for `en-US` or `1.234,5` and
+/// `29.02.2024` for `de`. The other languages use ISO 8601 dates and the
+/// English separators.
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use poem::i18n::I18NFormatter;
+/// use unic_langid::langid;
+///
+/// let formatter = I18NFormatter::new(&langid!("de-DE"));
+/// assert_eq!(formatter.format_number(1234567.891, 2), "1.234.567,89");
+/// assert_eq!(
+///     formatter.format_date(&NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+///     "29.02.2024"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct I18NFormatter {
+    conventions: &'static Conventions,
+}
+
+impl I18NFormatter {
+    /// Create a formatter for the language.
+    pub fn new(language: &LanguageIdentifier) -> Self {
+        Self {
+            conventions: lookup(language),
+        }
+    }
+
+    /// Formats the number with the grouping and decimal separators of the
+    /// language, rounded to `fraction_digits` digits after the decimal
+    /// separator.
+    pub fn format_number(&self, value: f64, fraction_digits: usize) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+
+        let digits = format!("{:.*}", fraction_digits, value.abs());
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits.as_str(), ""));
+        let mut s = String::with_capacity(digits.len() + integer.len() / 3 + 1);
+
+        if value.is_sign_negative() && digits.bytes().any(|c| matches!(c, b'1'..=b'9')) {
+            s.push('-');
+        }
+        for (i, c) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                s.push_str(self.conventions.group);
+            }
+            s.push(c);
+        }
+        if !fraction.is_empty() {
+            s.push_str(self.conventions.decimal);
+            s.push_str(fraction);
+        }
+        s
+    }
+
+    /// Formats the date in the short numeric style of the language.
+    pub fn format_date(&self, date: &impl Datelike) -> String {
+        let Conventions {
+            date_order,
+            date_separator: sep,
+            zero_pad,
+            ..
+        } = self.conventions;
+        let (year, month, day) = (date.year(), date.month(), date.day());
+        let (month, day) = if *zero_pad {
+            (format!("{month:02}"), format!("{day:02}"))
+        } else {
+            (month.to_string(), day.to_string())
+        };
+
+        match date_order {
+            DateOrder::DayMonthYear => format!("{day}{sep}{month}{sep}{year}"),
+            DateOrder::MonthDayYear => format!("{month}{sep}{day}{sep}{year}"),
+            DateOrder::YearMonthDay => format!("{year}{sep}{month}{sep}{day}"),
+        }
+    }
+
+    /// Formats the time in hours and minutes, with the 12-hour clock if it is
+    /// used by the language, such as `2:05 PM` for `en-US` and `14:05` for
+    /// `de`.
+    pub fn format_time(&self, time: &impl Timelike) -> String {
+        if self.conventions.hour12 {
+            let (pm, hour) = time.hour12();
+            let period = if pm { "PM" } else { "AM" };
+            format!("{}:{:02} {}", hour, time.minute(), period)
+        } else {
+            format!("{:02}:{:02}", time.hour(), time.minute())
+        }
+    }
+
+    /// Formats the date and the time, see [`I18NFormatter::format_date`] and
+    /// [`I18NFormatter::format_time`].
+    pub fn format_datetime<T: Datelike + Timelike>(&self, datetime: &T) -> String {
+        format!(
+            "{} {}",
+            self.format_date(datetime),
+            self.format_time(datetime)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use unic_langid::langid;
+
+    use super::*;
+
+    #[test]
+    fn format_number() {
+        let en = I18NFormatter::new(&langid!("en-US"));
+        assert_eq!(en.format_number(0.0, 0), "0");
+        assert_eq!(en.format_number(999.0, 0), "999");
+        assert_eq!(en.format_number(1000.0, 0), "1,000");
+        assert_eq!(en.format_number(-1234567.891, 2), "-1,234,567.89");
+        assert_eq!(en.format_number(-0.001, 2), "0.00");
+
+        let fr = I18NFormatter::new(&langid!("fr"));
+        assert_eq!(fr.format_number(12345.5, 1), "12\u{202f}345,5");
+
+        let unknown = I18NFormatter::new(&langid!("fi"));
+        assert_eq!(unknown.format_number(12345.5, 1), "12,345.5");
+    }
+
+    #[test]
+    fn format_date() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 9).unwrap();
+        for (language, expected) in [
+            (langid!("en"), "2/9/2024"),
+            (langid!("en-US"), "2/9/2024"),
+            (langid!("en-GB"), "09/02/2024"),
+            (langid!("de"), "09.02.2024"),
+            (langid!("nl"), "09-02-2024"),
+            (langid!("sv"), "2024-02-09"),
+            (langid!("zh-CN"), "2024/2/9"),
+            (langid!("fi"), "2024-02-09"),
+        ] {
+            assert_eq!(I18NFormatter::new(&language).format_date(&date), expected);
+        }
+    }
+
+    #[test]
+    fn format_time() {
+        let datetime = NaiveDate::from_ymd_opt(2024, 2, 9)
+            .unwrap()
+            .and_hms_opt(14, 5, 0)
+            .unwrap();
+        assert_eq!(
+            I18NFormatter::new(&langid!("en-US")).format_datetime(&datetime),
+            "2/9/2024 2:05 PM"
+        );
+        assert_eq!(
+            I18NFormatter::new(&langid!("de")).format_datetime(&datetime),
+            "09.02.2024 14:05"
+        );
+        assert_eq!(
+            I18NFormatter::new(&langid!("en-US")).format_time(&datetime.with_hour(0).unwrap()),
+            "12:05 AM"
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::str::FromStr;
 
+use chrono::{Datelike, Timelike};
+use fluent::FluentValue;
 use http::header;
 use smallvec::SmallVec;
 use unic_langid::LanguageIdentifier;
@@ -82,6 +84,55 @@ impl Locale {
     pub fn text(&self, id: impl AsRef<str>) -> Result<String, I18NError> {
         self.bundle.text(id)
     }
+
+    /// Gets the text with the `$count` argument, which selects the plural
+    /// variant of the message.
+    ///
+    /// See also: [`I18NBundle::text_with_count`](I18NBundle::text_with_count)
+    pub fn text_with_count<'a>(
+        &self,
+        id: impl AsRef<str>,
+        count: impl Into<FluentValue<'a>>,
+    ) -> Result<String, I18NError> {
+        self.bundle.text_with_count(id, count)
+    }
+
+    /// Returns the negotiated language.
+    ///
+    /// See also: [`I18NBundle::language`](I18NBundle::language)
+    pub fn language(&self) -> &LanguageIdentifier {
+        self.bundle.language()
+    }
+
+    /// Formats the number for the negotiated language.
+    ///
+    /// See also: [`I18NFormatter::format_number`](crate::i18n::I18NFormatter::format_number)
+    pub fn format_number(&self, value: f64, fraction_digits: usize) -> String {
+        self.bundle
+            .formatter()
+            .format_number(value, fraction_digits)
+    }
+
+    /// Formats the date for the negotiated language.
+    ///
+    /// See also: [`I18NFormatter::format_date`](crate::i18n::I18NFormatter::format_date)
+    pub fn format_date(&self, date: &impl Datelike) -> String {
+        self.bundle.formatter().format_date(date)
+    }
+
+    /// Formats the time for the negotiated language.
+    ///
+    /// See also: [`I18NFormatter::format_time`](crate::i18n::I18NFormatter::format_time)
+    pub fn format_time(&self, time: &impl Timelike) -> String {
+        self.bundle.formatter().format_time(time)
+    }
+
+    /// Formats the date and the time for the negotiated language.
+    ///
+    /// See also: [`I18NFormatter::format_datetime`](crate::i18n::I18NFormatter::format_datetime)
+    pub fn format_datetime<T: Datelike + Timelike>(&self, datetime: &T) -> String {
+        self.bundle.formatter().format_datetime(datetime)
+    }
 }
 
 #[async_trait::async_trait]
@@ -58,8 +58,16 @@
 //! # Use extractor
 //!
 //! See also: [`crate::i18n::Locale`]
+//!
+//! # Format numbers and dates
+//!
+//! [`I18NFormatter`] formats numbers and dates for a language, and is
+//! available from [`I18NBundle::formatter`] and the methods of
+//! [`Locale`], which can also be called from the Askama templates. For Tera
+//! templates, see `TeraTemplate::register_i18n_filters`.
 
 mod args;
+mod format;
 mod locale;
 mod resources;
 
@@ -68,6 +76,7 @@ pub use unic_langid;
 
 pub use self::{
     args::I18NArgs,
+    format::I18NFormatter,
     locale::Locale,
     resources::{I18NBundle, I18NResources, I18NResourcesBuilder},
 };
@@ -5,7 +5,7 @@ use std::{
     sync::Arc,
 };
 
-use fluent::{FluentMessage, FluentResource};
+use fluent::{FluentMessage, FluentResource, FluentValue};
 use intl_memoizer::concurrent::IntlLangMemoizer;
 use smallvec::SmallVec;
 use unic_langid::{langid, LanguageIdentifier};
@@ -16,7 +16,7 @@ type FluentBundle = fluent::bundle::FluentBundle<FluentResource, IntlLangMemoize
 
 use fluent_langneg::NegotiationStrategy;
 
-use crate::i18n::I18NArgs;
+use crate::i18n::{I18NArgs, I18NFormatter};
 
 struct InnerResources {
     available_languages: Vec<LanguageIdentifier>,
@@ -208,23 +208,30 @@ impl I18NResources {
             self.inner.strategy,
         );
 
-        I18NBundle(
-            resolved_languages
+        I18NBundle {
+            language: resolved_languages
+                .first()
+                .map(|language| (*language).clone())
+                .unwrap_or_else(|| self.inner.default_language.clone()),
+            bundles: resolved_languages
                 .into_iter()
                 .filter_map(|language| self.inner.bundles.get(language))
                 .cloned()
                 .collect(),
-        )
+        }
     }
 }
 
 /// A collection of localization messages.
-pub struct I18NBundle(SmallVec<[Arc<FluentBundle>; 8]>);
+pub struct I18NBundle {
+    language: LanguageIdentifier,
+    bundles: SmallVec<[Arc<FluentBundle>; 8]>,
+}
 
 impl I18NBundle {
     fn message(&self, id: impl AsRef<str>) -> Result<(&FluentBundle, FluentMessage), I18NError> {
         let id = id.as_ref();
-        for bundle in &self.0 {
+        for bundle in &self.bundles {
             if let Some(message) = bundle.get_message(id) {
                 return Ok((bundle, message));
             }
@@ -295,4 +302,57 @@ impl I18NBundle {
     pub fn text(&self, id: impl AsRef<str>) -> Result<String, I18NError> {
         self.text_with_args(id, I18NArgs::default())
     }
+
+    /// Gets the text with the `$count` argument, which selects the plural
+    /// variant of the message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::i18n::I18NResources;
+    /// use unic_langid::langid;
+    ///
+    /// let resources = I18NResources::builder()
+    ///     .add_ftl(
+    ///         "en-US",
+    ///         r#"
+    /// new-messages = { $count ->
+    ///     [one] You have one new message.
+    ///    *[other] You have { $count } new messages.
+    /// }
+    /// "#,
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// let bundle = resources.negotiate_languages(&[langid!("en-US")]);
+    ///
+    /// assert_eq!(
+    ///     bundle.text_with_count("new-messages", 1).unwrap(),
+    ///     "You have one new message."
+    /// );
+    /// assert_eq!(
+    ///     bundle.text_with_count("new-messages", 5).unwrap(),
+    ///     "You have \u{2068}5\u{2069} new messages."
+    /// );
+    /// ```
+    pub fn text_with_count<'a>(
+        &self,
+        id: impl AsRef<str>,
+        count: impl Into<FluentValue<'a>>,
+    ) -> Result<String, I18NError> {
+        self.text_with_args(id, (("count", count),))
+    }
+
+    /// Returns the negotiated language, or the default language if none of
+    /// the requested languages is available.
+    #[inline]
+    pub fn language(&self) -> &LanguageIdentifier {
+        &self.language
+    }
+
+    /// Returns a formatter of numbers and dates for the negotiated language.
+    #[inline]
+    pub fn formatter(&self) -> I18NFormatter {
+        I18NFormatter::new(&self.language)
+    }
 }
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use mime::Mime;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// The status of a job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum JobStatus {
+    /// The job is waiting in the queue.
+    Pending,
+    /// The job is running.
+    Running,
+    /// The job is completed and the output can be downloaded.
+    Completed,
+    /// The job failed.
+    Failed {
+        /// The error message, which is shown to the clients. The jobs run by
+        /// [`Jobs`](super::Jobs) store a generic message and log the error.
+        error: String,
+    },
+}
+
+impl JobStatus {
+    /// Returns `true` if the job is completed or failed.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed { .. })
+    }
+}
+
+/// The output of a completed job, which is downloaded as an attachment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobOutput {
+    /// The data.
+    pub data: Bytes,
+    /// The content type of the data.
+    pub content_type: Mime,
+    /// The filename of the attachment.
+    pub filename: Option<String>,
+}
+
+impl JobOutput {
+    /// Create a `JobOutput` with the data, the content type defaults to
+    /// `application/octet-stream`.
+    pub fn new(data: impl Into<Bytes>) -> Self {
+        Self {
+            data: data.into(),
+            content_type: mime::APPLICATION_OCTET_STREAM,
+            filename: None,
+        }
+    }
+
+    /// Sets the content type.
+    #[must_use]
+    pub fn content_type(self, content_type: Mime) -> Self {
+        Self {
+            content_type,
+            ..self
+        }
+    }
+
+    /// Sets the filename of the attachment.
+    #[must_use]
+    pub fn filename(self, filename: impl Into<String>) -> Self {
+        Self {
+            filename: Some(filename.into()),
+            ..self
+        }
+    }
+}
+
+/// Represents a back-end storage of the status and the output of the jobs.
+///
+/// The storage can be shared by multiple server instances, so the status and
+/// the output can be fetched from any instance.
+#[async_trait::async_trait]
+pub trait JobStore: Send + Sync + 'static {
+    /// Inserts or updates the status of the job, which expires after `ttl`.
+    async fn set_status(&self, id: &str, status: &JobStatus, ttl: Duration) -> Result<()>;
+
+    /// Returns the status of the job, or `None` if the job does not exist or
+    /// has expired.
+    async fn status(&self, id: &str) -> Result<Option<JobStatus>>;
+
+    /// Inserts the output of the job, which expires after `ttl`.
+    async fn set_output(&self, id: &str, output: &JobOutput, ttl: Duration) -> Result<()>;
+
+    /// Returns the output of the job.
+    async fn output(&self, id: &str) -> Result<Option<JobOutput>>;
+}
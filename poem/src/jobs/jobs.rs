@@ -0,0 +1,396 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::{thread_rng, Rng};
+use serde::Serialize;
+use tokio::sync::Semaphore;
+
+use crate::{
+    endpoint::make,
+    error::JobError,
+    get,
+    jobs::{JobOutput, JobStatus, JobStore},
+    web::{Accepted, Attachment, Json},
+    Endpoint, IntoResponse, Request, Response, Result, Route,
+};
+
+/// A job that runs in the background and produces an output to download.
+///
+/// It is implemented for the closures that return a future of
+/// `Result<JobOutput>`.
+#[async_trait::async_trait]
+pub trait Job: Send + 'static {
+    /// Runs the job.
+    async fn run(self) -> Result<JobOutput>;
+}
+
+#[async_trait::async_trait]
+impl<F, Fut> Job for F
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<JobOutput>> + Send,
+{
+    async fn run(self) -> Result<JobOutput> {
+        self().await
+    }
+}
+
+fn generate_job_id() -> String {
+    let random_bytes = thread_rng().gen::<[u8; 16]>();
+    URL_SAFE_NO_PAD.encode(random_bytes)
+}
+
+/// The response of a submitted job, `202 Accepted` with the URL of the status
+/// in the `Location` header and the body.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobAccepted {
+    /// The identifier of the job.
+    pub id: String,
+    /// The URL of the status of the job.
+    pub status_url: String,
+}
+
+impl IntoResponse for JobAccepted {
+    fn into_response(self) -> Response {
+        let location = self.status_url.clone();
+        Accepted::new(Json(self)).location(location).into_response()
+    }
+}
+
+/// A queue of the background jobs.
+///
+/// The long running requests, such as the bulk exports, submit a [`Job`] and
+/// return [`JobAccepted`] immediately instead of tying up the request. The
+/// clients poll the status from the [`JobsEndpoint`] and download the output
+/// when the job is completed.
+///
+/// The jobs are run by the server instance that submits them, at most
+/// `max_concurrency` at a time. The status and the output are saved in the
+/// [`JobStore`], which can be shared by multiple instances.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::StatusCode,
+///     jobs::{JobAccepted, JobOutput, Jobs, MemoryJobStore},
+///     post,
+///     test::TestClient,
+///     web::Data,
+///     EndpointExt, Result, Route,
+/// };
+///
+/// #[handler]
+/// async fn export(jobs: Data<&Jobs>) -> Result<JobAccepted> {
+///     jobs.submit(|| async {
+///         Ok(JobOutput::new("id,name\n1,alice\n")
+///             .content_type(mime::TEXT_CSV)
+///             .filename("users.csv"))
+///     })
+///     .await
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let jobs = Jobs::new(MemoryJobStore::new()).base_path("/jobs");
+/// let app = Route::new()
+///     .at("/export", post(export))
+///     .nest("/jobs", jobs.endpoint())
+///     .data(jobs);
+/// let cli = TestClient::new(app);
+///
+/// let resp = cli.post("/export").send().await;
+/// resp.assert_status(StatusCode::ACCEPTED);
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct Jobs {
+    store: Arc<dyn JobStore>,
+    semaphore: Arc<Semaphore>,
+    base_path: String,
+    ttl: Duration,
+}
+
+impl Jobs {
+    /// Create a `Jobs` with the store of the status and the output.
+    pub fn new(store: impl JobStore) -> Self {
+        Self {
+            store: Arc::new(store),
+            semaphore: Arc::new(Semaphore::new(4)),
+            base_path: "/jobs".to_string(),
+            ttl: Duration::from_secs(60 * 60),
+        }
+    }
+
+    /// Sets the maximum number of jobs that run at the same time, the other
+    /// jobs wait in the queue.
+    ///
+    /// Default is `4`.
+    #[must_use]
+    pub fn max_concurrency(self, max_concurrency: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrency.max(1))),
+            ..self
+        }
+    }
+
+    /// Sets the path where the [`JobsEndpoint`] is nested, which is used to
+    /// build the URLs of the status and the output.
+    ///
+    /// Default is `/jobs`.
+    #[must_use]
+    pub fn base_path(self, base_path: impl Into<String>) -> Self {
+        Self {
+            base_path: base_path.into().trim_end_matches('/').to_string(),
+            ..self
+        }
+    }
+
+    /// Sets how long the status and the output are kept.
+    ///
+    /// Default is `1 hour`.
+    #[must_use]
+    pub fn ttl(self, ttl: Duration) -> Self {
+        Self { ttl, ..self }
+    }
+
+    /// Submits the job to the queue.
+    pub async fn submit(&self, job: impl Job) -> Result<JobAccepted> {
+        let id = generate_job_id();
+        self.store
+            .set_status(&id, &JobStatus::Pending, self.ttl)
+            .await?;
+
+        tokio::spawn({
+            let jobs = self.clone();
+            let id = id.clone();
+            async move {
+                if let Err(err) = jobs.run(&id, job).await {
+                    tracing::error!(job = %id, error = %err, "failed to update the job");
+                }
+            }
+        });
+
+        Ok(JobAccepted {
+            status_url: format!("{}/{}", self.base_path, id),
+            id,
+        })
+    }
+
+    async fn run(&self, id: &str, job: impl Job) -> Result<()> {
+        let _permit = self.semaphore.acquire().await;
+        self.store
+            .set_status(id, &JobStatus::Running, self.ttl)
+            .await?;
+
+        let status = match job.run().await {
+            Ok(output) => {
+                self.store.set_output(id, &output, self.ttl).await?;
+                JobStatus::Completed
+            }
+            Err(err) => {
+                // the status is public to the holders of the url, so the
+                // details of the error are only logged
+                tracing::error!(job = %id, error = %err, "the job failed");
+                JobStatus::Failed {
+                    error: "the job failed".to_string(),
+                }
+            }
+        };
+        self.store.set_status(id, &status, self.ttl).await
+    }
+
+    /// Returns the status of the job.
+    pub async fn status(&self, id: &str) -> Result<Option<JobStatus>> {
+        self.store.status(id).await
+    }
+
+    /// Returns the endpoint that serves the status and the output of the jobs,
+    /// it should be nested at the [`base_path`](Jobs::base_path).
+    pub fn endpoint(&self) -> JobsEndpoint {
+        JobsEndpoint::new(self.clone())
+    }
+}
+
+#[derive(Serialize)]
+struct StatusBody<'a> {
+    id: &'a str,
+    #[serde(flatten)]
+    status: &'a JobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_url: Option<String>,
+}
+
+async fn status(jobs: Arc<Jobs>, req: Request) -> Result<Response> {
+    let id = req.raw_path_param("id").unwrap_or_default();
+    let status = jobs.status(id).await?.ok_or(JobError::NotFound)?;
+    let output_url =
+        (status == JobStatus::Completed).then(|| format!("{}/{}/output", jobs.base_path, id));
+    Ok(Json(StatusBody {
+        id,
+        status: &status,
+        output_url,
+    })
+    .into_response())
+}
+
+async fn output(jobs: Arc<Jobs>, req: Request) -> Result<Response> {
+    let id = req.raw_path_param("id").unwrap_or_default();
+    match jobs.status(id).await?.ok_or(JobError::NotFound)? {
+        JobStatus::Completed => {}
+        JobStatus::Failed { .. } => return Err(JobError::Failed.into()),
+        JobStatus::Pending | JobStatus::Running => return Err(JobError::NotReady.into()),
+    }
+
+    let output = jobs.store.output(id).await?.ok_or(JobError::NotFound)?;
+    let mut attachment = Attachment::new(output.data).content_type(output.content_type);
+    if let Some(filename) = output.filename {
+        attachment = attachment.filename(filename);
+    }
+    Ok(attachment.into_response())
+}
+
+/// An endpoint that serves the status and the output of the [`Jobs`].
+///
+/// - `GET /:id` returns the status of the job as JSON, with the `output_url`
+///   when the job is completed.
+/// - `GET /:id/output` downloads the output of the completed job.
+///
+/// # Errors
+///
+/// - [`JobError`]
+pub struct JobsEndpoint {
+    route: Route,
+}
+
+impl JobsEndpoint {
+    fn new(jobs: Jobs) -> Self {
+        let jobs = Arc::new(jobs);
+        let route = Route::new()
+            .at(
+                "/:id",
+                get(make({
+                    let jobs = jobs.clone();
+                    move |req| status(jobs.clone(), req)
+                })),
+            )
+            .at(
+                "/:id/output",
+                get(make(move |req| output(jobs.clone(), req))),
+            );
+        Self { route }
+    }
+}
+
+#[async_trait::async_trait]
+impl Endpoint for JobsEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        self.route.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use tokio::sync::oneshot;
+
+    use super::*;
+    use crate::{
+        error::InternalServerError, handler, http::StatusCode, jobs::MemoryJobStore, post,
+        test::TestClient, web::Data, EndpointExt,
+    };
+
+    #[tokio::test]
+    async fn export() {
+        let (tx, rx) = oneshot::channel::<()>();
+        let rx = parking_lot::Mutex::new(Some(rx));
+
+        #[handler(internal)]
+        async fn fail(jobs: Data<&Jobs>) -> Result<JobAccepted> {
+            jobs.submit(|| async { Err(InternalServerError(std::io::Error::other("disk full"))) })
+                .await
+        }
+
+        let jobs = Jobs::new(MemoryJobStore::new()).base_path("/api/jobs/");
+        let export = make({
+            let jobs = jobs.clone();
+            move |_| {
+                let jobs = jobs.clone();
+                let rx = rx.lock().take().unwrap();
+                async move {
+                    jobs.submit(|| async move {
+                        rx.await.ok();
+                        Ok(JobOutput::new("a,b")
+                            .content_type(mime::TEXT_CSV)
+                            .filename("export.csv"))
+                    })
+                    .await
+                }
+            }
+        });
+        let cli = TestClient::new(
+            Route::new()
+                .at("/export", post(export))
+                .at("/fail", post(fail))
+                .nest("/api/jobs", jobs.endpoint())
+                .data(jobs),
+        );
+
+        let resp = cli.post("/export").send().await;
+        resp.assert_status(StatusCode::ACCEPTED);
+        let status_url = resp.0.headers()["location"].to_str().unwrap().to_string();
+        assert!(status_url.starts_with("/api/jobs/"));
+        let id = status_url.trim_start_matches("/api/jobs/").to_string();
+
+        let resp = cli.get(&status_url).send().await;
+        resp.assert_status_is_ok();
+        let status = resp.0.into_body().into_json::<Value>().await.unwrap();
+        assert_eq!(status["id"], id);
+        assert!(status["status"] == "pending" || status["status"] == "running");
+        cli.get(format!("{status_url}/output"))
+            .send()
+            .await
+            .assert_status(StatusCode::CONFLICT);
+
+        tx.send(()).unwrap();
+        let output_url = format!("{status_url}/output");
+        loop {
+            let resp = cli.get(&status_url).send().await;
+            let status = resp.0.into_body().into_json::<Value>().await.unwrap();
+            if status["status"] == "completed" {
+                assert_eq!(status["output_url"], output_url);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let resp = cli.get(&output_url).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("text/csv");
+        resp.assert_header("content-disposition", "attachment; filename=\"export.csv\"");
+        resp.assert_text("a,b").await;
+
+        let resp = cli.post("/fail").send().await;
+        let status_url = resp.0.headers()["location"].to_str().unwrap().to_string();
+        loop {
+            let resp = cli.get(&status_url).send().await;
+            let status = resp.0.into_body().into_json::<Value>().await.unwrap();
+            if status["status"] == "failed" {
+                assert_eq!(status["error"], "the job failed");
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        cli.get(format!("{status_url}/output"))
+            .send()
+            .await
+            .assert_status(StatusCode::GONE);
+
+        cli.get("/api/jobs/unknown")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    jobs::{JobOutput, JobStatus, JobStore},
+    Result,
+};
+
+struct Entry {
+    status: Option<JobStatus>,
+    output: Option<JobOutput>,
+    expires_at: Instant,
+}
+
+/// A job store using memory.
+///
+/// The jobs are only visible to the server instance that runs them, use
+/// `RedisJobStore` to share them across multiple instances.
+#[derive(Default)]
+pub struct MemoryJobStore {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl MemoryJobStore {
+    /// Create a `MemoryJobStore`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn update(&self, id: &str, ttl: Duration, f: impl FnOnce(&mut Entry)) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock();
+        entries.retain(|_, entry| entry.expires_at > now);
+        let entry = entries.entry(id.to_string()).or_insert_with(|| Entry {
+            status: None,
+            output: None,
+            expires_at: now,
+        });
+        entry.expires_at = now + ttl;
+        f(entry);
+    }
+
+    fn get<T>(&self, id: &str, f: impl FnOnce(&Entry) -> Option<T>) -> Option<T> {
+        self.entries
+            .lock()
+            .get(id)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .and_then(f)
+    }
+}
+
+#[async_trait::async_trait]
+impl JobStore for MemoryJobStore {
+    async fn set_status(&self, id: &str, status: &JobStatus, ttl: Duration) -> Result<()> {
+        self.update(id, ttl, |entry| entry.status = Some(status.clone()));
+        Ok(())
+    }
+
+    async fn status(&self, id: &str) -> Result<Option<JobStatus>> {
+        Ok(self.get(id, |entry| entry.status.clone()))
+    }
+
+    async fn set_output(&self, id: &str, output: &JobOutput, ttl: Duration) -> Result<()> {
+        self.update(id, ttl, |entry| entry.output = Some(output.clone()));
+        Ok(())
+    }
+
+    async fn output(&self, id: &str) -> Result<Option<JobOutput>> {
+        Ok(self.get(id, |entry| entry.output.clone()))
+    }
+}
@@ -0,0 +1,18 @@
+//! Background jobs for the long running requests.
+//!
+//! A handler submits a [`Job`] to the [`Jobs`] queue and returns
+//! `202 Accepted` with the URL of the status, the clients poll the status from
+//! the [`JobsEndpoint`] and download the output when the job is completed.
+
+mod job_store;
+#[allow(clippy::module_inception)]
+mod jobs;
+mod memory_store;
+#[cfg(feature = "redis-jobs")]
+mod redis_store;
+
+pub use job_store::{JobOutput, JobStatus, JobStore};
+pub use jobs::{Job, JobAccepted, Jobs, JobsEndpoint};
+pub use memory_store::MemoryJobStore;
+#[cfg(feature = "redis-jobs")]
+pub use redis_store::RedisJobStore;
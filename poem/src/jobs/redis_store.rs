@@ -0,0 +1,120 @@
+use std::{collections::HashMap, time::Duration};
+
+use bytes::Bytes;
+use redis::{aio::ConnectionLike, Cmd};
+
+use crate::{
+    error::RedisJobError,
+    jobs::{JobOutput, JobStatus, JobStore},
+    Result,
+};
+
+/// A job store using redis.
+///
+/// # Errors
+///
+/// - [`RedisJobError`]
+#[cfg_attr(docsrs, doc(cfg(feature = "redis-jobs")))]
+pub struct RedisJobStore<T> {
+    connection: T,
+    prefix: String,
+}
+
+impl<T> RedisJobStore<T> {
+    /// Create a `RedisJobStore`.
+    pub fn new(connection: T) -> Self {
+        Self {
+            connection,
+            prefix: "poem:jobs:".to_string(),
+        }
+    }
+
+    /// Sets the prefix of the keys.
+    ///
+    /// Default is `poem:jobs:`.
+    #[must_use]
+    pub fn prefix(self, prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            ..self
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: ConnectionLike + Clone + Send + Sync + 'static> JobStore for RedisJobStore<T> {
+    async fn set_status(&self, id: &str, status: &JobStatus, ttl: Duration) -> Result<()> {
+        let value = serde_json::to_string(status).unwrap_or_default();
+        Cmd::set_ex(
+            format!("{}{}:status", self.prefix, id),
+            value,
+            ttl.as_secs().max(1),
+        )
+        .query_async::<_, ()>(&mut self.connection.clone())
+        .await
+        .map_err(RedisJobError::Redis)?;
+        Ok(())
+    }
+
+    async fn status(&self, id: &str) -> Result<Option<JobStatus>> {
+        let data: Option<String> = Cmd::get(format!("{}{}:status", self.prefix, id))
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(RedisJobError::Redis)?;
+        Ok(data.and_then(|data| serde_json::from_str(&data).ok()))
+    }
+
+    async fn set_output(&self, id: &str, output: &JobOutput, ttl: Duration) -> Result<()> {
+        let key = format!("{}{}:output", self.prefix, id);
+        let mut fields = vec![
+            ("data", output.data.to_vec()),
+            (
+                "content_type",
+                output.content_type.as_ref().as_bytes().to_vec(),
+            ),
+        ];
+        if let Some(filename) = &output.filename {
+            fields.push(("filename", filename.as_bytes().to_vec()));
+        }
+        redis::pipe()
+            .atomic()
+            .del(&key)
+            .ignore()
+            .hset_multiple(&key, &fields)
+            .ignore()
+            .expire(&key, ttl.as_secs().max(1) as i64)
+            .ignore()
+            .query_async::<_, ()>(&mut self.connection.clone())
+            .await
+            .map_err(RedisJobError::Redis)?;
+        Ok(())
+    }
+
+    async fn output(&self, id: &str) -> Result<Option<JobOutput>> {
+        let mut fields: HashMap<String, Vec<u8>> =
+            Cmd::hgetall(format!("{}{}:output", self.prefix, id))
+                .query_async(&mut self.connection.clone())
+                .await
+                .map_err(RedisJobError::Redis)?;
+        let data = match fields.remove("data") {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+
+        let mut output = JobOutput::new(Bytes::from(data));
+        if let Some(content_type) = fields
+            .remove("content_type")
+            .and_then(|value| String::from_utf8(value).ok())
+            .and_then(|value| value.parse().ok())
+        {
+            output = output.content_type(content_type);
+        }
+        if let Some(filename) = fields
+            .remove("filename")
+            .and_then(|value| String::from_utf8(value).ok())
+        {
+            output = output.filename(filename);
+        }
+        Ok(Some(output))
+    }
+}
@@ -232,6 +232,7 @@
 //! |------------------|--------------------------------|
 //! | server | Server and listener APIs(enable by default) |
 //! |compression  | Support decompress request body and compress response body |
+//! |zstd  | Support the `zstd` compression algorithm |
 //! |cookie            | Support for Cookie             |
 //! |csrf | Support for Cross-Site Request Forgery (CSRF) protection |
 //! |multipart         | Support for Multipart          |
@@ -240,22 +241,37 @@
 //! |opentelemetry     | Support for opentelemetry    |
 //! |prometheus        | Support for Prometheus       |
 //! |redis-session     | Support for RedisSession     |
+//! |redis-rate-limit  | Support for RedisRateLimitStore |
+//! |redis-jobs        | Support for RedisJobStore    |
+//! |request-id        | Support for request identifiers |
 //! |rustls            | Support for HTTP server over TLS with [`rustls`](https://crates.io/crates/rustls)  |
 //! |session           | Support for session    |
+//! |signing           | Support for signing responses and verifying signed requests |
 //! |sse               | Support Server-Sent Events (SSE)       |
 //! |tempfile          | Support for [`tempfile`](https://crates.io/crates/tempfile) |
+//! |timezone          | Support for the timezone of the client with [`chrono-tz`](https://crates.io/crates/chrono-tz) |
 //! |test              | Test utilities to test your endpoints. |
 //! |tower-compat      | Adapters for `tower::Layer` and `tower::Service`. |
 //! |websocket         | Support for WebSocket          |
 //! | anyhow        | Integrate with the [`anyhow`](https://crates.io/crates/anyhow) crate. |
 //! | eyre06        | Integrate with version 0.6.x of the [`eyre`](https://crates.io/crates/eyre) crate. |
 //! | i18n          | Support for internationalization |
+//! | introspection | Support for validating bearer tokens with OAuth 2.0 token introspection |
 //! | acme-native-roots | Support for ACME(Automatic Certificate Management Environment) |
 //! | acme-webpki-roots | Support for ACME using webpki TLS roots rather than native TLS roots |
 //! | tokio-metrics | Integrate with the [`tokio-metrics`](https://crates.io/crates/tokio-metrics) crate. |
 //! | embed  | Integrate with [`rust-embed`](https://crates.io/crates/rust-embed) crate. |
 //! | xml | Integrate with [`quick-xml`](https://crates.io/crates/quick-xml) crate. |
 //! | yaml | Integrate with [`serde-yaml`](https://crates.io/crates/serde-yaml) crate.                   |
+//! | msgpack | Integrate with [`rmp-serde`](https://crates.io/crates/rmp-serde) crate. |
+//! | csv | Integrate with [`csv`](https://crates.io/crates/csv) crate. |
+//! | tera | Integrate with [`tera`](https://crates.io/crates/tera) crate. |
+//! | askama | Integrate with [`askama`](https://crates.io/crates/askama) crate. |
+//! | handlebars | Integrate with [`handlebars`](https://crates.io/crates/handlebars) crate. |
+//! | socketio | Support for the Socket.IO protocol |
+//! | wasm | Support for running the handlers compiled to WebAssembly |
+//! | rhai | Integrate with [`rhai`](https://crates.io/crates/rhai) crate. |
+//! | jobs | Support for the background jobs with status polling |
 
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/poem-web/poem/master/favicon.ico")]
 #![doc(html_logo_url = "https://raw.githubusercontent.com/poem-web/poem/master/logo.png")]
@@ -270,6 +286,9 @@ pub mod error;
 #[cfg(feature = "i18n")]
 #[cfg_attr(docsrs, doc(cfg(feature = "i18n")))]
 pub mod i18n;
+#[cfg(feature = "jobs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jobs")))]
+pub mod jobs;
 #[cfg(feature = "server")]
 #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
 pub mod listener;
@@ -287,6 +306,8 @@ pub use http;
 
 mod addr;
 mod body;
+#[cfg(feature = "server")]
+mod h2c;
 mod request;
 mod response;
 mod route;
@@ -295,7 +316,7 @@ mod server;
 
 pub use addr::Addr;
 pub use async_trait::async_trait;
-pub use body::Body;
+pub use body::{Body, BodyObserver};
 pub use endpoint::{Endpoint, EndpointExt, IntoEndpoint};
 pub use error::{Error, Result};
 pub use middleware::Middleware;
@@ -307,5 +328,5 @@ pub use route::{
     RouteMethod, RouteScheme,
 };
 #[cfg(feature = "server")]
-pub use server::Server;
+pub use server::{Server, ShutdownSignal};
 pub use web::{FromRequest, IntoResponse, RequestBody};
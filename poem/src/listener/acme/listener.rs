@@ -28,9 +28,9 @@ use crate::{
             resolver::{ResolveServerCert, ACME_TLS_ALPN_NAME},
             AutoCert, ChallengeType, Http01TokensMap,
         },
-        Acceptor, HandshakeStream, Listener,
+        Acceptor, HandshakeStream, Listener, TlsInfo,
     },
-    web::{LocalAddr, RemoteAddr},
+    web::{ConnectInfoMap, LocalAddr, RemoteAddr},
 };
 
 pub(crate) async fn auto_cert_acceptor<T: Listener>(
@@ -225,9 +225,28 @@ impl<T: Acceptor> Acceptor for AutoCertAcceptor<T> {
     }
 
     async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
-        let (stream, local_addr, remote_addr, _) = self.inner.accept().await?;
-        let stream = HandshakeStream::new(self.acceptor.accept(stream));
-        return Ok((stream, local_addr, remote_addr, Scheme::HTTPS));
+        self.accept_with_info()
+            .await
+            .map(|(stream, local_addr, remote_addr, scheme, _)| {
+                (stream, local_addr, remote_addr, scheme)
+            })
+    }
+
+    async fn accept_with_info(
+        &mut self,
+    ) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme, ConnectInfoMap)> {
+        let (stream, local_addr, remote_addr, _, connect_info) =
+            self.inner.accept_with_info().await?;
+        let handshake = self.acceptor.accept(stream);
+        let stream = HandshakeStream::new({
+            let connect_info = connect_info.clone();
+            async move {
+                let stream = handshake.await?;
+                connect_info.insert(TlsInfo::from_rustls(stream.get_ref().1));
+                Ok(stream)
+            }
+        });
+        Ok((stream, local_addr, remote_addr, Scheme::HTTPS, connect_info))
     }
 }
 
@@ -8,7 +8,7 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf, Result as IoResult};
 
 use crate::{
     listener::{Acceptor, Listener},
-    web::{LocalAddr, RemoteAddr},
+    web::{ConnectInfoMap, LocalAddr, RemoteAddr},
 };
 
 /// Listener for the [`Listener::combine`](crate::listener::Listener::combine)
@@ -60,6 +60,21 @@ impl<A: Acceptor, B: Acceptor> Acceptor for Combined<A, B> {
             }
         }
     }
+
+    async fn accept_with_info(
+        &mut self,
+    ) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme, ConnectInfoMap)> {
+        tokio::select! {
+            res = self.a.accept_with_info() => {
+                let (stream, local_addr, remote_addr, scheme, connect_info) = res?;
+                Ok((CombinedStream::A(stream), local_addr, remote_addr, scheme, connect_info))
+            }
+            res = self.b.accept_with_info() => {
+                let (stream, local_addr, remote_addr, scheme, connect_info) = res?;
+                Ok((CombinedStream::B(stream), local_addr, remote_addr, scheme, connect_info))
+            }
+        }
+    }
 }
 
 /// A IO stream for CombinedAcceptor.
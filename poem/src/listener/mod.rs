@@ -40,14 +40,14 @@ pub use self::openssl_tls::{OpensslTlsAcceptor, OpensslTlsConfig, OpensslTlsList
 #[cfg(feature = "rustls")]
 pub use self::rustls::{RustlsAcceptor, RustlsCertificate, RustlsConfig, RustlsListener};
 #[cfg(any(feature = "rustls", feature = "native-tls", feature = "openssl-tls"))]
-pub use self::tls::IntoTlsConfigStream;
+pub use self::tls::{IntoTlsConfigStream, TlsInfo};
 #[cfg(unix)]
 pub use self::unix::{UnixAcceptor, UnixListener};
 pub use self::{
     combined::{Combined, CombinedStream},
     tcp::{TcpAcceptor, TcpListener},
 };
-use crate::web::{LocalAddr, RemoteAddr};
+use crate::web::{ConnectInfoMap, LocalAddr, RemoteAddr};
 
 /// Represents a acceptor type.
 #[async_trait::async_trait]
@@ -64,6 +64,19 @@ pub trait Acceptor: Send {
     /// established, the corresponding IO stream and the remote peer’s
     /// address will be returned.
     async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)>;
+
+    /// Accepts a new incoming connection from this listener, along with the
+    /// per-connection metadata that can be extracted with
+    /// [`ConnectInfo`](crate::web::ConnectInfo).
+    ///
+    /// The default implementation calls [`Acceptor::accept`] and attaches no
+    /// metadata.
+    async fn accept_with_info(
+        &mut self,
+    ) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme, ConnectInfoMap)> {
+        let (io, local_addr, remote_addr, scheme) = self.accept().await?;
+        Ok((io, local_addr, remote_addr, scheme, ConnectInfoMap::default()))
+    }
 }
 
 /// An owned dynamically typed Acceptor for use in cases where you can’t
@@ -261,6 +274,12 @@ impl<T: Acceptor + ?Sized> Acceptor for Box<T> {
     async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
         self.as_mut().accept().await
     }
+
+    async fn accept_with_info(
+        &mut self,
+    ) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme, ConnectInfoMap)> {
+        self.as_mut().accept_with_info().await
+    }
 }
 
 #[async_trait::async_trait]
@@ -361,6 +380,16 @@ impl<T: Acceptor> Acceptor for WrappedAcceptor<T> {
                 (BoxIo::new(io), local_addr, remote_addr, scheme)
             })
     }
+
+    async fn accept_with_info(
+        &mut self,
+    ) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme, ConnectInfoMap)> {
+        self.0.accept_with_info().await.map(
+            |(io, local_addr, remote_addr, scheme, connect_info)| {
+                (BoxIo::new(io), local_addr, remote_addr, scheme, connect_info)
+            },
+        )
+    }
 }
 
 #[cfg(test)]
@@ -7,8 +7,8 @@ use tokio::io::{Error as IoError, ErrorKind, Result as IoResult};
 use tokio_native_tls::{native_tls::Identity, TlsStream};
 
 use crate::{
-    listener::{Acceptor, HandshakeStream, IntoTlsConfigStream, Listener},
-    web::{LocalAddr, RemoteAddr},
+    listener::{Acceptor, HandshakeStream, IntoTlsConfigStream, Listener, TlsInfo},
+    web::{ConnectInfoMap, LocalAddr, RemoteAddr},
 };
 
 /// Native TLS Config.
@@ -149,6 +149,16 @@ where
     }
 
     async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
+        self.accept_with_info()
+            .await
+            .map(|(stream, local_addr, remote_addr, scheme, _)| {
+                (stream, local_addr, remote_addr, scheme)
+            })
+    }
+
+    async fn accept_with_info(
+        &mut self,
+    ) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme, ConnectInfoMap)> {
         loop {
             tokio::select! {
                 res = self.config_stream.next() => {
@@ -168,15 +178,26 @@ where
                         unreachable!()
                     }
                 }
-                res = self.inner.accept() => {
-                    let (stream, local_addr, remote_addr, _) = res?;
+                res = self.inner.accept_with_info() => {
+                    let (stream, local_addr, remote_addr, _, connect_info) = res?;
                     let tls_acceptor = match &self.current_tls_acceptor {
                         Some(tls_acceptor) => tls_acceptor.clone(),
                         None => return Err(IoError::new(ErrorKind::Other, "no valid tls config.")),
                     };
-                    let fut = async move { tls_acceptor.accept(stream).map_err(|err| IoError::new(ErrorKind::Other, err.to_string())).await };
+                    let fut = {
+                        let connect_info = connect_info.clone();
+                        async move {
+                            let stream = tls_acceptor.accept(stream).map_err(|err| IoError::new(ErrorKind::Other, err.to_string())).await?;
+                            connect_info.insert(TlsInfo {
+                                version: None,
+                                // the acceptor does not offer any ALPN protocols
+                                alpn_protocol: None,
+                            });
+                            Ok(stream)
+                        }
+                    };
                     let stream = HandshakeStream::new(fut);
-                    return Ok((stream, local_addr, remote_addr, Scheme::HTTPS));
+                    return Ok((stream, local_addr, remote_addr, Scheme::HTTPS, connect_info));
                 }
             }
         }
@@ -18,8 +18,8 @@ use tokio_openssl::SslStream;
 use tokio_util::either::Either;
 
 use crate::{
-    listener::{Acceptor, HandshakeStream, IntoTlsConfigStream, Listener},
-    web::{LocalAddr, RemoteAddr},
+    listener::{Acceptor, HandshakeStream, IntoTlsConfigStream, Listener, TlsInfo},
+    web::{ConnectInfoMap, LocalAddr, RemoteAddr},
 };
 
 /// Openssl configuration contains certificate's chain and private key.
@@ -194,6 +194,16 @@ where
     }
 
     async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
+        self.accept_with_info()
+            .await
+            .map(|(stream, local_addr, remote_addr, scheme, _)| {
+                (stream, local_addr, remote_addr, scheme)
+            })
+    }
+
+    async fn accept_with_info(
+        &mut self,
+    ) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme, ConnectInfoMap)> {
         loop {
             tokio::select! {
                 res = self.config_stream.next() => {
@@ -213,23 +223,31 @@ where
                         unreachable!()
                     }
                 }
-                res = self.inner.accept() => {
-                    let (stream, local_addr, remote_addr, _) = res?;
+                res = self.inner.accept_with_info() => {
+                    let (stream, local_addr, remote_addr, _, connect_info) = res?;
                     let tls_acceptor = match &self.current_tls_acceptor {
                         Some(tls_acceptor) => tls_acceptor.clone(),
                         None => return Err(IoError::new(ErrorKind::Other, "no valid tls config.")),
                     };
-                    let fut = async move {
-                        let ssl = Ssl::new(tls_acceptor.context()).map_err(|err|
-                            IoError::new(ErrorKind::Other, err.to_string()))?;
-                        let mut tls_stream = SslStream::new(ssl, stream).map_err(|err|
-                            IoError::new(ErrorKind::Other, err.to_string()))?;
-                        use std::pin::Pin;
-                        Pin::new(&mut tls_stream).accept().await.map_err(|err|
-                            IoError::new(ErrorKind::Other, err.to_string()))?;
-                        Ok(tls_stream) };
+                    let fut = {
+                        let connect_info = connect_info.clone();
+                        async move {
+                            let ssl = Ssl::new(tls_acceptor.context()).map_err(|err|
+                                IoError::new(ErrorKind::Other, err.to_string()))?;
+                            let mut tls_stream = SslStream::new(ssl, stream).map_err(|err|
+                                IoError::new(ErrorKind::Other, err.to_string()))?;
+                            use std::pin::Pin;
+                            Pin::new(&mut tls_stream).accept().await.map_err(|err|
+                                IoError::new(ErrorKind::Other, err.to_string()))?;
+                            connect_info.insert(TlsInfo {
+                                version: Some(tls_stream.ssl().version_str().to_string()),
+                                alpn_protocol: tls_stream.ssl().selected_alpn_protocol().map(ToOwned::to_owned),
+                            });
+                            Ok(tls_stream)
+                        }
+                    };
                     let stream = HandshakeStream::new(fut);
-                    return Ok((stream, local_addr, remote_addr, Scheme::HTTPS));
+                    return Ok((stream, local_addr, remote_addr, Scheme::HTTPS, connect_info));
                 }
             }
         }
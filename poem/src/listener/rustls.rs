@@ -12,14 +12,14 @@ use tokio_rustls::{
         crypto::ring::sign::any_supported_type,
         server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier},
         sign::CertifiedKey,
-        RootCertStore, ServerConfig,
+        ProtocolVersion, RootCertStore, ServerConfig, ServerConnection,
     },
     server::TlsStream,
 };
 
 use crate::{
-    listener::{Acceptor, HandshakeStream, IntoTlsConfigStream, Listener},
-    web::{LocalAddr, RemoteAddr},
+    listener::{Acceptor, HandshakeStream, IntoTlsConfigStream, Listener, TlsInfo},
+    web::{ClientCert, ConnectInfoMap, LocalAddr, RemoteAddr},
 };
 
 #[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
@@ -202,6 +202,9 @@ impl RustlsConfig {
     }
 
     /// Sets the trust anchor for optional client authentication.
+    ///
+    /// The verified client certificate can be extracted with
+    /// [`ClientCert`](crate::web::ClientCert).
     #[must_use]
     pub fn client_auth_optional(mut self, trust_anchor: impl Into<Vec<u8>>) -> Self {
         self.client_auth = TlsClientAuth::Optional(trust_anchor.into());
@@ -209,6 +212,9 @@ impl RustlsConfig {
     }
 
     /// Sets the trust anchor for required client authentication.
+    ///
+    /// The verified client certificate can be extracted with
+    /// [`ClientCert`](crate::web::ClientCert).
     #[must_use]
     pub fn client_auth_required(mut self, trust_anchor: impl Into<Vec<u8>>) -> Self {
         self.client_auth = TlsClientAuth::Required(trust_anchor.into());
@@ -366,6 +372,16 @@ where
     }
 
     async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
+        self.accept_with_info()
+            .await
+            .map(|(stream, local_addr, remote_addr, scheme, _)| {
+                (stream, local_addr, remote_addr, scheme)
+            })
+    }
+
+    async fn accept_with_info(
+        &mut self,
+    ) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme, ConnectInfoMap)> {
         loop {
             tokio::select! {
                 res = self.config_stream.next() => {
@@ -386,21 +402,48 @@ where
                         unreachable!()
                     }
                 }
-                res = self.inner.accept() => {
-                    let (stream, local_addr, remote_addr, _) = res?;
+                res = self.inner.accept_with_info() => {
+                    let (stream, local_addr, remote_addr, _, connect_info) = res?;
                     let tls_acceptor = match &self.current_tls_acceptor {
                         Some(tls_acceptor) => tls_acceptor,
                         None => return Err(IoError::new(ErrorKind::Other, "no valid tls config.")),
                     };
 
-                    let stream = HandshakeStream::new(tls_acceptor.accept(stream));
-                    return Ok((stream, local_addr, remote_addr, Scheme::HTTPS));
+                    let handshake = tls_acceptor.accept(stream);
+                    let stream = HandshakeStream::new({
+                        let connect_info = connect_info.clone();
+                        async move {
+                            let stream = handshake.await?;
+                            let conn = stream.get_ref().1;
+                            connect_info.insert(TlsInfo::from_rustls(conn));
+                            if let Some(cert) = conn.peer_certificates().and_then(|certs| {
+                                ClientCert::new(certs.iter().map(|cert| cert.to_vec()).collect())
+                            }) {
+                                connect_info.insert(cert);
+                            }
+                            Ok(stream)
+                        }
+                    });
+                    return Ok((stream, local_addr, remote_addr, Scheme::HTTPS, connect_info));
                 }
             }
         }
     }
 }
 
+impl TlsInfo {
+    pub(crate) fn from_rustls(conn: &ServerConnection) -> Self {
+        Self {
+            version: conn.protocol_version().map(|version| match version {
+                ProtocolVersion::TLSv1_2 => "TLSv1.2".to_string(),
+                ProtocolVersion::TLSv1_3 => "TLSv1.3".to_string(),
+                version => format!("{version:?}"),
+            }),
+            alpn_protocol: conn.alpn_protocol().map(ToOwned::to_owned),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct ResolveServerCert {
     certifcate_keys: HashMap<String, Arc<CertifiedKey>>,
@@ -11,3 +11,14 @@ pub trait IntoTlsConfigStream<C>: Send + 'static {
     /// Consume itself and return tls config stream.
     fn into_stream(self) -> IoResult<Self::Stream>;
 }
+
+/// The TLS metadata of a connection, attached to the
+/// [`ConnectInfoMap`](crate::web::ConnectInfoMap) by the TLS acceptors once
+/// the handshake is completed.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct TlsInfo {
+    /// The negotiated protocol version, such as `TLSv1.3`.
+    pub version: Option<String>,
+    /// The negotiated ALPN protocol.
+    pub alpn_protocol: Option<Vec<u8>>,
+}
@@ -13,7 +13,7 @@ use tokio::{
 
 use crate::{
     listener::{Acceptor, Listener},
-    web::{LocalAddr, RemoteAddr},
+    web::{ConnectInfoMap, LocalAddr, RemoteAddr},
 };
 
 /// A Unix domain socket listener.
@@ -123,6 +123,17 @@ impl Acceptor for UnixAcceptor {
             Scheme::HTTP,
         ))
     }
+
+    async fn accept_with_info(
+        &mut self,
+    ) -> Result<(Self::Io, LocalAddr, RemoteAddr, Scheme, ConnectInfoMap)> {
+        let (stream, local_addr, remote_addr, scheme) = self.accept().await?;
+        let connect_info = ConnectInfoMap::default();
+        if let Ok(cred) = stream.peer_cred() {
+            connect_info.insert(cred);
+        }
+        Ok((stream, local_addr, remote_addr, scheme, connect_info))
+    }
 }
 
 #[cfg(test)]
@@ -151,4 +162,22 @@ mod tests {
         drop(acceptor);
         std::fs::remove_file("test-socket").unwrap();
     }
+
+    #[tokio::test]
+    async fn peer_cred() {
+        let listener = UnixListener::bind("test-socket-cred");
+        let mut acceptor = listener.into_acceptor().await.unwrap();
+
+        tokio::spawn(async move {
+            let mut stream = UnixStream::connect("test-socket-cred").await.unwrap();
+            stream.write_i32(10).await.unwrap();
+        });
+
+        let (_, _, _, _, connect_info) = acceptor.accept_with_info().await.unwrap();
+        let cred = connect_info.get::<tokio::net::unix::UCred>().unwrap();
+        assert_eq!(cred.uid(), nix::unistd::getuid().as_raw());
+
+        drop(acceptor);
+        std::fs::remove_file("test-socket-cred").unwrap();
+    }
 }
@@ -0,0 +1,515 @@
+use std::{
+    fmt::Write,
+    str::FromStr,
+    sync::Arc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use headers::{authorization::Basic, Authorization, HeaderMapExt};
+
+use crate::{
+    http::{header::HeaderName, HeaderMap, StatusCode},
+    web::RealIp,
+    BodyObserver, Endpoint, FromRequest, IntoResponse, Middleware, Request, Response, Result,
+};
+
+const COMMON: &str = r#"%h %l %u %t "%r" %>s %b"#;
+const COMBINED: &str = r#"%h %l %u %t "%r" %>s %b "%{Referer}i" "%{User-Agent}i""#;
+
+type Writer = Arc<dyn Fn(&str) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy)]
+enum LatencyUnit {
+    Seconds,
+    Millis,
+    Micros,
+}
+
+#[derive(Debug, Clone)]
+enum Directive {
+    Literal(String),
+    RemoteIp,
+    User,
+    Time,
+    RequestLine,
+    Method,
+    Path,
+    Query,
+    Protocol,
+    Status,
+    Bytes { clf: bool },
+    Latency(LatencyUnit),
+    RequestHeader(HeaderName),
+    ResponseHeader(HeaderName),
+}
+
+fn parse_format(format: &str) -> Vec<Directive> {
+    let mut directives = Vec::new();
+    let mut literal = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+            continue;
+        }
+
+        let mut arg = None;
+        if chars.next_if_eq(&'{').is_some() {
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) => s.push(c),
+                    None => panic!("unclosed `{{` in the access log format `{format}`"),
+                }
+            }
+            arg = Some(s);
+        }
+        chars.next_if_eq(&'>');
+
+        let directive = match (chars.next(), arg.as_deref()) {
+            (Some('%'), None) => {
+                literal.push('%');
+                continue;
+            }
+            (Some('l'), None) => {
+                literal.push('-');
+                continue;
+            }
+            (Some('a' | 'h'), None) => Directive::RemoteIp,
+            (Some('u'), None) => Directive::User,
+            (Some('t'), None) => Directive::Time,
+            (Some('r'), None) => Directive::RequestLine,
+            (Some('m'), None) => Directive::Method,
+            (Some('U'), None) => Directive::Path,
+            (Some('q'), None) => Directive::Query,
+            (Some('H'), None) => Directive::Protocol,
+            (Some('s'), None) => Directive::Status,
+            (Some('b'), None) => Directive::Bytes { clf: true },
+            (Some('B'), None) => Directive::Bytes { clf: false },
+            (Some('D'), None) | (Some('T'), Some("us")) => Directive::Latency(LatencyUnit::Micros),
+            (Some('T'), Some("ms")) => Directive::Latency(LatencyUnit::Millis),
+            (Some('T'), None | Some("s")) => Directive::Latency(LatencyUnit::Seconds),
+            (Some('i'), Some(name)) => Directive::RequestHeader(
+                HeaderName::from_str(name)
+                    .unwrap_or_else(|_| panic!("invalid header name `{name}`")),
+            ),
+            (Some('o'), Some(name)) => Directive::ResponseHeader(
+                HeaderName::from_str(name)
+                    .unwrap_or_else(|_| panic!("invalid header name `{name}`")),
+            ),
+            _ => panic!("invalid directive in the access log format `{format}`"),
+        };
+        if !literal.is_empty() {
+            directives.push(Directive::Literal(std::mem::take(&mut literal)));
+        }
+        directives.push(directive);
+    }
+
+    if !literal.is_empty() {
+        directives.push(Directive::Literal(literal));
+    }
+    directives
+}
+
+/// Escapes the quotes, backslashes and non-printable characters of the values
+/// from the client, so that they cannot forge log lines.
+fn escape(value: &[u8]) -> String {
+    let mut s = String::with_capacity(value.len());
+    for &c in value {
+        match c {
+            b'"' => s.push_str("\\\""),
+            b'\\' => s.push_str("\\\\"),
+            0x20..=0x7e => s.push(c as char),
+            _ => {
+                let _ = write!(s, "\\x{c:02x}");
+            }
+        }
+    }
+    s
+}
+
+/// Formats the time as `[10/Oct/2000:13:55:36 +0000]` in UTC.
+fn format_time(time: SystemTime) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // converts the days since the epoch to the civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as u64;
+
+    format!(
+        "[{:02}/{}/{}:{:02}:{:02}:{:02} +0000]",
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// Middleware for writing an access log line for each request.
+///
+/// The format of the lines is a string with the directives of the
+/// [Apache `mod_log_config`](https://httpd.apache.org/docs/current/mod/mod_log_config.html#formats):
+///
+/// | Directive      | Description                                                        |
+/// |----------------|--------------------------------------------------------------------|
+/// | `%h`, `%a`     | The IP address of the client, see [`RealIp`]                       |
+/// | `%l`           | Always `-`                                                         |
+/// | `%u`           | The user of the Basic authentication                               |
+/// | `%t`           | The time the request was received, in UTC                          |
+/// | `%r`           | The request line, such as `GET /index.html HTTP/1.1`               |
+/// | `%m`           | The method                                                         |
+/// | `%U`           | The path                                                           |
+/// | `%q`           | The query string prefixed with `?`, or empty                       |
+/// | `%H`           | The protocol, such as `HTTP/1.1`                                   |
+/// | `%s`, `%>s`    | The status                                                         |
+/// | `%b`           | The size of the response body in bytes, or `-` if it is empty      |
+/// | `%B`           | The size of the response body in bytes                             |
+/// | `%D`           | The latency in microseconds                                        |
+/// | `%T`           | The latency in seconds, `%{ms}T` and `%{us}T` for other units      |
+/// | `%{Name}i`     | The value of the request header `Name`                             |
+/// | `%{Name}o`     | The value of the response header `Name`                            |
+/// | `%%`           | The percent sign                                                   |
+///
+/// The missing values are written as `-`, and the values from the client are
+/// escaped. The line is written when the response body is sent, so the
+/// latency includes the time to stream the body.
+///
+/// By default the lines are written with [`tracing`] at the `INFO` level, use
+/// [`AccessLog::writer`] to write them elsewhere.
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, middleware::AccessLog, EndpointExt, Route};
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = Route::new().at("/", index).with(AccessLog::combined());
+/// let app = Route::new()
+///     .at("/", index)
+///     .with(AccessLog::new(r#"%h "%r" %>s %b %{ms}T"#));
+/// ```
+pub struct AccessLog {
+    format: Arc<[Directive]>,
+    writer: Writer,
+}
+
+impl Default for AccessLog {
+    fn default() -> Self {
+        Self::common()
+    }
+}
+
+impl AccessLog {
+    /// Create an `AccessLog` middleware with the `format`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the format contains an invalid directive.
+    pub fn new(format: impl AsRef<str>) -> Self {
+        Self {
+            format: parse_format(format.as_ref()).into(),
+            writer: Arc::new(|line: &str| tracing::info!(target: module_path!(), "{}", line)),
+        }
+    }
+
+    /// Create an `AccessLog` middleware with the Common Log Format
+    /// (`%h %l %u %t "%r" %>s %b`).
+    pub fn common() -> Self {
+        Self::new(COMMON)
+    }
+
+    /// Create an `AccessLog` middleware with the Combined Log Format, which
+    /// appends the `Referer` and `User-Agent` headers to the Common Log
+    /// Format.
+    pub fn combined() -> Self {
+        Self::new(COMBINED)
+    }
+
+    /// Sets the function that writes the lines, instead of [`tracing`].
+    #[must_use]
+    pub fn writer<F>(self, f: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        Self {
+            writer: Arc::new(f),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for AccessLog {
+    type Output = AccessLogEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        AccessLogEndpoint {
+            inner: ep,
+            format: self.format.clone(),
+            writer: self.writer.clone(),
+        }
+    }
+}
+
+/// Endpoint for `AccessLog` middleware.
+pub struct AccessLogEndpoint<E> {
+    inner: E,
+    format: Arc<[Directive]>,
+    writer: Writer,
+}
+
+/// The line of a request, which is filled as the request, the response and
+/// the body become available.
+struct Line {
+    format: Arc<[Directive]>,
+    parts: Vec<Option<String>>,
+    start: Instant,
+    bytes: u64,
+    writer: Writer,
+}
+
+impl Line {
+    fn fill(&mut self, mut f: impl FnMut(&Directive) -> Option<String>) {
+        for (part, directive) in self.parts.iter_mut().zip(self.format.iter()) {
+            if part.is_none() {
+                *part = f(directive);
+            }
+        }
+    }
+
+    fn request(&mut self, req: &Request, remote_ip: Option<String>) {
+        let time = SystemTime::now();
+        let header = |name: &HeaderName| {
+            req.headers()
+                .get(name)
+                .map(|value| escape(value.as_bytes()))
+        };
+
+        self.fill(|directive| match directive {
+            Directive::Literal(s) => Some(s.clone()),
+            Directive::RemoteIp => remote_ip.clone(),
+            Directive::User => req
+                .headers()
+                .typed_get::<Authorization<Basic>>()
+                .map(|auth| escape(auth.username().as_bytes())),
+            Directive::Time => Some(format_time(time)),
+            Directive::RequestLine => Some(format!(
+                "{} {} {:?}",
+                req.method(),
+                escape(
+                    req.original_uri()
+                        .path_and_query()
+                        .map(|pq| pq.as_str())
+                        .unwrap_or("/")
+                        .as_bytes()
+                ),
+                req.version()
+            )),
+            Directive::Method => Some(req.method().to_string()),
+            Directive::Path => Some(escape(req.original_uri().path().as_bytes())),
+            Directive::Query => Some(
+                req.original_uri()
+                    .query()
+                    .map(|query| format!("?{}", escape(query.as_bytes())))
+                    .unwrap_or_default(),
+            ),
+            Directive::Protocol => Some(format!("{:?}", req.version())),
+            Directive::RequestHeader(name) => header(name),
+            _ => None,
+        });
+    }
+
+    fn response(&mut self, status: StatusCode, headers: &HeaderMap) {
+        self.fill(|directive| match directive {
+            Directive::Status => Some(status.as_u16().to_string()),
+            Directive::ResponseHeader(name) => {
+                headers.get(name).map(|value| escape(value.as_bytes()))
+            }
+            _ => None,
+        });
+    }
+
+    fn finish(&mut self) {
+        let elapsed = self.start.elapsed();
+        let bytes = self.bytes;
+        self.fill(|directive| match directive {
+            Directive::Bytes { clf: true } if bytes == 0 => None,
+            Directive::Bytes { .. } => Some(bytes.to_string()),
+            Directive::Latency(unit) => Some(match unit {
+                LatencyUnit::Seconds => elapsed.as_secs().to_string(),
+                LatencyUnit::Millis => elapsed.as_millis().to_string(),
+                LatencyUnit::Micros => elapsed.as_micros().to_string(),
+            }),
+            _ => None,
+        });
+
+        let line = self
+            .parts
+            .iter()
+            .map(|part| part.as_deref().unwrap_or("-"))
+            .collect::<String>();
+        (self.writer)(&line);
+    }
+}
+
+impl BodyObserver for Line {
+    fn on_data(&mut self, data: &[u8]) {
+        self.bytes += data.len() as u64;
+    }
+
+    fn on_finish(&mut self, _complete: bool) {
+        self.finish();
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for AccessLogEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let mut line = Line {
+            format: self.format.clone(),
+            parts: vec![None; self.format.len()],
+            start: Instant::now(),
+            bytes: 0,
+            writer: self.writer.clone(),
+        };
+
+        let remote_ip = if self
+            .format
+            .iter()
+            .any(|directive| matches!(directive, Directive::RemoteIp))
+        {
+            RealIp::from_request_without_body(&req)
+                .await
+                .ok()
+                .and_then(|real_ip| real_ip.0)
+                .or_else(|| req.remote_addr().as_socket_addr().map(|addr| addr.ip()))
+                .map(|ip| ip.to_string())
+        } else {
+            None
+        };
+        line.request(&req, remote_ip);
+
+        match self.inner.call(req).await {
+            Ok(resp) => {
+                let mut resp = resp.into_response();
+                line.response(resp.status(), resp.headers());
+                let body = resp.take_body().tee(line);
+                resp.set_body(body);
+                Ok(resp)
+            }
+            Err(err) => {
+                line.response(err.status(), &HeaderMap::new());
+                line.finish();
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::{handler, http::header, test::TestClient, EndpointExt, Route};
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    fn client(access_log: AccessLog) -> (TestClient<impl Endpoint>, Arc<Mutex<Vec<String>>>) {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let app = Route::new().at("/", index).with(access_log.writer({
+            let lines = lines.clone();
+            move |line: &str| lines.lock().push(line.to_string())
+        }));
+        (TestClient::new(app), lines)
+    }
+
+    #[test]
+    fn time() {
+        assert_eq!(
+            format_time(UNIX_EPOCH + Duration::from_secs(971186136)),
+            "[10/Oct/2000:13:55:36 +0000]"
+        );
+        assert_eq!(
+            format_time(UNIX_EPOCH + Duration::from_secs(1709164800)),
+            "[29/Feb/2024:00:00:00 +0000]"
+        );
+    }
+
+    #[tokio::test]
+    async fn combined() {
+        let (cli, lines) = client(AccessLog::combined());
+        let resp = cli
+            .get("/")
+            .query("a", &"b")
+            .header("x-real-ip", "203.0.113.195")
+            .header(header::AUTHORIZATION, "Basic YWxpY2U6c2VjcmV0")
+            .header(header::REFERER, "http://example.com/")
+            .header(header::USER_AGENT, "agent \"1\"")
+            .send()
+            .await;
+        resp.assert_text("hello").await;
+
+        let lines = lines.lock();
+        assert_eq!(lines.len(), 1);
+        let (head, tail) = lines[0].split_once(" [").unwrap();
+        assert_eq!(head, "203.0.113.195 - alice");
+        assert_eq!(
+            tail.split_once("] ").unwrap().1,
+            r#""GET /?a=b HTTP/1.1" 200 5 "http://example.com/" "agent \"1\"""#
+        );
+    }
+
+    #[tokio::test]
+    async fn custom() {
+        let (cli, lines) = client(AccessLog::new(
+            r#"%m %U%q %H %s %b %B %{content-type}o %{x-missing}i %% %{ms}T"#,
+        ));
+        cli.get("/missing")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        cli.get("/").send().await.assert_text("hello").await;
+
+        let lines = lines.lock();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("GET /missing HTTP/1.1 404 - 0 - - % "));
+        assert!(lines[1].starts_with("GET / HTTP/1.1 200 5 5 text/plain; charset=utf-8 - % "));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_directive() {
+        let _ = AccessLog::new("%z");
+    }
+}
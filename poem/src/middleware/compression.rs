@@ -1,83 +1,42 @@
-use std::{collections::HashSet, str::FromStr};
-
-use headers::HeaderMap;
+use std::{collections::HashSet, str::FromStr, sync::Arc};
 
 use crate::{
-    http::header,
-    web::{Compress, CompressionAlgo, CompressionLevel},
+    http::{header, Method, StatusCode},
+    web::{
+        negotiate_encoding, vary_accept_encoding, Compress, CompressionAlgo, CompressionLevel,
+        CompressionStats, StatsHandler,
+    },
     Body, Endpoint, IntoResponse, Middleware, Request, Response, Result,
 };
 
-enum ContentCoding {
-    Brotli,
-    Deflate,
-    Gzip,
-    Star,
-}
-
-impl FromStr for ContentCoding {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.eq_ignore_ascii_case("deflate") {
-            Ok(ContentCoding::Deflate)
-        } else if s.eq_ignore_ascii_case("gzip") {
-            Ok(ContentCoding::Gzip)
-        } else if s.eq_ignore_ascii_case("br") {
-            Ok(ContentCoding::Brotli)
-        } else if s == "*" {
-            Ok(ContentCoding::Star)
-        } else {
-            Err(())
-        }
-    }
-}
-
-fn parse_accept_encoding(
-    headers: &HeaderMap,
-    enabled_algorithms: &HashSet<CompressionAlgo>,
-) -> Option<ContentCoding> {
-    headers
-        .get_all(header::ACCEPT_ENCODING)
-        .iter()
-        .filter_map(|hval| hval.to_str().ok())
-        .flat_map(|s| s.split(',').map(str::trim))
-        .filter_map(|v| {
-            let (e, q) = match v.split_once(";q=") {
-                Some((e, q)) => (e, (q.parse::<f32>().ok()? * 1000.0) as i32),
-                None => (v, 1000),
-            };
-            let coding: ContentCoding = e.parse().ok()?;
-            Some((coding, q))
-        })
-        .filter(|(encoding, _)| {
-            if !enabled_algorithms.is_empty() {
-                match encoding {
-                    ContentCoding::Brotli => enabled_algorithms.contains(&CompressionAlgo::BR),
-                    ContentCoding::Deflate => {
-                        enabled_algorithms.contains(&CompressionAlgo::DEFLATE)
-                    }
-                    ContentCoding::Gzip => enabled_algorithms.contains(&CompressionAlgo::GZIP),
-                    _ => true,
-                }
-            } else {
-                true
-            }
-        })
-        .max_by_key(|(coding, q)| (*q, coding_priority(coding)))
-        .map(|(coding, _)| coding)
-}
+/// The supported algorithms in the order of preference.
+const ALGORITHMS: &[CompressionAlgo] = &[
+    CompressionAlgo::BR,
+    #[cfg(feature = "zstd")]
+    CompressionAlgo::ZSTD,
+    CompressionAlgo::GZIP,
+    CompressionAlgo::DEFLATE,
+];
 
 /// Middleware for decompress request body and compress response body.
 ///
 /// It selects the decompression algorithm according to the request
 /// `Content-Encoding` header, and selects the compression algorithm according
-/// to the request `Accept-Encoding` header.
+/// to the quality values in the request `Accept-Encoding` header.
+///
+/// The responses that are already encoded, have no body, or have the
+/// `Cache-Control: no-transform` header are not compressed. The
+/// `Vary: Accept-Encoding` header is added to all the responses that can be
+/// compressed, so that the caches store the variants separately. The body is
+/// compressed as it is streamed.
 #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
 #[derive(Default)]
 pub struct Compression {
     level: Option<CompressionLevel>,
     algorithms: HashSet<CompressionAlgo>,
+    stats_handler: Option<StatsHandler>,
+    min_size: u64,
+    content_types: Vec<String>,
 }
 
 impl Compression {
@@ -106,6 +65,67 @@ impl Compression {
             ..self
         }
     }
+
+    /// Sets the minimum size of the response body to compress, the responses
+    /// whose body is known to be smaller are not compressed.
+    ///
+    /// Default is `0`.
+    #[must_use]
+    pub fn min_size(self, bytes: u64) -> Self {
+        Self {
+            min_size: bytes,
+            ..self
+        }
+    }
+
+    /// Specify the content types of the responses to compress, such as
+    /// `text/html` or `text/*` (default to all).
+    #[must_use]
+    pub fn content_types<I, T>(self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            content_types: content_types.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    /// Sets a function that is called with the [`CompressionStats`] after
+    /// each response body has been compressed, which can be used to report
+    /// the compression ratio and the time spent compressing per algorithm to
+    /// a metrics system.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{handler, middleware::Compression, EndpointExt};
+    ///
+    /// #[handler]
+    /// fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// let app = index.with(Compression::new().on_stats(|stats| {
+    ///     tracing::info!(
+    ///         algo = %stats.algo,
+    ///         ratio = stats.ratio(),
+    ///         duration = ?stats.duration,
+    ///         "compressed response body"
+    ///     );
+    /// }));
+    /// ```
+    #[must_use]
+    pub fn on_stats<F>(self, f: F) -> Self
+    where
+        F: Fn(&CompressionStats) + Send + Sync + 'static,
+    {
+        Self {
+            stats_handler: Some(Arc::new(f)),
+            ..self
+        }
+    }
 }
 
 impl<E: Endpoint> Middleware<E> for Compression {
@@ -116,6 +136,9 @@ impl<E: Endpoint> Middleware<E> for Compression {
             ep,
             level: self.level,
             algorithms: self.algorithms.clone(),
+            stats_handler: self.stats_handler.clone(),
+            min_size: self.min_size,
+            content_types: self.content_types.clone(),
         }
     }
 }
@@ -126,15 +149,55 @@ pub struct CompressionEndpoint<E: Endpoint> {
     ep: E,
     level: Option<CompressionLevel>,
     algorithms: HashSet<CompressionAlgo>,
+    stats_handler: Option<StatsHandler>,
+    min_size: u64,
+    content_types: Vec<String>,
 }
 
-#[inline]
-fn coding_priority(c: &ContentCoding) -> u8 {
-    match *c {
-        ContentCoding::Deflate => 1,
-        ContentCoding::Gzip => 2,
-        ContentCoding::Brotli => 3,
-        _ => 0,
+impl<E: Endpoint> CompressionEndpoint<E> {
+    fn is_compressible(&self, resp: &mut Response) -> bool {
+        if resp.status().is_informational()
+            || resp.status() == StatusCode::NO_CONTENT
+            || resp.status() == StatusCode::NOT_MODIFIED
+            || resp.headers().contains_key(header::CONTENT_ENCODING)
+        {
+            return false;
+        }
+
+        let no_transform = resp
+            .headers()
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"));
+        if no_transform {
+            return false;
+        }
+
+        if !self.content_types.is_empty() {
+            let essence = match resp.content_type() {
+                Some(content_type) => content_type.split(';').next().unwrap_or_default().trim(),
+                None => return false,
+            };
+            let allowed =
+                self.content_types
+                    .iter()
+                    .any(|pattern| match pattern.strip_suffix('*') {
+                        Some(prefix) => essence
+                            .get(..prefix.len())
+                            .map_or(false, |s| s.eq_ignore_ascii_case(prefix)),
+                        None => essence.eq_ignore_ascii_case(pattern),
+                    });
+            if !allowed {
+                return false;
+            }
+        }
+
+        let body = resp.take_body();
+        let size = hyper::body::Body::size_hint(&body.0).exact();
+        resp.set_body(body);
+        size.map_or(true, |size| size > 0 && size >= self.min_size)
     }
 }
 
@@ -155,17 +218,25 @@ impl<E: Endpoint> Endpoint for CompressionEndpoint<E> {
         }
 
         // negotiate content-encoding
-        let compress_algo =
-            parse_accept_encoding(req.headers(), &self.algorithms).map(|coding| match coding {
-                ContentCoding::Gzip => CompressionAlgo::GZIP,
-                ContentCoding::Deflate => CompressionAlgo::DEFLATE,
-                ContentCoding::Star | ContentCoding::Brotli => CompressionAlgo::BR,
-            });
-
-        let resp = self.ep.call(req).await?;
+        let available = ALGORITHMS
+            .iter()
+            .filter(|algo| self.algorithms.is_empty() || self.algorithms.contains(*algo))
+            .map(CompressionAlgo::as_str)
+            .collect::<Vec<_>>();
+        let compress_algo = negotiate_encoding(req.headers(), &available)
+            .and_then(|coding| CompressionAlgo::from_str(coding).ok())
+            .filter(|_| req.method() != Method::HEAD);
+
+        let mut resp = self.ep.call(req).await?.into_response();
+        if !self.is_compressible(&mut resp) {
+            return Ok(resp);
+        }
+        vary_accept_encoding(resp.headers_mut());
+
         match compress_algo {
             Some(algo) => {
-                let mut compress = Compress::new(resp, algo);
+                let mut compress =
+                    Compress::new(resp, algo).with_stats_handler(self.stats_handler.clone());
                 if let Some(level) = self.level {
                     compress = compress.with_quality(level);
                 }
@@ -181,7 +252,7 @@ mod tests {
     use tokio::io::AsyncReadExt;
 
     use super::*;
-    use crate::{handler, test::TestClient, EndpointExt};
+    use crate::{endpoint::make_sync, handler, test::TestClient, EndpointExt};
 
     const DATA: &str = "abcdefghijklmnopqrstuvwxyz1234567890";
     const DATA_REV: &str = "0987654321zyxwvutsrqponmlkjihgfedcba";
@@ -251,10 +322,14 @@ mod tests {
             .send()
             .await;
         resp.assert_status_is_ok();
-        resp.assert_header("Content-Encoding", "br");
+        #[cfg(not(feature = "zstd"))]
+        let algo = CompressionAlgo::GZIP;
+        #[cfg(feature = "zstd")]
+        let algo = CompressionAlgo::ZSTD;
+        resp.assert_header("Content-Encoding", algo.as_str());
 
         let mut data = Vec::new();
-        let mut reader = CompressionAlgo::BR.decompress(resp.0.into_body().into_async_read());
+        let mut reader = algo.decompress(resp.0.into_body().into_async_read());
         reader.read_to_end(&mut data).await.unwrap();
         assert_eq!(data, DATA_REV.as_bytes());
     }
@@ -305,4 +380,110 @@ mod tests {
         resp.assert_status_is_ok();
         resp.assert_header("Content-Encoding", "br");
     }
+
+    #[tokio::test]
+    async fn test_on_stats() {
+        let stats = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let ep = index.with(Compression::default().on_stats({
+            let stats = stats.clone();
+            move |s| stats.lock().unwrap().push(s.clone())
+        }));
+        let cli = TestClient::new(ep);
+
+        let body = DATA.repeat(100);
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip")
+            .body(body.clone())
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header("Content-Encoding", "gzip");
+        let compressed = resp.0.into_body().into_vec().await.unwrap();
+
+        let stats = stats.lock().unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].algo, CompressionAlgo::GZIP);
+        assert_eq!(stats[0].input_bytes, body.len() as u64);
+        assert_eq!(stats[0].output_bytes, compressed.len() as u64);
+        assert!(stats[0].ratio() > 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_quality_zero() {
+        let ep = index.with(Compression::default());
+        let cli = TestClient::new(ep);
+
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "br; q=0, gzip ; q=0.5, deflate;q=0.2")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_header("Content-Encoding", "gzip");
+        resp.assert_header("Vary", "accept-encoding");
+
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "*;q=0")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_header_is_not_exist("Content-Encoding");
+        resp.assert_header("Vary", "accept-encoding");
+        resp.assert_text(DATA_REV).await;
+    }
+
+    #[tokio::test]
+    async fn test_skip_responses() {
+        let ep = index.with(
+            Compression::default()
+                .min_size(DATA.len() as u64 + 1)
+                .content_types(["text/*", "application/json"]),
+        );
+        let cli = TestClient::new(ep);
+
+        // smaller than the minimum size
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_header_is_not_exist("Content-Encoding");
+        resp.assert_header_is_not_exist("Vary");
+
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip")
+            .body(DATA.repeat(2))
+            .send()
+            .await;
+        resp.assert_header("Content-Encoding", "gzip");
+
+        // content type is not allowed
+        let ep = make_sync(|_| Response::builder().content_type("image/png").body(DATA))
+            .with(Compression::default().content_types(["text/*"]));
+        TestClient::new(ep)
+            .get("/")
+            .header("Accept-Encoding", "gzip")
+            .send()
+            .await
+            .assert_header_is_not_exist("Content-Encoding");
+
+        // already encoded or no-transform
+        for (name, value) in [
+            ("content-encoding", "br"),
+            ("cache-control", "no-transform"),
+        ] {
+            let ep = make_sync(move |_| Response::builder().header(name, value).body(DATA))
+                .with(Compression::default());
+            TestClient::new(ep)
+                .get("/")
+                .header("Accept-Encoding", "gzip")
+                .send()
+                .await
+                .assert_header_is_not_exist("Vary");
+        }
+    }
 }
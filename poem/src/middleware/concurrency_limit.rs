@@ -0,0 +1,262 @@
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use hyper::body::{Frame, SizeHint};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{
+    body::BoxBody,
+    http::{header, StatusCode},
+    Body, Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+
+/// Middleware for limiting the number of requests that are handled
+/// concurrently, for basic load shedding.
+///
+/// When all the permits are in use, up to `queue` requests wait for a permit,
+/// and the other requests are rejected immediately with
+/// `503 Service Unavailable` and the `Retry-After` header.
+///
+/// The permit is released when the body of the response has been sent (or
+/// the response is dropped), so streaming the body of the response is counted
+/// as well.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{handler, middleware::ConcurrencyLimit, EndpointExt};
+///
+/// #[handler]
+/// fn index() {}
+///
+/// let app = index.with(
+///     ConcurrencyLimit::new(64)
+///         .queue(256)
+///         .retry_after(Duration::from_secs(5)),
+/// );
+/// ```
+pub struct ConcurrencyLimit {
+    max_concurrency: usize,
+    queue: usize,
+    retry_after: Duration,
+}
+
+impl ConcurrencyLimit {
+    /// Create `ConcurrencyLimit` middleware that allows at most
+    /// `max_concurrency` requests to be handled at the same time.
+    pub fn new(max_concurrency: usize) -> Self {
+        Self {
+            max_concurrency,
+            queue: 0,
+            retry_after: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the maximum number of requests that wait for a permit.
+    ///
+    /// Default is `0`, the requests are rejected as soon as all the permits
+    /// are in use.
+    #[must_use]
+    pub fn queue(self, queue: usize) -> Self {
+        Self { queue, ..self }
+    }
+
+    /// Sets the value of the `Retry-After` header of the rejected requests.
+    ///
+    /// Default is `1s`.
+    #[must_use]
+    pub fn retry_after(self, retry_after: Duration) -> Self {
+        Self {
+            retry_after,
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ConcurrencyLimit {
+    type Output = ConcurrencyLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ConcurrencyLimitEndpoint {
+            inner: ep,
+            semaphore: Arc::new(Semaphore::new(self.max_concurrency)),
+            waiting: AtomicUsize::new(0),
+            queue: self.queue,
+            retry_after: self.retry_after.as_secs().max(1),
+        }
+    }
+}
+
+/// Endpoint for ConcurrencyLimit middleware.
+pub struct ConcurrencyLimitEndpoint<E> {
+    inner: E,
+    semaphore: Arc<Semaphore>,
+    waiting: AtomicUsize,
+    queue: usize,
+    retry_after: u64,
+}
+
+impl<E> ConcurrencyLimitEndpoint<E> {
+    fn service_unavailable(&self) -> Response {
+        tracing::debug!("concurrency limit exceeded");
+        Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header(header::RETRY_AFTER, self.retry_after)
+            .finish()
+    }
+}
+
+/// Holds the permit until the body is dropped.
+struct PermitBody {
+    inner: BoxBody,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl hyper::body::Body for PermitBody {
+    type Data = Bytes;
+    type Error = std::io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Decreases the number of waiting requests when dropped, including when the
+/// request is cancelled while waiting.
+struct WaitingGuard<'a>(&'a AtomicUsize);
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for ConcurrencyLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let permit = match self.semaphore.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                if self
+                    .waiting
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |waiting| {
+                        (waiting < self.queue).then_some(waiting + 1)
+                    })
+                    .is_err()
+                {
+                    return Ok(self.service_unavailable());
+                }
+                let _guard = WaitingGuard(&self.waiting);
+                match self.semaphore.clone().acquire_owned().await {
+                    Ok(permit) => permit,
+                    Err(_) => return Ok(self.service_unavailable()),
+                }
+            }
+        };
+
+        let resp = self.inner.call(req).await?.into_response();
+        Ok(resp.map_body(|body| {
+            Body::from(BoxBody::new(PermitBody {
+                inner: body.into(),
+                _permit: permit,
+            }))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::Notify;
+
+    use super::*;
+    use crate::{endpoint::make, EndpointExt};
+
+    #[tokio::test]
+    async fn concurrency_limit() {
+        let notify = Arc::new(Notify::new());
+        let ep = Arc::new(
+            make({
+                let notify = notify.clone();
+                move |_| {
+                    let notify = notify.clone();
+                    async move {
+                        notify.notified().await;
+                    }
+                }
+            })
+            .with(
+                ConcurrencyLimit::new(1)
+                    .queue(1)
+                    .retry_after(Duration::from_secs(5)),
+            ),
+        );
+
+        let first = tokio::spawn({
+            let ep = ep.clone();
+            async move { ep.call(Request::default()).await.unwrap().status() }
+        });
+        let second = tokio::spawn({
+            let ep = ep.clone();
+            async move { ep.call(Request::default()).await.unwrap().status() }
+        });
+        while ep.waiting.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // one request is handled and one is waiting
+        let resp = ep.call(Request::default()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            resp.headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok()),
+            Some("5")
+        );
+
+        notify.notify_one();
+        notify.notify_one();
+        assert_eq!(first.await.unwrap(), StatusCode::OK);
+        assert_eq!(second.await.unwrap(), StatusCode::OK);
+        assert_eq!(ep.waiting.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn permit_held_by_body() {
+        let ep = make(|_| async move { "hello" }).with(ConcurrencyLimit::new(1));
+
+        let resp = ep.call(Request::default()).await.unwrap();
+        assert_eq!(
+            ep.call(Request::default()).await.unwrap().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "hello");
+        assert_eq!(
+            ep.call(Request::default()).await.unwrap().status(),
+            StatusCode::OK
+        );
+    }
+}
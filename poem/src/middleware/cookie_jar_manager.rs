@@ -6,6 +6,14 @@ use crate::{
 };
 
 /// Middleware for CookieJar support.
+///
+/// The cookies are parsed once per request into the
+/// [`CookieJar`](crate::web::cookie::CookieJar), and only the added or removed
+/// cookies are written into the `Set-Cookie` headers of the response. If it is
+/// nested, the outermost one manages the cookies.
+///
+/// The key specified with [`CookieJarManager::with_key`] is used for the
+/// private (encrypted) and signed cookies.
 #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
 #[derive(Default)]
 pub struct CookieJarManager {
@@ -13,7 +21,7 @@ pub struct CookieJarManager {
 }
 
 impl CookieJarManager {
-    /// Creates a new `Compression` middleware.
+    /// Creates a new `CookieJarManager` middleware.
     #[must_use]
     pub fn new() -> Self {
         Self::default()
@@ -59,6 +67,7 @@ impl<E: Endpoint> Endpoint for CookieJarManagerEndpoint<E> {
             cookie_jar.key = self.key.clone();
             req.state_mut().cookie_jar = Some(cookie_jar.clone());
             let mut resp = self.inner.call(req).await?.into_response();
+            crate::web::add_flash_cookie(&cookie_jar, &resp);
             cookie_jar.append_delta_to_headers(resp.headers_mut());
             Ok(resp)
         } else {
@@ -88,6 +97,43 @@ mod tests {
             .assert_status_is_ok();
     }
 
+    #[tokio::test]
+    async fn test_cookie_jar_manager_delta() {
+        #[handler(internal)]
+        async fn index(cookie_jar: &CookieJar) {
+            cookie_jar.add(Cookie::new_with_str("value2", "99"));
+            cookie_jar.remove("value3");
+        }
+
+        let ep = index
+            .with(CookieJarManager::new())
+            .with(CookieJarManager::new());
+        let cli = TestClient::new(ep);
+        let resp = cli
+            .get("/")
+            .header("Cookie", "value1=88; value3=77")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+
+        let mut cookies = resp
+            .0
+            .headers()
+            .get_all("set-cookie")
+            .iter()
+            .map(|value| Cookie::parse(value.to_str().unwrap()).unwrap())
+            .map(|cookie| (cookie.name().to_string(), cookie.value_str().to_string()))
+            .collect::<Vec<_>>();
+        cookies.sort();
+        assert_eq!(
+            cookies,
+            vec![
+                ("value2".to_string(), "99".to_string()),
+                ("value3".to_string(), "".to_string())
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_cookie_jar_manager_with_key() {
         #[handler(internal)]
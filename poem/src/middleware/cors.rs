@@ -21,6 +21,11 @@ use crate::{
 
 /// Middleware for CORS
 ///
+/// The origin of a CORS request is checked, and a preflight request is
+/// answered, before the request is passed to the inner endpoint. Use
+/// [`Cors::prefix_override`] to apply a different configuration to a part of
+/// the routes.
+///
 /// # Errors
 ///
 /// - [`CorsError`]
@@ -28,12 +33,19 @@ use crate::{
 /// # Example
 ///
 /// ```
-/// use poem::{http::Method, middleware::Cors};
+/// use poem::{get, handler, http::Method, middleware::Cors, EndpointExt, Route};
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
 ///
 /// let cors = Cors::new()
 ///     .allow_method(Method::GET)
 ///     .allow_method(Method::POST)
 ///     .allow_credentials(false);
+///
+/// let app = Route::new().at("/", get(index)).with(cors);
 /// ```
 #[derive(Default)]
 #[allow(clippy::type_complexity)]
@@ -46,6 +58,7 @@ pub struct Cors {
     allow_methods: HashSet<Method>,
     expose_headers: HashSet<HeaderName>,
     max_age: i32,
+    overrides: Vec<(String, Cors)>,
 }
 
 impl Cors {
@@ -203,14 +216,42 @@ impl Cors {
         self.max_age = max_age;
         self
     }
-}
 
-impl<E: Endpoint> Middleware<E> for Cors {
-    type Output = CorsEndpoint<E>;
+    /// Use a different configuration for the requests whose path starts with
+    /// the specified prefix, including preflight requests.
+    ///
+    /// The prefix is matched against the path as seen by this middleware, so
+    /// for a middleware applied to a nested route it is relative to the nest
+    /// point. The longest matching prefix wins, and the overrides of `cors`
+    /// itself are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{middleware::Cors, EndpointExt, Route};
+    ///
+    /// let app = Route::new().with(
+    ///     Cors::new()
+    ///         .allow_origin("https://example.com")
+    ///         .prefix_override("/public", Cors::new())
+    ///         .prefix_override(
+    ///             "/partner",
+    ///             Cors::new()
+    ///                 .allow_origin("https://partner.com")
+    ///                 .allow_credentials(true),
+    ///         ),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn prefix_override(mut self, prefix: impl Into<String>, cors: Cors) -> Self {
+        self.overrides.push((prefix.into(), cors));
+        self
+    }
+}
 
-    fn transform(&self, ep: E) -> Self::Output {
-        CorsEndpoint {
-            inner: ep,
+impl Cors {
+    fn policy(&self) -> CorsPolicy {
+        CorsPolicy {
             allow_credentials: self.allow_credentials,
             allow_origins: self.allow_origins.clone(),
             allow_origins_wildcard: self.allow_origins_wildcard.clone(),
@@ -226,10 +267,34 @@ impl<E: Endpoint> Middleware<E> for Cors {
     }
 }
 
+impl<E: Endpoint> Middleware<E> for Cors {
+    type Output = CorsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        let mut overrides = self
+            .overrides
+            .iter()
+            .map(|(prefix, cors)| (prefix.clone(), cors.policy()))
+            .collect::<Vec<_>>();
+        overrides.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()));
+
+        CorsEndpoint {
+            inner: ep,
+            policy: self.policy(),
+            overrides,
+        }
+    }
+}
+
 /// Endpoint for Cors middleware.
-#[allow(clippy::type_complexity)]
 pub struct CorsEndpoint<E> {
     inner: E,
+    policy: CorsPolicy,
+    overrides: Vec<(String, CorsPolicy)>,
+}
+
+#[allow(clippy::type_complexity)]
+struct CorsPolicy {
     allow_credentials: bool,
     allow_origins: HashSet<HeaderValue>,
     allow_origins_wildcard: Vec<WildMatch>,
@@ -243,7 +308,24 @@ pub struct CorsEndpoint<E> {
     max_age: i32,
 }
 
-impl<E: Endpoint> CorsEndpoint<E> {
+fn match_prefix(path: &str, prefix: &str) -> bool {
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/'),
+        None => false,
+    }
+}
+
+impl<E> CorsEndpoint<E> {
+    fn policy(&self, path: &str) -> &CorsPolicy {
+        self.overrides
+            .iter()
+            .find(|(prefix, _)| match_prefix(path, prefix))
+            .map(|(_, policy)| policy)
+            .unwrap_or(&self.policy)
+    }
+}
+
+impl CorsPolicy {
     fn is_valid_origin(&self, origin: &HeaderValue) -> (bool, bool) {
         if self.allow_origins.contains(origin) {
             return (true, false);
@@ -276,6 +358,7 @@ impl<E: Endpoint> CorsEndpoint<E> {
     fn build_preflight_response(
         &self,
         origin: &HeaderValue,
+        request_method: &Method,
         request_headers: Option<&HeaderValue>,
     ) -> Response {
         let mut builder = Response::builder()
@@ -284,22 +367,23 @@ impl<E: Endpoint> CorsEndpoint<E> {
             .header(header::ACCESS_CONTROL_MAX_AGE, self.max_age);
 
         if self.allow_methods.is_empty() {
-            builder = builder.typed_header(
-                [
-                    Method::GET,
-                    Method::POST,
-                    Method::PUT,
-                    Method::DELETE,
-                    Method::HEAD,
-                    Method::OPTIONS,
-                    Method::CONNECT,
-                    Method::PATCH,
-                    Method::TRACE,
-                ]
-                .iter()
-                .cloned()
-                .collect::<AccessControlAllowMethods>(),
-            );
+            let mut methods = vec![
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::DELETE,
+                Method::HEAD,
+                Method::OPTIONS,
+                Method::CONNECT,
+                Method::PATCH,
+                Method::TRACE,
+            ];
+            // the extension methods are allowed too, such as `REPORT`
+            if !methods.contains(request_method) {
+                methods.push(request_method.clone());
+            }
+            builder =
+                builder.typed_header(methods.into_iter().collect::<AccessControlAllowMethods>());
         } else {
             builder = builder.typed_header(self.allow_methods_header.clone());
         }
@@ -321,31 +405,22 @@ impl<E: Endpoint> CorsEndpoint<E> {
         builder.body(())
     }
 
-    fn check_allow_headers<'a>(&self, req: &'a Request) -> (bool, Option<&'a HeaderValue>) {
-        let mut allow_headers = true;
+    fn check_allow_headers(&self, request_headers: Option<&HeaderValue>) -> bool {
+        let request_headers = match request_headers {
+            Some(request_headers) if !self.allow_headers.is_empty() => request_headers,
+            _ => return true,
+        };
 
-        let request_headers = if let Some(request_header) =
-            req.headers().get(header::ACCESS_CONTROL_REQUEST_HEADERS)
-        {
-            if !self.allow_headers.is_empty() {
-                allow_headers = false;
-                if let Ok(s) = request_header.to_str() {
-                    for header in s.split(',') {
-                        if let Ok(header) = HeaderName::from_str(header.trim()) {
-                            if self.allow_headers.contains(&header) {
-                                allow_headers = true;
-                                break;
-                            }
-                        }
+        if let Ok(s) = request_headers.to_str() {
+            for header in s.split(',') {
+                if let Ok(header) = HeaderName::from_str(header.trim()) {
+                    if self.allow_headers.contains(&header) {
+                        return true;
                     }
                 }
             }
-            Some(request_header)
-        } else {
-            None
-        };
-
-        (allow_headers, request_headers)
+        }
+        false
     }
 }
 
@@ -362,35 +437,34 @@ impl<E: Endpoint> Endpoint for CorsEndpoint<E> {
             }
         };
 
-        let (origin_is_allow, vary_header) = self.is_valid_origin(&origin);
+        let policy = self.policy(req.uri().path());
+        let (origin_is_allow, vary_header) = policy.is_valid_origin(&origin);
         if !origin_is_allow {
             return Err(CorsError::OriginNotAllowed.into());
         }
 
         if req.method() == Method::OPTIONS {
-            let allow_method = req
+            let request_method = match req
                 .headers()
                 .get(header::ACCESS_CONTROL_REQUEST_METHOD)
                 .and_then(|value| value.to_str().ok())
                 .and_then(|value| value.parse::<Method>().ok())
-                .map(|method| {
-                    if self.allow_methods.is_empty() {
-                        true
-                    } else {
-                        self.allow_methods.contains(&method)
-                    }
-                });
-            if !matches!(allow_method, Some(true)) {
-                return Err(CorsError::MethodNotAllowed.into());
-            }
-
-            let (allow_headers, request_headers) = self.check_allow_headers(&req);
+            {
+                Some(method)
+                    if policy.allow_methods.is_empty()
+                        || policy.allow_methods.contains(&method) =>
+                {
+                    method
+                }
+                _ => return Err(CorsError::MethodNotAllowed.into()),
+            };
 
-            if !allow_headers {
+            let request_headers = req.headers().get(header::ACCESS_CONTROL_REQUEST_HEADERS);
+            if !policy.check_allow_headers(request_headers) {
                 return Err(CorsError::HeadersNotAllowed.into());
             }
 
-            return Ok(self.build_preflight_response(&origin, request_headers));
+            return Ok(policy.build_preflight_response(&origin, &request_method, request_headers));
         }
 
         let mut resp = self.inner.get_response(req).await;
@@ -398,16 +472,16 @@ impl<E: Endpoint> Endpoint for CorsEndpoint<E> {
         resp.headers_mut()
             .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
 
-        if self.allow_credentials {
+        if policy.allow_credentials {
             resp.headers_mut().insert(
                 header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
                 HeaderValue::from_static("true"),
             );
         }
 
-        if !self.expose_headers.is_empty() {
+        if !policy.expose_headers.is_empty() {
             resp.headers_mut()
-                .typed_insert(self.expose_headers_header.clone());
+                .typed_insert(policy.expose_headers_header.clone());
         }
 
         if vary_header {
@@ -507,6 +581,26 @@ mod tests {
         resp.assert_header(header::VARY, "Origin");
     }
 
+    #[tokio::test]
+    async fn default_cors_extension_method() {
+        let ep = make_sync(|_| "hello").with(Cors::new());
+        let resp = TestClient::new(ep)
+            .options("/")
+            .header(header::ORIGIN, ALLOW_ORIGIN)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "REPORT")
+            .send()
+            .await;
+
+        resp.assert_status_is_ok();
+        resp.assert_header_csv(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            [
+                "GET", "POST", "PUT", "DELETE", "HEAD", "OPTIONS", "CONNECT", "PATCH", "TRACE",
+                "REPORT",
+            ],
+        );
+    }
+
     #[tokio::test]
     async fn allow_origins_fn_1() {
         let ep = make_sync(|_| "hello").with(Cors::new().allow_origins_fn(|_| true));
@@ -724,4 +818,95 @@ mod tests {
         resp.assert_status_is_ok();
         resp.assert_header(header::ACCESS_CONTROL_ALLOW_HEADERS, "content-type");
     }
+
+    #[test]
+    fn test_match_prefix() {
+        assert!(match_prefix("/api", "/api"));
+        assert!(match_prefix("/api/a", "/api"));
+        assert!(match_prefix("/api/a", "/api/"));
+        assert!(!match_prefix("/apis", "/api"));
+        assert!(!match_prefix("/a", "/api"));
+    }
+
+    #[tokio::test]
+    async fn prefix_override() {
+        let ep = make_sync(|_| "hello").with(
+            cors()
+                .prefix_override("/public", Cors::new())
+                .prefix_override("/partner", Cors::new().allow_origin("https://partner.com"))
+                .prefix_override(
+                    "/partner/internal",
+                    Cors::new().allow_origin("https://internal.com"),
+                ),
+        );
+        let cli = TestClient::new(ep);
+
+        get_request(&cli).send().await.assert_status_is_ok();
+        cli.get("/")
+            .header(header::ORIGIN, "https://partner.com")
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        let resp = cli
+            .get("/public/a")
+            .header(header::ORIGIN, "https://abc.com")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "https://abc.com");
+        resp.assert_header_is_not_exist(header::ACCESS_CONTROL_ALLOW_CREDENTIALS);
+
+        let resp = cli
+            .options("/partner/a")
+            .header(header::ORIGIN, "https://partner.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "PUT")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "https://partner.com");
+
+        cli.get("/partner/a")
+            .header(header::ORIGIN, ALLOW_ORIGIN)
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        cli.get("/partner/internal")
+            .header(header::ORIGIN, "https://internal.com")
+            .send()
+            .await
+            .assert_status_is_ok();
+        cli.get("/partners")
+            .header(header::ORIGIN, ALLOW_ORIGIN)
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+
+    #[tokio::test]
+    async fn rejected_before_calling_endpoint() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let ep = make_sync({
+            let calls = calls.clone();
+            move |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                "hello"
+            }
+        })
+        .with(cors());
+        let cli = TestClient::new(ep);
+
+        cli.post("/")
+            .header(header::ORIGIN, "https://foo.com")
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        opt_request(&cli).send().await.assert_status_is_ok();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        get_request(&cli).send().await.assert_status_is_ok();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
 }
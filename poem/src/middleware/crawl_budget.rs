@@ -0,0 +1,349 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    http::{header, HeaderValue, StatusCode},
+    web::{CacheControl, TenantId},
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+
+const KNOWN_BOTS: &[&str] = &[
+    "googlebot",
+    "bingbot",
+    "yandexbot",
+    "baiduspider",
+    "duckduckbot",
+    "slurp",
+    "applebot",
+    "petalbot",
+    "ahrefsbot",
+    "semrushbot",
+    "mj12bot",
+    "dotbot",
+    "gptbot",
+    "ccbot",
+    "bytespider",
+    "facebookexternalhit",
+    "twitterbot",
+];
+
+/// The policy applied to the requests of a bot, see [`CrawlBudget`].
+#[derive(Debug, Clone, Default)]
+pub struct BotPolicy {
+    rate_limit: Option<(u32, Duration)>,
+    cache_control: Option<CacheControl>,
+}
+
+impl BotPolicy {
+    /// Create a `BotPolicy` without limits.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Allows at most `requests` requests in each `period`, the requests can
+    /// be made in bursts.
+    #[must_use]
+    pub fn rate_limit(self, requests: u32, period: Duration) -> Self {
+        Self {
+            rate_limit: Some((requests.max(1), period)),
+            ..self
+        }
+    }
+
+    /// Sets the `Cache-Control` header of the successful responses that do
+    /// not have one.
+    #[must_use]
+    pub fn cache_control(self, cache_control: CacheControl) -> Self {
+        Self {
+            cache_control: Some(cache_control),
+            ..self
+        }
+    }
+}
+
+/// Middleware for applying separate rate limits and cache policies to the
+/// requests from the known bots, which is detected by the `User-Agent`
+/// header.
+///
+/// The requests for `/robots.txt` and the sitemaps (the paths starting with
+/// `/sitemap`) are never limited, so that the crawlers can always discover the
+/// pages. When a bot exceeds its budget, `429 Too Many Requests` is returned
+/// with the `Retry-After` header, which is respected by the major search
+/// engines.
+///
+/// If the tenant of the request is resolved by
+/// [`TenantResolver`](crate::middleware::TenantResolver), each tenant has
+/// separate budgets.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{
+///     endpoint::make_sync,
+///     http::StatusCode,
+///     middleware::{BotPolicy, CrawlBudget},
+///     test::TestClient,
+///     EndpointExt,
+/// };
+///
+/// let ep = make_sync(|_| "hello").with(
+///     CrawlBudget::new()
+///         .default_policy(BotPolicy::new().rate_limit(100, Duration::from_secs(60)))
+///         .bot("Googlebot", BotPolicy::new().rate_limit(1, Duration::from_secs(60))),
+/// );
+/// let cli = TestClient::new(ep);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let googlebot = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+/// let resp = cli.get("/").header("user-agent", googlebot).send().await;
+/// resp.assert_status_is_ok();
+///
+/// let resp = cli.get("/").header("user-agent", googlebot).send().await;
+/// resp.assert_status(StatusCode::TOO_MANY_REQUESTS);
+/// # });
+/// ```
+pub struct CrawlBudget {
+    bots: Vec<(String, Option<BotPolicy>)>,
+    default_policy: BotPolicy,
+}
+
+impl Default for CrawlBudget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrawlBudget {
+    /// Create `CrawlBudget` middleware that detects the well-known search
+    /// engine and SEO crawlers.
+    pub fn new() -> Self {
+        Self {
+            bots: KNOWN_BOTS
+                .iter()
+                .map(|name| (name.to_string(), None))
+                .collect(),
+            default_policy: BotPolicy::default(),
+        }
+    }
+
+    /// Sets the policy of the bots without a specific policy.
+    #[must_use]
+    pub fn default_policy(self, policy: BotPolicy) -> Self {
+        Self {
+            default_policy: policy,
+            ..self
+        }
+    }
+
+    /// Adds a bot whose `User-Agent` contains `name` (case-insensitive), or
+    /// sets the policy of a known bot.
+    #[must_use]
+    pub fn bot(mut self, name: impl AsRef<str>, policy: BotPolicy) -> Self {
+        let name = name.as_ref().to_ascii_lowercase();
+        self.bots.retain(|(bot, _)| *bot != name);
+        self.bots.insert(0, (name, Some(policy)));
+        self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for CrawlBudget {
+    type Output = CrawlBudgetEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        CrawlBudgetEndpoint {
+            inner: ep,
+            bots: self
+                .bots
+                .iter()
+                .map(|(name, policy)| {
+                    (
+                        name.clone(),
+                        Arc::new(
+                            policy
+                                .clone()
+                                .unwrap_or_else(|| self.default_policy.clone()),
+                        ),
+                    )
+                })
+                .collect(),
+            buckets: Default::default(),
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Endpoint for CrawlBudget middleware.
+pub struct CrawlBudgetEndpoint<E> {
+    inner: E,
+    bots: Vec<(String, Arc<BotPolicy>)>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl<E> CrawlBudgetEndpoint<E> {
+    /// Takes a token from the bucket of the bot, returns the time to wait if
+    /// the bucket is empty.
+    fn acquire(&self, name: &str, requests: u32, period: Duration) -> Result<(), Duration> {
+        let now = Instant::now();
+        let rate = requests as f64 / period.as_secs_f64();
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets.entry(name.to_string()).or_insert(Bucket {
+            tokens: requests as f64,
+            updated_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(requests as f64);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+fn is_discovery_path(path: &str) -> bool {
+    path == "/robots.txt" || path.starts_with("/sitemap")
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for CrawlBudgetEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let bot = req.header(header::USER_AGENT).and_then(|user_agent| {
+            let user_agent = user_agent.to_ascii_lowercase();
+            self.bots
+                .iter()
+                .find(|(name, _)| user_agent.contains(name.as_str()))
+        });
+        let (name, policy) = match bot {
+            Some((name, policy)) => (name, policy.clone()),
+            None => return self.inner.call(req).await.map(IntoResponse::into_response),
+        };
+
+        if let Some((requests, period)) = policy.rate_limit {
+            if !is_discovery_path(req.uri().path()) {
+                let key = match req.extensions().get::<TenantId>() {
+                    Some(tenant) => tenant.scoped(name),
+                    None => name.clone(),
+                };
+                if let Err(wait) = self.acquire(&key, requests, period) {
+                    tracing::debug!(bot = %name, "crawl budget exceeded");
+                    let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+                    return Ok(Response::builder()
+                        .status(StatusCode::TOO_MANY_REQUESTS)
+                        .header(header::RETRY_AFTER, retry_after)
+                        .finish());
+                }
+            }
+        }
+
+        let mut resp = self.inner.call(req).await?.into_response();
+        if let Some(cache_control) = &policy.cache_control {
+            if resp.is_success() && !resp.headers().contains_key(header::CACHE_CONTROL) {
+                resp.headers_mut().insert(
+                    header::CACHE_CONTROL,
+                    HeaderValue::from(cache_control.clone()),
+                );
+            }
+        }
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{endpoint::make_sync, test::TestClient, EndpointExt};
+
+    const GOOGLEBOT: &str =
+        "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+    const BINGBOT: &str = "Mozilla/5.0 (compatible; bingbot/2.0; +http://www.bing.com/bingbot.htm)";
+
+    #[tokio::test]
+    async fn rate_limit() {
+        let ep = make_sync(|_| "hello").with(
+            CrawlBudget::new()
+                .default_policy(BotPolicy::new().rate_limit(2, Duration::from_secs(60)))
+                .bot("my-crawler", BotPolicy::new()),
+        );
+        let cli = TestClient::new(ep);
+
+        for _ in 0..2 {
+            cli.get("/")
+                .header(header::USER_AGENT, GOOGLEBOT)
+                .send()
+                .await
+                .assert_status_is_ok();
+        }
+        let resp = cli
+            .get("/")
+            .header(header::USER_AGENT, GOOGLEBOT)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        assert!(resp.0.headers().contains_key(header::RETRY_AFTER));
+
+        // the sitemaps are not limited
+        cli.get("/sitemap.xml")
+            .header(header::USER_AGENT, GOOGLEBOT)
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        // other bots have separate budgets
+        cli.get("/")
+            .header(header::USER_AGENT, BINGBOT)
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        for user_agent in ["Mozilla/5.0 (X11; Linux x86_64)", "My-Crawler/1.0"] {
+            for _ in 0..3 {
+                cli.get("/")
+                    .header(header::USER_AGENT, user_agent)
+                    .send()
+                    .await
+                    .assert_status_is_ok();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn cache_control() {
+        let ep = make_sync(|_| "hello").with(
+            CrawlBudget::new().default_policy(
+                BotPolicy::new().cache_control(
+                    CacheControl::new()
+                        .public()
+                        .max_age(Duration::from_secs(3600)),
+                ),
+            ),
+        );
+        let cli = TestClient::new(ep);
+
+        cli.get("/")
+            .header(header::USER_AGENT, BINGBOT)
+            .send()
+            .await
+            .assert_header(header::CACHE_CONTROL, "public, max-age=3600");
+        cli.get("/")
+            .send()
+            .await
+            .assert_header_is_not_exist(header::CACHE_CONTROL);
+    }
+}
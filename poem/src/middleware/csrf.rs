@@ -1,12 +1,16 @@
 use std::{sync::Arc, time::Duration};
 
 use base64::{engine::general_purpose::STANDARD, Engine};
+#[cfg(feature = "multipart")]
+use bytes::Bytes;
 use libcsrf::{
     AesGcmCsrfProtection, CsrfCookie as RawCsrfCookie, CsrfProtection, CsrfToken as RawCsrfToken,
     UnencryptedCsrfCookie,
 };
 
 use crate::{
+    error::CsrfError,
+    http::{HeaderName, Method},
     middleware::{CookieJarManager, CookieJarManagerEndpoint},
     web::{
         cookie::{Cookie, SameSite},
@@ -17,6 +21,21 @@ use crate::{
 
 /// Middleware for Cross-Site Request Forgery (CSRF) protection.
 ///
+/// By default the token is issued to the handlers as [`CsrfToken`] and
+/// verified by the handlers with [`CsrfVerifier`]. With
+/// [`Csrf::enforce`], the middleware verifies the token of the requests with
+/// the unsafe methods itself, reading it from the `X-CSRF-Token` header or
+/// the `csrf_token` field of the `application/x-www-form-urlencoded` body,
+/// or of the `multipart/form-data` body with the `multipart` feature. The
+/// body is buffered to find the field, up to 1 MiB by default, see
+/// [`Csrf::max_body_size`], so the larger uploads must send the token in the
+/// header. The API routes authenticated by other means can be excluded with
+/// [`Csrf::ignore`].
+///
+/// # Errors
+///
+/// - [`CsrfError`] if the token is enforced and missing or invalid.
+///
 /// # Example
 ///
 /// ```
@@ -83,6 +102,11 @@ pub struct Csrf {
     http_only: bool,
     same_site: Option<SameSite>,
     ttl: Duration,
+    enforce: bool,
+    header_name: HeaderName,
+    form_field: String,
+    max_body_size: usize,
+    ignore: Vec<String>,
 }
 
 impl Default for Csrf {
@@ -94,6 +118,11 @@ impl Default for Csrf {
             http_only: true,
             same_site: Some(SameSite::Strict),
             ttl: Duration::from_secs(24 * 60 * 60),
+            enforce: false,
+            header_name: HeaderName::from_static("x-csrf-token"),
+            form_field: "csrf_token".to_string(),
+            max_body_size: 1024 * 1024,
+            ignore: Vec::new(),
         }
     }
 }
@@ -147,6 +176,61 @@ impl Csrf {
     pub fn ttl(self, ttl: Duration) -> Self {
         Self { ttl, ..self }
     }
+
+    /// Verifies the token of the requests with the unsafe methods, the
+    /// requests without a valid token are rejected with [`CsrfError`].
+    ///
+    /// Default is `false`.
+    #[must_use]
+    pub fn enforce(self, value: bool) -> Self {
+        Self {
+            enforce: value,
+            ..self
+        }
+    }
+
+    /// Sets the header that carries the token. Default is `X-CSRF-Token`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name.
+    #[must_use]
+    pub fn header_name(self, name: impl AsRef<str>) -> Self {
+        Self {
+            header_name: HeaderName::try_from(name.as_ref()).expect("valid header name"),
+            ..self
+        }
+    }
+
+    /// Sets the form field that carries the token. Default is `csrf_token`.
+    #[must_use]
+    pub fn form_field(self, name: impl Into<String>) -> Self {
+        Self {
+            form_field: name.into(),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size of the form body that is buffered to read the
+    /// token field, the larger bodies are rejected with `413 Payload Too
+    /// Large`. Default is 1 MiB.
+    #[must_use]
+    pub fn max_body_size(self, bytes: usize) -> Self {
+        Self {
+            max_body_size: bytes,
+            ..self
+        }
+    }
+
+    /// Skips the verification of the requests under the path prefix, such as
+    /// the API routes authenticated with bearer tokens.
+    #[must_use]
+    pub fn ignore(mut self, path_prefix: impl Into<String>) -> Self {
+        let path_prefix = path_prefix.into();
+        self.ignore
+            .push(path_prefix.trim_end_matches('/').to_string());
+        self
+    }
 }
 
 impl<E: Endpoint> Middleware<E> for Csrf {
@@ -161,6 +245,11 @@ impl<E: Endpoint> Middleware<E> for Csrf {
             http_only: self.http_only,
             same_site: self.same_site,
             ttl: self.ttl,
+            enforce: self.enforce,
+            header_name: self.header_name.clone(),
+            form_field: self.form_field.clone(),
+            max_body_size: self.max_body_size,
+            ignore: Arc::new(self.ignore.clone()),
         })
     }
 }
@@ -175,6 +264,11 @@ pub struct CsrfEndpoint<E> {
     http_only: bool,
     same_site: Option<SameSite>,
     ttl: Duration,
+    enforce: bool,
+    header_name: HeaderName,
+    form_field: String,
+    max_body_size: usize,
+    ignore: Arc<Vec<String>>,
 }
 
 impl<E> CsrfEndpoint<E> {
@@ -197,6 +291,73 @@ impl<E> CsrfEndpoint<E> {
             .generate_token_pair(existing_cookie_bytes.as_ref(), self.ttl.as_secs() as i64)
             .expect("couldn't generate token/cookie pair")
     }
+
+    fn is_ignored(&self, req: &Request) -> bool {
+        let path = req.uri().path();
+        self.ignore
+            .iter()
+            .any(|prefix| match path.strip_prefix(prefix.as_str()) {
+                Some(rest) => rest.is_empty() || rest.starts_with('/'),
+                None => false,
+            })
+    }
+
+    async fn request_token(&self, req: &mut Request) -> Result<Option<String>> {
+        if let Some(token) = req.header(&self.header_name) {
+            return Ok(Some(token.to_string()));
+        }
+
+        let content_type = match req
+            .content_type()
+            .and_then(|content_type| content_type.parse::<mime::Mime>().ok())
+        {
+            Some(content_type) => content_type,
+            None => return Ok(None),
+        };
+        let is_form = content_type.essence_str() == "application/x-www-form-urlencoded";
+        #[cfg(feature = "multipart")]
+        let is_multipart = content_type.essence_str() == mime::MULTIPART_FORM_DATA;
+        #[cfg(not(feature = "multipart"))]
+        let is_multipart = false;
+        if !is_form && !is_multipart {
+            return Ok(None);
+        }
+
+        let data = req.take_body().into_bytes_limit(self.max_body_size).await?;
+        #[cfg(feature = "multipart")]
+        let token = if is_multipart {
+            self.multipart_token(&content_type, data.clone()).await
+        } else {
+            self.form_token(&data)
+        };
+        #[cfg(not(feature = "multipart"))]
+        let token = self.form_token(&data);
+        req.set_body(data);
+        Ok(token)
+    }
+
+    fn form_token(&self, data: &[u8]) -> Option<String> {
+        serde_urlencoded::from_bytes::<Vec<(String, String)>>(data)
+            .ok()?
+            .into_iter()
+            .find(|(name, _)| name == &self.form_field)
+            .map(|(_, value)| value)
+    }
+
+    #[cfg(feature = "multipart")]
+    async fn multipart_token(&self, content_type: &mime::Mime, data: Bytes) -> Option<String> {
+        let boundary = multer::parse_boundary(content_type.as_ref()).ok()?;
+        let mut multipart = multer::Multipart::new(
+            futures_util::stream::once(async move { Ok::<_, std::io::Error>(data) }),
+            boundary,
+        );
+        while let Ok(Some(field)) = multipart.next_field().await {
+            if field.name() == Some(self.form_field.as_str()) {
+                return field.text().await.ok();
+            }
+        }
+        None
+    }
 }
 
 #[async_trait::async_trait]
@@ -210,6 +371,23 @@ impl<E: Endpoint> Endpoint for CsrfEndpoint<E> {
             .and_then(|cookie| STANDARD.decode(cookie.value_str()).ok())
             .and_then(|value| self.protect.parse_cookie(&value).ok());
 
+        let verifier = CsrfVerifier::new(existing_cookie.clone(), self.protect.clone());
+        if self.enforce
+            && !matches!(
+                *req.method(),
+                Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+            )
+            && !self.is_ignored(&req)
+        {
+            let token = self
+                .request_token(&mut req)
+                .await?
+                .ok_or(CsrfError::MissingToken)?;
+            if !verifier.is_valid(&token) {
+                return Err(CsrfError::InvalidToken.into());
+            }
+        }
+
         let (token, cookie) = self.generate_token(existing_cookie.as_ref());
         let csrf_cookie = {
             let mut cookie =
@@ -224,8 +402,7 @@ impl<E: Endpoint> Endpoint for CsrfEndpoint<E> {
         req.cookie().add(csrf_cookie);
         req.extensions_mut()
             .insert(CsrfToken(STANDARD.encode(token.value())));
-        req.extensions_mut()
-            .insert(CsrfVerifier::new(existing_cookie, self.protect.clone()));
+        req.extensions_mut().insert(verifier);
 
         self.inner.call(req).await
     }
@@ -236,7 +413,7 @@ mod tests {
     use http::{header, Method, StatusCode};
 
     use super::*;
-    use crate::{get, handler, EndpointExt, Error, IntoResponse, Result};
+    use crate::{get, handler, post, test::TestClient, EndpointExt, Error, IntoResponse, Result};
 
     const CSRF_TOKEN_NAME: &str = "X-CSRF-Token";
 
@@ -309,4 +486,103 @@ mod tests {
             "invalid token"
         );
     }
+
+    #[tokio::test]
+    async fn enforce() {
+        #[handler(internal)]
+        fn index(token: &CsrfToken, body: String) -> String {
+            format!("{token}|{body}")
+        }
+
+        let app = crate::Route::new()
+            .at("/", get(index).post(index))
+            .at("/api/users", post(index))
+            .with(Csrf::new().enforce(true).ignore("/api/"));
+        let cli = TestClient::new(app);
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        let cookie = resp
+            .0
+            .header(header::SET_COOKIE)
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_string();
+        let text = resp.0.into_body().into_string().await.unwrap();
+        let token = text.trim_end_matches('|').to_string();
+
+        cli.post("/")
+            .header(header::COOKIE, &cookie)
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        cli.post("/")
+            .header(header::COOKIE, &cookie)
+            .header("x-csrf-token", "invalid")
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        let resp = cli
+            .post("/")
+            .header(header::COOKIE, &cookie)
+            .header("x-csrf-token", &token)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+
+        // the form body is still readable by the handler
+        let form =
+            serde_urlencoded::to_string([("csrf_token", token.as_str()), ("a", "1")]).unwrap();
+        let resp = cli
+            .post("/")
+            .header(header::COOKIE, &cookie)
+            .content_type("application/x-www-form-urlencoded")
+            .body(form.clone())
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        let text = resp.0.into_body().into_string().await.unwrap();
+        assert!(text.ends_with(&format!("|{form}")));
+
+        // the form body is limited
+        let form = serde_urlencoded::to_string([("a", "a".repeat(2 * 1024 * 1024))]).unwrap();
+        cli.post("/")
+            .header(header::COOKIE, &cookie)
+            .content_type("application/x-www-form-urlencoded")
+            .body(form)
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+
+        #[cfg(feature = "multipart")]
+        {
+            let form = format!(
+                concat!(
+                    "--X\r\n",
+                    "Content-Disposition: form-data; name=\"a\"\r\n\r\n",
+                    "1\r\n",
+                    "--X\r\n",
+                    "Content-Disposition: form-data; name=\"csrf_token\"\r\n\r\n",
+                    "{}\r\n",
+                    "--X--\r\n"
+                ),
+                token
+            );
+            let resp = cli
+                .post("/")
+                .header(header::COOKIE, &cookie)
+                .content_type("multipart/form-data; boundary=X")
+                .body(form.clone())
+                .send()
+                .await;
+            resp.assert_status_is_ok();
+            let text = resp.0.into_body().into_string().await.unwrap();
+            assert!(text.ends_with(&format!("|{form}")));
+        }
+
+        cli.post("/api/users").send().await.assert_status_is_ok();
+    }
 }
@@ -0,0 +1,305 @@
+use std::{
+    io::{Error as IoError, ErrorKind, Result as IoResult},
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::{
+    error::{ReadBodyError, UnsupportedEncodingError},
+    http::header,
+    web::CompressionAlgo,
+    Body, Endpoint, Middleware, Request, Result,
+};
+
+const DEFAULT_MAX_SIZE: u64 = 8 * 1024 * 1024;
+
+/// The maximum number of content codings applied to a body.
+const MAX_CODINGS: usize = 2;
+
+pin_project_lite::pin_project! {
+    /// Fails with `ReadBodyError::PayloadTooLarge` if the reader produces more
+    /// than `remaining` bytes.
+    struct LimitedReader<R> {
+        #[pin]
+        inner: R,
+        remaining: u64,
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for LimitedReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let this = self.project();
+        let filled = buf.filled().len();
+        let res = this.inner.poll_read(cx, buf);
+        let n = (buf.filled().len() - filled) as u64;
+        if n > *this.remaining {
+            return Poll::Ready(Err(IoError::new(
+                ErrorKind::Other,
+                ReadBodyError::PayloadTooLarge,
+            )));
+        }
+        *this.remaining -= n;
+        res
+    }
+}
+
+/// Middleware for decompressing the request body according to the
+/// `Content-Encoding` header, before the extractors read it.
+///
+/// The supported content codings are `gzip`, `deflate`, `br` and `zstd` (with
+/// the `zstd` feature). After decompressing, the `Content-Encoding` and
+/// `Content-Length` headers are removed from the request.
+///
+/// To protect against decompression bombs, the size of the decompressed body
+/// is limited to 8 MiB by default, see [`Decompression::max_size`], and reading
+/// a larger body fails with [`ReadBodyError::PayloadTooLarge`], which responds
+/// with `413 Payload Too Large`. At most two content codings can be applied to
+/// the body.
+///
+/// # Errors
+///
+/// - [`UnsupportedEncodingError`]
+/// - [`ReadBodyError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, middleware::Decompression, EndpointExt};
+///
+/// #[handler]
+/// fn index(data: String) -> String {
+///     data
+/// }
+///
+/// let app = index.with(Decompression::new().max_size(1024 * 1024));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub struct Decompression {
+    max_size: u64,
+}
+
+impl Default for Decompression {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+}
+
+impl Decompression {
+    /// Creates a new `Decompression` middleware.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum size of the decompressed body in bytes.
+    ///
+    /// Default is 8 MiB.
+    #[must_use]
+    pub fn max_size(self, bytes: u64) -> Self {
+        Self { max_size: bytes }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Decompression {
+    type Output = DecompressionEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        DecompressionEndpoint {
+            inner: ep,
+            max_size: self.max_size,
+        }
+    }
+}
+
+/// Endpoint for Decompression middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub struct DecompressionEndpoint<E> {
+    inner: E,
+    max_size: u64,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for DecompressionEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let mut algorithms = Vec::new();
+        for value in req.headers().get_all(header::CONTENT_ENCODING) {
+            let value = value.to_str().map_err(|_| {
+                UnsupportedEncodingError(String::from_utf8_lossy(value.as_bytes()).into_owned())
+            })?;
+            for coding in value.split(',').map(str::trim) {
+                if coding.is_empty() || coding.eq_ignore_ascii_case("identity") {
+                    continue;
+                }
+                let algo = CompressionAlgo::from_str(&coding.to_ascii_lowercase())
+                    .map_err(|_| UnsupportedEncodingError(coding.to_string()))?;
+                if algorithms.len() == MAX_CODINGS {
+                    return Err(UnsupportedEncodingError(value.to_string()).into());
+                }
+                algorithms.push(algo);
+            }
+        }
+
+        if algorithms.is_empty() {
+            return self.inner.call(req).await;
+        }
+
+        // the codings are listed in the order in which they were applied
+        let mut reader: Pin<Box<dyn AsyncRead + Send>> =
+            Box::pin(req.take_body().into_async_read());
+        for algo in algorithms.iter().rev() {
+            reader = algo.decompress(reader);
+        }
+        let body = Body::from_async_read(LimitedReader {
+            inner: reader,
+            remaining: self.max_size,
+        });
+
+        req.headers_mut().remove(header::CONTENT_ENCODING);
+        req.headers_mut().remove(header::CONTENT_LENGTH);
+        req.set_body(body);
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+    use crate::{handler, test::TestClient, EndpointExt};
+
+    #[handler(internal)]
+    fn index(req: &Request, data: String) -> String {
+        assert!(req.headers().get(header::CONTENT_ENCODING).is_none());
+        data
+    }
+
+    async fn compress(algo: CompressionAlgo, data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        algo.compress(data, None)
+            .read_to_end(&mut output)
+            .await
+            .unwrap();
+        output
+    }
+
+    #[tokio::test]
+    async fn decompress() {
+        let cli = TestClient::new(index.with(Decompression::new()));
+
+        for algo in [
+            CompressionAlgo::BR,
+            CompressionAlgo::DEFLATE,
+            CompressionAlgo::GZIP,
+            #[cfg(feature = "zstd")]
+            CompressionAlgo::ZSTD,
+        ] {
+            let resp = cli
+                .post("/")
+                .header(header::CONTENT_ENCODING, algo.as_str())
+                .body(compress(algo, b"hello").await)
+                .send()
+                .await;
+            resp.assert_status_is_ok();
+            resp.assert_text("hello").await;
+        }
+
+        // multiple codings
+        let data = compress(CompressionAlgo::GZIP, b"hello").await;
+        let data = compress(CompressionAlgo::BR, &data).await;
+        let resp = cli
+            .post("/")
+            .header(header::CONTENT_ENCODING, "gzip, BR")
+            .body(data)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("hello").await;
+
+        // too many codings
+        let mut data_gzip3 = b"hello".to_vec();
+        for _ in 0..3 {
+            data_gzip3 = compress(CompressionAlgo::GZIP, &data_gzip3).await;
+        }
+        cli.post("/")
+            .header(header::CONTENT_ENCODING, "gzip, gzip, gzip")
+            .body(data_gzip3)
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        cli.post("/")
+            .header(header::CONTENT_ENCODING, "compress")
+            .body("hello")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn max_size() {
+        let cli = TestClient::new(index.with(Decompression::new().max_size(1024)));
+        let data = "a".repeat(1024);
+
+        let resp = cli
+            .post("/")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(compress(CompressionAlgo::GZIP, data.as_bytes()).await)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text(&data).await;
+
+        let data = "a".repeat(1024 * 1024);
+        cli.post("/")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(compress(CompressionAlgo::GZIP, data.as_bytes()).await)
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+
+        // the default limit
+        let data = vec![0; 9 * 1024 * 1024];
+        TestClient::new(index.with(Decompression::new()))
+            .post("/")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(compress(CompressionAlgo::GZIP, &data).await)
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn payload_too_large_when_streaming() {
+        #[handler(internal)]
+        async fn read(body: Body) -> Result<()> {
+            let mut data = Vec::new();
+            body.into_async_read()
+                .read_to_end(&mut data)
+                .await
+                .map_err(ReadBodyError::Io)?;
+            Ok(())
+        }
+
+        let data = "a".repeat(4096);
+        TestClient::new(read.with(Decompression::new().max_size(1024)))
+            .post("/")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(compress(CompressionAlgo::GZIP, data.as_bytes()).await)
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
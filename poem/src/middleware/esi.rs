@@ -0,0 +1,438 @@
+use futures_util::{stream, StreamExt, TryStreamExt};
+use regex::Regex;
+
+use crate::{
+    error::{EsiError, ReadBodyError, SubrequestError},
+    http::{header, HeaderMap},
+    web::Subrequest,
+    Body, Endpoint, FromRequest, IntoResponse, Middleware, Request, Response, Result,
+};
+
+const DEFAULT_MAX_SIZE: usize = 1024 * 1024;
+
+/// The `Surrogate-Control` header.
+const SURROGATE_CONTROL: &str = "surrogate-control";
+
+/// Middleware for processing the [Edge-Side Includes](https://www.w3.org/TR/esi-lang/)
+/// in the responses.
+///
+/// Only the responses that opt in with the `Surrogate-Control: content="ESI/1.0"`
+/// header are processed, and the header is removed from them. Since the
+/// subrequests are sent with the credentials of the request, do not opt in
+/// for the responses that contain the user input as is.
+///
+/// The `<esi:include src="..." />` tags are replaced by the bodies of the
+/// internal subrequests to the `src`, so a page can be composed of fragments
+/// that are cached separately. The `<esi:remove>` elements are removed.
+///
+/// If the subrequest fails, the `alt` is requested instead. If it fails too,
+/// the tag is removed when it has `onerror="continue"`, otherwise the
+/// response is an error.
+///
+/// The subrequests are sent with the [`Subrequest`] extractor, so the
+/// [`Subrequests`](crate::middleware::Subrequests) middleware must be applied
+/// outside of this one. The fragments are processed as well, up to the
+/// maximum depth of the subrequests. The size of the response and of each
+/// fragment is limited to 1 MiB by default, see [`Esi::max_size`].
+///
+/// # Errors
+///
+/// - [`EsiError`]
+/// - [`SubrequestError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     get, handler,
+///     middleware::{Esi, Subrequests},
+///     test::TestClient,
+///     web::Html,
+///     EndpointExt, IntoResponse, Route,
+/// };
+///
+/// #[handler]
+/// fn index() -> impl IntoResponse {
+///     Html(r#"<body><esi:include src="/header" />content</body>"#)
+///         .with_header("Surrogate-Control", r#"content="ESI/1.0""#)
+/// }
+///
+/// #[handler]
+/// fn header() -> Html<&'static str> {
+///     Html("<h1>poem</h1>")
+/// }
+///
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .at("/header", get(header))
+///     .with(Esi::new())
+///     .with(Subrequests::new());
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("<body><h1>poem</h1>content</body>").await;
+/// # });
+/// ```
+pub struct Esi {
+    max_concurrency: usize,
+    max_size: usize,
+    tag: Regex,
+    attribute: Regex,
+}
+
+impl Default for Esi {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 8,
+            max_size: DEFAULT_MAX_SIZE,
+            tag: Regex::new(
+                r"(?s)<esi:include\b([^>]*?)/?>(?:</esi:include>)?|<esi:remove>.*?</esi:remove>",
+            )
+            .unwrap(),
+            attribute: Regex::new(r#"([\w-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap(),
+        }
+    }
+}
+
+impl Esi {
+    /// Create `Esi` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the maximum number of the subrequests that are sent at the same
+    /// time for a response.
+    ///
+    /// Default is `8`.
+    #[must_use]
+    pub fn max_concurrency(self, max_concurrency: usize) -> Self {
+        Self {
+            max_concurrency: max_concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size in bytes of the response and of each included
+    /// fragment.
+    ///
+    /// Default is 1 MiB.
+    #[must_use]
+    pub fn max_size(self, max_size: usize) -> Self {
+        Self { max_size, ..self }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Esi {
+    type Output = EsiEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        EsiEndpoint {
+            inner: ep,
+            max_concurrency: self.max_concurrency,
+            max_size: self.max_size,
+            tag: self.tag.clone(),
+            attribute: self.attribute.clone(),
+        }
+    }
+}
+
+/// Endpoint for Esi middleware.
+pub struct EsiEndpoint<E> {
+    inner: E,
+    max_concurrency: usize,
+    max_size: usize,
+    tag: Regex,
+    attribute: Regex,
+}
+
+struct Include {
+    src: String,
+    alt: Option<String>,
+    continue_on_error: bool,
+}
+
+impl<E> EsiEndpoint<E> {
+    fn parse_include(&self, attributes: &str) -> Include {
+        let mut include = Include {
+            src: String::new(),
+            alt: None,
+            continue_on_error: false,
+        };
+        for caps in self.attribute.captures_iter(attributes) {
+            let value = caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .map(|value| value.as_str().replace("&amp;", "&"))
+                .unwrap_or_default();
+            match &caps[1] {
+                "src" => include.src = value,
+                "alt" => include.alt = Some(value),
+                "onerror" => include.continue_on_error = value == "continue",
+                _ => {}
+            }
+        }
+        include
+    }
+}
+
+fn is_enabled(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(SURROGATE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|directive| directive.trim().strip_prefix("content="))
+        .any(|value| {
+            value
+                .trim_matches('"')
+                .split_whitespace()
+                .any(|capability| capability.eq_ignore_ascii_case("ESI/1.0"))
+        })
+}
+
+async fn read_body(body: Body, max_size: usize) -> Result<String> {
+    let data = body
+        .into_bytes_limit(max_size)
+        .await
+        .map_err(|err| match err {
+            ReadBodyError::PayloadTooLarge => EsiError::BodyTooLarge(max_size).into(),
+            err => crate::Error::from(err),
+        })?;
+    Ok(String::from_utf8(data.to_vec()).map_err(ReadBodyError::from)?)
+}
+
+async fn fetch(sub: &Subrequest, uri: &str, max_size: usize) -> Result<String> {
+    let resp = sub.get(uri).await?;
+    if !resp.status().is_success() {
+        return Err(EsiError::IncludeFailed {
+            src: uri.to_string(),
+            status: resp.status(),
+        }
+        .into());
+    }
+    read_body(resp.into_body(), max_size).await
+}
+
+async fn resolve(sub: &Subrequest, include: &Include, max_size: usize) -> Result<String> {
+    let mut res = fetch(sub, &include.src, max_size).await;
+    if let (Err(_), Some(alt)) = (&res, &include.alt) {
+        res = fetch(sub, alt, max_size).await;
+    }
+    match res {
+        Err(_) if include.continue_on_error => Ok(String::new()),
+        res => res,
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for EsiEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let sub = Subrequest::from_request_without_body(&req).await.ok();
+        let resp = self.inner.call(req).await?.into_response();
+        if !is_enabled(resp.headers()) {
+            return Ok(resp);
+        }
+
+        let (mut parts, body) = resp.into_parts();
+        parts.headers.remove(SURROGATE_CONTROL);
+        let html = read_body(body, self.max_size).await?;
+        if !self.tag.is_match(&html) {
+            return Ok(Response::from_parts(parts, html.into()));
+        }
+        let sub = sub.ok_or(SubrequestError::NotEnabled)?;
+
+        let mut texts = Vec::new();
+        let mut includes = Vec::new();
+        let mut last = 0;
+        for caps in self.tag.captures_iter(&html) {
+            let tag = caps.get(0).unwrap();
+            texts.push(&html[last..tag.start()]);
+            includes.push(caps.get(1).map(|attrs| self.parse_include(attrs.as_str())));
+            last = tag.end();
+        }
+
+        let max_size = self.max_size;
+        let fragments = stream::iter(includes.into_iter().map(|item| {
+            let sub = sub.clone();
+            async move {
+                match item {
+                    Some(item) => resolve(&sub, &item, max_size).await,
+                    None => Ok(String::new()),
+                }
+            }
+        }))
+        .buffered(self.max_concurrency)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        let mut output = String::with_capacity(html.len());
+        for (text, fragment) in texts.into_iter().zip(fragments) {
+            output.push_str(text);
+            output.push_str(&fragment);
+        }
+        output.push_str(&html[last..]);
+
+        // the body is changed
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::ETAG);
+        Ok(Response::from_parts(parts, output.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{
+        get, handler,
+        http::StatusCode,
+        middleware::Subrequests,
+        test::TestClient,
+        web::{Html, Query},
+        EndpointExt, IntoResponse, Route,
+    };
+
+    #[derive(Deserialize)]
+    struct Params {
+        body: String,
+    }
+
+    fn esi_html(html: impl Into<String>) -> impl IntoResponse {
+        Html(html.into()).with_header(SURROGATE_CONTROL, r#"max-age=60, content="ESI/1.0""#)
+    }
+
+    #[handler(internal)]
+    fn page(Query(params): Query<Params>) -> impl IntoResponse {
+        esi_html(params.body)
+    }
+
+    #[handler(internal)]
+    fn fragment(req: &Request) -> impl IntoResponse {
+        esi_html(format!("[{}]", req.uri().query().unwrap_or_default()))
+    }
+
+    #[handler(internal)]
+    fn nested() -> impl IntoResponse {
+        esi_html(r#"<esi:include src="/fragment?nested" />"#)
+    }
+
+    #[handler(internal)]
+    fn large() -> impl IntoResponse {
+        esi_html("a".repeat(2048))
+    }
+
+    #[handler(internal)]
+    fn echo(Query(params): Query<Params>) -> Html<String> {
+        Html(params.body)
+    }
+
+    fn app() -> impl Endpoint {
+        Route::new()
+            .at("/page", get(page))
+            .at("/fragment", get(fragment))
+            .at("/nested", get(nested))
+            .at("/large", get(large))
+            .at("/echo", get(echo))
+            .with(Esi::new().max_concurrency(2).max_size(1024))
+            .with(Subrequests::new())
+    }
+
+    #[tokio::test]
+    async fn esi() {
+        let cli = TestClient::new(app());
+
+        cli.get("/page")
+            .query(
+                "body",
+                &r#"a<esi:include src="/fragment?1&amp;2"/>b<esi:include src='/nested'></esi:include>c<esi:remove>removed</esi:remove>d"#,
+            )
+            .send()
+            .await
+            .assert_text("a[1&2]b[nested]cd")
+            .await;
+
+        cli.get("/page")
+            .query(
+                "body",
+                &r#"a<esi:include src="/missing" alt="/fragment?alt" />b<esi:include src="/missing" onerror="continue" />c"#,
+            )
+            .send()
+            .await
+            .assert_text("a[alt]bc")
+            .await;
+
+        cli.get("/page")
+            .query("body", &r#"<esi:include src="/missing" />"#)
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+        // the fragment is too large
+        cli.get("/page")
+            .query("body", &r#"<esi:include src="/large" />"#)
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        cli.get("/large")
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn opt_in() {
+        let cli = TestClient::new(app());
+
+        let resp = cli
+            .get("/echo")
+            .query("body", &r#"<esi:include src="/fragment" />"#)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text(r#"<esi:include src="/fragment" />"#).await;
+
+        let resp = cli
+            .get("/page")
+            .query("body", &r#"<esi:include src="/fragment" />"#)
+            .send()
+            .await;
+        resp.assert_header_is_not_exist(SURROGATE_CONTROL);
+        resp.assert_text("[]").await;
+    }
+
+    #[test]
+    fn enabled() {
+        let headers = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(SURROGATE_CONTROL, value.parse().unwrap());
+            headers
+        };
+        assert!(is_enabled(&headers(r#"content="ESI/1.0""#)));
+        assert!(is_enabled(&headers(
+            r#"no-store, content="ESI/1.0 ESI-Inline/1.0""#
+        )));
+        assert!(!is_enabled(&headers("no-store")));
+        assert!(!is_enabled(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn not_enabled() {
+        let cli = TestClient::new(Route::new().at("/page", get(page)).with(Esi::new()));
+        cli.get("/page")
+            .query("body", &"<p>no tags</p>")
+            .send()
+            .await
+            .assert_text("<p>no tags</p>")
+            .await;
+        cli.get("/page")
+            .query("body", &r#"<esi:include src="/fragment" />"#)
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
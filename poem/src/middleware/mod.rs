@@ -1,29 +1,61 @@
 //! Commonly used middleware.
 
+mod access_log;
 mod add_data;
 mod catch_panic;
 #[cfg(feature = "compression")]
 mod compression;
+mod concurrency_limit;
 #[cfg(feature = "cookie")]
 mod cookie_jar_manager;
 mod cors;
+mod crawl_budget;
 #[cfg(feature = "csrf")]
 mod csrf;
+#[cfg(feature = "compression")]
+mod decompression;
+mod esi;
 mod force_https;
 mod normalize_path;
 #[cfg(feature = "opentelemetry")]
 mod opentelemetry_metrics;
 #[cfg(feature = "opentelemetry")]
 mod opentelemetry_tracing;
+#[cfg(feature = "prometheus")]
+mod prometheus_metrics;
 mod propagate_header;
+mod rate_limit;
+mod redirect_rules;
+#[cfg(feature = "redis-rate-limit")]
+mod redis_rate_limit;
+mod request_body_limit;
+#[cfg(feature = "request-id")]
+mod request_id;
+mod request_limits;
+#[cfg(feature = "rustls")]
+mod require_trust_domain;
+#[cfg(feature = "rhai")]
+mod rhai_script;
+mod security_headers;
 mod sensitive_header;
 mod set_header;
+#[cfg(feature = "signing")]
+mod sign_response;
 mod size_limit;
+mod subrequests;
+mod tenant_resolver;
+mod timeout;
+#[cfg(feature = "introspection")]
+mod token_introspection;
 #[cfg(feature = "tokio-metrics")]
 mod tokio_metrics_mw;
 #[cfg(feature = "tower-compat")]
 mod tower_compat;
 mod tracing_mw;
+#[cfg(feature = "server")]
+mod upgrade_registry;
+#[cfg(feature = "signing")]
+mod verify_signature;
 
 #[cfg(feature = "compression")]
 pub use self::compression::{Compression, CompressionEndpoint};
@@ -31,25 +63,69 @@ pub use self::compression::{Compression, CompressionEndpoint};
 pub use self::cookie_jar_manager::{CookieJarManager, CookieJarManagerEndpoint};
 #[cfg(feature = "csrf")]
 pub use self::csrf::{Csrf, CsrfEndpoint};
+#[cfg(feature = "compression")]
+pub use self::decompression::{Decompression, DecompressionEndpoint};
 #[cfg(feature = "opentelemetry")]
-pub use self::opentelemetry_metrics::{OpenTelemetryMetrics, OpenTelemetryMetricsEndpoint};
+pub use self::opentelemetry_metrics::{OpenTelemetryMetrics, OpenTelemetryMetricsEndpoint, Slo};
 #[cfg(feature = "opentelemetry")]
-pub use self::opentelemetry_tracing::{OpenTelemetryTracing, OpenTelemetryTracingEndpoint};
+pub use self::opentelemetry_tracing::{
+    OpenTelemetryTracing, OpenTelemetryTracingEndpoint, TraceSampling,
+};
+#[cfg(feature = "prometheus")]
+pub use self::prometheus_metrics::{PrometheusMetrics, PrometheusMetricsEndpoint};
+#[cfg(feature = "redis-rate-limit")]
+pub use self::redis_rate_limit::RedisRateLimitStore;
+#[cfg(feature = "request-id")]
+pub use self::request_id::{SetRequestId, SetRequestIdEndpoint};
+#[cfg(feature = "rustls")]
+pub use self::require_trust_domain::{RequireTrustDomain, RequireTrustDomainEndpoint};
+#[cfg(feature = "rhai")]
+pub use self::rhai_script::{RhaiScript, RhaiScriptEndpoint};
+#[cfg(feature = "signing")]
+pub use self::sign_response::{SignResponse, SignResponseEndpoint, SigningKey};
+#[cfg(feature = "introspection")]
+pub use self::token_introspection::{TokenInfo, TokenIntrospection, TokenIntrospectionEndpoint};
 #[cfg(feature = "tokio-metrics")]
 pub use self::tokio_metrics_mw::{TokioMetrics, TokioMetricsEndpoint};
 #[cfg(feature = "tower-compat")]
-pub use self::tower_compat::TowerLayerCompatExt;
+pub use self::tower_compat::{TowerLayerCompat, TowerLayerCompatExt};
+#[cfg(feature = "server")]
+pub use self::upgrade_registry::{
+    UpgradeHandler, UpgradeRegistry, UpgradeRegistryEndpoint, UpgradeRequest,
+};
+#[cfg(feature = "signing")]
+pub use self::verify_signature::{
+    KeyResolver, TenantKeyResolver, VerifiedSignature, VerifySignature, VerifySignatureEndpoint,
+    VerifyingKey,
+};
 pub use self::{
+    access_log::{AccessLog, AccessLogEndpoint},
     add_data::{AddData, AddDataEndpoint},
     catch_panic::{CatchPanic, CatchPanicEndpoint, PanicHandler},
+    concurrency_limit::{ConcurrencyLimit, ConcurrencyLimitEndpoint},
     cors::{Cors, CorsEndpoint},
+    crawl_budget::{BotPolicy, CrawlBudget, CrawlBudgetEndpoint},
+    esi::{Esi, EsiEndpoint},
     force_https::ForceHttps,
     normalize_path::{NormalizePath, NormalizePathEndpoint, TrailingSlash},
     propagate_header::{PropagateHeader, PropagateHeaderEndpoint},
+    rate_limit::{
+        MemoryRateLimitStore, RateLimit, RateLimitAlgorithm, RateLimitDecision, RateLimitEndpoint,
+        RateLimitPolicy, RateLimitStore,
+    },
+    redirect_rules::{RedirectRule, RedirectRules, RedirectRulesEndpoint},
+    request_body_limit::{RequestBodyLimit, RequestBodyLimitEndpoint},
+    request_limits::{RequestLimits, RequestLimitsEndpoint},
+    security_headers::{
+        FrameOptions, FrameOptionsEndpoint, FrameOrigins, SecurityHeaders, SecurityHeadersEndpoint,
+    },
     sensitive_header::{SensitiveHeader, SensitiveHeaderEndpoint},
     set_header::{SetHeader, SetHeaderEndpoint},
     size_limit::{SizeLimit, SizeLimitEndpoint},
-    tracing_mw::{Tracing, TracingEndpoint},
+    subrequests::{Subrequests, SubrequestsEndpoint},
+    tenant_resolver::{TenantResolver, TenantResolverEndpoint},
+    timeout::{Timeout, TimeoutEndpoint},
+    tracing_mw::{Tracing, TracingEndpoint, TracingWithSpan},
 };
 use crate::endpoint::Endpoint;
 
@@ -1,20 +1,191 @@
-use std::time::Instant;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
+use futures_util::TryStreamExt;
 use libopentelemetry::{
     global,
-    metrics::{Counter, Histogram, Unit},
-    Key,
+    metrics::{Counter, Histogram, ObservableGauge, Unit, UpDownCounter},
+    Key, KeyValue,
 };
 use opentelemetry_semantic_conventions::trace;
+use parking_lot::{Mutex, RwLock};
+
+use crate::{
+    http::header, route::PathPattern, Body, Endpoint, IntoResponse, Middleware, Request, Response,
+    Result,
+};
 
-use crate::{route::PathPattern, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+const HTTP_PATH_PATTERN: Key = Key::from_static_str("http.path_pattern");
+const SLO_BUCKETS: u32 = 60;
+
+/// A service level objective of a route, see
+/// [`OpenTelemetryMetrics::slo`].
+///
+/// A request is counted as bad if it fails with a server error (`5xx`), or
+/// takes longer than the latency threshold if one is set.
+#[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry")))]
+#[derive(Debug, Clone, Copy)]
+pub struct Slo {
+    objective: f64,
+    latency_threshold: Option<Duration>,
+    window: Duration,
+}
+
+impl Slo {
+    /// Create an `Slo` with the target ratio of good requests, such as
+    /// `0.999`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `objective` is not between `0` and `1` (exclusive).
+    pub fn new(objective: f64) -> Self {
+        assert!(
+            objective > 0.0 && objective < 1.0,
+            "the objective must be between 0 and 1"
+        );
+        Self {
+            objective,
+            latency_threshold: None,
+            window: Duration::from_secs(60 * 60),
+        }
+    }
+
+    /// Requests that take longer than `threshold` are counted as bad.
+    #[must_use]
+    pub fn latency_threshold(self, threshold: Duration) -> Self {
+        Self {
+            latency_threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    /// Sets the sliding window over which the burn rate is calculated,
+    /// default to `1 hour`.
+    #[must_use]
+    pub fn window(self, window: Duration) -> Self {
+        Self { window, ..self }
+    }
+}
+
+struct SloBucket {
+    start: Instant,
+    total: u64,
+    bad: u64,
+}
+
+struct SloTracker {
+    slo: Slo,
+    buckets: Mutex<VecDeque<SloBucket>>,
+}
+
+impl SloTracker {
+    fn new(slo: Slo) -> Self {
+        Self {
+            slo,
+            buckets: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn evict(&self, buckets: &mut VecDeque<SloBucket>, now: Instant) {
+        while let Some(bucket) = buckets.front() {
+            if now.saturating_duration_since(bucket.start) < self.slo.window {
+                break;
+            }
+            buckets.pop_front();
+        }
+    }
+
+    fn record(&self, now: Instant, elapsed: Duration, server_error: bool) {
+        let bad = server_error
+            || matches!(self.slo.latency_threshold, Some(threshold) if elapsed > threshold);
+        let bucket_len = self.slo.window / SLO_BUCKETS;
+
+        let mut buckets = self.buckets.lock();
+        self.evict(&mut buckets, now);
+        match buckets.back_mut() {
+            Some(bucket) if now.saturating_duration_since(bucket.start) < bucket_len => {
+                bucket.total += 1;
+                bucket.bad += bad as u64;
+            }
+            _ => buckets.push_back(SloBucket {
+                start: now,
+                total: 1,
+                bad: bad as u64,
+            }),
+        }
+    }
+
+    /// Returns the ratio of bad requests in the window to the error budget.
+    fn burn_rate(&self, now: Instant) -> f64 {
+        let mut buckets = self.buckets.lock();
+        self.evict(&mut buckets, now);
+        let (total, bad) = buckets.iter().fold((0, 0), |(total, bad), bucket| {
+            (total + bucket.total, bad + bucket.bad)
+        });
+        if total == 0 {
+            return 0.0;
+        }
+        (bad as f64 / total as f64) / (1.0 - self.slo.objective)
+    }
+}
+
+type SloTrackers = Arc<RwLock<HashMap<String, Arc<SloTracker>>>>;
 
 /// Middleware for metrics with OpenTelemetry.
+///
+/// The following metrics are recorded:
+///
+/// - `poem_requests_count`: the number of requests, by method, route and
+///   status.
+/// - `poem_errors_count`: the number of failed requests, by method, route
+///   and status.
+/// - `poem_request_duration_ms`: the histogram of request durations, by
+///   method, route and status.
+/// - `poem_active_requests`: the number of requests in progress, by method.
+/// - `poem_request_body_size_bytes`: the histogram of request body sizes, by
+///   method and route.
+/// - `poem_slo_burn_rate`: the rate at which each route with an [`Slo`]
+///   consumes its error budget, by route. A value of `1` means the budget is
+///   used up exactly at the end of the window.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{
+///     get, handler,
+///     middleware::{OpenTelemetryMetrics, Slo},
+///     EndpointExt, Route,
+/// };
+///
+/// #[handler]
+/// fn user() -> &'static str {
+///     "user"
+/// }
+///
+/// let app = Route::new().at("/users/:id", get(user)).with(
+///     OpenTelemetryMetrics::new().slo(
+///         "/users/:id",
+///         Slo::new(0.999).latency_threshold(Duration::from_millis(300)),
+///     ),
+/// );
+/// ```
 #[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry")))]
 pub struct OpenTelemetryMetrics {
     request_count: Counter<u64>,
     error_count: Counter<u64>,
     duration: Histogram<f64>,
+    active_requests: UpDownCounter<i64>,
+    request_body_size: Histogram<u64>,
+    slos: SloTrackers,
+    _slo_burn_rate: ObservableGauge<f64>,
 }
 
 impl Default for OpenTelemetryMetrics {
@@ -27,6 +198,23 @@ impl OpenTelemetryMetrics {
     /// Create `OpenTelemetryMetrics` middleware with `meter`.
     pub fn new() -> Self {
         let meter = global::meter("poem");
+        let slos = SloTrackers::default();
+        let slo_burn_rate = meter
+            .f64_observable_gauge("poem_slo_burn_rate")
+            .with_description("error budget burn rate per route with a service level objective")
+            .with_callback({
+                let slos = slos.clone();
+                move |observer| {
+                    let now = Instant::now();
+                    for (path_pattern, tracker) in slos.read().iter() {
+                        observer.observe(
+                            tracker.burn_rate(now),
+                            &[HTTP_PATH_PATTERN.string(path_pattern.clone())],
+                        );
+                    }
+                }
+            })
+            .init();
         Self {
             request_count: meter
                 .u64_counter("poem_requests_count")
@@ -43,8 +231,29 @@ impl OpenTelemetryMetrics {
                     "request duration histogram (in milliseconds, since start of service)",
                 )
                 .init(),
+            active_requests: meter
+                .i64_up_down_counter("poem_active_requests")
+                .with_description("number of requests in progress")
+                .init(),
+            request_body_size: meter
+                .u64_histogram("poem_request_body_size_bytes")
+                .with_unit(Unit::new("bytes"))
+                .with_description("request body size histogram per route (in bytes)")
+                .init(),
+            slos,
+            _slo_burn_rate: slo_burn_rate,
         }
     }
+
+    /// Sets the service level objective of the route with the specified path
+    /// pattern, such as `/users/:id`, to record its burn rate.
+    #[must_use]
+    pub fn slo(self, path_pattern: impl Into<String>, slo: Slo) -> Self {
+        self.slos
+            .write()
+            .insert(path_pattern.into(), Arc::new(SloTracker::new(slo)));
+        self
+    }
 }
 
 impl<E: Endpoint> Middleware<E> for OpenTelemetryMetrics {
@@ -55,6 +264,9 @@ impl<E: Endpoint> Middleware<E> for OpenTelemetryMetrics {
             request_count: self.request_count.clone(),
             error_count: self.error_count.clone(),
             duration: self.duration.clone(),
+            active_requests: self.active_requests.clone(),
+            request_body_size: self.request_body_size.clone(),
+            slos: self.slos.clone(),
             inner: ep,
         }
     }
@@ -66,26 +278,83 @@ pub struct OpenTelemetryMetricsEndpoint<E> {
     request_count: Counter<u64>,
     error_count: Counter<u64>,
     duration: Histogram<f64>,
+    active_requests: UpDownCounter<i64>,
+    request_body_size: Histogram<u64>,
+    slos: SloTrackers,
     inner: E,
 }
 
+/// Decrements the active requests when the request is completed or
+/// cancelled.
+struct ActiveRequestGuard<'a> {
+    counter: &'a UpDownCounter<i64>,
+    labels: [KeyValue; 1],
+}
+
+impl<'a> ActiveRequestGuard<'a> {
+    fn new(counter: &'a UpDownCounter<i64>, labels: [KeyValue; 1]) -> Self {
+        counter.add(1, &labels);
+        Self { counter, labels }
+    }
+}
+
+impl Drop for ActiveRequestGuard<'_> {
+    fn drop(&mut self) {
+        self.counter.add(-1, &self.labels);
+    }
+}
+
 #[async_trait::async_trait]
 impl<E: Endpoint> Endpoint for OpenTelemetryMetricsEndpoint<E> {
     type Output = Response;
 
-    async fn call(&self, req: Request) -> Result<Self::Output> {
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
         let mut labels = Vec::with_capacity(3);
         labels.push(trace::HTTP_REQUEST_METHOD.string(req.method().to_string()));
-        labels.push(trace::URL_FULL.string(req.original_uri().to_string()));
+        let _active = ActiveRequestGuard::new(&self.active_requests, [labels[0].clone()]);
+
+        // use the `Content-Length` header if present, otherwise count the bytes
+        // read from the body
+        let content_length = req
+            .header(header::CONTENT_LENGTH)
+            .and_then(|value| value.parse::<u64>().ok());
+        let body_size = Arc::new(AtomicU64::new(0));
+        if content_length.is_none() {
+            let counter = body_size.clone();
+            let body = req.take_body().into_bytes_stream().inspect_ok(move |data| {
+                counter.fetch_add(data.len() as u64, Ordering::Relaxed);
+            });
+            req.set_body(Body::from_bytes_stream(body));
+        }
+        let mut body_size_labels = vec![labels[0].clone()];
 
         let s = Instant::now();
         let res = self.inner.call(req).await.map(IntoResponse::into_response);
         let elapsed = s.elapsed();
 
+        let path_pattern = match &res {
+            Ok(resp) => resp.data::<PathPattern>(),
+            Err(err) => err.data::<PathPattern>(),
+        };
+        if let Some(path_pattern) = path_pattern {
+            body_size_labels.push(HTTP_PATH_PATTERN.string(path_pattern.0.to_string()));
+
+            if let Some(tracker) = self.slos.read().get(&*path_pattern.0) {
+                let status = match &res {
+                    Ok(resp) => resp.status(),
+                    Err(err) => err.status(),
+                };
+                tracker.record(Instant::now(), elapsed, status.is_server_error());
+            }
+        }
+        self.request_body_size.record(
+            content_length.unwrap_or_else(|| body_size.load(Ordering::Relaxed)),
+            &body_size_labels,
+        );
+
         match &res {
             Ok(resp) => {
                 if let Some(path_pattern) = resp.data::<PathPattern>() {
-                    const HTTP_PATH_PATTERN: Key = Key::from_static_str("http.path_pattern");
                     labels.push(HTTP_PATH_PATTERN.string(path_pattern.0.to_string()));
                 }
 
@@ -93,13 +362,11 @@ impl<E: Endpoint> Endpoint for OpenTelemetryMetricsEndpoint<E> {
             }
             Err(err) => {
                 if let Some(path_pattern) = err.data::<PathPattern>() {
-                    const HTTP_PATH_PATTERN: Key = Key::from_static_str("http.path_pattern");
                     labels.push(HTTP_PATH_PATTERN.string(path_pattern.0.to_string()));
                 }
 
                 labels.push(trace::HTTP_RESPONSE_STATUS_CODE.i64(err.status().as_u16() as i64));
                 self.error_count.add(1, &labels);
-                labels.push(trace::EXCEPTION_MESSAGE.string(err.to_string()));
             }
         }
 
@@ -110,3 +377,38 @@ impl<E: Endpoint> Endpoint for OpenTelemetryMetricsEndpoint<E> {
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slo_burn_rate() {
+        let tracker = SloTracker::new(
+            Slo::new(0.9)
+                .latency_threshold(Duration::from_millis(100))
+                .window(Duration::from_secs(60)),
+        );
+        let start = Instant::now();
+        assert_eq!(tracker.burn_rate(start), 0.0);
+
+        tracker.record(start, Duration::from_millis(10), true);
+        tracker.record(start, Duration::from_millis(200), false);
+        for i in 1..=8 {
+            tracker.record(
+                start + Duration::from_secs(i),
+                Duration::from_millis(10),
+                false,
+            );
+        }
+        assert!((tracker.burn_rate(start + Duration::from_secs(10)) - 2.0).abs() < 1e-9);
+
+        // the bad requests are out of the window
+        tracker.record(
+            start + Duration::from_secs(61),
+            Duration::from_millis(10),
+            false,
+        );
+        assert_eq!(tracker.burn_rate(start + Duration::from_secs(61)), 0.0);
+    }
+}
@@ -1,23 +1,138 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
 
 use libopentelemetry::{
     global,
-    propagation::Extractor,
-    trace::{FutureExt, Span, SpanKind, TraceContextExt, Tracer},
+    propagation::{Extractor, Injector},
+    trace::{
+        FutureExt, SamplingDecision, SamplingResult, Span, SpanKind, SpanRef, TraceContextExt,
+        Tracer,
+    },
     Context, Key,
 };
 use opentelemetry_semantic_conventions::{resource, trace};
 
 use crate::{
+    http::{HeaderName, HeaderValue, StatusCode},
     route::PathPattern,
     web::{headers::HeaderMapExt, RealIp},
     Endpoint, FromRequest, IntoResponse, Middleware, Request, Response, Result,
 };
 
+/// The sampling rule of a route group, see
+/// [`OpenTelemetryTracing::sampling`].
+///
+/// A request is sampled if it fails with a server error (`5xx`), takes
+/// longer than the slow threshold, or otherwise with the probability of the
+/// sampling ratio.
+#[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry")))]
+#[derive(Debug, Clone, Copy)]
+pub struct TraceSampling {
+    ratio: f64,
+    slow_threshold: Option<Duration>,
+    server_errors: bool,
+}
+
+impl TraceSampling {
+    /// Create a `TraceSampling` that samples the ratio of the requests which
+    /// are neither failed nor slow, such as `0.01`.
+    pub fn new(ratio: f64) -> Self {
+        Self {
+            ratio,
+            slow_threshold: None,
+            server_errors: true,
+        }
+    }
+
+    /// Always sample the requests that take longer than `threshold`.
+    #[must_use]
+    pub fn slow_threshold(self, threshold: Duration) -> Self {
+        Self {
+            slow_threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    /// Specifies whether to always sample the requests that fail with a
+    /// server error, default to `true`.
+    #[must_use]
+    pub fn server_errors(self, enabled: bool) -> Self {
+        Self {
+            server_errors: enabled,
+            ..self
+        }
+    }
+
+    fn should_sample(&self, status: StatusCode, elapsed: Duration, sample: f64) -> bool {
+        (self.server_errors && status.is_server_error())
+            || matches!(self.slow_threshold, Some(threshold) if elapsed > threshold)
+            || sample < self.ratio
+    }
+}
+
+struct SamplingRules {
+    default: TraceSampling,
+    groups: Vec<(String, TraceSampling)>,
+}
+
+impl SamplingRules {
+    /// Returns the rule of the longest route group that contains the path.
+    fn find(&self, path: &str) -> &TraceSampling {
+        self.groups
+            .iter()
+            .filter(|(prefix, _)| {
+                let prefix = prefix.trim_end_matches('/');
+                match path.strip_prefix(prefix) {
+                    Some(rest) => rest.is_empty() || rest.starts_with('/'),
+                    None => false,
+                }
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, rule)| rule)
+            .unwrap_or(&self.default)
+    }
+}
+
 /// Middleware for tracing with OpenTelemetry.
+///
+/// The trace context and the baggage of the incoming request, such as the
+/// `traceparent` and `baggage` headers, are extracted with the global
+/// propagator and attached to the context of the inner endpoint. Use
+/// [`propagate_response`](Self::propagate_response) to inject the context of
+/// the server span into the response headers.
+///
+/// # Sampling
+///
+/// By default, the sampling decision is made by the sampler of the tracer
+/// when the request starts. With [`sampling`](Self::sampling) and
+/// [`sampling_group`](Self::sampling_group), the decision is made after the
+/// response is produced, so failed and slow requests can always be kept while
+/// only a fraction of the others are recorded. Requests whose remote parent
+/// is sampled are always sampled.
+///
+/// Because the span is only created once the decision is made, the spans
+/// created by the inner endpoint are not children of it.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::middleware::{OpenTelemetryTracing, TraceSampling};
+///
+/// fn tracing<T>(tracer: T) -> OpenTelemetryTracing<T> {
+///     OpenTelemetryTracing::new(tracer)
+///         .sampling(TraceSampling::new(0.01).slow_threshold(Duration::from_millis(500)))
+///         .sampling_group("/api/admin", TraceSampling::new(1.0))
+/// }
+/// ```
 #[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry")))]
 pub struct OpenTelemetryTracing<T> {
     tracer: Arc<T>,
+    sampling: Option<Arc<SamplingRules>>,
+    propagate_response: bool,
 }
 
 impl<T> OpenTelemetryTracing<T> {
@@ -25,8 +140,62 @@ impl<T> OpenTelemetryTracing<T> {
     pub fn new(tracer: T) -> Self {
         Self {
             tracer: Arc::new(tracer),
+            sampling: None,
+            propagate_response: false,
+        }
+    }
+
+    /// Specifies whether to inject the context of the server span into the
+    /// headers of the response with the global propagator, such as
+    /// `traceparent`, default to `false`.
+    ///
+    /// The context is not injected into the responses of the errors.
+    #[must_use]
+    pub fn propagate_response(self, enabled: bool) -> Self {
+        Self {
+            propagate_response: enabled,
+            ..self
+        }
+    }
+
+    /// Sets the sampling rule of the requests that do not belong to any
+    /// route group, default to sampling all requests.
+    #[must_use]
+    pub fn sampling(self, rule: TraceSampling) -> Self {
+        let groups = self.take_groups();
+        Self {
+            sampling: Some(Arc::new(SamplingRules {
+                default: rule,
+                groups,
+            })),
+            ..self
+        }
+    }
+
+    /// Sets the sampling rule of the route group with the specified prefix,
+    /// such as `/api/admin`, which is matched against the path pattern of
+    /// the route.
+    #[must_use]
+    pub fn sampling_group(self, prefix: impl Into<String>, rule: TraceSampling) -> Self {
+        let default = self
+            .sampling
+            .as_ref()
+            .map(|rules| rules.default)
+            .unwrap_or_else(|| TraceSampling::new(1.0));
+        let mut groups = self.take_groups();
+        groups.push((prefix.into(), rule));
+        Self {
+            sampling: Some(Arc::new(SamplingRules { default, groups })),
+            ..self
         }
     }
+
+    fn take_groups(&self) -> Vec<(String, TraceSampling)> {
+        self.sampling
+            .as_ref()
+            .map(|rules| rules.groups.clone())
+            .unwrap_or_default()
+    }
 }
 
 impl<T, E> Middleware<E> for OpenTelemetryTracing<T>
@@ -40,6 +209,8 @@ where
     fn transform(&self, ep: E) -> Self::Output {
         OpenTelemetryTracingEndpoint {
             tracer: self.tracer.clone(),
+            sampling: self.sampling.clone(),
+            propagate_response: self.propagate_response,
             inner: ep,
         }
     }
@@ -49,6 +220,8 @@ where
 #[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry")))]
 pub struct OpenTelemetryTracingEndpoint<T, E> {
     tracer: Arc<T>,
+    sampling: Option<Arc<SamplingRules>>,
+    propagate_response: bool,
     inner: E,
 }
 
@@ -67,6 +240,19 @@ impl<'a> Extractor for HeaderExtractor<'a> {
     }
 }
 
+struct HeaderInjector<'a>(&'a mut http::HeaderMap);
+
+impl<'a> Injector for HeaderInjector<'a> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl<T, E> Endpoint for OpenTelemetryTracingEndpoint<T, E>
 where
@@ -102,9 +288,51 @@ where
             attributes.push(HTTP_PATH_PATTERN.string(path_pattern.0.to_string()));
         }
 
+        let span_name = format!("{} {}", req.method(), req.uri());
+
+        if let Some(sampling) = &self.sampling {
+            let path = req.uri().path().to_string();
+            let start_time = SystemTime::now();
+            let s = Instant::now();
+            let res = self
+                .inner
+                .call(req)
+                .with_context(parent_cx.clone())
+                .await
+                .map(IntoResponse::into_response);
+            let elapsed = s.elapsed();
+
+            let (status, path_pattern) = match &res {
+                Ok(resp) => (resp.status(), resp.data::<PathPattern>()),
+                Err(err) => (err.status(), err.data::<PathPattern>()),
+            };
+            let rule = sampling.find(path_pattern.map(|pattern| &*pattern.0).unwrap_or(&path));
+            let parent_sampled = parent_cx.span().span_context().is_sampled();
+            if !parent_sampled && !rule.should_sample(status, elapsed, rand::random()) {
+                return res;
+            }
+
+            let mut span = self
+                .tracer
+                .span_builder(span_name)
+                .with_kind(SpanKind::Server)
+                .with_attributes(attributes)
+                .with_start_time(start_time)
+                .with_sampling_result(SamplingResult {
+                    decision: SamplingDecision::RecordAndSample,
+                    attributes: vec![],
+                    trace_state: parent_cx.span().span_context().trace_state().clone(),
+                })
+                .start_with_context(&*self.tracer, &parent_cx);
+            span.add_event_with_timestamp("request.started".to_string(), start_time, vec![]);
+
+            let cx = parent_cx.with_span(span);
+            return self.inject_context(&cx, record_result(cx.span(), res));
+        }
+
         let mut span = self
             .tracer
-            .span_builder(format!("{} {}", req.method(), req.uri()))
+            .span_builder(span_name)
             .with_kind(SpanKind::Server)
             .with_attributes(attributes)
             .start_with_context(&*self.tracer, &parent_cx);
@@ -112,39 +340,78 @@ where
         span.add_event("request.started".to_string(), vec![]);
 
         async move {
-            let res = self.inner.call(req).await;
+            let res = self.inner.call(req).await.map(IntoResponse::into_response);
             let cx = Context::current();
-            let span = cx.span();
-
-            match res {
-                Ok(resp) => {
-                    let resp = resp.into_response();
-                    span.add_event("request.completed".to_string(), vec![]);
-                    span.set_attribute(
-                        trace::HTTP_RESPONSE_STATUS_CODE.i64(resp.status().as_u16() as i64),
-                    );
-                    if let Some(content_length) =
-                        resp.headers().typed_get::<headers::ContentLength>()
-                    {
-                        span.set_attribute(
-                            trace::HTTP_RESPONSE_BODY_SIZE.i64(content_length.0 as i64),
-                        );
-                    }
-                    Ok(resp)
-                }
-                Err(err) => {
-                    span.set_attribute(
-                        trace::HTTP_RESPONSE_STATUS_CODE.i64(err.status().as_u16() as i64),
-                    );
-                    span.add_event(
-                        "request.error".to_string(),
-                        vec![trace::EXCEPTION_MESSAGE.string(err.to_string())],
-                    );
-                    Err(err)
-                }
-            }
+            self.inject_context(&cx, record_result(cx.span(), res))
         }
-        .with_context(Context::current_with_span(span))
+        .with_context(parent_cx.with_span(span))
         .await
     }
 }
+
+impl<T, E> OpenTelemetryTracingEndpoint<T, E> {
+    fn inject_context(&self, cx: &Context, res: Result<Response>) -> Result<Response> {
+        let mut resp = res?;
+        if self.propagate_response {
+            global::get_text_map_propagator(|propagator| {
+                propagator.inject_context(cx, &mut HeaderInjector(resp.headers_mut()))
+            });
+        }
+        Ok(resp)
+    }
+}
+
+fn record_result(span: SpanRef<'_>, res: Result<Response>) -> Result<Response> {
+    match res {
+        Ok(resp) => {
+            span.add_event("request.completed".to_string(), vec![]);
+            span.set_attribute(trace::HTTP_RESPONSE_STATUS_CODE.i64(resp.status().as_u16() as i64));
+            if let Some(content_length) = resp.headers().typed_get::<headers::ContentLength>() {
+                span.set_attribute(trace::HTTP_RESPONSE_BODY_SIZE.i64(content_length.0 as i64));
+            }
+            Ok(resp)
+        }
+        Err(err) => {
+            span.set_attribute(trace::HTTP_RESPONSE_STATUS_CODE.i64(err.status().as_u16() as i64));
+            span.add_event(
+                "request.error".to_string(),
+                vec![trace::EXCEPTION_MESSAGE.string(err.to_string())],
+            );
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampling_rules() {
+        let rules = SamplingRules {
+            default: TraceSampling::new(0.01).slow_threshold(Duration::from_millis(500)),
+            groups: vec![
+                ("/api".to_string(), TraceSampling::new(0.5)),
+                (
+                    "/api/admin/".to_string(),
+                    TraceSampling::new(1.0).server_errors(false),
+                ),
+            ],
+        };
+
+        assert_eq!(rules.find("/").ratio, 0.01);
+        assert_eq!(rules.find("/apis").ratio, 0.01);
+        assert_eq!(rules.find("/api").ratio, 0.5);
+        assert_eq!(rules.find("/api/users/:id").ratio, 0.5);
+        assert_eq!(rules.find("/api/admin").ratio, 1.0);
+        assert_eq!(rules.find("/api/admin/users").ratio, 1.0);
+
+        let rule = rules.find("/");
+        let fast = Duration::from_millis(10);
+        assert!(!rule.should_sample(StatusCode::OK, fast, 0.5));
+        assert!(rule.should_sample(StatusCode::OK, fast, 0.001));
+        assert!(rule.should_sample(StatusCode::BAD_GATEWAY, fast, 0.5));
+        assert!(!rule.should_sample(StatusCode::NOT_FOUND, fast, 0.5));
+        assert!(rule.should_sample(StatusCode::OK, Duration::from_secs(1), 0.5));
+    }
+}
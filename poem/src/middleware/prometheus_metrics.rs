@@ -0,0 +1,279 @@
+use std::time::Instant;
+
+use libprometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry};
+
+use crate::{
+    endpoint::PrometheusExporter, http::StatusCode, route::PathPattern, Endpoint, IntoResponse,
+    Middleware, Request, Response, Result,
+};
+
+const LABELS: &[&str] = &["method", "path_pattern", "status"];
+
+/// Middleware for metrics with [`prometheus`](https://crates.io/crates/prometheus).
+///
+/// The following metrics are recorded:
+///
+/// - `poem_http_requests_total`: the number of requests, by method, route and
+///   status class.
+/// - `poem_http_request_duration_seconds`: the histogram of request
+///   durations, by method, route and status class.
+/// - `poem_http_requests_in_flight`: the number of requests in progress, by
+///   method.
+///
+/// The route is the path pattern of the matched route, such as `/users/:id`,
+/// or empty if no route is matched, and the status class is one of `1xx`,
+/// `2xx`, `3xx`, `4xx` and `5xx`, so the number of series is bounded.
+///
+/// Use [`PrometheusMetrics::exporter`] to create an endpoint that exports the
+/// metrics in the text exposition format.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     get, handler, middleware::PrometheusMetrics, test::TestClient, EndpointExt, Route,
+/// };
+///
+/// #[handler]
+/// fn user() -> &'static str {
+///     "user"
+/// }
+///
+/// let metrics = PrometheusMetrics::new();
+/// let app = Route::new()
+///     .at("/users/:id", get(user))
+///     .at("/metrics", metrics.exporter())
+///     .with(metrics);
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.get("/users/1").send().await.assert_status_is_ok();
+/// let resp = cli.get("/metrics").send().await;
+/// let text = resp.0.into_body().into_string().await.unwrap();
+/// assert!(text.contains(
+///     r#"poem_http_requests_total{method="GET",path_pattern="/users/:id",status="2xx"} 1"#
+/// ));
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "prometheus")))]
+pub struct PrometheusMetrics {
+    registry: Registry,
+    requests_total: IntCounterVec,
+    request_duration: HistogramVec,
+    requests_in_flight: IntGaugeVec,
+}
+
+impl Default for PrometheusMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrometheusMetrics {
+    /// Create `PrometheusMetrics` middleware with a new registry.
+    pub fn new() -> Self {
+        Self::with_registry(Registry::new())
+    }
+
+    /// Create `PrometheusMetrics` middleware that registers the metrics to
+    /// `registry`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the metrics are already registered to `registry`.
+    pub fn with_registry(registry: Registry) -> Self {
+        let requests_total = IntCounterVec::new(
+            Opts::new("poem_http_requests_total", "total number of requests"),
+            LABELS,
+        )
+        .unwrap();
+        let request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "poem_http_request_duration_seconds",
+                "request duration histogram (in seconds)",
+            ),
+            LABELS,
+        )
+        .unwrap();
+        let requests_in_flight = IntGaugeVec::new(
+            Opts::new(
+                "poem_http_requests_in_flight",
+                "number of requests in progress",
+            ),
+            &["method"],
+        )
+        .unwrap();
+
+        registry
+            .register(Box::new(requests_total.clone()))
+            .expect("register poem_http_requests_total");
+        registry
+            .register(Box::new(request_duration.clone()))
+            .expect("register poem_http_request_duration_seconds");
+        registry
+            .register(Box::new(requests_in_flight.clone()))
+            .expect("register poem_http_requests_in_flight");
+
+        Self {
+            registry,
+            requests_total,
+            request_duration,
+            requests_in_flight,
+        }
+    }
+
+    /// Returns the registry of the metrics.
+    #[inline]
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Create an endpoint for exporting the metrics in the text exposition
+    /// format.
+    pub fn exporter(&self) -> PrometheusExporter {
+        PrometheusExporter::new(self.registry.clone())
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for PrometheusMetrics {
+    type Output = PrometheusMetricsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        PrometheusMetricsEndpoint {
+            requests_total: self.requests_total.clone(),
+            request_duration: self.request_duration.clone(),
+            requests_in_flight: self.requests_in_flight.clone(),
+            inner: ep,
+        }
+    }
+}
+
+/// Endpoint for PrometheusMetrics middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "prometheus")))]
+pub struct PrometheusMetricsEndpoint<E> {
+    requests_total: IntCounterVec,
+    request_duration: HistogramVec,
+    requests_in_flight: IntGaugeVec,
+    inner: E,
+}
+
+/// Decrements the requests in flight when the request is completed or
+/// cancelled.
+struct InFlightGuard<'a> {
+    gauge: &'a IntGaugeVec,
+    method: &'a str,
+}
+
+impl<'a> InFlightGuard<'a> {
+    fn new(gauge: &'a IntGaugeVec, method: &'a str) -> Self {
+        gauge.with_label_values(&[method]).inc();
+        Self { gauge, method }
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.gauge.with_label_values(&[self.method]).dec();
+    }
+}
+
+fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() / 100 {
+        1 => "1xx",
+        2 => "2xx",
+        3 => "3xx",
+        4 => "4xx",
+        _ => "5xx",
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for PrometheusMetricsEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let method = req.method().to_string();
+        let _in_flight = InFlightGuard::new(&self.requests_in_flight, &method);
+
+        let s = Instant::now();
+        let res = self.inner.call(req).await.map(IntoResponse::into_response);
+        let elapsed = s.elapsed();
+
+        let (path_pattern, status) = match &res {
+            Ok(resp) => (resp.data::<PathPattern>(), resp.status()),
+            Err(err) => (err.data::<PathPattern>(), err.status()),
+        };
+        let path_pattern = path_pattern.map(|path_pattern| &*path_pattern.0);
+        let labels = [
+            method.as_str(),
+            path_pattern.unwrap_or_default(),
+            status_class(status),
+        ];
+        self.requests_total.with_label_values(&labels).inc();
+        self.request_duration
+            .with_label_values(&labels)
+            .observe(elapsed.as_secs_f64());
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get, handler, test::TestClient, EndpointExt, Route};
+
+    #[tokio::test]
+    async fn prometheus_metrics() {
+        #[handler(internal)]
+        fn user() -> &'static str {
+            "user"
+        }
+
+        #[handler(internal)]
+        fn fail() -> StatusCode {
+            StatusCode::BAD_GATEWAY
+        }
+
+        let metrics = PrometheusMetrics::new();
+        let app = Route::new()
+            .at("/users/:id", get(user))
+            .at("/fail", get(fail))
+            .at("/metrics", metrics.exporter())
+            .with(metrics);
+        let cli = TestClient::new(app);
+
+        cli.get("/users/1").send().await.assert_status_is_ok();
+        cli.get("/users/2").send().await.assert_status_is_ok();
+        cli.get("/fail")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_GATEWAY);
+        cli.get("/missing")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+
+        let resp = cli.get("/metrics").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("text/plain; version=0.0.4");
+        let text = resp.0.into_body().into_string().await.unwrap();
+        for line in [
+            r#"poem_http_requests_total{method="GET",path_pattern="/users/:id",status="2xx"} 2"#,
+            r#"poem_http_requests_total{method="GET",path_pattern="/fail",status="5xx"} 1"#,
+            r#"poem_http_requests_total{method="GET",path_pattern="",status="4xx"} 1"#,
+            r#"poem_http_request_duration_seconds_count{method="GET",path_pattern="/users/:id",status="2xx"} 2"#,
+            r#"poem_http_requests_in_flight{method="GET"} 1"#,
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing `{line}`");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn register_twice() {
+        let registry = Registry::new();
+        let _ = PrometheusMetrics::with_registry(registry.clone());
+        let _ = PrometheusMetrics::with_registry(registry);
+    }
+}
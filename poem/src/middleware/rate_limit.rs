@@ -0,0 +1,574 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    http::{header, HeaderName, StatusCode},
+    web::TenantId,
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+
+/// The default maximum number of keys in [`MemoryRateLimitStore`].
+const DEFAULT_MAX_KEYS: usize = 100_000;
+
+type KeyExtractor = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+
+/// The algorithm used by the [`RateLimit`] middleware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitAlgorithm {
+    /// The requests are allowed in bursts of up to `requests`, and the budget
+    /// is refilled continuously.
+    TokenBucket,
+    /// The number of requests in the last `period` is estimated from the
+    /// current and the previous fixed windows.
+    SlidingWindow,
+}
+
+/// Middleware for limiting the rate of the requests.
+///
+/// The requests are counted by key, which is the IP address of the client by
+/// default. The requests without a key are not limited. If the tenant of the
+/// request is resolved by
+/// [`TenantResolver`](crate::middleware::TenantResolver), each tenant has
+/// separate limits.
+///
+/// The `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset`
+/// (in seconds) headers are added to the responses. When the limit is
+/// exceeded, `429 Too Many Requests` is returned with the `Retry-After`
+/// header.
+///
+/// Each endpoint created by this middleware has its own counters unless a
+/// [`RateLimitStore`] is shared with [`RateLimit::store`], apply it to a nested
+/// [`Route`](crate::Route) to share the limit in a group of routes.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{handler, http::StatusCode, middleware::RateLimit, test::TestClient, EndpointExt};
+///
+/// #[handler]
+/// fn index() {}
+///
+/// let app = index.with(RateLimit::new(1, Duration::from_secs(60)).key_by_header("x-api-key"));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").header("x-api-key", "abc").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_header("x-ratelimit-remaining", "0");
+///
+/// let resp = cli.get("/").header("x-api-key", "abc").send().await;
+/// resp.assert_status(StatusCode::TOO_MANY_REQUESTS);
+/// # });
+/// ```
+pub struct RateLimit {
+    requests: u32,
+    period: Duration,
+    algorithm: RateLimitAlgorithm,
+    key: KeyExtractor,
+    store: Option<Arc<dyn RateLimitStore>>,
+}
+
+impl RateLimit {
+    /// Create `RateLimit` middleware that allows `requests` requests in each
+    /// `period` for each client IP address.
+    pub fn new(requests: u32, period: Duration) -> Self {
+        Self {
+            requests: requests.max(1),
+            period,
+            algorithm: RateLimitAlgorithm::TokenBucket,
+            key: Arc::new(|req: &Request| {
+                Some(match req.remote_addr().as_socket_addr() {
+                    Some(addr) => addr.ip().to_string(),
+                    None => req.remote_addr().to_string(),
+                })
+            }),
+            store: None,
+        }
+    }
+
+    /// Sets the algorithm.
+    ///
+    /// Default is [`RateLimitAlgorithm::TokenBucket`].
+    #[must_use]
+    pub fn algorithm(self, algorithm: RateLimitAlgorithm) -> Self {
+        Self { algorithm, ..self }
+    }
+
+    /// Sets the storage of the counters.
+    ///
+    /// By default, each endpoint created by this middleware keeps its own
+    /// counters in memory. The endpoints that share a storage also share the
+    /// counters of the same keys.
+    #[must_use]
+    pub fn store(self, store: impl RateLimitStore) -> Self {
+        Self {
+            store: Some(Arc::new(store)),
+            ..self
+        }
+    }
+
+    /// Counts the requests by the value of the header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name.
+    #[must_use]
+    pub fn key_by_header(self, name: impl AsRef<str>) -> Self {
+        let name = HeaderName::try_from(name.as_ref()).expect("valid header name");
+        self.key_by(move |req| req.header(&name).map(ToString::to_string))
+    }
+
+    /// Counts the requests by the key returned by the function, the requests
+    /// are not limited if it returns `None`.
+    #[must_use]
+    pub fn key_by<F>(self, f: F) -> Self
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        Self {
+            key: Arc::new(f),
+            ..self
+        }
+    }
+}
+
+/// The limit applied to the requests of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitPolicy {
+    /// The number of requests allowed in each period.
+    pub requests: u32,
+    /// The period.
+    pub period: Duration,
+    /// The algorithm.
+    pub algorithm: RateLimitAlgorithm,
+}
+
+/// The result of counting a request by a [`RateLimitStore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    /// Whether the request is allowed.
+    pub allowed: bool,
+    /// The number of requests that are allowed now.
+    pub remaining: u32,
+    /// The time until the limit is fully reset.
+    pub reset: Duration,
+    /// The time until the next request is allowed.
+    pub retry_after: Duration,
+}
+
+impl RateLimitDecision {
+    /// Create a decision of [`RateLimitAlgorithm::TokenBucket`] with the
+    /// number of tokens left in the bucket after counting the request.
+    pub fn token_bucket(policy: &RateLimitPolicy, allowed: bool, tokens: f64) -> Self {
+        let limit = policy.requests as f64;
+        let rate = limit / policy.period.as_secs_f64();
+        Self {
+            allowed,
+            remaining: tokens.max(0.0) as u32,
+            reset: Duration::from_secs_f64(((limit - tokens) / rate).max(0.0)),
+            retry_after: Duration::from_secs_f64((1.0 - tokens).max(0.0) / rate),
+        }
+    }
+
+    /// Create a decision of [`RateLimitAlgorithm::SlidingWindow`] with the
+    /// estimated number of requests before counting the request and the
+    /// elapsed time of the current window.
+    pub fn sliding_window(
+        policy: &RateLimitPolicy,
+        allowed: bool,
+        estimated: f64,
+        elapsed: Duration,
+    ) -> Self {
+        let used = estimated + if allowed { 1.0 } else { 0.0 };
+        let reset = policy.period.saturating_sub(elapsed);
+        Self {
+            allowed,
+            remaining: (policy.requests as f64 - used).max(0.0) as u32,
+            reset,
+            retry_after: reset,
+        }
+    }
+}
+
+/// A storage of the counters of the [`RateLimit`] middleware.
+///
+/// The counters are kept in the memory of the process by default, use a
+/// shared storage such as
+/// [`RedisRateLimitStore`](crate::middleware::RedisRateLimitStore) to enforce
+/// consistent limits across multiple server instances.
+#[async_trait::async_trait]
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Counts a request of the key, and returns whether the request is
+    /// allowed by the policy.
+    async fn hit(&self, key: &str, policy: &RateLimitPolicy) -> Result<RateLimitDecision>;
+}
+
+#[async_trait::async_trait]
+impl<T: RateLimitStore> RateLimitStore for Arc<T> {
+    async fn hit(&self, key: &str, policy: &RateLimitPolicy) -> Result<RateLimitDecision> {
+        self.as_ref().hit(key, policy).await
+    }
+}
+
+enum State {
+    TokenBucket {
+        tokens: f64,
+        updated_at: Instant,
+    },
+    SlidingWindow {
+        window_start: Instant,
+        previous: u32,
+        current: u32,
+    },
+}
+
+impl State {
+    fn new(policy: &RateLimitPolicy, now: Instant) -> Self {
+        match policy.algorithm {
+            RateLimitAlgorithm::TokenBucket => State::TokenBucket {
+                tokens: policy.requests as f64,
+                updated_at: now,
+            },
+            RateLimitAlgorithm::SlidingWindow => State::SlidingWindow {
+                window_start: now,
+                previous: 0,
+                current: 0,
+            },
+        }
+    }
+
+    /// Returns `true` if the state is the same as a new state.
+    fn is_idle(&self, policy: &RateLimitPolicy, now: Instant) -> bool {
+        match self {
+            State::TokenBucket { updated_at, .. } => {
+                now.duration_since(*updated_at) >= policy.period
+            }
+            State::SlidingWindow { window_start, .. } => {
+                now.duration_since(*window_start) >= policy.period * 2
+            }
+        }
+    }
+
+    fn hit(&mut self, policy: &RateLimitPolicy, now: Instant) -> RateLimitDecision {
+        let limit = policy.requests as f64;
+        let period = policy.period.as_secs_f64();
+
+        match self {
+            State::TokenBucket { tokens, updated_at } => {
+                let rate = limit / period;
+                *tokens =
+                    (*tokens + now.duration_since(*updated_at).as_secs_f64() * rate).min(limit);
+                *updated_at = now;
+
+                let allowed = *tokens >= 1.0;
+                if allowed {
+                    *tokens -= 1.0;
+                }
+                RateLimitDecision::token_bucket(policy, allowed, *tokens)
+            }
+            State::SlidingWindow {
+                window_start,
+                previous,
+                current,
+            } => {
+                let mut elapsed = now.duration_since(*window_start);
+                if elapsed >= policy.period * 2 {
+                    *window_start = now;
+                    *previous = 0;
+                    *current = 0;
+                    elapsed = Duration::ZERO;
+                } else if elapsed >= policy.period {
+                    *window_start += policy.period;
+                    *previous = *current;
+                    *current = 0;
+                    elapsed -= policy.period;
+                }
+
+                let weight = 1.0 - elapsed.as_secs_f64() / period;
+                let estimated = *previous as f64 * weight + *current as f64;
+                let allowed = estimated + 1.0 <= limit;
+                if allowed {
+                    *current += 1;
+                }
+                RateLimitDecision::sliding_window(policy, allowed, estimated, elapsed)
+            }
+        }
+    }
+}
+
+struct MemoryStates {
+    states: HashMap<String, State>,
+    swept_at: Instant,
+}
+
+/// A [`RateLimitStore`] that keeps the counters in memory.
+///
+/// The idle keys are removed at most once per period of the policy. When the
+/// number of keys reaches [`MemoryRateLimitStore::max_keys`], an arbitrary key
+/// is evicted to make room for a new one, which resets its counters.
+pub struct MemoryRateLimitStore {
+    states: Mutex<MemoryStates>,
+    max_keys: usize,
+}
+
+impl Default for MemoryRateLimitStore {
+    fn default() -> Self {
+        Self {
+            states: Mutex::new(MemoryStates {
+                states: HashMap::new(),
+                swept_at: Instant::now(),
+            }),
+            max_keys: DEFAULT_MAX_KEYS,
+        }
+    }
+}
+
+impl MemoryRateLimitStore {
+    /// Create a `MemoryRateLimitStore`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of keys.
+    ///
+    /// Default is `100000`.
+    #[must_use]
+    pub fn max_keys(self, max_keys: usize) -> Self {
+        Self {
+            max_keys: max_keys.max(1),
+            ..self
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn hit(&self, key: &str, policy: &RateLimitPolicy) -> Result<RateLimitDecision> {
+        let now = Instant::now();
+        let mut guard = self.states.lock();
+        let MemoryStates { states, swept_at } = &mut *guard;
+
+        if !states.contains_key(key) {
+            if now.duration_since(*swept_at) >= policy.period {
+                states.retain(|_, state| !state.is_idle(policy, now));
+                *swept_at = now;
+            }
+            if states.len() >= self.max_keys {
+                if let Some(evicted) = states.keys().next().cloned() {
+                    states.remove(&evicted);
+                }
+            }
+        }
+        let state = states
+            .entry(key.to_string())
+            .or_insert_with(|| State::new(policy, now));
+        if !matches!(
+            (policy.algorithm, &*state),
+            (RateLimitAlgorithm::TokenBucket, State::TokenBucket { .. })
+                | (
+                    RateLimitAlgorithm::SlidingWindow,
+                    State::SlidingWindow { .. }
+                )
+        ) {
+            *state = State::new(policy, now);
+        }
+        Ok(state.hit(policy, now))
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RateLimit {
+    type Output = RateLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RateLimitEndpoint {
+            inner: ep,
+            policy: RateLimitPolicy {
+                requests: self.requests,
+                period: self.period,
+                algorithm: self.algorithm,
+            },
+            key: self.key.clone(),
+            store: self
+                .store
+                .clone()
+                .unwrap_or_else(|| Arc::new(MemoryRateLimitStore::new())),
+        }
+    }
+}
+
+/// Endpoint for RateLimit middleware.
+pub struct RateLimitEndpoint<E> {
+    inner: E,
+    policy: RateLimitPolicy,
+    key: KeyExtractor,
+    store: Arc<dyn RateLimitStore>,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for RateLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let key = match (self.key)(&req) {
+            Some(key) => key,
+            None => return self.inner.call(req).await.map(IntoResponse::into_response),
+        };
+        let key = match req.extensions().get::<TenantId>() {
+            Some(tenant) => tenant.scoped(&key),
+            None => key,
+        };
+
+        let decision = self.store.hit(&key, &self.policy).await?;
+        let mut resp = if decision.allowed {
+            self.inner.call(req).await?.into_response()
+        } else {
+            tracing::debug!("rate limit exceeded");
+            Response::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .header(
+                    header::RETRY_AFTER,
+                    decision.retry_after.as_secs_f64().ceil().max(1.0) as u64,
+                )
+                .finish()
+        };
+
+        let headers = resp.headers_mut();
+        headers.insert("x-ratelimit-limit", self.policy.requests.into());
+        headers.insert("x-ratelimit-remaining", decision.remaining.into());
+        headers.insert(
+            "x-ratelimit-reset",
+            (decision.reset.as_secs_f64().ceil() as u64).into(),
+        );
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{endpoint::make_sync, test::TestClient, EndpointExt};
+
+    #[tokio::test]
+    async fn token_bucket() {
+        let ep = make_sync(|_| ())
+            .with(RateLimit::new(2, Duration::from_secs(60)).key_by_header("x-api-key"));
+        let cli = TestClient::new(ep);
+
+        for remaining in ["1", "0"] {
+            let resp = cli.get("/").header("x-api-key", "a").send().await;
+            resp.assert_status_is_ok();
+            resp.assert_header("x-ratelimit-limit", "2");
+            resp.assert_header("x-ratelimit-remaining", remaining);
+        }
+
+        let resp = cli.get("/").header("x-api-key", "a").send().await;
+        resp.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        resp.assert_header("retry-after", "30");
+        resp.assert_header("x-ratelimit-reset", "60");
+
+        // other keys are counted separately
+        cli.get("/")
+            .header("x-api-key", "b")
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        // the requests without a key are not limited
+        for _ in 0..3 {
+            let resp = cli.get("/").send().await;
+            resp.assert_status_is_ok();
+            resp.assert_header_is_not_exist("x-ratelimit-limit");
+        }
+    }
+
+    #[tokio::test]
+    async fn sliding_window() {
+        let ep = make_sync(|_| ()).with(
+            RateLimit::new(2, Duration::from_millis(200))
+                .algorithm(RateLimitAlgorithm::SlidingWindow)
+                .key_by(|req| Some(req.uri().path().to_string())),
+        );
+        let cli = TestClient::new(ep);
+
+        cli.get("/a").send().await.assert_status_is_ok();
+        cli.get("/a").send().await.assert_status_is_ok();
+        cli.get("/a")
+            .send()
+            .await
+            .assert_status(StatusCode::TOO_MANY_REQUESTS);
+        cli.get("/b").send().await.assert_status_is_ok();
+
+        // both windows are expired
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        let resp = cli.get("/a").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header("x-ratelimit-remaining", "1");
+    }
+
+    #[tokio::test]
+    async fn shared_store() {
+        let store = Arc::new(MemoryRateLimitStore::new());
+        let limit = || {
+            RateLimit::new(2, Duration::from_secs(60))
+                .key_by_header("x-api-key")
+                .store(store.clone())
+        };
+        let a = TestClient::new(make_sync(|_| ()).with(limit()));
+        let b = TestClient::new(make_sync(|_| ()).with(limit()));
+
+        a.get("/")
+            .header("x-api-key", "a")
+            .send()
+            .await
+            .assert_status_is_ok();
+        b.get("/")
+            .header("x-api-key", "a")
+            .send()
+            .await
+            .assert_status_is_ok();
+        a.get("/")
+            .header("x-api-key", "a")
+            .send()
+            .await
+            .assert_status(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn max_keys() {
+        let store = MemoryRateLimitStore::new().max_keys(2);
+        let policy = RateLimitPolicy {
+            requests: 1,
+            period: Duration::from_secs(60),
+            algorithm: RateLimitAlgorithm::TokenBucket,
+        };
+
+        for key in ["a", "b", "c", "d"] {
+            assert!(store.hit(key, &policy).await.unwrap().allowed);
+            assert!(!store.hit(key, &policy).await.unwrap().allowed);
+            assert!(store.states.lock().states.len() <= 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn sweep_idle_keys() {
+        let store = MemoryRateLimitStore::new();
+        let policy = RateLimitPolicy {
+            requests: 1,
+            period: Duration::from_millis(100),
+            algorithm: RateLimitAlgorithm::TokenBucket,
+        };
+
+        store.hit("a", &policy).await.unwrap();
+        store.hit("b", &policy).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        store.hit("c", &policy).await.unwrap();
+        assert_eq!(store.states.lock().states.len(), 1);
+    }
+}
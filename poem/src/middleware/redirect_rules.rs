@@ -0,0 +1,386 @@
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{
+    error::RedirectRuleError,
+    http::{header, uri::PathAndQuery, HeaderName, StatusCode, Uri},
+    web::checked_header_value,
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+
+fn default_status() -> u16 {
+    302
+}
+
+/// A rule of the [`RedirectRules`] middleware.
+///
+/// The rules can be deserialized from the configuration files, for example:
+///
+/// ```json
+/// {
+///     "pattern": "^/blog/(?P<year>\\d+)/(.*)$",
+///     "target": "https://blog.example.com/${year}/$2",
+///     "status": 301,
+///     "host": "www.example.com",
+///     "headers": { "user-agent": "Mozilla" }
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedirectRule {
+    pattern: String,
+    target: String,
+    #[serde(default = "default_status")]
+    status: u16,
+    #[serde(default)]
+    rewrite: bool,
+    #[serde(default)]
+    host: Option<String>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+impl RedirectRule {
+    /// Create a rule that redirects the requests whose path matches the
+    /// regular expression `pattern` to `target` with `302 Found`.
+    ///
+    /// The target can refer to the capture groups of the pattern with `$1` or
+    /// `${name}`. If the target does not contain a query string, the query
+    /// string of the request is appended to it.
+    pub fn new(pattern: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            target: target.into(),
+            status: default_status(),
+            rewrite: false,
+            host: None,
+            headers: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the status code of the redirect response.
+    #[must_use]
+    pub fn status(self, status: StatusCode) -> Self {
+        Self {
+            status: status.as_u16(),
+            ..self
+        }
+    }
+
+    /// Rewrites the path of the request and passes it to the inner endpoint
+    /// instead of redirecting.
+    #[must_use]
+    pub fn rewrite(self) -> Self {
+        Self {
+            rewrite: true,
+            ..self
+        }
+    }
+
+    /// Only applies the rule if the `Host` of the request is `host`.
+    #[must_use]
+    pub fn host(self, host: impl Into<String>) -> Self {
+        Self {
+            host: Some(host.into()),
+            ..self
+        }
+    }
+
+    /// Only applies the rule if the value of the header matches the regular
+    /// expression `pattern`.
+    #[must_use]
+    pub fn header(mut self, name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), pattern.into());
+        self
+    }
+
+    fn compile(self) -> Result<CompiledRule, RedirectRuleError> {
+        let compile_regex = |pattern: String| {
+            Regex::new(&pattern).map_err(|err| RedirectRuleError::InvalidPattern {
+                pattern,
+                reason: err.to_string(),
+            })
+        };
+
+        let status = StatusCode::from_u16(self.status)
+            .ok()
+            .filter(StatusCode::is_redirection)
+            .ok_or(RedirectRuleError::InvalidStatus(self.status))?;
+        let headers = self
+            .headers
+            .into_iter()
+            .map(|(name, pattern)| {
+                let name = HeaderName::try_from(name.as_str())
+                    .map_err(|_| RedirectRuleError::InvalidHeaderName(name))?;
+                Ok((name, compile_regex(pattern)?))
+            })
+            .collect::<Result<_, RedirectRuleError>>()?;
+
+        Ok(CompiledRule {
+            pattern: compile_regex(self.pattern)?,
+            target: self.target,
+            status,
+            rewrite: self.rewrite,
+            host: self.host.map(|host| host.to_ascii_lowercase()),
+            headers,
+        })
+    }
+}
+
+struct CompiledRule {
+    pattern: Regex,
+    target: String,
+    status: StatusCode,
+    rewrite: bool,
+    host: Option<String>,
+    headers: Vec<(HeaderName, Regex)>,
+}
+
+impl CompiledRule {
+    fn apply(&self, req: &Request) -> Option<String> {
+        if let Some(host) = &self.host {
+            let req_host = req
+                .header(header::HOST)
+                .or_else(|| req.uri().host())
+                .map(|host| host.split(':').next().unwrap_or_default());
+            if !req_host.map_or(false, |req_host| req_host.eq_ignore_ascii_case(host)) {
+                return None;
+            }
+        }
+
+        for (name, pattern) in &self.headers {
+            if !req
+                .header(name)
+                .map_or(false, |value| pattern.is_match(value))
+            {
+                return None;
+            }
+        }
+
+        let captures = self.pattern.captures(req.uri().path())?;
+        let mut target = String::new();
+        captures.expand(&self.target, &mut target);
+        if let Some(query) = req.uri().query() {
+            if !target.contains('?') {
+                target.push('?');
+                target.push_str(query);
+            }
+        }
+        Some(target)
+    }
+}
+
+/// Middleware for redirecting or rewriting the requests with declarative
+/// rules, which can be loaded from the configuration files.
+///
+/// The rules are checked in order, and only the first matching rule is
+/// applied.
+///
+/// # Errors
+///
+/// - [`RedirectRuleError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     endpoint::make_sync,
+///     http::{header, StatusCode},
+///     middleware::{RedirectRule, RedirectRules},
+///     test::TestClient,
+///     EndpointExt,
+/// };
+///
+/// let rules = RedirectRules::new([
+///     RedirectRule::new("^/docs/(.*)$", "https://docs.example.com/$1")
+///         .status(StatusCode::MOVED_PERMANENTLY),
+///     RedirectRule::new("^/v1/(.*)$", "/v2/$1").rewrite(),
+/// ])
+/// .unwrap();
+/// let ep = make_sync(|req| req.uri().to_string()).with(rules);
+/// let cli = TestClient::new(ep);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/docs/intro").send().await;
+/// resp.assert_status(StatusCode::MOVED_PERMANENTLY);
+/// resp.assert_header(header::LOCATION, "https://docs.example.com/intro");
+///
+/// let resp = cli.get("/v1/users").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("/v2/users").await;
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct RedirectRules {
+    rules: Arc<Vec<CompiledRule>>,
+}
+
+impl RedirectRules {
+    /// Create `RedirectRules` middleware with the rules.
+    pub fn new(rules: impl IntoIterator<Item = RedirectRule>) -> Result<Self, RedirectRuleError> {
+        Ok(Self {
+            rules: Arc::new(
+                rules
+                    .into_iter()
+                    .map(RedirectRule::compile)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+        })
+    }
+
+    /// Create `RedirectRules` middleware with the rules in a JSON array.
+    pub fn from_json(json: &str) -> Result<Self, RedirectRuleError> {
+        Self::new(
+            serde_json::from_str::<Vec<RedirectRule>>(json)
+                .map_err(|err| RedirectRuleError::Load(err.to_string()))?,
+        )
+    }
+
+    /// Create `RedirectRules` middleware with the rules in a configuration
+    /// file.
+    ///
+    /// The file contains an array of rules in JSON format, or in YAML format if
+    /// the extension is `yaml` or `yml` and the `yaml` feature is enabled.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, RedirectRuleError> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)
+            .map_err(|err| RedirectRuleError::Load(err.to_string()))?;
+
+        #[cfg(feature = "yaml")]
+        if matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("yaml" | "yml")
+        ) {
+            return Self::new(
+                serde_yaml::from_str::<Vec<RedirectRule>>(&data)
+                    .map_err(|err| RedirectRuleError::Load(err.to_string()))?,
+            );
+        }
+
+        Self::from_json(&data)
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RedirectRules {
+    type Output = RedirectRulesEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RedirectRulesEndpoint {
+            inner: ep,
+            rules: self.rules.clone(),
+        }
+    }
+}
+
+/// Endpoint for RedirectRules middleware.
+pub struct RedirectRulesEndpoint<E> {
+    inner: E,
+    rules: Arc<Vec<CompiledRule>>,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for RedirectRulesEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let matched = self
+            .rules
+            .iter()
+            .find_map(|rule| rule.apply(&req).map(|target| (rule, target)));
+
+        if let Some((rule, target)) = matched {
+            if !rule.rewrite {
+                let location = checked_header_value(header::LOCATION.as_str(), &target)?;
+                return Ok(Response::builder()
+                    .status(rule.status)
+                    .header(header::LOCATION, location)
+                    .finish());
+            }
+
+            let mut parts = req.uri().clone().into_parts();
+            parts.path_and_query = Some(
+                PathAndQuery::try_from(target.as_str())
+                    .map_err(|_| RedirectRuleError::InvalidTarget(target.clone()))?,
+            );
+            *req.uri_mut() =
+                Uri::from_parts(parts).map_err(|_| RedirectRuleError::InvalidTarget(target))?;
+        }
+
+        Ok(self.inner.call(req).await?.into_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{endpoint::make_sync, test::TestClient, EndpointExt};
+
+    #[tokio::test]
+    async fn redirect_rules() {
+        let rules = RedirectRules::from_json(
+            r#"[
+                {
+                    "pattern": "^/blog/(?P<year>\\d+)/(.*)$",
+                    "target": "https://blog.example.com/${year}/$2",
+                    "status": 301,
+                    "host": "www.example.com"
+                },
+                {
+                    "pattern": "^/mobile$",
+                    "target": "/m?from=desktop",
+                    "headers": { "user-agent": "Mobile" }
+                },
+                { "pattern": "^/old/(.*)$", "target": "/new/$1", "rewrite": true }
+            ]"#,
+        )
+        .unwrap();
+        let cli = TestClient::new(make_sync(|req| req.uri().to_string()).with(rules));
+
+        let resp = cli
+            .get("/blog/2024/hello")
+            .header(header::HOST, "WWW.example.com:8080")
+            .query("a", &1)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::MOVED_PERMANENTLY);
+        resp.assert_header(header::LOCATION, "https://blog.example.com/2024/hello?a=1");
+
+        cli.get("/blog/2024/hello")
+            .header(header::HOST, "example.com")
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        let resp = cli
+            .get("/mobile")
+            .header(header::USER_AGENT, "Mobile Safari")
+            .query("a", &1)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::FOUND);
+        resp.assert_header(header::LOCATION, "/m?from=desktop");
+        cli.get("/mobile").send().await.assert_status_is_ok();
+
+        let resp = cli.get("/old/users").query("a", &1).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("/new/users?a=1").await;
+    }
+
+    #[test]
+    fn invalid_rules() {
+        assert!(matches!(
+            RedirectRules::new([RedirectRule::new("(", "/")]),
+            Err(RedirectRuleError::InvalidPattern { .. })
+        ));
+        assert!(matches!(
+            RedirectRules::new([RedirectRule::new("/", "/").status(StatusCode::OK)]),
+            Err(RedirectRuleError::InvalidStatus(200))
+        ));
+        assert!(matches!(
+            RedirectRules::from_json(r#"[{ "pattern": "/", "target": "/", "code": 301 }]"#),
+            Err(RedirectRuleError::Load(_))
+        ));
+    }
+}
@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use redis::{aio::ConnectionLike, Script};
+
+use crate::{
+    error::RedisRateLimitError,
+    middleware::{RateLimitAlgorithm, RateLimitDecision, RateLimitPolicy, RateLimitStore},
+    Result,
+};
+
+// The scripts use the clock of the redis server, so the instances of the
+// application agree on the time. The fractional values are returned in
+// thousandths because redis truncates the numbers returned from lua.
+
+const TOKEN_BUCKET: &str = r"
+local limit = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(state[1]) or limit
+local ts = tonumber(state[2]) or now
+tokens = math.min(limit, tokens + math.max(0, now - ts) * limit / period)
+
+local allowed = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+redis.call('PEXPIRE', KEYS[1], period)
+return { allowed, math.floor(tokens * 1000) }
+";
+
+const SLIDING_WINDOW: &str = r"
+local limit = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local state = redis.call('HMGET', KEYS[1], 'start', 'previous', 'current')
+local start = tonumber(state[1]) or now
+local previous = tonumber(state[2]) or 0
+local current = tonumber(state[3]) or 0
+local elapsed = now - start
+if elapsed >= 2 * period then
+    start, previous, current, elapsed = now, 0, 0, 0
+elseif elapsed >= period then
+    start, previous, current, elapsed = start + period, current, 0, elapsed - period
+end
+
+local estimated = previous * (1 - elapsed / period) + current
+local allowed = 0
+if estimated + 1 <= limit then
+    current = current + 1
+    allowed = 1
+end
+
+redis.call('HSET', KEYS[1], 'start', start, 'previous', previous, 'current', current)
+redis.call('PEXPIRE', KEYS[1], 2 * period)
+return { allowed, math.floor(estimated * 1000), elapsed }
+";
+
+/// A [`RateLimitStore`] that keeps the counters in redis, so the limits are
+/// consistent across multiple server instances.
+///
+/// The counters are updated atomically by lua scripts and expire when they
+/// are idle.
+///
+/// # Errors
+///
+/// - [`RedisRateLimitError`]
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use poem::{
+///     handler,
+///     middleware::{RateLimit, RedisRateLimitStore},
+///     EndpointExt,
+/// };
+/// use redis::{aio::ConnectionManager, Client};
+///
+/// #[handler]
+/// fn index() {}
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let client = Client::open("redis://127.0.0.1/").unwrap();
+/// let connection = ConnectionManager::new(client).await.unwrap();
+/// let app = index.with(
+///     RateLimit::new(100, Duration::from_secs(60)).store(RedisRateLimitStore::new(connection)),
+/// );
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "redis-rate-limit")))]
+pub struct RedisRateLimitStore<T> {
+    connection: T,
+    prefix: String,
+    token_bucket: Script,
+    sliding_window: Script,
+}
+
+impl<T> RedisRateLimitStore<T> {
+    /// Create a `RedisRateLimitStore`.
+    pub fn new(connection: T) -> Self {
+        Self {
+            connection,
+            prefix: "poem:rate-limit:".to_string(),
+            token_bucket: Script::new(TOKEN_BUCKET),
+            sliding_window: Script::new(SLIDING_WINDOW),
+        }
+    }
+
+    /// Sets the prefix of the keys.
+    ///
+    /// Default is `poem:rate-limit:`.
+    #[must_use]
+    pub fn prefix(self, prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            ..self
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: ConnectionLike + Clone + Send + Sync + 'static> RateLimitStore for RedisRateLimitStore<T> {
+    async fn hit(&self, key: &str, policy: &RateLimitPolicy) -> Result<RateLimitDecision> {
+        let key = format!("{}{}", self.prefix, key);
+        let period = policy.period.as_millis().max(1) as u64;
+        let script = match policy.algorithm {
+            RateLimitAlgorithm::TokenBucket => &self.token_bucket,
+            RateLimitAlgorithm::SlidingWindow => &self.sliding_window,
+        };
+        let res: Vec<i64> = script
+            .key(key)
+            .arg(policy.requests)
+            .arg(period)
+            .invoke_async(&mut self.connection.clone())
+            .await
+            .map_err(RedisRateLimitError::Redis)?;
+
+        let allowed = res.first().copied().unwrap_or_default() == 1;
+        let value = res.get(1).copied().unwrap_or_default() as f64 / 1000.0;
+        Ok(match policy.algorithm {
+            RateLimitAlgorithm::TokenBucket => {
+                RateLimitDecision::token_bucket(policy, allowed, value)
+            }
+            RateLimitAlgorithm::SlidingWindow => {
+                let elapsed = res.get(2).copied().unwrap_or_default().max(0) as u64;
+                RateLimitDecision::sliding_window(
+                    policy,
+                    allowed,
+                    value,
+                    Duration::from_millis(elapsed),
+                )
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use redis::{aio::ConnectionManager, Client};
+
+    use super::*;
+    use crate::{
+        endpoint::make_sync, http::StatusCode, middleware::RateLimit, test::TestClient, EndpointExt,
+    };
+
+    #[tokio::test]
+    async fn redis_rate_limit() {
+        let client = match Client::open("redis://127.0.0.1/") {
+            Ok(client) => client,
+            Err(_) => return,
+        };
+        let connection = match ConnectionManager::new(client).await {
+            Ok(connection) => connection,
+            Err(_) => return,
+        };
+
+        for algorithm in [
+            RateLimitAlgorithm::TokenBucket,
+            RateLimitAlgorithm::SlidingWindow,
+        ] {
+            let store = RedisRateLimitStore::new(connection.clone()).prefix(format!(
+                "poem-test:{:?}:{}:",
+                algorithm,
+                std::process::id()
+            ));
+            let cli = TestClient::new(
+                make_sync(|_| ()).with(
+                    RateLimit::new(2, Duration::from_secs(60))
+                        .algorithm(algorithm)
+                        .key_by_header("x-api-key")
+                        .store(store),
+                ),
+            );
+
+            for remaining in ["1", "0"] {
+                let resp = cli.get("/").header("x-api-key", "a").send().await;
+                resp.assert_status_is_ok();
+                resp.assert_header("x-ratelimit-remaining", remaining);
+            }
+            cli.get("/")
+                .header("x-api-key", "a")
+                .send()
+                .await
+                .assert_status(StatusCode::TOO_MANY_REQUESTS);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    io::{Error as IoError, ErrorKind, Result as IoResult},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::{
+    error::ReadBodyError, web::headers::HeaderMapExt, Body, Endpoint, Middleware, Request, Result,
+};
+
+/// The limit shared by the nested `RequestBodyLimit` middlewares, the
+/// innermost one wins because the body is read after all of them are called.
+#[derive(Clone)]
+struct SharedLimit(Arc<AtomicU64>);
+
+pin_project_lite::pin_project! {
+    struct LimitedReader<R> {
+        #[pin]
+        inner: R,
+        limit: Arc<AtomicU64>,
+        content_length: Option<u64>,
+        read: u64,
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for LimitedReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let this = self.project();
+        let limit = this.limit.load(Ordering::Relaxed);
+        let payload_too_large = || IoError::new(ErrorKind::Other, ReadBodyError::PayloadTooLarge);
+
+        // reject the declared length before reading anything
+        if this.content_length.map_or(false, |len| len > limit) {
+            return Poll::Ready(Err(payload_too_large()));
+        }
+
+        let filled = buf.filled().len();
+        let res = this.inner.poll_read(cx, buf);
+        *this.read += (buf.filled().len() - filled) as u64;
+        if *this.read > limit {
+            return Poll::Ready(Err(payload_too_large()));
+        }
+        res
+    }
+}
+
+/// Middleware for limiting the size of the request body.
+///
+/// Unlike [`SizeLimit`](crate::middleware::SizeLimit), this middleware does
+/// not trust the `Content-Length` header. The bytes are counted while the body
+/// is streamed, so the chunked requests are limited too, and reading a body
+/// that exceeds the limit fails with [`ReadBodyError::PayloadTooLarge`]
+/// (`413 Payload Too Large`).
+///
+/// It can be applied to the whole application, and overridden for the
+/// specific routes by applying it again, the innermost limit is used.
+///
+/// # Errors
+///
+/// - [`ReadBodyError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler, http::StatusCode, middleware::RequestBodyLimit, post, test::TestClient,
+///     EndpointExt, Route,
+/// };
+///
+/// #[handler]
+/// fn index(data: Vec<u8>) -> String {
+///     data.len().to_string()
+/// }
+///
+/// let app = Route::new()
+///     .at("/", post(index))
+///     .at("/upload", post(index).with(RequestBodyLimit::new(1024 * 1024)))
+///     .with(RequestBodyLimit::new(16));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.post("/")
+///     .body(vec![0; 32])
+///     .send()
+///     .await
+///     .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+/// cli.post("/upload")
+///     .body(vec![0; 32])
+///     .send()
+///     .await
+///     .assert_text("32")
+///     .await;
+/// # });
+/// ```
+pub struct RequestBodyLimit {
+    max_size: u64,
+}
+
+impl RequestBodyLimit {
+    /// Create `RequestBodyLimit` middleware with the maximum size of the
+    /// request body in bytes.
+    pub fn new(max_size: u64) -> Self {
+        Self { max_size }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RequestBodyLimit {
+    type Output = RequestBodyLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RequestBodyLimitEndpoint {
+            inner: ep,
+            max_size: self.max_size,
+        }
+    }
+}
+
+/// Endpoint for RequestBodyLimit middleware.
+pub struct RequestBodyLimitEndpoint<E> {
+    inner: E,
+    max_size: u64,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for RequestBodyLimitEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        if let Some(SharedLimit(limit)) = req.extensions().get::<SharedLimit>() {
+            limit.store(self.max_size, Ordering::Relaxed);
+            return self.inner.call(req).await;
+        }
+
+        let limit = Arc::new(AtomicU64::new(self.max_size));
+        let content_length = req
+            .headers()
+            .typed_get::<headers::ContentLength>()
+            .map(|len| len.0);
+        let body = req.take_body();
+        req.set_body(Body::from_async_read(LimitedReader {
+            inner: body.into_async_read(),
+            limit: limit.clone(),
+            content_length,
+            read: 0,
+        }));
+        req.extensions_mut().insert(SharedLimit(limit));
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{handler, post, test::TestClient, EndpointExt, Route};
+
+    #[handler(internal)]
+    fn index(data: Vec<u8>) -> String {
+        data.len().to_string()
+    }
+
+    #[tokio::test]
+    async fn streamed_body() {
+        let cli = TestClient::new(index.with(RequestBodyLimit::new(10)));
+
+        cli.post("/")
+            .body(vec![0; 10])
+            .send()
+            .await
+            .assert_text("10")
+            .await;
+
+        // no content-length
+        let chunks = stream::iter((0..4).map(|_| Ok::<_, IoError>(vec![0u8; 4])));
+        cli.post("/")
+            .body(Body::from_bytes_stream(chunks))
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+
+        // a lying content-length
+        cli.post("/")
+            .header("content-length", 5)
+            .body(vec![0; 20])
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn override_per_route() {
+        let app = Route::new()
+            .at("/", post(index))
+            .at("/large", post(index).with(RequestBodyLimit::new(100)))
+            .at("/small", post(index).with(RequestBodyLimit::new(2)))
+            .with(RequestBodyLimit::new(10));
+        let cli = TestClient::new(app);
+
+        cli.post("/")
+            .body(vec![0; 50])
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        cli.post("/large")
+            .body(vec![0; 50])
+            .send()
+            .await
+            .assert_text("50")
+            .await;
+        cli.post("/small")
+            .body(vec![0; 5])
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    fmt::Write,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rand::{thread_rng, Rng};
+
+use crate::{
+    http::header::HeaderName,
+    web::{checked_header_value, RequestId},
+    Endpoint, Middleware, Request, Response, Result,
+};
+
+type Generator = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// The maximum length of the incoming request ids that are kept.
+const MAX_INCOMING_LEN: usize = 128;
+
+/// Generates a random (version 4) UUID, such as
+/// `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+fn uuid() -> String {
+    let mut bytes = thread_rng().gen::<[u8; 16]>();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let mut s = String::with_capacity(36);
+    for (i, byte) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            s.push('-');
+        }
+        let _ = write!(s, "{byte:02x}");
+    }
+    s
+}
+
+/// Generates a [ULID](https://github.com/ulid/spec), such as
+/// `01ARZ3NDEKTSV4RRFFQ69G5FAV`, which is sorted by the time it is generated.
+fn ulid() -> String {
+    const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+        & ((1 << 48) - 1);
+    let random = thread_rng().gen::<u128>() & ((1 << 80) - 1);
+    let value = (millis << 80) | random;
+
+    (0..26)
+        .rev()
+        .map(|i| ALPHABET[((value >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// Middleware for setting the identifier of each request.
+///
+/// The identifier is read from the `X-Request-Id` header of the request, or
+/// generated if the header is missing, and is:
+///
+/// - inserted into the request extensions as [`RequestId`], which can be
+///   extracted by the handlers;
+/// - set to the header of the request, so that it is forwarded by the proxies
+///   and clients that copy the headers;
+/// - recorded to the `request_id` field of the span created by the
+///   [`Tracing`](crate::middleware::Tracing) middleware, if `SetRequestId` is
+///   applied outside of it;
+/// - echoed in the header of the response, including the error responses.
+///
+/// The incoming identifiers longer than 128 bytes or containing characters
+/// other than visible ASCII are replaced, so that they cannot be used to
+/// forge log lines.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     middleware::{SetRequestId, Tracing},
+///     test::TestClient,
+///     web::RequestId,
+///     EndpointExt,
+/// };
+///
+/// #[handler]
+/// fn index(request_id: RequestId) -> String {
+///     request_id.to_string()
+/// }
+///
+/// let app = index.with(Tracing).with(SetRequestId::new().ulid());
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// let request_id = resp.0.header("x-request-id").unwrap().to_string();
+/// assert_eq!(request_id.len(), 26);
+/// resp.assert_text(request_id).await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "request-id")))]
+pub struct SetRequestId {
+    header_name: HeaderName,
+    generator: Generator,
+    trust_incoming: bool,
+}
+
+impl Default for SetRequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SetRequestId {
+    /// Create `SetRequestId` middleware that generates random UUIDs.
+    pub fn new() -> Self {
+        Self {
+            header_name: HeaderName::from_static("x-request-id"),
+            generator: Arc::new(uuid),
+            trust_incoming: true,
+        }
+    }
+
+    /// Sets the name of the header, default to `X-Request-Id`.
+    #[must_use]
+    pub fn header_name(self, header_name: HeaderName) -> Self {
+        Self {
+            header_name,
+            ..self
+        }
+    }
+
+    /// Generates ULIDs, which are sorted by the time they are generated,
+    /// instead of UUIDs.
+    #[must_use]
+    pub fn ulid(self) -> Self {
+        Self {
+            generator: Arc::new(ulid),
+            ..self
+        }
+    }
+
+    /// Generates the identifiers with `f`.
+    #[must_use]
+    pub fn generator<F>(self, f: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        Self {
+            generator: Arc::new(f),
+            ..self
+        }
+    }
+
+    /// Sets whether to keep the identifiers of the incoming requests, default
+    /// to `true`.
+    ///
+    /// Disable it if the server is not behind a proxy that sets the header,
+    /// so that the clients cannot choose the identifiers.
+    #[must_use]
+    pub fn trust_incoming(self, trust_incoming: bool) -> Self {
+        Self {
+            trust_incoming,
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for SetRequestId {
+    type Output = SetRequestIdEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        SetRequestIdEndpoint {
+            inner: ep,
+            header_name: self.header_name.clone(),
+            generator: self.generator.clone(),
+            trust_incoming: self.trust_incoming,
+        }
+    }
+}
+
+/// Endpoint for `SetRequestId` middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "request-id")))]
+pub struct SetRequestIdEndpoint<E> {
+    inner: E,
+    header_name: HeaderName,
+    generator: Generator,
+    trust_incoming: bool,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for SetRequestIdEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let incoming = req
+            .headers()
+            .get(&self.header_name)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| {
+                self.trust_incoming
+                    && !id.is_empty()
+                    && id.len() <= MAX_INCOMING_LEN
+                    && id.bytes().all(|c| c.is_ascii_graphic())
+            })
+            .map(ToString::to_string);
+        let id = incoming.unwrap_or_else(|| (self.generator)());
+        let value = checked_header_value(self.header_name.as_str(), &id)?;
+
+        req.headers_mut()
+            .insert(self.header_name.clone(), value.clone());
+        req.extensions_mut().insert(RequestId::new(id));
+
+        let mut resp = self.inner.get_response(req).await;
+        resp.headers_mut().insert(self.header_name.clone(), value);
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{handler, http::StatusCode, test::TestClient, EndpointExt};
+
+    #[handler(internal)]
+    fn index(request_id: RequestId) -> String {
+        request_id.to_string()
+    }
+
+    #[test]
+    fn generators() {
+        let id = uuid();
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(uuid(), id);
+
+        let id = ulid();
+        assert_eq!(id.len(), 26);
+        assert!(id
+            .bytes()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()));
+        assert!(id.as_bytes()[0] <= b'7');
+    }
+
+    #[tokio::test]
+    async fn set_request_id() {
+        let cli = TestClient::new(index.with(SetRequestId::new()));
+
+        let resp = cli.get("/").send().await;
+        let id = resp.0.header("x-request-id").unwrap().to_string();
+        assert_eq!(id.len(), 36);
+        resp.assert_text(id).await;
+
+        let resp = cli.get("/").header("x-request-id", "abc").send().await;
+        resp.assert_header("x-request-id", "abc");
+        resp.assert_text("abc").await;
+
+        // invalid incoming ids are replaced
+        let resp = cli.get("/").header("x-request-id", "a b").send().await;
+        let id = resp.0.header("x-request-id").unwrap().to_string();
+        assert_eq!(id.len(), 36);
+    }
+
+    #[tokio::test]
+    async fn custom() {
+        let cli = TestClient::new(
+            index.with(
+                SetRequestId::new()
+                    .header_name(HeaderName::from_static("x-trace"))
+                    .generator(|| "generated".to_string())
+                    .trust_incoming(false),
+            ),
+        );
+
+        let resp = cli.get("/").header("x-trace", "abc").send().await;
+        resp.assert_header("x-trace", "generated");
+        resp.assert_text("generated").await;
+    }
+
+    #[tokio::test]
+    async fn error_response() {
+        #[handler(internal)]
+        fn fail() -> Result<()> {
+            Err(crate::Error::from_status(StatusCode::BAD_REQUEST))
+        }
+
+        let cli = TestClient::new(fail.with(SetRequestId::new()));
+        let resp = cli.get("/").header("x-request-id", "abc").send().await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+        resp.assert_header("x-request-id", "abc");
+    }
+}
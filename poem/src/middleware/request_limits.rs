@@ -0,0 +1,226 @@
+use crate::{error::RequestLimitError, Endpoint, Middleware, Request, Result};
+
+/// Middleware for limiting the size of the request headers and the length of
+/// the request URI.
+///
+/// A `warn` level event is emitted to the `poem::request_limits` target when
+/// a request exceeds the warning threshold (80% of the limit by default), so
+/// operators can detect abusive clients before the hard limits start
+/// rejecting requests.
+///
+/// The size of the headers is the sum of the length of each header name and
+/// value, plus 4 bytes per header for the separator and the line break.
+///
+/// # Errors
+///
+/// - [`RequestLimitError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::StatusCode,
+///     middleware::RequestLimits,
+///     test::TestClient,
+///     EndpointExt,
+/// };
+///
+/// #[handler]
+/// fn index() {}
+///
+/// let app = index.with(RequestLimits::new().max_uri_length(16));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.get("/")
+///     .send()
+///     .await
+///     .assert_status_is_ok();
+/// cli.get("/a-very-long-request-uri")
+///     .send()
+///     .await
+///     .assert_status(StatusCode::URI_TOO_LONG);
+/// # });
+/// ```
+pub struct RequestLimits {
+    max_header_size: Option<usize>,
+    max_uri_length: Option<usize>,
+    warn_ratio: f64,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestLimits {
+    /// Create `RequestLimits` middleware.
+    pub fn new() -> Self {
+        Self {
+            max_header_size: None,
+            max_uri_length: None,
+            warn_ratio: 0.8,
+        }
+    }
+
+    /// Sets the maximum size of the request headers in bytes.
+    ///
+    /// Requests that exceed this size are rejected with
+    /// `431 Request Header Fields Too Large`.
+    #[must_use]
+    pub fn max_header_size(self, max_size: usize) -> Self {
+        Self {
+            max_header_size: Some(max_size),
+            ..self
+        }
+    }
+
+    /// Sets the maximum length of the request URI in bytes.
+    ///
+    /// Requests that exceed this length are rejected with
+    /// `414 URI Too Long`.
+    #[must_use]
+    pub fn max_uri_length(self, max_length: usize) -> Self {
+        Self {
+            max_uri_length: Some(max_length),
+            ..self
+        }
+    }
+
+    /// Sets the ratio of the limit at which a warning is emitted, the default
+    /// value is `0.8`.
+    #[must_use]
+    pub fn warn_ratio(self, ratio: f64) -> Self {
+        Self {
+            warn_ratio: ratio.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RequestLimits {
+    type Output = RequestLimitsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RequestLimitsEndpoint {
+            inner: ep,
+            max_header_size: self.max_header_size,
+            max_uri_length: self.max_uri_length,
+            warn_ratio: self.warn_ratio,
+        }
+    }
+}
+
+/// Endpoint for RequestLimits middleware.
+pub struct RequestLimitsEndpoint<E> {
+    inner: E,
+    max_header_size: Option<usize>,
+    max_uri_length: Option<usize>,
+    warn_ratio: f64,
+}
+
+impl<E> RequestLimitsEndpoint<E> {
+    fn exceeds_warn_threshold(&self, size: usize, limit: usize) -> bool {
+        size as f64 >= limit as f64 * self.warn_ratio
+    }
+}
+
+fn header_size(req: &Request) -> usize {
+    req.headers()
+        .iter()
+        .map(|(name, value)| name.as_str().len() + value.len() + 4)
+        .sum()
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for RequestLimitsEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if let Some(max_uri_length) = self.max_uri_length {
+            let uri_length = req.uri().to_string().len();
+            if uri_length > max_uri_length {
+                tracing::warn!(
+                    target: "poem::request_limits",
+                    remote_addr = %req.remote_addr(),
+                    uri_length,
+                    max_uri_length,
+                    "request uri exceeds the limit"
+                );
+                return Err(RequestLimitError::UriTooLong.into());
+            } else if self.exceeds_warn_threshold(uri_length, max_uri_length) {
+                tracing::warn!(
+                    target: "poem::request_limits",
+                    remote_addr = %req.remote_addr(),
+                    uri_length,
+                    max_uri_length,
+                    "request uri is approaching the limit"
+                );
+            }
+        }
+
+        if let Some(max_header_size) = self.max_header_size {
+            let header_size = header_size(&req);
+            if header_size > max_header_size {
+                tracing::warn!(
+                    target: "poem::request_limits",
+                    remote_addr = %req.remote_addr(),
+                    header_size,
+                    max_header_size,
+                    "request headers exceed the limit"
+                );
+                return Err(RequestLimitError::HeadersTooLarge.into());
+            } else if self.exceeds_warn_threshold(header_size, max_header_size) {
+                tracing::warn!(
+                    target: "poem::request_limits",
+                    remote_addr = %req.remote_addr(),
+                    header_size,
+                    max_header_size,
+                    "request headers are approaching the limit"
+                );
+            }
+        }
+
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{
+        endpoint::{make_sync, EndpointExt},
+        test::TestClient,
+    };
+
+    #[tokio::test]
+    async fn request_limits() {
+        let ep = make_sync(|_| ()).with(
+            RequestLimits::new()
+                .max_uri_length(10)
+                .max_header_size(32),
+        );
+        let cli = TestClient::new(ep);
+
+        cli.get("/abc").send().await.assert_status_is_ok();
+        cli.get("/abcdefghijk")
+            .send()
+            .await
+            .assert_status(StatusCode::URI_TOO_LONG);
+
+        cli.get("/")
+            .header("x-a", "1")
+            .send()
+            .await
+            .assert_status_is_ok();
+        cli.get("/")
+            .header("x-a", "a".repeat(32))
+            .send()
+            .await
+            .assert_status(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+    }
+}
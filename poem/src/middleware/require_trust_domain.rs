@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+use crate::{
+    error::PeerIdentityError, web::PeerIdentity, Endpoint, FromRequest, Middleware, Request,
+    Result,
+};
+
+/// Middleware for only accepting the clients whose SPIFFE identity belongs to
+/// one of the specified trust domains, see [`PeerIdentity`].
+///
+/// The [`PeerIdentity`] of the accepted client is added to the request
+/// extensions.
+///
+/// # Errors
+///
+/// - [`ClientCertRequiredError`](crate::error::ClientCertRequiredError)
+/// - [`PeerIdentityError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     middleware::RequireTrustDomain,
+///     web::{Data, PeerIdentity},
+///     EndpointExt,
+/// };
+///
+/// #[handler]
+/// fn index(Data(peer): Data<&PeerIdentity>) -> String {
+///     format!("hello, {}", peer.spiffe_id())
+/// }
+///
+/// let app = index.with(RequireTrustDomain::new(["example.org"]));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+#[derive(Debug, Clone)]
+pub struct RequireTrustDomain {
+    trust_domains: HashSet<String>,
+}
+
+impl RequireTrustDomain {
+    /// Create new `RequireTrustDomain` middleware with the allowed trust
+    /// domains.
+    #[must_use]
+    pub fn new<I, T>(trust_domains: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            trust_domains: trust_domains.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RequireTrustDomain {
+    type Output = RequireTrustDomainEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RequireTrustDomainEndpoint {
+            inner: ep,
+            trust_domains: self.trust_domains.clone(),
+        }
+    }
+}
+
+/// Endpoint for RequireTrustDomain middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+pub struct RequireTrustDomainEndpoint<E> {
+    inner: E,
+    trust_domains: HashSet<String>,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for RequireTrustDomainEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let peer = PeerIdentity::from_request_without_body(&req).await?;
+        if !self.trust_domains.contains(peer.trust_domain()) {
+            return Err(
+                PeerIdentityError::TrustDomainNotAllowed(peer.trust_domain().to_string()).into(),
+            );
+        }
+        req.extensions_mut().insert(peer);
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{handler, http::StatusCode, web::ClientCert, EndpointExt};
+
+    fn client_cert() -> ClientCert {
+        let chain = rustls_pemfile::certs(
+            &mut include_bytes!("../listener/certs/spiffe.pem").as_ref(),
+        )
+        .map(|cert| cert.unwrap().to_vec())
+        .collect();
+        ClientCert::new(chain).unwrap()
+    }
+
+    #[handler(internal)]
+    fn index(req: &Request) -> String {
+        req.extensions()
+            .get::<PeerIdentity>()
+            .unwrap()
+            .spiffe_id()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn require_trust_domain() {
+        let req = Request::default();
+        req.connect_info().insert(client_cert());
+        let resp = index
+            .with(RequireTrustDomain::new(["example.org"]))
+            .call(req)
+            .await
+            .unwrap();
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            "spiffe://example.org/ns/default/sa/web"
+        );
+
+        let req = Request::default();
+        req.connect_info().insert(client_cert());
+        let err = index
+            .with(RequireTrustDomain::new(["other.org"]))
+            .call(req)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::FORBIDDEN);
+
+        let err = index
+            .with(RequireTrustDomain::new(["example.org"]))
+            .call(Request::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::UNAUTHORIZED);
+    }
+}
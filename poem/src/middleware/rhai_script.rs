@@ -0,0 +1,431 @@
+use std::sync::Arc;
+
+use librhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use parking_lot::RwLock;
+
+use crate::{
+    error::ScriptError,
+    http::{uri::PathAndQuery, HeaderMap, HeaderName, HeaderValue, StatusCode, Uri},
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+
+struct Inner {
+    engine: Engine,
+    ast: RwLock<AST>,
+}
+
+/// Middleware for rewriting the requests with
+/// [`rhai`](https://crates.io/crates/rhai) scripts.
+///
+/// The script is evaluated for each request, with the following variables:
+///
+/// - `method`: The method of the request.
+/// - `path`: The path of the request, which can be rewritten.
+/// - `query`: The query string of the request, which can be rewritten.
+/// - `headers`: A map of the lowercase header names to the header values,
+///   which can be modified. A header with multiple values, such as `Cookie`,
+///   is an array of the values, and a header is set to multiple values by
+///   assigning an array.
+///
+/// If the script returns a map, the request is not passed to the inner
+/// endpoint, and the map is used as the response, with the `status` (defaults
+/// to `200`), `body` (defaults to an empty string) and `headers` fields.
+///
+/// The script can be replaced with [`RhaiScript::reload`], which affects all
+/// the endpoints created by the middleware and its clones.
+///
+/// The script is evaluated on the async worker thread, so the default engine
+/// limits the number of operations, the call depth and the size of the
+/// strings, arrays and maps, to abort runaway scripts instead of stalling the
+/// other requests on that thread.
+///
+/// # Errors
+///
+/// - [`ScriptError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     endpoint::make_sync, http::StatusCode, middleware::RhaiScript, test::TestClient,
+///     EndpointExt,
+/// };
+///
+/// let script = RhaiScript::new(
+///     r#"
+///     if !("authorization" in headers) {
+///         return #{ status: 401, body: "login required" };
+///     }
+///     path = "/v2" + path;
+///     "#,
+/// )
+/// .unwrap();
+/// let ep = make_sync(|req| req.uri().path().to_string()).with(script);
+/// let cli = TestClient::new(ep);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/users").send().await;
+/// resp.assert_status(StatusCode::UNAUTHORIZED);
+///
+/// let resp = cli
+///     .get("/users")
+///     .header("authorization", "Bearer abc")
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("/v2/users").await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "rhai")))]
+#[derive(Clone)]
+pub struct RhaiScript {
+    inner: Arc<Inner>,
+}
+
+impl RhaiScript {
+    /// Create `RhaiScript` middleware with the source of the script.
+    pub fn new(source: impl AsRef<str>) -> Result<Self, ScriptError> {
+        Self::with_engine(Self::default_engine(), source)
+    }
+
+    /// Returns the engine used by [`RhaiScript::new`], which can be
+    /// customized and passed to [`RhaiScript::with_engine`].
+    ///
+    /// It allows `100_000` operations per evaluation, `32` levels of
+    /// function calls, `64` levels of expressions, strings of `64KiB`, and
+    /// arrays and maps of `1024` items.
+    pub fn default_engine() -> Engine {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(100_000)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 64)
+            .set_max_string_size(64 * 1024)
+            .set_max_array_size(1024)
+            .set_max_map_size(1024);
+        engine
+    }
+
+    /// Create `RhaiScript` middleware with the source of the script and a
+    /// custom engine, which can be used to register functions or to change
+    /// the limits. A bare [`Engine::new`] has no limits, so a script that
+    /// never terminates blocks the worker thread.
+    pub fn with_engine(engine: Engine, source: impl AsRef<str>) -> Result<Self, ScriptError> {
+        let ast = engine
+            .compile(source.as_ref())
+            .map_err(|err| ScriptError::Compile(err.to_string()))?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                engine,
+                ast: RwLock::new(ast),
+            }),
+        })
+    }
+
+    /// Replaces the script, the requests that are being handled are not
+    /// affected.
+    pub fn reload(&self, source: impl AsRef<str>) -> Result<(), ScriptError> {
+        let ast = self
+            .inner
+            .engine
+            .compile(source.as_ref())
+            .map_err(|err| ScriptError::Compile(err.to_string()))?;
+        *self.inner.ast.write() = ast;
+        Ok(())
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RhaiScript {
+    type Output = RhaiScriptEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RhaiScriptEndpoint {
+            inner: ep,
+            script: self.inner.clone(),
+        }
+    }
+}
+
+/// Endpoint for RhaiScript middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "rhai")))]
+pub struct RhaiScriptEndpoint<E> {
+    inner: E,
+    script: Arc<Inner>,
+}
+
+fn headers_to_map(headers: &HeaderMap) -> Map {
+    let mut map = Map::new();
+    for name in headers.keys() {
+        let mut values = headers
+            .get_all(name)
+            .iter()
+            .map(|value| Dynamic::from(String::from_utf8_lossy(value.as_bytes()).into_owned()))
+            .collect::<Array>();
+        let value = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            values.into()
+        };
+        map.insert(name.as_str().into(), value);
+    }
+    map
+}
+
+/// Returns the values of a header in the map, an array is a header with
+/// multiple values.
+fn header_values(value: &Dynamic) -> Vec<String> {
+    match value.read_lock::<Array>() {
+        Some(values) => values.iter().map(ToString::to_string).collect(),
+        None => vec![value.to_string()],
+    }
+}
+
+fn apply_headers(headers: &mut HeaderMap, old: &Map, new: &Map) -> Result<(), ScriptError> {
+    for name in old.keys() {
+        if new.get(name).map_or(true, Dynamic::is_unit) {
+            headers.remove(name.as_str());
+        }
+    }
+
+    for (name, value) in new {
+        if value.is_unit() {
+            continue;
+        }
+        let values = header_values(value);
+        if old.get(name).map(header_values).as_ref() == Some(&values) {
+            continue;
+        }
+        let name = HeaderName::try_from(name.as_str())
+            .map_err(|_| ScriptError::Eval(format!("invalid header name `{name}`")))?;
+        headers.remove(&name);
+        for value in values {
+            let value = HeaderValue::try_from(value)
+                .map_err(|_| ScriptError::Eval(format!("invalid value of header `{name}`")))?;
+            headers.append(name.clone(), value);
+        }
+    }
+
+    Ok(())
+}
+
+fn map_to_response(mut map: Map) -> Result<Response, ScriptError> {
+    let status = match map.remove("status") {
+        Some(status) => status
+            .as_int()
+            .ok()
+            .and_then(|status| u16::try_from(status).ok())
+            .and_then(|status| StatusCode::from_u16(status).ok())
+            .ok_or_else(|| ScriptError::Eval("invalid status code".to_string()))?,
+        None => StatusCode::OK,
+    };
+    let body = map
+        .remove("body")
+        .map(|body| body.to_string())
+        .unwrap_or_default();
+
+    let mut resp = Response::builder().status(status).body(body);
+    if let Some(headers) = map.remove("headers") {
+        let headers = headers
+            .try_cast::<Map>()
+            .ok_or_else(|| ScriptError::Eval("`headers` must be a map".to_string()))?;
+        apply_headers(resp.headers_mut(), &Map::new(), &headers)?;
+    }
+    Ok(resp)
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for RhaiScriptEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let path = req.uri().path().to_string();
+        let query = req.uri().query().unwrap_or_default().to_string();
+        let headers = headers_to_map(req.headers());
+
+        let mut scope = Scope::new();
+        scope.push_constant("method", req.method().to_string());
+        scope.push("path", path.clone());
+        scope.push("query", query.clone());
+        scope.push("headers", headers.clone());
+
+        let res = {
+            let ast = self.script.ast.read();
+            self.script
+                .engine
+                .eval_ast_with_scope::<Dynamic>(&mut scope, &ast)
+                .map_err(|err| ScriptError::Eval(err.to_string()))?
+        };
+
+        if res.is::<Map>() {
+            let map = res.cast::<Map>();
+            return Ok(map_to_response(map)?);
+        } else if !res.is_unit() {
+            return Err(
+                ScriptError::Eval("the script must return `()` or a map".to_string()).into(),
+            );
+        }
+
+        let new_headers = scope
+            .get_value::<Map>("headers")
+            .ok_or_else(|| ScriptError::Eval("`headers` must be a map".to_string()))?;
+        apply_headers(req.headers_mut(), &headers, &new_headers)?;
+
+        let new_path = scope.get_value::<String>("path").unwrap_or_default();
+        let new_query = scope.get_value::<String>("query").unwrap_or_default();
+        if new_path != path || new_query != query {
+            let path_and_query = if new_query.is_empty() {
+                new_path
+            } else {
+                format!("{new_path}?{new_query}")
+            };
+            let mut parts = req.uri().clone().into_parts();
+            parts.path_and_query = Some(
+                PathAndQuery::try_from(path_and_query)
+                    .map_err(|_| ScriptError::Eval("invalid path".to_string()))?,
+            );
+            *req.uri_mut() = Uri::from_parts(parts)
+                .map_err(|_| ScriptError::Eval("invalid path".to_string()))?;
+        }
+
+        Ok(self.inner.call(req).await?.into_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{endpoint::make_sync, http::header, test::TestClient, EndpointExt};
+
+    fn echo() -> impl Endpoint {
+        make_sync(|req| {
+            format!(
+                "{} {}",
+                req.uri(),
+                req.headers()
+                    .get("x-user")
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or("-")
+            )
+        })
+    }
+
+    #[tokio::test]
+    async fn rewrite() {
+        let script = RhaiScript::new(
+            r#"
+            path = "/api" + path;
+            query += "&from=gateway";
+            headers["x-user"] = headers.remove("x-token");
+            "#,
+        )
+        .unwrap();
+        let cli = TestClient::new(echo().with(script));
+
+        let resp = cli
+            .get("/users")
+            .query("a", &1)
+            .header("x-token", "alice")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("/api/users?a=1&from=gateway alice").await;
+    }
+
+    #[tokio::test]
+    async fn multiple_values() {
+        let script = RhaiScript::new(
+            r#"
+            headers["x-count"] = headers["cookie"].len().to_string();
+            headers["cookie"] += "c=3";
+            "#,
+        )
+        .unwrap();
+        let ep = make_sync(|req| {
+            let cookies = req
+                .headers()
+                .get_all(header::COOKIE)
+                .iter()
+                .map(|value| value.to_str().unwrap())
+                .collect::<Vec<_>>();
+            format!("{} {}", req.header("x-count").unwrap(), cookies.join("|"))
+        });
+        let cli = TestClient::new(ep.with(script));
+
+        let resp = cli
+            .get("/")
+            .header(header::COOKIE, "a=1")
+            .header(header::COOKIE, "b=2")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("2 a=1|b=2|c=3").await;
+
+        let script = RhaiScript::new(
+            r#"#{ headers: #{ "set-cookie": ["a=1", "b=2"], "x-a": "1" } }"#,
+        )
+        .unwrap();
+        let resp = TestClient::new(echo().with(script)).get("/").send().await;
+        resp.assert_header_all(header::SET_COOKIE, ["a=1", "b=2"]);
+        resp.assert_header("x-a", "1");
+    }
+
+    #[tokio::test]
+    async fn limits() {
+        let script = RhaiScript::new("loop {}").unwrap();
+        let cli = TestClient::new(echo().with(script.clone()));
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+        script.reload(r#"let s = "a"; loop { s += s; }"#).unwrap();
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn short_circuit() {
+        let script = RhaiScript::new(
+            r#"
+            if method == "DELETE" {
+                return #{ status: 403, body: "forbidden", headers: #{ "x-reason": "method" } };
+            }
+            "#,
+        )
+        .unwrap();
+        let cli = TestClient::new(echo().with(script));
+
+        let resp = cli.delete("/").send().await;
+        resp.assert_status(StatusCode::FORBIDDEN);
+        resp.assert_header("x-reason", "method");
+        resp.assert_text("forbidden").await;
+
+        cli.get("/").send().await.assert_status_is_ok();
+    }
+
+    #[tokio::test]
+    async fn reload() {
+        let script = RhaiScript::new("").unwrap();
+        let cli = TestClient::new(echo().with(script.clone()));
+        cli.get("/").send().await.assert_status_is_ok();
+
+        script.reload("#{ status: 503 }").unwrap();
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+        assert!(matches!(
+            script.reload("if {"),
+            Err(ScriptError::Compile(_))
+        ));
+
+        script.reload("42").unwrap();
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,358 @@
+use crate::{
+    http::{header, HeaderValue},
+    Endpoint, Middleware, Request, Response, Result,
+};
+
+/// Controls whether the response can be embedded in a frame by other pages,
+/// with the `X-Frame-Options` header and the `frame-ancestors` directive of
+/// the `Content-Security-Policy` header.
+///
+/// It is used by the [`SecurityHeaders`] middleware as the default policy,
+/// and can also be applied to a route as a middleware to override the default
+/// policy for that route.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FrameOptions {
+    /// The response cannot be embedded by any page.
+    Deny,
+    /// The response can only be embedded by pages of the same origin.
+    SameOrigin,
+    /// The response can only be embedded by pages of the specified origins,
+    /// created with [`FrameOptions::allow_from`].
+    ///
+    /// Only the `frame-ancestors` directive is sent, because `X-Frame-Options:
+    /// ALLOW-FROM` is not supported by modern browsers.
+    AllowFrom(FrameOrigins),
+    /// The response can be embedded by any page, no header is sent.
+    Any,
+}
+
+/// The validated origins of [`FrameOptions::AllowFrom`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FrameOrigins(Vec<String>);
+
+impl FrameOrigins {
+    /// Returns the origins.
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl FrameOptions {
+    /// Create a [`FrameOptions::AllowFrom`] with the specified origins.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the origins is not a valid header value or contains
+    /// `;` or `,`.
+    pub fn allow_from<I, T>(origins: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        FrameOptions::AllowFrom(FrameOrigins(
+            origins
+                .into_iter()
+                .map(|origin| {
+                    let origin = origin.into();
+                    if HeaderValue::from_str(&origin).is_err() || origin.contains([';', ',']) {
+                        panic!("illegal origin");
+                    }
+                    origin
+                })
+                .collect(),
+        ))
+    }
+
+    fn x_frame_options(&self) -> Option<HeaderValue> {
+        match self {
+            FrameOptions::Deny => Some(HeaderValue::from_static("DENY")),
+            FrameOptions::SameOrigin => Some(HeaderValue::from_static("SAMEORIGIN")),
+            FrameOptions::AllowFrom(_) | FrameOptions::Any => None,
+        }
+    }
+
+    fn frame_ancestors(&self) -> Option<String> {
+        match self {
+            FrameOptions::Deny => Some("frame-ancestors 'none'".to_string()),
+            FrameOptions::SameOrigin => Some("frame-ancestors 'self'".to_string()),
+            FrameOptions::AllowFrom(FrameOrigins(origins)) if origins.is_empty() => {
+                Some("frame-ancestors 'none'".to_string())
+            }
+            FrameOptions::AllowFrom(FrameOrigins(origins)) => {
+                Some(format!("frame-ancestors {}", origins.join(" ")))
+            }
+            FrameOptions::Any => None,
+        }
+    }
+
+    fn apply(&self, resp: &mut Response) {
+        let headers = resp.headers_mut();
+
+        if !headers.contains_key(header::X_FRAME_OPTIONS) {
+            if let Some(value) = self.x_frame_options() {
+                headers.insert(header::X_FRAME_OPTIONS, value);
+            }
+        }
+
+        if let Some(frame_ancestors) = self.frame_ancestors() {
+            let csp = match headers
+                .get(header::CONTENT_SECURITY_POLICY)
+                .and_then(|value| value.to_str().ok())
+            {
+                Some(csp) if csp.contains("frame-ancestors") => None,
+                Some(csp) if !csp.trim().is_empty() => Some(format!(
+                    "{}; {frame_ancestors}",
+                    csp.trim().trim_end_matches(';')
+                )),
+                _ => Some(frame_ancestors),
+            };
+            if let Some(value) = csp.and_then(|csp| HeaderValue::try_from(csp).ok()) {
+                headers.insert(header::CONTENT_SECURITY_POLICY, value);
+            }
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for FrameOptions {
+    type Output = FrameOptionsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        FrameOptionsEndpoint {
+            inner: ep,
+            options: self.clone(),
+        }
+    }
+}
+
+/// Endpoint for FrameOptions middleware.
+pub struct FrameOptionsEndpoint<E> {
+    inner: E,
+    options: FrameOptions,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for FrameOptionsEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let mut resp = self.inner.get_response(req).await;
+        resp.set_data(self.options.clone());
+        Ok(resp)
+    }
+}
+
+/// Middleware for adding common security headers to responses.
+///
+/// The following headers are added unless they are already present:
+///
+/// - `X-Frame-Options` and the `frame-ancestors` directive of
+///   `Content-Security-Policy`, according to [`FrameOptions`], default to
+///   [`FrameOptions::Deny`].
+/// - `X-Content-Type-Options: nosniff`, can be disabled with
+///   [`SecurityHeaders::nosniff`].
+///
+/// Use [`FrameOptions`] as a middleware on a route to override the frame
+/// policy for that route.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     get, handler,
+///     middleware::{FrameOptions, SecurityHeaders},
+///     test::TestClient,
+///     EndpointExt, Route,
+/// };
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .at(
+///         "/widget",
+///         get(index).with(FrameOptions::allow_from(["https://partner.com"])),
+///     )
+///     .with(SecurityHeaders::new());
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_header("x-frame-options", "DENY");
+/// resp.assert_header("content-security-policy", "frame-ancestors 'none'");
+///
+/// let resp = cli.get("/widget").send().await;
+/// resp.assert_header_is_not_exist("x-frame-options");
+/// resp.assert_header(
+///     "content-security-policy",
+///     "frame-ancestors https://partner.com",
+/// );
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    frame_options: FrameOptions,
+    nosniff: bool,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self {
+            frame_options: FrameOptions::Deny,
+            nosniff: true,
+        }
+    }
+}
+
+impl SecurityHeaders {
+    /// Create new `SecurityHeaders` middleware.
+    #[must_use]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the default frame policy, default to [`FrameOptions::Deny`].
+    #[must_use]
+    pub fn frame_options(self, frame_options: FrameOptions) -> Self {
+        Self {
+            frame_options,
+            ..self
+        }
+    }
+
+    /// Sets whether to add `X-Content-Type-Options: nosniff`, default to
+    /// `true`.
+    #[must_use]
+    pub fn nosniff(self, nosniff: bool) -> Self {
+        Self { nosniff, ..self }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for SecurityHeaders {
+    type Output = SecurityHeadersEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        SecurityHeadersEndpoint {
+            inner: ep,
+            config: self.clone(),
+        }
+    }
+}
+
+/// Endpoint for SecurityHeaders middleware.
+pub struct SecurityHeadersEndpoint<E> {
+    inner: E,
+    config: SecurityHeaders,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for SecurityHeadersEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let mut resp = self.inner.get_response(req).await;
+
+        let frame_options = resp
+            .extensions_mut()
+            .remove::<FrameOptions>()
+            .unwrap_or_else(|| self.config.frame_options.clone());
+        frame_options.apply(&mut resp);
+
+        if self.config.nosniff && !resp.headers().contains_key(header::X_CONTENT_TYPE_OPTIONS) {
+            resp.headers_mut().insert(
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            );
+        }
+
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{handler, test::TestClient, EndpointExt, IntoResponse};
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    #[tokio::test]
+    async fn default_headers() {
+        let cli = TestClient::new(index.with(SecurityHeaders::new()));
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header(header::X_FRAME_OPTIONS, "DENY");
+        resp.assert_header(header::CONTENT_SECURITY_POLICY, "frame-ancestors 'none'");
+        resp.assert_header(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+
+        let cli = TestClient::new(
+            index.with(
+                SecurityHeaders::new()
+                    .frame_options(FrameOptions::Any)
+                    .nosniff(false),
+            ),
+        );
+        let resp = cli.get("/").send().await;
+        resp.assert_header_is_not_exist(header::X_FRAME_OPTIONS);
+        resp.assert_header_is_not_exist(header::CONTENT_SECURITY_POLICY);
+        resp.assert_header_is_not_exist(header::X_CONTENT_TYPE_OPTIONS);
+    }
+
+    #[tokio::test]
+    async fn route_override() {
+        let cli = TestClient::new(
+            index
+                .with(FrameOptions::SameOrigin)
+                .with(SecurityHeaders::new()),
+        );
+        let resp = cli.get("/").send().await;
+        resp.assert_header(header::X_FRAME_OPTIONS, "SAMEORIGIN");
+        resp.assert_header(header::CONTENT_SECURITY_POLICY, "frame-ancestors 'self'");
+
+        let cli = TestClient::new(
+            index
+                .with(FrameOptions::allow_from(["https://a.com", "https://b.com"]))
+                .with(SecurityHeaders::new()),
+        );
+        let resp = cli.get("/").send().await;
+        resp.assert_header_is_not_exist(header::X_FRAME_OPTIONS);
+        resp.assert_header(
+            header::CONTENT_SECURITY_POLICY,
+            "frame-ancestors https://a.com https://b.com",
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_csp() {
+        #[handler(internal)]
+        fn with_csp() -> impl IntoResponse {
+            "hello".with_header(header::CONTENT_SECURITY_POLICY, "default-src 'self';")
+        }
+
+        let cli = TestClient::new(with_csp.with(SecurityHeaders::new()));
+        let resp = cli.get("/").send().await;
+        resp.assert_header(
+            header::CONTENT_SECURITY_POLICY,
+            "default-src 'self'; frame-ancestors 'none'",
+        );
+    }
+
+    #[test]
+    fn allow_from() {
+        let FrameOptions::AllowFrom(origins) = FrameOptions::allow_from(["https://a.com"]) else {
+            panic!("expect `AllowFrom`");
+        };
+        assert_eq!(origins.as_slice(), ["https://a.com"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn illegal_origin() {
+        let _ = FrameOptions::allow_from(["https://a.com; script-src *"]);
+    }
+}
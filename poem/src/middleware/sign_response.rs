@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use base64::engine::{general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::{
+    error::KeyRejected,
+    hmac,
+    signature::{Ed25519KeyPair, KeyPair},
+};
+
+use crate::{
+    http::{header::HeaderName, HeaderValue, StatusCode},
+    Endpoint, Error, Middleware, Request, Response, Result,
+};
+
+#[derive(Clone)]
+enum KeyKind {
+    Hs256(hmac::Key),
+    Ed25519(Arc<Ed25519KeyPair>),
+}
+
+/// A key used by the [`SignResponse`] middleware to sign the response bodies.
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+#[derive(Clone)]
+pub struct SigningKey {
+    kind: KeyKind,
+    key_id: Option<String>,
+}
+
+impl SigningKey {
+    /// Create a `HS256` (HMAC with SHA-256) key with the shared secret.
+    pub fn hs256(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            kind: KeyKind::Hs256(hmac::Key::new(hmac::HMAC_SHA256, secret.as_ref())),
+            key_id: None,
+        }
+    }
+
+    /// Create an `EdDSA` (Ed25519) key from a PKCS#8 v1 or v2 document.
+    pub fn ed25519_from_pkcs8(pkcs8: impl AsRef<[u8]>) -> Result<Self, KeyRejected> {
+        Ok(Self {
+            kind: KeyKind::Ed25519(Arc::new(Ed25519KeyPair::from_pkcs8_maybe_unchecked(
+                pkcs8.as_ref(),
+            )?)),
+            key_id: None,
+        })
+    }
+
+    /// Sets the key ID, which is added to the `kid` parameter of the JWS
+    /// header so that the clients can choose the key to verify the
+    /// signature.
+    #[must_use]
+    pub fn key_id(self, key_id: impl Into<String>) -> Self {
+        Self {
+            key_id: Some(key_id.into()),
+            ..self
+        }
+    }
+
+    /// Returns the public key of the `EdDSA` key, or `None` for the `HS256`
+    /// key.
+    pub fn public_key(&self) -> Option<&[u8]> {
+        match &self.kind {
+            KeyKind::Hs256(_) => None,
+            KeyKind::Ed25519(key_pair) => Some(key_pair.public_key().as_ref()),
+        }
+    }
+
+    fn alg(&self) -> &'static str {
+        match &self.kind {
+            KeyKind::Hs256(_) => "HS256",
+            KeyKind::Ed25519(_) => "EdDSA",
+        }
+    }
+
+    fn sign(&self, data: &[u8]) -> Vec<u8> {
+        match &self.kind {
+            KeyKind::Hs256(key) => hmac::sign(key, data).as_ref().to_vec(),
+            KeyKind::Ed25519(key_pair) => key_pair.sign(data).as_ref().to_vec(),
+        }
+    }
+
+    /// Returns the detached JWS (RFC 7515, Appendix F) of the payload, in the
+    /// form of `<header>..<signature>`.
+    pub fn detached_jws(&self, payload: &[u8]) -> String {
+        let mut header = serde_json::json!({ "alg": self.alg() });
+        if let Some(key_id) = &self.key_id {
+            header["kid"] = key_id.clone().into();
+        }
+        let header = URL_SAFE_NO_PAD.encode(header.to_string());
+        let signing_input = format!("{header}.{}", URL_SAFE_NO_PAD.encode(payload));
+        let signature = URL_SAFE_NO_PAD.encode(self.sign(signing_input.as_bytes()));
+        format!("{header}..{signature}")
+    }
+}
+
+/// Middleware for signing the response bodies with a detached JWS, so that
+/// the clients can verify the authenticity of the payloads end-to-end.
+///
+/// The signature is added to the `X-JWS-Signature` header by default, the
+/// clients reconstruct the JWS by inserting the base64url-encoded body
+/// between the two dots.
+///
+/// NOTE: The whole response body is buffered in memory to compute the
+/// signature.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     middleware::{SignResponse, SigningKey},
+///     test::TestClient,
+///     EndpointExt,
+/// };
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let key = SigningKey::hs256(b"secret").key_id("key-1");
+/// let app = index.with(SignResponse::new(key.clone()));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_header("x-jws-signature", key.detached_jws(b"hello"));
+/// resp.assert_text("hello").await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+#[derive(Clone)]
+pub struct SignResponse {
+    key: SigningKey,
+    header_name: HeaderName,
+}
+
+impl SignResponse {
+    /// Create new `SignResponse` middleware with the signing key.
+    #[must_use]
+    pub fn new(key: SigningKey) -> Self {
+        Self {
+            key,
+            header_name: HeaderName::from_static("x-jws-signature"),
+        }
+    }
+
+    /// Sets the name of the header that contains the signature, default to
+    /// `X-JWS-Signature`.
+    #[must_use]
+    pub fn header_name(self, header_name: HeaderName) -> Self {
+        Self {
+            header_name,
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for SignResponse {
+    type Output = SignResponseEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        SignResponseEndpoint {
+            inner: ep,
+            config: self.clone(),
+        }
+    }
+}
+
+/// Endpoint for SignResponse middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+pub struct SignResponseEndpoint<E> {
+    inner: E,
+    config: SignResponse,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for SignResponseEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let mut resp = self.inner.get_response(req).await;
+        let body = resp
+            .take_body()
+            .into_bytes()
+            .await
+            .map_err(|err| Error::new(err, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+        let signature = self.config.key.detached_jws(&body);
+        resp.headers_mut().insert(
+            self.config.header_name.clone(),
+            HeaderValue::try_from(signature)
+                .expect("base64url encoded value is a valid header value"),
+        );
+        resp.set_body(body);
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ring::{
+        rand::SystemRandom,
+        signature::{UnparsedPublicKey, ED25519},
+    };
+
+    use super::*;
+    use crate::{handler, test::TestClient, EndpointExt};
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    fn split_jws(jws: &str, payload: &[u8]) -> (serde_json::Value, String, Vec<u8>) {
+        let (header, signature) = jws.split_once("..").unwrap();
+        let header_json: serde_json::Value =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header).unwrap()).unwrap();
+        let signing_input = format!("{header}.{}", URL_SAFE_NO_PAD.encode(payload));
+        (
+            header_json,
+            signing_input,
+            URL_SAFE_NO_PAD.decode(signature).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn hs256() {
+        let cli = TestClient::new(
+            index.with(SignResponse::new(SigningKey::hs256(b"secret").key_id("k1"))),
+        );
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        let jws = resp
+            .0
+            .headers()
+            .get("x-jws-signature")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        resp.assert_text("hello").await;
+
+        let (header, signing_input, signature) = split_jws(&jws, b"hello");
+        assert_eq!(header, serde_json::json!({ "alg": "HS256", "kid": "k1" }));
+        hmac::verify(
+            &hmac::Key::new(hmac::HMAC_SHA256, b"secret"),
+            signing_input.as_bytes(),
+            &signature,
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn ed25519() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let key = SigningKey::ed25519_from_pkcs8(pkcs8.as_ref()).unwrap();
+        let public_key = key.public_key().unwrap().to_vec();
+
+        let cli = TestClient::new(
+            index.with(SignResponse::new(key).header_name(HeaderName::from_static("signature"))),
+        );
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        let jws = resp
+            .0
+            .headers()
+            .get("signature")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let (header, signing_input, signature) = split_jws(&jws, b"hello");
+        assert_eq!(header, serde_json::json!({ "alg": "EdDSA" }));
+        UnparsedPublicKey::new(&ED25519, public_key)
+            .verify(signing_input.as_bytes(), &signature)
+            .unwrap();
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use crate::{
+    http::{header, HeaderName},
+    web::SubrequestDispatcher,
+    Endpoint, EndpointExt, Middleware, Request, Result,
+};
+
+/// Middleware that enables the [`Subrequest`](crate::web::Subrequest)
+/// extractor, which sends internal subrequests to the wrapped endpoint.
+///
+/// The subrequests are handled by the wrapped endpoint, including the
+/// middlewares applied inside of this one, so apply it to the router after
+/// the middlewares that the subrequests should not pass through again, such as
+/// the access logs.
+///
+/// See [`Subrequest`](crate::web::Subrequest) for an example.
+pub struct Subrequests {
+    inherit_headers: Vec<HeaderName>,
+    max_depth: usize,
+}
+
+impl Default for Subrequests {
+    fn default() -> Self {
+        Self {
+            inherit_headers: vec![
+                header::AUTHORIZATION,
+                header::COOKIE,
+                header::ACCEPT_LANGUAGE,
+            ],
+            max_depth: 4,
+        }
+    }
+}
+
+impl Subrequests {
+    /// Create `Subrequests` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a header that the subrequests inherit from the current request.
+    ///
+    /// Default are `Authorization`, `Cookie` and `Accept-Language`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name.
+    #[must_use]
+    pub fn inherit_header(mut self, name: impl AsRef<str>) -> Self {
+        let name = HeaderName::try_from(name.as_ref()).expect("valid header name");
+        if !self.inherit_headers.contains(&name) {
+            self.inherit_headers.push(name);
+        }
+        self
+    }
+
+    /// Sets the maximum nesting depth of the subrequests, to stop the
+    /// subrequests that call themselves.
+    ///
+    /// Default is `4`.
+    #[must_use]
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+}
+
+impl<E: Endpoint + 'static> Middleware<E> for Subrequests {
+    type Output = SubrequestsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        let inner = Arc::new(ep);
+        SubrequestsEndpoint {
+            inner: inner.clone(),
+            dispatcher: SubrequestDispatcher {
+                ep: Arc::new(inner.map_to_response()),
+                inherit_headers: Arc::new(self.inherit_headers.clone()),
+                max_depth: self.max_depth,
+            },
+        }
+    }
+}
+
+/// Endpoint for Subrequests middleware.
+pub struct SubrequestsEndpoint<E> {
+    inner: Arc<E>,
+    dispatcher: SubrequestDispatcher,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for SubrequestsEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        req.extensions_mut().insert(self.dispatcher.clone());
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        get, handler,
+        http::StatusCode,
+        test::TestClient,
+        web::{Data, Subrequest},
+        Route,
+    };
+
+    #[derive(Clone)]
+    struct User(&'static str);
+
+    #[handler(internal)]
+    fn whoami(req: &Request, user: Option<Data<&User>>) -> String {
+        format!(
+            "{} {} {}",
+            user.map(|user| user.0 .0).unwrap_or("anonymous"),
+            req.header(header::AUTHORIZATION).unwrap_or_default(),
+            req.header("x-other").unwrap_or_default(),
+        )
+    }
+
+    #[handler(internal)]
+    async fn proxy(sub: Subrequest, req: &Request) -> Result<String> {
+        let path = match req.uri().query() {
+            Some("loop") => "/proxy?loop",
+            Some(path) => path,
+            None => "/whoami",
+        };
+        let resp = sub.get(path).await?;
+        Ok(format!(
+            "{} {}",
+            resp.status().as_u16(),
+            resp.into_body().into_string().await?
+        ))
+    }
+
+    #[tokio::test]
+    async fn subrequest() {
+        let app = Route::new()
+            .at("/whoami", get(whoami))
+            .at("/proxy", get(proxy))
+            .with(Subrequests::new().max_depth(2))
+            .data(User("alice"));
+        let cli = TestClient::new(app);
+
+        // inherits the extensions and the `Authorization` header only
+        cli.get("/proxy")
+            .header(header::AUTHORIZATION, "Bearer 1")
+            .header("x-other", "1")
+            .send()
+            .await
+            .assert_text("200 alice Bearer 1 ")
+            .await;
+
+        let resp = cli.get("/proxy?/missing").send().await;
+        let text = resp.0.into_body().into_string().await.unwrap();
+        assert!(text.starts_with("404 "), "{text}");
+
+        // `/proxy` calls itself until the maximum depth is exceeded
+        let resp = cli.get("/proxy?loop").send().await;
+        let text = resp.0.into_body().into_string().await.unwrap();
+        assert!(text.starts_with("200 508 "), "{text}");
+    }
+
+    #[tokio::test]
+    async fn not_enabled() {
+        let cli = TestClient::new(Route::new().at("/proxy", get(proxy)));
+        cli.get("/proxy")
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
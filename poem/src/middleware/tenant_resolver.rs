@@ -0,0 +1,419 @@
+use std::sync::Arc;
+
+use crate::{
+    error::TenantError,
+    http::{header, uri::PathAndQuery, HeaderName, Uri},
+    web::{Tenant, TenantId, TenantProvider},
+    Endpoint, Middleware, Request, Result,
+};
+
+#[derive(Clone)]
+enum Strategy {
+    Subdomain(String),
+    PathPrefix,
+    Header(HeaderName),
+}
+
+impl Strategy {
+    fn candidate(&self, req: &Request) -> Option<String> {
+        match self {
+            Strategy::Subdomain(base_domain) => {
+                let host = req
+                    .header(header::HOST)
+                    .or_else(|| req.uri().host())?
+                    .split(':')
+                    .next()?
+                    .to_ascii_lowercase();
+                let subdomain = host.strip_suffix(base_domain.as_str())?.strip_suffix('.')?;
+                (!subdomain.is_empty() && !subdomain.contains('.')).then(|| subdomain.to_string())
+            }
+            Strategy::PathPrefix => req
+                .uri()
+                .path()
+                .strip_prefix('/')?
+                .split('/')
+                .next()
+                .filter(|segment| !segment.is_empty())
+                .map(ToString::to_string),
+            Strategy::Header(name) => req
+                .header(name)
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(ToString::to_string),
+        }
+    }
+}
+
+/// Middleware for resolving the tenant of the request.
+///
+/// The strategies are tried in the order in which they were added, the first
+/// one that identifies a tenant known by the [`TenantProvider`] wins. The
+/// resolved tenant is inserted into the request extensions as
+/// [`Tenant<T>`](Tenant) and [`TenantId`], and can be extracted by the
+/// handlers.
+///
+/// When the tenant is resolved by the path prefix, the prefix is removed from
+/// the path of the request, so `/acme/users` is routed as `/users`.
+///
+/// # Errors
+///
+/// - [`TenantError::Missing`] if none of the strategies identifies a tenant.
+/// - [`TenantError::Unknown`] if the identified tenants do not exist.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use poem::{
+///     endpoint::make_sync, http::StatusCode, middleware::TenantResolver, test::TestClient,
+///     web::TenantId, EndpointExt,
+/// };
+///
+/// let tenants = HashMap::from([("acme".to_string(), ()), ("globex".to_string(), ())]);
+/// let ep = make_sync(|req| {
+///     format!(
+///         "{} {}",
+///         req.extensions().get::<TenantId>().unwrap(),
+///         req.uri().path()
+///     )
+/// })
+/// .with(
+///     TenantResolver::new(tenants)
+///         .header("x-tenant-id")
+///         .path_prefix(),
+/// );
+/// let cli = TestClient::new(ep);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/users").header("x-tenant-id", "acme").send().await;
+/// resp.assert_text("acme /users").await;
+///
+/// let resp = cli.get("/globex/users").send().await;
+/// resp.assert_text("globex /users").await;
+///
+/// let resp = cli.get("/initech/users").send().await;
+/// resp.assert_status(StatusCode::NOT_FOUND);
+/// # });
+/// ```
+pub struct TenantResolver<P> {
+    provider: Arc<P>,
+    strategies: Vec<Strategy>,
+    optional: bool,
+}
+
+impl<P: TenantProvider> TenantResolver<P> {
+    /// Create `TenantResolver` middleware with the provider of the tenants.
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            strategies: Vec::new(),
+            optional: false,
+        }
+    }
+
+    /// Resolves the tenant by the subdomain of `base_domain` in the `Host`
+    /// header, e.g. `acme` in `acme.example.com`.
+    #[must_use]
+    pub fn subdomain(mut self, base_domain: impl Into<String>) -> Self {
+        let base_domain = base_domain.into();
+        self.strategies.push(Strategy::Subdomain(
+            base_domain.trim_matches('.').to_ascii_lowercase(),
+        ));
+        self
+    }
+
+    /// Resolves the tenant by the first segment of the path, e.g. `acme` in
+    /// `/acme/users`.
+    #[must_use]
+    pub fn path_prefix(mut self) -> Self {
+        self.strategies.push(Strategy::PathPrefix);
+        self
+    }
+
+    /// Resolves the tenant by the value of the header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name.
+    #[must_use]
+    pub fn header(mut self, name: impl AsRef<str>) -> Self {
+        let name = HeaderName::try_from(name.as_ref()).expect("valid header name");
+        self.strategies.push(Strategy::Header(name));
+        self
+    }
+
+    /// Passes the requests that do not identify a tenant to the inner
+    /// endpoint, instead of failing with [`TenantError::Missing`].
+    ///
+    /// The requests that identify an unknown tenant still fail with
+    /// [`TenantError::Unknown`].
+    #[must_use]
+    pub fn optional(self) -> Self {
+        Self {
+            optional: true,
+            ..self
+        }
+    }
+}
+
+impl<P: TenantProvider, E: Endpoint> Middleware<E> for TenantResolver<P> {
+    type Output = TenantResolverEndpoint<P, E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        TenantResolverEndpoint {
+            inner: ep,
+            provider: self.provider.clone(),
+            strategies: Arc::new(self.strategies.clone()),
+            optional: self.optional,
+        }
+    }
+}
+
+/// Endpoint for TenantResolver middleware.
+pub struct TenantResolverEndpoint<P, E> {
+    inner: E,
+    provider: Arc<P>,
+    strategies: Arc<Vec<Strategy>>,
+    optional: bool,
+}
+
+fn strip_path_prefix(uri: &Uri, prefix: &str) -> Option<Uri> {
+    let path = &uri.path()[prefix.len() + 1..];
+    let path_and_query = match (path, uri.query()) {
+        ("", Some(query)) => format!("/?{query}"),
+        ("", None) => "/".to_string(),
+        (path, Some(query)) => format!("{path}?{query}"),
+        (path, None) => path.to_string(),
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(PathAndQuery::try_from(path_and_query).ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+#[async_trait::async_trait]
+impl<P: TenantProvider, E: Endpoint> Endpoint for TenantResolverEndpoint<P, E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let mut unknown = None;
+
+        for strategy in self.strategies.iter() {
+            let id = match strategy.candidate(&req) {
+                Some(id) => id,
+                None => continue,
+            };
+            let config = match self.provider.get_tenant(&id).await? {
+                Some(config) => config,
+                None => {
+                    unknown.get_or_insert(id);
+                    continue;
+                }
+            };
+
+            if let Strategy::PathPrefix = strategy {
+                if let Some(uri) = strip_path_prefix(req.uri(), &id) {
+                    *req.uri_mut() = uri;
+                }
+            }
+
+            let id = TenantId(id.into());
+            req.extensions_mut().insert(id.clone());
+            req.extensions_mut().insert(Tenant::new(id, config));
+            return self.inner.call(req).await;
+        }
+
+        match unknown {
+            Some(id) => Err(TenantError::Unknown(id).into()),
+            None if self.optional => self.inner.call(req).await,
+            None => Err(TenantError::Missing.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        handler,
+        http::StatusCode,
+        middleware::{BotPolicy, CrawlBudget},
+        test::TestClient,
+        EndpointExt,
+    };
+
+    #[derive(Debug, Clone)]
+    struct Config {
+        name: &'static str,
+    }
+
+    fn tenants() -> HashMap<String, Config> {
+        HashMap::from([
+            ("acme".to_string(), Config { name: "ACME" }),
+            ("globex".to_string(), Config { name: "Globex" }),
+        ])
+    }
+
+    #[handler(internal)]
+    fn index(req: &Request, tenant: Tenant<Config>) -> String {
+        format!("{} {} {}", tenant.id(), tenant.name, req.uri())
+    }
+
+    #[tokio::test]
+    async fn resolve_chain() {
+        let cli = TestClient::new(
+            index.with(
+                TenantResolver::new(tenants())
+                    .subdomain("example.com")
+                    .header("x-tenant")
+                    .path_prefix(),
+            ),
+        );
+
+        let resp = cli
+            .get("/a")
+            .header(header::HOST, "Globex.Example.com:3000")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("globex Globex /a").await;
+
+        let resp = cli
+            .get("/a")
+            .header(header::HOST, "example.com")
+            .header("x-tenant", "acme")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("acme ACME /a").await;
+
+        // the unknown subdomain falls through to the path prefix
+        let resp = cli
+            .get("/acme/a/b")
+            .header(header::HOST, "www.example.com")
+            .query("x", &1)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("acme ACME /a/b?x=1").await;
+
+        let resp = cli.get("/globex").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("globex Globex /").await;
+
+        cli.get("/initech/a")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        cli.get("/")
+            .header(header::HOST, "a.b.example.com")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn optional() {
+        #[handler(internal)]
+        fn index(tenant: Option<TenantId>) -> String {
+            tenant.map(|id| id.to_string()).unwrap_or_default()
+        }
+
+        let cli = TestClient::new(
+            index.with(TenantResolver::new(tenants()).header("x-tenant").optional()),
+        );
+        cli.get("/").send().await.assert_text("").await;
+        cli.get("/")
+            .header("x-tenant", "acme")
+            .send()
+            .await
+            .assert_text("acme")
+            .await;
+        cli.get("/")
+            .header("x-tenant", "initech")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn isolated_crawl_budget() {
+        let cli = TestClient::new(
+            index
+                .with(CrawlBudget::new().default_policy(
+                    BotPolicy::new().rate_limit(1, std::time::Duration::from_secs(60)),
+                ))
+                .with(TenantResolver::new(tenants()).header("x-tenant")),
+        );
+
+        for tenant in ["acme", "globex"] {
+            cli.get("/")
+                .header("x-tenant", tenant)
+                .header(header::USER_AGENT, "Googlebot/2.1")
+                .send()
+                .await
+                .assert_status_is_ok();
+        }
+        cli.get("/")
+            .header("x-tenant", "acme")
+            .header(header::USER_AGENT, "Googlebot/2.1")
+            .send()
+            .await
+            .assert_status(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[cfg(feature = "session")]
+    #[tokio::test]
+    async fn isolated_sessions() {
+        use crate::session::{CookieConfig, MemoryStorage, ServerSession, Session};
+
+        #[handler(internal)]
+        fn index(session: &Session, tenant: TenantId) -> String {
+            let value = session.get::<String>("tenant").unwrap_or_default();
+            if value.is_empty() {
+                session.set("tenant", tenant.as_str());
+            }
+            value
+        }
+
+        let cli = TestClient::new(
+            index
+                .with(ServerSession::new(
+                    CookieConfig::default(),
+                    MemoryStorage::new(),
+                ))
+                .with(TenantResolver::new(tenants()).header("x-tenant")),
+        );
+
+        let resp = cli.get("/").header("x-tenant", "acme").send().await;
+        let cookie = resp
+            .0
+            .headers()
+            .get(header::SET_COOKIE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_string();
+        resp.assert_text("").await;
+
+        cli.get("/")
+            .header("x-tenant", "acme")
+            .header(header::COOKIE, &cookie)
+            .send()
+            .await
+            .assert_text("acme")
+            .await;
+        cli.get("/")
+            .header("x-tenant", "globex")
+            .header(header::COOKIE, &cookie)
+            .send()
+            .await
+            .assert_text("")
+            .await;
+    }
+}
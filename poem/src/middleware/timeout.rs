@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use crate::{error::TimeoutError, http::StatusCode, Endpoint, Middleware, Request, Result};
+
+/// Middleware for cancelling the requests whose handlers do not complete
+/// within the duration.
+///
+/// When the timeout elapses, the future of the inner endpoint is dropped and
+/// [`TimeoutError`] is returned, with `408 Request Timeout` by default. Use
+/// [`Timeout::status`] to respond with `504 Gateway Timeout` instead, which is
+/// more appropriate for the endpoints that wait for the upstream services.
+///
+/// Only producing the response is limited, the body of the response can be
+/// streamed for any length of time. The paths added by [`Timeout::exempt`]
+/// are never cancelled, so the long-polling and streaming routes can be
+/// mounted with the other routes.
+///
+/// # Errors
+///
+/// - [`TimeoutError`]
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{
+///     get, handler, http::StatusCode, middleware::Timeout, test::TestClient, EndpointExt, Route,
+/// };
+///
+/// #[handler]
+/// async fn slow() -> &'static str {
+///     tokio::time::sleep(Duration::from_secs(10)).await;
+///     "done"
+/// }
+///
+/// let app = Route::new()
+///     .at("/slow", get(slow))
+///     .with(Timeout::new(Duration::from_millis(50)).status(StatusCode::GATEWAY_TIMEOUT));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/slow").send().await;
+/// resp.assert_status(StatusCode::GATEWAY_TIMEOUT);
+/// # });
+/// ```
+pub struct Timeout {
+    duration: Duration,
+    status: StatusCode,
+    exempt: Vec<String>,
+}
+
+impl Timeout {
+    /// Create `Timeout` middleware with the duration.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            status: StatusCode::REQUEST_TIMEOUT,
+            exempt: Vec::new(),
+        }
+    }
+
+    /// Sets the status code of the response when the timeout elapses.
+    ///
+    /// Default is `408 Request Timeout`.
+    #[must_use]
+    pub fn status(self, status: StatusCode) -> Self {
+        Self { status, ..self }
+    }
+
+    /// Never cancels the requests whose path is `path` or starts with
+    /// `path/`.
+    #[must_use]
+    pub fn exempt(mut self, path: impl Into<String>) -> Self {
+        let path = path.into();
+        self.exempt.push(path.trim_end_matches('/').to_string());
+        self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Timeout {
+    type Output = TimeoutEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        TimeoutEndpoint {
+            inner: ep,
+            duration: self.duration,
+            status: self.status,
+            exempt: self.exempt.clone(),
+        }
+    }
+}
+
+/// Endpoint for Timeout middleware.
+pub struct TimeoutEndpoint<E> {
+    inner: E,
+    duration: Duration,
+    status: StatusCode,
+    exempt: Vec<String>,
+}
+
+impl<E> TimeoutEndpoint<E> {
+    fn is_exempt(&self, req: &Request) -> bool {
+        let path = req.uri().path();
+        self.exempt.iter().any(|prefix| {
+            path.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for TimeoutEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if self.is_exempt(&req) {
+            return self.inner.call(req).await;
+        }
+
+        match tokio::time::timeout(self.duration, self.inner.call(req)).await {
+            Ok(res) => res,
+            Err(_) => {
+                tracing::debug!(timeout = ?self.duration, "request timed out");
+                Err(TimeoutError(self.status).into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get, handler, test::TestClient, EndpointExt, Route};
+
+    #[handler(internal)]
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        "done"
+    }
+
+    #[handler(internal)]
+    fn fast() -> &'static str {
+        "done"
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let app = Route::new()
+            .at("/fast", get(fast))
+            .at("/slow", get(slow))
+            .at("/events/slow", get(slow))
+            .with(Timeout::new(Duration::from_millis(50)).exempt("/events/"));
+        let cli = TestClient::new(app);
+
+        cli.get("/fast").send().await.assert_text("done").await;
+        cli.get("/slow")
+            .send()
+            .await
+            .assert_status(StatusCode::REQUEST_TIMEOUT);
+        cli.get("/events/slow")
+            .send()
+            .await
+            .assert_text("done")
+            .await;
+        cli.get("/slow")
+            .header(crate::http::header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .assert_status(StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn status() {
+        let cli = TestClient::new(
+            slow.with(Timeout::new(Duration::from_millis(50)).status(StatusCode::GATEWAY_TIMEOUT)),
+        );
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::GATEWAY_TIMEOUT);
+    }
+}
@@ -0,0 +1,411 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use headers::{authorization::Bearer, Authorization, HeaderMapExt};
+use parking_lot::Mutex;
+use serde::Deserialize;
+
+use crate::{error::IntrospectionError, Endpoint, Middleware, Request, Result};
+
+/// The response of the token introspection endpoint, see
+/// [RFC 7662](https://www.rfc-editor.org/rfc/rfc7662#section-2.2).
+///
+/// It is added to the request extensions by the [`TokenIntrospection`]
+/// middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "introspection")))]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TokenInfo {
+    /// Whether the token is active.
+    pub active: bool,
+    /// The space-separated scopes associated with the token.
+    pub scope: Option<String>,
+    /// The client identifier for the OAuth 2.0 client that requested the
+    /// token.
+    pub client_id: Option<String>,
+    /// The human-readable identifier for the resource owner.
+    pub username: Option<String>,
+    /// The type of the token.
+    pub token_type: Option<String>,
+    /// The timestamp indicating when the token will expire.
+    pub exp: Option<u64>,
+    /// The timestamp indicating when the token was issued.
+    pub iat: Option<u64>,
+    /// The timestamp indicating when the token is not to be used before.
+    pub nbf: Option<u64>,
+    /// The subject of the token.
+    pub sub: Option<String>,
+    /// The intended audience of the token, a string or an array of strings.
+    pub aud: Option<serde_json::Value>,
+    /// The issuer of the token.
+    pub iss: Option<String>,
+    /// The identifier of the token.
+    pub jti: Option<String>,
+    /// Other members of the response.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TokenInfo {
+    /// Returns an iterator over the scopes associated with the token.
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scope.as_deref().unwrap_or_default().split_whitespace()
+    }
+
+    /// Returns `true` if the token has the specified scope.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().any(|s| s == scope)
+    }
+}
+
+struct CacheEntry {
+    info: Arc<TokenInfo>,
+    expires_at: Instant,
+}
+
+/// Middleware for validating opaque bearer tokens with an OAuth 2.0 token
+/// introspection endpoint, see
+/// [RFC 7662](https://www.rfc-editor.org/rfc/rfc7662).
+///
+/// The token in the `Authorization: Bearer` header is posted to the
+/// introspection endpoint, authenticated with the client credentials. If the
+/// token is active and has all the required scopes, the [`TokenInfo`] is added
+/// to the request extensions.
+///
+/// The introspection results are cached for 60 seconds by default, but never
+/// beyond the expiration time of the token.
+///
+/// # Errors
+///
+/// - [`IntrospectionError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     middleware::{TokenInfo, TokenIntrospection},
+///     web::Data,
+///     EndpointExt,
+/// };
+///
+/// #[handler]
+/// fn index(Data(token): Data<&TokenInfo>) -> String {
+///     format!("hello, {}", token.sub.as_deref().unwrap_or("anonymous"))
+/// }
+///
+/// let app = index.with(
+///     TokenIntrospection::new("https://auth.example.com/oauth2/introspect")
+///         .client_credentials("gateway", "secret")
+///         .required_scopes(["orders:read"]),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "introspection")))]
+#[derive(Clone)]
+pub struct TokenIntrospection {
+    endpoint: String,
+    client: reqwest::Client,
+    client_credentials: Option<(String, String)>,
+    required_scopes: Vec<String>,
+    cache_ttl: Duration,
+    max_cache_entries: usize,
+}
+
+impl TokenIntrospection {
+    /// Create new `TokenIntrospection` middleware with the URL of the
+    /// introspection endpoint.
+    #[must_use]
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            client: reqwest::Client::new(),
+            client_credentials: None,
+            required_scopes: Vec::new(),
+            cache_ttl: Duration::from_secs(60),
+            max_cache_entries: 10000,
+        }
+    }
+
+    /// Sets the client credentials used to authenticate to the introspection
+    /// endpoint with HTTP Basic authentication.
+    #[must_use]
+    pub fn client_credentials(
+        self,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            client_credentials: Some((client_id.into(), client_secret.into())),
+            ..self
+        }
+    }
+
+    /// Sets the HTTP client used to call the introspection endpoint.
+    #[must_use]
+    pub fn http_client(self, client: reqwest::Client) -> Self {
+        Self { client, ..self }
+    }
+
+    /// Sets the scopes that the token must have.
+    #[must_use]
+    pub fn required_scopes<I, T>(self, scopes: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            required_scopes: scopes.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    /// Sets how long the introspection results are cached, default to 60
+    /// seconds.
+    ///
+    /// Set to zero to disable the cache.
+    #[must_use]
+    pub fn cache_ttl(self, cache_ttl: Duration) -> Self {
+        Self { cache_ttl, ..self }
+    }
+
+    /// Sets the maximum number of cached introspection results, default to
+    /// `10000`.
+    #[must_use]
+    pub fn max_cache_entries(self, max_cache_entries: usize) -> Self {
+        Self {
+            max_cache_entries,
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for TokenIntrospection {
+    type Output = TokenIntrospectionEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        TokenIntrospectionEndpoint {
+            inner: ep,
+            config: self.clone(),
+            cache: Default::default(),
+        }
+    }
+}
+
+/// Endpoint for TokenIntrospection middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "introspection")))]
+pub struct TokenIntrospectionEndpoint<E> {
+    inner: E,
+    config: TokenIntrospection,
+    cache: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl<E> TokenIntrospectionEndpoint<E> {
+    fn cached(&self, token: &str) -> Option<Arc<TokenInfo>> {
+        let cache = self.cache.lock();
+        let entry = cache.get(token)?;
+        if entry.expires_at <= Instant::now() {
+            return None;
+        }
+        Some(entry.info.clone())
+    }
+
+    fn insert_cache(&self, token: &str, info: Arc<TokenInfo>) {
+        let mut ttl = self.config.cache_ttl;
+        if let Some(exp) = info.exp {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            ttl = ttl.min(Duration::from_secs(exp.saturating_sub(now)));
+        }
+        if ttl.is_zero() || self.config.max_cache_entries == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut cache = self.cache.lock();
+        if cache.len() >= self.config.max_cache_entries {
+            cache.retain(|_, entry| entry.expires_at > now);
+            if cache.len() >= self.config.max_cache_entries {
+                cache.clear();
+            }
+        }
+        cache.insert(
+            token.to_string(),
+            CacheEntry {
+                info,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    async fn introspect(&self, token: &str) -> Result<Arc<TokenInfo>, IntrospectionError> {
+        if let Some(info) = self.cached(token) {
+            return Ok(info);
+        }
+
+        let mut builder = self.config.client.post(&self.config.endpoint);
+        if let Some((client_id, client_secret)) = &self.config.client_credentials {
+            builder = builder.basic_auth(client_id, Some(client_secret));
+        }
+        let info = builder
+            .form(&[("token", token), ("token_type_hint", "access_token")])
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|err| IntrospectionError::Request(err.to_string()))?
+            .json::<TokenInfo>()
+            .await
+            .map_err(|err| IntrospectionError::Request(err.to_string()))?;
+
+        let info = Arc::new(info);
+        self.insert_cache(token, info.clone());
+        Ok(info)
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for TokenIntrospectionEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let token = req
+            .headers()
+            .typed_get::<Authorization<Bearer>>()
+            .ok_or(IntrospectionError::MissingToken)?;
+        let info = self.introspect(token.token()).await?;
+
+        if !info.active {
+            return Err(IntrospectionError::InvalidToken.into());
+        }
+        if let Some(scope) = self
+            .config
+            .required_scopes
+            .iter()
+            .find(|scope| !info.has_scope(scope))
+        {
+            return Err(IntrospectionError::InsufficientScope(scope.clone()).into());
+        }
+
+        req.extensions_mut().insert(TokenInfo::clone(&info));
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{
+        handler,
+        http::{header, StatusCode},
+        listener::{Acceptor, Listener, TcpListener},
+        test::TestClient,
+        web::{headers::authorization::Basic, Data, Form, Json, TypedHeader},
+        EndpointExt, Server,
+    };
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Deserialize)]
+    struct IntrospectRequest {
+        token: String,
+    }
+
+    #[handler(internal)]
+    fn introspect(
+        Form(req): Form<IntrospectRequest>,
+        auth: Option<TypedHeader<Authorization<Basic>>>,
+    ) -> Json<serde_json::Value> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        let authorized = matches!(
+            auth,
+            Some(TypedHeader(auth)) if auth.username() == "gateway" && auth.password() == "secret"
+        );
+        Json(match req.token.as_str() {
+            "good" if authorized => serde_json::json!({
+                "active": true,
+                "scope": "orders:read orders:write",
+                "sub": "sunli",
+                "custom": 1,
+            }),
+            _ => serde_json::json!({ "active": false }),
+        })
+    }
+
+    #[handler(internal)]
+    fn index(Data(info): Data<&TokenInfo>) -> String {
+        info.sub.clone().unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn token_introspection() {
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor.local_addr().remove(0);
+        let addr = addr.as_socket_addr().unwrap();
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(introspect));
+
+        let cli = TestClient::new(
+            index.with(
+                TokenIntrospection::new(format!("http://{addr}/"))
+                    .client_credentials("gateway", "secret")
+                    .required_scopes(["orders:read"]),
+            ),
+        );
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+        resp.assert_header(header::WWW_AUTHENTICATE, "Bearer");
+
+        for _ in 0..2 {
+            let resp = cli
+                .get("/")
+                .header(header::AUTHORIZATION, "Bearer good")
+                .send()
+                .await;
+            resp.assert_status_is_ok();
+            resp.assert_text("sunli").await;
+        }
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+        let resp = cli
+            .get("/")
+            .header(header::AUTHORIZATION, "Bearer bad")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+        resp.assert_header(header::WWW_AUTHENTICATE, "Bearer error=\"invalid_token\"");
+
+        let cli = TestClient::new(
+            index.with(
+                TokenIntrospection::new(format!("http://{addr}/"))
+                    .client_credentials("gateway", "secret")
+                    .required_scopes(["orders:delete"]),
+            ),
+        );
+        let resp = cli
+            .get("/")
+            .header(header::AUTHORIZATION, "Bearer good")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::FORBIDDEN);
+        resp.assert_header(
+            header::WWW_AUTHENTICATE,
+            "Bearer error=\"insufficient_scope\", scope=\"orders:delete\"",
+        );
+
+        let cli = TestClient::new(index.with(TokenIntrospection::new("http://127.0.0.1:1/")));
+        let resp = cli
+            .get("/")
+            .header(header::AUTHORIZATION, "Bearer good")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
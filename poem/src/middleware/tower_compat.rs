@@ -3,11 +3,18 @@ use std::{
     task::{Context, Poll},
 };
 
+use bytes::Bytes;
 use futures_util::{future::BoxFuture, FutureExt};
 use http::StatusCode;
+use http_body_util::BodyExt;
+use parking_lot::Mutex;
 use tower::{buffer::Buffer, BoxError, Layer, Service, ServiceExt};
 
-use crate::{Endpoint, Error, IntoResponse, Middleware, Request, Result};
+use crate::{
+    body::BoxBody,
+    request::{RequestParts, RequestState},
+    Body, Endpoint, Error, IntoResponse, Middleware, Request, Response, Result,
+};
 
 #[doc(hidden)]
 #[derive(Debug, thiserror::Error)]
@@ -99,11 +106,152 @@ where
     }
 }
 
+/// A middleware that wraps an endpoint with a tower layer that works with the
+/// request and response types of the `http` crate, such as the layers
+/// provided by `tower-http`.
+///
+/// Unlike [`TowerLayerCompatExt::compat`], the layer sees
+/// `http::Request<B>` and `http::Response<B>`, the state of the poem request
+/// (such as the remote address and the path parameters) is passed to the
+/// wrapped endpoint unchanged.
+///
+/// # Example
+///
+/// ```ignore
+/// use poem::{handler, middleware::TowerLayerCompat, EndpointExt};
+/// use tower_http::trace::TraceLayer;
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = index.with(TowerLayerCompat::new(TraceLayer::new_for_http()));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "tower-compat")))]
+pub struct TowerLayerCompat<L>(L);
+
+impl<L> TowerLayerCompat<L> {
+    /// Create a `TowerLayerCompat` middleware with the tower layer.
+    pub fn new(layer: L) -> Self {
+        Self(layer)
+    }
+}
+
+impl<E, L, ResBody> Middleware<E> for TowerLayerCompat<L>
+where
+    E: Endpoint,
+    L: Layer<EndpointToHttpService<E>>,
+    L::Service:
+        Service<http::Request<BoxBody>, Response = http::Response<ResBody>> + Send + 'static,
+    <L::Service as Service<http::Request<BoxBody>>>::Future: Send,
+    <L::Service as Service<http::Request<BoxBody>>>::Error: Into<BoxError> + Send + Sync,
+    ResBody: hyper::body::Body + Send + Sync + 'static,
+    ResBody::Data: Into<Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Output = HttpServiceToEndpoint<L::Service>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        let new_svc = self.0.layer(EndpointToHttpService(Arc::new(ep)));
+        HttpServiceToEndpoint(Buffer::new(new_svc, 32))
+    }
+}
+
+/// Carries the state of the poem request through the tower layer.
+#[derive(Clone)]
+struct StateSlot(Arc<Mutex<Option<RequestState>>>);
+
+/// An endpoint to tower service adapter that accepts `http::Request`.
+pub struct EndpointToHttpService<E>(Arc<E>);
+
+impl<E> Service<http::Request<BoxBody>> for EndpointToHttpService<E>
+where
+    E: Endpoint + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = WrappedError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        let ep = self.0.clone();
+        let (mut parts, body) = req.into_parts();
+        let state = parts
+            .extensions
+            .remove::<StateSlot>()
+            .and_then(|slot| slot.0.lock().take())
+            .unwrap_or_default();
+        let req = Request::from_parts(
+            RequestParts {
+                method: parts.method,
+                uri: parts.uri,
+                version: parts.version,
+                headers: parts.headers,
+                extensions: parts.extensions,
+                state,
+            },
+            Body::from(body),
+        );
+
+        async move {
+            let resp = ep.call(req).await.map_err(WrappedError)?;
+            Ok(resp.into_response().into())
+        }
+        .boxed()
+    }
+}
+
+/// A tower service to endpoint adapter that accepts `http::Request`.
+pub struct HttpServiceToEndpoint<Svc: Service<http::Request<BoxBody>>>(
+    Buffer<Svc, http::Request<BoxBody>>,
+);
+
+#[async_trait::async_trait]
+impl<Svc, ResBody> Endpoint for HttpServiceToEndpoint<Svc>
+where
+    Svc: Service<http::Request<BoxBody>, Response = http::Response<ResBody>> + Send + 'static,
+    Svc::Future: Send,
+    Svc::Error: Into<BoxError> + Send + Sync,
+    ResBody: hyper::body::Body + Send + Sync + 'static,
+    ResBody::Data: Into<Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let (mut parts, body) = req.into_parts();
+        let state = std::mem::take(&mut parts.state);
+        let mut http_req = http::Request::new(body.0);
+        *http_req.method_mut() = parts.method;
+        *http_req.uri_mut() = parts.uri;
+        *http_req.version_mut() = parts.version;
+        *http_req.headers_mut() = parts.headers;
+        *http_req.extensions_mut() = parts.extensions;
+        http_req
+            .extensions_mut()
+            .insert(StateSlot(Arc::new(Mutex::new(Some(state)))));
+
+        let mut svc = self.0.clone();
+        svc.ready().await.map_err(boxed_err_to_poem_err)?;
+        let resp = svc.call(http_req).await.map_err(boxed_err_to_poem_err)?;
+        let (parts, body) = resp.into_parts();
+        let body = body
+            .map_frame(|frame| frame.map_data(Into::into))
+            .map_err(std::io::Error::other)
+            .boxed();
+        Ok(http::Response::from_parts(parts, body).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::{endpoint::make_sync, test::TestClient, EndpointExt};
+    use crate::{endpoint::make_sync, handler, test::TestClient, web::Path, EndpointExt, Route};
 
     #[tokio::test]
     async fn test_tower_layer() {
@@ -142,4 +290,74 @@ mod tests {
         let cli = TestClient::new(ep);
         cli.get("/").send().await.assert_status_is_ok();
     }
+
+    #[tokio::test]
+    async fn test_tower_http_layer() {
+        #[derive(Clone)]
+        struct HeaderService<S> {
+            inner: S,
+        }
+
+        impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for HeaderService<S>
+        where
+            S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+            S::Future: Send + 'static,
+        {
+            type Response = S::Response;
+            type Error = S::Error;
+            type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+            fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+                self.inner.poll_ready(cx)
+            }
+
+            fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+                req.headers_mut()
+                    .insert("x-request", http::HeaderValue::from_static("tower"));
+                let fut = self.inner.call(req);
+                async move {
+                    let mut resp = fut.await?;
+                    resp.headers_mut()
+                        .insert("x-response", http::HeaderValue::from_static("tower"));
+                    Ok(resp)
+                }
+                .boxed()
+            }
+        }
+
+        struct HeaderLayer;
+
+        impl<S> Layer<S> for HeaderLayer {
+            type Service = HeaderService<S>;
+
+            fn layer(&self, inner: S) -> Self::Service {
+                HeaderService { inner }
+            }
+        }
+
+        #[handler(internal)]
+        fn index(Path(id): Path<String>, req: &Request) -> String {
+            format!(
+                "{id} {}",
+                req.headers()
+                    .get("x-request")
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+            )
+        }
+
+        let app = Route::new().at("/users/:id", index.with(TowerLayerCompat::new(HeaderLayer)));
+        let resp = TestClient::new(app).get("/users/100").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header("x-response", "tower");
+        resp.assert_text("100 tower").await;
+
+        let app = make_sync(|_| Err::<(), _>(Error::from_status(StatusCode::CONFLICT)))
+            .with(TowerLayerCompat::new(HeaderLayer));
+        TestClient::new(app)
+            .get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::CONFLICT);
+    }
 }
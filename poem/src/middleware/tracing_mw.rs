@@ -1,27 +1,131 @@
-use std::time::Instant;
+use std::{sync::Arc, time::Instant};
 
-use tracing::{Instrument, Level};
+use tracing::{field, Instrument, Level, Span};
 
 use crate::{
     route::PathPattern, web::RealIp, Endpoint, FromRequest, IntoResponse, Middleware, Request,
     Response, Result,
 };
 
+type SpanBuilder = Arc<dyn Fn(&Request) -> Span + Send + Sync>;
+
 /// Middleware for [`tracing`](https://crates.io/crates/tracing).
+///
+/// It creates a `request` span for each request with the remote address, the
+/// version, the method and the uri, and records the `request_id` set by
+/// `SetRequestId` (with the `request-id` feature), the `path_pattern` of the
+/// matched route, the `status`, the `error` and the `duration` to the span
+/// when the request is completed.
+///
+/// Use [`Tracing::span_builder`] to create the spans with custom fields.
 #[derive(Default)]
 pub struct Tracing;
 
+impl Tracing {
+    /// Create a `Tracing` middleware that creates the spans with `f`.
+    ///
+    /// The `path_pattern`, `status`, `error` and `duration` fields are
+    /// recorded if the span declares them, for example with
+    /// `path_pattern = tracing::field::Empty`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     handler, middleware::Tracing, test::TestClient, EndpointExt, Request, Route,
+    /// };
+    /// use tracing::field::Empty;
+    ///
+    /// #[handler]
+    /// fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// let app = Route::new()
+    ///     .at("/", index)
+    ///     .with(Tracing::span_builder(|req: &Request| {
+    ///         tracing::info_span!(
+    ///             "request",
+    ///             method = %req.method(),
+    ///             tenant = req.header("x-tenant").unwrap_or_default(),
+    ///             path_pattern = Empty,
+    ///             status = Empty,
+    ///             error = Empty,
+    ///             duration = Empty,
+    ///         )
+    ///     }));
+    /// let cli = TestClient::new(app);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// cli.get("/").send().await.assert_status_is_ok();
+    /// # });
+    /// ```
+    pub fn span_builder<F>(f: F) -> TracingWithSpan
+    where
+        F: Fn(&Request) -> Span + Send + Sync + 'static,
+    {
+        TracingWithSpan {
+            span_builder: Arc::new(f),
+        }
+    }
+}
+
 impl<E: Endpoint> Middleware<E> for Tracing {
     type Output = TracingEndpoint<E>;
 
     fn transform(&self, ep: E) -> Self::Output {
-        TracingEndpoint { inner: ep }
+        TracingEndpoint {
+            inner: ep,
+            span_builder: None,
+        }
+    }
+}
+
+/// Middleware for [`tracing`](https://crates.io/crates/tracing) with a custom
+/// span builder, created by [`Tracing::span_builder`].
+pub struct TracingWithSpan {
+    span_builder: SpanBuilder,
+}
+
+impl<E: Endpoint> Middleware<E> for TracingWithSpan {
+    type Output = TracingEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        TracingEndpoint {
+            inner: ep,
+            span_builder: Some(self.span_builder.clone()),
+        }
     }
 }
 
 /// Endpoint for `Tracing` middleware.
 pub struct TracingEndpoint<E> {
     inner: E,
+    span_builder: Option<SpanBuilder>,
+}
+
+async fn default_span(req: &Request) -> Span {
+    let remote_addr = RealIp::from_request_without_body(req)
+        .await
+        .ok()
+        .and_then(|real_ip| real_ip.0)
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|| req.remote_addr().to_string());
+
+    tracing::span!(
+        target: module_path!(),
+        Level::INFO,
+        "request",
+        remote_addr = %remote_addr,
+        version = ?req.version(),
+        method = %req.method(),
+        uri = %req.original_uri(),
+        request_id = field::Empty,
+        path_pattern = field::Empty,
+        status = field::Empty,
+        error = field::Empty,
+        duration = field::Empty,
+    )
 }
 
 #[async_trait::async_trait]
@@ -29,35 +133,34 @@ impl<E: Endpoint> Endpoint for TracingEndpoint<E> {
     type Output = Response;
 
     async fn call(&self, req: Request) -> Result<Self::Output> {
-        let remote_addr = RealIp::from_request_without_body(&req)
-            .await
-            .ok()
-            .and_then(|real_ip| real_ip.0)
-            .map(|addr| addr.to_string())
-            .unwrap_or_else(|| req.remote_addr().to_string());
-
-        let span = tracing::span!(
-            target: module_path!(),
-            Level::INFO,
-            "request",
-            remote_addr = %remote_addr,
-            version = ?req.version(),
-            method = %req.method(),
-            uri = %req.original_uri(),
-        );
+        let span = match &self.span_builder {
+            Some(span_builder) => span_builder(&req),
+            None => default_span(&req).await,
+        };
 
+        #[cfg(feature = "request-id")]
+        if let Some(request_id) = req.data::<crate::web::RequestId>() {
+            span.record("request_id", request_id.as_str());
+        }
         if let Some(path_pattern) = req.data::<PathPattern>() {
             span.record("path_pattern", path_pattern.0.as_ref());
         }
 
+        let instrumented = span.clone();
         async move {
             let now = Instant::now();
             let res = self.inner.call(req).await;
             let duration = now.elapsed();
 
+            span.record("duration", field::debug(duration));
+
             match res {
                 Ok(resp) => {
                     let resp = resp.into_response();
+                    if let Some(path_pattern) = resp.data::<PathPattern>() {
+                        span.record("path_pattern", path_pattern.0.as_ref());
+                    }
+                    span.record("status", field::display(resp.status()));
                     tracing::info!(
                         status = %resp.status(),
                         duration = ?duration,
@@ -66,6 +169,11 @@ impl<E: Endpoint> Endpoint for TracingEndpoint<E> {
                     Ok(resp)
                 }
                 Err(err) => {
+                    if let Some(path_pattern) = err.data::<PathPattern>() {
+                        span.record("path_pattern", path_pattern.0.as_ref());
+                    }
+                    span.record("status", field::display(err.status()));
+                    span.record("error", field::display(&err));
                     tracing::info!(
                         status = %err.status(),
                         error = %err,
@@ -76,7 +184,46 @@ impl<E: Endpoint> Endpoint for TracingEndpoint<E> {
                 }
             }
         }
-        .instrument(span)
+        .instrument(instrumented)
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{handler, http::StatusCode, test::TestClient, EndpointExt, Route};
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    #[tokio::test]
+    async fn tracing_mw() {
+        let cli = TestClient::new(Route::new().at("/", index).with(Tracing));
+        cli.get("/").send().await.assert_text("hello").await;
+        cli.get("/missing")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn span_builder() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let cli = TestClient::new(Route::new().at("/", index).with(Tracing::span_builder({
+            let count = count.clone();
+            move |req| {
+                count.fetch_add(1, Ordering::SeqCst);
+                tracing::info_span!("custom", method = %req.method(), status = field::Empty)
+            }
+        })));
+
+        cli.get("/").send().await.assert_text("hello").await;
+        cli.get("/").send().await.assert_text("hello").await;
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,283 @@
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use crate::{
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    web::RemoteAddr,
+    Body, Endpoint, IntoResponse, Middleware, Request, Response, Result, Upgraded,
+};
+
+/// The request that initiated a protocol upgrade, see [`UpgradeRegistry`].
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+#[derive(Debug, Clone)]
+pub struct UpgradeRequest {
+    /// The protocol token selected from the `Upgrade` header, in lowercase.
+    pub protocol: String,
+    /// The request URI.
+    pub uri: Uri,
+    /// The request headers.
+    pub headers: HeaderMap,
+    /// The remote address of the client.
+    pub remote_addr: RemoteAddr,
+}
+
+/// A handler for the connections upgraded to a protocol, see
+/// [`UpgradeRegistry`].
+///
+/// It is implemented for the functions that take [`Upgraded`] and
+/// [`UpgradeRequest`].
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+#[async_trait::async_trait]
+pub trait UpgradeHandler: Send + Sync + 'static {
+    /// Checks the request before the `101 Switching Protocols` response is
+    /// sent, returns an error to reject the upgrade.
+    ///
+    /// The default implementation accepts all requests.
+    async fn check(&self, req: &Request) -> Result<()> {
+        let _ = req;
+        Ok(())
+    }
+
+    /// Handles the upgraded connection, which is called after the
+    /// `101 Switching Protocols` response has been sent.
+    async fn handle(&self, upgraded: Upgraded, req: UpgradeRequest);
+}
+
+#[async_trait::async_trait]
+impl<F, Fut> UpgradeHandler for F
+where
+    F: Fn(Upgraded, UpgradeRequest) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    async fn handle(&self, upgraded: Upgraded, req: UpgradeRequest) {
+        (self)(upgraded, req).await
+    }
+}
+
+/// Middleware for dispatching the protocol upgrades requested with the
+/// `Upgrade` header to the registered handlers.
+///
+/// If the `Connection` header of a request contains `upgrade`, and the
+/// `Upgrade` header contains a registered protocol, a
+/// `101 Switching Protocols` response is returned, and the handler of the
+/// protocol is spawned with the upgraded connection. The first registered
+/// protocol in the `Upgrade` header is selected. Other requests are passed to
+/// the inner endpoint, so it can be combined with
+/// [`WebSocket`](crate::web::websocket::WebSocket).
+///
+/// Only `HTTP/1.1` connections can be upgraded.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     middleware::{UpgradeRegistry, UpgradeRequest},
+///     EndpointExt, Upgraded,
+/// };
+/// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = index.with(UpgradeRegistry::new().register(
+///     "echo",
+///     |mut upgraded: Upgraded, _req: UpgradeRequest| async move {
+///         let mut buf = [0; 1024];
+///         while let Ok(n @ 1..) = upgraded.read(&mut buf).await {
+///             if upgraded.write_all(&buf[..n]).await.is_err() {
+///                 break;
+///             }
+///         }
+///     },
+/// ));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+#[derive(Default, Clone)]
+pub struct UpgradeRegistry {
+    handlers: HashMap<String, Arc<dyn UpgradeHandler>>,
+}
+
+impl UpgradeRegistry {
+    /// Create new `UpgradeRegistry` middleware.
+    #[must_use]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers the handler of the protocol, such as `h2c`, the protocol
+    /// token is case-insensitive.
+    #[must_use]
+    pub fn register(mut self, protocol: impl AsRef<str>, handler: impl UpgradeHandler) -> Self {
+        self.handlers
+            .insert(protocol.as_ref().to_ascii_lowercase(), Arc::new(handler));
+        self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for UpgradeRegistry {
+    type Output = UpgradeRegistryEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        UpgradeRegistryEndpoint {
+            inner: ep,
+            handlers: self.handlers.clone(),
+        }
+    }
+}
+
+/// Endpoint for UpgradeRegistry middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub struct UpgradeRegistryEndpoint<E> {
+    inner: E,
+    handlers: HashMap<String, Arc<dyn UpgradeHandler>>,
+}
+
+fn has_token(headers: &HeaderMap, name: header::HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|item| item.trim().eq_ignore_ascii_case(token))
+}
+
+impl<E> UpgradeRegistryEndpoint<E> {
+    fn find_handler(&self, req: &Request) -> Option<(String, Arc<dyn UpgradeHandler>)> {
+        if !has_token(req.headers(), header::CONNECTION, "upgrade") {
+            return None;
+        }
+
+        req.headers()
+            .get_all(header::UPGRADE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|protocol| protocol.trim().to_ascii_lowercase())
+            .find_map(|protocol| {
+                let handler = self.handlers.get(&protocol)?.clone();
+                Some((protocol, handler))
+            })
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for UpgradeRegistryEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let (protocol, handler) = match self.find_handler(&req) {
+            Some(item) => item,
+            None => return self.inner.call(req).await.map(IntoResponse::into_response),
+        };
+
+        handler.check(&req).await?;
+        let on_upgrade = req.take_upgrade()?;
+        let upgrade_req = UpgradeRequest {
+            protocol: protocol.clone(),
+            uri: req.uri().clone(),
+            headers: req.headers().clone(),
+            remote_addr: req.remote_addr().clone(),
+        };
+
+        tokio::spawn(async move {
+            if let Ok(upgraded) = on_upgrade.await {
+                handler.handle(upgraded, upgrade_req).await;
+            }
+        });
+
+        Ok(Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(header::CONNECTION, "upgrade")
+            .header(
+                header::UPGRADE,
+                HeaderValue::from_str(&protocol).expect("valid header value"),
+            )
+            .body(Body::empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+
+    use super::*;
+    use crate::{
+        handler,
+        listener::{Acceptor, Listener, TcpListener},
+        EndpointExt, Server,
+    };
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    #[tokio::test]
+    async fn upgrade() {
+        let app = index.with(UpgradeRegistry::new().register(
+            "Echo",
+            |mut upgraded: Upgraded, req: UpgradeRequest| async move {
+                assert_eq!(req.protocol, "echo");
+                let mut buf = [0; 5];
+                upgraded.read_exact(&mut buf).await.unwrap();
+                upgraded.write_all(&buf).await.unwrap();
+            },
+        ));
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(app));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade\r\nUpgrade: foo, ECHO\r\n\r\n",
+            )
+            .await
+            .unwrap();
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        let head = String::from_utf8(head).unwrap().to_ascii_lowercase();
+        assert!(head.starts_with("http/1.1 101 switching protocols\r\n"));
+        assert!(head.contains("upgrade: echo\r\n"));
+
+        stream.write_all(b"hello").await.unwrap();
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+    }
+
+    #[tokio::test]
+    async fn not_registered() {
+        let ep = index.with(UpgradeRegistry::new().register(
+            "echo",
+            |_upgraded: Upgraded, _req: UpgradeRequest| async move {},
+        ));
+        let resp = ep
+            .call(
+                Request::builder()
+                    .header(header::CONNECTION, "upgrade")
+                    .header(header::UPGRADE, "websocket")
+                    .finish(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
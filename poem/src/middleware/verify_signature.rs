@@ -0,0 +1,980 @@
+use std::{
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::engine::{general_purpose::STANDARD, Engine};
+use ring::{
+    hmac,
+    signature::{self, UnparsedPublicKey, VerificationAlgorithm},
+};
+
+use crate::{
+    error::SignatureVerificationError, http::header, web::Tenant, Endpoint, Middleware, Request,
+    Result,
+};
+
+#[derive(Clone)]
+enum VerifyingKeyKind {
+    HmacSha256(hmac::Key),
+    Public {
+        algorithm: &'static dyn VerificationAlgorithm,
+        key: Arc<[u8]>,
+    },
+}
+
+/// A key used by the [`VerifySignature`] middleware to verify the signatures
+/// of the requests.
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+#[derive(Clone)]
+pub struct VerifyingKey {
+    alg: &'static str,
+    kind: VerifyingKeyKind,
+}
+
+impl VerifyingKey {
+    /// Create a `hmac-sha256` key with the shared secret.
+    pub fn hmac_sha256(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            alg: "hmac-sha256",
+            kind: VerifyingKeyKind::HmacSha256(hmac::Key::new(
+                hmac::HMAC_SHA256,
+                secret.as_ref(),
+            )),
+        }
+    }
+
+    fn public(
+        alg: &'static str,
+        algorithm: &'static dyn VerificationAlgorithm,
+        key: impl AsRef<[u8]>,
+    ) -> Self {
+        Self {
+            alg,
+            kind: VerifyingKeyKind::Public {
+                algorithm,
+                key: key.as_ref().into(),
+            },
+        }
+    }
+
+    /// Create a `ed25519` key with the 32-byte public key.
+    pub fn ed25519(public_key: impl AsRef<[u8]>) -> Self {
+        Self::public("ed25519", &signature::ED25519, public_key)
+    }
+
+    /// Create a `ecdsa-p256-sha256` key with the uncompressed public point.
+    pub fn ecdsa_p256_sha256(public_key: impl AsRef<[u8]>) -> Self {
+        Self::public(
+            "ecdsa-p256-sha256",
+            &signature::ECDSA_P256_SHA256_FIXED,
+            public_key,
+        )
+    }
+
+    /// Create a `rsa-pss-sha512` key with the DER-encoded PKCS#1
+    /// `RSAPublicKey`.
+    pub fn rsa_pss_sha512(public_key: impl AsRef<[u8]>) -> Self {
+        Self::public(
+            "rsa-pss-sha512",
+            &signature::RSA_PSS_2048_8192_SHA512,
+            public_key,
+        )
+    }
+
+    /// Create a `rsa-v1_5-sha256` key with the DER-encoded PKCS#1
+    /// `RSAPublicKey`.
+    pub fn rsa_v1_5_sha256(public_key: impl AsRef<[u8]>) -> Self {
+        Self::public(
+            "rsa-v1_5-sha256",
+            &signature::RSA_PKCS1_2048_8192_SHA256,
+            public_key,
+        )
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        match &self.kind {
+            VerifyingKeyKind::HmacSha256(key) => hmac::verify(key, data, signature).is_ok(),
+            VerifyingKeyKind::Public { algorithm, key } => {
+                UnparsedPublicKey::new(*algorithm, key)
+                    .verify(data, signature)
+                    .is_ok()
+            }
+        }
+    }
+}
+
+/// Resolves the key used to verify a signature from the `keyid` parameter.
+///
+/// It is implemented for `Fn(&str) -> Option<VerifyingKey>`.
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+#[async_trait::async_trait]
+pub trait KeyResolver: Send + Sync + 'static {
+    /// Returns the key with the specified key ID, or `None` if the key is
+    /// unknown.
+    async fn resolve(&self, key_id: &str) -> Option<VerifyingKey>;
+
+    /// Returns the key with the specified key ID for the request.
+    ///
+    /// The default implementation calls [`KeyResolver::resolve`], override it
+    /// to resolve the keys depending on the request.
+    async fn resolve_request(&self, _req: &Request, key_id: &str) -> Option<VerifyingKey> {
+        self.resolve(key_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<F> KeyResolver for F
+where
+    F: Fn(&str) -> Option<VerifyingKey> + Send + Sync + 'static,
+{
+    async fn resolve(&self, key_id: &str) -> Option<VerifyingKey> {
+        (self)(key_id)
+    }
+}
+
+/// A [`KeyResolver`] that resolves the keys from the configuration of the
+/// tenant resolved by the [`TenantResolver`](crate::middleware::TenantResolver)
+/// middleware, so each tenant signs the requests with its own keys.
+///
+/// `T` is the [`TenantProvider::Config`](crate::web::TenantProvider::Config)
+/// type, the requests without a tenant are rejected with
+/// [`SignatureVerificationError::UnknownKey`]. Use
+/// [`CachedTenantProvider`](crate::web::CachedTenantProvider) to avoid fetching
+/// the secrets of the tenant for each request.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use poem::{
+///     handler,
+///     middleware::{TenantKeyResolver, TenantResolver, VerifySignature, VerifyingKey},
+///     EndpointExt,
+/// };
+///
+/// #[derive(Clone)]
+/// struct TenantConfig {
+///     signing_secret: String,
+/// }
+///
+/// #[handler]
+/// fn index() {}
+///
+/// let tenants: HashMap<String, TenantConfig> = HashMap::new();
+/// let app = index
+///     .with(VerifySignature::new(TenantKeyResolver::new(
+///         |config: &TenantConfig, key_id: &str| {
+///             (key_id == "default").then(|| VerifyingKey::hmac_sha256(&config.signing_secret))
+///         },
+///     )))
+///     .with(TenantResolver::new(tenants).header("x-tenant-id"));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+pub struct TenantKeyResolver<T, F> {
+    f: F,
+    _mark: PhantomData<fn(&T)>,
+}
+
+impl<T, F> TenantKeyResolver<T, F>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T, &str) -> Option<VerifyingKey> + Send + Sync + 'static,
+{
+    /// Create a `TenantKeyResolver` with a function that returns the key with
+    /// the specified key ID from the configuration of the tenant.
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            _mark: PhantomData,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T, F> KeyResolver for TenantKeyResolver<T, F>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T, &str) -> Option<VerifyingKey> + Send + Sync + 'static,
+{
+    async fn resolve(&self, _key_id: &str) -> Option<VerifyingKey> {
+        None
+    }
+
+    async fn resolve_request(&self, req: &Request, key_id: &str) -> Option<VerifyingKey> {
+        let tenant = req.extensions().get::<Tenant<T>>()?;
+        (self.f)(tenant.config(), key_id)
+    }
+}
+
+/// The signature that has been verified by the [`VerifySignature`]
+/// middleware, which is added to the request extensions.
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VerifiedSignature {
+    /// The label of the signature.
+    pub label: String,
+    /// The key ID of the signature.
+    pub key_id: String,
+    /// The covered components of the signature.
+    pub components: Vec<String>,
+}
+
+/// Middleware for verifying the
+/// [HTTP Message Signatures](https://www.rfc-editor.org/rfc/rfc9421) of the
+/// requests.
+///
+/// The request is accepted if any signature in the `Signature-Input` and
+/// `Signature` headers is valid, covers all the required components and has
+/// not expired. The verified signature is added to the request extensions as
+/// [`VerifiedSignature`].
+///
+/// The derived components `@method`, `@target-uri`, `@authority`, `@scheme`,
+/// `@request-target`, `@path` and `@query` are supported, as well as the
+/// HTTP fields without parameters.
+///
+/// NOTE: The content of the body is not verified, require the
+/// `content-digest` component and check the `Content-Digest` header against
+/// the body to protect it.
+///
+/// # Errors
+///
+/// - [`SignatureVerificationError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     middleware::{VerifiedSignature, VerifySignature, VerifyingKey},
+///     web::Data,
+///     EndpointExt,
+/// };
+///
+/// #[handler]
+/// fn index(Data(signature): Data<&VerifiedSignature>) -> String {
+///     format!("signed by {}", signature.key_id)
+/// }
+///
+/// let app = index.with(
+///     VerifySignature::new(|key_id: &str| match key_id {
+///         "partner-1" => Some(VerifyingKey::hmac_sha256(b"secret")),
+///         _ => None,
+///     })
+///     .required_components(["@method", "@target-uri", "content-digest"]),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+#[derive(Clone)]
+pub struct VerifySignature {
+    resolver: Arc<dyn KeyResolver>,
+    required_components: Vec<String>,
+    label: Option<String>,
+    max_age: Option<Duration>,
+}
+
+impl VerifySignature {
+    /// Create new `VerifySignature` middleware with the key resolver.
+    #[must_use]
+    pub fn new(resolver: impl KeyResolver) -> Self {
+        Self {
+            resolver: Arc::new(resolver),
+            required_components: Vec::new(),
+            label: None,
+            max_age: None,
+        }
+    }
+
+    /// Sets the components that must be covered by the signature, such as
+    /// `@method` or `content-digest`.
+    #[must_use]
+    pub fn required_components<I, T>(self, components: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            required_components: components
+                .into_iter()
+                .map(|component| component.into().to_ascii_lowercase())
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Only verify the signature with the specified label.
+    #[must_use]
+    pub fn label(self, label: impl Into<String>) -> Self {
+        Self {
+            label: Some(label.into()),
+            ..self
+        }
+    }
+
+    /// Sets the maximum age of the signature, which requires the `created`
+    /// parameter.
+    #[must_use]
+    pub fn max_age(self, max_age: Duration) -> Self {
+        Self {
+            max_age: Some(max_age),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for VerifySignature {
+    type Output = VerifySignatureEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        VerifySignatureEndpoint {
+            inner: ep,
+            config: self.clone(),
+        }
+    }
+}
+
+/// Endpoint for VerifySignature middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "signing")))]
+pub struct VerifySignatureEndpoint<E> {
+    inner: E,
+    config: VerifySignature,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for VerifySignatureEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let verified = self.verify(&req).await?;
+        req.extensions_mut().insert(verified);
+        self.inner.call(req).await
+    }
+}
+
+impl<E> VerifySignatureEndpoint<E> {
+    async fn verify(&self, req: &Request) -> Result<VerifiedSignature, SignatureVerificationError> {
+        let inputs = combined_header(req, "signature-input")
+            .ok_or(SignatureVerificationError::MissingSignature)?;
+        let signatures = combined_header(req, "signature")
+            .ok_or(SignatureVerificationError::MissingSignature)?;
+        let inputs = parse_signature_inputs(&inputs)
+            .ok_or_else(|| SignatureVerificationError::InvalidHeader("signature-input".into()))?;
+        let signatures = parse_signatures(&signatures)
+            .ok_or_else(|| SignatureVerificationError::InvalidHeader("signature".into()))?;
+
+        let mut err = SignatureVerificationError::MissingSignature;
+        for input in inputs {
+            if matches!(&self.config.label, Some(label) if label != &input.label) {
+                continue;
+            }
+            let signature = match signatures.iter().find(|(label, _)| label == &input.label) {
+                Some((_, signature)) => signature,
+                None => continue,
+            };
+            match self.verify_signature(req, input, signature).await {
+                Ok(verified) => return Ok(verified),
+                Err(e) => err = e,
+            }
+        }
+        Err(err)
+    }
+
+    async fn verify_signature(
+        &self,
+        req: &Request,
+        input: SignatureInput,
+        signature: &[u8],
+    ) -> Result<VerifiedSignature, SignatureVerificationError> {
+        if let Some(component) = self
+            .config
+            .required_components
+            .iter()
+            .find(|component| !input.components.contains(component))
+        {
+            return Err(SignatureVerificationError::MissingRequiredComponent(
+                component.clone(),
+            ));
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if matches!(input.expires, Some(expires) if expires <= now) {
+            return Err(SignatureVerificationError::Expired);
+        }
+        if let Some(max_age) = self.config.max_age {
+            match input.created {
+                Some(created) if now.saturating_sub(created) <= max_age.as_secs() => {}
+                _ => return Err(SignatureVerificationError::Expired),
+            }
+        }
+
+        let key_id = input
+            .key_id
+            .ok_or(SignatureVerificationError::UnknownKey)?;
+        let key = self
+            .config
+            .resolver
+            .resolve_request(req, &key_id)
+            .await
+            .ok_or(SignatureVerificationError::UnknownKey)?;
+        if matches!(&input.alg, Some(alg) if alg != key.alg) {
+            return Err(SignatureVerificationError::InvalidSignature);
+        }
+
+        let base = signature_base(req, &input.components, &input.raw)?;
+        if !key.verify(base.as_bytes(), signature) {
+            return Err(SignatureVerificationError::InvalidSignature);
+        }
+
+        Ok(VerifiedSignature {
+            label: input.label,
+            key_id,
+            components: input.components,
+        })
+    }
+}
+
+fn combined_header(req: &Request, name: &str) -> Option<String> {
+    let values = req
+        .headers()
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.join(", "))
+}
+
+fn component_value(req: &Request, name: &str) -> Option<String> {
+    let uri = req.original_uri();
+    let authority = || {
+        uri.authority()
+            .map(|authority| authority.as_str())
+            .or_else(|| req.header(header::HOST))
+            .map(|authority| authority.to_ascii_lowercase())
+    };
+    let request_target = || {
+        uri.path_and_query()
+            .map(|path_and_query| path_and_query.as_str())
+            .unwrap_or_else(|| uri.path())
+            .to_string()
+    };
+
+    match name {
+        "@method" => Some(req.method().as_str().to_string()),
+        "@target-uri" => Some(format!(
+            "{}://{}{}",
+            req.scheme().as_str().to_ascii_lowercase(),
+            authority()?,
+            request_target()
+        )),
+        "@authority" => authority(),
+        "@scheme" => Some(req.scheme().as_str().to_ascii_lowercase()),
+        "@request-target" => Some(request_target()),
+        "@path" => Some(uri.path().to_string()),
+        "@query" => Some(format!("?{}", uri.query().unwrap_or_default())),
+        name if name.starts_with('@') => None,
+        name => {
+            let values = req
+                .headers()
+                .get_all(name)
+                .iter()
+                .map(|value| value.to_str().map(str::trim))
+                .collect::<Result<Vec<_>, _>>()
+                .ok()?;
+            if values.is_empty() {
+                return None;
+            }
+            Some(values.join(", "))
+        }
+    }
+}
+
+fn signature_base(
+    req: &Request,
+    components: &[String],
+    params: &str,
+) -> Result<String, SignatureVerificationError> {
+    let mut base = String::new();
+    for component in components {
+        let value = component_value(req, component)
+            .ok_or_else(|| SignatureVerificationError::MissingComponent(component.clone()))?;
+        base.push_str(&format!("\"{component}\": {value}\n"));
+    }
+    base.push_str(&format!("\"@signature-params\": {params}"));
+    Ok(base)
+}
+
+struct SignatureInput {
+    label: String,
+    components: Vec<String>,
+    key_id: Option<String>,
+    alg: Option<String>,
+    created: Option<u64>,
+    expires: Option<u64>,
+    raw: String,
+}
+
+enum BareItem {
+    String(String),
+    Integer(i64),
+    Other,
+}
+
+/// A minimal parser for the structured field values (RFC 8941) used by the
+/// `Signature-Input` and `Signature` headers.
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn eat(&mut self, ch: u8) -> bool {
+        if self.peek() == Some(ch) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn is_end(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn parse_key(&mut self) -> Option<String> {
+        let start = self.pos;
+        match self.peek() {
+            Some(b'a'..=b'z' | b'*') => self.pos += 1,
+            _ => return None,
+        }
+        while matches!(
+            self.peek(),
+            Some(b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.' | b'*')
+        ) {
+            self.pos += 1;
+        }
+        Some(self.input[start..self.pos].to_string())
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        if !self.eat(b'"') {
+            return None;
+        }
+        let mut s = String::new();
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    self.pos += 1;
+                    return Some(s);
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    match self.peek()? {
+                        ch @ (b'"' | b'\\') => s.push(ch as char),
+                        _ => return None,
+                    }
+                }
+                ch @ 0x20..=0x7e => s.push(ch as char),
+                _ => return None,
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn parse_bare_item(&mut self) -> Option<BareItem> {
+        match self.peek()? {
+            b'"' => self.parse_string().map(BareItem::String),
+            b'-' | b'0'..=b'9' => {
+                let start = self.pos;
+                self.pos += 1;
+                while matches!(self.peek(), Some(b'0'..=b'9')) {
+                    self.pos += 1;
+                }
+                self.input[start..self.pos]
+                    .parse()
+                    .ok()
+                    .map(BareItem::Integer)
+            }
+            b'?' => {
+                self.pos += 1;
+                match self.peek()? {
+                    b'0' | b'1' => {
+                        self.pos += 1;
+                        Some(BareItem::Other)
+                    }
+                    _ => None,
+                }
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'*' => {
+                while matches!(
+                    self.peek(),
+                    Some(ch) if ch.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&ch)
+                ) {
+                    self.pos += 1;
+                }
+                Some(BareItem::Other)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_params(&mut self) -> Option<Vec<(String, BareItem)>> {
+        let mut params = Vec::new();
+        while self.eat(b';') {
+            self.skip_whitespace();
+            let key = self.parse_key()?;
+            let value = if self.eat(b'=') {
+                self.parse_bare_item()?
+            } else {
+                BareItem::Other
+            };
+            params.push((key, value));
+        }
+        Some(params)
+    }
+
+    /// Parses the separator between the members of a dictionary, returns
+    /// `false` if the end of the input is reached.
+    fn parse_member_separator(&mut self) -> Option<bool> {
+        self.skip_whitespace();
+        if self.is_end() {
+            return Some(false);
+        }
+        if !self.eat(b',') {
+            return None;
+        }
+        self.skip_whitespace();
+        Some(true)
+    }
+}
+
+fn parse_signature_inputs(value: &str) -> Option<Vec<SignatureInput>> {
+    let mut parser = Parser::new(value);
+    let mut inputs = Vec::new();
+
+    parser.skip_whitespace();
+    loop {
+        let label = parser.parse_key()?;
+        if !parser.eat(b'=') {
+            return None;
+        }
+
+        let start = parser.pos;
+        if !parser.eat(b'(') {
+            return None;
+        }
+        let mut components = Vec::new();
+        loop {
+            while parser.eat(b' ') {}
+            if parser.eat(b')') {
+                break;
+            }
+            components.push(parser.parse_string()?);
+            if parser.peek() == Some(b';') {
+                // the parameters of the components are not supported
+                return None;
+            }
+        }
+
+        let mut input = SignatureInput {
+            label,
+            components,
+            key_id: None,
+            alg: None,
+            created: None,
+            expires: None,
+            raw: String::new(),
+        };
+        for (key, value) in parser.parse_params()? {
+            match (key.as_str(), value) {
+                ("keyid", BareItem::String(key_id)) => input.key_id = Some(key_id),
+                ("alg", BareItem::String(alg)) => input.alg = Some(alg),
+                ("created", BareItem::Integer(created)) => {
+                    input.created = Some(created.try_into().ok()?)
+                }
+                ("expires", BareItem::Integer(expires)) => {
+                    input.expires = Some(expires.try_into().ok()?)
+                }
+                ("keyid" | "alg" | "created" | "expires", _) => return None,
+                _ => {}
+            }
+        }
+        input.raw = value[start..parser.pos].to_string();
+        inputs.push(input);
+
+        if !parser.parse_member_separator()? {
+            return Some(inputs);
+        }
+    }
+}
+
+fn parse_signatures(value: &str) -> Option<Vec<(String, Vec<u8>)>> {
+    let mut parser = Parser::new(value);
+    let mut signatures = Vec::new();
+
+    parser.skip_whitespace();
+    loop {
+        let label = parser.parse_key()?;
+        if !parser.eat(b'=') || !parser.eat(b':') {
+            return None;
+        }
+        let start = parser.pos;
+        while !matches!(parser.peek()?, b':') {
+            parser.pos += 1;
+        }
+        let signature = STANDARD.decode(&value[start..parser.pos]).ok()?;
+        parser.pos += 1;
+        parser.parse_params()?;
+        signatures.push((label, signature));
+
+        if !parser.parse_member_separator()? {
+            return Some(signatures);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        handler,
+        http::{Method, StatusCode},
+        test::TestClient,
+        web::Data,
+        EndpointExt,
+    };
+
+    const SIGNATURE_PARAMS: &str =
+        r#"("@method" "@authority" "@path" "content-type");created=1618884473;keyid="test-key""#;
+
+    fn sign(base: &str) -> String {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"secret");
+        STANDARD.encode(hmac::sign(&key, base.as_bytes()))
+    }
+
+    fn resolver(key_id: &str) -> Option<VerifyingKey> {
+        match key_id {
+            "test-key" => Some(VerifyingKey::hmac_sha256(b"secret")),
+            _ => None,
+        }
+    }
+
+    #[handler(internal)]
+    fn index(Data(signature): Data<&VerifiedSignature>) -> String {
+        format!("{}:{}", signature.label, signature.key_id)
+    }
+
+    #[test]
+    fn parse() {
+        let inputs = parse_signature_inputs(&format!(
+            r#"sig1={SIGNATURE_PARAMS}, sig2=();alg="ed25519";expires=1;nonce="a\"b""#
+        ))
+        .unwrap();
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0].label, "sig1");
+        assert_eq!(
+            inputs[0].components,
+            vec!["@method", "@authority", "@path", "content-type"]
+        );
+        assert_eq!(inputs[0].key_id.as_deref(), Some("test-key"));
+        assert_eq!(inputs[0].created, Some(1618884473));
+        assert_eq!(inputs[0].raw, SIGNATURE_PARAMS);
+        assert!(inputs[1].components.is_empty());
+        assert_eq!(inputs[1].alg.as_deref(), Some("ed25519"));
+        assert_eq!(inputs[1].expires, Some(1));
+
+        assert!(parse_signature_inputs(r#"sig1=("@query-param";name="a")"#).is_none());
+        assert!(parse_signature_inputs("sig1=(\"@method\"").is_none());
+
+        let signatures = parse_signatures("sig1=:aGVsbG8=:, sig2=::").unwrap();
+        assert_eq!(signatures[0], ("sig1".to_string(), b"hello".to_vec()));
+        assert_eq!(signatures[1], ("sig2".to_string(), vec![]));
+        assert!(parse_signatures("sig1=aGVsbG8=").is_none());
+    }
+
+    #[tokio::test]
+    async fn verify() {
+        let base = format!(
+            "\"@method\": POST\n\"@authority\": example.com\n\"@path\": /\n\"content-type\": application/json\n\"@signature-params\": {SIGNATURE_PARAMS}"
+        );
+        let signature = sign(&base);
+        let cli = TestClient::new(
+            index.with(VerifySignature::new(resolver).required_components(["@method"])),
+        );
+
+        let resp = cli
+            .post("/")
+            .header(header::HOST, "Example.com")
+            .content_type("application/json")
+            .header("signature-input", format!("sig1={SIGNATURE_PARAMS}"))
+            .header("signature", format!("sig1=:{signature}:"))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("sig1:test-key").await;
+
+        let resp = cli
+            .request(Method::PUT, "/")
+            .header(header::HOST, "example.com")
+            .content_type("application/json")
+            .header("signature-input", format!("sig1={SIGNATURE_PARAMS}"))
+            .header("signature", format!("sig1=:{signature}:"))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+
+        let resp = cli
+            .post("/")
+            .header(header::HOST, "example.com")
+            .header("signature-input", format!("sig1={SIGNATURE_PARAMS}"))
+            .header("signature", format!("sig1=:{signature}:"))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+
+        let resp = cli.post("/").send().await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn tenant_keys() {
+        use std::collections::HashMap;
+
+        use crate::middleware::TenantResolver;
+
+        let base = format!(
+            "\"@method\": POST\n\"@authority\": example.com\n\"@path\": /\n\"content-type\": application/json\n\"@signature-params\": {SIGNATURE_PARAMS}"
+        );
+        let signature = sign(&base);
+        let tenants = HashMap::from([
+            ("acme".to_string(), "secret".to_string()),
+            ("globex".to_string(), "other-secret".to_string()),
+        ]);
+        let cli = TestClient::new(
+            index
+                .with(VerifySignature::new(TenantKeyResolver::new(
+                    |secret: &String, key_id: &str| {
+                        (key_id == "test-key").then(|| VerifyingKey::hmac_sha256(secret))
+                    },
+                )))
+                .with(TenantResolver::new(tenants).header("x-tenant").optional()),
+        );
+
+        for (tenant, status) in [
+            (Some("acme"), StatusCode::OK),
+            (Some("globex"), StatusCode::UNAUTHORIZED),
+            (None, StatusCode::UNAUTHORIZED),
+        ] {
+            let mut req = cli
+                .post("/")
+                .header(header::HOST, "example.com")
+                .content_type("application/json")
+                .header("signature-input", format!("sig1={SIGNATURE_PARAMS}"))
+                .header("signature", format!("sig1=:{signature}:"));
+            if let Some(tenant) = tenant {
+                req = req.header("x-tenant", tenant);
+            }
+            req.send().await.assert_status(status);
+        }
+    }
+
+    #[tokio::test]
+    async fn requirements() {
+        let params = r#"("@method");keyid="test-key";expires=1"#;
+        let signature = sign(&format!(
+            "\"@method\": GET\n\"@signature-params\": {params}"
+        ));
+
+        async fn check(ep: impl Endpoint, params: &str, signature: &str) -> StatusCode {
+            let req = Request::builder()
+                .header("signature-input", format!("sig1={params}"))
+                .header("signature", format!("sig1=:{signature}:"))
+                .finish();
+            match ep.call(req).await {
+                Ok(_) => StatusCode::OK,
+                Err(err) => err.status(),
+            }
+        }
+
+        assert_eq!(
+            check(
+                index.with(VerifySignature::new(resolver)),
+                params,
+                &signature
+            )
+            .await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let params = r#"("@method");keyid="test-key""#;
+        let signature = sign(&format!(
+            "\"@method\": GET\n\"@signature-params\": {params}"
+        ));
+        assert_eq!(
+            check(
+                index.with(VerifySignature::new(resolver)),
+                params,
+                &signature
+            )
+            .await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            check(
+                index.with(VerifySignature::new(resolver).required_components(["@path"])),
+                params,
+                &signature
+            )
+            .await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            check(
+                index.with(VerifySignature::new(resolver).max_age(Duration::from_secs(60))),
+                params,
+                &signature
+            )
+            .await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            check(
+                index.with(VerifySignature::new(resolver).label("sig2")),
+                params,
+                &signature
+            )
+            .await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            check(
+                index.with(VerifySignature::new(|_: &str| -> Option<VerifyingKey> { None })),
+                params,
+                &signature
+            )
+            .await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
@@ -7,6 +7,7 @@ use std::{
     task::{Context, Poll},
 };
 
+use bytes::Bytes;
 use http::uri::Scheme;
 use http_body_util::BodyExt;
 use hyper::{body::Incoming, rt::Write as _};
@@ -18,7 +19,7 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use crate::web::cookie::CookieJar;
 use crate::{
     body::{Body, BoxBody},
-    error::{ParsePathError, ParseQueryError, UpgradeError},
+    error::{ParsePathError, ParseQueryError, PathError, UpgradeError},
     http::{
         header::{self, HeaderMap, HeaderName, HeaderValue},
         Extensions, Method, Uri, Version,
@@ -26,7 +27,7 @@ use crate::{
     route::PathParams,
     web::{
         headers::{Header, HeaderMapExt},
-        LocalAddr, PathDeserializer, RemoteAddr,
+        ConnectInfoMap, LocalAddr, PathDeserializer, RemoteAddr, RequestLifecycle,
     },
     RequestBody,
 };
@@ -35,11 +36,32 @@ pub(crate) struct RequestState {
     pub(crate) local_addr: LocalAddr,
     pub(crate) remote_addr: RemoteAddr,
     pub(crate) scheme: Scheme,
+    pub(crate) connect_info: ConnectInfoMap,
     pub(crate) original_uri: Uri,
     pub(crate) match_params: PathParams,
+    pub(crate) raw_match_params: PathParams,
+    pub(crate) invalid_match_param: Option<(String, String)>,
     #[cfg(feature = "cookie")]
     pub(crate) cookie_jar: Option<CookieJar>,
     pub(crate) on_upgrade: Mutex<Option<OnUpgrade>>,
+    pub(crate) extractor_cache: Mutex<Extensions>,
+    pub(crate) lifecycle: RequestLifecycle,
+}
+
+impl RequestState {
+    /// Returns an error if a path parameter is not valid UTF-8 after
+    /// percent-decoding.
+    pub(crate) fn check_match_params(&self) -> Result<(), PathError> {
+        match &self.invalid_match_param {
+            Some((name, value)) => Err(PathError {
+                name: name.clone(),
+                value: value.clone(),
+                expected_type: None,
+                reason: "invalid UTF-8 after percent-decoding".to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Default for RequestState {
@@ -48,11 +70,16 @@ impl Default for RequestState {
             local_addr: Default::default(),
             remote_addr: Default::default(),
             scheme: Scheme::HTTP,
+            connect_info: Default::default(),
             original_uri: Default::default(),
             match_params: vec![],
+            raw_match_params: vec![],
+            invalid_match_param: None,
             #[cfg(feature = "cookie")]
             cookie_jar: None,
             on_upgrade: Default::default(),
+            extractor_cache: Default::default(),
+            lifecycle: Default::default(),
         }
     }
 }
@@ -117,6 +144,35 @@ impl From<(http::Request<Incoming>, LocalAddr, RemoteAddr, Scheme)> for Request
             RemoteAddr,
             Scheme,
         ),
+    ) -> Self {
+        (
+            req,
+            local_addr,
+            remote_addr,
+            scheme,
+            ConnectInfoMap::default(),
+        )
+            .into()
+    }
+}
+
+impl
+    From<(
+        http::Request<Incoming>,
+        LocalAddr,
+        RemoteAddr,
+        Scheme,
+        ConnectInfoMap,
+    )> for Request
+{
+    fn from(
+        (req, local_addr, remote_addr, scheme, connect_info): (
+            http::Request<Incoming>,
+            LocalAddr,
+            RemoteAddr,
+            Scheme,
+            ConnectInfoMap,
+        ),
     ) -> Self {
         let (mut parts, body) = req.into_parts();
         let on_upgrade = Mutex::new(
@@ -137,11 +193,51 @@ impl From<(http::Request<Incoming>, LocalAddr, RemoteAddr, Scheme)> for Request
                 local_addr,
                 remote_addr,
                 scheme,
+                connect_info,
                 original_uri: parts.uri,
                 match_params: Default::default(),
+                raw_match_params: Default::default(),
+                invalid_match_param: None,
                 #[cfg(feature = "cookie")]
                 cookie_jar: None,
                 on_upgrade,
+                extractor_cache: Default::default(),
+                lifecycle: Default::default(),
+            },
+        }
+    }
+}
+
+impl<B> From<http::Request<B>> for Request
+where
+    B: hyper::body::Body + Send + Sync + 'static,
+    B::Data: Into<Bytes>,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn from(req: http::Request<B>) -> Self {
+        let (mut parts, body) = req.into_parts();
+        let on_upgrade = Mutex::new(
+            parts
+                .extensions
+                .remove::<hyper::upgrade::OnUpgrade>()
+                .map(|fut| OnUpgrade { fut }),
+        );
+
+        Self {
+            method: parts.method,
+            uri: parts.uri.clone(),
+            version: parts.version,
+            headers: parts.headers,
+            extensions: parts.extensions,
+            body: Body(
+                body.map_frame(|frame| frame.map_data(Into::into))
+                    .map_err(Error::other)
+                    .boxed(),
+            ),
+            state: RequestState {
+                original_uri: parts.uri,
+                on_upgrade,
+                ..Default::default()
             },
         }
     }
@@ -294,6 +390,9 @@ impl Request {
     /// # });
     /// ```
     pub fn path_params<T: DeserializeOwned>(&self) -> Result<T, ParsePathError> {
+        self.state()
+            .check_match_params()
+            .map_err(|_| ParsePathError)?;
         T::deserialize(PathDeserializer::new(&self.state().match_params))
             .map_err(|_| ParsePathError)
     }
@@ -389,6 +488,19 @@ impl Request {
         &self.state.local_addr
     }
 
+    /// Returns a reference to the per-connection metadata attached by the
+    /// acceptor.
+    #[inline]
+    pub fn connect_info(&self) -> &ConnectInfoMap {
+        &self.state.connect_info
+    }
+
+    /// Returns a reference to the lifecycle event bus of this request.
+    #[inline]
+    pub fn lifecycle(&self) -> &RequestLifecycle {
+        &self.state.lifecycle
+    }
+
     /// Returns a reference to the [`CookieJar`]
     #[cfg(feature = "cookie")]
     #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
@@ -609,12 +721,15 @@ impl RequestBuilder {
     pub fn body(self, body: impl Into<Body>) -> Request {
         Request {
             method: self.method,
-            uri: self.uri,
+            uri: self.uri.clone(),
             version: self.version,
             headers: self.headers,
             extensions: self.extensions,
             body: body.into(),
-            state: Default::default(),
+            state: RequestState {
+                original_uri: self.uri,
+                ..Default::default()
+            },
         }
     }
 
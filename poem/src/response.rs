@@ -259,6 +259,33 @@ impl Response {
             self.body,
         )
     }
+
+    /// Consumes the response and returns a new response with the body
+    /// transformed by `f`, the status, headers and extensions are preserved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{Body, Response};
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = Response::builder()
+    ///     .header("x-custom", "1")
+    ///     .body("hello")
+    ///     .map_body(|body| Body::from_async_read(body.into_async_read()));
+    /// assert_eq!(resp.header("x-custom"), Some("1"));
+    /// assert_eq!(resp.into_body().into_string().await.unwrap(), "hello");
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn map_body<F, B>(self, f: F) -> Self
+    where
+        F: FnOnce(Body) -> B,
+        B: Into<Body>,
+    {
+        let (parts, body) = self.into_parts();
+        Self::from_parts(parts, f(body).into())
+    }
 }
 
 /// An response builder.
@@ -364,4 +391,25 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
         assert_eq!(resp.body.into_string().await.unwrap(), "abc");
     }
+
+    #[tokio::test]
+    async fn parts() {
+        let resp = Response::builder()
+            .status(StatusCode::CREATED)
+            .header("x-a", "1")
+            .extension(10i32)
+            .body("abc");
+        let (mut parts, body) = resp.into_parts();
+        parts.headers.insert("x-b", "2".parse().unwrap());
+
+        let resp = Response::from_parts(parts, body).map_body(|body| {
+            assert!(!body.is_empty());
+            "def"
+        });
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.header("x-a"), Some("1"));
+        assert_eq!(resp.header("x-b"), Some("2"));
+        assert_eq!(resp.extensions().get::<i32>(), Some(&10));
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "def");
+    }
 }
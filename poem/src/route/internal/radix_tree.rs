@@ -444,6 +444,10 @@ pub(crate) type PathParams = Vec<(String, String)>;
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) struct Matches<'a, T> {
     pub(crate) params: PathParams,
+    pub(crate) raw_params: PathParams,
+    /// The name and the raw value of the first parameter that is not valid
+    /// UTF-8 after percent-decoding, which is missing from `params`.
+    pub(crate) invalid_param: Option<(String, String)>,
     pub(crate) data: &'a NodeData<T>,
 }
 
@@ -515,16 +519,27 @@ impl<T> RadixTree<T> {
         match self.root.matches(path.as_bytes(), &mut params) {
             Some(data) => {
                 let mut params2 = Vec::with_capacity(params.len());
+                let mut raw_params = Vec::with_capacity(params.len());
+                let mut invalid_param = None;
                 for (name, value) in params {
-                    if let (Ok(name), Ok(value)) = (
-                        std::str::from_utf8(name),
-                        percent_encoding::percent_decode(value).decode_utf8(),
-                    ) {
-                        params2.push((name.to_string(), value.into_owned()));
+                    if let (Ok(name), Ok(raw_value)) =
+                        (std::str::from_utf8(name), std::str::from_utf8(value))
+                    {
+                        match percent_encoding::percent_decode(value).decode_utf8() {
+                            Ok(value) => params2.push((name.to_string(), value.into_owned())),
+                            Err(_) => {
+                                invalid_param.get_or_insert_with(|| {
+                                    (name.to_string(), raw_value.to_string())
+                                });
+                            }
+                        }
+                        raw_params.push((name.to_string(), raw_value.to_string()));
                     }
                 }
                 Some(Matches {
                     params: params2,
+                    raw_params,
+                    invalid_param,
                     data,
                 })
             }
@@ -1171,7 +1186,9 @@ mod tests {
             assert_eq!(
                 tree.matches(path),
                 res.as_mut().map(|(params, data)| Matches {
+                    raw_params: params.clone(),
                     params: std::mem::take(params),
+                    invalid_param: None,
                     data
                 })
             );
@@ -1250,5 +1267,40 @@ mod tests {
         assert_eq!(matches.data.data, 1);
         assert_eq!(matches.params[0].0, "id");
         assert_eq!(matches.params[0].1, "你好");
+        assert_eq!(matches.raw_params[0].0, "id");
+        assert_eq!(matches.raw_params[0].1, "%E4%BD%A0%E5%A5%BD");
+    }
+
+    #[test]
+    fn test_percent_encoded_slash() {
+        let mut tree = RadixTree::default();
+        tree.add("/a/:id/b", 1).unwrap();
+
+        assert!(tree.matches("/a/x/y/b").is_none());
+
+        let matches = tree.matches("/a/x%2Fy/b").unwrap();
+        assert_eq!(matches.data.data, 1);
+        assert_eq!(matches.params[0].1, "x/y");
+        assert_eq!(matches.raw_params[0].1, "x%2Fy");
+    }
+
+    #[test]
+    fn test_percent_decode_failure() {
+        let mut tree = RadixTree::default();
+        tree.add("/a/:id/:name", 1).unwrap();
+
+        let matches = tree.matches("/a/%FF/b").unwrap();
+        assert_eq!(matches.params, vec![("name".to_string(), "b".to_string())]);
+        assert_eq!(
+            matches.raw_params,
+            vec![
+                ("id".to_string(), "%FF".to_string()),
+                ("name".to_string(), "b".to_string())
+            ]
+        );
+        assert_eq!(
+            matches.invalid_param,
+            Some(("id".to_string(), "%FF".to_string()))
+        );
     }
 }
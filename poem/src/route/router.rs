@@ -7,6 +7,7 @@ use crate::{
     error::{NotFoundError, ParsePathError, RouteError},
     http::{uri::PathAndQuery, Uri},
     route::{check_result, internal::radix_tree::RadixTree},
+    web::LifecycleStage,
     Endpoint, EndpointExt, IntoEndpoint, IntoResponse, Request, Response, Result,
 };
 
@@ -256,6 +257,7 @@ impl Route {
                     }
 
                     params.pop().expect("can't be empty due to a check above");
+                    req.state_mut().raw_match_params.pop();
                 }
 
                 let new_uri = {
@@ -334,7 +336,12 @@ impl Endpoint for Route {
     async fn call(&self, mut req: Request) -> Result<Self::Output> {
         match self.tree.matches(req.uri().path()) {
             Some(matches) => {
-                req.state_mut().match_params.extend(matches.params);
+                let state = req.state_mut();
+                state.match_params.extend(matches.params);
+                state.raw_match_params.extend(matches.raw_params);
+                if state.invalid_match_param.is_none() {
+                    state.invalid_match_param = matches.invalid_param;
+                }
 
                 let pattern = match matches.data.pattern.strip_suffix("/*--poem-rest") {
                     Some(pattern) => pattern.into(),
@@ -350,6 +357,7 @@ impl Endpoint for Route {
                     (Some(parent), None) => PathPattern(format!("{}{}", parent.0, pattern).into()),
                 };
                 req.set_data(pattern.clone());
+                req.lifecycle().emit(LifecycleStage::Routed);
 
                 let result = matches.data.data.call(req).await;
 
@@ -1,10 +1,18 @@
 use crate::{
-    endpoint::BoxEndpoint, error::MethodNotAllowedError, http::Method, Endpoint, EndpointExt,
-    IntoEndpoint, Request, Response, Result,
+    endpoint::BoxEndpoint,
+    error::MethodNotAllowedError,
+    http::{header, Method, StatusCode},
+    Endpoint, EndpointExt, IntoEndpoint, Request, Response, Result,
 };
 
 /// Routing object for HTTP methods
 ///
+/// Any method can be registered with [`RouteMethod::method`], including the
+/// extension methods such as `REPORT` and `MKCALENDAR` used by CalDAV and
+/// CardDAV. `HEAD` falls back to `GET`, and if no endpoint is registered for
+/// `OPTIONS`, it responds with `204 No Content` and the `Allow` header
+/// listing the [allowed methods](RouteMethod::allowed_methods).
+///
 /// # Errors
 ///
 /// - [`MethodNotAllowedError`]
@@ -72,6 +80,24 @@ impl RouteMethod {
         self
     }
 
+    /// Returns the methods allowed by this object, in the order they were
+    /// registered, including `HEAD` if `GET` is registered and `OPTIONS`.
+    pub fn allowed_methods(&self) -> Vec<Method> {
+        let mut methods: Vec<Method> = Vec::new();
+        for (method, _) in &self.methods {
+            if !methods.contains(method) {
+                methods.push(method.clone());
+            }
+        }
+        if methods.contains(&Method::GET) && !methods.contains(&Method::HEAD) {
+            methods.push(Method::HEAD);
+        }
+        if !methods.contains(&Method::OPTIONS) {
+            methods.push(Method::OPTIONS);
+        }
+        methods
+    }
+
     /// Sets the endpoint for `GET`.
     #[must_use]
     pub fn get<E>(self, ep: E) -> Self
@@ -161,6 +187,80 @@ impl RouteMethod {
     {
         self.method(Method::TRACE, ep)
     }
+
+    /// Sets the endpoint for the WebDAV `PROPFIND` method.
+    #[must_use]
+    pub fn propfind<E>(self, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.method(webdav_method("PROPFIND"), ep)
+    }
+
+    /// Sets the endpoint for the WebDAV `PROPPATCH` method.
+    #[must_use]
+    pub fn proppatch<E>(self, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.method(webdav_method("PROPPATCH"), ep)
+    }
+
+    /// Sets the endpoint for the WebDAV `MKCOL` method.
+    #[must_use]
+    pub fn mkcol<E>(self, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.method(webdav_method("MKCOL"), ep)
+    }
+
+    /// Sets the endpoint for the WebDAV `COPY` method.
+    #[must_use]
+    pub fn copy<E>(self, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.method(webdav_method("COPY"), ep)
+    }
+
+    /// Sets the endpoint for the WebDAV `MOVE` method.
+    #[must_use]
+    pub fn r#move<E>(self, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.method(webdav_method("MOVE"), ep)
+    }
+
+    /// Sets the endpoint for the WebDAV `LOCK` method.
+    #[must_use]
+    pub fn lock<E>(self, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.method(webdav_method("LOCK"), ep)
+    }
+
+    /// Sets the endpoint for the WebDAV `UNLOCK` method.
+    #[must_use]
+    pub fn unlock<E>(self, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.method(webdav_method("UNLOCK"), ep)
+    }
+}
+
+fn webdav_method(name: &'static str) -> Method {
+    Method::from_bytes(name.as_bytes()).expect("valid method")
 }
 
 #[async_trait::async_trait]
@@ -182,6 +282,18 @@ impl Endpoint for RouteMethod {
                     resp.set_body(());
                     return Ok(resp);
                 }
+                if req.method() == Method::OPTIONS {
+                    let allow = self
+                        .allowed_methods()
+                        .iter()
+                        .map(Method::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    return Ok(Response::builder()
+                        .status(StatusCode::NO_CONTENT)
+                        .header(header::ALLOW, allow)
+                        .finish());
+                }
                 Err(MethodNotAllowedError.into())
             }
         }
@@ -347,4 +459,105 @@ mod tests {
         resp.assert_status_is_ok();
         resp.assert_text("").await;
     }
+
+    #[tokio::test]
+    async fn extension_methods() {
+        #[handler(internal)]
+        fn index(method: Method) -> String {
+            method.to_string()
+        }
+
+        let report = Method::from_bytes(b"REPORT").unwrap();
+        let mkcalendar = Method::from_bytes(b"MKCALENDAR").unwrap();
+        let route = RouteMethod::new()
+            .get(index)
+            .method(report.clone(), index)
+            .method(mkcalendar.clone(), index);
+        assert_eq!(
+            route.allowed_methods(),
+            [
+                Method::GET,
+                report.clone(),
+                mkcalendar.clone(),
+                Method::HEAD,
+                Method::OPTIONS
+            ]
+        );
+
+        let cli = TestClient::new(route);
+        for method in [report, mkcalendar] {
+            let resp = cli.request(method.clone(), "/").send().await;
+            resp.assert_status_is_ok();
+            resp.assert_text(method.as_str()).await;
+        }
+
+        let resp = cli.request(Method::OPTIONS, "/").send().await;
+        resp.assert_status(StatusCode::NO_CONTENT);
+        resp.assert_header(header::ALLOW, "GET, REPORT, MKCALENDAR, HEAD, OPTIONS");
+
+        cli.request(Method::from_bytes(b"ACL").unwrap(), "/")
+            .send()
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn options_method() {
+        #[handler(internal)]
+        fn index() -> &'static str {
+            "hello"
+        }
+
+        let resp = TestClient::new(RouteMethod::new().post(index))
+            .request(Method::OPTIONS, "/")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NO_CONTENT);
+        resp.assert_header(header::ALLOW, "POST, OPTIONS");
+
+        let resp = TestClient::new(RouteMethod::new().post(index).options(index))
+            .request(Method::OPTIONS, "/")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("hello").await;
+    }
+
+    #[tokio::test]
+    async fn webdav_methods() {
+        #[handler(internal)]
+        fn index() -> &'static str {
+            "hello"
+        }
+
+        macro_rules! test_method {
+            ($(($id:ident, $method:literal)),*) => {
+                $(
+                let route = RouteMethod::new().$id(index).get(index);
+                let resp = TestClient::new(route)
+                    .request(Method::from_bytes($method).unwrap(), "/")
+                    .send()
+                    .await;
+                resp.assert_status_is_ok();
+                resp.assert_text("hello").await;
+                )*
+            };
+        }
+
+        test_method!(
+            (propfind, b"PROPFIND"),
+            (proppatch, b"PROPPATCH"),
+            (mkcol, b"MKCOL"),
+            (copy, b"COPY"),
+            (r#move, b"MOVE"),
+            (lock, b"LOCK"),
+            (unlock, b"UNLOCK")
+        );
+
+        let resp = TestClient::new(RouteMethod::new().get(index))
+            .request(Method::from_bytes(b"PROPFIND").unwrap(), "/")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    }
 }
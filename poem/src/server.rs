@@ -11,6 +11,7 @@ use std::{
     task::{Context, Poll},
 };
 
+use futures_util::{future::BoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
 use http::uri::Scheme;
 use hyper::body::Incoming;
 use hyper_util::server::conn::auto;
@@ -18,14 +19,17 @@ use pin_project_lite::pin_project;
 use tokio::{
     io::{AsyncRead, AsyncWrite, ReadBuf, Result as IoResult},
     sync::{oneshot, Notify},
+    task::JoinHandle,
     time::Duration,
 };
 use tokio_util::sync::CancellationToken;
 
 use crate::{
+    body::BoxBody,
+    h2c::H2cUpgrade,
     listener::{Acceptor, AcceptorExt, Listener},
-    web::{LocalAddr, RemoteAddr},
-    Endpoint, EndpointExt, IntoEndpoint, Response,
+    web::{ConnectInfoMap, LocalAddr, RemoteAddr},
+    Endpoint, EndpointExt, IntoEndpoint, Request, Response,
 };
 
 enum Either<L, A> {
@@ -33,12 +37,39 @@ enum Either<L, A> {
     Acceptor(A),
 }
 
+type Sidecar = Box<dyn FnOnce(ShutdownSignal) -> BoxFuture<'static, IoResult<()>> + Send>;
+
+/// A signal that is triggered when the graceful shutdown of a [`Server`] is
+/// initiated, which is passed to the sidecar tasks, see [`Server::sidecar`].
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal(CancellationToken);
+
+impl ShutdownSignal {
+    /// Waits until the graceful shutdown is initiated.
+    pub async fn wait(&self) {
+        self.0.cancelled().await
+    }
+
+    /// Returns `true` if the graceful shutdown has been initiated.
+    pub fn is_shutdown(&self) -> bool {
+        self.0.is_cancelled()
+    }
+}
+
 /// An HTTP Server.
+///
+/// Each connection is served with `HTTP/1.1` or `HTTP/2`. On listeners
+/// without TLS, `HTTP/2` over cleartext (`h2c`) is used if the client starts
+/// the connection with the `HTTP/2` connection preface (prior knowledge), as
+/// gRPC clients do, or with the `Upgrade: h2c` mechanism of `HTTP/1.1`.
+/// Upgrade requests with a body are answered with `HTTP/1.1` instead.
 #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
 pub struct Server<L, A> {
     listener: Either<L, A>,
     name: Option<String>,
     idle_timeout: Option<Duration>,
+    sidecars: Vec<Sidecar>,
 }
 
 impl<L: Listener> Server<L, Infallible> {
@@ -48,6 +79,7 @@ impl<L: Listener> Server<L, Infallible> {
             listener: Either::Listener(listener),
             name: None,
             idle_timeout: None,
+            sidecars: Vec::new(),
         }
     }
 }
@@ -59,6 +91,7 @@ impl<A: Acceptor> Server<Infallible, A> {
             listener: Either::Acceptor(acceptor),
             name: None,
             idle_timeout: None,
+            sidecars: Vec::new(),
         }
     }
 }
@@ -79,7 +112,8 @@ where
     }
 
     /// Specify connection idle timeout. Connections will be terminated if there was no activity
-    /// within this period of time
+    /// within this period of time, including the connections upgraded with
+    /// `Upgrade: h2c`.
     #[must_use]
     pub fn idle_timeout(self, timeout: Duration) -> Self {
         Self {
@@ -88,6 +122,114 @@ where
         }
     }
 
+    /// Adds a sidecar task for a non-HTTP protocol, such as a UDP socket,
+    /// which shares the lifecycle of this server.
+    ///
+    /// The task is spawned when the server is started, and receives a
+    /// [`ShutdownSignal`] that is triggered when the graceful shutdown is
+    /// initiated. The server waits for the task to complete before it stops,
+    /// and aborts it once the graceful shutdown timeout elapses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{handler, listener::TcpListener, Server};
+    /// use tokio::net::UdpSocket;
+    ///
+    /// #[handler]
+    /// fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// let server = Server::new(TcpListener::bind("0.0.0.0:3000")).sidecar(|shutdown| async move {
+    ///     let socket = UdpSocket::bind("0.0.0.0:3001").await?;
+    ///     let mut buf = [0; 1500];
+    ///     loop {
+    ///         tokio::select! {
+    ///             _ = shutdown.wait() => return Ok(()),
+    ///             res = socket.recv_from(&mut buf) => {
+    ///                 let (n, addr) = res?;
+    ///                 socket.send_to(&buf[..n], addr).await?;
+    ///             }
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    #[must_use]
+    pub fn sidecar<F, Fut>(mut self, f: F) -> Self
+    where
+        F: FnOnce(ShutdownSignal) -> Fut + Send + 'static,
+        Fut: Future<Output = IoResult<()>> + Send + 'static,
+    {
+        self.sidecars
+            .push(Box::new(move |shutdown| f(shutdown).boxed()));
+        self
+    }
+
+    /// Adds a sidecar listener for a non-HTTP protocol over TCP (or any
+    /// other [`Listener`]), which shares the lifecycle of this server.
+    ///
+    /// Each accepted connection is passed to `handler` in a new task. When
+    /// the graceful shutdown is initiated, the listener stops accepting new
+    /// connections, and the server waits for the accepted connections to be
+    /// handled, see [`Server::sidecar`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{handler, listener::TcpListener, Server};
+    /// use tokio::io::AsyncWriteExt;
+    ///
+    /// #[handler]
+    /// fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// let server = Server::new(TcpListener::bind("0.0.0.0:3000")).tcp_sidecar(
+    ///     TcpListener::bind("0.0.0.0:3001"),
+    ///     |mut io, _remote_addr| async move {
+    ///         let _ = io.write_all(b"ok\n").await;
+    ///     },
+    /// );
+    /// ```
+    #[must_use]
+    pub fn tcp_sidecar<T, F, Fut>(self, listener: T, handler: F) -> Self
+    where
+        T: Listener + 'static,
+        T::Acceptor: 'static,
+        F: Fn(<T::Acceptor as Acceptor>::Io, RemoteAddr) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.sidecar(move |shutdown| async move {
+            let mut acceptor = listener.into_acceptor().await?;
+            for addr in acceptor.local_addr() {
+                tracing::info!(addr = %addr, "sidecar listening");
+            }
+
+            let mut connections = FuturesUnordered::new();
+            let mut backoff = AcceptBackoff::default();
+            loop {
+                tokio::select! {
+                    _ = shutdown.wait() => break,
+                    res = acceptor.accept() => {
+                        match res {
+                            Ok((io, _, remote_addr, _)) => {
+                                backoff.reset();
+                                connections.push(AbortOnDrop(tokio::spawn(handler(io, remote_addr))));
+                            }
+                            Err(err) => backoff.wait(&err).await,
+                        }
+                    }
+                    Some(_) = connections.next(), if !connections.is_empty() => {}
+                }
+            }
+
+            drop(acceptor);
+            while connections.next().await.is_some() {}
+            Ok(())
+        })
+    }
+
     /// Run this server.
     pub async fn run<E>(self, ep: E) -> IoResult<()>
     where
@@ -114,6 +256,7 @@ where
             listener,
             name,
             idle_timeout,
+            sidecars,
         } = self;
         let name = name.as_deref();
         let alive_connections = Arc::new(AtomicUsize::new(0));
@@ -131,8 +274,24 @@ where
         for addr in acceptor.local_addr() {
             tracing::info!(name = name, addr = %addr, "listening");
         }
+        let shutdown_signal = ShutdownSignal(server_graceful_shutdown_token.clone());
+        // the sidecars are aborted if this future returns or is dropped early
+        let sidecars = sidecars
+            .into_iter()
+            .map(|sidecar| {
+                let fut = sidecar(shutdown_signal.clone());
+                let name = name.map(ToString::to_string);
+                AbortOnDrop(tokio::spawn(async move {
+                    if let Err(err) = fut.await {
+                        tracing::error!(name = name.as_deref(), error = %err, "sidecar failed");
+                    }
+                }))
+            })
+            .collect::<Vec<_>>();
+
         tracing::info!(name = name, "server started");
 
+        let mut backoff = AcceptBackoff::default();
         loop {
             tokio::select! {
                 _ = &mut signal => {
@@ -154,34 +313,42 @@ where
                     }
                     break;
                 },
-                res = acceptor.accept() => {
-                    if let Ok((socket, local_addr, remote_addr, scheme)) = res {
-                        alive_connections.fetch_add(1, Ordering::Release);
-
-                        let ep = ep.clone();
-                        let alive_connections = alive_connections.clone();
-                        let notify = notify.clone();
-                        let timeout_token = timeout_token.clone();
-                        let server_graceful_shutdown_token = server_graceful_shutdown_token.clone();
-
-                        tokio::spawn(async move {
-                            let serve_connection = serve_connection(socket, local_addr, remote_addr, scheme, ep, server_graceful_shutdown_token, idle_timeout);
-
-                            if timeout.is_some() {
-                                tokio::select! {
-                                    _ = serve_connection => {}
-                                    _ = timeout_token.cancelled() => {}
-                                }
-                            } else {
-                               serve_connection.await;
+                res = acceptor.accept_with_info() => {
+                    let (socket, local_addr, remote_addr, scheme, connect_info) = match res {
+                        Ok(res) => {
+                            backoff.reset();
+                            res
+                        }
+                        Err(err) => {
+                            backoff.wait(&err).await;
+                            continue;
+                        }
+                    };
+                    alive_connections.fetch_add(1, Ordering::Release);
+
+                    let ep = ep.clone();
+                    let alive_connections = alive_connections.clone();
+                    let notify = notify.clone();
+                    let timeout_token = timeout_token.clone();
+                    let server_graceful_shutdown_token = server_graceful_shutdown_token.clone();
+
+                    tokio::spawn(async move {
+                        let serve_connection = serve_connection(socket, local_addr, remote_addr, scheme, connect_info, ep, server_graceful_shutdown_token, idle_timeout);
+
+                        if timeout.is_some() {
+                            tokio::select! {
+                                _ = serve_connection => {}
+                                _ = timeout_token.cancelled() => {}
                             }
+                        } else {
+                           serve_connection.await;
+                        }
 
-                            if alive_connections.fetch_sub(1, Ordering::Acquire) == 1 {
-                                // We have to notify only if there is a registered waiter on shutdown
-                                notify.notify_waiters();
-                            }
-                        });
-                    }
+                        if alive_connections.fetch_sub(1, Ordering::Acquire) == 1 {
+                            // We have to notify only if there is a registered waiter on shutdown
+                            notify.notify_waiters();
+                        }
+                    });
                 }
             }
         }
@@ -192,11 +359,61 @@ where
             notify.notified().await;
         }
 
+        for mut sidecar in sidecars {
+            tokio::select! {
+                _ = &mut sidecar => {}
+                _ = timeout_token.cancelled() => {}
+            }
+        }
+
         tracing::info!(name = name, "server stopped");
         Ok(())
     }
 }
 
+/// Aborts the task when the handle is dropped, so that the sidecars are
+/// aborted with the server, and the connections of a sidecar listener with the
+/// sidecar.
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Future for AbortOnDrop {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map(|_| ())
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Delays accepting after an error, such as running out of file descriptors,
+/// instead of retrying in a busy loop.
+#[derive(Default)]
+struct AcceptBackoff(Option<Duration>);
+
+impl AcceptBackoff {
+    const MIN_DELAY: Duration = Duration::from_millis(5);
+    const MAX_DELAY: Duration = Duration::from_secs(1);
+
+    async fn wait(&mut self, err: &io::Error) {
+        let delay = match self.0 {
+            Some(delay) => (delay * 2).min(Self::MAX_DELAY),
+            None => Self::MIN_DELAY,
+        };
+        self.0 = Some(delay);
+        tracing::error!(error = %err, delay = ?delay, "failed to accept connection");
+        tokio::time::sleep(delay).await;
+    }
+
+    fn reset(&mut self) {
+        self.0 = None;
+    }
+}
+
 pin_project! {
     struct ClosingInactiveConnection<T> {
         #[pin]
@@ -308,31 +525,45 @@ impl<T> ClosingInactiveConnection<T> {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn serve_connection(
     socket: impl AsyncRead + AsyncWrite + Send + Unpin + 'static,
     local_addr: LocalAddr,
     remote_addr: RemoteAddr,
     scheme: Scheme,
+    connect_info: ConnectInfoMap,
     ep: Arc<dyn Endpoint<Output = Response>>,
     server_graceful_shutdown_token: CancellationToken,
     idle_connection_close_timeout: Option<Duration>,
 ) {
     let connection_shutdown_token = CancellationToken::new();
+    connect_info.insert(local_addr.clone());
+    connect_info.insert(remote_addr.clone());
+
+    let ctx = ConnectionContext {
+        ep,
+        local_addr,
+        remote_addr: remote_addr.clone(),
+        scheme,
+        connect_info,
+    };
+    let h2c_upgrade = Arc::new(parking_lot::Mutex::new(None));
 
     let service = hyper::service::service_fn({
-        let remote_addr = remote_addr.clone();
+        let ctx = ctx.clone();
+        let h2c_upgrade = h2c_upgrade.clone();
 
-        move |req: http::Request<Incoming>| {
-            let ep = ep.clone();
-            let local_addr = local_addr.clone();
-            let remote_addr = remote_addr.clone();
-            let scheme = scheme.clone();
+        move |mut req: http::Request<Incoming>| {
+            let ctx = ctx.clone();
+            let h2c_upgrade = h2c_upgrade.clone();
             async move {
-                Ok::<http::Response<_>, Infallible>(
-                    ep.get_response((req, local_addr, remote_addr, scheme).into())
-                        .await
-                        .into(),
-                )
+                if ctx.scheme == Scheme::HTTP {
+                    if let Some(upgrade) = H2cUpgrade::from_request(&req) {
+                        *h2c_upgrade.lock() = Some((hyper::upgrade::on(&mut req), upgrade));
+                        return Ok(H2cUpgrade::switching_protocols());
+                    }
+                }
+                Ok::<http::Response<_>, Infallible>(ctx.call(req).await)
             }
         }
     });
@@ -362,9 +593,329 @@ async fn serve_connection(
         _ = conn => {
             // Connection completed successfully.
         },
+        _ = connection_shutdown_token.cancelled() => {
+            tracing::info!(remote_addr=%remote_addr, "closing connection due to inactivity");
+            return;
+        }
+        _ = server_graceful_shutdown_token.cancelled() => return,
+    }
+
+    // The connection has been upgraded to `HTTP/2`, see `H2cUpgrade`. The
+    // upgraded IO reads and writes through the `ClosingInactiveConnection`, so
+    // the idle timeout still cancels `connection_shutdown_token`.
+    let Some((on_upgrade, upgrade)) = h2c_upgrade.lock().take() else {
+        return;
+    };
+    let Ok(upgraded) = on_upgrade.await else {
+        return;
+    };
+    let service = hyper::service::service_fn(move |req: http::Request<Incoming>| {
+        let ctx = ctx.clone();
+        async move { Ok::<http::Response<_>, Infallible>(ctx.call(req).await) }
+    });
+    let builder = hyper::server::conn::http2::Builder::new(hyper_util::rt::TokioExecutor::new());
+    let conn = builder.serve_connection(
+        hyper_util::rt::TokioIo::new(upgrade.wrap(hyper_util::rt::TokioIo::new(upgraded))),
+        service,
+    );
+    futures_util::pin_mut!(conn);
+
+    tokio::select! {
+        _ = conn => {}
         _ = connection_shutdown_token.cancelled() => {
             tracing::info!(remote_addr=%remote_addr, "closing connection due to inactivity");
         }
         _ = server_graceful_shutdown_token.cancelled() => {}
     }
 }
+
+/// The state of a connection, which is shared by its requests.
+#[derive(Clone)]
+struct ConnectionContext {
+    ep: Arc<dyn Endpoint<Output = Response>>,
+    local_addr: LocalAddr,
+    remote_addr: RemoteAddr,
+    scheme: Scheme,
+    connect_info: ConnectInfoMap,
+}
+
+impl ConnectionContext {
+    async fn call(&self, req: http::Request<Incoming>) -> http::Response<BoxBody> {
+        let req: Request = (
+            req,
+            self.local_addr.clone(),
+            self.remote_addr.clone(),
+            self.scheme.clone(),
+            self.connect_info.clone(),
+        )
+            .into();
+        let lifecycle = req.lifecycle().clone();
+        let resp = self.ep.get_response(req).await;
+        lifecycle.finish(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+
+    use super::*;
+    use crate::{handler, listener::TcpListener};
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    async fn start_server() -> SocketAddr {
+        start_server_with_idle_timeout(None).await
+    }
+
+    async fn start_server_with_idle_timeout(idle_timeout: Option<Duration>) -> SocketAddr {
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+        let mut server = Server::new_with_acceptor(acceptor);
+        if let Some(idle_timeout) = idle_timeout {
+            server = server.idle_timeout(idle_timeout);
+        }
+        tokio::spawn(server.run(index));
+        addr
+    }
+
+    #[tokio::test]
+    async fn sidecar_shutdown() {
+        let (tx, rx) = oneshot::channel::<()>();
+        let stopped = Arc::new(Notify::new());
+
+        let server = Server::new(TcpListener::bind("127.0.0.1:0"))
+            .sidecar({
+                let stopped = stopped.clone();
+                move |shutdown| async move {
+                    shutdown.wait().await;
+                    assert!(shutdown.is_shutdown());
+                    stopped.notify_one();
+                    Ok(())
+                }
+            })
+            .sidecar(|_| async move {
+                // never completes, aborted after the timeout
+                futures_util::future::pending::<()>().await;
+                Ok(())
+            });
+        let handle = tokio::spawn(server.run_with_graceful_shutdown(
+            index,
+            async move {
+                let _ = rx.await;
+            },
+            Some(Duration::from_millis(100)),
+        ));
+
+        tx.send(()).unwrap();
+        stopped.notified().await;
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn sidecar_aborted_with_server() {
+        let (started_tx, started_rx) = oneshot::channel::<()>();
+        let (dropped_tx, dropped_rx) = oneshot::channel::<()>();
+        let server = Server::new(TcpListener::bind("127.0.0.1:0")).sidecar(|_| async move {
+            let _dropped_tx = dropped_tx;
+            let _ = started_tx.send(());
+            futures_util::future::pending::<()>().await;
+            Ok(())
+        });
+        let handle = tokio::spawn(server.run(index));
+
+        started_rx.await.unwrap();
+        handle.abort();
+        // the sender is dropped with the sidecar
+        assert!(dropped_rx.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn accept_backoff() {
+        let mut backoff = AcceptBackoff::default();
+        let err = io::Error::other("accept");
+        backoff.wait(&err).await;
+        assert_eq!(backoff.0, Some(AcceptBackoff::MIN_DELAY));
+        backoff.wait(&err).await;
+        assert_eq!(backoff.0, Some(AcceptBackoff::MIN_DELAY * 2));
+        backoff.reset();
+        assert_eq!(backoff.0, None);
+    }
+
+    #[tokio::test]
+    async fn h2c_prior_knowledge() {
+        let addr = start_server().await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n")
+            .await
+            .unwrap();
+        // an empty SETTINGS frame
+        stream
+            .write_all(&[0, 0, 0, 0x4, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+
+        // the server must reply with its own SETTINGS frame
+        let mut frame_header = [0; 9];
+        stream.read_exact(&mut frame_header).await.unwrap();
+        assert_eq!(frame_header[3], 0x4);
+    }
+
+    async fn read_head(stream: &mut TcpStream) -> String {
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        String::from_utf8(head).unwrap().to_ascii_lowercase()
+    }
+
+    async fn read_frame(stream: &mut TcpStream) -> ([u8; 9], Vec<u8>) {
+        let mut frame_header = [0; 9];
+        stream.read_exact(&mut frame_header).await.unwrap();
+        let len = u32::from_be_bytes([0, frame_header[0], frame_header[1], frame_header[2]]);
+        let mut payload = vec![0; len as usize];
+        stream.read_exact(&mut payload).await.unwrap();
+        (frame_header, payload)
+    }
+
+    #[tokio::test]
+    async fn h2c_upgrade() {
+        let addr = start_server().await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: AAMAAABkAAQAAP__\r\n\r\n",
+            )
+            .await
+            .unwrap();
+
+        let head = read_head(&mut stream).await;
+        assert!(head.starts_with("http/1.1 101 switching protocols\r\n"));
+        assert!(head.contains("upgrade: h2c\r\n"));
+
+        stream
+            .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n")
+            .await
+            .unwrap();
+        stream
+            .write_all(&[0, 0, 0, 0x4, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+
+        // the server SETTINGS frame, then the response of the stream 1
+        let (frame_header, _) = read_frame(&mut stream).await;
+        assert_eq!(frame_header[3], 0x4);
+        let mut headers = None;
+        loop {
+            let (frame_header, payload) = read_frame(&mut stream).await;
+            if frame_header[5..] != [0, 0, 0, 1] {
+                continue;
+            }
+            match frame_header[3] {
+                // `:status: 200` is the index 8 of the static table
+                0x1 => headers = Some(payload[0]),
+                0x0 if !payload.is_empty() => {
+                    assert_eq!(payload, b"hello");
+                    break;
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(headers, Some(0x88));
+    }
+
+    /// Upgrades the connection to `HTTP/2` and returns an `h2` client for it.
+    ///
+    /// The response of the upgrade request is sent on the stream 1, which the
+    /// client does not know about, so its own requests start from the stream 3.
+    async fn h2c_upgrade_client(
+        addr: SocketAddr,
+    ) -> (
+        h2::client::SendRequest<bytes::Bytes>,
+        h2::client::Connection<TcpStream, bytes::Bytes>,
+    ) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: AAMAAABkAAQAAP__\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let head = read_head(&mut stream).await;
+        assert!(head.starts_with("http/1.1 101 switching protocols\r\n"));
+
+        h2::client::Builder::new()
+            .initial_stream_id(3)
+            .handshake(stream)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn h2c_upgrade_with_h2_client() {
+        let addr = start_server().await;
+        let (client, conn) = h2c_upgrade_client(addr).await;
+        tokio::spawn(conn);
+
+        for _ in 0..2 {
+            let mut client = client.clone().ready().await.unwrap();
+            let req = http::Request::get("http://localhost/").body(()).unwrap();
+            let (resp, _) = client.send_request(req, true).unwrap();
+            let resp = resp.await.unwrap();
+            assert_eq!(resp.status(), http::StatusCode::OK);
+            let data = resp.into_body().data().await.unwrap().unwrap();
+            assert_eq!(data, "hello");
+        }
+    }
+
+    #[tokio::test]
+    async fn h2c_upgrade_idle_timeout() {
+        let addr = start_server_with_idle_timeout(Some(Duration::from_millis(200))).await;
+        let (client, conn) = h2c_upgrade_client(addr).await;
+        let conn = tokio::spawn(conn);
+
+        let mut client = client.ready().await.unwrap();
+        let req = http::Request::get("http://localhost/").body(()).unwrap();
+        let (resp, _) = client.send_request(req, true).unwrap();
+        assert_eq!(resp.await.unwrap().status(), http::StatusCode::OK);
+
+        // the server closes the upgraded connection after it is idle
+        tokio::time::timeout(Duration::from_secs(5), conn)
+            .await
+            .expect("the connection is closed")
+            .unwrap()
+            .ok();
+    }
+
+    #[tokio::test]
+    async fn h2c_upgrade_with_body() {
+        let addr = start_server().await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                b"POST / HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: AAMAAABkAAQAAP__\r\nContent-Length: 3\r\n\r\nabc",
+            )
+            .await
+            .unwrap();
+
+        let head = read_head(&mut stream).await;
+        assert!(head.starts_with("http/1.1 200 ok\r\n"));
+    }
+}
@@ -26,6 +26,7 @@ pub struct CookieConfig {
     http_only: bool,
     max_age: Option<Duration>,
     same_site: Option<SameSite>,
+    rolling: bool,
 }
 
 impl Default for CookieConfig {
@@ -39,6 +40,7 @@ impl Default for CookieConfig {
             http_only: true,
             max_age: None,
             same_site: None,
+            rolling: false,
         }
     }
 }
@@ -128,6 +130,26 @@ impl CookieConfig {
         }
     }
 
+    /// Sets whether the expiration of the session is extended on every
+    /// request, even if the session is unchanged. Default is `false`.
+    ///
+    /// It only takes effect when the `MaxAge` is set, the session then expires
+    /// after being idle for the `MaxAge` instead of after it is saved.
+    #[must_use]
+    pub fn rolling(self, value: bool) -> Self {
+        Self {
+            rolling: value,
+            ..self
+        }
+    }
+
+    /// Returns `true` if the session should be saved again to extend the
+    /// expiration.
+    #[inline]
+    pub(crate) fn is_rolling(&self) -> bool {
+        self.rolling && self.max_age.is_some()
+    }
+
     /// Returns the TTL(time-to-live) of the cookie.
     #[inline]
     pub(crate) fn ttl(&self) -> Option<Duration> {
@@ -68,7 +68,14 @@ impl<E: Endpoint> Endpoint for CookieSessionEndpoint<E> {
             SessionStatus::Purged => {
                 self.config.remove_cookie(&cookie_jar);
             }
-            SessionStatus::Unchanged => {}
+            SessionStatus::Unchanged => {
+                if self.config.is_rolling() && !session.is_empty() {
+                    self.config.set_cookie_value(
+                        &cookie_jar,
+                        &serde_json::to_string(&session.entries()).unwrap_or_default(),
+                    );
+                }
+            }
         };
 
         Ok(resp)
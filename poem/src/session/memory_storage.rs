@@ -134,6 +134,25 @@ mod tests {
         client.assert_cookies(vec![]);
     }
 
+    #[tokio::test]
+    async fn rolling() {
+        let app = Route::new().at("/:action", index).with(ServerSession::new(
+            CookieConfig::default()
+                .max_age(Duration::from_secs(2))
+                .rolling(true),
+            MemoryStorage::new(),
+        ));
+        let mut client = TestClient::default();
+
+        client.call(&app, 1).await;
+
+        // the unchanged session is saved again to extend the expiration
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        client.call(&app, 0).await;
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        client.call(&app, 2).await;
+    }
+
     #[tokio::test]
     async fn timeout() {
         let storage = MemoryStorage::new();
@@ -6,10 +6,16 @@ use rand::{thread_rng, Rng};
 use crate::{
     middleware::{CookieJarManager, CookieJarManagerEndpoint},
     session::{session_storage::SessionStorage, CookieConfig, Session, SessionStatus},
+    web::TenantId,
     Endpoint, Middleware, Request, Result,
 };
 
 /// Middleware for server-side session.
+///
+/// If the tenant of the request is resolved by
+/// [`TenantResolver`](crate::middleware::TenantResolver), the sessions are
+/// stored with the keys scoped by [`TenantId::scoped`], so a session of a
+/// tenant cannot be loaded by the requests of the other tenants.
 pub struct ServerSession<T> {
     config: Arc<CookieConfig>,
     storage: Arc<T>,
@@ -62,9 +68,15 @@ where
 
     async fn call(&self, mut req: Request) -> Result<Self::Output> {
         let cookie_jar = req.cookie().clone();
+        let tenant = req.extensions().get::<TenantId>().cloned();
+        let storage_key = |session_id: &str| match &tenant {
+            Some(tenant) => tenant.scoped(session_id),
+            None => session_id.to_string(),
+        };
+
         let mut session_id = self.config.get_cookie_value(&cookie_jar);
         let session = match &session_id {
-            Some(id) => match self.storage.load_session(id).await? {
+            Some(id) => match self.storage.load_session(&storage_key(id)).await? {
                 Some(entries) => Session::new(entries),
                 None => {
                     session_id = None;
@@ -81,35 +93,62 @@ where
             SessionStatus::Changed => match session_id {
                 Some(session_id) => {
                     self.storage
-                        .update_session(&session_id, &session.entries(), self.config.ttl())
+                        .update_session(
+                            &storage_key(&session_id),
+                            &session.entries(),
+                            self.config.ttl(),
+                        )
                         .await?;
                 }
                 None => {
                     let session_id = generate_session_id();
                     self.config.set_cookie_value(&cookie_jar, &session_id);
                     self.storage
-                        .update_session(&session_id, &session.entries(), self.config.ttl())
+                        .update_session(
+                            &storage_key(&session_id),
+                            &session.entries(),
+                            self.config.ttl(),
+                        )
                         .await?;
                 }
             },
             SessionStatus::Renewed => {
                 if let Some(session_id) = session_id {
-                    self.storage.remove_session(&session_id).await?;
+                    self.storage
+                        .remove_session(&storage_key(&session_id))
+                        .await?;
                 }
 
                 let session_id = generate_session_id();
                 self.config.set_cookie_value(&cookie_jar, &session_id);
                 self.storage
-                    .update_session(&session_id, &session.entries(), self.config.ttl())
+                    .update_session(
+                        &storage_key(&session_id),
+                        &session.entries(),
+                        self.config.ttl(),
+                    )
                     .await?;
             }
             SessionStatus::Purged => {
                 if let Some(session_id) = session_id {
-                    self.storage.remove_session(&session_id).await?;
+                    self.storage
+                        .remove_session(&storage_key(&session_id))
+                        .await?;
                     self.config.remove_cookie(&cookie_jar);
                 }
             }
-            SessionStatus::Unchanged => {}
+            SessionStatus::Unchanged => {
+                if let Some(session_id) = session_id.filter(|_| self.config.is_rolling()) {
+                    self.config.set_cookie_value(&cookie_jar, &session_id);
+                    self.storage
+                        .update_session(
+                            &storage_key(&session_id),
+                            &session.entries(),
+                            self.config.ttl(),
+                        )
+                        .await?;
+                }
+            }
         };
 
         Ok(resp)
@@ -0,0 +1,141 @@
+use mime::Mime;
+
+use crate::{
+    http::header,
+    web::{ContentDisposition, DispositionType},
+    Body, IntoResponse, Response,
+};
+
+/// A response for downloading a file, which sets the `Content-Disposition`
+/// header with the filename encoded as described in
+/// [RFC 5987](https://datatracker.ietf.org/doc/html/rfc5987).
+///
+/// The content type defaults to `application/octet-stream`.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::{header, StatusCode},
+///     test::TestClient,
+///     web::Attachment,
+/// };
+///
+/// #[handler]
+/// fn download() -> Attachment<&'static str> {
+///     Attachment::new("a,b,c").filename("报告.csv").content_type(mime::TEXT_CSV)
+/// }
+///
+/// let cli = TestClient::new(download);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("text/csv");
+/// resp.assert_header(
+///     header::CONTENT_DISPOSITION,
+///     "attachment; filename=\"__.csv\"; filename*=UTF-8''%E6%8A%A5%E5%91%8A.csv",
+/// );
+/// resp.assert_text("a,b,c").await;
+/// # });
+/// ```
+pub struct Attachment<T> {
+    data: T,
+    disposition: ContentDisposition,
+    content_type: Option<Mime>,
+}
+
+impl<T: Into<Body> + Send> Attachment<T> {
+    /// Create an attachment with the data.
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            disposition: ContentDisposition::attachment(),
+            content_type: None,
+        }
+    }
+
+    /// Sets the filename, it will be sanitized with
+    /// [`sanitize_filename`](crate::web::sanitize_filename).
+    #[must_use]
+    pub fn filename(self, filename: impl AsRef<str>) -> Self {
+        Self {
+            disposition: self.disposition.filename(filename),
+            ..self
+        }
+    }
+
+    /// Sets the disposition type, default to [`DispositionType::Attachment`].
+    #[must_use]
+    pub fn disposition_type(self, ty: DispositionType) -> Self {
+        let mut disposition = ContentDisposition::new(ty);
+        if let Some(filename) = self.disposition.get_filename() {
+            disposition = disposition.filename(filename);
+        }
+        Self {
+            disposition,
+            ..self
+        }
+    }
+
+    /// Sets the content type, default to `application/octet-stream`.
+    #[must_use]
+    pub fn content_type(self, content_type: Mime) -> Self {
+        Self {
+            content_type: Some(content_type),
+            ..self
+        }
+    }
+}
+
+impl<T: Into<Body> + Send> IntoResponse for Attachment<T> {
+    fn into_response(self) -> Response {
+        let content_type = self
+            .content_type
+            .unwrap_or(mime::APPLICATION_OCTET_STREAM);
+        Response::builder()
+            .content_type(content_type.as_ref())
+            .header(
+                header::CONTENT_DISPOSITION,
+                self.disposition.to_header_value(),
+            )
+            .body(self.data.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{handler, test::TestClient};
+
+    #[tokio::test]
+    async fn attachment() {
+        #[handler(internal)]
+        fn index() -> Attachment<Vec<u8>> {
+            Attachment::new(vec![1, 2, 3])
+        }
+
+        let resp = TestClient::new(index).get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("application/octet-stream");
+        resp.assert_header(header::CONTENT_DISPOSITION, "attachment");
+        resp.assert_bytes(vec![1, 2, 3]).await;
+    }
+
+    #[tokio::test]
+    async fn inline() {
+        #[handler(internal)]
+        fn index() -> Attachment<&'static str> {
+            Attachment::new("hello")
+                .filename("../a\".txt")
+                .disposition_type(DispositionType::Inline)
+                .content_type(mime::TEXT_PLAIN_UTF_8)
+        }
+
+        let resp = TestClient::new(index).get("/").send().await;
+        resp.assert_content_type("text/plain; charset=utf-8");
+        resp.assert_header(header::CONTENT_DISPOSITION, "inline; filename=\"_a_.txt\"");
+        resp.assert_text("hello").await;
+    }
+}
@@ -0,0 +1,192 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    http::{header, StatusCode},
+    Error, IntoResponse, Response,
+};
+
+/// The result of an item in a [`BatchResult`].
+#[derive(Debug, Serialize)]
+struct BatchItem {
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// A response for batch endpoints that reports the result of each item.
+///
+/// The response status is always `207 Multi-Status`, and the body is a JSON
+/// object that contains the status and the body or the error message of each
+/// item, in the order they were added.
+///
+/// ```json
+/// {
+///   "items": [
+///     { "status": 201, "body": { "id": 1 } },
+///     { "status": 409, "error": "conflict" }
+///   ]
+/// }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::StatusCode,
+///     test::TestClient,
+///     web::{BatchResult, Json},
+///     Error, Result,
+/// };
+///
+/// fn create(name: &str) -> Result<String> {
+///     if name.is_empty() {
+///         return Err(Error::from_string("empty name", StatusCode::BAD_REQUEST));
+///     }
+///     Ok(name.to_string())
+/// }
+///
+/// #[handler]
+/// fn index(Json(names): Json<Vec<String>>) -> BatchResult<String> {
+///     names.iter().map(|name| create(name)).collect()
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.post("/").body_json(&["a", ""]).send().await;
+/// resp.assert_status(StatusCode::MULTI_STATUS);
+/// resp.assert_json(serde_json::json!({
+///     "items": [
+///         { "status": 200, "body": "a" },
+///         { "status": 400, "error": "empty name" },
+///     ]
+/// }))
+/// .await;
+/// # });
+/// ```
+pub struct BatchResult<T> {
+    items: Vec<(StatusCode, Result<T, Error>)>,
+}
+
+impl<T> Default for BatchResult<T> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<T> BatchResult<T> {
+    /// Create an empty `BatchResult`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the result of an item, a successful item has the status `200
+    /// OK`, a failed item has the status of the error.
+    pub fn push(&mut self, result: Result<T, Error>) {
+        self.items.push((StatusCode::OK, result));
+    }
+
+    /// Appends a successful item with the specified status.
+    pub fn push_ok(&mut self, status: StatusCode, value: T) {
+        self.items.push((status, Ok(value)));
+    }
+
+    /// Appends a failed item.
+    pub fn push_err(&mut self, err: impl Into<Error>) {
+        self.items.push((StatusCode::OK, Err(err.into())));
+    }
+
+    /// Returns the number of items.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if there are no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns `true` if all items are successful.
+    pub fn is_all_ok(&self) -> bool {
+        self.items.iter().all(|(_, res)| res.is_ok())
+    }
+}
+
+impl<T, E: Into<Error>> FromIterator<Result<T, E>> for BatchResult<T> {
+    fn from_iter<I: IntoIterator<Item = Result<T, E>>>(iter: I) -> Self {
+        let mut batch = BatchResult::new();
+        for result in iter {
+            batch.push(result.map_err(Into::into));
+        }
+        batch
+    }
+}
+
+impl<T: Serialize + Send> IntoResponse for BatchResult<T> {
+    fn into_response(self) -> Response {
+        let mut items = Vec::with_capacity(self.items.len());
+
+        for (status, result) in self.items {
+            let item = match result.map(|value| serde_json::to_value(&value)) {
+                Ok(Ok(body)) => BatchItem {
+                    status: status.as_u16(),
+                    body: Some(body),
+                    error: None,
+                },
+                Ok(Err(err)) => BatchItem {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    body: None,
+                    error: Some(err.to_string()),
+                },
+                Err(err) => BatchItem {
+                    status: err.status().as_u16(),
+                    body: None,
+                    error: Some(err.to_string()),
+                },
+            };
+            items.push(item);
+        }
+
+        let data = serde_json::to_vec(&serde_json::json!({ "items": items }))
+            .expect("valid json value");
+        Response::builder()
+            .status(StatusCode::MULTI_STATUS)
+            .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+            .body(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NotFoundError;
+
+    #[tokio::test]
+    async fn batch_result() {
+        let mut batch = BatchResult::new();
+        batch.push_ok(StatusCode::CREATED, 1);
+        batch.push(Ok(2));
+        batch.push_err(NotFoundError);
+        assert_eq!(batch.len(), 3);
+        assert!(!batch.is_all_ok());
+
+        let resp = batch.into_response();
+        assert_eq!(resp.status(), StatusCode::MULTI_STATUS);
+        assert_eq!(
+            serde_json::from_slice::<Value>(&resp.into_body().into_vec().await.unwrap()).unwrap(),
+            serde_json::json!({
+                "items": [
+                    { "status": 201, "body": 1 },
+                    { "status": 200, "body": 2 },
+                    { "status": 404, "error": "not found" },
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+use crate::{
+    http::{header, HeaderValue},
+    IntoResponse, Response,
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Visibility {
+    Public,
+    Private,
+}
+
+/// A builder of the `Cache-Control` response header.
+///
+/// The directives are written in a fixed order, and the durations are
+/// written in seconds. Use [`IntoResponse::with_cache_control`] to set it to
+/// a response.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{http::header, web::CacheControl, IntoResponse};
+///
+/// let resp = "hello"
+///     .with_cache_control(
+///         CacheControl::new()
+///             .public()
+///             .max_age(Duration::from_secs(60))
+///             .stale_while_revalidate(Duration::from_secs(600)),
+///     )
+///     .into_response();
+/// assert_eq!(
+///     resp.header(header::CACHE_CONTROL),
+///     Some("public, max-age=60, stale-while-revalidate=600")
+/// );
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CacheControl {
+    visibility: Option<Visibility>,
+    no_cache: bool,
+    no_store: bool,
+    no_transform: bool,
+    must_revalidate: bool,
+    proxy_revalidate: bool,
+    immutable: bool,
+    max_age: Option<Duration>,
+    s_maxage: Option<Duration>,
+    stale_while_revalidate: Option<Duration>,
+    stale_if_error: Option<Duration>,
+}
+
+impl CacheControl {
+    /// Create a `CacheControl` without directives.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the `public` directive, which allows the shared caches to store
+    /// the response. This overrides [`CacheControl::private`].
+    #[must_use]
+    pub fn public(self) -> Self {
+        Self {
+            visibility: Some(Visibility::Public),
+            ..self
+        }
+    }
+
+    /// Sets the `private` directive, which allows only the private cache of
+    /// the client to store the response. This overrides
+    /// [`CacheControl::public`].
+    #[must_use]
+    pub fn private(self) -> Self {
+        Self {
+            visibility: Some(Visibility::Private),
+            ..self
+        }
+    }
+
+    /// Sets the `no-cache` directive, which requires the caches to validate
+    /// the response with the server before using it.
+    #[must_use]
+    pub fn no_cache(self) -> Self {
+        Self {
+            no_cache: true,
+            ..self
+        }
+    }
+
+    /// Sets the `no-store` directive, which forbids the caches to store the
+    /// response.
+    #[must_use]
+    pub fn no_store(self) -> Self {
+        Self {
+            no_store: true,
+            ..self
+        }
+    }
+
+    /// Sets the `no-transform` directive.
+    #[must_use]
+    pub fn no_transform(self) -> Self {
+        Self {
+            no_transform: true,
+            ..self
+        }
+    }
+
+    /// Sets the `must-revalidate` directive, which forbids the caches to use
+    /// the response after it becomes stale without validating it.
+    #[must_use]
+    pub fn must_revalidate(self) -> Self {
+        Self {
+            must_revalidate: true,
+            ..self
+        }
+    }
+
+    /// Sets the `proxy-revalidate` directive, which is the same as
+    /// `must-revalidate` for the shared caches only.
+    #[must_use]
+    pub fn proxy_revalidate(self) -> Self {
+        Self {
+            proxy_revalidate: true,
+            ..self
+        }
+    }
+
+    /// Sets the `immutable` directive, which indicates that the response will
+    /// not change while it is fresh.
+    #[must_use]
+    pub fn immutable(self) -> Self {
+        Self {
+            immutable: true,
+            ..self
+        }
+    }
+
+    /// Sets the `max-age` directive.
+    #[must_use]
+    pub fn max_age(self, duration: Duration) -> Self {
+        Self {
+            max_age: Some(duration),
+            ..self
+        }
+    }
+
+    /// Sets the `s-maxage` directive, which overrides `max-age` for the
+    /// shared caches.
+    #[must_use]
+    pub fn s_maxage(self, duration: Duration) -> Self {
+        Self {
+            s_maxage: Some(duration),
+            ..self
+        }
+    }
+
+    /// Sets the `stale-while-revalidate` directive, which allows the caches
+    /// to use the stale response while revalidating it in the background.
+    #[must_use]
+    pub fn stale_while_revalidate(self, duration: Duration) -> Self {
+        Self {
+            stale_while_revalidate: Some(duration),
+            ..self
+        }
+    }
+
+    /// Sets the `stale-if-error` directive, which allows the caches to use
+    /// the stale response if the server responds with an error.
+    #[must_use]
+    pub fn stale_if_error(self, duration: Duration) -> Self {
+        Self {
+            stale_if_error: Some(duration),
+            ..self
+        }
+    }
+}
+
+impl Display for CacheControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let flags = [
+            (self.visibility == Some(Visibility::Public), "public"),
+            (self.visibility == Some(Visibility::Private), "private"),
+            (self.no_cache, "no-cache"),
+            (self.no_store, "no-store"),
+            (self.no_transform, "no-transform"),
+            (self.must_revalidate, "must-revalidate"),
+            (self.proxy_revalidate, "proxy-revalidate"),
+            (self.immutable, "immutable"),
+        ];
+        let durations = [
+            (self.max_age, "max-age"),
+            (self.s_maxage, "s-maxage"),
+            (self.stale_while_revalidate, "stale-while-revalidate"),
+            (self.stale_if_error, "stale-if-error"),
+        ];
+
+        let directives = flags
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, name)| name.to_string())
+            .chain(durations.into_iter().filter_map(|(duration, name)| {
+                duration.map(|duration| format!("{name}={}", duration.as_secs()))
+            }))
+            .collect::<Vec<_>>();
+        f.write_str(&directives.join(", "))
+    }
+}
+
+impl From<CacheControl> for HeaderValue {
+    fn from(cache_control: CacheControl) -> Self {
+        HeaderValue::try_from(cache_control.to_string()).expect("valid header value")
+    }
+}
+
+/// Returned by [`with_cache_control`](IntoResponse::with_cache_control)
+/// method.
+pub struct WithCacheControl<T> {
+    pub(crate) inner: T,
+    pub(crate) cache_control: CacheControl,
+}
+
+impl<T: IntoResponse> IntoResponse for WithCacheControl<T> {
+    fn into_response(self) -> Response {
+        let mut resp = self.inner.into_response();
+        resp.headers_mut()
+            .insert(header::CACHE_CONTROL, self.cache_control.into());
+        resp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directives() {
+        assert_eq!(CacheControl::new().to_string(), "");
+        assert_eq!(CacheControl::new().no_store().to_string(), "no-store");
+        assert_eq!(
+            CacheControl::new()
+                .public()
+                .private()
+                .must_revalidate()
+                .max_age(Duration::from_secs(60))
+                .s_maxage(Duration::from_millis(1500))
+                .stale_if_error(Duration::from_secs(86400))
+                .to_string(),
+            "private, must-revalidate, max-age=60, s-maxage=1, stale-if-error=86400"
+        );
+        assert_eq!(
+            CacheControl::new()
+                .public()
+                .immutable()
+                .max_age(Duration::from_secs(31536000))
+                .to_string(),
+            "public, immutable, max-age=31536000"
+        );
+    }
+
+    #[test]
+    fn with_cache_control() {
+        let resp = "hello"
+            .with_header(header::CACHE_CONTROL, "no-cache")
+            .with_cache_control(CacheControl::new().no_store())
+            .into_response();
+        assert_eq!(
+            resp.headers()
+                .get_all(header::CACHE_CONTROL)
+                .iter()
+                .collect::<Vec<_>>(),
+            ["no-store"]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::ops::Deref;
+
+use crate::{FromRequest, Request, RequestBody, Result};
+
+/// An extractor that memoizes the result of the inner extractor, so that
+/// extracting the same type multiple times in middlewares and handlers of a
+/// request only computes it once.
+///
+/// Only successful results are cached, if the inner extractor fails, it will
+/// be called again on the next extraction.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use poem::{
+///     handler, test::TestClient, web::Cached, Endpoint, EndpointExt, FromRequest, Request,
+///     RequestBody, Result,
+/// };
+///
+/// static QUERIES: AtomicUsize = AtomicUsize::new(0);
+///
+/// #[derive(Clone)]
+/// struct CurrentUser(String);
+///
+/// #[poem::async_trait]
+/// impl<'a> FromRequest<'a> for CurrentUser {
+///     async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+///         // query the database
+///         QUERIES.fetch_add(1, Ordering::SeqCst);
+///         Ok(CurrentUser("sunli".to_string()))
+///     }
+/// }
+///
+/// #[handler]
+/// fn index(Cached(user): Cached<CurrentUser>) -> String {
+///     user.0
+/// }
+///
+/// let app = index.around(|ep, req| async move {
+///     let Cached(user) = Cached::<CurrentUser>::from_request_without_body(&req).await?;
+///     assert_eq!(user.0, "sunli");
+///     ep.call(req).await
+/// });
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("sunli").await;
+/// assert_eq!(QUERIES.load(Ordering::SeqCst), 1);
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cached<T>(pub T);
+
+impl<T> Deref for Cached<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a, T> FromRequest<'a> for Cached<T>
+where
+    T: FromRequest<'a> + Clone + Send + Sync + 'static,
+{
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let cached = req.state().extractor_cache.lock().get::<T>().cloned();
+        if let Some(value) = cached {
+            return Ok(Cached(value));
+        }
+
+        let value = T::from_request(req, body).await?;
+        req.state().extractor_cache.lock().insert(value.clone());
+        Ok(Cached(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{error::NotFoundError, Error};
+
+    #[tokio::test]
+    async fn cached() {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Clone)]
+        struct Counter(usize);
+
+        #[async_trait::async_trait]
+        impl<'a> FromRequest<'a> for Counter {
+            async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+                let n = COUNT.fetch_add(1, Ordering::SeqCst);
+                if req.header("fail").is_some() && n == 0 {
+                    return Err(Error::from(NotFoundError));
+                }
+                Ok(Counter(n))
+            }
+        }
+
+        async fn count(req: &Request) -> Result<usize> {
+            let Cached(Counter(n)) = Cached::<Counter>::from_request_without_body(req).await?;
+            Ok(n)
+        }
+
+        let req = Request::builder().header("fail", "1").finish();
+        assert!(count(&req).await.is_err());
+        assert_eq!(count(&req).await.unwrap(), 1);
+        assert_eq!(count(&req).await.unwrap(), 1);
+        assert_eq!(count(&Request::default()).await.unwrap(), 2);
+    }
+}
@@ -0,0 +1,194 @@
+use std::{net::IpAddr, sync::Arc};
+
+use x509_parser::{
+    extensions::GeneralName,
+    prelude::{FromDer, X509Certificate},
+};
+
+use crate::{error::ClientCertRequiredError, FromRequest, Request, RequestBody, Result};
+
+/// A subject alternative name of a certificate.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SubjectAltName {
+    /// A DNS name.
+    Dns(String),
+    /// An email address.
+    Email(String),
+    /// An URI.
+    Uri(String),
+    /// An IP address.
+    Ip(IpAddr),
+}
+
+/// An extractor for the verified certificate chain of the client, when client
+/// authentication is enabled with
+/// [`RustlsConfig::client_auth_required`](crate::listener::RustlsConfig::client_auth_required)
+/// or
+/// [`RustlsConfig::client_auth_optional`](crate::listener::RustlsConfig::client_auth_optional).
+///
+/// Use `Option<ClientCert>` if the client certificate is optional.
+///
+/// # Errors
+///
+/// - [`ClientCertRequiredError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, http::StatusCode, web::ClientCert, Error, Result};
+///
+/// #[handler]
+/// fn index(cert: ClientCert) -> Result<String> {
+///     let name = cert
+///         .common_name()
+///         .ok_or_else(|| Error::from_status(StatusCode::FORBIDDEN))?;
+///     Ok(format!("hello, {name}"))
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+#[derive(Debug, Clone)]
+pub struct ClientCert {
+    chain: Arc<[Vec<u8>]>,
+}
+
+impl ClientCert {
+    pub(crate) fn new(chain: Vec<Vec<u8>>) -> Option<Self> {
+        if chain.is_empty() {
+            return None;
+        }
+        Some(Self {
+            chain: chain.into(),
+        })
+    }
+
+    /// Returns the DER-encoded end-entity certificate.
+    #[inline]
+    pub fn der(&self) -> &[u8] {
+        &self.chain[0]
+    }
+
+    /// Returns the DER-encoded certificate chain, starting with the
+    /// end-entity certificate.
+    #[inline]
+    pub fn chain(&self) -> &[Vec<u8>] {
+        &self.chain
+    }
+
+    fn with_certificate<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&X509Certificate) -> Option<R>,
+    {
+        let (_, cert) = X509Certificate::from_der(self.der()).ok()?;
+        f(&cert)
+    }
+
+    /// Returns the subject of the end-entity certificate, such as
+    /// `CN=client, O=example`.
+    pub fn subject(&self) -> Option<String> {
+        self.with_certificate(|cert| Some(cert.subject().to_string()))
+    }
+
+    /// Returns the first common name in the subject of the end-entity
+    /// certificate.
+    pub fn common_name(&self) -> Option<String> {
+        self.with_certificate(|cert| {
+            cert.subject()
+                .iter_common_name()
+                .next()
+                .and_then(|cn| cn.as_str().ok())
+                .map(ToString::to_string)
+        })
+    }
+
+    /// Returns the subject alternative names of the end-entity certificate.
+    pub fn subject_alt_names(&self) -> Vec<SubjectAltName> {
+        self.with_certificate(|cert| {
+            let ext = cert.subject_alternative_name().ok()??;
+            Some(
+                ext.value
+                    .general_names
+                    .iter()
+                    .filter_map(|name| match name {
+                        GeneralName::DNSName(name) => Some(SubjectAltName::Dns(name.to_string())),
+                        GeneralName::RFC822Name(name) => {
+                            Some(SubjectAltName::Email(name.to_string()))
+                        }
+                        GeneralName::URI(uri) => Some(SubjectAltName::Uri(uri.to_string())),
+                        GeneralName::IPAddress(ip) => match ip.len() {
+                            4 => Some(SubjectAltName::Ip(IpAddr::from(
+                                <[u8; 4]>::try_from(*ip).ok()?,
+                            ))),
+                            16 => Some(SubjectAltName::Ip(IpAddr::from(
+                                <[u8; 16]>::try_from(*ip).ok()?,
+                            ))),
+                            _ => None,
+                        },
+                        _ => None,
+                    })
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for ClientCert {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req
+            .connect_info()
+            .get::<ClientCert>()
+            .ok_or(ClientCertRequiredError)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+
+    fn client_cert() -> ClientCert {
+        let chain = rustls_pemfile::certs(
+            &mut include_bytes!("../listener/certs/cert1.pem").as_ref(),
+        )
+        .map(|cert| cert.unwrap().to_vec())
+        .collect();
+        ClientCert::new(chain).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let cert = client_cert();
+        assert_eq!(cert.subject().as_deref(), Some("CN=testserver.com"));
+        assert_eq!(cert.common_name().as_deref(), Some("testserver.com"));
+        assert_eq!(
+            cert.subject_alt_names(),
+            vec![
+                SubjectAltName::Dns("testserver.com".to_string()),
+                SubjectAltName::Dns("second.testserver.com".to_string()),
+                SubjectAltName::Dns("localhost".to_string()),
+            ]
+        );
+        assert!(ClientCert::new(vec![]).is_none());
+    }
+
+    #[tokio::test]
+    async fn extractor() {
+        let req = Request::default();
+        assert_eq!(
+            ClientCert::from_request_without_body(&req)
+                .await
+                .unwrap_err()
+                .status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert!(Option::<ClientCert>::from_request_without_body(&req)
+            .await
+            .unwrap()
+            .is_none());
+
+        req.connect_info().insert(client_cert());
+        let cert = ClientCert::from_request_without_body(&req).await.unwrap();
+        assert_eq!(cert.chain().len(), 1);
+    }
+}
@@ -1,10 +1,17 @@
 use std::{
     fmt::{self, Display, Formatter},
+    io::Result as IoResult,
     pin::Pin,
     str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
 };
 
-use tokio::io::{AsyncRead, BufReader};
+use tokio::io::{AsyncRead, BufReader, ReadBuf};
 
 use crate::{
     http::{header, HeaderValue},
@@ -13,8 +20,12 @@ use crate::{
 };
 
 /// The compression algorithms.
+///
+/// The set of variants depends on the enabled features, so matching on this
+/// enum must include a wildcard arm.
 #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
 pub enum CompressionAlgo {
     /// brotli
     BR,
@@ -22,6 +33,10 @@ pub enum CompressionAlgo {
     DEFLATE,
     /// gzip
     GZIP,
+    /// zstd
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    ZSTD,
 }
 
 impl FromStr for CompressionAlgo {
@@ -32,6 +47,8 @@ impl FromStr for CompressionAlgo {
             "br" => CompressionAlgo::BR,
             "deflate" => CompressionAlgo::DEFLATE,
             "gzip" => CompressionAlgo::GZIP,
+            #[cfg(feature = "zstd")]
+            "zstd" => CompressionAlgo::ZSTD,
             _ => return Err(()),
         })
     }
@@ -44,6 +61,8 @@ impl CompressionAlgo {
             CompressionAlgo::BR => "br",
             CompressionAlgo::DEFLATE => "deflate",
             CompressionAlgo::GZIP => "gzip",
+            #[cfg(feature = "zstd")]
+            CompressionAlgo::ZSTD => "zstd",
         }
     }
 
@@ -71,6 +90,13 @@ impl CompressionAlgo {
                     level.unwrap_or(CompressionLevel::Default),
                 ),
             ),
+            #[cfg(feature = "zstd")]
+            CompressionAlgo::ZSTD => Box::pin(
+                async_compression::tokio::bufread::ZstdEncoder::with_quality(
+                    BufReader::new(reader),
+                    level.unwrap_or(CompressionLevel::Default),
+                ),
+            ),
         }
     }
 
@@ -88,6 +114,10 @@ impl CompressionAlgo {
             CompressionAlgo::GZIP => Box::pin(async_compression::tokio::bufread::GzipDecoder::new(
                 BufReader::new(reader),
             )),
+            #[cfg(feature = "zstd")]
+            CompressionAlgo::ZSTD => Box::pin(async_compression::tokio::bufread::ZstdDecoder::new(
+                BufReader::new(reader),
+            )),
         }
     }
 }
@@ -98,6 +128,121 @@ impl Display for CompressionAlgo {
     }
 }
 
+/// The statistics of compressing a response body, which are reported after
+/// the whole body has been compressed.
+///
+/// See [`Compression::on_stats`](crate::middleware::Compression::on_stats).
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CompressionStats {
+    /// The compression algorithm.
+    pub algo: CompressionAlgo,
+    /// The size of the uncompressed body.
+    pub input_bytes: u64,
+    /// The size of the compressed body.
+    pub output_bytes: u64,
+    /// The time spent compressing, excluding the time spent producing the
+    /// uncompressed body.
+    pub duration: Duration,
+}
+
+impl CompressionStats {
+    /// Returns the compression ratio, which is the size of the uncompressed
+    /// body divided by the size of the compressed body.
+    pub fn ratio(&self) -> f64 {
+        if self.output_bytes == 0 {
+            return 0.0;
+        }
+        self.input_bytes as f64 / self.output_bytes as f64
+    }
+}
+
+pub(crate) type StatsHandler = Arc<dyn Fn(&CompressionStats) + Send + Sync>;
+
+#[derive(Default)]
+struct InputCounter {
+    bytes: AtomicU64,
+    nanos: AtomicU64,
+}
+
+pin_project_lite::pin_project! {
+    /// Counts the bytes read from the uncompressed body and the time spent
+    /// reading it.
+    struct CountingReader<R> {
+        #[pin]
+        inner: R,
+        counter: Arc<InputCounter>,
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for CountingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let this = self.project();
+        let start = Instant::now();
+        let filled = buf.filled().len();
+        let res = this.inner.poll_read(cx, buf);
+        this.counter
+            .nanos
+            .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        this.counter
+            .bytes
+            .fetch_add((buf.filled().len() - filled) as u64, Ordering::Relaxed);
+        res
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Measures the compressed body and reports the statistics at the end of
+    /// the body.
+    struct MeasuredReader<R> {
+        #[pin]
+        inner: R,
+        algo: CompressionAlgo,
+        counter: Arc<InputCounter>,
+        output_bytes: u64,
+        elapsed: Duration,
+        handler: Option<StatsHandler>,
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for MeasuredReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let this = self.project();
+        let start = Instant::now();
+        let filled = buf.filled().len();
+        let has_capacity = buf.remaining() > 0;
+        let res = this.inner.poll_read(cx, buf);
+        *this.elapsed += start.elapsed();
+
+        let n = buf.filled().len() - filled;
+        *this.output_bytes += n as u64;
+
+        if matches!(res, Poll::Ready(Ok(()))) && n == 0 && has_capacity {
+            if let Some(handler) = this.handler.take() {
+                let input_nanos = this.counter.nanos.load(Ordering::Relaxed);
+                handler(&CompressionStats {
+                    algo: *this.algo,
+                    input_bytes: this.counter.bytes.load(Ordering::Relaxed),
+                    output_bytes: *this.output_bytes,
+                    duration: this
+                        .elapsed
+                        .saturating_sub(Duration::from_nanos(input_nanos)),
+                });
+            }
+        }
+
+        res
+    }
+}
+
 /// Compress the response body with the specified algorithm and set the
 /// `Content-Encoding` header.
 ///
@@ -119,6 +264,7 @@ pub struct Compress<T> {
     inner: T,
     algo: CompressionAlgo,
     level: Option<CompressionLevel>,
+    stats_handler: Option<StatsHandler>,
 }
 
 impl<T> Compress<T> {
@@ -128,6 +274,7 @@ impl<T> Compress<T> {
             inner,
             algo,
             level: None,
+            stats_handler: None,
         }
     }
 
@@ -140,6 +287,14 @@ impl<T> Compress<T> {
             ..self
         }
     }
+
+    #[must_use]
+    pub(crate) fn with_stats_handler(self, stats_handler: Option<StatsHandler>) -> Self {
+        Self {
+            stats_handler,
+            ..self
+        }
+    }
 }
 
 impl<T: IntoResponse> IntoResponse for Compress<T> {
@@ -153,9 +308,28 @@ impl<T: IntoResponse> IntoResponse for Compress<T> {
         );
         resp.headers_mut().remove(header::CONTENT_LENGTH);
 
-        resp.set_body(Body::from_async_read(
-            self.algo.compress(body.into_async_read(), self.level),
-        ));
+        match self.stats_handler {
+            Some(handler) => {
+                let counter = Arc::new(InputCounter::default());
+                let reader = CountingReader {
+                    inner: body.into_async_read(),
+                    counter: counter.clone(),
+                };
+                resp.set_body(Body::from_async_read(MeasuredReader {
+                    inner: self.algo.compress(reader, self.level),
+                    algo: self.algo,
+                    counter,
+                    output_bytes: 0,
+                    elapsed: Duration::ZERO,
+                    handler: Some(handler),
+                }));
+            }
+            None => {
+                resp.set_body(Body::from_async_read(
+                    self.algo.compress(body.into_async_read(), self.level),
+                ));
+            }
+        }
         resp
     }
 }
@@ -203,5 +377,7 @@ mod tests {
         test_algo(CompressionAlgo::BR).await;
         test_algo(CompressionAlgo::DEFLATE).await;
         test_algo(CompressionAlgo::GZIP).await;
+        #[cfg(feature = "zstd")]
+        test_algo(CompressionAlgo::ZSTD).await;
     }
 }
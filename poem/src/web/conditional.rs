@@ -0,0 +1,354 @@
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use futures_util::{stream, FutureExt, StreamExt};
+use http_body_util::{BodyExt, BodyStream, StreamBody};
+use hyper::body::Frame;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    body::BoxBody,
+    error::PreconditionError,
+    http::{header, StatusCode},
+    web::{
+        headers::{ETag, HeaderMapExt, LastModified},
+        Preconditions,
+    },
+    Body, IntoResponse, Response,
+};
+
+/// The headers that are kept in a `304 Not Modified` response, see
+/// [RFC 7232](https://datatracker.ietf.org/doc/html/rfc7232#section-4.1).
+const NOT_MODIFIED_HEADERS: &[header::HeaderName] = &[
+    header::CACHE_CONTROL,
+    header::CONTENT_LOCATION,
+    header::DATE,
+    header::ETAG,
+    header::EXPIRES,
+    header::LAST_MODIFIED,
+    header::VARY,
+];
+
+/// A response wrapper that sets the `ETag` header and evaluates the
+/// [`Preconditions`] of the request, converting the response to
+/// `304 Not Modified` if the client already has the current representation.
+///
+/// The `ETag` is taken from [`Conditional::etag`], or the `ETag` header of the
+/// inner response, or computed by hashing the body. Only the bodies that are
+/// buffered in memory, such as strings, bytes and JSON, are hashed, streaming
+/// bodies are sent without an `ETag`. Only successful responses are affected.
+///
+/// This is intended for the `GET` and `HEAD` requests, for the requests that
+/// modify the resource, use [`Preconditions::evaluate`] before modifying it.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::{header, StatusCode},
+///     test::TestClient,
+///     web::{Conditional, Preconditions},
+/// };
+///
+/// #[handler]
+/// fn index(preconditions: Preconditions) -> Conditional<&'static str> {
+///     preconditions.conditional("hello")
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// let etag = resp.0.header(header::ETAG).unwrap().to_string();
+///
+/// let resp = cli
+///     .get("/")
+///     .header(header::IF_NONE_MATCH, etag)
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::NOT_MODIFIED);
+/// resp.assert_text("").await;
+/// # });
+/// ```
+pub struct Conditional<T> {
+    preconditions: Preconditions,
+    inner: T,
+    etag: Option<ETag>,
+    last_modified: Option<SystemTime>,
+}
+
+impl<T> Conditional<T> {
+    /// Create a `Conditional` response with the preconditions of the request.
+    pub fn new(preconditions: Preconditions, inner: T) -> Self {
+        Self {
+            preconditions,
+            inner,
+            etag: None,
+            last_modified: None,
+        }
+    }
+
+    /// Sets the `ETag` of the response, instead of hashing the body.
+    #[must_use]
+    pub fn etag(self, etag: ETag) -> Self {
+        Self {
+            etag: Some(etag),
+            ..self
+        }
+    }
+
+    /// Sets the modification time, which is sent in the `Last-Modified`
+    /// header and compared with `If-Modified-Since`.
+    #[must_use]
+    pub fn last_modified(self, last_modified: SystemTime) -> Self {
+        Self {
+            last_modified: Some(last_modified),
+            ..self
+        }
+    }
+}
+
+impl Preconditions {
+    /// Create a [`Conditional`] response with these preconditions.
+    pub fn conditional<T>(self, inner: T) -> Conditional<T> {
+        Conditional::new(self, inner)
+    }
+}
+
+/// Computes the `ETag` from the first 128 bits of the SHA-256 digest of the
+/// data, which is stable across processes and versions.
+fn hash_etag(data: &[u8]) -> ETag {
+    let digest = Sha256::digest(data);
+    let hex = digest[..16]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+    format!("\"{hex}\"").parse().expect("valid etag")
+}
+
+/// Returns the data of the body if all of it is available without waiting,
+/// otherwise returns a body with the same frames, including the trailers and
+/// errors.
+fn buffered_data(body: Body) -> Result<Bytes, Body> {
+    let mut body: BoxBody = body.into();
+    let mut chunks = Vec::new();
+    let mut tail = None;
+
+    loop {
+        match body.frame().now_or_never() {
+            Some(Some(Ok(frame))) => match frame.into_data() {
+                Ok(data) => chunks.push(data),
+                Err(frame) => {
+                    tail = Some(Ok(frame));
+                    break;
+                }
+            },
+            Some(Some(Err(err))) => {
+                tail = Some(Err(err));
+                break;
+            }
+            Some(None) => return Ok(chunks.concat().into()),
+            None => break,
+        }
+    }
+
+    let head = stream::iter(
+        chunks
+            .into_iter()
+            .map(|data| Ok(Frame::data(data)))
+            .chain(tail),
+    );
+    Err(BoxBody::new(StreamBody::new(head.chain(BodyStream::new(body)))).into())
+}
+
+impl<T: IntoResponse> IntoResponse for Conditional<T> {
+    fn into_response(self) -> Response {
+        let mut resp = self.inner.into_response();
+        if !resp.status().is_success() {
+            return resp;
+        }
+
+        let etag = match self.etag {
+            Some(etag) => Some(etag),
+            None => match resp.headers().typed_get::<ETag>() {
+                Some(etag) => Some(etag),
+                None => match buffered_data(resp.take_body()) {
+                    Ok(data) => {
+                        let etag = hash_etag(&data);
+                        resp.set_body(data);
+                        Some(etag)
+                    }
+                    Err(body) => {
+                        resp.set_body(body);
+                        None
+                    }
+                },
+            },
+        };
+        if let Some(etag) = &etag {
+            resp.headers_mut().typed_insert(etag.clone());
+        }
+        if let Some(last_modified) = self.last_modified {
+            resp.headers_mut()
+                .typed_insert(LastModified::from(last_modified));
+        }
+
+        match self
+            .preconditions
+            .evaluate(etag.as_ref(), self.last_modified)
+        {
+            Ok(()) => resp,
+            Err(PreconditionError::NotModified) => {
+                let mut not_modified = Response::builder()
+                    .status(StatusCode::NOT_MODIFIED)
+                    .finish();
+                for name in NOT_MODIFIED_HEADERS {
+                    for value in resp.headers().get_all(name) {
+                        not_modified
+                            .headers_mut()
+                            .append(name.clone(), value.clone());
+                    }
+                }
+                not_modified
+            }
+            Err(err) => crate::Error::from(err).into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{handler, test::TestClient, web::headers::IfModifiedSince, FromRequest, Request};
+
+    #[tokio::test]
+    async fn hash_body() {
+        #[handler(internal)]
+        fn index(preconditions: Preconditions) -> Conditional<Response> {
+            preconditions.conditional(
+                Response::builder()
+                    .header(header::CACHE_CONTROL, "max-age=60")
+                    .header("x-custom", "1")
+                    .body("hello"),
+            )
+        }
+
+        let cli = TestClient::new(index);
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        assert_eq!(
+            resp.0.headers().typed_get::<ETag>(),
+            Some(hash_etag(b"hello"))
+        );
+        let etag = resp.0.header(header::ETAG).unwrap().to_string();
+        resp.assert_text("hello").await;
+
+        let resp = cli
+            .get("/")
+            .header(header::IF_NONE_MATCH, &etag)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NOT_MODIFIED);
+        resp.assert_header(header::ETAG, etag);
+        resp.assert_header(header::CACHE_CONTROL, "max-age=60");
+        resp.assert_header_is_not_exist("x-custom");
+
+        cli.get("/")
+            .header(header::IF_NONE_MATCH, "\"other\"")
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+
+    #[tokio::test]
+    async fn last_modified() {
+        let modified = SystemTime::now() - Duration::from_secs(3600);
+        let req = Request::builder()
+            .typed_header(IfModifiedSince::from(SystemTime::now()))
+            .finish();
+        let (req, mut body) = req.split();
+        let preconditions = Preconditions::from_request(&req, &mut body).await.unwrap();
+
+        let resp = preconditions
+            .conditional("hello")
+            .etag("\"v1\"".parse().unwrap())
+            .last_modified(modified)
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(resp.header(header::ETAG), Some("\"v1\""));
+        assert!(resp.headers().contains_key(header::LAST_MODIFIED));
+    }
+
+    #[tokio::test]
+    async fn streaming_body() {
+        let body = Body::from_bytes_stream(stream::once(async {
+            tokio::task::yield_now().await;
+            Ok::<_, std::io::Error>(Bytes::from_static(b"hello"))
+        }));
+        let resp = Conditional::new(
+            Preconditions::from_request_without_body(&Request::default())
+                .await
+                .unwrap(),
+            body,
+        )
+        .into_response();
+        assert!(resp.headers().get(header::ETAG).is_none());
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn trailers_and_errors() {
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("x-checksum", "1".parse().unwrap());
+        let frames = vec![
+            Ok(Frame::data(Bytes::from_static(b"hello"))),
+            Ok(Frame::trailers(trailers)),
+        ];
+        let body = BoxBody::new(StreamBody::new(stream::iter(frames)));
+        let resp = Conditional::new(
+            Preconditions::from_request_without_body(&Request::default())
+                .await
+                .unwrap(),
+            Body::from(body),
+        )
+        .into_response();
+        assert!(resp.headers().get(header::ETAG).is_none());
+        let collected = BoxBody::from(resp.into_body()).collect().await.unwrap();
+        assert_eq!(
+            collected.trailers().unwrap().get("x-checksum").unwrap(),
+            "1"
+        );
+        assert_eq!(collected.to_bytes(), "hello");
+
+        let body = Body::from_bytes_stream(stream::iter(vec![
+            Ok(Bytes::from_static(b"hello")),
+            Err(std::io::Error::other("broken")),
+        ]));
+        let resp = Conditional::new(
+            Preconditions::from_request_without_body(&Request::default())
+                .await
+                .unwrap(),
+            body,
+        )
+        .into_response();
+        assert!(resp.headers().get(header::ETAG).is_none());
+        assert!(resp.into_body().into_bytes().await.is_err());
+    }
+
+    #[test]
+    fn error_response() {
+        let preconditions = Preconditions::from_request_without_body(&Request::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        let resp = preconditions
+            .conditional(StatusCode::NOT_FOUND)
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert!(resp.headers().get(header::ETAG).is_none());
+    }
+}
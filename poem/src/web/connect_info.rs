@@ -0,0 +1,123 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+use crate::{error::GetDataError, http::Extensions, FromRequest, Request, RequestBody, Result};
+
+/// The per-connection metadata attached by the
+/// [`Acceptor`](crate::listener::Acceptor), shared by all requests of the
+/// connection.
+///
+/// The server always attaches the [`LocalAddr`](crate::web::LocalAddr) and
+/// [`RemoteAddr`](crate::web::RemoteAddr) of the connection, and the built-in
+/// acceptors attach the following metadata:
+///
+/// - `UnixAcceptor` attaches the peer credentials as
+///   [`tokio::net::unix::UCred`].
+/// - The TLS acceptors attach [`TlsInfo`](crate::listener::TlsInfo) once the
+///   handshake is completed.
+#[derive(Clone, Default)]
+pub struct ConnectInfoMap(Arc<Mutex<Extensions>>);
+
+impl Debug for ConnectInfoMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectInfoMap").finish()
+    }
+}
+
+impl ConnectInfoMap {
+    /// Inserts a value, replacing the existing value of the same type.
+    pub fn insert<T: Clone + Send + Sync + 'static>(&self, value: T) {
+        self.0.lock().insert(value);
+    }
+
+    /// Returns a clone of the value of the specified type.
+    pub fn get<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.0.lock().get::<T>().cloned()
+    }
+}
+
+/// An extractor that can extract the per-connection metadata attached by the
+/// [`Acceptor`](crate::listener::Acceptor).
+///
+/// See also [`ConnectInfoMap`].
+///
+/// # Errors
+///
+/// - [`GetDataError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     web::{ConnectInfo, RemoteAddr},
+///     Endpoint, Request,
+/// };
+///
+/// #[handler]
+/// fn index(ConnectInfo(remote_addr): ConnectInfo<RemoteAddr>) -> String {
+///     remote_addr.to_string()
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let req = Request::default();
+/// let addr = "127.0.0.1:3000".parse::<std::net::SocketAddr>().unwrap();
+/// req.connect_info().insert(RemoteAddr(addr.into()));
+/// let resp = index.call(req).await.unwrap();
+/// assert_eq!(
+///     resp.into_body().into_string().await.unwrap(),
+///     "socket://127.0.0.1:3000"
+/// );
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectInfo<T>(pub T);
+
+impl<T> Deref for ConnectInfo<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a, T: Clone + Send + Sync + 'static> FromRequest<'a> for ConnectInfo<T> {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(ConnectInfo(
+            req.connect_info()
+                .get::<T>()
+                .ok_or_else(|| GetDataError(std::any::type_name::<T>()))?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn connect_info() {
+        let req = Request::default();
+        req.connect_info().insert(10i32);
+
+        let ConnectInfo(value) = ConnectInfo::<i32>::from_request_without_body(&req)
+            .await
+            .unwrap();
+        assert_eq!(value, 10);
+        assert_eq!(
+            ConnectInfo::<String>::from_request_without_body(&req)
+                .await
+                .unwrap_err()
+                .status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt::Write;
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::http::HeaderValue;
+
+/// Characters that must be percent-encoded in the `ext-value` of RFC 5987,
+/// everything except `attr-char`.
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+const MAX_FILENAME_LEN: usize = 255;
+
+/// Sanitizes a user-supplied filename so that it is safe to be used for a
+/// downloaded file.
+///
+/// Path separators, control characters and characters that are reserved on
+/// common filesystems are replaced with `_`, the bidirectional formatting
+/// characters that can disguise the extension (such as `U+202E RIGHT-TO-LEFT
+/// OVERRIDE`) are removed along with leading dots and surrounding whitespace,
+/// and the result is truncated to 255 bytes. If nothing is left, `download` is
+/// returned.
+///
+/// # Example
+///
+/// ```
+/// use poem::web::sanitize_filename;
+///
+/// assert_eq!(sanitize_filename("../../etc/passwd"), "_.._etc_passwd");
+/// assert_eq!(sanitize_filename("report\r\n.pdf"), "report__.pdf");
+/// assert_eq!(sanitize_filename("résumé.pdf"), "résumé.pdf");
+/// assert_eq!(sanitize_filename("..."), "download");
+/// ```
+pub fn sanitize_filename(filename: &str) -> String {
+    let sanitized = filename
+        .chars()
+        .filter(|ch| !is_bidi_format(*ch))
+        .map(|ch| match ch {
+            '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+            ch if ch.is_control() => '_',
+            ch => ch,
+        })
+        .collect::<String>();
+    let sanitized = sanitized.trim().trim_start_matches('.').trim();
+
+    let mut res = String::new();
+    for ch in sanitized.chars() {
+        if res.len() + ch.len_utf8() > MAX_FILENAME_LEN {
+            break;
+        }
+        res.push(ch);
+    }
+
+    if res.is_empty() {
+        "download".to_string()
+    } else {
+        res
+    }
+}
+
+/// Returns `true` for the bidirectional formatting characters of Unicode.
+fn is_bidi_format(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// The disposition type of the `Content-Disposition` header.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DispositionType {
+    /// Indicate it can be displayed inside the Web page, or as the Web page
+    Inline,
+    /// Indicate it should be downloaded; most browsers presenting a 'Save as'
+    /// dialog
+    Attachment,
+}
+
+impl DispositionType {
+    #[inline]
+    fn as_str(&self) -> &'static str {
+        match self {
+            DispositionType::Inline => "inline",
+            DispositionType::Attachment => "attachment",
+        }
+    }
+}
+
+/// A builder for the value of the `Content-Disposition` response header.
+///
+/// The filename is sanitized with [`sanitize_filename`], and if it contains
+/// non-ASCII characters, it is encoded as described in
+/// [RFC 5987](https://datatracker.ietf.org/doc/html/rfc5987) with an ASCII
+/// fallback for older clients.
+///
+/// # Example
+///
+/// ```
+/// use poem::web::ContentDisposition;
+///
+/// let value = ContentDisposition::attachment()
+///     .filename("résumé.pdf")
+///     .to_header_value();
+/// assert_eq!(
+///     value,
+///     "attachment; filename=\"r_sum_.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf"
+/// );
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ContentDisposition {
+    ty: DispositionType,
+    filename: Option<String>,
+}
+
+impl ContentDisposition {
+    /// Create a `Content-Disposition` with the specified disposition type.
+    pub fn new(ty: DispositionType) -> Self {
+        Self { ty, filename: None }
+    }
+
+    /// Create an `inline` `Content-Disposition`.
+    pub fn inline() -> Self {
+        Self::new(DispositionType::Inline)
+    }
+
+    /// Create an `attachment` `Content-Disposition`.
+    pub fn attachment() -> Self {
+        Self::new(DispositionType::Attachment)
+    }
+
+    /// Sets the filename, it will be sanitized with [`sanitize_filename`].
+    #[must_use]
+    pub fn filename(self, filename: impl AsRef<str>) -> Self {
+        Self {
+            filename: Some(sanitize_filename(filename.as_ref())),
+            ..self
+        }
+    }
+
+    /// Returns the disposition type.
+    #[inline]
+    pub fn disposition_type(&self) -> DispositionType {
+        self.ty
+    }
+
+    /// Returns the sanitized filename.
+    #[inline]
+    pub fn get_filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Converts to a header value.
+    pub fn to_header_value(&self) -> HeaderValue {
+        let mut value = self.ty.as_str().to_string();
+
+        if let Some(filename) = &self.filename {
+            let fallback = filename
+                .chars()
+                .map(|ch| if ch.is_ascii() { ch } else { '_' })
+                .collect::<String>();
+            _ = write!(value, "; filename=\"{fallback}\"");
+
+            if !filename.is_ascii() {
+                _ = write!(
+                    value,
+                    "; filename*=UTF-8''{}",
+                    utf8_percent_encode(filename, ATTR_CHAR)
+                );
+            }
+        }
+
+        HeaderValue::try_from(value).expect("valid header value")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize() {
+        assert_eq!(sanitize_filename("a.txt"), "a.txt");
+        assert_eq!(sanitize_filename("  .hidden "), "hidden");
+        assert_eq!(sanitize_filename("a\"b.txt"), "a_b.txt");
+        assert_eq!(sanitize_filename("C:\\Windows\\a.exe"), "C__Windows_a.exe");
+        assert_eq!(sanitize_filename(""), "download");
+        assert_eq!(
+            sanitize_filename("invoice\u{202E}fdp.exe"),
+            "invoicefdp.exe"
+        );
+        assert_eq!(
+            sanitize_filename("\u{2067}a\u{2069}\u{200F}.txt\u{061C}"),
+            "a.txt"
+        );
+        assert_eq!(sanitize_filename("\u{202E}\u{202C}"), "download");
+        assert_eq!(sanitize_filename(&"你".repeat(100)).len(), 255);
+    }
+
+    #[test]
+    fn header_value() {
+        assert_eq!(ContentDisposition::inline().to_header_value(), "inline");
+        assert_eq!(
+            ContentDisposition::attachment()
+                .filename("a b.txt")
+                .to_header_value(),
+            "attachment; filename=\"a b.txt\""
+        );
+        assert_eq!(
+            ContentDisposition::attachment()
+                .filename("a\r\nSet-Cookie: a=b")
+                .to_header_value(),
+            "attachment; filename=\"a__Set-Cookie_ a=b\""
+        );
+        assert_eq!(
+            ContentDisposition::attachment()
+                .filename("你好.txt")
+                .to_header_value(),
+            "attachment; filename=\"__.txt\"; filename*=UTF-8''%E4%BD%A0%E5%A5%BD.txt"
+        );
+    }
+}
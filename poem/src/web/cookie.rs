@@ -14,8 +14,9 @@ use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    error::ParseCookieError,
+    error::{InvalidHeaderValueError, ParseCookieError},
     http::{header, HeaderMap},
+    web::checked_header_value,
     FromRequest, Request, RequestBody, Result,
 };
 
@@ -289,6 +290,28 @@ impl Cookie {
     pub fn value<'de, T: Deserialize<'de>>(&'de self) -> Result<T, ParseCookieError> {
         serde_json::from_str(self.0.value()).map_err(ParseCookieError::ParseJsonValue)
     }
+
+    /// Converts `self` to a `Set-Cookie` header value.
+    ///
+    /// The name and value are percent-encoded, but the other attributes such
+    /// as `Path` and `Domain` are not, so an error is returned if they contain
+    /// a line break or any other character that is not allowed in a header
+    /// value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use poem::web::cookie::Cookie;
+    ///
+    /// let mut cookie = Cookie::new_with_str("foo", "bar");
+    /// assert!(cookie.to_header_value().is_ok());
+    ///
+    /// cookie.set_path("/\r\nSet-Cookie: a=b");
+    /// assert!(cookie.to_header_value().is_err());
+    /// ```
+    pub fn to_header_value(&self) -> Result<HeaderValue, InvalidHeaderValueError> {
+        checked_header_value(header::SET_COOKIE.as_str(), &self.to_string())
+    }
 }
 
 #[async_trait::async_trait]
@@ -532,8 +555,13 @@ impl CookieJar {
         let cookie = self.jar.lock();
         for cookie in cookie.delta() {
             let value = cookie.encoded().to_string();
-            if let Ok(value) = HeaderValue::from_str(&value) {
-                headers.append(header::SET_COOKIE, value);
+            match checked_header_value(header::SET_COOKIE.as_str(), &value) {
+                Ok(value) => {
+                    headers.append(header::SET_COOKIE, value);
+                }
+                Err(err) => {
+                    tracing::warn!(name = cookie.name(), error = %err, "invalid cookie");
+                }
             }
         }
     }
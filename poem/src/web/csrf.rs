@@ -1,4 +1,4 @@
-use std::{ops::Deref, sync::Arc};
+use std::{fmt::Display, ops::Deref, sync::Arc};
 
 use base64::engine::{general_purpose::STANDARD, Engine};
 use libcsrf::{AesGcmCsrfProtection, CsrfProtection, UnencryptedCsrfCookie};
@@ -7,6 +7,9 @@ use crate::{FromRequest, Request, RequestBody, Result};
 
 /// A CSRF Token for the next request.
 ///
+/// It implements `Display`, so it can be passed to the templates and rendered
+/// in a hidden form field.
+///
 /// See also [`Csrf`](crate::middleware::Csrf)
 #[cfg_attr(docsrs, doc(cfg(feature = "csrf")))]
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -20,6 +23,12 @@ impl Deref for CsrfToken {
     }
 }
 
+impl Display for CsrfToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 #[async_trait::async_trait]
 impl<'a> FromRequest<'a> for &'a CsrfToken {
     async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
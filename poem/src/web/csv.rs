@@ -0,0 +1,164 @@
+use std::error::Error as StdError;
+
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+
+use crate::{Body, IntoResponse, Response};
+
+/// A `text/csv` response whose records are produced by a stream, such as
+/// rows read from a database cursor, which are serialized and sent to the
+/// client incrementally without buffering the whole export.
+///
+/// By default, a header row is written from the field names of the first
+/// record, and the fields are separated by commas.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::{stream, Stream};
+/// use poem::{handler, test::TestClient, web::CsvResponse};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: i32,
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn export() -> CsvResponse<impl Stream<Item = Result<User, std::io::Error>> + Send> {
+///     CsvResponse::new(stream::iter((1..=2).map(|id| {
+///         Ok(User {
+///             id,
+///             name: format!("user{id}"),
+///         })
+///     })))
+///     .delimiter(b';')
+/// }
+///
+/// let cli = TestClient::new(export);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("text/csv; charset=utf-8");
+/// resp.assert_text("id;name\n1;user1\n2;user2\n").await;
+/// # });
+/// ```
+pub struct CsvResponse<S> {
+    stream: S,
+    has_headers: bool,
+    delimiter: u8,
+}
+
+impl<S, T, E> CsvResponse<S>
+where
+    S: Stream<Item = Result<T, E>> + Send + 'static,
+    T: Serialize + 'static,
+    E: Into<Box<dyn StdError + Send + Sync>> + 'static,
+{
+    /// Create a CSV response with the stream of records.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            has_headers: true,
+            delimiter: b',',
+        }
+    }
+
+    /// Sets whether to write a header row from the field names of the
+    /// records, default to `true`.
+    #[must_use]
+    pub fn has_headers(self, has_headers: bool) -> Self {
+        Self {
+            has_headers,
+            ..self
+        }
+    }
+
+    /// Sets the field delimiter, default to `b','`.
+    #[must_use]
+    pub fn delimiter(self, delimiter: u8) -> Self {
+        Self { delimiter, ..self }
+    }
+}
+
+impl<S, T, E> IntoResponse for CsvResponse<S>
+where
+    S: Stream<Item = Result<T, E>> + Send + 'static,
+    T: Serialize + 'static,
+    E: Into<Box<dyn StdError + Send + Sync>> + 'static,
+{
+    fn into_response(self) -> Response {
+        let mut has_headers = self.has_headers;
+        let delimiter = self.delimiter;
+        let stream = self.stream.map(move |record| {
+            let record = record.map_err(Into::<Box<dyn StdError + Send + Sync>>::into)?;
+            // the header row is only written before the first record
+            let mut writer = libcsv::WriterBuilder::new()
+                .has_headers(std::mem::take(&mut has_headers))
+                .delimiter(delimiter)
+                .from_writer(Vec::new());
+            writer.serialize(record)?;
+            let data = writer.into_inner().map_err(|err| err.into_error())?;
+            Ok::<_, Box<dyn StdError + Send + Sync>>(data)
+        });
+
+        Response::builder()
+            .content_type("text/csv; charset=utf-8")
+            .body(Body::from_stream(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Record {
+        a: i32,
+        b: &'static str,
+    }
+
+    #[tokio::test]
+    async fn csv() {
+        let records = vec![
+            Ok::<_, std::io::Error>(Record { a: 1, b: "x,y" }),
+            Ok(Record { a: 2, b: "z" }),
+        ];
+
+        let resp = CsvResponse::new(stream::iter(records)).into_response();
+        assert_eq!(resp.content_type(), Some("text/csv; charset=utf-8"));
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            "a,b\n1,\"x,y\"\n2,z\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn without_headers() {
+        let records = vec![Ok::<_, std::io::Error>(Record { a: 1, b: "x" })];
+        let resp = CsvResponse::new(stream::iter(records))
+            .has_headers(false)
+            .delimiter(b'\t')
+            .into_response();
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "1\tx\n");
+    }
+
+    #[tokio::test]
+    async fn stream_error() {
+        let records = vec![
+            Ok(Record { a: 1, b: "x" }),
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "cursor closed")),
+        ];
+        let err = CsvResponse::new(stream::iter(records))
+            .into_response()
+            .into_body()
+            .into_bytes()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cursor closed"));
+    }
+}
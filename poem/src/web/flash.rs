@@ -0,0 +1,298 @@
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    web::cookie::{Cookie, CookieJar, SameSite},
+    FromRequest, IntoResponse, Request, RequestBody, Response, Result,
+};
+
+/// The name of the cookie that stores the flash messages.
+const FLASH_COOKIE: &str = "_flash";
+
+/// The level of a [`FlashMessage`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlashLevel {
+    /// Debug
+    Debug,
+    /// Info
+    Info,
+    /// Success
+    Success,
+    /// Warning
+    Warning,
+    /// Error
+    Error,
+}
+
+/// A message that is displayed once, usually on the page after a redirect.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FlashMessage {
+    /// The level of the message.
+    pub level: FlashLevel,
+    /// The text of the message.
+    pub message: String,
+}
+
+/// An extractor for the flash messages set by
+/// [`with_flash`](IntoResponse::with_flash) in the previous response.
+///
+/// The messages are stored in a private cookie, which is encrypted so that the
+/// client can neither read nor forge them, and is removed once the messages
+/// are extracted, so they are displayed only once. Both this extractor and
+/// [`with_flash`](IntoResponse::with_flash) require the
+/// [`CookieJarManager`](crate::middleware::CookieJarManager) middleware with
+/// a key, see [`CookieJarManager::with_key`](crate::middleware::CookieJarManager::with_key),
+/// otherwise the messages are discarded with a warning.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     get, handler,
+///     http::{header, StatusCode},
+///     middleware::CookieJarManager,
+///     post,
+///     test::TestClient,
+///     web::{cookie::CookieKey, Flash, FlashLevel, Redirect},
+///     EndpointExt, IntoResponse, Route,
+/// };
+///
+/// #[handler]
+/// fn submit() -> impl IntoResponse {
+///     Redirect::see_other("/").with_flash(FlashLevel::Success, "saved")
+/// }
+///
+/// #[handler]
+/// fn index(flash: Flash) -> String {
+///     flash
+///         .iter()
+///         .map(|msg| msg.message.as_str())
+///         .collect::<Vec<_>>()
+///         .join(",")
+/// }
+///
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .at("/submit", post(submit))
+///     .with(CookieJarManager::with_key(CookieKey::generate()));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.post("/submit").send().await;
+/// resp.assert_status(StatusCode::SEE_OTHER);
+/// let cookie = resp.0.header(header::SET_COOKIE).unwrap().to_string();
+///
+/// let resp = cli.get("/").header(header::COOKIE, cookie).send().await;
+/// resp.assert_text("saved").await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Flash(pub Vec<FlashMessage>);
+
+impl Deref for Flash {
+    type Target = Vec<FlashMessage>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for Flash {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let cookie_jar = req.cookie();
+        if cookie_jar.get(FLASH_COOKIE).is_none() {
+            return Ok(Flash::default());
+        }
+        let key = match &cookie_jar.key {
+            Some(key) => key,
+            None => {
+                tracing::warn!("the flash messages require `CookieJarManager::with_key`");
+                return Ok(Flash::default());
+            }
+        };
+
+        // a cookie that fails to decrypt is discarded as well
+        let messages = cookie_jar
+            .private_with_key(key)
+            .get(FLASH_COOKIE)
+            .and_then(|cookie| cookie.value().ok())
+            .unwrap_or_default();
+        let mut removal = flash_cookie(&[]);
+        removal.make_removal();
+        cookie_jar.add(removal);
+        Ok(Flash(messages))
+    }
+}
+
+/// The flash messages of a response, which are written to the cookie jar by
+/// the `CookieJarManager` middleware.
+#[derive(Clone)]
+struct FlashMessages(Vec<FlashMessage>);
+
+/// Adds the flash messages set by [`with_flash`](IntoResponse::with_flash)
+/// to the cookie jar, after the cookie has been removed by the `Flash`
+/// extractor.
+pub(crate) fn add_flash_cookie(cookie_jar: &CookieJar, resp: &Response) {
+    if let Some(FlashMessages(messages)) = resp.data::<FlashMessages>() {
+        match &cookie_jar.key {
+            Some(key) => cookie_jar.private_with_key(key).add(flash_cookie(messages)),
+            None => tracing::warn!("the flash messages require `CookieJarManager::with_key`"),
+        }
+    }
+}
+
+fn flash_cookie(messages: &[FlashMessage]) -> Cookie {
+    let mut cookie = Cookie::new(FLASH_COOKIE, messages);
+    cookie.set_path("/");
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Lax);
+    cookie
+}
+
+/// Returned by [`with_flash`](IntoResponse::with_flash) method.
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+pub struct WithFlash<T> {
+    pub(crate) inner: T,
+    pub(crate) message: FlashMessage,
+}
+
+impl<T: IntoResponse> IntoResponse for WithFlash<T> {
+    fn into_response(self) -> Response {
+        let mut resp = self.inner.into_response();
+
+        // merge with the messages set by the inner response
+        let mut messages = resp
+            .data::<FlashMessages>()
+            .map(|FlashMessages(messages)| messages.clone())
+            .unwrap_or_default();
+        messages.push(self.message);
+        resp.set_data(FlashMessages(messages));
+        resp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        handler,
+        http::{header, StatusCode},
+        middleware::CookieJarManager,
+        test::TestClient,
+        web::cookie::CookieKey,
+        EndpointExt, Route,
+    };
+
+    #[tokio::test]
+    async fn flash() {
+        #[handler(internal)]
+        fn set() -> impl IntoResponse {
+            StatusCode::SEE_OTHER
+                .with_header(header::SET_COOKIE, "a=1")
+                .with_flash(FlashLevel::Info, "hello")
+                .with_flash(FlashLevel::Error, "world")
+        }
+
+        #[handler(internal)]
+        fn get(flash: Flash) -> String {
+            serde_json::to_string(&flash.0).unwrap()
+        }
+
+        let cli = TestClient::new(
+            Route::new()
+                .at("/set", set)
+                .at("/get", get)
+                .with(CookieJarManager::with_key(CookieKey::generate())),
+        );
+        let resp = cli.get("/set").send().await;
+        let cookies = resp
+            .0
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|value| value.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies[0], "a=1");
+        let flash = Cookie::parse(&cookies[1]).unwrap();
+        assert_eq!(flash.name(), FLASH_COOKIE);
+        assert_eq!(flash.path(), Some("/"));
+        assert!(!flash.value_str().contains("hello"));
+
+        let resp = cli
+            .get("/get")
+            .header(header::COOKIE, cookies[1].split(';').next().unwrap())
+            .send()
+            .await;
+        let removal = Cookie::parse(resp.0.header(header::SET_COOKIE).unwrap()).unwrap();
+        assert_eq!(removal.name(), FLASH_COOKIE);
+        assert_eq!(removal.max_age(), Some(std::time::Duration::ZERO));
+        resp.assert_text(
+            r#"[{"level":"info","message":"hello"},{"level":"error","message":"world"}]"#,
+        )
+        .await;
+
+        let resp = cli.get("/get").send().await;
+        resp.assert_header_is_not_exist(header::SET_COOKIE);
+        resp.assert_text("[]").await;
+    }
+
+    #[tokio::test]
+    async fn forged_cookie() {
+        #[handler(internal)]
+        fn get(flash: Flash) -> String {
+            serde_json::to_string(&flash.0).unwrap()
+        }
+
+        let forged = Cookie::new(
+            FLASH_COOKIE,
+            vec![FlashMessage {
+                level: FlashLevel::Info,
+                message: "forged".to_string(),
+            }],
+        );
+        let resp = TestClient::new(get.with(CookieJarManager::with_key(CookieKey::generate())))
+            .get("/")
+            .header(header::COOKIE, forged.to_string())
+            .send()
+            .await;
+        let removal = Cookie::parse(resp.0.header(header::SET_COOKIE).unwrap()).unwrap();
+        assert_eq!(removal.max_age(), Some(std::time::Duration::ZERO));
+        resp.assert_text("[]").await;
+    }
+
+    #[tokio::test]
+    async fn without_key() {
+        #[handler(internal)]
+        fn set() -> impl IntoResponse {
+            "set".with_flash(FlashLevel::Info, "hello")
+        }
+
+        #[handler(internal)]
+        fn get(flash: Flash) -> String {
+            serde_json::to_string(&flash.0).unwrap()
+        }
+
+        let cli = TestClient::new(
+            Route::new()
+                .at("/set", set)
+                .at("/get", get)
+                .with(CookieJarManager::new()),
+        );
+        let resp = cli.get("/set").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header_is_not_exist(header::SET_COOKIE);
+
+        let resp = cli
+            .get("/get")
+            .header(header::COOKIE, format!("{FLASH_COOKIE}=abc"))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("[]").await;
+    }
+}
@@ -12,10 +12,54 @@ use crate::{
     FromRequest, Request, Result,
 };
 
+/// Specifies where the [`Form`] extractor reads the parameters from.
+///
+/// Use [`EndpointExt::data`](crate::EndpointExt::data) to change the behavior
+/// of the [`Form`] extractor for an endpoint, the default is
+/// [`FormSource::MethodBased`].
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     test::TestClient,
+///     web::{Form, FormSource},
+///     EndpointExt,
+/// };
+///
+/// #[handler]
+/// fn index(Form(params): Form<Vec<(String, String)>>) -> String {
+///     format!("{:?}", params)
+/// }
+///
+/// let app = index.data(FormSource::Query);
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.delete("/").query("a", &"1").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text(r#"[("a", "1")]"#).await;
+/// # });
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum FormSource {
+    /// Always parse the parameters from the body.
+    Body,
+    /// Always parse the parameters from the query string.
+    Query,
+    /// Parse the parameters from the query string if the method is `GET` or
+    /// `HEAD`, otherwise from the body.
+    #[default]
+    MethodBased,
+}
+
 /// An extractor that can deserialize some type from query string or body.
 ///
-/// If the method is not `GET`, the query parameters will be parsed from the
-/// body, otherwise it is like [`Query`](crate::web::Query).
+/// By default, if the method is `GET` or `HEAD`, it is like
+/// [`Query`](crate::web::Query), otherwise the parameters will be parsed from
+/// the body. Use [`FormSource`] to change this behavior, or use
+/// [`UrlEncodedBody`] to always parse the body.
 ///
 /// If the `Content-Type` is not `application/x-www-form-urlencoded`, then a
 /// `Bad Request` response will be returned.
@@ -89,30 +133,101 @@ impl<T> DerefMut for Form<T> {
 #[async_trait::async_trait]
 impl<'a, T: DeserializeOwned> FromRequest<'a> for Form<T> {
     async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
-        if req.method() == Method::GET {
+        let source = req.data::<FormSource>().copied().unwrap_or_default();
+        let from_query = match source {
+            FormSource::Body => false,
+            FormSource::Query => true,
+            FormSource::MethodBased => req.method() == Method::GET || req.method() == Method::HEAD,
+        };
+
+        if from_query {
             Ok(
                 serde_urlencoded::from_str(req.uri().query().unwrap_or_default())
                     .map_err(ParseFormError::UrlDecode)
                     .map(Self)?,
             )
         } else {
-            let content_type = req
-                .headers()
-                .get(header::CONTENT_TYPE)
-                .and_then(|content_type| content_type.to_str().ok())
-                .ok_or(ParseFormError::ContentTypeRequired)?;
-            if !is_form_content_type(content_type) {
-                return Err(ParseFormError::InvalidContentType(content_type.into()).into());
-            }
-
-            Ok(Self(
-                serde_urlencoded::from_bytes(&body.take()?.into_vec().await?)
-                    .map_err(ParseFormError::UrlDecode)?,
-            ))
+            parse_body(req, body).await.map(Self)
         }
     }
 }
 
+/// An extractor that deserializes some type from an
+/// `application/x-www-form-urlencoded` body, regardless of the method.
+///
+/// If the `Content-Type` is not `application/x-www-form-urlencoded`, then a
+/// `Unsupported Media Type` response will be returned.
+///
+/// # Errors
+///
+/// - [`ReadBodyError`](crate::error::ReadBodyError)
+/// - [`ParseFormError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, test::TestClient, web::UrlEncodedBody, Route};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct DeleteDocument {
+///     reason: String,
+/// }
+///
+/// #[handler]
+/// fn index(UrlEncodedBody(DeleteDocument { reason }): UrlEncodedBody<DeleteDocument>) -> String {
+///     reason
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .delete("/")
+///     .form(&[("reason", "obsolete")])
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("obsolete").await;
+/// # });
+/// ```
+pub struct UrlEncodedBody<T>(pub T);
+
+impl<T> Deref for UrlEncodedBody<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for UrlEncodedBody<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a, T: DeserializeOwned> FromRequest<'a> for UrlEncodedBody<T> {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        parse_body(req, body).await.map(Self)
+    }
+}
+
+async fn parse_body<T: DeserializeOwned>(req: &Request, body: &mut RequestBody) -> Result<T> {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .ok_or(ParseFormError::ContentTypeRequired)?;
+    if !is_form_content_type(content_type) {
+        return Err(ParseFormError::InvalidContentType(content_type.into()).into());
+    }
+
+    Ok(serde_urlencoded::from_bytes(&body.take()?.into_vec().await?)
+        .map_err(ParseFormError::UrlDecode)?)
+}
+
 fn is_form_content_type(content_type: &str) -> bool {
     matches!(content_type.parse::<mime::Mime>(), 
         Ok(content_type) if content_type.type_() == "application" 
@@ -128,7 +243,7 @@ mod tests {
     use serde::Deserialize;
 
     use super::*;
-    use crate::{handler, test::TestClient};
+    use crate::{handler, test::TestClient, EndpointExt};
 
     #[tokio::test]
     async fn test_form_extractor() {
@@ -166,4 +281,60 @@ mod tests {
             .await
             .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
     }
+
+    #[tokio::test]
+    async fn test_form_source() {
+        #[derive(Deserialize)]
+        struct Params {
+            name: String,
+        }
+
+        #[handler(internal)]
+        async fn index(form: Form<Params>) -> String {
+            form.0.name
+        }
+
+        let cli = TestClient::new(index);
+        let resp = cli.head("/").query("name", &"abc").send().await;
+        resp.assert_status_is_ok();
+
+        let cli = TestClient::new(index.data(FormSource::Query));
+        let resp = cli.delete("/").query("name", &"abc").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("abc").await;
+
+        let cli = TestClient::new(index.data(FormSource::Body));
+        let resp = cli.get("/").form(&[("name", "abc")]).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("abc").await;
+        cli.get("/")
+            .query("name", &"abc")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_url_encoded_body_extractor() {
+        #[derive(Deserialize)]
+        struct Params {
+            name: String,
+        }
+
+        #[handler(internal)]
+        async fn index(UrlEncodedBody(params): UrlEncodedBody<Params>) -> String {
+            params.name
+        }
+
+        let cli = TestClient::new(index);
+        let resp = cli.delete("/").form(&[("name", "abc")]).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("abc").await;
+
+        cli.get("/")
+            .query("name", &"abc")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
 }
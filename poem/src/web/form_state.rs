@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    error::ValidationError,
+    http::header,
+    web::cookie::{Cookie, SameSite},
+    FromRequest, IntoResponse, Request, RequestBody, Response, Result,
+};
+
+/// The name of the cookie that stores the form state.
+const FORM_STATE_COOKIE: &str = "_form";
+
+/// The submitted values and the validation errors of a form, which are kept
+/// for the next request to render the form again after a redirect
+/// (POST-redirect-GET).
+///
+/// It is set by [`with_form_state`](IntoResponse::with_form_state) and
+/// extracted once in the next request, like [`Flash`](crate::web::Flash), so
+/// it requires the [`CookieJarManager`](crate::middleware::CookieJarManager)
+/// middleware. It is serialized as `{"values": {..}, "errors": {..}}`, and
+/// can be passed to the templates directly.
+///
+/// The state is stored in a cookie, so the values are limited to about 4k
+/// bytes, and the sensitive values such as passwords should be removed with
+/// [`FormState::except`].
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     error::ValidationError,
+///     get, handler,
+///     http::{header, StatusCode},
+///     middleware::CookieJarManager,
+///     test::TestClient,
+///     web::{Form, FormState, Redirect},
+///     EndpointExt, IntoResponse, Response, Route,
+/// };
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Signup {
+///     email: String,
+///     password: String,
+/// }
+///
+/// #[handler]
+/// fn signup(Form(form): Form<Signup>) -> Response {
+///     let errors = ValidationError::new().field("email", "invalid email");
+///     Redirect::see_other("/signup")
+///         .with_form_state(FormState::new(&form, errors).except("password"))
+///         .into_response()
+/// }
+///
+/// #[handler]
+/// fn signup_page(form: FormState) -> String {
+///     format!(
+///         "{} {}",
+///         form.value("email").unwrap_or_default(),
+///         form.field_errors("email").join(",")
+///     )
+/// }
+///
+/// let app = Route::new()
+///     .at("/signup", get(signup_page).post(signup))
+///     .with(CookieJarManager::new());
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/signup")
+///     .form(&[("email", "alice"), ("password", "secret")])
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::SEE_OTHER);
+/// let cookie = resp.0.header(header::SET_COOKIE).unwrap().to_string();
+///
+/// let resp = cli.get("/signup").header(header::COOKIE, cookie).send().await;
+/// resp.assert_text("alice invalid email").await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FormState {
+    values: BTreeMap<String, String>,
+    errors: BTreeMap<String, Vec<String>>,
+}
+
+impl FormState {
+    /// Create a `FormState` with the submitted values, such as the
+    /// deserialized form, and the validation errors.
+    ///
+    /// The values that are not strings, numbers or booleans are ignored.
+    pub fn new(values: impl Serialize, errors: ValidationError) -> Self {
+        let values = match serde_json::to_value(values) {
+            Ok(Value::Object(values)) => values
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    let value = match value {
+                        Value::String(value) => value,
+                        Value::Number(value) => value.to_string(),
+                        Value::Bool(value) => value.to_string(),
+                        _ => return None,
+                    };
+                    Some((name, value))
+                })
+                .collect(),
+            _ => BTreeMap::new(),
+        };
+        Self {
+            values,
+            errors: errors.errors().clone(),
+        }
+    }
+
+    /// Removes the value of the field, such as a password, which should not
+    /// be stored in the cookie.
+    #[must_use]
+    pub fn except(mut self, name: &str) -> Self {
+        self.values.remove(name);
+        self
+    }
+
+    /// Returns the submitted value of the field.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Returns the submitted values.
+    #[inline]
+    pub fn values(&self) -> &BTreeMap<String, String> {
+        &self.values
+    }
+
+    /// Returns the error messages of the field.
+    pub fn field_errors(&self, name: &str) -> &[String] {
+        self.errors.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns the error messages of the fields.
+    #[inline]
+    pub fn errors(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.errors
+    }
+
+    /// Returns `true` if there are validation errors.
+    #[inline]
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Returns `true` if there are neither values nor errors, such as when
+    /// the form is displayed for the first time.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.errors.is_empty()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for FormState {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let cookie_jar = req.cookie();
+        let cookie = match cookie_jar.get(FORM_STATE_COOKIE) {
+            Some(cookie) => cookie,
+            None => return Ok(FormState::default()),
+        };
+
+        let mut removal = form_state_cookie(&FormState::default());
+        removal.make_removal();
+        cookie_jar.add(removal);
+        Ok(cookie.value().unwrap_or_default())
+    }
+}
+
+fn form_state_cookie(state: &FormState) -> Cookie {
+    let mut cookie = Cookie::new(FORM_STATE_COOKIE, state);
+    cookie.set_path("/");
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Lax);
+    cookie
+}
+
+/// Returned by [`with_form_state`](IntoResponse::with_form_state) method.
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+pub struct WithFormState<T> {
+    pub(crate) inner: T,
+    pub(crate) state: FormState,
+}
+
+impl<T: IntoResponse> IntoResponse for WithFormState<T> {
+    fn into_response(self) -> Response {
+        let mut resp = self.inner.into_response();
+        match form_state_cookie(&self.state).to_header_value() {
+            Ok(value) => {
+                resp.headers_mut().append(header::SET_COOKIE, value);
+                resp
+            }
+            Err(err) => crate::Error::from(err).into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{handler, middleware::CookieJarManager, test::TestClient, EndpointExt};
+
+    #[test]
+    fn values() {
+        let state = FormState::new(
+            json!({ "name": "alice", "age": 20, "admin": false, "tags": ["a"], "password": "1", "bio": null }),
+            ValidationError::new().field("age", "too young"),
+        )
+        .except("password");
+        assert_eq!(
+            state.values(),
+            &BTreeMap::from([
+                ("admin".to_string(), "false".to_string()),
+                ("age".to_string(), "20".to_string()),
+                ("name".to_string(), "alice".to_string()),
+            ])
+        );
+        assert_eq!(state.field_errors("age"), ["too young"]);
+        assert!(state.field_errors("name").is_empty());
+        assert!(state.has_errors());
+    }
+
+    #[tokio::test]
+    async fn form_state() {
+        #[handler(internal)]
+        fn get(state: FormState) -> String {
+            serde_json::to_string(&state).unwrap()
+        }
+
+        let resp = ()
+            .with_form_state(FormState::new(
+                json!({ "name": "alice" }),
+                ValidationError::new().field("name", "taken"),
+            ))
+            .into_response();
+        let cookie = resp.headers()[header::SET_COOKIE].to_str().unwrap();
+
+        let cli = TestClient::new(get.with(CookieJarManager::new()));
+        let resp = cli
+            .get("/")
+            .header(header::COOKIE, cookie.split(';').next().unwrap())
+            .send()
+            .await;
+        let removal = Cookie::parse(resp.0.header(header::SET_COOKIE).unwrap()).unwrap();
+        assert_eq!(removal.name(), FORM_STATE_COOKIE);
+        resp.assert_text(r#"{"values":{"name":"alice"},"errors":{"name":["taken"]}}"#)
+            .await;
+
+        let resp = cli.get("/").send().await;
+        resp.assert_header_is_not_exist(header::SET_COOKIE);
+        resp.assert_text(r#"{"values":{},"errors":{}}"#).await;
+    }
+}
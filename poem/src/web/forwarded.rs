@@ -0,0 +1,272 @@
+use std::net::IpAddr;
+
+use crate::{error::ParseForwardedError, FromRequest, Request, RequestBody, Result};
+
+/// The name of a node in the `Forwarded` header.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum ForwardedNodeName {
+    /// The node is unknown (`unknown`).
+    Unknown,
+    /// An IP address.
+    Ip(IpAddr),
+    /// An obfuscated identifier, such as `_hidden`.
+    Obfuscated(String),
+}
+
+/// A node in the `for` or `by` parameter of the `Forwarded` header.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ForwardedNode {
+    /// The name of the node.
+    pub name: ForwardedNodeName,
+    /// The port of the node.
+    pub port: Option<u16>,
+}
+
+impl ForwardedNode {
+    /// Returns the IP address of the node if it is not unknown or obfuscated.
+    #[inline]
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self.name {
+            ForwardedNodeName::Ip(ip) => Some(ip),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> From<rfc7239::NodeIdentifier<'a>> for ForwardedNode {
+    fn from(node: rfc7239::NodeIdentifier<'a>) -> Self {
+        Self {
+            name: match node.name {
+                rfc7239::NodeName::Unknown => ForwardedNodeName::Unknown,
+                rfc7239::NodeName::Ip(ip) => ForwardedNodeName::Ip(ip),
+                rfc7239::NodeName::Obfuscated(name) => {
+                    ForwardedNodeName::Obfuscated(name.to_string())
+                }
+            },
+            port: node.port,
+        }
+    }
+}
+
+/// An element of the `Forwarded` header, added by a single proxy.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ForwardedElement {
+    /// The interface where the request came in to the proxy (`by`).
+    pub by: Option<ForwardedNode>,
+    /// The client that initiated the request (`for`).
+    pub for_: Option<ForwardedNode>,
+    /// The `Host` request header as received by the proxy (`host`).
+    pub host: Option<String>,
+    /// The protocol used to make the request (`proto`).
+    pub proto: Option<String>,
+}
+
+/// An extractor for the `Forwarded` header, as described in
+/// [RFC 7239](https://datatracker.ietf.org/doc/html/rfc7239).
+///
+/// The elements of all `Forwarded` headers are returned in order, the first
+/// element was added by the proxy closest to the client. If the request does
+/// not contain the `Forwarded` header, the list of elements is empty.
+///
+/// # Errors
+///
+/// - [`ParseForwardedError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, test::TestClient, web::Forwarded};
+///
+/// #[handler]
+/// fn index(forwarded: Forwarded) -> String {
+///     format!(
+///         "{:?} {:?}",
+///         forwarded.client_ip(),
+///         forwarded.proto().unwrap_or("http")
+///     )
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .header(
+///         "forwarded",
+///         "for=192.0.2.60;proto=https;by=203.0.113.43, for=198.51.100.17",
+///     )
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("Some(192.0.2.60) \"https\"").await;
+/// # });
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Forwarded(pub Vec<ForwardedElement>);
+
+impl Forwarded {
+    /// Parses the value of a `Forwarded` header.
+    pub fn parse(value: &str) -> Result<Self, ParseForwardedError> {
+        let mut elements = Vec::new();
+        Self::parse_into(value, &mut elements)?;
+        Ok(Self(elements))
+    }
+
+    fn parse_into(
+        value: &str,
+        elements: &mut Vec<ForwardedElement>,
+    ) -> Result<(), ParseForwardedError> {
+        for item in rfc7239::parse(value) {
+            let item = item.map_err(|err| ParseForwardedError(err.to_string()))?;
+            elements.push(ForwardedElement {
+                by: item.forwarded_by.map(Into::into),
+                for_: item.forwarded_for.map(Into::into),
+                host: item.host.map(ToString::to_string),
+                proto: item.protocol.map(ToString::to_string),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the elements of the header.
+    #[inline]
+    pub fn elements(&self) -> &[ForwardedElement] {
+        &self.0
+    }
+
+    /// Returns `true` if the header contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the first IP address in the `for` parameters, which is the
+    /// client that initiated the request.
+    ///
+    /// **NOTE:** The client can send its own `Forwarded` header, and the
+    /// proxies append their elements to it, so the first address can be
+    /// spoofed. Don't use it for access control or rate limiting, use
+    /// [`Forwarded::trusted_client_ip`] instead.
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.0
+            .iter()
+            .find_map(|element| element.for_.as_ref().and_then(ForwardedNode::ip))
+    }
+
+    /// Returns the IP address in the `for` parameter of the element added by
+    /// the outermost of `trusted_proxies` proxies in front of the server.
+    ///
+    /// Each proxy appends an element, so the last `trusted_proxies` elements
+    /// can be trusted and the preceding ones are ignored. Returns `None` if
+    /// there are fewer elements or `trusted_proxies` is `0`.
+    pub fn trusted_client_ip(&self, trusted_proxies: usize) -> Option<IpAddr> {
+        let index = self.0.len().checked_sub(trusted_proxies)?;
+        self.0.get(index)?.for_.as_ref().and_then(ForwardedNode::ip)
+    }
+
+    /// Returns the first `host` parameter.
+    pub fn host(&self) -> Option<&str> {
+        self.0.iter().find_map(|element| element.host.as_deref())
+    }
+
+    /// Returns the first `proto` parameter.
+    pub fn proto(&self) -> Option<&str> {
+        self.0.iter().find_map(|element| element.proto.as_deref())
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for Forwarded {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let mut elements = Vec::new();
+        for value in req.headers().get_all("forwarded") {
+            let value = value
+                .to_str()
+                .map_err(|_| ParseForwardedError("not a valid string".to_string()))?;
+            Self::parse_into(value, &mut elements)?;
+        }
+        Ok(Self(elements))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+
+    #[test]
+    fn parse() {
+        let forwarded = Forwarded::parse(
+            "for=\"[2001:db8:cafe::17]:4711\";host=example.com;proto=https;by=_proxy, for=unknown",
+        )
+        .unwrap();
+        assert_eq!(
+            forwarded,
+            Forwarded(vec![
+                ForwardedElement {
+                    by: Some(ForwardedNode {
+                        name: ForwardedNodeName::Obfuscated("_proxy".to_string()),
+                        port: None,
+                    }),
+                    for_: Some(ForwardedNode {
+                        name: ForwardedNodeName::Ip("2001:db8:cafe::17".parse().unwrap()),
+                        port: Some(4711),
+                    }),
+                    host: Some("example.com".to_string()),
+                    proto: Some("https".to_string()),
+                },
+                ForwardedElement {
+                    for_: Some(ForwardedNode {
+                        name: ForwardedNodeName::Unknown,
+                        port: None,
+                    }),
+                    ..Default::default()
+                },
+            ])
+        );
+        assert_eq!(
+            forwarded.client_ip(),
+            Some("2001:db8:cafe::17".parse().unwrap())
+        );
+        assert_eq!(forwarded.host(), Some("example.com"));
+        assert_eq!(forwarded.proto(), Some("https"));
+
+        assert!(Forwarded::parse("for").is_err());
+    }
+
+    #[tokio::test]
+    async fn extractor() {
+        let req = Request::builder()
+            .header("forwarded", "for=192.0.2.43")
+            .header("forwarded", "for=198.51.100.17;proto=http")
+            .finish();
+        let forwarded = Forwarded::from_request_without_body(&req).await.unwrap();
+        assert_eq!(forwarded.elements().len(), 2);
+        assert_eq!(forwarded.client_ip(), Some("192.0.2.43".parse().unwrap()));
+        assert_eq!(forwarded.proto(), Some("http"));
+        assert_eq!(
+            forwarded.trusted_client_ip(1),
+            Some("198.51.100.17".parse().unwrap())
+        );
+        assert_eq!(
+            forwarded.trusted_client_ip(2),
+            Some("192.0.2.43".parse().unwrap())
+        );
+        assert_eq!(forwarded.trusted_client_ip(0), None);
+        assert_eq!(forwarded.trusted_client_ip(3), None);
+
+        let forwarded = Forwarded::from_request_without_body(&Request::default())
+            .await
+            .unwrap();
+        assert!(forwarded.is_empty());
+
+        let req = Request::builder().header("forwarded", "for").finish();
+        assert_eq!(
+            Forwarded::from_request_without_body(&req)
+                .await
+                .unwrap_err()
+                .status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
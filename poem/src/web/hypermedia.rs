@@ -0,0 +1,502 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    error::ResponseError, http::header, web::checked_header_value, FromRequest, IntoResponse,
+    Request, RequestBody, Response, Result,
+};
+
+const TURBO_STREAM: &str = "text/vnd.turbo-stream.html";
+
+fn header_str<'a>(req: &'a Request, name: &str) -> Option<&'a str> {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+}
+
+fn header_bool(req: &Request, name: &str) -> bool {
+    header_str(req, name) == Some("true")
+}
+
+/// An extractor for the request headers sent by [htmx](https://htmx.org).
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     test::TestClient,
+///     web::{HxRequest, Html},
+/// };
+///
+/// #[handler]
+/// fn users(hx: HxRequest) -> Html<&'static str> {
+///     if hx.is_partial() {
+///         Html("<tr><td>alice</td></tr>")
+///     } else {
+///         Html("<html><table><tr><td>alice</td></tr></table></html>")
+///     }
+/// }
+///
+/// let cli = TestClient::new(users);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").header("HX-Request", "true").send().await;
+/// resp.assert_text("<tr><td>alice</td></tr>").await;
+/// # });
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct HxRequest {
+    /// Whether the request is sent by htmx (`HX-Request`).
+    pub is_htmx: bool,
+    /// Whether the request is sent by an element with `hx-boost`
+    /// (`HX-Boosted`).
+    pub boosted: bool,
+    /// Whether the request restores the history after a cache miss
+    /// (`HX-History-Restore-Request`).
+    pub history_restore: bool,
+    /// The URL of the browser (`HX-Current-URL`).
+    pub current_url: Option<String>,
+    /// The response of the user to an `hx-prompt` (`HX-Prompt`).
+    pub prompt: Option<String>,
+    /// The id of the target element (`HX-Target`).
+    pub target: Option<String>,
+    /// The id of the triggered element (`HX-Trigger`).
+    pub trigger: Option<String>,
+    /// The name of the triggered element (`HX-Trigger-Name`).
+    pub trigger_name: Option<String>,
+}
+
+impl HxRequest {
+    /// Returns `true` if the request expects a partial of the page, which is
+    /// a request sent by htmx that is neither boosted nor a history restore.
+    pub fn is_partial(&self) -> bool {
+        self.is_htmx && !self.boosted && !self.history_restore
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for HxRequest {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let header_string = |name| header_str(req, name).map(ToString::to_string);
+        Ok(Self {
+            is_htmx: header_bool(req, "hx-request"),
+            boosted: header_bool(req, "hx-boosted"),
+            history_restore: header_bool(req, "hx-history-restore-request"),
+            current_url: header_string("hx-current-url"),
+            prompt: header_string("hx-prompt"),
+            target: header_string("hx-target"),
+            trigger: header_string("hx-trigger"),
+            trigger_name: header_string("hx-trigger-name"),
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct HxTriggers(Vec<(String, Value)>);
+
+impl HxTriggers {
+    fn to_header(&self) -> Option<String> {
+        if self.0.is_empty() {
+            return None;
+        }
+        if self.0.iter().all(|(_, detail)| detail.is_null()) {
+            let names = self.0.iter().map(|(name, _)| name.as_str());
+            return Some(names.collect::<Vec<_>>().join(", "));
+        }
+        let events = self.0.iter().cloned().collect::<Map<_, _>>();
+        Some(Value::Object(events).to_string())
+    }
+}
+
+/// A response with the response headers of [htmx](https://htmx.org), such
+/// as `HX-Trigger` and `HX-Redirect`.
+///
+/// If a header value contains a line break or any other character that is
+/// not allowed in a header value, an
+/// [`InvalidHeaderValueError`](crate::error::InvalidHeaderValueError)
+/// response is returned instead.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     test::TestClient,
+///     web::{Html, HxResponse},
+/// };
+/// use serde_json::json;
+///
+/// #[handler]
+/// fn save() -> HxResponse<Html<&'static str>> {
+///     HxResponse::new(Html("<p>saved</p>"))
+///         .trigger_with_detail("notify", json!({ "level": "info" }))
+///         .push_url("/users/1")
+/// }
+///
+/// let cli = TestClient::new(save);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.post("/").send().await;
+/// resp.assert_header("hx-trigger", r#"{"notify":{"level":"info"}}"#);
+/// resp.assert_header("hx-push-url", "/users/1");
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct HxResponse<T> {
+    inner: T,
+    headers: Vec<(&'static str, String)>,
+    trigger: HxTriggers,
+    trigger_after_settle: HxTriggers,
+    trigger_after_swap: HxTriggers,
+}
+
+impl<T> HxResponse<T> {
+    /// Create an `HxResponse` with the response.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            headers: Vec::new(),
+            trigger: Default::default(),
+            trigger_after_settle: Default::default(),
+            trigger_after_swap: Default::default(),
+        }
+    }
+
+    fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// Triggers the event on the client as soon as the response is received
+    /// (`HX-Trigger`).
+    #[must_use]
+    pub fn trigger(mut self, event: impl Into<String>) -> Self {
+        self.trigger.0.push((event.into(), Value::Null));
+        self
+    }
+
+    /// Triggers the event with the detail on the client as soon as the
+    /// response is received (`HX-Trigger`).
+    #[must_use]
+    pub fn trigger_with_detail(mut self, event: impl Into<String>, detail: impl Serialize) -> Self {
+        let detail = serde_json::to_value(detail).unwrap_or_default();
+        self.trigger.0.push((event.into(), detail));
+        self
+    }
+
+    /// Triggers the event on the client after the settling step
+    /// (`HX-Trigger-After-Settle`).
+    #[must_use]
+    pub fn trigger_after_settle(mut self, event: impl Into<String>) -> Self {
+        self.trigger_after_settle
+            .0
+            .push((event.into(), Value::Null));
+        self
+    }
+
+    /// Triggers the event on the client after the swap step
+    /// (`HX-Trigger-After-Swap`).
+    #[must_use]
+    pub fn trigger_after_swap(mut self, event: impl Into<String>) -> Self {
+        self.trigger_after_swap.0.push((event.into(), Value::Null));
+        self
+    }
+
+    /// Redirects the client to the URL with a full page reload
+    /// (`HX-Redirect`).
+    #[must_use]
+    pub fn redirect(self, url: impl Into<String>) -> Self {
+        self.header("hx-redirect", url)
+    }
+
+    /// Redirects the client to the URL without a full page reload
+    /// (`HX-Location`).
+    #[must_use]
+    pub fn location(self, url: impl Into<String>) -> Self {
+        self.header("hx-location", url)
+    }
+
+    /// Makes the client do a full refresh of the page (`HX-Refresh`).
+    #[must_use]
+    pub fn refresh(self) -> Self {
+        self.header("hx-refresh", "true")
+    }
+
+    /// Pushes the URL into the history of the browser (`HX-Push-Url`).
+    #[must_use]
+    pub fn push_url(self, url: impl Into<String>) -> Self {
+        self.header("hx-push-url", url)
+    }
+
+    /// Replaces the current URL in the location bar (`HX-Replace-Url`).
+    #[must_use]
+    pub fn replace_url(self, url: impl Into<String>) -> Self {
+        self.header("hx-replace-url", url)
+    }
+
+    /// Specifies how the response will be swapped, such as `outerHTML`
+    /// (`HX-Reswap`).
+    #[must_use]
+    pub fn reswap(self, swap: impl Into<String>) -> Self {
+        self.header("hx-reswap", swap)
+    }
+
+    /// Updates the target of the content to the CSS selector
+    /// (`HX-Retarget`).
+    #[must_use]
+    pub fn retarget(self, selector: impl Into<String>) -> Self {
+        self.header("hx-retarget", selector)
+    }
+
+    /// Selects the part of the response to be swapped in with the CSS
+    /// selector (`HX-Reselect`).
+    #[must_use]
+    pub fn reselect(self, selector: impl Into<String>) -> Self {
+        self.header("hx-reselect", selector)
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for HxResponse<T> {
+    fn into_response(self) -> Response {
+        let triggers = [
+            ("hx-trigger", self.trigger),
+            ("hx-trigger-after-settle", self.trigger_after_settle),
+            ("hx-trigger-after-swap", self.trigger_after_swap),
+        ];
+        let headers = self.headers.into_iter().chain(
+            triggers
+                .into_iter()
+                .filter_map(|(name, triggers)| Some((name, triggers.to_header()?))),
+        );
+
+        let mut resp = self.inner.into_response();
+        for (name, value) in headers {
+            match checked_header_value(name, &value) {
+                Ok(value) => {
+                    resp.headers_mut().insert(name, value);
+                }
+                Err(err) => return err.as_response(),
+            }
+        }
+        resp
+    }
+}
+
+/// An extractor for the request headers sent by
+/// [Turbo](https://turbo.hotwired.dev).
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct TurboRequest {
+    /// The id of the `<turbo-frame>` that sends the request (`Turbo-Frame`).
+    pub frame: Option<String>,
+    /// Whether the client accepts the [`TurboStream`] responses, which is
+    /// `true` for the form submissions.
+    pub accepts_stream: bool,
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for TurboRequest {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(Self {
+            frame: header_str(req, "turbo-frame").map(ToString::to_string),
+            accepts_stream: req
+                .headers()
+                .get_all(header::ACCEPT)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .any(|value| value.contains(TURBO_STREAM)),
+        })
+    }
+}
+
+/// A [Turbo Streams](https://turbo.hotwired.dev/handbook/streams) response
+/// with the `text/vnd.turbo-stream.html` content type.
+///
+/// The HTML of the actions is not escaped.
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, test::TestClient, web::TurboStream};
+///
+/// #[handler]
+/// fn create() -> TurboStream {
+///     TurboStream::new()
+///         .append("messages", "<p>hello</p>")
+///         .remove("empty")
+/// }
+///
+/// let cli = TestClient::new(create);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.post("/").send().await;
+/// resp.assert_content_type("text/vnd.turbo-stream.html; charset=utf-8");
+/// resp.assert_text(concat!(
+///     r#"<turbo-stream action="append" target="messages"><template><p>hello</p></template></turbo-stream>"#,
+///     r#"<turbo-stream action="remove" target="empty"></turbo-stream>"#,
+/// ))
+/// .await;
+/// # });
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct TurboStream {
+    body: String,
+}
+
+impl TurboStream {
+    /// Create an empty `TurboStream`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends an action on the element with the id `target`, such as
+    /// `morph`, with the HTML of the `<template>`.
+    #[must_use]
+    pub fn action(mut self, action: &str, target: &str, html: Option<&str>) -> Self {
+        let attr = |value: &str| {
+            value
+                .replace('&', "&amp;")
+                .replace('"', "&quot;")
+                .replace('<', "&lt;")
+        };
+        self.body.push_str(&format!(
+            r#"<turbo-stream action="{}" target="{}">"#,
+            attr(action),
+            attr(target)
+        ));
+        if let Some(html) = html {
+            self.body.push_str(&format!("<template>{html}</template>"));
+        }
+        self.body.push_str("</turbo-stream>");
+        self
+    }
+
+    /// Appends the HTML to the element with the id `target`.
+    #[must_use]
+    pub fn append(self, target: &str, html: impl AsRef<str>) -> Self {
+        self.action("append", target, Some(html.as_ref()))
+    }
+
+    /// Prepends the HTML to the element with the id `target`.
+    #[must_use]
+    pub fn prepend(self, target: &str, html: impl AsRef<str>) -> Self {
+        self.action("prepend", target, Some(html.as_ref()))
+    }
+
+    /// Replaces the element with the id `target` with the HTML.
+    #[must_use]
+    pub fn replace(self, target: &str, html: impl AsRef<str>) -> Self {
+        self.action("replace", target, Some(html.as_ref()))
+    }
+
+    /// Replaces the content of the element with the id `target` with the
+    /// HTML.
+    #[must_use]
+    pub fn update(self, target: &str, html: impl AsRef<str>) -> Self {
+        self.action("update", target, Some(html.as_ref()))
+    }
+
+    /// Inserts the HTML before the element with the id `target`.
+    #[must_use]
+    pub fn before(self, target: &str, html: impl AsRef<str>) -> Self {
+        self.action("before", target, Some(html.as_ref()))
+    }
+
+    /// Inserts the HTML after the element with the id `target`.
+    #[must_use]
+    pub fn after(self, target: &str, html: impl AsRef<str>) -> Self {
+        self.action("after", target, Some(html.as_ref()))
+    }
+
+    /// Removes the element with the id `target`.
+    #[must_use]
+    pub fn remove(self, target: &str) -> Self {
+        self.action("remove", target, None)
+    }
+}
+
+impl IntoResponse for TurboStream {
+    fn into_response(self) -> Response {
+        Response::builder()
+            .content_type(format!("{TURBO_STREAM}; charset=utf-8"))
+            .body(self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{http::StatusCode, web::Html};
+
+    #[tokio::test]
+    async fn hx_request() {
+        let req = Request::builder()
+            .header("HX-Request", "true")
+            .header("HX-Target", "users")
+            .header("HX-Current-URL", "http://localhost/users")
+            .finish();
+        let hx = HxRequest::from_request_without_body(&req).await.unwrap();
+        assert!(hx.is_partial());
+        assert_eq!(hx.target.as_deref(), Some("users"));
+        assert_eq!(hx.current_url.as_deref(), Some("http://localhost/users"));
+        assert_eq!(hx.trigger, None);
+
+        let req = Request::builder()
+            .header("HX-Request", "true")
+            .header("HX-Boosted", "true")
+            .finish();
+        let hx = HxRequest::from_request_without_body(&req).await.unwrap();
+        assert!(hx.is_htmx);
+        assert!(!hx.is_partial());
+
+        let hx = HxRequest::from_request_without_body(&Request::default())
+            .await
+            .unwrap();
+        assert_eq!(hx, HxRequest::default());
+    }
+
+    #[test]
+    fn hx_response() {
+        let resp = HxResponse::new(Html("ok"))
+            .trigger("a")
+            .trigger("b")
+            .trigger_after_swap("c")
+            .push_url("/users/1")
+            .reswap("outerHTML")
+            .into_response();
+        assert_eq!(resp.headers()["hx-trigger"], "a, b");
+        assert_eq!(resp.headers()["hx-trigger-after-swap"], "c");
+        assert!(!resp.headers().contains_key("hx-trigger-after-settle"));
+        assert_eq!(resp.headers()["hx-push-url"], "/users/1");
+        assert_eq!(resp.headers()["hx-reswap"], "outerHTML");
+
+        let resp = HxResponse::new(())
+            .redirect("/login\r\nset-cookie: a=1")
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(resp.headers().get("hx-redirect").is_none());
+    }
+
+    #[tokio::test]
+    async fn turbo() {
+        let req = Request::builder()
+            .header("Turbo-Frame", "messages")
+            .header(header::ACCEPT, "text/vnd.turbo-stream.html, text/html")
+            .finish();
+        let turbo = TurboRequest::from_request_without_body(&req).await.unwrap();
+        assert_eq!(turbo.frame.as_deref(), Some("messages"));
+        assert!(turbo.accepts_stream);
+
+        let resp = TurboStream::new()
+            .replace("a\"", "<p>1</p>")
+            .into_response();
+        assert_eq!(
+            resp.content_type(),
+            Some("text/vnd.turbo-stream.html; charset=utf-8")
+        );
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            r#"<turbo-stream action="replace" target="a&quot;"><template><p>1</p></template></turbo-stream>"#
+        );
+    }
+}
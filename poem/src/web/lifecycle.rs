@@ -0,0 +1,310 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use hyper::body::{Frame, SizeHint};
+use parking_lot::Mutex;
+
+use crate::{body::BoxBody, FromRequest, Request, RequestBody, Response, Result};
+
+/// A stage in the lifecycle of a request.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LifecycleStage {
+    /// The request has been received by the server.
+    Received,
+    /// The request has been matched by a [`Route`](crate::Route).
+    Routed,
+    /// The arguments of a [`handler`](crate::handler) or an operation of
+    /// `poem-openapi` have been extracted.
+    Extracted,
+    /// The [`handler`](crate::handler) function or the operation of
+    /// `poem-openapi` has returned.
+    Handled,
+    /// The server starts sending the response.
+    ResponseStarted,
+    /// The response body has been sent, or dropped because the connection
+    /// was closed.
+    Completed,
+}
+
+/// An event emitted when a request enters a [`LifecycleStage`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LifecycleEvent {
+    /// The stage.
+    pub stage: LifecycleStage,
+    /// The time at which the stage was entered.
+    pub at: Instant,
+}
+
+type Listener = Arc<dyn Fn(&LifecycleEvent) + Send + Sync>;
+
+struct Inner {
+    events: Vec<LifecycleEvent>,
+    listeners: Vec<Listener>,
+}
+
+struct State {
+    received: Instant,
+    /// Allocated when the first listener subscribes, so that the requests
+    /// without listeners don't record the events.
+    inner: OnceLock<Mutex<Inner>>,
+}
+
+/// The event bus of the lifecycle of a request, which observability
+/// middlewares can subscribe to for the timing of each [`LifecycleStage`].
+///
+/// The [`Received`](LifecycleStage::Received) event is emitted when the
+/// request is created, and the events that have already been emitted are
+/// replayed to a new listener. The other events are only recorded once a
+/// listener has subscribed, so a middleware receives the `Received` event and
+/// the events emitted after the first listener subscribed.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     web::{LifecycleStage, RequestLifecycle},
+///     Endpoint, EndpointExt, Request,
+/// };
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = index.around(|ep, req: Request| async move {
+///     req.lifecycle().subscribe(|event| {
+///         if event.stage == LifecycleStage::Handled {
+///             tracing::info!(at = ?event.at, "request handled");
+///         }
+///     });
+///     ep.call(req).await
+/// });
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let req = Request::default();
+/// let lifecycle: RequestLifecycle = req.lifecycle().clone();
+/// app.call(req).await.unwrap();
+/// let stages = lifecycle
+///     .events()
+///     .into_iter()
+///     .map(|event| event.stage)
+///     .collect::<Vec<_>>();
+/// assert_eq!(
+///     stages,
+///     [
+///         LifecycleStage::Received,
+///         LifecycleStage::Extracted,
+///         LifecycleStage::Handled
+///     ]
+/// );
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct RequestLifecycle(Arc<State>);
+
+impl Debug for RequestLifecycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestLifecycle")
+            .field("events", &self.events())
+            .finish()
+    }
+}
+
+impl Default for RequestLifecycle {
+    fn default() -> Self {
+        Self(Arc::new(State {
+            received: Instant::now(),
+            inner: OnceLock::new(),
+        }))
+    }
+}
+
+impl RequestLifecycle {
+    /// Subscribes to the events, the events that have already been emitted
+    /// are passed to the listener immediately.
+    pub fn subscribe<F>(&self, f: F)
+    where
+        F: Fn(&LifecycleEvent) + Send + Sync + 'static,
+    {
+        let listener: Listener = Arc::new(f);
+        let events = {
+            let mut inner = self
+                .0
+                .inner
+                .get_or_init(|| {
+                    Mutex::new(Inner {
+                        events: vec![self.received()],
+                        listeners: Vec::new(),
+                    })
+                })
+                .lock();
+            inner.listeners.push(listener.clone());
+            inner.events.clone()
+        };
+        for event in &events {
+            listener(event);
+        }
+    }
+
+    /// Returns the events that have been recorded.
+    pub fn events(&self) -> Vec<LifecycleEvent> {
+        match self.0.inner.get() {
+            Some(inner) => inner.lock().events.clone(),
+            None => vec![self.received()],
+        }
+    }
+
+    fn received(&self) -> LifecycleEvent {
+        LifecycleEvent {
+            stage: LifecycleStage::Received,
+            at: self.0.received,
+        }
+    }
+
+    /// Emits an event of the stage.
+    ///
+    /// This is called by the server, [`Route`](crate::Route) and the
+    /// [`handler`](crate::handler) macro, and can be used to emit the events
+    /// of custom endpoints.
+    pub fn emit(&self, stage: LifecycleStage) {
+        let Some(inner) = self.0.inner.get() else {
+            return;
+        };
+        let event = LifecycleEvent {
+            stage,
+            at: Instant::now(),
+        };
+        let listeners = {
+            let mut inner = inner.lock();
+            inner.events.push(event);
+            inner.listeners.clone()
+        };
+        for listener in listeners {
+            listener(&event);
+        }
+    }
+
+    fn has_listeners(&self) -> bool {
+        self.0.inner.get().is_some()
+    }
+
+    /// Emits the [`ResponseStarted`](LifecycleStage::ResponseStarted) event,
+    /// and the [`Completed`](LifecycleStage::Completed) event once the body of
+    /// the returned response is dropped.
+    pub(crate) fn finish(&self, resp: Response) -> http::Response<BoxBody> {
+        if !self.has_listeners() {
+            return resp.into();
+        }
+
+        self.emit(LifecycleStage::ResponseStarted);
+        let resp: http::Response<BoxBody> = resp.into();
+        resp.map(|body| {
+            CompletionBody {
+                inner: body,
+                lifecycle: self.clone(),
+            }
+            .boxed()
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for RequestLifecycle {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req.lifecycle().clone())
+    }
+}
+
+struct CompletionBody {
+    inner: BoxBody,
+    lifecycle: RequestLifecycle,
+}
+
+impl Drop for CompletionBody {
+    fn drop(&mut self) {
+        self.lifecycle.emit(LifecycleStage::Completed);
+    }
+}
+
+impl hyper::body::Body for CompletionBody {
+    type Data = Bytes;
+    type Error = std::io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn completed_on_drop() {
+        let lifecycle = RequestLifecycle::default();
+        let stages = Arc::new(Mutex::new(Vec::new()));
+        lifecycle.subscribe({
+            let stages = stages.clone();
+            move |event| stages.lock().push(event.stage)
+        });
+        assert_eq!(*stages.lock(), [LifecycleStage::Received]);
+
+        let resp = lifecycle.finish(Response::builder().body("hello"));
+        assert_eq!(
+            *stages.lock(),
+            [LifecycleStage::Received, LifecycleStage::ResponseStarted]
+        );
+
+        let data = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(data, "hello");
+        assert_eq!(
+            *stages.lock(),
+            [
+                LifecycleStage::Received,
+                LifecycleStage::ResponseStarted,
+                LifecycleStage::Completed
+            ]
+        );
+    }
+
+    #[test]
+    fn not_recorded_without_listeners() {
+        let lifecycle = RequestLifecycle::default();
+        lifecycle.emit(LifecycleStage::Routed);
+        assert!(!lifecycle.has_listeners());
+        let stages = |lifecycle: &RequestLifecycle| {
+            lifecycle
+                .events()
+                .into_iter()
+                .map(|event| event.stage)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(stages(&lifecycle), [LifecycleStage::Received]);
+
+        lifecycle.subscribe(|_| {});
+        lifecycle.emit(LifecycleStage::Extracted);
+        assert_eq!(
+            stages(&lifecycle),
+            [LifecycleStage::Received, LifecycleStage::Extracted]
+        );
+    }
+}
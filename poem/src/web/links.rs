@@ -0,0 +1,326 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    http::{header, StatusCode},
+    IntoResponse, Response,
+};
+
+/// Characters that must be percent-encoded in a path segment.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// A registry of named routes, used to build the URLs of the links instead of
+/// formatting them by hand.
+///
+/// The patterns use the same syntax as [`Route`](crate::Route), `:name` and
+/// `:name<regex>` are replaced by the percent-encoded parameter and `*name`
+/// by the parameter as-is. Add it to the application with
+/// [`EndpointExt::data`](crate::EndpointExt::data) and extract it with
+/// [`Data<&RouteNames>`](crate::web::Data).
+///
+/// # Example
+///
+/// ```
+/// use poem::web::RouteNames;
+///
+/// let names = RouteNames::new()
+///     .add("users", "/users")
+///     .add("user", "/users/:id");
+///
+/// assert_eq!(names.url_for("user", &[("id", "1")]).as_deref(), Some("/users/1"));
+/// assert_eq!(names.url_for("user", &[]), None);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct RouteNames {
+    routes: Arc<BTreeMap<String, String>>,
+}
+
+impl RouteNames {
+    /// Create an empty `RouteNames`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the route `name` with the path `pattern`.
+    #[must_use]
+    pub fn add(mut self, name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.routes).insert(name.into(), pattern.into());
+        self
+    }
+
+    /// Returns the path pattern of the route `name`.
+    pub fn pattern(&self, name: &str) -> Option<&str> {
+        self.routes.get(name).map(String::as_str)
+    }
+
+    /// Builds the path of the route `name` with the parameters.
+    ///
+    /// Returns `None` if the route does not exist or a parameter of the
+    /// pattern is missing.
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Option<String> {
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+        };
+
+        let mut url = String::new();
+        for (idx, segment) in self.pattern(name)?.split('/').enumerate() {
+            if idx > 0 {
+                url.push('/');
+            }
+            if let Some(name) = segment.strip_prefix(':') {
+                let name = name.split('<').next().unwrap_or_default();
+                url.extend(utf8_percent_encode(param(name)?, SEGMENT));
+            } else if let Some(name) = segment.strip_prefix('*') {
+                url.push_str(param(name)?.trim_start_matches('/'));
+            } else {
+                url.push_str(segment);
+            }
+        }
+        Some(url)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Link {
+    href: String,
+}
+
+/// A JSON response decorated with the hypermedia links of the resource.
+///
+/// The links are added to the `_links` field of the serialized object, as
+/// `{"_links": {"self": {"href": "/users/1"}}}`. Values that do not serialize
+/// to an object, such as the pages of a collection, are wrapped in the `items`
+/// field.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     get, handler,
+///     test::TestClient,
+///     web::{Data, Linked, Path, RouteNames},
+///     EndpointExt, Route,
+/// };
+/// use serde::Serialize;
+/// use serde_json::json;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn user(Path(id): Path<String>, names: Data<&RouteNames>) -> Linked<User> {
+///     Linked::new(User {
+///         name: "sunli".to_string(),
+///     })
+///     .route(names.0, "self", "user", &[("id", &id)])
+///     .route(names.0, "collection", "users", &[])
+/// }
+///
+/// let names = RouteNames::new()
+///     .add("users", "/users")
+///     .add("user", "/users/:id");
+/// let app = Route::new().at("/users/:id", get(user)).data(names);
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/users/1").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_json(json!({
+///     "name": "sunli",
+///     "_links": {
+///         "self": { "href": "/users/1" },
+///         "collection": { "href": "/users" },
+///     },
+/// }))
+/// .await;
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Linked<T> {
+    value: T,
+    links: BTreeMap<String, Link>,
+}
+
+impl<T> Linked<T> {
+    /// Create a `Linked` response without links.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            links: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a link with the relation `rel`, replacing the previous link of
+    /// the same relation.
+    #[must_use]
+    pub fn link(mut self, rel: impl Into<String>, href: impl Into<String>) -> Self {
+        self.links.insert(rel.into(), Link { href: href.into() });
+        self
+    }
+
+    /// Adds a link with the relation `rel` to the named route.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route does not exist or a parameter is missing.
+    #[must_use]
+    pub fn route(self, names: &RouteNames, rel: &str, name: &str, params: &[(&str, &str)]) -> Self {
+        let href = names
+            .url_for(name, params)
+            .unwrap_or_else(|| panic!("invalid route `{name}` for link `{rel}`"));
+        self.link(rel, href)
+    }
+
+    /// Adds a link with the relation `rel` to the named route if `cond` is
+    /// `true`, which is useful for the optional links such as `next`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the route does not exist or a parameter is missing.
+    #[must_use]
+    pub fn route_if(
+        self,
+        cond: bool,
+        names: &RouteNames,
+        rel: &str,
+        name: &str,
+        params: &[(&str, &str)],
+    ) -> Self {
+        if cond {
+            self.route(names, rel, name, params)
+        } else {
+            self
+        }
+    }
+}
+
+impl<T: Serialize + Send> IntoResponse for Linked<T> {
+    fn into_response(self) -> Response {
+        let links = serde_json::to_value(&self.links).unwrap_or_default();
+        let data = serde_json::to_value(&self.value).and_then(|value| {
+            let mut obj = match value {
+                Value::Object(obj) => obj,
+                value => {
+                    let mut obj = Map::new();
+                    obj.insert("items".to_string(), value);
+                    obj
+                }
+            };
+            obj.insert("_links".to_string(), links);
+            serde_json::to_vec(&obj)
+        });
+        match data {
+            Ok(data) => Response::builder()
+                .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+                .body(data),
+            Err(err) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        endpoint::make_sync,
+        get, handler,
+        test::TestClient,
+        web::{Data, Query},
+        EndpointExt, Route,
+    };
+
+    #[test]
+    fn url_for() {
+        let names = RouteNames::new()
+            .add("user", "/users/:id<\\d+>/posts/:post")
+            .add("file", "/files/*path");
+
+        assert_eq!(
+            names
+                .url_for("user", &[("id", "1"), ("post", "a b/c")])
+                .as_deref(),
+            Some("/users/1/posts/a%20b%2Fc")
+        );
+        assert_eq!(
+            names.url_for("file", &[("path", "/a/b.txt")]).as_deref(),
+            Some("/files/a/b.txt")
+        );
+        assert_eq!(names.url_for("user", &[("id", "1")]), None);
+        assert_eq!(names.url_for("post", &[]), None);
+    }
+
+    #[tokio::test]
+    async fn collection() {
+        #[derive(serde::Deserialize)]
+        struct Params {
+            page: u32,
+        }
+
+        #[handler(internal)]
+        fn users(Query(params): Query<Params>, names: Data<&RouteNames>) -> Linked<Vec<u32>> {
+            let page = params.page.to_string();
+            let next = (params.page + 1).to_string();
+            Linked::new(vec![params.page])
+                .link(
+                    "self",
+                    format!("{}?page={page}", names.url_for("users", &[]).unwrap()),
+                )
+                .route_if(params.page < 2, names.0, "next", "page", &[("page", &next)])
+        }
+
+        let names = RouteNames::new()
+            .add("users", "/users")
+            .add("page", "/users/pages/:page");
+        let cli = TestClient::new(Route::new().at("/users", get(users)).data(names));
+
+        cli.get("/users")
+            .query("page", &1)
+            .send()
+            .await
+            .assert_json(json!({
+                "items": [1],
+                "_links": {
+                    "self": { "href": "/users?page=1" },
+                    "next": { "href": "/users/pages/2" },
+                },
+            }))
+            .await;
+        cli.get("/users")
+            .query("page", &2)
+            .send()
+            .await
+            .assert_json(json!({
+                "items": [2],
+                "_links": { "self": { "href": "/users?page=2" } },
+            }))
+            .await;
+
+        let cli = TestClient::new(make_sync(|_| Linked::new(json!({ "a": 1 }))));
+        let resp = cli.get("/").send().await;
+        resp.assert_content_type("application/json; charset=utf-8");
+        resp.assert_json(json!({ "a": 1, "_links": {} })).await;
+    }
+}
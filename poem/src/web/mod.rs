@@ -2,27 +2,67 @@
 
 mod accept;
 mod addr;
+mod attachment;
+mod batch;
+mod cache_control;
+mod cached;
+#[cfg(feature = "rustls")]
+mod client_cert;
 #[cfg(feature = "compression")]
 mod compress;
+mod conditional;
+mod connect_info;
+mod content_disposition;
 #[cfg(feature = "cookie")]
 #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
 pub mod cookie;
+#[cfg(feature = "csv")]
+mod csv;
 mod data;
+#[cfg(feature = "cookie")]
+mod flash;
 mod form;
+#[cfg(feature = "cookie")]
+mod form_state;
+mod forwarded;
+mod hypermedia;
 mod json;
+mod lifecycle;
+mod links;
 #[cfg(feature = "multipart")]
 mod multipart;
+mod negotiate;
+mod odata;
 mod path;
+#[cfg(feature = "rustls")]
+mod peer_identity;
+#[cfg(any(feature = "compression", feature = "static-files"))]
+mod precompressed;
+mod precondition;
+mod problem_details;
 mod query;
+mod range;
 mod real_ip;
 mod redirect;
+#[cfg(feature = "request-id")]
+mod request_id;
+#[cfg(feature = "socketio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "socketio")))]
+pub mod socketio;
 #[cfg(feature = "sse")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sse")))]
 pub mod sse;
 #[cfg(feature = "static-files")]
 mod static_file;
+mod status;
+mod streaming;
+mod subrequest;
 #[cfg(feature = "tempfile")]
 mod tempfile;
+mod template;
+mod tenant;
+#[cfg(feature = "timezone")]
+mod timezone;
 #[cfg(feature = "xml")]
 mod xml;
 #[cfg(feature = "yaml")]
@@ -42,18 +82,50 @@ use std::{convert::Infallible, fmt::Debug};
 pub use async_compression::Level as CompressionLevel;
 use bytes::Bytes;
 use http::header;
+#[cfg(feature = "multipart")]
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+pub use poem_derive::Multipart;
 
+#[cfg(feature = "rustls")]
+pub use self::client_cert::{ClientCert, SubjectAltName};
+#[cfg(feature = "compression")]
+pub(crate) use self::compress::StatsHandler;
 #[cfg(feature = "compression")]
-pub use self::compress::{Compress, CompressionAlgo};
+pub use self::compress::{Compress, CompressionAlgo, CompressionStats};
 #[cfg(feature = "csrf")]
 pub use self::csrf::{CsrfToken, CsrfVerifier};
+#[cfg(feature = "csv")]
+pub use self::csv::CsvResponse;
+#[cfg(feature = "cookie")]
+pub(crate) use self::flash::add_flash_cookie;
+#[cfg(feature = "cookie")]
+pub use self::flash::{Flash, FlashLevel, FlashMessage, WithFlash};
+#[cfg(feature = "cookie")]
+pub use self::form_state::{FormState, WithFormState};
 #[cfg(feature = "multipart")]
-pub use self::multipart::{Field, Multipart};
-pub(crate) use self::path::PathDeserializer;
+pub use self::multipart::{Field, Multipart, MultipartField, Upload};
+#[cfg(feature = "rustls")]
+pub use self::peer_identity::{PeerIdentity, SpiffeId};
+#[cfg(feature = "compression")]
+pub use self::precompressed::Precompressed;
+#[cfg(any(feature = "compression", feature = "static-files"))]
+pub(crate) use self::precompressed::{negotiate_encoding, vary_accept_encoding};
+#[cfg(feature = "request-id")]
+pub use self::request_id::RequestId;
 #[cfg(feature = "static-files")]
-pub use self::static_file::{StaticFileRequest, StaticFileResponse};
+pub(crate) use self::static_file::{guess_content_type, metadata_etag};
+#[cfg(feature = "static-files")]
+pub use self::static_file::{NamedFile, StaticFileRequest, StaticFileResponse};
 #[cfg(feature = "tempfile")]
 pub use self::tempfile::TempFile;
+#[cfg(feature = "askama")]
+pub use self::template::AskamaTemplate;
+#[cfg(feature = "handlebars")]
+pub use self::template::HandlebarsTemplate;
+#[cfg(feature = "tera")]
+pub use self::template::TeraTemplate;
+#[cfg(feature = "timezone")]
+pub use self::timezone::{Timezone, TimezoneConfig};
 #[cfg(feature = "xml")]
 pub use self::xml::Xml;
 #[cfg(feature = "yaml")]
@@ -61,18 +133,40 @@ pub use self::yaml::Yaml;
 pub use self::{
     accept::Accept,
     addr::{LocalAddr, RemoteAddr},
+    attachment::Attachment,
+    batch::BatchResult,
+    cache_control::{CacheControl, WithCacheControl},
+    cached::Cached,
+    conditional::Conditional,
+    connect_info::{ConnectInfo, ConnectInfoMap},
+    content_disposition::{sanitize_filename, ContentDisposition, DispositionType},
     data::Data,
-    form::Form,
+    form::{Form, FormSource, UrlEncodedBody},
+    forwarded::{Forwarded, ForwardedElement, ForwardedNode, ForwardedNodeName},
+    hypermedia::{HxRequest, HxResponse, TurboRequest, TurboStream},
     json::Json,
-    path::Path,
+    lifecycle::{LifecycleEvent, LifecycleStage, RequestLifecycle},
+    links::{Linked, RouteNames},
+    negotiate::Negotiate,
+    odata::{CompareOp, Filter, Literal, OData, ODataEntity, ODataQuery, OrderBy, StringFunction},
+    path::{Path, RawPathParam},
+    precondition::Preconditions,
+    problem_details::ProblemDetails,
     query::Query,
+    range::{PartialContent, Range},
     real_ip::RealIp,
     redirect::Redirect,
+    status::{Accepted, Created, NoContent},
+    streaming::Streaming,
+    subrequest::Subrequest,
+    template::{StreamingTemplate, SuspenseTemplate, Template, TemplateResponse},
+    tenant::{CachedTenantProvider, Tenant, TenantId, TenantProvider},
     typed_header::TypedHeader,
 };
+pub(crate) use self::{path::PathDeserializer, subrequest::SubrequestDispatcher};
 use crate::{
     body::Body,
-    error::{ReadBodyError, Result},
+    error::{InvalidHeaderValueError, ReadBodyError, Result},
     http::{
         header::{HeaderMap, HeaderName},
         HeaderValue, Method, StatusCode, Uri, Version,
@@ -81,6 +175,19 @@ use crate::{
     response::Response,
 };
 
+/// Creates a header value from a string that may be controlled by the user,
+/// rejecting line breaks and other bytes that are not allowed in header values.
+pub(crate) fn checked_header_value(
+    name: &str,
+    value: &str,
+) -> Result<HeaderValue, InvalidHeaderValueError> {
+    if value.bytes().any(|b| b == b'\r' || b == b'\n') {
+        return Err(InvalidHeaderValueError::LineBreak(name.to_string()));
+    }
+    HeaderValue::from_str(value)
+        .map_err(|_| InvalidHeaderValueError::InvalidCharacter(name.to_string()))
+}
+
 /// The body parameter type of [`FromRequest::from_request`] method.
 #[derive(Default)]
 pub struct RequestBody(Option<Body>);
@@ -396,6 +503,16 @@ pub trait FromRequest<'a>: Sized {
 ///    Convert `T` to response and set the specified status code [`StatusCode`],
 /// and then merge the specified [`HeaderMap`].
 ///
+/// - **([(HeaderName, HeaderValue); N], T)**
+///
+///    Convert `T` to response and set the specified headers, replacing the
+/// existing values. A header name repeated in the array gets all its values.
+///
+/// - **(StatusCode, [(HeaderName, HeaderValue); N], T)**
+///
+///    Convert `T` to response and set the specified status code [`StatusCode`],
+/// and then set the specified headers like the previous one.
+///
 /// - **Response**
 ///
 ///    The implementation for [`Response`] always returns itself.
@@ -489,6 +606,124 @@ pub trait IntoResponse: Send {
         }
     }
 
+    /// Wrap an `impl IntoResponse` to add a `Set-Cookie` header.
+    ///
+    /// The header is appended, so the existing `Set-Cookie` headers of the
+    /// response are kept. If the cookie contains a line break or any other
+    /// character that is not allowed in a header value, an
+    /// [`InvalidHeaderValueError`] response is returned instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     http::header,
+    ///     web::cookie::{Cookie, SameSite},
+    ///     IntoResponse,
+    /// };
+    ///
+    /// let mut cookie = Cookie::new_with_str("session", "abc");
+    /// cookie.set_path("/");
+    /// cookie.set_http_only(true);
+    /// cookie.set_secure(true);
+    /// cookie.set_same_site(SameSite::Strict);
+    ///
+    /// let resp = "hello"
+    ///     .with_header(header::SET_COOKIE, "theme=dark")
+    ///     .with_cookie(cookie)
+    ///     .into_response();
+    /// let cookies = resp
+    ///     .headers()
+    ///     .get_all(header::SET_COOKIE)
+    ///     .iter()
+    ///     .map(|value| value.to_str().unwrap())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     cookies,
+    ///     [
+    ///         "theme=dark",
+    ///         "session=abc; HttpOnly; SameSite=Strict; Secure; Path=/"
+    ///     ]
+    /// );
+    /// ```
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    fn with_cookie(self, cookie: cookie::Cookie) -> WithCookie<Self>
+    where
+        Self: Sized,
+    {
+        WithCookie {
+            inner: self,
+            cookie,
+        }
+    }
+
+    /// Wrap an `impl IntoResponse` to add a flash message, which can be
+    /// extracted with [`Flash`] in the next request.
+    ///
+    /// The messages added by the nested `with_flash` calls are kept. The
+    /// messages are stored by the
+    /// [`CookieJarManager`](crate::middleware::CookieJarManager) middleware,
+    /// which requires a key.
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    fn with_flash(self, level: FlashLevel, message: impl Into<String>) -> WithFlash<Self>
+    where
+        Self: Sized,
+    {
+        WithFlash {
+            inner: self,
+            message: FlashMessage {
+                level,
+                message: message.into(),
+            },
+        }
+    }
+
+    /// Wrap an `impl IntoResponse` to keep the submitted values and the
+    /// validation errors of a form, which can be extracted with [`FormState`]
+    /// in the next request.
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    fn with_form_state(self, state: FormState) -> WithFormState<Self>
+    where
+        Self: Sized,
+    {
+        WithFormState { inner: self, state }
+    }
+
+    /// Wrap an `impl IntoResponse` to set the `Cache-Control` header, which
+    /// replaces the existing `Cache-Control` headers of the response.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use poem::{http::header, web::CacheControl, IntoResponse};
+    ///
+    /// let resp = "hello"
+    ///     .with_cache_control(
+    ///         CacheControl::new()
+    ///             .private()
+    ///             .max_age(Duration::from_secs(60)),
+    ///     )
+    ///     .into_response();
+    /// assert_eq!(
+    ///     resp.header(header::CACHE_CONTROL),
+    ///     Some("private, max-age=60")
+    /// );
+    /// ```
+    fn with_cache_control(self, cache_control: CacheControl) -> WithCacheControl<Self>
+    where
+        Self: Sized,
+    {
+        WithCacheControl {
+            inner: self,
+            cache_control,
+        }
+    }
+
     /// Wrap an `impl IntoResponse` to with a new content type.
     ///
     /// # Example
@@ -582,6 +817,28 @@ impl<T: IntoResponse> IntoResponse for WithHeader<T> {
     }
 }
 
+/// Returned by [`with_cookie`](IntoResponse::with_cookie) method.
+#[cfg(feature = "cookie")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+pub struct WithCookie<T> {
+    inner: T,
+    cookie: cookie::Cookie,
+}
+
+#[cfg(feature = "cookie")]
+impl<T: IntoResponse> IntoResponse for WithCookie<T> {
+    fn into_response(self) -> Response {
+        match self.cookie.to_header_value() {
+            Ok(value) => {
+                let mut resp = self.inner.into_response();
+                resp.headers_mut().append(header::SET_COOKIE, value);
+                resp
+            }
+            Err(err) => crate::Error::from(err).into_response(),
+        }
+    }
+}
+
 /// Returned by [`with_content_type`](IntoResponse::with_content_type) method.
 pub struct WithContentType<T> {
     inner: T,
@@ -599,7 +856,7 @@ impl<T: IntoResponse> IntoResponse for WithContentType<T> {
     }
 }
 
-/// Returned by [`with_header`](IntoResponse::with_status) method.
+/// Returned by [`with_status`](IntoResponse::with_status) method.
 pub struct WithStatus<T> {
     inner: T,
     status: StatusCode,
@@ -716,6 +973,27 @@ impl<T: IntoResponse> IntoResponse for (HeaderMap, T) {
     }
 }
 
+impl<T: IntoResponse, const N: usize> IntoResponse for ([(HeaderName, HeaderValue); N], T) {
+    fn into_response(self) -> Response {
+        let mut resp = self.1.into_response();
+        resp.headers_mut()
+            .extend(self.0.into_iter().collect::<HeaderMap>());
+        resp
+    }
+}
+
+impl<T: IntoResponse, const N: usize> IntoResponse
+    for (StatusCode, [(HeaderName, HeaderValue); N], T)
+{
+    fn into_response(self) -> Response {
+        let mut resp = self.2.into_response();
+        resp.set_status(self.0);
+        resp.headers_mut()
+            .extend(self.1.into_iter().collect::<HeaderMap>());
+        resp
+    }
+}
+
 /// An HTML response.
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct Html<T>(pub T);
@@ -900,6 +1178,66 @@ mod tests {
         );
         assert_eq!(resp.into_body().into_string().await.unwrap(), "abc");
 
+        // ([(HeaderName, HeaderValue); N], T)
+        let resp = (
+            [
+                (header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+                (header::VARY, HeaderValue::from_static("Accept")),
+                (header::VARY, HeaderValue::from_static("Origin")),
+            ],
+            "abc",
+        )
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL),
+            Some(&HeaderValue::from_static("no-cache"))
+        );
+        assert_eq!(resp.headers().get_all(header::VARY).iter().count(), 2);
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "abc");
+
+        let resp = (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )],
+            "{}",
+        )
+            .into_response();
+        assert_eq!(
+            resp.headers()
+                .get_all(header::CONTENT_TYPE)
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![HeaderValue::from_static("application/json")]
+        );
+
+        // (StatusCode, [(HeaderName, HeaderValue); N], T)
+        let resp = (
+            StatusCode::CREATED,
+            [(header::LOCATION, HeaderValue::from_static("/items/1"))],
+            "abc",
+        )
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(
+            resp.headers().get(header::LOCATION),
+            Some(&HeaderValue::from_static("/items/1"))
+        );
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "abc");
+
+        let resp = (
+            StatusCode::CREATED,
+            [(header::CONTENT_TYPE, HeaderValue::from_static("text/csv"))],
+            "a,b",
+        )
+            .into_response();
+        assert_eq!(
+            resp.headers().get_all(header::CONTENT_TYPE).iter().count(),
+            1
+        );
+        assert_eq!(resp.content_type(), Some("text/csv"));
+
         // StatusCode
         let resp = StatusCode::CREATED.into_response();
         assert_eq!(resp.status(), StatusCode::CREATED);
@@ -958,6 +1296,35 @@ mod tests {
             Some(&HeaderValue::from_static("789"))
         );
 
+        // WithCookie
+        #[cfg(feature = "cookie")]
+        {
+            let mut cookie = cookie::Cookie::new_with_str("a", "1");
+            cookie.set_max_age(std::time::Duration::from_secs(60));
+            cookie.set_domain("example.com");
+            let resp = Response::builder()
+                .header(header::SET_COOKIE, "b=2")
+                .finish()
+                .with_cookie(cookie)
+                .into_response();
+            assert_eq!(
+                resp.headers()
+                    .get_all(header::SET_COOKIE)
+                    .iter()
+                    .collect::<Vec<_>>(),
+                [
+                    &HeaderValue::from_static("b=2"),
+                    &HeaderValue::from_static("a=1; Domain=example.com; Max-Age=60")
+                ]
+            );
+
+            let mut cookie = cookie::Cookie::new_with_str("a", "1");
+            cookie.set_path("/\r\nLocation: /evil");
+            let resp = "hello".with_cookie(cookie).into_response();
+            assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            assert!(resp.headers().get(header::SET_COOKIE).is_none());
+        }
+
         // WithStatus
         let resp = StatusCode::CONFLICT
             .with_status(StatusCode::BAD_GATEWAY)
@@ -1,10 +1,12 @@
 use std::{
-    fmt::{self, Debug, Formatter},
+    fmt::{self, Debug, Display, Formatter},
     str::FromStr,
 };
 
+use bytes::Bytes;
 use futures_util::TryStreamExt;
 use mime::Mime;
+use serde::de::DeserializeOwned;
 #[cfg(feature = "tempfile")]
 use tokio::fs::File;
 use tokio::io::{AsyncRead, AsyncReadExt};
@@ -58,6 +60,17 @@ impl Field {
 
     /// Get the full data of the field as bytes.
     pub async fn bytes(self) -> Result<Vec<u8>, ParseMultipartError> {
+        self.bytes_with_limit(None).await
+    }
+
+    /// Get the full data of the field as bytes, returns
+    /// [`ParseMultipartError::FieldTooLarge`] if the data exceeds `limit`
+    /// bytes.
+    pub(crate) async fn bytes_with_limit(
+        self,
+        limit: Option<usize>,
+    ) -> Result<Vec<u8>, ParseMultipartError> {
+        let name = self.name().unwrap_or_default().to_string();
         let mut data = Vec::new();
         let mut buf = [0; 2048];
         let mut reader = self.into_async_read();
@@ -65,6 +78,11 @@ impl Field {
             let sz = reader.read(&mut buf[..]).await?;
             if sz > 0 {
                 data.extend_from_slice(&buf[..sz]);
+                if let Some(limit) = limit {
+                    if data.len() > limit {
+                        return Err(ParseMultipartError::FieldTooLarge { name, limit });
+                    }
+                }
             } else {
                 break;
             }
@@ -79,13 +97,53 @@ impl Field {
         Ok(String::from_utf8(self.bytes().await?)?)
     }
 
+    #[doc(hidden)]
+    pub async fn __parse_from_str<T>(self, limit: Option<usize>) -> Result<T, ParseMultipartError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let name = self.name().unwrap_or_default().to_string();
+        let text = String::parse_field(self, limit).await?;
+        text.parse().map_err(|err: T::Err| ParseMultipartError::ParseField {
+            name,
+            reason: err.to_string(),
+        })
+    }
+
+    #[doc(hidden)]
+    pub async fn __parse_json<T: DeserializeOwned>(
+        self,
+        limit: Option<usize>,
+    ) -> Result<T, ParseMultipartError> {
+        let name = self.name().unwrap_or_default().to_string();
+        let data = self.bytes_with_limit(limit).await?;
+        serde_json::from_slice(&data).map_err(|err| ParseMultipartError::ParseField {
+            name,
+            reason: err.to_string(),
+        })
+    }
+
     /// Write the full field data to a temporary file and return it.
     #[cfg(feature = "tempfile")]
     #[cfg_attr(docsrs, doc(cfg(feature = "tempfile")))]
     pub async fn tempfile(self) -> Result<File, ParseMultipartError> {
-        let mut reader = self.into_async_read();
+        self.tempfile_with_limit(None).await
+    }
+
+    #[cfg(feature = "tempfile")]
+    async fn tempfile_with_limit(self, limit: Option<usize>) -> Result<File, ParseMultipartError> {
+        let name = self.name().unwrap_or_default().to_string();
         let mut file = tokio::fs::File::from_std(::libtempfile::tempfile()?);
-        tokio::io::copy(&mut reader, &mut file).await?;
+        let mut reader = self
+            .into_async_read()
+            .take(limit.map(|limit| limit as u64 + 1).unwrap_or(u64::MAX));
+        let size = tokio::io::copy(&mut reader, &mut file).await?;
+        if let Some(limit) = limit {
+            if size > limit as u64 {
+                return Err(ParseMultipartError::FieldTooLarge { name, limit });
+            }
+        }
         file.seek(SeekFrom::Start(0)).await?;
         Ok(file)
     }
@@ -167,6 +225,173 @@ impl Multipart {
     }
 }
 
+/// An uploaded file in a multipart form.
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+pub struct Upload {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    data: Vec<u8>,
+}
+
+impl Debug for Upload {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Upload");
+        if let Some(file_name) = self.file_name() {
+            d.field("file_name", &file_name);
+        }
+        if let Some(content_type) = self.content_type() {
+            d.field("content_type", &content_type);
+        }
+        d.field("size", &self.size());
+        d.finish()
+    }
+}
+
+impl Upload {
+    /// Get the content type of the file.
+    #[inline]
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// The file name found in the `Content-Disposition` header.
+    #[inline]
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Returns the size of the file in bytes.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the file data.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consumes this file to return the file data.
+    #[inline]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Represents a type that can be parsed from a field of a multipart form.
+///
+/// This trait is used by the [`Multipart`](derive@crate::web::Multipart)
+/// derive macro.
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+#[async_trait::async_trait]
+pub trait MultipartField: Sized + Send {
+    /// Parse from the first field with the specified name.
+    ///
+    /// If `limit` is specified, the field data must not exceed `limit` bytes.
+    async fn parse_field(field: Field, limit: Option<usize>) -> Result<Self, ParseMultipartError>;
+
+    /// Parse from a repeated field with the specified name.
+    async fn parse_repeated_field(
+        self,
+        field: Field,
+        _limit: Option<usize>,
+    ) -> Result<Self, ParseMultipartError> {
+        Err(ParseMultipartError::DuplicateField(
+            field.name().unwrap_or_default().to_string(),
+        ))
+    }
+
+    /// Returns the value used when the field is missing, or [`None`] if the
+    /// field is required.
+    fn missing_value() -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! impl_multipart_field_from_str {
+    ($($ty:ty),*) => {
+        $(
+        #[async_trait::async_trait]
+        impl MultipartField for $ty {
+            async fn parse_field(field: Field, limit: Option<usize>) -> Result<Self, ParseMultipartError> {
+                field.__parse_from_str(limit).await
+            }
+        }
+        )*
+    };
+}
+
+impl_multipart_field_from_str!(
+    bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+#[async_trait::async_trait]
+impl MultipartField for String {
+    async fn parse_field(field: Field, limit: Option<usize>) -> Result<Self, ParseMultipartError> {
+        Ok(String::from_utf8(field.bytes_with_limit(limit).await?)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl MultipartField for Bytes {
+    async fn parse_field(field: Field, limit: Option<usize>) -> Result<Self, ParseMultipartError> {
+        Ok(field.bytes_with_limit(limit).await?.into())
+    }
+}
+
+#[async_trait::async_trait]
+impl MultipartField for Upload {
+    async fn parse_field(field: Field, limit: Option<usize>) -> Result<Self, ParseMultipartError> {
+        let file_name = field.file_name().map(ToString::to_string);
+        let content_type = field.content_type().map(ToString::to_string);
+        Ok(Upload {
+            file_name,
+            content_type,
+            data: field.bytes_with_limit(limit).await?,
+        })
+    }
+}
+
+#[cfg(feature = "tempfile")]
+#[async_trait::async_trait]
+impl MultipartField for crate::web::TempFile {
+    async fn parse_field(field: Field, limit: Option<usize>) -> Result<Self, ParseMultipartError> {
+        Ok(crate::web::TempFile(field.tempfile_with_limit(limit).await?))
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: MultipartField> MultipartField for Option<T> {
+    async fn parse_field(field: Field, limit: Option<usize>) -> Result<Self, ParseMultipartError> {
+        Ok(Some(T::parse_field(field, limit).await?))
+    }
+
+    fn missing_value() -> Option<Self> {
+        Some(None)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: MultipartField> MultipartField for Vec<T> {
+    async fn parse_field(field: Field, limit: Option<usize>) -> Result<Self, ParseMultipartError> {
+        Ok(vec![T::parse_field(field, limit).await?])
+    }
+
+    async fn parse_repeated_field(
+        mut self,
+        field: Field,
+        limit: Option<usize>,
+    ) -> Result<Self, ParseMultipartError> {
+        self.push(T::parse_field(field, limit).await?);
+        Ok(self)
+    }
+
+    fn missing_value() -> Option<Self> {
+        Some(Vec::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,4 +443,74 @@ mod tests {
             .await;
         resp.assert_status_is_ok();
     }
+
+    #[tokio::test]
+    async fn test_multipart_derive() {
+        #[derive(crate::web::Multipart)]
+        #[multipart(internal)]
+        struct Form {
+            name: String,
+            #[multipart(rename = "value")]
+            number: i32,
+            tags: Vec<String>,
+            comment: Option<String>,
+            #[multipart(json)]
+            meta: serde_json::Value,
+            file: Upload,
+        }
+
+        #[handler(internal)]
+        async fn index(form: Form) {
+            assert_eq!(form.name, "abc");
+            assert_eq!(form.number, 100);
+            assert_eq!(form.tags, vec!["a".to_string(), "b".to_string()]);
+            assert_eq!(form.comment, None);
+            assert_eq!(form.meta, serde_json::json!({ "a": 1 }));
+            assert_eq!(form.file.file_name(), Some("a.txt"));
+            assert_eq!(form.file.content_type(), Some("text/plain"));
+            assert_eq!(form.file.as_bytes(), b"hello");
+        }
+
+        let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nabc\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"value\"\r\n\r\n100\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\na\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\nb\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"meta\"\r\n\r\n{\"a\":1}\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\nContent-Type: text/plain\r\n\r\nhello\r\n--X-BOUNDARY--\r\n";
+        let cli = TestClient::new(index);
+        cli.post("/")
+            .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+            .body(data)
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+
+    #[tokio::test]
+    async fn test_multipart_derive_errors() {
+        #[derive(crate::web::Multipart)]
+        #[multipart(internal)]
+        #[allow(dead_code)]
+        struct Form {
+            #[multipart(limit = 3)]
+            name: String,
+            value: i32,
+        }
+
+        #[handler(internal)]
+        async fn index(_form: Form) {}
+
+        let cli = TestClient::new(index);
+
+        let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nabc\r\n--X-BOUNDARY--\r\n";
+        cli.post("/")
+            .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+            .body(data)
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"value\"\r\n\r\n1\r\n--X-BOUNDARY--\r\n";
+        cli.post("/")
+            .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+            .body(data)
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
@@ -0,0 +1,276 @@
+use mime::Mime;
+use serde::Serialize;
+
+use crate::{
+    error::NotAcceptableError,
+    http::{header, StatusCode},
+    web::Accept,
+    IntoResponse, Response,
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Format {
+    Json,
+    #[cfg(feature = "xml")]
+    Xml,
+    #[cfg(feature = "yaml")]
+    Yaml,
+    #[cfg(feature = "msgpack")]
+    MsgPack,
+}
+
+/// The supported formats, in the order of preference for wildcards.
+const FORMATS: &[Format] = &[
+    Format::Json,
+    #[cfg(feature = "xml")]
+    Format::Xml,
+    #[cfg(feature = "yaml")]
+    Format::Yaml,
+    #[cfg(feature = "msgpack")]
+    Format::MsgPack,
+];
+
+impl Format {
+    fn media_types(&self) -> &'static [&'static str] {
+        match self {
+            Format::Json => &["application/json"],
+            #[cfg(feature = "xml")]
+            Format::Xml => &["application/xml", "text/xml"],
+            #[cfg(feature = "yaml")]
+            Format::Yaml => &["application/yaml", "application/x-yaml", "text/yaml"],
+            #[cfg(feature = "msgpack")]
+            Format::MsgPack => &[
+                "application/msgpack",
+                "application/x-msgpack",
+                "application/vnd.msgpack",
+            ],
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json; charset=utf-8",
+            #[cfg(feature = "xml")]
+            Format::Xml => "application/xml; charset=utf-8",
+            #[cfg(feature = "yaml")]
+            Format::Yaml => "application/yaml; charset=utf-8",
+            #[cfg(feature = "msgpack")]
+            Format::MsgPack => "application/msgpack",
+        }
+    }
+
+    fn is_exact_match(&self, mime: &Mime) -> bool {
+        self.media_types()
+            .iter()
+            .any(|media_type| mime.essence_str().eq_ignore_ascii_case(media_type))
+    }
+
+    fn matches(&self, mime: &Mime) -> bool {
+        if mime.subtype() != mime::STAR {
+            return self.is_exact_match(mime);
+        }
+        mime.type_() == mime::STAR
+            || self
+                .media_types()
+                .iter()
+                .any(|media_type| media_type.split('/').next() == Some(mime.type_().as_str()))
+    }
+
+    fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Format::Json => serde_json::to_vec(value).map_err(|err| err.to_string()),
+            #[cfg(feature = "xml")]
+            Format::Xml => crate::web::xml::to_xml_string(value)
+                .map(String::into_bytes)
+                .map_err(|err| err.to_string()),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::to_string(value)
+                .map(String::into_bytes)
+                .map_err(|err| err.to_string()),
+            #[cfg(feature = "msgpack")]
+            Format::MsgPack => rmp_serde::to_vec_named(value).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+fn is_refused(mime: &Mime) -> bool {
+    mime.get_param("q")
+        .and_then(|q| q.as_str().parse::<f32>().ok())
+        .map_or(false, |q| q <= 0.0)
+}
+
+/// Selects the format for the media types in the order of preference.
+fn select_format(accept: &[Mime]) -> Option<Format> {
+    if accept.is_empty() {
+        return Some(Format::Json);
+    }
+
+    let refused = accept
+        .iter()
+        .filter(|mime| is_refused(mime))
+        .collect::<Vec<_>>();
+    accept
+        .iter()
+        .filter(|mime| !is_refused(mime))
+        .find_map(|mime| {
+            FORMATS.iter().copied().find(|format| {
+                format.matches(mime) && !refused.iter().any(|mime| format.is_exact_match(mime))
+            })
+        })
+}
+
+/// A response that serializes `T` in the format preferred by the `Accept`
+/// header of the request.
+///
+/// The following formats are supported:
+///
+/// | Format  | Media types                                                              | Feature   |
+/// |---------|--------------------------------------------------------------------------|-----------|
+/// | JSON    | `application/json`                                                       |           |
+/// | XML     | `application/xml`, `text/xml`                                            | `xml`     |
+/// | YAML    | `application/yaml`, `application/x-yaml`, `text/yaml`                    | `yaml`    |
+/// | MsgPack | `application/msgpack`, `application/x-msgpack`, `application/vnd.msgpack` | `msgpack` |
+///
+/// JSON is used if the `Accept` header is missing. If none of the media types
+/// in the `Accept` header are supported, a [`NotAcceptableError`] response is
+/// returned.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::{header, StatusCode},
+///     test::TestClient,
+///     web::{Accept, Negotiate},
+/// };
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn index(accept: Accept) -> Negotiate<User> {
+///     accept.negotiate(User {
+///         name: "sunli".to_string(),
+///     })
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .header(header::ACCEPT, "text/html;q=0.9, application/json")
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("application/json; charset=utf-8");
+/// resp.assert_text(r#"{"name":"sunli"}"#).await;
+///
+/// let resp = cli.get("/").header(header::ACCEPT, "text/html").send().await;
+/// resp.assert_status(StatusCode::NOT_ACCEPTABLE);
+/// # });
+/// ```
+pub struct Negotiate<T> {
+    accept: Vec<Mime>,
+    value: T,
+}
+
+impl<T> Negotiate<T> {
+    /// Create a `Negotiate` response with the media types of the `Accept`
+    /// header.
+    pub fn new(accept: Accept, value: T) -> Self {
+        Self {
+            accept: accept.0,
+            value,
+        }
+    }
+}
+
+impl Accept {
+    /// Create a [`Negotiate`] response with the media types of this header.
+    pub fn negotiate<T>(self, value: T) -> Negotiate<T> {
+        Negotiate::new(self, value)
+    }
+}
+
+impl<T: Serialize + Send> IntoResponse for Negotiate<T> {
+    fn into_response(self) -> Response {
+        let format = match select_format(&self.accept) {
+            Some(format) => format,
+            None => {
+                return crate::Error::from(NotAcceptableError)
+                    .into_response()
+                    .with_header(header::VARY, "accept")
+                    .into_response()
+            }
+        };
+
+        match format.serialize(&self.value) {
+            Ok(data) => Response::builder()
+                .header(header::CONTENT_TYPE, format.content_type())
+                .header(header::VARY, "accept")
+                .body(data),
+            Err(err) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mimes(s: &[&str]) -> Vec<Mime> {
+        s.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn select() {
+        assert_eq!(select_format(&[]), Some(Format::Json));
+        assert_eq!(select_format(&mimes(&["*/*"])), Some(Format::Json));
+        assert_eq!(
+            select_format(&mimes(&["text/html", "application/*"])),
+            Some(Format::Json)
+        );
+        assert_eq!(select_format(&mimes(&["text/html"])), None);
+        assert_eq!(
+            select_format(&mimes(&["*/*", "application/json;q=0"])),
+            FORMATS.get(1).copied()
+        );
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn select_xml() {
+        assert_eq!(
+            select_format(&mimes(&["text/xml", "application/json;q=0.5"])),
+            Some(Format::Xml)
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tokio::test]
+    async fn negotiate_yaml() {
+        #[derive(Serialize)]
+        struct Value {
+            a: i32,
+        }
+
+        let resp =
+            Negotiate::new(Accept(mimes(&["application/yaml"])), Value { a: 1 }).into_response();
+        assert_eq!(resp.content_type(), Some("application/yaml; charset=utf-8"));
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "a: 1\n");
+    }
+
+    #[test]
+    fn not_acceptable() {
+        let resp = Negotiate::new(Accept(mimes(&["text/html"])), 1).into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "accept");
+    }
+}
@@ -0,0 +1,664 @@
+use std::{marker::PhantomData, ops::Deref};
+
+use crate::{error::ParseODataError, FromRequest, Request, RequestBody, Result};
+
+/// The maximum nesting depth of the `$filter` expressions.
+const MAX_DEPTH: usize = 32;
+
+/// The description of an entity that can be queried with [`OData`].
+///
+/// Only the fields in [`ODataEntity::FIELDS`] can be used in the query
+/// options, so the storage layers can translate the query without exposing
+/// other columns.
+pub trait ODataEntity {
+    /// The fields that can be filtered, selected and ordered by.
+    const FIELDS: &'static [&'static str];
+
+    /// The maximum value of `$top`.
+    const MAX_TOP: u64 = 100;
+}
+
+/// A comparison operator of the `$filter` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    /// `eq`
+    Eq,
+    /// `ne`
+    Ne,
+    /// `gt`
+    Gt,
+    /// `ge`
+    Ge,
+    /// `lt`
+    Lt,
+    /// `le`
+    Le,
+}
+
+/// A string function of the `$filter` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringFunction {
+    /// `contains(field, 'value')`
+    Contains,
+    /// `startswith(field, 'value')`
+    StartsWith,
+    /// `endswith(field, 'value')`
+    EndsWith,
+}
+
+/// A literal value of the `$filter` option.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// An integer.
+    Int(i64),
+    /// A floating point number.
+    Float(f64),
+    /// A string enclosed in single quotes.
+    String(String),
+}
+
+/// The expression of the `$filter` option.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Compares a field with a literal.
+    Compare {
+        /// The field.
+        field: String,
+        /// The operator.
+        op: CompareOp,
+        /// The literal.
+        value: Literal,
+    },
+    /// Calls a string function on a field.
+    Function {
+        /// The function.
+        function: StringFunction,
+        /// The field.
+        field: String,
+        /// The argument.
+        value: String,
+    },
+    /// Both expressions are true.
+    And(Box<Filter>, Box<Filter>),
+    /// Either expression is true.
+    Or(Box<Filter>, Box<Filter>),
+    /// The expression is false.
+    Not(Box<Filter>),
+}
+
+/// An item of the `$orderby` option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderBy {
+    /// The field.
+    pub field: String,
+    /// Whether the order is descending.
+    pub descending: bool,
+}
+
+/// The parsed OData query options.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ODataQuery {
+    /// The `$filter` option.
+    pub filter: Option<Filter>,
+    /// The `$select` option, empty if all the fields are selected.
+    pub select: Vec<String>,
+    /// The `$orderby` option.
+    pub orderby: Vec<OrderBy>,
+    /// The `$top` option.
+    pub top: Option<u64>,
+    /// The `$skip` option.
+    pub skip: Option<u64>,
+}
+
+impl ODataQuery {
+    /// Parses the query options from a query string, only the fields in
+    /// `fields` are allowed.
+    ///
+    /// The parameters that do not start with `$` are ignored.
+    pub fn parse(query: &str, fields: &[&str], max_top: u64) -> Result<Self, ParseODataError> {
+        let pairs: Vec<(String, String)> = serde_urlencoded::from_str(query)
+            .map_err(|err| ParseODataError::InvalidQuery(err.to_string()))?;
+        let mut res = Self::default();
+        let mut seen = Vec::new();
+
+        for (name, value) in &pairs {
+            if !name.starts_with('$') {
+                continue;
+            }
+            if seen.contains(&name) {
+                return Err(ParseODataError::DuplicateOption(name.clone()));
+            }
+            seen.push(name);
+
+            match name.as_str() {
+                "$filter" => {
+                    res.filter = Some(Parser::new(value, fields)?.parse_filter()?);
+                }
+                "$select" => {
+                    res.select = parse_list(value, "$select")?
+                        .into_iter()
+                        .map(|field| check_field(fields, field))
+                        .collect::<Result<_, _>>()?;
+                }
+                "$orderby" => {
+                    res.orderby = parse_list(value, "$orderby")?
+                        .into_iter()
+                        .map(|item| {
+                            let mut parts = item.split_whitespace();
+                            let field = check_field(fields, parts.next().unwrap_or_default())?;
+                            let descending = match parts.next() {
+                                None | Some("asc") => false,
+                                Some("desc") => true,
+                                Some(dir) => return Err(invalid("$orderby", dir)),
+                            };
+                            match parts.next() {
+                                Some(extra) => Err(invalid("$orderby", extra)),
+                                None => Ok(OrderBy { field, descending }),
+                            }
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                "$top" => {
+                    let top = parse_number(value, "$top")?;
+                    if top > max_top {
+                        return Err(ParseODataError::TopTooLarge(max_top));
+                    }
+                    res.top = Some(top);
+                }
+                "$skip" => res.skip = Some(parse_number(value, "$skip")?),
+                _ => return Err(ParseODataError::UnsupportedOption(name.clone())),
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+fn invalid(option: &'static str, message: impl Into<String>) -> ParseODataError {
+    ParseODataError::InvalidValue {
+        option,
+        message: message.into(),
+    }
+}
+
+fn check_field(fields: &[&str], field: &str) -> Result<String, ParseODataError> {
+    if fields.contains(&field) {
+        Ok(field.to_string())
+    } else {
+        Err(ParseODataError::FieldNotAllowed(field.to_string()))
+    }
+}
+
+fn parse_list<'a>(value: &'a str, option: &'static str) -> Result<Vec<&'a str>, ParseODataError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|item| {
+            if item.is_empty() {
+                Err(invalid(option, "empty item"))
+            } else {
+                Ok(item)
+            }
+        })
+        .collect()
+}
+
+fn parse_number(value: &str, option: &'static str) -> Result<u64, ParseODataError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid(option, format!("`{value}` is not a non-negative integer")))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Number(String),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, ParseODataError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => Token::Comma,
+                });
+            }
+            '\'' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\'') if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            value.push('\'');
+                        }
+                        Some('\'') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(invalid("$filter", "unterminated string")),
+                    }
+                }
+                tokens.push(Token::String(value));
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut value = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_digit() || matches!(c, '-' | '.' | 'e' | 'E' | '+')) {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut value = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || matches!(c, '_' | '.' | '/')) {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(value));
+            }
+            c => return Err(invalid("$filter", format!("unexpected character `{c}`"))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+    fields: &'a [&'a str],
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &str, fields: &'a [&'a str]) -> Result<Self, ParseODataError> {
+        Ok(Self {
+            tokens: tokenize(s)?,
+            pos: 0,
+            depth: 0,
+            fields,
+        })
+    }
+
+    fn parse_filter(mut self) -> Result<Filter, ParseODataError> {
+        let filter = self.parse_or()?;
+        match self.next() {
+            Some(token) => Err(unexpected(&token)),
+            None => Ok(filter),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Ident(ident)) if ident == keyword)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseODataError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(unexpected(&token)),
+            None => Err(invalid("$filter", "unexpected end of expression")),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), ParseODataError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(invalid("$filter", "expression is nested too deeply"));
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, ParseODataError> {
+        let mut filter = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, ParseODataError> {
+        let mut filter = self.parse_unary()?;
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            filter = Filter::And(Box::new(filter), Box::new(self.parse_unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, ParseODataError> {
+        self.enter()?;
+        let filter = if self.peek_keyword("not") {
+            self.pos += 1;
+            Filter::Not(Box::new(self.parse_unary()?))
+        } else {
+            self.parse_primary()?
+        };
+        self.depth -= 1;
+        Ok(filter)
+    }
+
+    fn parse_primary(&mut self) -> Result<Filter, ParseODataError> {
+        let ident = match self.next() {
+            Some(Token::LParen) => {
+                let filter = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                return Ok(filter);
+            }
+            Some(Token::Ident(ident)) => ident,
+            Some(token) => return Err(unexpected(&token)),
+            None => return Err(invalid("$filter", "unexpected end of expression")),
+        };
+
+        let function = match ident.as_str() {
+            "contains" => Some(StringFunction::Contains),
+            "startswith" => Some(StringFunction::StartsWith),
+            "endswith" => Some(StringFunction::EndsWith),
+            _ => None,
+        };
+        if let Some(function) = function {
+            self.expect(Token::LParen)?;
+            let field = self.parse_field()?;
+            self.expect(Token::Comma)?;
+            let value = match self.next() {
+                Some(Token::String(value)) => value,
+                Some(token) => return Err(unexpected(&token)),
+                None => return Err(invalid("$filter", "unexpected end of expression")),
+            };
+            self.expect(Token::RParen)?;
+            return Ok(Filter::Function {
+                function,
+                field,
+                value,
+            });
+        }
+
+        let field = check_field(self.fields, &ident)?;
+        let op = match self.next() {
+            Some(Token::Ident(op)) => match op.as_str() {
+                "eq" => CompareOp::Eq,
+                "ne" => CompareOp::Ne,
+                "gt" => CompareOp::Gt,
+                "ge" => CompareOp::Ge,
+                "lt" => CompareOp::Lt,
+                "le" => CompareOp::Le,
+                _ => return Err(invalid("$filter", format!("unknown operator `{op}`"))),
+            },
+            Some(token) => return Err(unexpected(&token)),
+            None => return Err(invalid("$filter", "unexpected end of expression")),
+        };
+        let value = self.parse_literal()?;
+        Ok(Filter::Compare { field, op, value })
+    }
+
+    fn parse_field(&mut self) -> Result<String, ParseODataError> {
+        match self.next() {
+            Some(Token::Ident(ident)) => check_field(self.fields, &ident),
+            Some(token) => Err(unexpected(&token)),
+            None => Err(invalid("$filter", "unexpected end of expression")),
+        }
+    }
+
+    fn parse_literal(&mut self) -> Result<Literal, ParseODataError> {
+        match self.next() {
+            Some(Token::String(value)) => Ok(Literal::String(value)),
+            Some(Token::Number(value)) => value
+                .parse()
+                .map(Literal::Int)
+                .or_else(|_| value.parse().map(Literal::Float))
+                .map_err(|_| invalid("$filter", format!("invalid number `{value}`"))),
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "null" => Ok(Literal::Null),
+                "true" => Ok(Literal::Bool(true)),
+                "false" => Ok(Literal::Bool(false)),
+                _ => Err(invalid(
+                    "$filter",
+                    format!("expected a literal, found `{ident}`"),
+                )),
+            },
+            Some(token) => Err(unexpected(&token)),
+            None => Err(invalid("$filter", "unexpected end of expression")),
+        }
+    }
+}
+
+fn unexpected(token: &Token) -> ParseODataError {
+    let token = match token {
+        Token::Ident(ident) => ident.clone(),
+        Token::String(value) => format!("'{value}'"),
+        Token::Number(value) => value.clone(),
+        Token::LParen => "(".to_string(),
+        Token::RParen => ")".to_string(),
+        Token::Comma => ",".to_string(),
+    };
+    invalid("$filter", format!("unexpected `{token}`"))
+}
+
+/// An extractor that parses the OData query options `$filter`, `$select`,
+/// `$orderby`, `$top` and `$skip`.
+///
+/// `T` describes the fields that can be used in the options, the
+/// `$filter` expression is parsed into a [`Filter`] that storage layers can
+/// translate into their own queries. The supported expressions are the
+/// comparisons (`eq`, `ne`, `gt`, `ge`, `lt`, `le`), `and`, `or`, `not`,
+/// parentheses and the `contains`, `startswith` and `endswith` functions.
+///
+/// # Errors
+///
+/// - [`ParseODataError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     get, handler,
+///     http::StatusCode,
+///     test::TestClient,
+///     web::{OData, ODataEntity},
+///     Route,
+/// };
+///
+/// struct User;
+///
+/// impl ODataEntity for User {
+///     const FIELDS: &'static [&'static str] = &["name", "age"];
+/// }
+///
+/// #[handler]
+/// fn users(query: OData<User>) -> String {
+///     format!("{:?} {:?}", query.top, query.orderby[0].field)
+/// }
+///
+/// let app = Route::new().at("/users", get(users));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/users")
+///     .query("$filter", &"age gt 18 and startswith(name, 'a')")
+///     .query("$orderby", &"age desc")
+///     .query("$top", &10)
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("Some(10) \"age\"").await;
+///
+/// let resp = cli
+///     .get("/users")
+///     .query("$filter", &"password eq 'secret'")
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::BAD_REQUEST);
+/// # });
+/// ```
+pub struct OData<T>(ODataQuery, PhantomData<fn() -> T>);
+
+impl<T> OData<T> {
+    /// Consumes this object and returns the parsed query options.
+    #[inline]
+    pub fn into_inner(self) -> ODataQuery {
+        self.0
+    }
+}
+
+impl<T> std::fmt::Debug for OData<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OData").field(&self.0).finish()
+    }
+}
+
+impl<T> Clone for OData<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<T> Deref for OData<T> {
+    type Target = ODataQuery;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a, T: ODataEntity> FromRequest<'a> for OData<T> {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let query =
+            ODataQuery::parse(req.uri().query().unwrap_or_default(), T::FIELDS, T::MAX_TOP)?;
+        Ok(Self(query, PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: &[&str] = &["name", "age", "address/city"];
+
+    fn parse(query: &str) -> Result<ODataQuery, ParseODataError> {
+        ODataQuery::parse(query, FIELDS, 100)
+    }
+
+    fn compare(field: &str, op: CompareOp, value: Literal) -> Box<Filter> {
+        Box::new(Filter::Compare {
+            field: field.to_string(),
+            op,
+            value,
+        })
+    }
+
+    #[test]
+    fn filter() {
+        let query = parse(
+            "$filter=not (age ge 18 or age eq null) and contains(address/city, 'O''Brien') \
+             and name ne 'a'&page=1",
+        )
+        .unwrap();
+        assert_eq!(
+            query.filter,
+            Some(Filter::And(
+                Box::new(Filter::And(
+                    Box::new(Filter::Not(Box::new(Filter::Or(
+                        compare("age", CompareOp::Ge, Literal::Int(18)),
+                        compare("age", CompareOp::Eq, Literal::Null),
+                    )))),
+                    Box::new(Filter::Function {
+                        function: StringFunction::Contains,
+                        field: "address/city".to_string(),
+                        value: "O'Brien".to_string(),
+                    }),
+                )),
+                compare("name", CompareOp::Ne, Literal::String("a".to_string())),
+            ))
+        );
+
+        assert_eq!(
+            parse("$filter=age lt -1.5").unwrap().filter,
+            Some(*compare("age", CompareOp::Lt, Literal::Float(-1.5)))
+        );
+
+        for filter in [
+            "password eq 1",
+            "age eq",
+            "age like 1",
+            "(age eq 1",
+            "age eq 1 age",
+            "name eq 'a",
+            "contains(password, 'a')",
+            "age eq 1;",
+        ] {
+            assert!(parse(&format!("$filter={filter}")).is_err(), "{filter}");
+        }
+
+        let nested = format!("$filter={}age eq 1{}", "(".repeat(64), ")".repeat(64));
+        assert!(parse(&nested).is_err());
+    }
+
+    #[test]
+    fn options() {
+        let query = parse("$select=name,%20age&$orderby=age desc,name&$top=10&$skip=20").unwrap();
+        assert_eq!(query.select, vec!["name", "age"]);
+        assert_eq!(
+            query.orderby,
+            vec![
+                OrderBy {
+                    field: "age".to_string(),
+                    descending: true,
+                },
+                OrderBy {
+                    field: "name".to_string(),
+                    descending: false,
+                },
+            ]
+        );
+        assert_eq!(query.top, Some(10));
+        assert_eq!(query.skip, Some(20));
+
+        assert_eq!(
+            parse("$select=password"),
+            Err(ParseODataError::FieldNotAllowed("password".to_string()))
+        );
+        assert_eq!(parse("$top=101"), Err(ParseODataError::TopTooLarge(100)));
+        assert_eq!(
+            parse("$top=1&$top=2"),
+            Err(ParseODataError::DuplicateOption("$top".to_string()))
+        );
+        assert_eq!(
+            parse("$expand=orders"),
+            Err(ParseODataError::UnsupportedOption("$expand".to_string()))
+        );
+        assert!(parse("$skip=-1").is_err());
+        assert!(parse("$orderby=age up").is_err());
+        assert!(parse("$select=name,").is_err());
+    }
+}
@@ -5,14 +5,71 @@ use serde::{
     forward_to_deserialize_any, Deserializer,
 };
 
+use crate::error::{Error as PoemError, ParsePathError, PathError};
+
 /// This type represents errors that can occur when deserializing.
 #[derive(Debug, Eq, PartialEq)]
-pub(crate) struct PathDeserializerError(pub(crate) String);
+pub(crate) struct PathDeserializerError {
+    msg: String,
+    expected_type: Option<String>,
+    param: Option<(String, String)>,
+}
+
+impl PathDeserializerError {
+    fn expected(msg: String, expected_type: impl Display) -> Self {
+        Self {
+            msg,
+            expected_type: Some(expected_type.to_string()),
+            param: None,
+        }
+    }
+
+    fn with_expected(mut self, expected_type: impl Display) -> Self {
+        if self.expected_type.is_none() {
+            self.expected_type = Some(expected_type.to_string());
+        }
+        self
+    }
+
+    fn with_param(mut self, name: &str, value: &str) -> Self {
+        if self.param.is_none() {
+            self.param = Some((name.to_string(), value.to_string()));
+        }
+        self
+    }
+
+    /// Converts to [`PathError`] if the error occurred when parsing a
+    /// parameter, otherwise converts to [`ParsePathError`].
+    pub(crate) fn into_error(self) -> PoemError {
+        match self.param {
+            Some((name, value)) => PathError {
+                name,
+                value,
+                expected_type: self.expected_type,
+                reason: self.msg,
+            }
+            .into(),
+            None => ParsePathError.into(),
+        }
+    }
+}
 
 impl de::Error for PathDeserializerError {
     #[inline]
     fn custom<T: Display>(msg: T) -> Self {
-        PathDeserializerError(msg.to_string())
+        PathDeserializerError {
+            msg: msg.to_string(),
+            expected_type: None,
+            param: None,
+        }
+    }
+
+    fn invalid_type(unexp: de::Unexpected, exp: &dyn de::Expected) -> Self {
+        PathDeserializerError::expected(format!("invalid type: {unexp}, expected {exp}"), exp)
+    }
+
+    fn invalid_value(unexp: de::Unexpected, exp: &dyn de::Expected) -> Self {
+        PathDeserializerError::expected(format!("invalid value: {unexp}, expected {exp}"), exp)
     }
 }
 
@@ -26,9 +83,17 @@ impl std::error::Error for PathDeserializerError {
 impl fmt::Display for PathDeserializerError {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            PathDeserializerError(msg) => write!(f, "{msg}"),
-        }
+        write!(f, "{}", self.msg)
+    }
+}
+
+/// Displays what a visitor expects, so that errors raised through
+/// [`de::Error::custom`] (e.g. by `Uuid`) still report the expected type.
+struct Expecting<'a, V>(&'a V);
+
+impl<'de, V: Visitor<'de>> Display for Expecting<'_, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(f)
     }
 }
 
@@ -62,14 +127,17 @@ macro_rules! parse_single_value {
                 ));
             }
 
-            let value = self.url_params[0].1.parse().map_err(|_| {
-                PathDeserializerError::custom(format!(
-                    "can not parse `{:?}` to a `{}`",
-                    self.url_params[0].1.as_str(),
-                    $tp
-                ))
+            let (name, value) = &self.url_params[0];
+            let parsed = value.parse().map_err(|_| {
+                PathDeserializerError::expected(
+                    format!("can not parse `{:?}` to a `{}`", value.as_str(), $tp),
+                    $tp,
+                )
+                .with_param(name, value)
             })?;
-            visitor.$visit_fn(value)
+            visitor
+                .$visit_fn::<PathDeserializerError>(parsed)
+                .map_err(|err| err.with_param(name, value))
         }
     };
 }
@@ -119,7 +187,11 @@ impl<'de> Deserializer<'de> for PathDeserializer<'de> {
                 self.url_params.len()
             )));
         }
-        visitor.visit_str(&self.url_params[0].1)
+        let (name, value) = &self.url_params[0];
+        let expected = Expecting(&visitor).to_string();
+        visitor
+            .visit_str::<PathDeserializerError>(value)
+            .map_err(|err| err.with_expected(expected).with_param(name, value))
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -241,15 +313,16 @@ impl<'de> Deserializer<'de> for PathDeserializer<'de> {
             )));
         }
 
-        visitor.visit_enum(EnumDeserializer {
-            value: &self.url_params[0].1,
-        })
+        let (name, value) = &self.url_params[0];
+        visitor
+            .visit_enum(EnumDeserializer { value })
+            .map_err(|err: PathDeserializerError| err.with_param(name, value))
     }
 }
 
 struct MapDeserializer<'de> {
     params: &'de [(String, String)],
-    value: Option<&'de str>,
+    value: Option<(&'de str, &'de str)>,
 }
 
 impl<'de> MapAccess<'de> for MapDeserializer<'de> {
@@ -261,7 +334,7 @@ impl<'de> MapAccess<'de> for MapDeserializer<'de> {
     {
         match self.params.split_first() {
             Some(((key, value), tail)) => {
-                self.value = Some(value);
+                self.value = Some((key, value));
                 self.params = tail;
                 seed.deserialize(KeyDeserializer { key }).map(Some)
             }
@@ -274,7 +347,9 @@ impl<'de> MapAccess<'de> for MapDeserializer<'de> {
         V: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(ValueDeserializer { value }),
+            Some((key, value)) => seed
+                .deserialize(ValueDeserializer { value })
+                .map_err(|err| err.with_param(key, value)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
@@ -323,10 +398,10 @@ macro_rules! parse_value {
             V: Visitor<'de>,
         {
             let v = self.value.parse().map_err(|_| {
-                PathDeserializerError::custom(format!(
-                    "can not parse `{:?}` to a `{}`",
-                    self.value, $ty
-                ))
+                PathDeserializerError::expected(
+                    format!("can not parse `{:?}` to a `{}`", self.value, $ty),
+                    $ty,
+                )
             })?;
             visitor.$visit_fn(v)
         }
@@ -348,7 +423,7 @@ impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
     parse_value!(deserialize_bool, visit_bool, "bool");
     parse_value!(deserialize_i8, visit_i8, "i8");
     parse_value!(deserialize_i16, visit_i16, "i16");
-    parse_value!(deserialize_i32, visit_i32, "i16");
+    parse_value!(deserialize_i32, visit_i32, "i32");
     parse_value!(deserialize_i64, visit_i64, "i64");
     parse_value!(deserialize_u8, visit_u8, "u8");
     parse_value!(deserialize_u16, visit_u16, "u16");
@@ -364,7 +439,10 @@ impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_borrowed_str(self.value)
+        let expected = Expecting(&visitor).to_string();
+        visitor
+            .visit_borrowed_str::<PathDeserializerError>(self.value)
+            .map_err(|err| err.with_expected(expected))
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -529,9 +607,12 @@ impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         match self.params.split_first() {
-            Some(((_, value), tail)) => {
+            Some(((name, value), tail)) => {
                 self.params = tail;
-                Ok(Some(seed.deserialize(ValueDeserializer { value })?))
+                Ok(Some(
+                    seed.deserialize(ValueDeserializer { value })
+                        .map_err(|err| err.with_param(name, value))?,
+                ))
             }
             None => Ok(None),
         }
@@ -668,4 +749,60 @@ mod tests {
                 .collect()
         );
     }
+
+    #[test]
+    fn test_param_error() {
+        let url_params = create_url_params(vec![("a", "1"), ("b", "true"), ("c", "abc")]);
+        let err = Struct::deserialize(PathDeserializer::new(&create_url_params(vec![
+            ("a", "x"),
+            ("b", "true"),
+            ("c", "abc"),
+        ])))
+        .unwrap_err();
+        assert_eq!(err.param, Some(("a".to_string(), "x".to_string())));
+        assert_eq!(err.expected_type.as_deref(), Some("i32"));
+
+        let err = <(i32, MyEnum)>::deserialize(PathDeserializer::new(&url_params)).unwrap_err();
+        assert_eq!(err.param, Some(("b".to_string(), "true".to_string())));
+
+        let err = i32::deserialize(PathDeserializer::new(&url_params)).unwrap_err();
+        assert_eq!(err.param, None);
+    }
+
+    #[test]
+    fn test_custom_error_expected_type() {
+        // Parses like `Uuid`, reporting failures through `de::Error::custom`.
+        #[derive(Debug)]
+        struct Id;
+
+        impl<'de> Deserialize<'de> for Id {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct IdVisitor;
+
+                impl<'de> Visitor<'de> for IdVisitor {
+                    type Value = Id;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("an id string")
+                    }
+
+                    fn visit_str<E: de::Error>(self, _v: &str) -> Result<Id, E> {
+                        Err(E::custom("id parsing failed"))
+                    }
+                }
+
+                deserializer.deserialize_str(IdVisitor)
+            }
+        }
+
+        let url_params = create_url_params(vec![("a", "1"), ("b", "x")]);
+        let err = <(i32, Id)>::deserialize(PathDeserializer::new(&url_params)).unwrap_err();
+        assert_eq!(err.param, Some(("b".to_string(), "x".to_string())));
+        assert_eq!(err.expected_type.as_deref(), Some("an id string"));
+
+        let url_params = create_url_params(vec![("a", "x")]);
+        let err = Id::deserialize(PathDeserializer::new(&url_params)).unwrap_err();
+        assert_eq!(err.param, Some(("a".to_string(), "x".to_string())));
+        assert_eq!(err.expected_type.as_deref(), Some("an id string"));
+    }
 }
@@ -5,14 +5,18 @@ use std::ops::{Deref, DerefMut};
 pub(crate) use de::PathDeserializer;
 use serde::de::DeserializeOwned;
 
-use crate::{error::ParsePathError, FromRequest, Request, RequestBody, Result};
+use crate::{FromRequest, Request, RequestBody, Result};
 
 /// An extractor that will get captures from the URL and parse them using
 /// `serde`.
 ///
+/// The captured values are percent-decoded, use [`RawPathParam`] to get the
+/// encoded values.
+///
 /// # Errors
 ///
-/// - [`ParsePathError`]
+/// - [`PathError`](crate::error::PathError)
+/// - [`ParsePathError`](crate::error::ParsePathError)
 ///
 /// # Example
 ///
@@ -117,10 +121,11 @@ impl<T> DerefMut for Path<T> {
 }
 
 impl<T: DeserializeOwned> Path<T> {
-    async fn internal_from_request(req: &Request) -> Result<Self, ParsePathError> {
+    async fn internal_from_request(req: &Request) -> Result<Self> {
+        req.state().check_match_params()?;
         Ok(Path(
             T::deserialize(de::PathDeserializer::new(&req.state().match_params))
-                .map_err(|_| ParsePathError)?,
+                .map_err(|err| err.into_error())?,
         ))
     }
 }
@@ -128,6 +133,147 @@ impl<T: DeserializeOwned> Path<T> {
 #[async_trait::async_trait]
 impl<'a, T: DeserializeOwned> FromRequest<'a> for Path<T> {
     async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
-        Self::internal_from_request(req).await.map_err(Into::into)
+        Self::internal_from_request(req).await
+    }
+}
+
+/// An extractor that will get captures from the URL without percent-decoding
+/// and parse them using `serde`.
+///
+/// This is useful for routes that need to forward the path segments as they
+/// were received, for example, a proxy. Note that an encoded slash (`%2F`)
+/// never splits a segment, so a parameter may contain a slash after it has
+/// been decoded. A parameter that is not valid UTF-8 after percent-decoding
+/// can still be extracted with `RawPathParam`, while [`Path`] returns
+/// [`PathError`](crate::error::PathError).
+///
+/// # Errors
+///
+/// - [`PathError`](crate::error::PathError)
+/// - [`ParsePathError`](crate::error::ParsePathError)
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     get, handler,
+///     test::TestClient,
+///     web::{Path, RawPathParam},
+///     Route,
+/// };
+///
+/// #[handler]
+/// async fn proxy(Path(path): Path<String>, RawPathParam(raw): RawPathParam<String>) -> String {
+///     format!("{}|{}", path, raw)
+/// }
+///
+/// let app = Route::new().at("/proxy/:path", get(proxy));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/proxy/a%2Fb").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("a/b|a%2Fb").await;
+/// # });
+/// ```
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct RawPathParam<T>(pub T);
+
+impl<T> Deref for RawPathParam<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for RawPathParam<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: DeserializeOwned> RawPathParam<T> {
+    async fn internal_from_request(req: &Request) -> Result<Self> {
+        Ok(RawPathParam(
+            T::deserialize(de::PathDeserializer::new(&req.state().raw_match_params))
+                .map_err(|err| err.into_error())?,
+        ))
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a, T: DeserializeOwned> FromRequest<'a> for RawPathParam<T> {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Self::internal_from_request(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{handler, http::StatusCode, test::TestClient, EndpointExt, Route};
+
+    #[tokio::test]
+    async fn percent_decoding() {
+        #[handler(internal)]
+        async fn index(
+            Path((a, b)): Path<(String, String)>,
+            RawPathParam((raw_a, raw_b)): RawPathParam<(String, String)>,
+        ) -> String {
+            format!("{a}|{b}|{raw_a}|{raw_b}")
+        }
+
+        let cli = TestClient::new(Route::new().at("/:a/:b", index));
+        let resp = cli.get("/a%20b/c%2Fd").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("a b|c/d|a%20b|c%2Fd").await;
+    }
+
+    #[tokio::test]
+    async fn invalid_utf8() {
+        #[handler(internal)]
+        async fn raw(RawPathParam((a, b)): RawPathParam<(String, String)>) -> String {
+            format!("{a}|{b}")
+        }
+
+        #[handler(internal)]
+        async fn decoded(Path(b): Path<String>) -> String {
+            b
+        }
+
+        let cli = TestClient::new(
+            Route::new()
+                .at("/raw/:a/:b", raw)
+                .at("/decoded/:a/:b", decoded),
+        );
+        let resp = cli.get("/raw/%FF/b").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("%FF|b").await;
+
+        cli.get("/decoded/%FF/b")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn path_error() {
+        #[handler(internal)]
+        async fn index(Path((_a, _b)): Path<(i32, u8)>) {}
+
+        let cli = TestClient::new(Route::new().at("/:a/:b", index).catch_error(
+            |err: crate::error::PathError| async move {
+                format!(
+                    "{}|{}|{}",
+                    err.name,
+                    err.value,
+                    err.expected_type.unwrap_or_default()
+                )
+            },
+        ));
+        let resp = cli.get("/1/abc").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("b|abc|u8").await;
     }
 }
@@ -0,0 +1,238 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::{
+    error::PeerIdentityError,
+    web::{ClientCert, SubjectAltName},
+    FromRequest, Request, RequestBody, Result,
+};
+
+/// A [SPIFFE ID](https://github.com/spiffe/spiffe/blob/main/standards/SPIFFE-ID.md),
+/// such as `spiffe://example.org/ns/default/sa/web`.
+///
+/// # Example
+///
+/// ```
+/// use poem::web::SpiffeId;
+///
+/// let id: SpiffeId = "spiffe://example.org/ns/default/sa/web".parse().unwrap();
+/// assert_eq!(id.trust_domain(), "example.org");
+/// assert_eq!(id.path(), "/ns/default/sa/web");
+///
+/// assert!("spiffe://example.org/ns/../web".parse::<SpiffeId>().is_err());
+/// assert!("https://example.org/web".parse::<SpiffeId>().is_err());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SpiffeId {
+    trust_domain: String,
+    path: String,
+}
+
+impl SpiffeId {
+    /// Returns the trust domain.
+    #[inline]
+    pub fn trust_domain(&self) -> &str {
+        &self.trust_domain
+    }
+
+    /// Returns the path, which is empty or starts with `/`.
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl FromStr for SpiffeId {
+    type Err = PeerIdentityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PeerIdentityError::InvalidSpiffeId(s.to_string());
+
+        let rest = s.strip_prefix("spiffe://").ok_or_else(invalid)?;
+        let (trust_domain, path) = match rest.find('/') {
+            Some(idx) => rest.split_at(idx),
+            None => (rest, ""),
+        };
+
+        if trust_domain.is_empty()
+            || !trust_domain
+                .bytes()
+                .all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_'))
+        {
+            return Err(invalid());
+        }
+
+        if !path.is_empty() {
+            for segment in path[1..].split('/') {
+                if segment.is_empty()
+                    || segment == "."
+                    || segment == ".."
+                    || !segment
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'_'))
+                {
+                    return Err(invalid());
+                }
+            }
+        }
+
+        Ok(Self {
+            trust_domain: trust_domain.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+impl Display for SpiffeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "spiffe://{}{}", self.trust_domain, self.path)
+    }
+}
+
+/// An extractor for the SPIFFE identity of the client, parsed from the URI
+/// subject alternative name of the
+/// [X.509-SVID](https://github.com/spiffe/spiffe/blob/main/standards/X509-SVID.md)
+/// presented during mutual TLS authentication.
+///
+/// Use the [`RequireTrustDomain`](crate::middleware::RequireTrustDomain)
+/// middleware to only accept clients from specific trust domains.
+///
+/// # Errors
+///
+/// - [`ClientCertRequiredError`](crate::error::ClientCertRequiredError)
+/// - [`PeerIdentityError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, web::PeerIdentity};
+///
+/// #[handler]
+/// fn index(peer: PeerIdentity) -> String {
+///     format!("hello, {}", peer.spiffe_id())
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+#[derive(Debug, Clone)]
+pub struct PeerIdentity {
+    spiffe_id: SpiffeId,
+    cert: ClientCert,
+}
+
+impl PeerIdentity {
+    /// Parses the SPIFFE identity from the client certificate.
+    ///
+    /// The end-entity certificate must contain exactly one URI subject
+    /// alternative name, which is a valid SPIFFE ID.
+    pub fn from_client_cert(cert: ClientCert) -> Result<Self, PeerIdentityError> {
+        let mut uris = cert
+            .subject_alt_names()
+            .into_iter()
+            .filter_map(|name| match name {
+                SubjectAltName::Uri(uri) => Some(uri),
+                _ => None,
+            });
+
+        let spiffe_id = match (uris.next(), uris.next()) {
+            (Some(uri), None) => uri.parse()?,
+            (None, _) => return Err(PeerIdentityError::MissingSpiffeId),
+            (Some(_), Some(_)) => return Err(PeerIdentityError::MultipleUris),
+        };
+        Ok(Self { spiffe_id, cert })
+    }
+
+    /// Returns the SPIFFE ID.
+    #[inline]
+    pub fn spiffe_id(&self) -> &SpiffeId {
+        &self.spiffe_id
+    }
+
+    /// Returns the trust domain of the SPIFFE ID.
+    #[inline]
+    pub fn trust_domain(&self) -> &str {
+        self.spiffe_id.trust_domain()
+    }
+
+    /// Returns the client certificate.
+    #[inline]
+    pub fn client_cert(&self) -> &ClientCert {
+        &self.cert
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for PeerIdentity {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let cert = ClientCert::from_request(req, body).await?;
+        Ok(PeerIdentity::from_client_cert(cert)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+
+    fn client_cert(pem: &[u8]) -> ClientCert {
+        let chain = rustls_pemfile::certs(&mut &*pem)
+            .map(|cert| cert.unwrap().to_vec())
+            .collect();
+        ClientCert::new(chain).unwrap()
+    }
+
+    #[test]
+    fn parse_spiffe_id() {
+        let id: SpiffeId = "spiffe://example.org".parse().unwrap();
+        assert_eq!(id.trust_domain(), "example.org");
+        assert_eq!(id.path(), "");
+        assert_eq!(id.to_string(), "spiffe://example.org");
+
+        for s in [
+            "spiffe://",
+            "spiffe:///a",
+            "spiffe://Example.org/a",
+            "spiffe://example.org/",
+            "spiffe://example.org/a//b",
+            "spiffe://example.org/a/./b",
+            "spiffe://example.org/a?b=1",
+            "spiffe://user@example.org/a",
+            "spiffe://example.org:8080/a",
+        ] {
+            assert!(s.parse::<SpiffeId>().is_err(), "{s}");
+        }
+    }
+
+    #[tokio::test]
+    async fn extractor() {
+        let req = Request::default();
+        assert_eq!(
+            PeerIdentity::from_request_without_body(&req)
+                .await
+                .unwrap_err()
+                .status(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        req.connect_info()
+            .insert(client_cert(include_bytes!("../listener/certs/cert1.pem")));
+        assert_eq!(
+            PeerIdentity::from_request_without_body(&req)
+                .await
+                .unwrap_err()
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+
+        req.connect_info()
+            .insert(client_cert(include_bytes!("../listener/certs/spiffe.pem")));
+        let peer = PeerIdentity::from_request_without_body(&req).await.unwrap();
+        assert_eq!(
+            peer.spiffe_id().to_string(),
+            "spiffe://example.org/ns/default/sa/web"
+        );
+        assert_eq!(peer.trust_domain(), "example.org");
+    }
+}
@@ -0,0 +1,239 @@
+use crate::http::{header, HeaderMap, HeaderValue};
+#[cfg(feature = "compression")]
+use crate::{web::CompressionAlgo, Body, IntoResponse, Request, Response};
+
+/// Selects the most preferred of the `available` content codings according
+/// to the `Accept-Encoding` header, the earlier codings are preferred when
+/// the quality values are equal.
+///
+/// Returns `None` if none of them is acceptable, or the client prefers the
+/// `identity` coding.
+pub(crate) fn negotiate_encoding<'a>(
+    headers: &HeaderMap,
+    available: &[&'a str],
+) -> Option<&'a str> {
+    let mut star = None;
+    let mut identity = None;
+    let mut qualities = Vec::new();
+
+    for item in headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+    {
+        let mut parts = item.split(';').map(str::trim);
+        let coding = match parts.next() {
+            Some(coding) if !coding.is_empty() => coding,
+            _ => continue,
+        };
+        let q = parts
+            .find_map(|param| param.strip_prefix("q="))
+            .map(|q| q.parse::<f32>().unwrap_or(0.0))
+            .unwrap_or(1.0);
+
+        if coding == "*" {
+            star = Some(q);
+        } else if coding.eq_ignore_ascii_case("identity") {
+            identity = Some(q);
+        } else {
+            qualities.push((coding, q));
+        }
+    }
+
+    let mut selected: Option<(&str, f32)> = None;
+    for coding in available {
+        let q = qualities
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(coding))
+            .map(|(_, q)| *q)
+            .or(star)
+            .unwrap_or(0.0);
+        if q > 0.0 && selected.map_or(true, |(_, selected_q)| q > selected_q) {
+            selected = Some((coding, q));
+        }
+    }
+
+    match (selected, identity) {
+        (Some((_, q)), Some(identity_q)) if identity_q > q => None,
+        (selected, _) => selected.map(|(coding, _)| coding),
+    }
+}
+
+/// Appends `Accept-Encoding` to the `Vary` header.
+pub(crate) fn vary_accept_encoding(headers: &mut HeaderMap) {
+    headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+}
+
+/// A response that serves one of the pre-compressed variants of the body,
+/// instead of compressing it for each request.
+///
+/// The variant is selected according to the `Accept-Encoding` header of the
+/// request, and the `Content-Encoding` header is set accordingly. If none of
+/// the variants is acceptable, the inner response is sent as it is. The
+/// `Vary: Accept-Encoding` header is always added, so that the caches store
+/// the variants separately. Only successful responses are affected.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     test::TestClient,
+///     web::{CompressionAlgo, Precompressed},
+///     Request, Response,
+/// };
+///
+/// const APP_JS: &str = "console.log('hello')";
+/// const APP_JS_GZ: &[u8] = &[/* compressed at build time */];
+///
+/// #[handler]
+/// fn app_js(req: &Request) -> Response {
+///     Precompressed::new(APP_JS)
+///         .variant(CompressionAlgo::GZIP, APP_JS_GZ)
+///         .respond_to(req)
+/// }
+///
+/// let cli = TestClient::new(app_js);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .header("accept-encoding", "gzip, br")
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_header("content-encoding", "gzip");
+/// resp.assert_header("vary", "accept-encoding");
+/// # });
+/// ```
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub struct Precompressed<T> {
+    inner: T,
+    variants: Vec<(CompressionAlgo, Body)>,
+}
+
+#[cfg(feature = "compression")]
+impl<T: IntoResponse> Precompressed<T> {
+    /// Create a `Precompressed` response with the uncompressed response.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            variants: Vec::new(),
+        }
+    }
+
+    /// Adds a variant of the body compressed with the algorithm.
+    ///
+    /// The variants added first are preferred if the client accepts multiple
+    /// variants equally.
+    #[must_use]
+    pub fn variant(mut self, algo: CompressionAlgo, body: impl Into<Body>) -> Self {
+        self.variants.push((algo, body.into()));
+        self
+    }
+
+    /// Consumes this object and returns the response of the variant
+    /// accepted by the request.
+    pub fn respond_to(self, req: &Request) -> Response {
+        let mut resp = self.inner.into_response();
+        if !resp.status().is_success() {
+            return resp;
+        }
+        vary_accept_encoding(resp.headers_mut());
+
+        let available = self
+            .variants
+            .iter()
+            .map(|(algo, _)| algo.as_str())
+            .collect::<Vec<_>>();
+        let coding = match negotiate_encoding(req.headers(), &available) {
+            Some(coding) => coding,
+            None => return resp,
+        };
+
+        let body = self
+            .variants
+            .into_iter()
+            .find(|(algo, _)| algo.as_str() == coding)
+            .map(|(_, body)| body);
+        if let Some(body) = body {
+            resp.headers_mut().remove(header::CONTENT_LENGTH);
+            resp.headers_mut()
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static(coding));
+            resp.set_body(body);
+        }
+        resp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn negotiate(accept_encoding: Option<&str>) -> Option<&'static str> {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = accept_encoding {
+            headers.insert(header::ACCEPT_ENCODING, value.parse().unwrap());
+        }
+        negotiate_encoding(&headers, &["br", "gzip"])
+    }
+
+    #[test]
+    fn negotiate_accept_encoding() {
+        assert_eq!(negotiate(None), None);
+        assert_eq!(negotiate(Some("gzip, deflate, br")), Some("br"));
+        assert_eq!(negotiate(Some("gzip, deflate")), Some("gzip"));
+        assert_eq!(negotiate(Some("gzip;q=1.0, br;q=0.5")), Some("gzip"));
+        assert_eq!(negotiate(Some("br;q=0, *")), Some("gzip"));
+        assert_eq!(negotiate(Some("deflate")), None);
+        assert_eq!(negotiate(Some("identity, gzip;q=0.5")), None);
+        assert_eq!(negotiate(Some("identity;q=0.5, gzip")), Some("gzip"));
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn precompressed() {
+        use crate::{http::StatusCode, test::TestClient};
+
+        let ep = crate::endpoint::make_sync(|req| {
+            Precompressed::new("plain")
+                .variant(CompressionAlgo::BR, "br")
+                .variant(CompressionAlgo::GZIP, "gzip")
+                .respond_to(&req)
+        });
+        let cli = TestClient::new(ep);
+
+        let resp = cli.get("/").header("accept-encoding", "gzip").send().await;
+        resp.assert_header("content-encoding", "gzip");
+        resp.assert_header("vary", "accept-encoding");
+        resp.assert_text("gzip").await;
+
+        let resp = cli
+            .get("/")
+            .header("accept-encoding", "gzip, br")
+            .send()
+            .await;
+        resp.assert_header("content-encoding", "br");
+        resp.assert_text("br").await;
+
+        let resp = cli.get("/").send().await;
+        resp.assert_header_is_not_exist("content-encoding");
+        resp.assert_header("vary", "accept-encoding");
+        resp.assert_text("plain").await;
+
+        let ep = crate::endpoint::make_sync(|req| {
+            Precompressed::new(StatusCode::NOT_FOUND)
+                .variant(CompressionAlgo::GZIP, "gzip")
+                .respond_to(&req)
+        });
+        let resp = TestClient::new(ep)
+            .get("/")
+            .header("accept-encoding", "gzip")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NOT_FOUND);
+        resp.assert_header_is_not_exist("content-encoding");
+    }
+}
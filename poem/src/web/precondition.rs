@@ -0,0 +1,241 @@
+use std::time::SystemTime;
+
+use crate::{
+    error::PreconditionError,
+    http::Method,
+    web::headers::{ETag, HeaderMapExt, IfMatch, IfModifiedSince, IfNoneMatch, IfUnmodifiedSince},
+    FromRequest, Request, RequestBody, Result,
+};
+
+/// An extractor for the conditional request headers `If-Match`,
+/// `If-None-Match`, `If-Modified-Since` and `If-Unmodified-Since`.
+///
+/// Use [`Preconditions::evaluate`] to check them against the current `ETag`
+/// and modification time of the resource, as described in
+/// [RFC 7232](https://datatracker.ietf.org/doc/html/rfc7232#section-6).
+///
+/// # Example
+///
+/// ```
+/// use std::str::FromStr;
+///
+/// use poem::{
+///     handler,
+///     http::{header, StatusCode},
+///     test::TestClient,
+///     web::{headers::ETag, Preconditions},
+///     IntoResponse, Result,
+/// };
+///
+/// #[handler]
+/// fn index(preconditions: Preconditions) -> Result<impl IntoResponse> {
+///     let etag = ETag::from_str("\"v1\"").unwrap();
+///     preconditions.evaluate(Some(&etag), None)?;
+///     Ok("hello".with_header(header::ETAG, "\"v1\""))
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.get("/").send().await.assert_status_is_ok();
+/// cli.get("/")
+///     .header(header::IF_NONE_MATCH, "\"v1\"")
+///     .send()
+///     .await
+///     .assert_status(StatusCode::NOT_MODIFIED);
+/// cli.put("/")
+///     .header(header::IF_MATCH, "\"v0\"")
+///     .send()
+///     .await
+///     .assert_status(StatusCode::PRECONDITION_FAILED);
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Preconditions {
+    method: Method,
+    if_match: Option<IfMatch>,
+    if_unmodified_since: Option<IfUnmodifiedSince>,
+    if_none_match: Option<IfNoneMatch>,
+    if_modified_since: Option<IfModifiedSince>,
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for Preconditions {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(Self {
+            method: req.method().clone(),
+            if_match: req.headers().typed_get::<IfMatch>(),
+            if_unmodified_since: req.headers().typed_get::<IfUnmodifiedSince>(),
+            if_none_match: req.headers().typed_get::<IfNoneMatch>(),
+            if_modified_since: req.headers().typed_get::<IfModifiedSince>(),
+        })
+    }
+}
+
+impl Preconditions {
+    /// Returns the `If-Match` header.
+    #[inline]
+    pub fn if_match(&self) -> Option<&IfMatch> {
+        self.if_match.as_ref()
+    }
+
+    /// Returns the `If-None-Match` header.
+    #[inline]
+    pub fn if_none_match(&self) -> Option<&IfNoneMatch> {
+        self.if_none_match.as_ref()
+    }
+
+    /// Returns the `If-Modified-Since` header.
+    #[inline]
+    pub fn if_modified_since(&self) -> Option<&IfModifiedSince> {
+        self.if_modified_since.as_ref()
+    }
+
+    /// Returns the `If-Unmodified-Since` header.
+    #[inline]
+    pub fn if_unmodified_since(&self) -> Option<&IfUnmodifiedSince> {
+        self.if_unmodified_since.as_ref()
+    }
+
+    /// Returns `true` if the request does not contain any conditional
+    /// headers.
+    pub fn is_empty(&self) -> bool {
+        self.if_match.is_none()
+            && self.if_unmodified_since.is_none()
+            && self.if_none_match.is_none()
+            && self.if_modified_since.is_none()
+    }
+
+    /// Evaluates the preconditions against the current `ETag` and
+    /// modification time of the resource.
+    ///
+    /// Returns [`PreconditionError::PreconditionFailed`] if `If-Match` or
+    /// `If-Unmodified-Since` does not pass, or if `If-None-Match` does not
+    /// pass for a method other than `GET` and `HEAD`. Returns
+    /// [`PreconditionError::NotModified`] if `If-None-Match` or
+    /// `If-Modified-Since` does not pass for `GET` and `HEAD`.
+    pub fn evaluate(
+        &self,
+        etag: Option<&ETag>,
+        last_modified: Option<SystemTime>,
+    ) -> Result<(), PreconditionError> {
+        let is_get_or_head = self.method == Method::GET || self.method == Method::HEAD;
+
+        if let Some(if_match) = &self.if_match {
+            if !etag.map_or(false, |etag| if_match.precondition_passes(etag)) {
+                return Err(PreconditionError::PreconditionFailed);
+            }
+        } else if let (Some(if_unmodified_since), Some(last_modified)) =
+            (&self.if_unmodified_since, last_modified)
+        {
+            if !if_unmodified_since.precondition_passes(last_modified) {
+                return Err(PreconditionError::PreconditionFailed);
+            }
+        }
+
+        if let Some(if_none_match) = &self.if_none_match {
+            // without a current representation, even `*` does not match
+            let passes = etag.map_or(true, |etag| if_none_match.precondition_passes(etag));
+            if !passes {
+                return Err(if is_get_or_head {
+                    PreconditionError::NotModified
+                } else {
+                    PreconditionError::PreconditionFailed
+                });
+            }
+        } else if let (Some(if_modified_since), Some(last_modified), true) =
+            (&self.if_modified_since, last_modified, is_get_or_head)
+        {
+            if !if_modified_since.is_modified(last_modified) {
+                return Err(PreconditionError::NotModified);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, time::Duration};
+
+    use super::*;
+    use crate::http::header;
+
+    async fn preconditions(req: Request) -> Preconditions {
+        let (req, mut body) = req.split();
+        Preconditions::from_request(&req, &mut body).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn evaluate_etag() {
+        let etag = ETag::from_str("\"abc\"").unwrap();
+
+        let p = preconditions(Request::builder().finish()).await;
+        assert!(p.is_empty());
+        assert_eq!(p.evaluate(Some(&etag), None), Ok(()));
+
+        let p = preconditions(
+            Request::builder()
+                .method(Method::PUT)
+                .header(header::IF_MATCH, "\"abc\"")
+                .finish(),
+        )
+        .await;
+        assert_eq!(p.evaluate(Some(&etag), None), Ok(()));
+        assert_eq!(
+            p.evaluate(None, None),
+            Err(PreconditionError::PreconditionFailed)
+        );
+
+        let p = preconditions(
+            Request::builder()
+                .header(header::IF_NONE_MATCH, "\"abc\"")
+                .finish(),
+        )
+        .await;
+        assert_eq!(
+            p.evaluate(Some(&etag), None),
+            Err(PreconditionError::NotModified)
+        );
+
+        let p = preconditions(
+            Request::builder()
+                .method(Method::PUT)
+                .header(header::IF_NONE_MATCH, "*")
+                .finish(),
+        )
+        .await;
+        assert_eq!(
+            p.evaluate(Some(&etag), None),
+            Err(PreconditionError::PreconditionFailed)
+        );
+        assert_eq!(p.evaluate(None, None), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn evaluate_modified_time() {
+        let now = SystemTime::now();
+        let past = now - Duration::from_secs(3600);
+
+        let p = preconditions(
+            Request::builder()
+                .typed_header(IfModifiedSince::from(now))
+                .finish(),
+        )
+        .await;
+        assert_eq!(p.evaluate(None, Some(past)), Err(PreconditionError::NotModified));
+
+        let p = preconditions(
+            Request::builder()
+                .method(Method::DELETE)
+                .typed_header(IfUnmodifiedSince::from(past))
+                .finish(),
+        )
+        .await;
+        assert_eq!(
+            p.evaluate(None, Some(now)),
+            Err(PreconditionError::PreconditionFailed)
+        );
+    }
+}
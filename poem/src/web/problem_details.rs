@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    http::{header, StatusCode},
+    Error, IntoResponse, Response,
+};
+
+/// A problem details response defined in
+/// [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807), which is serialized as
+/// JSON with the `application/problem+json` content type.
+///
+/// It can be converted from [`Error`], and converted into [`Error`] to be
+/// returned from handlers that return [`Result`](crate::Result).
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::StatusCode,
+///     test::TestClient,
+///     web::ProblemDetails,
+///     Result,
+/// };
+/// use serde_json::json;
+///
+/// #[handler]
+/// fn transfer() -> Result<()> {
+///     Err(ProblemDetails::new(StatusCode::FORBIDDEN)
+///         .problem_type("https://example.com/probs/out-of-credit")
+///         .title("You do not have enough credit.")
+///         .detail("Your current balance is 30, but that costs 50.")
+///         .instance("/account/12345/msgs/abc")
+///         .extension("balance", 30)
+///         .into())
+/// }
+///
+/// let cli = TestClient::new(transfer);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.post("/").send().await;
+/// resp.assert_status(StatusCode::FORBIDDEN);
+/// resp.assert_content_type("application/problem+json");
+/// resp.assert_json(json!({
+///     "type": "https://example.com/probs/out-of-credit",
+///     "title": "You do not have enough credit.",
+///     "status": 403,
+///     "detail": "Your current balance is 30, but that costs 50.",
+///     "instance": "/account/12345/msgs/abc",
+///     "balance": 30,
+/// }))
+/// .await;
+/// # });
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    /// A URI reference that identifies the problem type, default to
+    /// `about:blank`.
+    #[serde(rename = "type", default = "default_type")]
+    pub problem_type: String,
+    /// A short, human-readable summary of the problem type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The HTTP status code.
+    pub status: u16,
+    /// A human-readable explanation specific to this occurrence of the
+    /// problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// A URI reference that identifies the specific occurrence of the
+    /// problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Additional members.
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+fn default_type() -> String {
+    "about:blank".to_string()
+}
+
+impl ProblemDetails {
+    /// Create a problem details with the status code, the title is set to
+    /// the canonical reason of the status code.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            problem_type: default_type(),
+            title: status.canonical_reason().map(ToString::to_string),
+            status: status.as_u16(),
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Sets the problem type.
+    #[must_use]
+    pub fn problem_type(self, problem_type: impl Into<String>) -> Self {
+        Self {
+            problem_type: problem_type.into(),
+            ..self
+        }
+    }
+
+    /// Sets the title.
+    #[must_use]
+    pub fn title(self, title: impl Into<String>) -> Self {
+        Self {
+            title: Some(title.into()),
+            ..self
+        }
+    }
+
+    /// Sets the detail.
+    #[must_use]
+    pub fn detail(self, detail: impl Into<String>) -> Self {
+        Self {
+            detail: Some(detail.into()),
+            ..self
+        }
+    }
+
+    /// Sets the instance.
+    #[must_use]
+    pub fn instance(self, instance: impl Into<String>) -> Self {
+        Self {
+            instance: Some(instance.into()),
+            ..self
+        }
+    }
+
+    /// Adds an extension member.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value cannot be serialized to JSON.
+    #[must_use]
+    pub fn extension(mut self, name: impl Into<String>, value: impl Serialize) -> Self {
+        self.extensions.insert(
+            name.into(),
+            serde_json::to_value(value).expect("valid json value"),
+        );
+        self
+    }
+
+    /// Returns the status code, or `500 Internal Server Error` if the status
+    /// is not a valid status code.
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+impl From<Error> for ProblemDetails {
+    fn from(err: Error) -> Self {
+        let problem = ProblemDetails::new(err.status());
+        // errors created from a status code have no message besides the status
+        let detail = err.to_string();
+        if detail != err.status().to_string() {
+            problem.detail(detail)
+        } else {
+            problem
+        }
+    }
+}
+
+impl From<ProblemDetails> for Error {
+    fn from(problem: ProblemDetails) -> Self {
+        Error::from_response(problem.into_response())
+    }
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let data = match serde_json::to_vec(&self) {
+            Ok(data) => data,
+            Err(err) => {
+                return Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(err.to_string())
+            }
+        };
+        Response::builder()
+            .status(self.status())
+            .header(header::CONTENT_TYPE, "application/problem+json")
+            .body(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::error::NotFoundError;
+
+    #[tokio::test]
+    async fn into_response() {
+        let resp = ProblemDetails::new(StatusCode::NOT_FOUND)
+            .detail("user not found")
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.content_type(), Some("application/problem+json"));
+        assert_eq!(
+            resp.into_body().into_json::<Value>().await.unwrap(),
+            json!({
+                "type": "about:blank",
+                "title": "Not Found",
+                "status": 404,
+                "detail": "user not found",
+            })
+        );
+    }
+
+    #[test]
+    fn from_error() {
+        let problem = ProblemDetails::from(Error::from(NotFoundError));
+        assert_eq!(problem.status(), StatusCode::NOT_FOUND);
+        assert_eq!(problem.detail.as_deref(), Some("not found"));
+
+        let problem = ProblemDetails::from(Error::from_status(StatusCode::BAD_REQUEST));
+        assert_eq!(problem.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(problem.title.as_deref(), Some("Bad Request"));
+        assert_eq!(problem.detail, None);
+    }
+
+    #[test]
+    fn deserialize() {
+        let problem: ProblemDetails = serde_json::from_value(json!({
+            "status": 409,
+            "retry": true,
+        }))
+        .unwrap();
+        assert_eq!(problem.problem_type, "about:blank");
+        assert_eq!(problem.status(), StatusCode::CONFLICT);
+        assert_eq!(problem.extensions.get("retry"), Some(&Value::Bool(true)));
+    }
+}
@@ -0,0 +1,497 @@
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    io::{Error as IoError, ErrorKind, SeekFrom},
+    ops::{Bound, Range as StdRange},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use futures_util::stream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use crate::{
+    http::{header, HeaderValue, StatusCode},
+    web::headers::{self, HeaderMapExt},
+    Body, FromRequest, IntoResponse, Request, RequestBody, Response, Result,
+};
+
+/// The maximum number of ranges to serve, a request with more ranges is
+/// answered with the whole content.
+const MAX_RANGES: usize = 16;
+
+/// The size of the chunks read from a seekable source.
+const CHUNK_SIZE: u64 = 8 * 1024;
+
+/// An extractor for the `Range` request header, supports multiple ranges.
+///
+/// If the request does not contain the `Range` header or it cannot be
+/// parsed, the extractor succeeds and the whole content should be returned.
+///
+/// See also [`PartialContent`].
+#[derive(Debug, Clone, Default)]
+pub struct Range(Option<headers::Range>);
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for Range {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(Self(req.headers().typed_get::<headers::Range>()))
+    }
+}
+
+impl Range {
+    /// Returns `true` if the request contains a valid `Range` header.
+    #[inline]
+    pub fn is_present(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Returns the satisfiable byte ranges for content with the specified
+    /// length, the end of each range is exclusive.
+    ///
+    /// The ranges are sorted, and the overlapping or adjacent ranges are
+    /// coalesced.
+    ///
+    /// Returns [`None`] if the request does not contain a `Range` header, or
+    /// if it contains more than `16` ranges after coalescing, in which case
+    /// the whole content should be returned.
+    pub fn satisfiable_ranges(&self, len: u64) -> Option<Vec<StdRange<u64>>> {
+        let range = self.0.as_ref()?;
+        let mut ranges = range
+            .satisfiable_ranges(len)
+            .filter_map(|(start, end)| {
+                let start = match start {
+                    Bound::Included(n) => n,
+                    Bound::Excluded(n) => n + 1,
+                    Bound::Unbounded => 0,
+                };
+                let end = match end {
+                    Bound::Included(n) => n + 1,
+                    Bound::Excluded(n) => n,
+                    Bound::Unbounded => len,
+                }
+                .min(len);
+                (start < end).then_some(start..end)
+            })
+            .collect::<Vec<_>>();
+
+        ranges.sort_by_key(|range| range.start);
+        let mut coalesced: Vec<StdRange<u64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match coalesced.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => coalesced.push(range),
+            }
+        }
+
+        (coalesced.len() <= MAX_RANGES).then_some(coalesced)
+    }
+}
+
+trait SeekableRead: AsyncRead + AsyncSeek + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncSeek + Send + Unpin> SeekableRead for T {}
+
+enum Source {
+    Bytes(Bytes),
+    Seekable(Box<dyn SeekableRead>),
+}
+
+impl Source {
+    async fn seek(&mut self, pos: u64) -> std::io::Result<()> {
+        if let Source::Seekable(reader) = self {
+            reader.seek(SeekFrom::Start(pos)).await?;
+        }
+        Ok(())
+    }
+
+    /// Reads the next chunk of the range, which is at `pos` and has
+    /// `remaining` bytes left.
+    async fn read_chunk(&mut self, pos: u64, remaining: u64) -> std::io::Result<Bytes> {
+        match self {
+            Source::Bytes(data) => Ok(data.slice(pos as usize..(pos + remaining) as usize)),
+            Source::Seekable(reader) => {
+                let mut data = vec![0; remaining.min(CHUNK_SIZE) as usize];
+                let n = reader.read(&mut data).await?;
+                if n == 0 {
+                    return Err(IoError::from(ErrorKind::UnexpectedEof));
+                }
+                data.truncate(n);
+                Ok(data.into())
+            }
+        }
+    }
+}
+
+enum PartialBody {
+    Full(Body),
+    Single(Body, StdRange<u64>),
+    Multiple(Source, Vec<StdRange<u64>>),
+    Unsatisfiable,
+}
+
+/// The state of a streamed `multipart/byteranges` body.
+struct MultipartState {
+    source: Source,
+    /// The head of each part and its range.
+    parts: VecDeque<(String, StdRange<u64>)>,
+    /// The position and the remaining length of the current part.
+    current: Option<(u64, u64)>,
+    closing: Option<String>,
+}
+
+fn multipart_body(state: MultipartState) -> Body {
+    Body::from_bytes_stream(stream::try_unfold(state, |mut state| async move {
+        if let Some((pos, remaining)) = state.current {
+            if remaining == 0 {
+                state.current = None;
+                return Ok(Some((Bytes::from_static(b"\r\n"), state)));
+            }
+            let chunk = state.source.read_chunk(pos, remaining).await?;
+            let n = chunk.len() as u64;
+            state.current = Some((pos + n, remaining - n));
+            return Ok(Some((chunk, state)));
+        }
+
+        if let Some((head, range)) = state.parts.pop_front() {
+            state.source.seek(range.start).await?;
+            state.current = Some((range.start, range.end - range.start));
+            return Ok(Some((Bytes::from(head), state)));
+        }
+
+        match state.closing.take() {
+            Some(closing) => Ok(Some((Bytes::from(closing), state))),
+            None => Ok::<_, IoError>(None),
+        }
+    }))
+}
+
+/// A response that serves the ranges requested by [`Range`].
+///
+/// - If the request does not contain a `Range` header, the whole content is
+///   returned with `200 OK`.
+/// - If there is a single satisfiable range, it is returned with `206 Partial
+///   Content` and the `Content-Range` header.
+/// - If there are multiple satisfiable ranges, they are returned with `206
+///   Partial Content` as a `multipart/byteranges` body. The overlapping or
+///   adjacent ranges are coalesced, and if there are more than `16` ranges,
+///   the whole content is returned with `200 OK`.
+/// - If none of the ranges are satisfiable, `416 Range Not Satisfiable` is
+///   returned with `Content-Range: bytes */<length>`.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::StatusCode,
+///     test::TestClient,
+///     web::{PartialContent, Range},
+/// };
+///
+/// #[handler]
+/// fn index(range: Range) -> PartialContent {
+///     PartialContent::from_bytes(&range, "hello world").content_type("text/plain")
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").header("range", "bytes=0-4").send().await;
+/// resp.assert_status(StatusCode::PARTIAL_CONTENT);
+/// resp.assert_header("content-range", "bytes 0-4/11");
+/// resp.assert_text("hello").await;
+///
+/// let resp = cli.get("/").header("range", "bytes=20-").send().await;
+/// resp.assert_status(StatusCode::RANGE_NOT_SATISFIABLE);
+/// resp.assert_header("content-range", "bytes */11");
+/// # });
+/// ```
+pub struct PartialContent {
+    body: PartialBody,
+    len: u64,
+    content_type: Option<String>,
+}
+
+impl PartialContent {
+    /// Create a `PartialContent` from in-memory data.
+    pub fn from_bytes(range: &Range, data: impl Into<Bytes>) -> Self {
+        let data = data.into();
+        let len = data.len() as u64;
+
+        let body = match range.satisfiable_ranges(len) {
+            None => PartialBody::Full(Body::from_bytes(data)),
+            Some(ranges) if ranges.is_empty() => PartialBody::Unsatisfiable,
+            Some(mut ranges) if ranges.len() == 1 => {
+                let range = ranges.remove(0);
+                PartialBody::Single(
+                    Body::from_bytes(data.slice(range.start as usize..range.end as usize)),
+                    range,
+                )
+            }
+            Some(ranges) => PartialBody::Multiple(Source::Bytes(data), ranges),
+        };
+
+        Self {
+            body,
+            len,
+            content_type: None,
+        }
+    }
+
+    /// Create a `PartialContent` from a seekable source with the specified
+    /// length.
+    ///
+    /// The ranges are streamed from the source.
+    pub async fn from_seekable<R>(range: &Range, mut reader: R, len: u64) -> std::io::Result<Self>
+    where
+        R: AsyncRead + AsyncSeek + Send + Unpin + 'static,
+    {
+        let body = match range.satisfiable_ranges(len) {
+            None => PartialBody::Full(Body::from_async_read(reader.take(len))),
+            Some(ranges) if ranges.is_empty() => PartialBody::Unsatisfiable,
+            Some(mut ranges) if ranges.len() == 1 => {
+                let range = ranges.remove(0);
+                reader.seek(SeekFrom::Start(range.start)).await?;
+                PartialBody::Single(
+                    Body::from_async_read(reader.take(range.end - range.start)),
+                    range,
+                )
+            }
+            Some(ranges) => PartialBody::Multiple(Source::Seekable(Box::new(reader)), ranges),
+        };
+
+        Ok(Self {
+            body,
+            len,
+            content_type: None,
+        })
+    }
+
+    /// Sets the content type of the content.
+    #[must_use]
+    pub fn content_type(self, content_type: impl Into<String>) -> Self {
+        Self {
+            content_type: Some(content_type.into()),
+            ..self
+        }
+    }
+}
+
+fn content_range(range: &StdRange<u64>, len: u64) -> String {
+    format!("bytes {}-{}/{}", range.start, range.end - 1, len)
+}
+
+impl IntoResponse for PartialContent {
+    fn into_response(self) -> Response {
+        let mut builder = Response::builder().header(header::ACCEPT_RANGES, "bytes");
+
+        match self.body {
+            PartialBody::Full(body) => {
+                if let Some(content_type) = self.content_type {
+                    builder = builder.content_type(content_type);
+                }
+                builder.header(header::CONTENT_LENGTH, self.len).body(body)
+            }
+            PartialBody::Single(body, range) => {
+                if let Some(content_type) = self.content_type {
+                    builder = builder.content_type(content_type);
+                }
+                builder
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(header::CONTENT_RANGE, content_range(&range, self.len))
+                    .header(header::CONTENT_LENGTH, range.end - range.start)
+                    .body(body)
+            }
+            PartialBody::Multiple(source, ranges) => {
+                let boundary = format!(
+                    "byteranges_{:x}",
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_nanos())
+                        .unwrap_or_default()
+                );
+                let parts = ranges
+                    .into_iter()
+                    .map(|range| {
+                        let mut head = format!("--{boundary}\r\n");
+                        if let Some(content_type) = &self.content_type {
+                            _ = write!(head, "Content-Type: {content_type}\r\n");
+                        }
+                        _ = write!(
+                            head,
+                            "Content-Range: {}\r\n\r\n",
+                            content_range(&range, self.len)
+                        );
+                        (head, range)
+                    })
+                    .collect();
+
+                builder
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .content_type(format!("multipart/byteranges; boundary={boundary}"))
+                    .body(multipart_body(MultipartState {
+                        source,
+                        parts,
+                        current: None,
+                        closing: Some(format!("--{boundary}--\r\n")),
+                    }))
+            }
+            PartialBody::Unsatisfiable => builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(
+                    header::CONTENT_RANGE,
+                    HeaderValue::try_from(format!("bytes */{}", self.len)).unwrap(),
+                )
+                .finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{handler, test::TestClient};
+
+    #[tokio::test]
+    #[allow(clippy::single_range_in_vec_init)]
+    async fn satisfiable_ranges() {
+        async fn range(value: &str) -> Range {
+            let (req, mut body) = Request::builder().header("range", value).finish().split();
+            Range::from_request(&req, &mut body).await.unwrap()
+        }
+
+        assert_eq!(
+            range("bytes=0-4").await.satisfiable_ranges(10),
+            Some(vec![0..5])
+        );
+        assert_eq!(
+            range("bytes=5-").await.satisfiable_ranges(10),
+            Some(vec![5..10])
+        );
+        assert_eq!(
+            range("bytes=-3").await.satisfiable_ranges(10),
+            Some(vec![7..10])
+        );
+        assert_eq!(
+            range("bytes=0-100").await.satisfiable_ranges(10),
+            Some(vec![0..10])
+        );
+        assert_eq!(
+            range("bytes=0-1, 4-5").await.satisfiable_ranges(10),
+            Some(vec![0..2, 4..6])
+        );
+        assert_eq!(
+            range("bytes=4-5, 0-1").await.satisfiable_ranges(10),
+            Some(vec![0..2, 4..6])
+        );
+        assert_eq!(
+            range("bytes=0-3, 2-5, 6-7, 9-")
+                .await
+                .satisfiable_ranges(10),
+            Some(vec![0..8, 9..10])
+        );
+        assert_eq!(
+            range("bytes=0-, 0-, 0-").await.satisfiable_ranges(10),
+            Some(vec![0..10])
+        );
+        let many = (0..17)
+            .map(|n| format!("{}-{}", n * 2, n * 2))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            range(&format!("bytes={}", many.join(",")))
+                .await
+                .satisfiable_ranges(100),
+            None
+        );
+        assert_eq!(
+            range("bytes=20-").await.satisfiable_ranges(10),
+            Some(vec![])
+        );
+        assert_eq!(Range::default().satisfiable_ranges(10), None);
+    }
+
+    #[tokio::test]
+    async fn partial_content() {
+        #[handler(internal)]
+        async fn index(range: Range) -> PartialContent {
+            PartialContent::from_seekable(&range, Cursor::new(b"0123456789".to_vec()), 10)
+                .await
+                .unwrap()
+        }
+
+        let cli = TestClient::new(index);
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header(header::ACCEPT_RANGES, "bytes");
+        resp.assert_text("0123456789").await;
+
+        let resp = cli.get("/").header("range", "bytes=2-5").send().await;
+        resp.assert_status(StatusCode::PARTIAL_CONTENT);
+        resp.assert_header(header::CONTENT_RANGE, "bytes 2-5/10");
+        resp.assert_text("2345").await;
+
+        let resp = cli.get("/").header("range", "bytes=0-1,8-").send().await;
+        resp.assert_status(StatusCode::PARTIAL_CONTENT);
+        let content_type = resp.0.content_type().unwrap().to_string();
+        assert!(content_type.starts_with("multipart/byteranges; boundary="));
+        let text = resp.0.into_body().into_string().await.unwrap();
+        assert!(text.contains("Content-Range: bytes 0-1/10\r\n\r\n01\r\n"));
+        assert!(text.contains("Content-Range: bytes 8-9/10\r\n\r\n89\r\n"));
+        assert!(text.ends_with("--\r\n"));
+
+        let resp = cli.get("/").header("range", "bytes=0-,0-,0-").send().await;
+        resp.assert_status(StatusCode::PARTIAL_CONTENT);
+        resp.assert_header(header::CONTENT_RANGE, "bytes 0-9/10");
+        resp.assert_text("0123456789").await;
+
+        let many = (0..17).map(|n| format!("{n}-{n}")).collect::<Vec<_>>();
+        let resp = cli
+            .get("/")
+            .header("range", format!("bytes={}", many.join(",")))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::PARTIAL_CONTENT);
+        resp.assert_header(header::CONTENT_RANGE, "bytes 0-9/10");
+
+        let resp = cli.get("/").header("range", "bytes=10-").send().await;
+        resp.assert_status(StatusCode::RANGE_NOT_SATISFIABLE);
+        resp.assert_header(header::CONTENT_RANGE, "bytes */10");
+    }
+
+    #[tokio::test]
+    async fn too_many_ranges() {
+        #[handler(internal)]
+        async fn index(range: Range) -> PartialContent {
+            PartialContent::from_bytes(&range, vec![b'a'; 100])
+        }
+
+        let cli = TestClient::new(index);
+        let ranges = |n: u64| {
+            (0..n)
+                .map(|n| format!("{}-{}", n * 2, n * 2))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let resp = cli
+            .get("/")
+            .header("range", format!("bytes={}", ranges(16)))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::PARTIAL_CONTENT);
+        let text = resp.0.into_body().into_string().await.unwrap();
+        assert_eq!(text.matches("Content-Range:").count(), 16);
+
+        let resp = cli
+            .get("/")
+            .header("range", format!("bytes={}", ranges(17)))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header(header::CONTENT_LENGTH, "100");
+    }
+}
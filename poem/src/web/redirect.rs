@@ -1,12 +1,18 @@
 use std::fmt::Display;
 
 use crate::{
-    http::{header, StatusCode},
-    IntoResponse, Response,
+    error::InvalidHeaderValueError,
+    http::{header, StatusCode, Uri},
+    web::checked_header_value,
+    IntoResponse, Request, Response,
 };
 
 /// A redirect response.
 ///
+/// If the location contains a line break or any other character that is not
+/// allowed in a header value, an [`InvalidHeaderValueError`] response is
+/// returned instead of the redirect.
+///
 /// # Example
 ///
 /// ```
@@ -68,13 +74,89 @@ impl Redirect {
             uri: uri.to_string(),
         }
     }
+
+    /// A simple `302` redirect to a different location.
+    pub fn found(uri: impl Display) -> Self {
+        Self {
+            status: StatusCode::FOUND,
+            uri: uri.to_string(),
+        }
+    }
+
+    /// A `303` redirect to the page specified by the `Referer` header, or to
+    /// `fallback` if the request does not contain the `Referer` header.
+    ///
+    /// To prevent open redirects, the `Referer` header is only used if it is
+    /// a relative reference or its authority is the same as the `Host` header
+    /// of the request.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     handler,
+    ///     http::{header, StatusCode},
+    ///     test::TestClient,
+    ///     web::Redirect,
+    ///     Request,
+    /// };
+    ///
+    /// #[handler]
+    /// fn save(req: &Request) -> Redirect {
+    ///     Redirect::back(req, "/")
+    /// }
+    ///
+    /// let cli = TestClient::new(save);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = cli
+    ///     .post("/")
+    ///     .header(header::HOST, "example.com")
+    ///     .header(header::REFERER, "https://example.com/edit")
+    ///     .send()
+    ///     .await;
+    /// resp.assert_status(StatusCode::SEE_OTHER);
+    /// resp.assert_header(header::LOCATION, "https://example.com/edit");
+    ///
+    /// let resp = cli.post("/").send().await;
+    /// resp.assert_header(header::LOCATION, "/");
+    /// # });
+    /// ```
+    pub fn back(req: &Request, fallback: impl Display) -> Self {
+        let referer = req
+            .header(header::REFERER)
+            .filter(|referer| is_same_origin(req, referer));
+        match referer {
+            Some(referer) => Self::see_other(referer),
+            None => Self::see_other(fallback),
+        }
+    }
+
+    /// Checks whether the location is a valid header value.
+    pub fn validate(&self) -> Result<(), InvalidHeaderValueError> {
+        checked_header_value(header::LOCATION.as_str(), &self.uri).map(|_| ())
+    }
+}
+
+fn is_same_origin(req: &Request, referer: &str) -> bool {
+    let uri = match referer.parse::<Uri>() {
+        Ok(uri) => uri,
+        Err(_) => return false,
+    };
+    match uri.authority() {
+        Some(authority) => req
+            .header(header::HOST)
+            .is_some_and(|host| authority.as_str().eq_ignore_ascii_case(host)),
+        None => referer.starts_with('/') && !referer.starts_with("//"),
+    }
 }
 
 impl IntoResponse for Redirect {
     fn into_response(self) -> Response {
-        self.status
-            .with_header(header::LOCATION, self.uri)
-            .into_response()
+        match checked_header_value(header::LOCATION.as_str(), &self.uri) {
+            Ok(location) => self.status.with_header(header::LOCATION, location).into_response(),
+            Err(err) => crate::Error::from(err).into_response(),
+        }
     }
 }
 
@@ -102,4 +184,63 @@ mod tests {
     test_redirect!(moved_permanent, MOVED_PERMANENTLY);
     test_redirect!(see_other, SEE_OTHER);
     test_redirect!(temporary, TEMPORARY_REDIRECT);
+    test_redirect!(found, FOUND);
+
+    #[test]
+    fn back() {
+        let location = |req: Request| {
+            Redirect::back(&req, "/home")
+                .into_response()
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+                .unwrap()
+        };
+
+        assert_eq!(location(Request::default()), "/home");
+        assert_eq!(
+            location(Request::builder().header(header::REFERER, "/a?b=1").finish()),
+            "/a?b=1"
+        );
+        assert_eq!(
+            location(
+                Request::builder()
+                    .header(header::HOST, "example.com:8080")
+                    .header(header::REFERER, "http://example.com:8080/a")
+                    .finish()
+            ),
+            "http://example.com:8080/a"
+        );
+        assert_eq!(
+            location(
+                Request::builder()
+                    .header(header::HOST, "example.com")
+                    .header(header::REFERER, "https://evil.com/a")
+                    .finish()
+            ),
+            "/home"
+        );
+        assert_eq!(
+            location(
+                Request::builder()
+                    .header(header::REFERER, "//evil.com/a")
+                    .finish()
+            ),
+            "/home"
+        );
+    }
+
+    #[test]
+    fn header_injection() {
+        let redirect = Redirect::see_other("/a\r\nSet-Cookie: a=b");
+        assert_eq!(
+            redirect.validate(),
+            Err(InvalidHeaderValueError::LineBreak("location".to_string()))
+        );
+        let resp = redirect.into_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(resp.headers().get(header::LOCATION).is_none());
+        assert!(resp.headers().get(header::SET_COOKIE).is_none());
+    }
 }
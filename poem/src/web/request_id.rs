@@ -0,0 +1,67 @@
+use std::{fmt::Display, ops::Deref, sync::Arc};
+
+use crate::{error::GetDataError, FromRequest, Request, RequestBody, Result};
+
+/// The identifier of the request, which is set by the
+/// [`SetRequestId`](crate::middleware::SetRequestId) middleware.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler, middleware::SetRequestId, test::TestClient, web::RequestId, EndpointExt,
+/// };
+///
+/// #[handler]
+/// fn index(request_id: RequestId) -> String {
+///     request_id.to_string()
+/// }
+///
+/// let cli = TestClient::new(index.with(SetRequestId::new()));
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").header("x-request-id", "abc").send().await;
+/// resp.assert_header("x-request-id", "abc");
+/// resp.assert_text("abc").await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "request-id")))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(Arc<str>);
+
+impl RequestId {
+    pub(crate) fn new(id: impl Into<Arc<str>>) -> Self {
+        Self(id.into())
+    }
+
+    /// Returns the identifier as a string slice.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for RequestId {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for RequestId {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req
+            .extensions()
+            .get::<RequestId>()
+            .cloned()
+            .ok_or_else(|| GetDataError(std::any::type_name::<RequestId>()))?)
+    }
+}
@@ -0,0 +1,427 @@
+//! Socket.IO server.
+//!
+//! This module implements the server side of the
+//! [Engine.IO protocol v4](https://socket.io/docs/v4/engine-io-protocol/) and
+//! the [Socket.IO protocol v5](https://socket.io/docs/v4/socket-io-protocol/),
+//! over HTTP long-polling and WebSocket, so that the Socket.IO JavaScript
+//! clients of version 3 and 4 can connect to a poem server.
+//!
+//! Namespaces, events and acknowledgements are supported. Binary events with
+//! attachments and the acknowledgements of the events emitted by the server
+//! are not supported.
+//!
+//! # Example
+//!
+//! ```
+//! use poem::{web::socketio::SocketIo, Route};
+//!
+//! let app = Route::new().nest(
+//!     "/socket.io",
+//!     SocketIo::new().namespace("/", |mut socket| async move {
+//!         while let Some(event) = socket.recv().await {
+//!             if event.name == "chat" {
+//!                 let _ = socket.emit("chat", &event.args);
+//!             }
+//!             if let Some(ack) = event.ack {
+//!                 let _ = ack.send("received");
+//!             }
+//!         }
+//!     }),
+//! );
+//! ```
+
+mod packet;
+mod session;
+mod socket;
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+use futures_util::{FutureExt, SinkExt, StreamExt};
+use serde::Deserialize;
+
+pub use self::socket::{Ack, Event, Socket};
+use self::{
+    packet::{EnginePacket, PACKET_SEPARATOR},
+    session::{Config, Session, State},
+};
+use crate::{
+    error::SocketIoError,
+    http::Method,
+    web::websocket::{Message, WebSocket, WebSocketStream},
+    Endpoint, FromRequest, IntoEndpoint, IntoResponse, Request, Response, Result,
+};
+
+/// A Socket.IO server endpoint, which is usually nested at `/socket.io`, the
+/// default path of the clients.
+#[cfg_attr(docsrs, doc(cfg(feature = "socketio")))]
+pub struct SocketIo {
+    config: Config,
+}
+
+impl Default for SocketIo {
+    fn default() -> Self {
+        Self {
+            config: Config {
+                namespaces: HashMap::new(),
+                ping_interval: Duration::from_secs(25),
+                ping_timeout: Duration::from_secs(20),
+                max_payload: 1_000_000,
+            },
+        }
+    }
+}
+
+impl SocketIo {
+    /// Create a `SocketIo` endpoint.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers the handler of a namespace, such as `/` or `/admin`, which
+    /// is called with a [`Socket`] for each client connected to the
+    /// namespace.
+    ///
+    /// The clients connecting to the namespaces that are not registered are
+    /// rejected.
+    #[must_use]
+    pub fn namespace<F, Fut>(mut self, namespace: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(Socket) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.config.namespaces.insert(
+            namespace.into(),
+            Arc::new(move |socket| handler(socket).boxed()),
+        );
+        self
+    }
+
+    /// Sets the interval of the heartbeat, default to `25s`.
+    #[must_use]
+    pub fn ping_interval(mut self, interval: Duration) -> Self {
+        self.config.ping_interval = interval;
+        self
+    }
+
+    /// Sets the time to wait for the response to the heartbeat before the
+    /// session is closed, default to `20s`.
+    #[must_use]
+    pub fn ping_timeout(mut self, timeout: Duration) -> Self {
+        self.config.ping_timeout = timeout;
+        self
+    }
+
+    /// Sets the maximum number of bytes of a polling request, default to
+    /// `1000000`.
+    #[must_use]
+    pub fn max_payload(mut self, max_payload: usize) -> Self {
+        self.config.max_payload = max_payload;
+        self
+    }
+}
+
+impl IntoEndpoint for SocketIo {
+    type Endpoint = SocketIoEndpoint;
+
+    fn into_endpoint(self) -> Self::Endpoint {
+        SocketIoEndpoint {
+            state: Arc::new(State::new(self.config)),
+        }
+    }
+}
+
+/// Endpoint for [`SocketIo`].
+#[cfg_attr(docsrs, doc(cfg(feature = "socketio")))]
+pub struct SocketIoEndpoint {
+    state: Arc<State>,
+}
+
+#[derive(Deserialize)]
+struct EngineQuery {
+    #[serde(rename = "EIO")]
+    eio: Option<String>,
+    transport: Option<String>,
+    sid: Option<String>,
+}
+
+fn polling_response(packets: &[EnginePacket]) -> Response {
+    let payload = packets
+        .iter()
+        .map(EnginePacket::encode)
+        .collect::<Vec<_>>()
+        .join(&PACKET_SEPARATOR.to_string());
+    Response::builder()
+        .content_type("text/plain; charset=UTF-8")
+        .body(payload)
+}
+
+impl SocketIoEndpoint {
+    fn session(&self, sid: &str) -> Result<Arc<Session>> {
+        self.state
+            .session(sid)
+            .ok_or_else(|| SocketIoError::UnknownSession.into())
+    }
+
+    async fn poll(&self, session: Arc<Session>) -> Result<Response> {
+        if session.is_upgraded() {
+            return Err(SocketIoError::BadRequest.into());
+        }
+        // only one polling request is allowed at a time
+        let mut outbound = session
+            .outbound_rx
+            .try_lock()
+            .map_err(|_| SocketIoError::BadRequest)?;
+
+        let first = tokio::select! {
+            packet = outbound.recv() => packet,
+            _ = session.closed.cancelled() => None,
+        };
+        let packets = match first {
+            Some(packet) => {
+                let mut packets = vec![packet];
+                while let Ok(packet) = outbound.try_recv() {
+                    packets.push(packet);
+                }
+                packets
+            }
+            None => vec![EnginePacket::Close],
+        };
+        Ok(polling_response(&packets))
+    }
+
+    async fn post(&self, session: Arc<Session>, req: Request) -> Result<Response> {
+        let data = req
+            .into_body()
+            .into_bytes_limit(self.state.config.max_payload)
+            .await?;
+        let payload = String::from_utf8(data.to_vec()).map_err(|_| SocketIoError::BadRequest)?;
+        for packet in payload.split(PACKET_SEPARATOR) {
+            match EnginePacket::decode(packet) {
+                Some(packet) => session.handle_packet(&self.state.config, packet),
+                None => return Err(SocketIoError::BadRequest.into()),
+            }
+        }
+        Ok(Response::builder().content_type("text/html").body("ok"))
+    }
+}
+
+#[async_trait::async_trait]
+impl Endpoint for SocketIoEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let query: EngineQuery = serde_urlencoded::from_str(req.uri().query().unwrap_or_default())
+            .map_err(|_| SocketIoError::BadRequest)?;
+        if query.eio.as_deref() != Some("4") {
+            return Err(SocketIoError::UnsupportedProtocolVersion.into());
+        }
+
+        match (query.transport.as_deref(), query.sid) {
+            (Some("polling"), None) => {
+                if req.method() != Method::GET {
+                    return Err(SocketIoError::BadRequest.into());
+                }
+                let session = self.state.create_session();
+                Ok(polling_response(&[self
+                    .state
+                    .open_packet(&session, &["websocket"])]))
+            }
+            (Some("polling"), Some(sid)) => {
+                let session = self.session(&sid)?;
+                match *req.method() {
+                    Method::GET => self.poll(session).await,
+                    Method::POST => self.post(session, req).await,
+                    _ => Err(SocketIoError::BadRequest.into()),
+                }
+            }
+            (Some("websocket"), sid) => {
+                let session = sid.map(|sid| self.session(&sid)).transpose()?;
+                let ws = WebSocket::from_request_without_body(&req).await?;
+                let state = self.state.clone();
+                Ok(ws
+                    .on_upgrade(move |socket| serve_websocket(state, session, socket))
+                    .into_response())
+            }
+            _ => Err(SocketIoError::UnknownTransport.into()),
+        }
+    }
+}
+
+async fn serve_websocket(
+    state: Arc<State>,
+    session: Option<Arc<Session>>,
+    socket: WebSocketStream,
+) {
+    let (mut sink, mut stream) = socket.split();
+
+    let session = match session {
+        Some(session) => {
+            // upgrade from the polling transport
+            loop {
+                let packet = match stream.next().await {
+                    Some(Ok(Message::Text(text))) => EnginePacket::decode(&text),
+                    Some(Ok(_)) => continue,
+                    _ => return,
+                };
+                match packet {
+                    Some(EnginePacket::Ping(data)) if data == "probe" => {
+                        let pong = EnginePacket::Pong(data).encode();
+                        if sink.send(Message::Text(pong)).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(EnginePacket::Upgrade) => {
+                        session.upgraded.store(true, Ordering::Release);
+                        // completes the pending polling request
+                        session.send(EnginePacket::Noop);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            session
+        }
+        None => {
+            let session = state.create_session();
+            session.upgraded.store(true, Ordering::Release);
+            let open = state.open_packet(&session, &[]).encode();
+            if sink.send(Message::Text(open)).await.is_err() {
+                session.close();
+                return;
+            }
+            session
+        }
+    };
+
+    let mut outbound = session.outbound_rx.lock().await;
+    loop {
+        tokio::select! {
+            packet = outbound.recv() => match packet {
+                Some(packet) => {
+                    if sink.send(Message::Text(packet.encode())).await.is_err() {
+                        break;
+                    }
+                }
+                None => break,
+            },
+            msg = stream.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    if let Some(packet) = EnginePacket::decode(&text) {
+                        session.handle_packet(&state.config, packet);
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = session.closed.cancelled() => break,
+        }
+    }
+    session.close();
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{TestClient, TestResponse},
+    };
+
+    async fn text(resp: TestResponse) -> String {
+        resp.assert_status_is_ok();
+        resp.0.into_body().into_string().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn polling() {
+        let cli = TestClient::new(SocketIo::new().namespace("/", |mut socket| async move {
+            while let Some(event) = socket.recv().await {
+                let _ = socket.emit(event.name, &event.args[0]);
+                if let Some(ack) = event.ack {
+                    let _ = ack.send("ok");
+                }
+            }
+        }));
+
+        let open = text(
+            cli.get("/")
+                .query("EIO", &4)
+                .query("transport", &"polling")
+                .send()
+                .await,
+        )
+        .await;
+        let open: Value = serde_json::from_str(open.strip_prefix('0').unwrap()).unwrap();
+        let sid = open["sid"].as_str().unwrap().to_string();
+        assert_eq!(open["upgrades"], serde_json::json!(["websocket"]));
+
+        let send = |payload: &'static str| {
+            cli.post("/")
+                .query("EIO", &4)
+                .query("transport", &"polling")
+                .query("sid", &sid)
+                .body(payload)
+                .send()
+        };
+        let poll = || {
+            cli.get("/")
+                .query("EIO", &4)
+                .query("transport", &"polling")
+                .query("sid", &sid)
+                .send()
+        };
+
+        assert_eq!(text(send("40").await).await, "ok");
+        assert!(text(poll().await).await.starts_with("40{\"sid\":"));
+
+        assert_eq!(
+            text(send("42[\"hello\",1]\x1e421[\"world\",2]").await).await,
+            "ok"
+        );
+        let mut payload = String::new();
+        while payload.split('\x1e').count() < 3 {
+            if !payload.is_empty() {
+                payload.push('\x1e');
+            }
+            payload.push_str(&text(poll().await).await);
+        }
+        assert_eq!(payload, "42[\"hello\",1]\x1e42[\"world\",2]\x1e431[\"ok\"]");
+
+        assert_eq!(text(send("40/admin,").await).await, "ok");
+        assert_eq!(
+            text(poll().await).await,
+            "44/admin,{\"message\":\"Invalid namespace\"}"
+        );
+    }
+
+    #[tokio::test]
+    async fn bad_requests() {
+        let cli = TestClient::new(SocketIo::new());
+        cli.get("/")
+            .query("EIO", &3)
+            .query("transport", &"polling")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        cli.get("/")
+            .query("EIO", &4)
+            .query("transport", &"polling")
+            .query("sid", &"unknown")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        cli.get("/")
+            .query("EIO", &4)
+            .query("transport", &"flash")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+}
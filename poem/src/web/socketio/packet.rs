@@ -0,0 +1,193 @@
+use serde_json::Value;
+
+/// The separator of the packets in the payload of the polling transport.
+pub(crate) const PACKET_SEPARATOR: char = '\x1e';
+
+/// An Engine.IO packet.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum EnginePacket {
+    Open(String),
+    Close,
+    Ping(String),
+    Pong(String),
+    Message(String),
+    Upgrade,
+    Noop,
+}
+
+impl EnginePacket {
+    pub(crate) fn encode(&self) -> String {
+        match self {
+            EnginePacket::Open(data) => format!("0{data}"),
+            EnginePacket::Close => "1".to_string(),
+            EnginePacket::Ping(data) => format!("2{data}"),
+            EnginePacket::Pong(data) => format!("3{data}"),
+            EnginePacket::Message(data) => format!("4{data}"),
+            EnginePacket::Upgrade => "5".to_string(),
+            EnginePacket::Noop => "6".to_string(),
+        }
+    }
+
+    pub(crate) fn decode(s: &str) -> Option<Self> {
+        let mut chars = s.chars();
+        let ty = chars.next()?;
+        let data = chars.as_str().to_string();
+        Some(match ty {
+            '0' => EnginePacket::Open(data),
+            '1' => EnginePacket::Close,
+            '2' => EnginePacket::Ping(data),
+            '3' => EnginePacket::Pong(data),
+            '4' => EnginePacket::Message(data),
+            '5' => EnginePacket::Upgrade,
+            '6' => EnginePacket::Noop,
+            _ => return None,
+        })
+    }
+}
+
+pub(crate) const CONNECT: u8 = 0;
+pub(crate) const DISCONNECT: u8 = 1;
+pub(crate) const EVENT: u8 = 2;
+pub(crate) const ACK: u8 = 3;
+pub(crate) const CONNECT_ERROR: u8 = 4;
+
+/// A Socket.IO packet, which is carried by an Engine.IO message packet.
+///
+/// The binary packets with attachments are not supported.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SocketPacket {
+    pub(crate) ty: u8,
+    pub(crate) namespace: String,
+    pub(crate) id: Option<u64>,
+    pub(crate) data: Option<Value>,
+}
+
+impl SocketPacket {
+    pub(crate) fn new(ty: u8, namespace: impl Into<String>, data: Option<Value>) -> Self {
+        Self {
+            ty,
+            namespace: namespace.into(),
+            id: None,
+            data,
+        }
+    }
+
+    pub(crate) fn encode(&self) -> String {
+        let mut s = self.ty.to_string();
+        if self.namespace != "/" {
+            s.push_str(&self.namespace);
+            s.push(',');
+        }
+        if let Some(id) = self.id {
+            s.push_str(&id.to_string());
+        }
+        if let Some(data) = &self.data {
+            s.push_str(&data.to_string());
+        }
+        s
+    }
+
+    pub(crate) fn decode(s: &str) -> Option<Self> {
+        let ty = s.get(..1)?.parse::<u8>().ok()?;
+        if ty > CONNECT_ERROR {
+            return None;
+        }
+
+        let mut rest = &s[1..];
+        let mut namespace = "/";
+        if rest.starts_with('/') {
+            match rest.find(',') {
+                Some(idx) => {
+                    namespace = &rest[..idx];
+                    rest = &rest[idx + 1..];
+                }
+                None => {
+                    namespace = rest;
+                    rest = "";
+                }
+            }
+        }
+
+        let id_len = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let id = match id_len {
+            0 => None,
+            _ => Some(rest[..id_len].parse().ok()?),
+        };
+        rest = &rest[id_len..];
+
+        let data = match rest {
+            "" => None,
+            _ => Some(serde_json::from_str(rest).ok()?),
+        };
+
+        Some(Self {
+            ty,
+            namespace: namespace.to_string(),
+            id,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn engine_packet() {
+        for packet in [
+            EnginePacket::Open("{}".to_string()),
+            EnginePacket::Close,
+            EnginePacket::Ping("probe".to_string()),
+            EnginePacket::Pong(String::new()),
+            EnginePacket::Message("hello".to_string()),
+            EnginePacket::Upgrade,
+            EnginePacket::Noop,
+        ] {
+            assert_eq!(EnginePacket::decode(&packet.encode()), Some(packet));
+        }
+        assert_eq!(EnginePacket::decode(""), None);
+        assert_eq!(EnginePacket::decode("9"), None);
+    }
+
+    #[test]
+    fn socket_packet() {
+        assert_eq!(
+            SocketPacket::decode("0"),
+            Some(SocketPacket::new(CONNECT, "/", None))
+        );
+        assert_eq!(
+            SocketPacket::decode(r#"0/admin,{"token":"123"}"#),
+            Some(SocketPacket::new(
+                CONNECT,
+                "/admin",
+                Some(json!({"token": "123"}))
+            ))
+        );
+        assert_eq!(
+            SocketPacket::decode(r#"2/admin,12["hello",1]"#),
+            Some(SocketPacket {
+                ty: EVENT,
+                namespace: "/admin".to_string(),
+                id: Some(12),
+                data: Some(json!(["hello", 1])),
+            })
+        );
+        assert_eq!(
+            SocketPacket::decode(r#"51-["hello",{"_placeholder":true,"num":0}]"#),
+            None
+        );
+        assert_eq!(SocketPacket::decode("2[invalid"), None);
+
+        let packet = SocketPacket {
+            ty: ACK,
+            namespace: "/admin".to_string(),
+            id: Some(3),
+            data: Some(json!(["ok"])),
+        };
+        assert_eq!(packet.encode(), r#"3/admin,3["ok"]"#);
+        assert_eq!(SocketPacket::new(DISCONNECT, "/", None).encode(), "1");
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use futures_util::future::BoxFuture;
+use parking_lot::Mutex;
+use rand::{thread_rng, Rng};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, Mutex as AsyncMutex, Notify};
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    packet::{EnginePacket, SocketPacket, CONNECT, CONNECT_ERROR, DISCONNECT, EVENT},
+    socket::{Ack, Event, Socket},
+};
+
+pub(crate) type NamespaceHandler = Arc<dyn Fn(Socket) -> BoxFuture<'static, ()> + Send + Sync>;
+
+#[derive(Clone)]
+pub(crate) struct Config {
+    pub(crate) namespaces: HashMap<String, NamespaceHandler>,
+    pub(crate) ping_interval: Duration,
+    pub(crate) ping_timeout: Duration,
+    pub(crate) max_payload: usize,
+}
+
+pub(crate) fn generate_id() -> String {
+    URL_SAFE_NO_PAD.encode(thread_rng().gen::<[u8; 15]>())
+}
+
+/// The state shared by all sessions of a `SocketIo` endpoint.
+pub(crate) struct State {
+    pub(crate) config: Config,
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+}
+
+impl State {
+    pub(crate) fn new(config: Config) -> Self {
+        Self {
+            config,
+            sessions: Default::default(),
+        }
+    }
+
+    pub(crate) fn session(&self, sid: &str) -> Option<Arc<Session>> {
+        self.sessions.lock().get(sid).cloned()
+    }
+
+    /// Creates a session, which is removed when it is closed or the client
+    /// does not respond to the heartbeat.
+    pub(crate) fn create_session(self: &Arc<Self>) -> Arc<Session> {
+        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
+        let session = Arc::new(Session {
+            sid: generate_id(),
+            outbound_tx,
+            outbound_rx: AsyncMutex::new(outbound_rx),
+            sockets: Default::default(),
+            pong: Notify::new(),
+            upgraded: AtomicBool::new(false),
+            closed: CancellationToken::new(),
+        });
+        self.sessions
+            .lock()
+            .insert(session.sid.clone(), session.clone());
+        tokio::spawn(heartbeat(self.clone(), session.clone()));
+        session
+    }
+
+    /// Returns the open packet of the session.
+    pub(crate) fn open_packet(&self, session: &Session, upgrades: &[&str]) -> EnginePacket {
+        EnginePacket::Open(
+            json!({
+                "sid": session.sid,
+                "upgrades": upgrades,
+                "pingInterval": self.config.ping_interval.as_millis() as u64,
+                "pingTimeout": self.config.ping_timeout.as_millis() as u64,
+                "maxPayload": self.config.max_payload,
+            })
+            .to_string(),
+        )
+    }
+}
+
+async fn heartbeat(state: Arc<State>, session: Arc<Session>) {
+    loop {
+        tokio::select! {
+            _ = session.closed.cancelled() => break,
+            _ = tokio::time::sleep(state.config.ping_interval) => {}
+        }
+
+        session.send(EnginePacket::Ping(String::new()));
+        tokio::select! {
+            _ = session.closed.cancelled() => break,
+            _ = session.pong.notified() => {}
+            _ = tokio::time::sleep(state.config.ping_timeout) => {
+                session.close();
+                break;
+            }
+        }
+    }
+    state.sessions.lock().remove(&session.sid);
+}
+
+/// An Engine.IO session, which may be transported by HTTP long-polling or
+/// WebSocket.
+pub(crate) struct Session {
+    pub(crate) sid: String,
+    outbound_tx: mpsc::UnboundedSender<EnginePacket>,
+    /// The packets to be sent, which are taken by the active transport.
+    pub(crate) outbound_rx: AsyncMutex<mpsc::UnboundedReceiver<EnginePacket>>,
+    /// The event senders of the connected namespaces.
+    sockets: Mutex<HashMap<String, mpsc::UnboundedSender<Event>>>,
+    pong: Notify,
+    pub(crate) upgraded: AtomicBool,
+    pub(crate) closed: CancellationToken,
+}
+
+impl Session {
+    pub(crate) fn send(&self, packet: EnginePacket) {
+        let _ = self.outbound_tx.send(packet);
+    }
+
+    pub(crate) fn send_socket_packet(&self, packet: SocketPacket) {
+        self.send(EnginePacket::Message(packet.encode()));
+    }
+
+    pub(crate) fn is_upgraded(&self) -> bool {
+        self.upgraded.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn close(&self) {
+        self.closed.cancel();
+        self.sockets.lock().clear();
+    }
+
+    /// Removes the socket of the namespace, returns `false` if the socket
+    /// has already been removed.
+    pub(crate) fn remove_socket(&self, namespace: &str) -> bool {
+        self.sockets.lock().remove(namespace).is_some()
+    }
+
+    pub(crate) fn handle_packet(self: &Arc<Self>, config: &Config, packet: EnginePacket) {
+        match packet {
+            EnginePacket::Ping(data) => self.send(EnginePacket::Pong(data)),
+            EnginePacket::Pong(_) => self.pong.notify_one(),
+            EnginePacket::Message(data) => {
+                if let Some(packet) = SocketPacket::decode(&data) {
+                    self.handle_socket_packet(config, packet);
+                }
+            }
+            EnginePacket::Close => self.close(),
+            EnginePacket::Open(_) | EnginePacket::Upgrade | EnginePacket::Noop => {}
+        }
+    }
+
+    fn handle_socket_packet(self: &Arc<Self>, config: &Config, packet: SocketPacket) {
+        match packet.ty {
+            CONNECT => {
+                let handler = match config.namespaces.get(&packet.namespace) {
+                    Some(handler) => handler,
+                    None => {
+                        self.send_socket_packet(SocketPacket::new(
+                            CONNECT_ERROR,
+                            packet.namespace,
+                            Some(json!({ "message": "Invalid namespace" })),
+                        ));
+                        return;
+                    }
+                };
+
+                let (tx, rx) = mpsc::unbounded_channel();
+                let id = generate_id();
+                self.sockets.lock().insert(packet.namespace.clone(), tx);
+                self.send_socket_packet(SocketPacket::new(
+                    CONNECT,
+                    packet.namespace.clone(),
+                    Some(json!({ "sid": id })),
+                ));
+                tokio::spawn(handler(Socket::new(
+                    id,
+                    packet.namespace,
+                    packet.data.unwrap_or(Value::Null),
+                    rx,
+                    self.clone(),
+                )));
+            }
+            DISCONNECT => {
+                self.remove_socket(&packet.namespace);
+            }
+            EVENT => {
+                let mut args = match packet.data {
+                    Some(Value::Array(args)) => args,
+                    _ => return,
+                };
+                let name = match args.first() {
+                    Some(Value::String(name)) => name.clone(),
+                    _ => return,
+                };
+                args.remove(0);
+                let sender = match self.sockets.lock().get(&packet.namespace) {
+                    Some(sender) => sender.clone(),
+                    None => return,
+                };
+                let ack = packet
+                    .id
+                    .map(|id| Ack::new(id, packet.namespace, self.clone()));
+                let _ = sender.send(Event { name, args, ack });
+            }
+            _ => {}
+        }
+    }
+}
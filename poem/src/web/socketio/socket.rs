@@ -0,0 +1,156 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use super::{
+    packet::{SocketPacket, ACK, DISCONNECT, EVENT},
+    session::Session,
+};
+
+/// A Socket.IO connection to a namespace, which is passed to the handler
+/// registered with [`SocketIo::namespace`](super::SocketIo::namespace).
+///
+/// The client is disconnected from the namespace when the socket is dropped.
+pub struct Socket {
+    id: String,
+    namespace: String,
+    auth: Value,
+    events: mpsc::UnboundedReceiver<Event>,
+    session: Arc<Session>,
+}
+
+impl Debug for Socket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Socket")
+            .field("id", &self.id)
+            .field("namespace", &self.namespace)
+            .finish()
+    }
+}
+
+impl Socket {
+    pub(crate) fn new(
+        id: String,
+        namespace: String,
+        auth: Value,
+        events: mpsc::UnboundedReceiver<Event>,
+        session: Arc<Session>,
+    ) -> Self {
+        Self {
+            id,
+            namespace,
+            auth,
+            events,
+            session,
+        }
+    }
+
+    /// Returns the id of this socket.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the namespace of this socket, such as `/` or `/admin`.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Returns the `auth` payload sent by the client when connecting to the
+    /// namespace, or [`Value::Null`] if there is none.
+    pub fn auth(&self) -> &Value {
+        &self.auth
+    }
+
+    /// Receives the next event sent by the client, returns `None` if the
+    /// client has disconnected.
+    pub async fn recv(&mut self) -> Option<Event> {
+        self.events.recv().await
+    }
+
+    /// Emits an event with the data to the client.
+    pub fn emit(&self, event: impl Into<String>, data: impl Serialize) -> serde_json::Result<()> {
+        let data = serde_json::to_value(data)?;
+        self.session.send_socket_packet(SocketPacket::new(
+            EVENT,
+            self.namespace.clone(),
+            Some(Value::Array(vec![Value::String(event.into()), data])),
+        ));
+        Ok(())
+    }
+
+    /// Disconnects the client from the namespace.
+    pub fn disconnect(self) {
+        drop(self);
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        if self.session.remove_socket(&self.namespace) {
+            self.session.send_socket_packet(SocketPacket::new(
+                DISCONNECT,
+                self.namespace.clone(),
+                None,
+            ));
+        }
+    }
+}
+
+/// An event sent by the client.
+#[derive(Debug)]
+pub struct Event {
+    /// The name of the event.
+    pub name: String,
+    /// The arguments of the event.
+    pub args: Vec<Value>,
+    /// The acknowledgement requested by the client.
+    pub ack: Option<Ack>,
+}
+
+impl Event {
+    /// Deserializes the first argument of the event.
+    pub fn data<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        T::deserialize(self.args.first().unwrap_or(&Value::Null))
+    }
+}
+
+/// The acknowledgement of an [`Event`], which replies to the callback of
+/// the client.
+pub struct Ack {
+    id: u64,
+    namespace: String,
+    session: Arc<Session>,
+}
+
+impl Debug for Ack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ack").field("id", &self.id).finish()
+    }
+}
+
+impl Ack {
+    pub(crate) fn new(id: u64, namespace: String, session: Arc<Session>) -> Self {
+        Self {
+            id,
+            namespace,
+            session,
+        }
+    }
+
+    /// Sends the acknowledgement with the data.
+    pub fn send(self, data: impl Serialize) -> serde_json::Result<()> {
+        let data = serde_json::to_value(data)?;
+        self.session.send_socket_packet(SocketPacket {
+            ty: ACK,
+            namespace: self.namespace,
+            id: Some(self.id),
+            data: Some(Value::Array(vec![data])),
+        });
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt::{self, Display, Formatter};
 
+use serde::Serialize;
+
 /// An "event", either an incoming message or some meta-action that needs to be
 /// applied to the stream.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -60,6 +62,22 @@ impl Event {
         }
     }
 
+    /// Create a server-sent event message whose data is the JSON
+    /// representation of `data`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::web::sse::Event;
+    /// use serde_json::json;
+    ///
+    /// let event = Event::json(&json!({ "n": 1 })).unwrap().event_type("count");
+    /// assert_eq!(event.to_string(), "event: count\ndata: {\"n\":1}\n\n");
+    /// ```
+    pub fn json(data: &impl Serialize) -> serde_json::Result<Self> {
+        Ok(Self::message(serde_json::to_string(data)?))
+    }
+
     /// Set the id of the message. If the event is not a message type, there
     /// will be no effect.
     #[must_use]
@@ -71,4 +71,30 @@ data: 5
             s = now;
         }
     }
+
+    #[tokio::test]
+    async fn keep_alive_comment() {
+        let sse = SSE::new(futures_util::stream::pending())
+            .keep_alive(Duration::from_millis(10))
+            .keep_alive_comment("ping\r\n");
+        let mut body = sse.into_response().into_body().into_async_read();
+        let mut buf = [0; 10];
+        body.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b": ping  \n\n");
+    }
+
+    #[tokio::test]
+    async fn json_event() {
+        #[derive(serde::Serialize)]
+        struct Count {
+            n: i32,
+        }
+
+        let sse = SSE::new(futures_util::stream::iter(vec![
+            Event::json(&Count { n: 1 }).unwrap().id("1"),
+            Event::retry(1000),
+        ]));
+        let data = sse.into_response().into_body().into_string().await.unwrap();
+        assert_eq!(data, "id: 1\ndata: {\"n\":1}\n\nretry: 1000\n\n");
+    }
 }
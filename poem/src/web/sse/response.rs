@@ -41,6 +41,7 @@ use crate::{Body, IntoResponse, Response};
 pub struct SSE {
     stream: BoxStream<'static, Event>,
     keep_alive: Option<Duration>,
+    keep_alive_comment: Bytes,
 }
 
 impl SSE {
@@ -49,6 +50,7 @@ impl SSE {
         Self {
             stream: stream.boxed(),
             keep_alive: None,
+            keep_alive_comment: Bytes::from_static(b":\n\n"),
         }
     }
 
@@ -60,6 +62,18 @@ impl SSE {
             ..self
         }
     }
+
+    /// Set the text of the keep alive comments, default to an empty comment.
+    ///
+    /// Line breaks in the text are replaced with spaces.
+    #[must_use]
+    pub fn keep_alive_comment(self, text: impl AsRef<str>) -> Self {
+        let text = text.as_ref().replace(['\r', '\n'], " ");
+        Self {
+            keep_alive_comment: Bytes::from(format!(": {text}\n\n")),
+            ..self
+        }
+    }
 }
 
 impl IntoResponse for SSE {
@@ -69,7 +83,7 @@ impl IntoResponse for SSE {
             .map(|event| Ok::<_, std::io::Error>(Bytes::from(event.to_string())))
             .boxed();
         if let Some(duration) = self.keep_alive {
-            let comment = Bytes::from_static(b":\n\n");
+            let comment = self.keep_alive_comment;
             let mut interval =
                 tokio::time::interval_at(tokio::time::Instant::now() + duration, duration);
             stream = futures_util::stream::poll_fn(move |cx| {
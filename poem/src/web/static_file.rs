@@ -1,7 +1,7 @@
 use std::{
     collections::Bound,
     fs::Metadata,
-    io::{Seek, SeekFrom},
+    io::{ErrorKind, Seek, SeekFrom},
     path::Path,
     str::FromStr,
     time::{SystemTime, UNIX_EPOCH},
@@ -12,13 +12,14 @@ use headers::{
     ContentRange, ETag, HeaderMapExt, IfMatch, IfModifiedSince, IfNoneMatch, IfUnmodifiedSince,
     Range,
 };
-use http::{header, StatusCode};
+use http::{header, HeaderMap, StatusCode};
 use httpdate::HttpDate;
 use mime::Mime;
 use tokio::{fs::File, io::AsyncReadExt};
 
 use crate::{
-    error::StaticFileError, Body, FromRequest, IntoResponse, Request, RequestBody, Response, Result,
+    error::{ResponseError, StaticFileError},
+    Body, FromRequest, IntoResponse, Request, RequestBody, Response, Result,
 };
 
 /// A response for static file extractor.
@@ -104,17 +105,21 @@ pub struct StaticFileRequest {
 #[async_trait::async_trait]
 impl<'a> FromRequest<'a> for StaticFileRequest {
     async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
-        Ok(Self {
-            if_match: req.headers().typed_get::<IfMatch>(),
-            if_unmodified_since: req.headers().typed_get::<IfUnmodifiedSince>(),
-            if_none_match: req.headers().typed_get::<IfNoneMatch>(),
-            if_modified_since: req.headers().typed_get::<IfModifiedSince>(),
-            range: req.headers().typed_get::<Range>(),
-        })
+        Ok(Self::from_headers(req.headers()))
     }
 }
 
 impl StaticFileRequest {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            if_match: headers.typed_get::<IfMatch>(),
+            if_unmodified_since: headers.typed_get::<IfUnmodifiedSince>(),
+            if_none_match: headers.typed_get::<IfNoneMatch>(),
+            if_modified_since: headers.typed_get::<IfModifiedSince>(),
+            range: headers.typed_get::<Range>(),
+        }
+    }
+
     /// Create static file response.
     ///
     /// `prefer_utf8` - Specifies whether text responses should signal a UTF-8
@@ -184,22 +189,34 @@ impl StaticFileRequest {
         if !path.exists() || !path.is_file() {
             return Err(StaticFileError::NotFound);
         }
-        let guess = mime_guess::from_path(path);
-        let mut file = std::fs::File::open(path)?;
+        let file = std::fs::File::open(path)?;
+        let metadata = file.metadata()?;
+        self.create_response_from_file(file, metadata, guess_content_type(path, prefer_utf8))
+    }
+
+    /// Create the response of the pre-compressed variant of the file at
+    /// `path` stored at `encoded_path`, with the content type of the original
+    /// file.
+    pub(crate) fn create_encoded_response(
+        self,
+        path: &Path,
+        encoded_path: &Path,
+        prefer_utf8: bool,
+    ) -> Result<StaticFileResponse, StaticFileError> {
+        let file = std::fs::File::open(encoded_path)?;
         let metadata = file.metadata()?;
+        self.create_response_from_file(file, metadata, guess_content_type(path, prefer_utf8))
+    }
 
+    fn create_response_from_file(
+        self,
+        mut file: std::fs::File,
+        metadata: Metadata,
+        content_type: Option<String>,
+    ) -> Result<StaticFileResponse, StaticFileError> {
         // content length
         let mut content_length = metadata.len();
 
-        // content type
-        let content_type = guess.first().map(|mime| {
-            if prefer_utf8 {
-                equiv_utf8_text(mime).to_string()
-            } else {
-                mime.to_string()
-            }
-        });
-
         // etag and last modified
         let mut etag_str = String::new();
         let mut last_modified_str = String::new();
@@ -285,6 +302,116 @@ impl StaticFileRequest {
     }
 }
 
+/// A response for serving a file from the filesystem.
+///
+/// The content type is guessed from the file extension, the `ETag` and
+/// `Last-Modified` headers are set from the metadata of the file, and the file
+/// is streamed in chunks instead of being loaded into memory.
+///
+/// The conditional request headers (`If-None-Match`, `If-Modified-Since`,
+/// `If-Match` and `If-Unmodified-Since`) and the `Range` header of the request
+/// passed to [`NamedFile::open_for`] are honored.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::{header, StatusCode},
+///     test::TestClient,
+///     web::NamedFile,
+///     Request, Result,
+/// };
+///
+/// #[handler]
+/// async fn index(req: &Request) -> Result<NamedFile> {
+///     Ok(NamedFile::open_for(req, "Cargo.toml").await?)
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// let etag = resp.0.headers().get(header::ETAG).cloned().unwrap();
+///
+/// let resp = cli
+///     .get("/")
+///     .header(header::IF_NONE_MATCH, etag)
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::NOT_MODIFIED);
+///
+/// let resp = cli.get("/").header(header::RANGE, "bytes=0-8").send().await;
+/// resp.assert_status(StatusCode::PARTIAL_CONTENT);
+/// resp.assert_text("[package]").await;
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct NamedFile {
+    file: std::fs::File,
+    metadata: Metadata,
+    content_type: Option<String>,
+    request: StaticFileRequest,
+}
+
+impl NamedFile {
+    /// Opens the file at the specified path, to respond to the request.
+    ///
+    /// Returns [`StaticFileError::NotFound`] if the path does not exist or is
+    /// not a file.
+    pub async fn open_for(req: &Request, path: impl AsRef<Path>) -> Result<Self, StaticFileError> {
+        let path = path.as_ref();
+        let file = match File::open(path).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Err(StaticFileError::NotFound),
+            Err(err) => return Err(err.into()),
+        };
+        let metadata = file.metadata().await?;
+        if !metadata.is_file() {
+            return Err(StaticFileError::NotFound);
+        }
+
+        Ok(Self {
+            file: file.into_std().await,
+            metadata,
+            content_type: guess_content_type(path, true),
+            request: StaticFileRequest::from_headers(req.headers()),
+        })
+    }
+
+    /// Sets the content type, instead of guessing it from the file extension.
+    #[must_use]
+    pub fn content_type(self, content_type: impl Into<String>) -> Self {
+        Self {
+            content_type: Some(content_type.into()),
+            ..self
+        }
+    }
+}
+
+impl IntoResponse for NamedFile {
+    fn into_response(self) -> Response {
+        match self
+            .request
+            .create_response_from_file(self.file, self.metadata, self.content_type)
+        {
+            Ok(resp) => resp.into_response(),
+            Err(err) => err.as_response(),
+        }
+    }
+}
+
+pub(crate) fn guess_content_type(path: &Path, prefer_utf8: bool) -> Option<String> {
+    mime_guess::from_path(path).first().map(|mime| {
+        if prefer_utf8 {
+            equiv_utf8_text(mime).to_string()
+        } else {
+            mime.to_string()
+        }
+    })
+}
+
 fn equiv_utf8_text(ct: Mime) -> Mime {
     if ct == mime::APPLICATION_JAVASCRIPT {
         return mime::APPLICATION_JAVASCRIPT_UTF_8;
@@ -314,6 +441,15 @@ fn equiv_utf8_text(ct: Mime) -> Mime {
 }
 
 #[allow(unused_variables)]
+/// Returns the `ETag` of a file, which is the same as the one sent with the
+/// content of the file.
+pub(crate) fn metadata_etag(metadata: &Metadata) -> Option<String> {
+    metadata
+        .modified()
+        .ok()
+        .map(|modified| etag(ino(metadata), &modified, metadata.len()))
+}
+
 fn ino(md: &Metadata) -> u64 {
     #[cfg(unix)]
     {
@@ -541,6 +677,58 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn named_file() {
+        use crate::{handler, test::TestClient};
+
+        #[handler(internal)]
+        async fn index(req: &Request) -> Result<NamedFile> {
+            Ok(NamedFile::open_for(req, "Cargo.toml").await?)
+        }
+
+        let cli = TestClient::new(index);
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("text/x-toml");
+        let last_modified = resp
+            .0
+            .headers()
+            .get(header::LAST_MODIFIED)
+            .cloned()
+            .unwrap();
+        resp.assert_text(std::fs::read_to_string("Cargo.toml").unwrap())
+            .await;
+
+        let resp = cli
+            .get("/")
+            .header(header::IF_MODIFIED_SINCE, last_modified)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NOT_MODIFIED);
+
+        let resp = cli.get("/").header(header::RANGE, "bytes=1-7").send().await;
+        resp.assert_status(StatusCode::PARTIAL_CONTENT);
+        resp.assert_text("package").await;
+
+        let req = Request::default();
+        assert!(matches!(
+            NamedFile::open_for(&req, "not-exists").await.unwrap_err(),
+            StaticFileError::NotFound
+        ));
+        assert!(matches!(
+            NamedFile::open_for(&req, "src").await.unwrap_err(),
+            StaticFileError::NotFound
+        ));
+
+        let resp = NamedFile::open_for(&req, "Cargo.toml")
+            .await
+            .unwrap()
+            .content_type("text/plain")
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.content_type(), Some("text/plain"));
+    }
+
     #[tokio::test]
     async fn test_range_413() {
         let md = std::fs::metadata("Cargo.toml").unwrap();
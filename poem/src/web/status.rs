@@ -0,0 +1,223 @@
+use std::fmt::Display;
+
+use crate::{
+    http::{header, StatusCode},
+    web::checked_header_value,
+    IntoResponse, Response,
+};
+
+fn with_location(
+    status: StatusCode,
+    inner: impl IntoResponse,
+    location: Option<String>,
+) -> Response {
+    let location = match location
+        .map(|location| checked_header_value(header::LOCATION.as_str(), &location))
+        .transpose()
+    {
+        Ok(location) => location,
+        Err(err) => return crate::Error::from(err).into_response(),
+    };
+
+    let mut resp = inner.into_response();
+    resp.set_status(status);
+    if let Some(location) = location {
+        resp.headers_mut().insert(header::LOCATION, location);
+    }
+    resp
+}
+
+/// A `201 Created` response, with an optional `Location` header pointing to
+/// the created resource.
+///
+/// If the location contains a line break or any other character that is not
+/// allowed in a header value, an
+/// [`InvalidHeaderValueError`](crate::error::InvalidHeaderValueError)
+/// response is returned instead.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::{header, StatusCode},
+///     test::TestClient,
+///     web::{Created, Json},
+/// };
+/// use serde_json::json;
+///
+/// #[handler]
+/// fn create_user() -> Created<Json<serde_json::Value>> {
+///     Created::new(Json(json!({ "id": 1 }))).location("/users/1")
+/// }
+///
+/// let cli = TestClient::new(create_user);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.post("/").send().await;
+/// resp.assert_status(StatusCode::CREATED);
+/// resp.assert_header(header::LOCATION, "/users/1");
+/// resp.assert_json(json!({ "id": 1 })).await;
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Created<T> {
+    inner: T,
+    location: Option<String>,
+}
+
+impl<T> Created<T> {
+    /// Create a `201 Created` response with the body.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            location: None,
+        }
+    }
+
+    /// Sets the `Location` header.
+    #[must_use]
+    pub fn location(self, location: impl Display) -> Self {
+        Self {
+            location: Some(location.to_string()),
+            ..self
+        }
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Created<T> {
+    fn into_response(self) -> Response {
+        with_location(StatusCode::CREATED, self.inner, self.location)
+    }
+}
+
+/// A `202 Accepted` response for requests that are accepted for processing
+/// but not completed yet, with an optional `Location` header pointing to a
+/// status monitor.
+///
+/// If the location contains a line break or any other character that is not
+/// allowed in a header value, an
+/// [`InvalidHeaderValueError`](crate::error::InvalidHeaderValueError)
+/// response is returned instead.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     http::{header, StatusCode},
+///     test::TestClient,
+///     web::Accepted,
+/// };
+///
+/// #[handler]
+/// fn start_job() -> Accepted<()> {
+///     Accepted::new(()).location("/jobs/1")
+/// }
+///
+/// let cli = TestClient::new(start_job);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.post("/").send().await;
+/// resp.assert_status(StatusCode::ACCEPTED);
+/// resp.assert_header(header::LOCATION, "/jobs/1");
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Accepted<T> {
+    inner: T,
+    location: Option<String>,
+}
+
+impl<T> Accepted<T> {
+    /// Create a `202 Accepted` response with the body.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            location: None,
+        }
+    }
+
+    /// Sets the `Location` header.
+    #[must_use]
+    pub fn location(self, location: impl Display) -> Self {
+        Self {
+            location: Some(location.to_string()),
+            ..self
+        }
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Accepted<T> {
+    fn into_response(self) -> Response {
+        with_location(StatusCode::ACCEPTED, self.inner, self.location)
+    }
+}
+
+/// A `204 No Content` response.
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, http::StatusCode, test::TestClient, web::NoContent};
+///
+/// #[handler]
+/// fn delete_user() -> NoContent {
+///     NoContent
+/// }
+///
+/// let cli = TestClient::new(delete_user);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.delete("/").send().await;
+/// resp.assert_status(StatusCode::NO_CONTENT);
+/// resp.assert_text("").await;
+/// # });
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct NoContent;
+
+impl IntoResponse for NoContent {
+    fn into_response(self) -> Response {
+        StatusCode::NO_CONTENT.into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn created() {
+        let resp = Created::new("hello").location("/a/1").into_response();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.headers().get(header::LOCATION).unwrap(), "/a/1");
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "hello");
+
+        let resp = Created::new(()).into_response();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert!(resp.headers().get(header::LOCATION).is_none());
+    }
+
+    #[test]
+    fn accepted() {
+        let resp = Accepted::new(()).location("/jobs/1").into_response();
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        assert_eq!(resp.headers().get(header::LOCATION).unwrap(), "/jobs/1");
+    }
+
+    #[test]
+    fn invalid_location() {
+        let resp = Created::new(())
+            .location("/a\r\nSet-Cookie: a=b")
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(resp.headers().get(header::LOCATION).is_none());
+        assert!(resp.headers().get(header::SET_COOKIE).is_none());
+    }
+
+    #[test]
+    fn no_content() {
+        assert_eq!(NoContent.into_response().status(), StatusCode::NO_CONTENT);
+    }
+}
@@ -0,0 +1,107 @@
+use std::error::Error as StdError;
+
+use bytes::Bytes;
+use futures_util::Stream;
+
+use crate::{Body, IntoResponse, Response};
+
+/// A response whose body is produced by a stream, such as rows read from a
+/// database cursor or a generated archive, which is sent to the client with
+/// the chunked transfer encoding without being buffered.
+///
+/// The content type defaults to `application/octet-stream`.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::{stream, Stream};
+/// use poem::{handler, test::TestClient, web::Streaming};
+///
+/// #[handler]
+/// fn index() -> Streaming<impl Stream<Item = Result<String, std::io::Error>> + Send> {
+///     Streaming::new(stream::iter((1..=3).map(|n| Ok(format!("{{\"n\":{n}}}\n")))))
+///         .content_type("application/x-ndjson")
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("application/x-ndjson");
+/// resp.assert_text("{\"n\":1}\n{\"n\":2}\n{\"n\":3}\n").await;
+/// # });
+/// ```
+pub struct Streaming<S> {
+    stream: S,
+    content_type: String,
+}
+
+impl<S, O, E> Streaming<S>
+where
+    S: Stream<Item = Result<O, E>> + Send + 'static,
+    O: Into<Bytes> + 'static,
+    E: Into<Box<dyn StdError + Send + Sync>> + 'static,
+{
+    /// Create a streaming response.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            content_type: "application/octet-stream".to_string(),
+        }
+    }
+
+    /// Sets the content type, default to `application/octet-stream`.
+    #[must_use]
+    pub fn content_type(self, content_type: impl Into<String>) -> Self {
+        Self {
+            content_type: content_type.into(),
+            ..self
+        }
+    }
+}
+
+impl<S, O, E> IntoResponse for Streaming<S>
+where
+    S: Stream<Item = Result<O, E>> + Send + 'static,
+    O: Into<Bytes> + 'static,
+    E: Into<Box<dyn StdError + Send + Sync>> + 'static,
+{
+    fn into_response(self) -> Response {
+        Response::builder()
+            .content_type(self.content_type)
+            .body(Body::from_stream(self.stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+    use crate::{handler, test::TestClient};
+
+    #[tokio::test]
+    async fn streaming() {
+        #[handler(internal)]
+        fn index() -> Streaming<impl Stream<Item = Result<&'static str, std::io::Error>> + Send>
+        {
+            Streaming::new(stream::iter(vec![Ok("a"), Ok("b"), Ok("c")]))
+        }
+
+        let resp = TestClient::new(index).get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("application/octet-stream");
+        resp.assert_text("abc").await;
+    }
+
+    #[tokio::test]
+    async fn stream_error() {
+        let body = Body::from_stream(stream::iter(vec![
+            Ok::<_, Box<dyn StdError + Send + Sync>>("a"),
+            Err("database error".into()),
+        ]));
+        let err = body.into_bytes().await.unwrap_err();
+        assert!(err.to_string().contains("database error"));
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use crate::{
+    error::SubrequestError,
+    http::{header::HeaderName, Extensions, HeaderMap, Method, Uri},
+    web::{LocalAddr, RemoteAddr},
+    Endpoint, FromRequest, Request, RequestBody, Response, Result,
+};
+
+/// The router and the configuration of the subrequests, inserted into the
+/// request extensions by the [`Subrequests`](crate::middleware::Subrequests)
+/// middleware.
+#[derive(Clone)]
+pub(crate) struct SubrequestDispatcher {
+    pub(crate) ep: Arc<dyn Endpoint<Output = Response>>,
+    pub(crate) inherit_headers: Arc<Vec<HeaderName>>,
+    pub(crate) max_depth: usize,
+}
+
+/// The nesting depth of a subrequest.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SubrequestDepth(pub(crate) usize);
+
+/// An extractor that sends internal subrequests to the router of the
+/// [`Subrequests`](crate::middleware::Subrequests) middleware, without a
+/// network hop.
+///
+/// The subrequests inherit the extensions, the remote and local addresses of
+/// the current request, and the headers configured with
+/// [`Subrequests::inherit_header`](crate::middleware::Subrequests::inherit_header),
+/// such as `Authorization` and `Cookie`. The headers and the extensions of the
+/// subrequest take precedence over the inherited ones.
+///
+/// The errors of the subrequests are returned as responses, like the errors
+/// of the requests from the network.
+///
+/// # Errors
+///
+/// - [`SubrequestError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     get, handler, middleware::Subrequests, test::TestClient, web::Subrequest, EndpointExt,
+///     Result, Route,
+/// };
+///
+/// #[handler]
+/// fn user() -> &'static str {
+///     "alice"
+/// }
+///
+/// #[handler]
+/// fn orders() -> &'static str {
+///     "3 orders"
+/// }
+///
+/// #[handler]
+/// async fn dashboard(sub: Subrequest) -> Result<String> {
+///     let (user_resp, orders_resp) = tokio::join!(sub.get("/user"), sub.get("/orders"));
+///     Ok(format!(
+///         "{}: {}",
+///         user_resp?.into_body().into_string().await?,
+///         orders_resp?.into_body().into_string().await?
+///     ))
+/// }
+///
+/// let app = Route::new()
+///     .at("/user", get(user))
+///     .at("/orders", get(orders))
+///     .at("/dashboard", get(dashboard))
+///     .with(Subrequests::new());
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/dashboard").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("alice: 3 orders").await;
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct Subrequest {
+    dispatcher: SubrequestDispatcher,
+    depth: usize,
+    headers: HeaderMap,
+    extensions: Extensions,
+    remote_addr: RemoteAddr,
+    local_addr: LocalAddr,
+}
+
+impl Subrequest {
+    /// Sends the subrequest and returns the response.
+    pub async fn call(&self, mut req: Request) -> Result<Response> {
+        let depth = self.depth + 1;
+        if depth > self.dispatcher.max_depth {
+            return Err(SubrequestError::TooDeep(self.dispatcher.max_depth).into());
+        }
+
+        for (name, value) in &self.headers {
+            if !req.headers().contains_key(name) {
+                req.headers_mut().insert(name.clone(), value.clone());
+            }
+        }
+        let mut extensions = self.extensions.clone();
+        extensions.extend(std::mem::take(req.extensions_mut()));
+        extensions.insert(SubrequestDepth(depth));
+        *req.extensions_mut() = extensions;
+
+        let uri = req.uri().clone();
+        let state = req.state_mut();
+        state.remote_addr = self.remote_addr.clone();
+        state.local_addr = self.local_addr.clone();
+        state.original_uri = uri;
+
+        Ok(self.dispatcher.ep.get_response(req).await)
+    }
+
+    /// Sends a `GET` subrequest to the `uri`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SubrequestError::InvalidUri`] if `uri` is invalid.
+    pub async fn get(&self, uri: impl AsRef<str>) -> Result<Response> {
+        self.request(Method::GET, uri).await
+    }
+
+    /// Sends a subrequest with the `method` and an empty body to the `uri`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SubrequestError::InvalidUri`] if `uri` is invalid.
+    pub async fn request(&self, method: Method, uri: impl AsRef<str>) -> Result<Response> {
+        let uri = uri.as_ref();
+        let uri = Uri::try_from(uri).map_err(|_| SubrequestError::InvalidUri(uri.to_string()))?;
+        self.call(Request::builder().method(method).uri(uri).finish())
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for Subrequest {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let dispatcher = req
+            .extensions()
+            .get::<SubrequestDispatcher>()
+            .cloned()
+            .ok_or(SubrequestError::NotEnabled)?;
+
+        let mut headers = HeaderMap::new();
+        for name in dispatcher.inherit_headers.iter() {
+            for value in req.headers().get_all(name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+
+        Ok(Self {
+            depth: req
+                .extensions()
+                .get::<SubrequestDepth>()
+                .map(|depth| depth.0)
+                .unwrap_or_default(),
+            dispatcher,
+            headers,
+            extensions: req.extensions().clone(),
+            remote_addr: req.remote_addr().clone(),
+            local_addr: req.local_addr().clone(),
+        })
+    }
+}
@@ -17,7 +17,7 @@ use crate::{error::ReadBodyError, FromRequest, Request, RequestBody, Result};
 ///
 /// - [`ReadBodyError`]
 #[cfg_attr(docsrs, doc(cfg(feature = "tempfile")))]
-pub struct TempFile(File);
+pub struct TempFile(pub(crate) File);
 
 impl TempFile {
     async fn internal_from_request(body: &mut RequestBody) -> Result<Self, ReadBodyError> {
@@ -0,0 +1,37 @@
+use crate::{error::RenderTemplateError, web::Template, Result};
+
+/// A template rendered with [`askama`](https://crates.io/crates/askama).
+///
+/// # Example
+///
+/// ```ignore
+/// use askama::Template;
+/// use poem::{
+///     handler,
+///     web::{AskamaTemplate, TemplateResponse},
+/// };
+///
+/// #[derive(Template)]
+/// #[template(source = "<h1>Hello {{ name }}!</h1>", ext = "html")]
+/// struct Hello {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn index() -> TemplateResponse<AskamaTemplate<Hello>> {
+///     TemplateResponse(AskamaTemplate(Hello {
+///         name: "poem".to_string(),
+///     }))
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "askama")))]
+#[derive(Debug, Clone)]
+pub struct AskamaTemplate<T>(pub T);
+
+impl<T: libaskama::Template> Template for AskamaTemplate<T> {
+    fn render(&self) -> Result<String> {
+        self.0
+            .render()
+            .map_err(|err| RenderTemplateError::new(err).into())
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use libhandlebars::Handlebars;
+use serde::Serialize;
+
+use crate::{error::RenderTemplateError, web::Template, Result};
+
+/// A template rendered with
+/// [`handlebars`](https://crates.io/crates/handlebars).
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use libhandlebars::Handlebars;
+/// use poem::{
+///     handler,
+///     test::TestClient,
+///     web::{Data, HandlebarsTemplate, TemplateResponse},
+///     EndpointExt,
+/// };
+/// use serde_json::{json, Value};
+///
+/// #[handler]
+/// fn index(
+///     registry: Data<&Arc<Handlebars<'static>>>,
+/// ) -> TemplateResponse<HandlebarsTemplate<Value>> {
+///     TemplateResponse(HandlebarsTemplate::new(
+///         registry.0.clone(),
+///         "index",
+///         json!({ "name": "poem" }),
+///     ))
+/// }
+///
+/// let mut registry = Handlebars::new();
+/// registry
+///     .register_template_string("index", "<h1>Hello {{name}}!</h1>")
+///     .unwrap();
+/// let cli = TestClient::new(index.data(Arc::new(registry)));
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("<h1>Hello poem!</h1>").await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "handlebars")))]
+pub struct HandlebarsTemplate<T> {
+    registry: Arc<Handlebars<'static>>,
+    name: String,
+    data: T,
+}
+
+impl<T> HandlebarsTemplate<T> {
+    /// Create a template that renders the template `name` of `registry` with
+    /// the data.
+    pub fn new(registry: Arc<Handlebars<'static>>, name: impl Into<String>, data: T) -> Self {
+        Self {
+            registry,
+            name: name.into(),
+            data,
+        }
+    }
+}
+
+impl<T: Serialize> Template for HandlebarsTemplate<T> {
+    fn render(&self) -> Result<String> {
+        self.registry
+            .render(&self.name, &self.data)
+            .map_err(|err| RenderTemplateError::new(err).into())
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(feature = "askama")]
+mod askama;
+#[cfg(feature = "handlebars")]
+mod handlebars;
+mod streaming;
+mod suspense;
+#[cfg(feature = "tera")]
+mod tera;
+
+#[cfg(feature = "askama")]
+pub use self::askama::AskamaTemplate;
+#[cfg(feature = "handlebars")]
+pub use self::handlebars::HandlebarsTemplate;
+pub use self::streaming::StreamingTemplate;
+pub use self::suspense::SuspenseTemplate;
+#[cfg(feature = "tera")]
+pub use self::tera::TeraTemplate;
+use crate::{web::Html, IntoResponse, Response, Result};
+
+/// A template that can be rendered to an HTML response.
+///
+/// It is implemented by the integrations of template engines:
+///
+/// | Type                 | Feature      |
+/// |----------------------|--------------|
+/// | `TeraTemplate`       | `tera`       |
+/// | `AskamaTemplate`     | `askama`     |
+/// | `HandlebarsTemplate` | `handlebars` |
+///
+/// Errors of the template engines are converted to
+/// [`RenderTemplateError`](crate::error::RenderTemplateError).
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     test::TestClient,
+///     web::{Template, TemplateResponse},
+///     Result,
+/// };
+///
+/// struct Hello {
+///     name: String,
+/// }
+///
+/// impl Template for Hello {
+///     fn render(&self) -> Result<String> {
+///         Ok(format!("<h1>Hello {}!</h1>", self.name))
+///     }
+/// }
+///
+/// #[handler]
+/// fn index() -> TemplateResponse<Hello> {
+///     TemplateResponse(Hello {
+///         name: "poem".to_string(),
+///     })
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("text/html; charset=utf-8");
+/// resp.assert_text("<h1>Hello poem!</h1>").await;
+/// # });
+/// ```
+pub trait Template {
+    /// Renders the template to a string.
+    fn render(&self) -> Result<String>;
+
+    /// Renders the template to an [`Html`] response.
+    fn render_html(&self) -> Result<Html<String>> {
+        self.render().map(Html)
+    }
+}
+
+/// A response that renders the template as HTML.
+///
+/// If the template fails to render, the error is returned as the response.
+///
+/// Use [`StreamingTemplate`] to send the head of the page before the data of
+/// the rest is ready.
+#[derive(Debug, Clone)]
+pub struct TemplateResponse<T>(pub T);
+
+impl<T: Template + Send> IntoResponse for TemplateResponse<T> {
+    fn into_response(self) -> Response {
+        match self.0.render_html() {
+            Ok(html) => html.into_response(),
+            Err(err) => err.into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::RenderTemplateError, http::StatusCode};
+
+    struct Failing;
+
+    impl Template for Failing {
+        fn render(&self) -> Result<String> {
+            Err(RenderTemplateError::new("missing variable `name`").into())
+        }
+    }
+
+    #[tokio::test]
+    async fn render_error() {
+        let resp = TemplateResponse(Failing).into_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            "failed to render template: missing variable `name`"
+        );
+    }
+}
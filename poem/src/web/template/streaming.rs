@@ -0,0 +1,174 @@
+use std::{future::Future, io::Error as IoError};
+
+use futures_util::{
+    future::{ready, BoxFuture},
+    stream, FutureExt, StreamExt,
+};
+
+use crate::{web::Template, Body, Error, IntoResponse, Response, Result};
+
+/// A response that renders the page in parts and streams them to the client
+/// as the data of each part resolves, so the browser can start to load the
+/// stylesheets and the scripts in the head before the slow data of the page
+/// is ready.
+///
+/// The head is rendered and sent first. The data of the parts is loaded
+/// concurrently, and the parts are sent in the order they are added.
+///
+/// If the head fails to render, the error is returned as the response. Once
+/// the head is sent the status cannot be changed, so the response is aborted
+/// if a part fails.
+///
+/// Use [`SuspenseTemplate`](crate::web::SuspenseTemplate) to send the parts
+/// in the order their data resolves.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     test::TestClient,
+///     web::{StreamingTemplate, Template},
+///     Result,
+/// };
+///
+/// struct Text(String);
+///
+/// impl Template for Text {
+///     fn render(&self) -> Result<String> {
+///         Ok(self.0.clone())
+///     }
+/// }
+///
+/// async fn load_orders() -> Result<Text> {
+///     Ok(Text("<ul><li>order 1</li></ul>".to_string()))
+/// }
+///
+/// #[handler]
+/// fn index() -> StreamingTemplate {
+///     StreamingTemplate::new(Text("<html><head></head><body>".to_string()))
+///         .part(load_orders())
+///         .template(Text("</body></html>".to_string()))
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("text/html; charset=utf-8");
+/// resp.assert_text("<html><head></head><body><ul><li>order 1</li></ul></body></html>")
+///     .await;
+/// # });
+/// ```
+pub struct StreamingTemplate {
+    head: Result<String>,
+    parts: Vec<BoxFuture<'static, Result<String>>>,
+}
+
+impl StreamingTemplate {
+    /// Create a streaming response that starts with the `head`.
+    pub fn new(head: impl Template) -> Self {
+        Self {
+            head: head.render(),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Appends a part rendered from the template that `data` resolves to.
+    #[must_use]
+    pub fn part<F, T>(mut self, data: F) -> Self
+    where
+        F: Future<Output = Result<T>> + Send + 'static,
+        T: Template,
+    {
+        self.parts.push(async move { data.await?.render() }.boxed());
+        self
+    }
+
+    /// Appends a part rendered from the `template` whose data is already
+    /// available, such as the footer.
+    #[must_use]
+    pub fn template(mut self, template: impl Template) -> Self {
+        self.parts.push(ready(template.render()).boxed());
+        self
+    }
+}
+
+impl IntoResponse for StreamingTemplate {
+    fn into_response(self) -> Response {
+        let head = match self.head {
+            Ok(head) => head,
+            Err(err) => return err.into_response(),
+        };
+
+        let concurrency = self.parts.len().max(1);
+        let body = stream::once(async move { Ok::<_, Error>(head) })
+            .chain(stream::iter(self.parts).buffered(concurrency))
+            .map(|res| res.map_err(|err| IoError::other(err.to_string())));
+        Response::builder()
+            .content_type("text/html; charset=utf-8")
+            .body(Body::from_bytes_stream(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::oneshot;
+
+    use super::*;
+    use crate::{error::RenderTemplateError, http::StatusCode};
+
+    struct Text(&'static str);
+
+    impl Template for Text {
+        fn render(&self) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    struct Failing;
+
+    impl Template for Failing {
+        fn render(&self) -> Result<String> {
+            Err(RenderTemplateError::new("missing variable `name`").into())
+        }
+    }
+
+    #[tokio::test]
+    async fn streaming_template() {
+        let (tx, rx) = oneshot::channel();
+        let resp = StreamingTemplate::new(Text("<head>"))
+            .part(async move {
+                rx.await.ok();
+                Ok(Text("<main>"))
+            })
+            .part(async { Ok(Text("<aside>")) })
+            .template(Text("<footer>"))
+            .into_response();
+        assert_eq!(resp.content_type(), Some("text/html; charset=utf-8"));
+
+        // the head is sent before the data of the parts resolves
+        let mut body = Box::pin(resp.into_body().into_bytes_stream());
+        assert_eq!(body.next().await.unwrap().unwrap(), "<head>");
+
+        tx.send(()).unwrap();
+        let mut rest = String::new();
+        while let Some(chunk) = body.next().await {
+            rest.push_str(std::str::from_utf8(&chunk.unwrap()).unwrap());
+        }
+        assert_eq!(rest, "<main><aside><footer>");
+    }
+
+    #[tokio::test]
+    async fn render_error() {
+        let resp = StreamingTemplate::new(Failing).into_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let resp = StreamingTemplate::new(Text("<head>"))
+            .template(Failing)
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.into_body().into_string().await.is_err());
+    }
+}
@@ -0,0 +1,234 @@
+use std::{future::Future, io::Error as IoError};
+
+use futures_util::{future::BoxFuture, stream, FutureExt, StreamExt};
+
+use crate::{web::Template, Body, Error, IntoResponse, Response, Result};
+
+const RUNTIME: &str = "function $poemSuspense(id){\
+var t=document.querySelector('template[data-suspense=\"'+id+'\"]'),\
+e=document.getElementById(id);\
+if(t&&e){e.replaceWith(t.content);}\
+if(t){t.remove();}}";
+
+/// A response that sends the shell of the page with the fallbacks of the
+/// suspense boundaries immediately, and streams the content of each boundary
+/// as its data resolves, in any order (out-of-order streaming).
+///
+/// A boundary is an element of the shell with an `id`, such as
+/// `<div id="orders">Loading...</div>`. When the data of the boundary
+/// resolves, the rendered content is sent in a `<template>` with a script that
+/// replaces the element, so the page is usable before the slowest data is
+/// ready and works with the frontends that hydrate the server-rendered HTML.
+///
+/// If the shell fails to render, the error is returned as the response. Once
+/// the shell is sent the status cannot be changed, so the response is aborted
+/// if a boundary fails.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     handler,
+///     test::TestClient,
+///     web::{SuspenseTemplate, Template},
+///     Result,
+/// };
+///
+/// struct Text(String);
+///
+/// impl Template for Text {
+///     fn render(&self) -> Result<String> {
+///         Ok(self.0.clone())
+///     }
+/// }
+///
+/// async fn load_orders() -> Result<Text> {
+///     Ok(Text("<ul><li>order 1</li></ul>".to_string()))
+/// }
+///
+/// #[handler]
+/// fn index() -> SuspenseTemplate {
+///     SuspenseTemplate::new(Text(
+///         r#"<html><body><div id="orders">Loading...</div>"#.to_string(),
+///     ))
+///     .boundary("orders", load_orders())
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// let html = resp.0.into_body().into_string().await.unwrap();
+/// assert!(html.contains(r#"<template data-suspense="orders"><ul><li>order 1</li></ul></template>"#));
+/// # });
+/// ```
+pub struct SuspenseTemplate {
+    shell: Result<String>,
+    boundaries: Vec<BoxFuture<'static, Result<(String, String)>>>,
+    nonce: Option<String>,
+}
+
+impl SuspenseTemplate {
+    /// Create a response that starts with the `shell`.
+    pub fn new(shell: impl Template) -> Self {
+        Self {
+            shell: shell.render(),
+            boundaries: Vec::new(),
+            nonce: None,
+        }
+    }
+
+    /// Adds a boundary that replaces the element with the `id` by the template
+    /// that `data` resolves to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is empty or contains characters other than ASCII
+    /// letters, digits, `-` and `_`.
+    #[must_use]
+    pub fn boundary<F, T>(mut self, id: impl Into<String>, data: F) -> Self
+    where
+        F: Future<Output = Result<T>> + Send + 'static,
+        T: Template,
+    {
+        let id = id.into();
+        assert!(
+            !id.is_empty()
+                && id
+                    .bytes()
+                    .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'),
+            "invalid suspense boundary id `{id}`"
+        );
+        self.boundaries.push(
+            async move {
+                let content = data.await?.render()?;
+                Ok((id, content))
+            }
+            .boxed(),
+        );
+        self
+    }
+
+    /// Sets the `nonce` attribute of the scripts, which is required if the
+    /// `Content-Security-Policy` of the page restricts the inline scripts.
+    #[must_use]
+    pub fn nonce(self, nonce: impl Into<String>) -> Self {
+        Self {
+            nonce: Some(nonce.into()),
+            ..self
+        }
+    }
+}
+
+impl IntoResponse for SuspenseTemplate {
+    fn into_response(self) -> Response {
+        let shell = match self.shell {
+            Ok(shell) => shell,
+            Err(err) => return err.into_response(),
+        };
+        if self.boundaries.is_empty() {
+            return Response::builder()
+                .content_type("text/html; charset=utf-8")
+                .body(shell);
+        }
+
+        let script_tag = match &self.nonce {
+            Some(nonce) => format!(
+                r#"<script nonce="{}">"#,
+                nonce.replace('&', "&amp;").replace('"', "&quot;")
+            ),
+            None => "<script>".to_string(),
+        };
+        let head = format!("{shell}{script_tag}{RUNTIME}</script>");
+
+        let concurrency = self.boundaries.len();
+        let boundaries = stream::iter(self.boundaries)
+            .buffer_unordered(concurrency)
+            .map(move |res| {
+                res.map(|(id, content)| {
+                    format!(
+                        r#"<template data-suspense="{id}">{content}</template>{script_tag}$poemSuspense("{id}")</script>"#
+                    )
+                })
+            });
+        let body = stream::once(async move { Ok::<_, Error>(head) })
+            .chain(boundaries)
+            .map(|res| res.map_err(|err| IoError::other(err.to_string())));
+        Response::builder()
+            .content_type("text/html; charset=utf-8")
+            .body(Body::from_bytes_stream(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::oneshot;
+
+    use super::*;
+    use crate::{error::RenderTemplateError, http::StatusCode};
+
+    struct Text(&'static str);
+
+    impl Template for Text {
+        fn render(&self) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn out_of_order() {
+        let (tx, rx) = oneshot::channel();
+        let resp = SuspenseTemplate::new(Text(r#"<div id="a">...</div><div id="b">...</div>"#))
+            .boundary("a", async move {
+                rx.await.ok();
+                Ok(Text("A"))
+            })
+            .boundary("b", async { Ok(Text("B")) })
+            .nonce("abc")
+            .into_response();
+        assert_eq!(resp.content_type(), Some("text/html; charset=utf-8"));
+
+        let mut body = Box::pin(resp.into_body().into_bytes_stream());
+        let shell = body.next().await.unwrap().unwrap();
+        assert!(shell.starts_with(br#"<div id="a">...</div><div id="b">...</div><script nonce="abc">function $poemSuspense"#));
+
+        // `b` is sent before `a`
+        assert_eq!(
+            body.next().await.unwrap().unwrap(),
+            r#"<template data-suspense="b">B</template><script nonce="abc">$poemSuspense("b")</script>"#
+        );
+        tx.send(()).unwrap();
+        assert_eq!(
+            body.next().await.unwrap().unwrap(),
+            r#"<template data-suspense="a">A</template><script nonce="abc">$poemSuspense("a")</script>"#
+        );
+        assert!(body.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        struct Failing;
+
+        impl Template for Failing {
+            fn render(&self) -> Result<String> {
+                Err(RenderTemplateError::new("missing variable `name`").into())
+            }
+        }
+
+        let resp = SuspenseTemplate::new(Failing).into_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let resp = SuspenseTemplate::new(Text("<div id=\"a\"></div>"))
+            .boundary("a", async { Ok(Failing) })
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.into_body().into_string().await.is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_id() {
+        let _ = SuspenseTemplate::new(Text("")).boundary("a\")", async { Ok(Text("")) });
+    }
+}
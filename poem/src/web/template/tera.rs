@@ -0,0 +1,151 @@
+#[cfg(feature = "i18n")]
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[cfg(feature = "i18n")]
+use libtera::Value;
+use libtera::{Context, Tera};
+
+use crate::{error::RenderTemplateError, web::Template, Result};
+
+/// A template rendered with [`tera`](https://crates.io/crates/tera).
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use poem::{
+///     handler,
+///     test::TestClient,
+///     web::{Data, TeraTemplate, TemplateResponse},
+///     EndpointExt,
+/// };
+/// use libtera::{Context, Tera};
+///
+/// #[handler]
+/// fn index(tera: Data<&Arc<Tera>>) -> TemplateResponse<TeraTemplate> {
+///     let mut context = Context::new();
+///     context.insert("name", "poem");
+///     TemplateResponse(TeraTemplate::new(tera.0.clone(), "index.html", context))
+/// }
+///
+/// let mut tera = Tera::default();
+/// tera.add_raw_template("index.html", "<h1>Hello {{ name }}!</h1>")
+///     .unwrap();
+/// let cli = TestClient::new(index.data(Arc::new(tera)));
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("<h1>Hello poem!</h1>").await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "tera")))]
+pub struct TeraTemplate {
+    tera: Arc<Tera>,
+    name: String,
+    context: Context,
+}
+
+impl TeraTemplate {
+    /// Create a template that renders the template `name` of `tera` with the
+    /// context.
+    pub fn new(tera: Arc<Tera>, name: impl Into<String>, context: Context) -> Self {
+        Self {
+            tera,
+            name: name.into(),
+            context,
+        }
+    }
+}
+
+impl Template for TeraTemplate {
+    fn render(&self) -> Result<String> {
+        self.tera
+            .render(&self.name, &self.context)
+            .map_err(|err| RenderTemplateError::new(err).into())
+    }
+}
+
+#[cfg(feature = "i18n")]
+impl TeraTemplate {
+    /// Registers the filters that format the values for the language in the
+    /// `locale` argument, such as `en-US`:
+    ///
+    /// - `format_number(locale, digits=0)` formats a number, see
+    ///   [`I18NFormatter::format_number`](crate::i18n::I18NFormatter::format_number).
+    /// - `format_date(locale)` formats a date in the `YYYY-MM-DD` format, such
+    ///   as a serialized `chrono::NaiveDate`, see
+    ///   [`I18NFormatter::format_date`](crate::i18n::I18NFormatter::format_date).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use libtera::{Context, Tera};
+    /// use poem::web::{TeraTemplate, Template};
+    ///
+    /// let mut tera = Tera::default();
+    /// TeraTemplate::register_i18n_filters(&mut tera);
+    /// tera.add_raw_template(
+    ///     "price.html",
+    ///     r#"{{ price | format_number(locale=locale, digits=2) }} {{ date | format_date(locale=locale) }}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut context = Context::new();
+    /// // the language of the `Locale` extractor, `locale.language().to_string()`
+    /// context.insert("locale", "de-DE");
+    /// context.insert("price", &1234.5);
+    /// context.insert("date", "2024-02-29");
+    /// let template = TeraTemplate::new(Arc::new(tera), "price.html", context);
+    /// assert_eq!(template.render().unwrap(), "1.234,50 29.02.2024");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "i18n")))]
+    pub fn register_i18n_filters(tera: &mut Tera) {
+        tera.register_filter(
+            "format_number",
+            |value: &Value, args: &HashMap<String, Value>| {
+                let formatter = i18n_formatter(args)?;
+                let value = value
+                    .as_f64()
+                    .ok_or_else(|| libtera::Error::msg("`format_number` expects a number"))?;
+                let digits = match args.get("digits") {
+                    Some(digits) => digits
+                        .as_u64()
+                        .ok_or_else(|| libtera::Error::msg("`digits` must be an integer"))?,
+                    None => 0,
+                };
+                Ok(Value::String(
+                    formatter.format_number(value, digits as usize),
+                ))
+            },
+        );
+        tera.register_filter(
+            "format_date",
+            |value: &Value, args: &HashMap<String, Value>| {
+                let formatter = i18n_formatter(args)?;
+                let date = value
+                    .as_str()
+                    .and_then(|s| s.get(..10))
+                    .and_then(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+                    .ok_or_else(|| libtera::Error::msg("`format_date` expects a date"))?;
+                Ok(Value::String(formatter.format_date(&date)))
+            },
+        );
+    }
+}
+
+#[cfg(feature = "i18n")]
+fn i18n_formatter(args: &HashMap<String, Value>) -> libtera::Result<crate::i18n::I18NFormatter> {
+    let locale = args
+        .get("locale")
+        .and_then(Value::as_str)
+        .ok_or_else(|| libtera::Error::msg("the `locale` argument is required"))?;
+    let language = locale
+        .parse::<crate::i18n::unic_langid::LanguageIdentifier>()
+        .map_err(|_| libtera::Error::msg(format!("invalid locale `{locale}`")))?;
+    Ok(crate::i18n::I18NFormatter::new(&language))
+}
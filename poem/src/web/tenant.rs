@@ -0,0 +1,469 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::RwLock;
+
+use crate::{error::TenantError, FromRequest, Request, RequestBody, Result};
+
+/// A provider of the tenant configurations used by the
+/// [`TenantResolver`](crate::middleware::TenantResolver) middleware.
+///
+/// It is implemented for `HashMap<String, T>`, implement this trait to load
+/// the tenants from a database or a configuration service.
+#[async_trait::async_trait]
+pub trait TenantProvider: Send + Sync + 'static {
+    /// The configuration of a tenant.
+    type Config: Clone + Send + Sync + 'static;
+
+    /// Returns the configuration of the tenant, or `None` if the tenant does
+    /// not exist.
+    async fn get_tenant(&self, id: &str) -> Result<Option<Self::Config>>;
+}
+
+#[async_trait::async_trait]
+impl<T: Clone + Send + Sync + 'static> TenantProvider for HashMap<String, T> {
+    type Config = T;
+
+    async fn get_tenant(&self, id: &str) -> Result<Option<Self::Config>> {
+        Ok(self.get(id).cloned())
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: TenantProvider> TenantProvider for Arc<T> {
+    type Config = T::Config;
+
+    async fn get_tenant(&self, id: &str) -> Result<Option<Self::Config>> {
+        self.as_ref().get_tenant(id).await
+    }
+}
+
+struct CacheEntry<T> {
+    config: Option<T>,
+    expires_at: Instant,
+}
+
+const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+struct CachedTenantProviderInner<P: TenantProvider> {
+    provider: P,
+    cache: RwLock<HashMap<String, CacheEntry<P::Config>>>,
+}
+
+/// A [`TenantProvider`] that caches the configurations fetched from another
+/// provider.
+///
+/// The configurations are cached for `ttl`, and the unknown tenants for a
+/// shorter time, see [`CachedTenantProvider::negative_ttl`]. If the inner
+/// provider fails when an entry expires, the stale configuration is used until
+/// the next attempt, so a temporary outage of the configuration service does
+/// not take down all the tenants.
+///
+/// Since the tenant ids come from the requests, the cache holds at most
+/// 10,000 entries by default, see [`CachedTenantProvider::max_entries`]. When
+/// it is full, the expired entries and the unknown tenants are evicted first.
+///
+/// The provider is cheap to clone and the clones share the cache, keep a clone
+/// to refresh the tenants when their settings or secrets are rotated.
+///
+/// # Example
+///
+/// ```
+/// use std::{collections::HashMap, time::Duration};
+///
+/// use poem::{
+///     handler,
+///     middleware::TenantResolver,
+///     web::{CachedTenantProvider, Tenant},
+///     EndpointExt,
+/// };
+///
+/// #[derive(Clone)]
+/// struct TenantConfig {
+///     api_secret: String,
+/// }
+///
+/// #[handler]
+/// fn index(_tenant: Tenant<TenantConfig>) {}
+///
+/// let tenants: HashMap<String, TenantConfig> = HashMap::new();
+/// let provider = CachedTenantProvider::new(tenants, Duration::from_secs(300));
+/// let app = index.with(TenantResolver::new(provider.clone()).header("x-tenant-id"));
+///
+/// // after the secrets of `acme` are rotated
+/// provider.invalidate("acme");
+/// ```
+pub struct CachedTenantProvider<P: TenantProvider> {
+    inner: Arc<CachedTenantProviderInner<P>>,
+    ttl: Duration,
+    negative_ttl: Duration,
+    max_entries: usize,
+}
+
+impl<P: TenantProvider> Clone for CachedTenantProvider<P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            ttl: self.ttl,
+            negative_ttl: self.negative_ttl,
+            max_entries: self.max_entries,
+        }
+    }
+}
+
+impl<P: TenantProvider> CachedTenantProvider<P> {
+    /// Create a `CachedTenantProvider` that caches the configurations of
+    /// `provider` for `ttl`.
+    pub fn new(provider: P, ttl: Duration) -> Self {
+        Self {
+            inner: Arc::new(CachedTenantProviderInner {
+                provider,
+                cache: Default::default(),
+            }),
+            ttl,
+            negative_ttl: DEFAULT_NEGATIVE_TTL.min(ttl),
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+
+    /// Sets how long the unknown tenants are cached, use [`Duration::ZERO`]
+    /// to not cache them.
+    ///
+    /// Default is 5 seconds, or `ttl` if it is shorter.
+    #[must_use]
+    pub fn negative_ttl(self, negative_ttl: Duration) -> Self {
+        Self {
+            negative_ttl,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of cached tenants.
+    ///
+    /// Default is `10000`.
+    #[must_use]
+    pub fn max_entries(self, max_entries: usize) -> Self {
+        Self {
+            max_entries: max_entries.max(1),
+            ..self
+        }
+    }
+
+    /// Fetches the configuration of the tenant from the inner provider and
+    /// updates the cache.
+    pub async fn refresh(&self, id: &str) -> Result<Option<P::Config>> {
+        let config = self.inner.provider.get_tenant(id).await?;
+        let ttl = if config.is_some() {
+            self.ttl
+        } else {
+            self.negative_ttl
+        };
+
+        let mut cache = self.inner.cache.write();
+        if config.is_none() && ttl.is_zero() {
+            cache.remove(id);
+            return Ok(config);
+        }
+        if cache.len() >= self.max_entries && !cache.contains_key(id) {
+            evict(&mut cache, self.max_entries);
+        }
+        cache.insert(
+            id.to_string(),
+            CacheEntry {
+                config: config.clone(),
+                expires_at: Instant::now() + ttl,
+            },
+        );
+        Ok(config)
+    }
+
+    /// Removes the tenant from the cache, the configuration is fetched again
+    /// on the next request.
+    pub fn invalidate(&self, id: &str) {
+        self.inner.cache.write().remove(id);
+    }
+
+    /// Removes all the tenants from the cache.
+    pub fn invalidate_all(&self) {
+        self.inner.cache.write().clear();
+    }
+}
+
+/// Makes room in a full cache, removing a tenth of the entries at least so that
+/// the cost is amortized over the following insertions.
+fn evict<T>(cache: &mut HashMap<String, CacheEntry<T>>, max_entries: usize) {
+    let now = Instant::now();
+    cache.retain(|_, entry| entry.expires_at > now && entry.config.is_some());
+
+    let target = max_entries - max_entries / 10 - 1;
+    if cache.len() > target {
+        let keys = cache
+            .keys()
+            .take(cache.len() - target)
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            cache.remove(&key);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<P: TenantProvider> TenantProvider for CachedTenantProvider<P> {
+    type Config = P::Config;
+
+    async fn get_tenant(&self, id: &str) -> Result<Option<Self::Config>> {
+        let stale = match self.inner.cache.read().get(id) {
+            Some(entry) if entry.expires_at > Instant::now() => return Ok(entry.config.clone()),
+            Some(entry) => Some(entry.config.clone()),
+            None => None,
+        };
+
+        match (self.refresh(id).await, stale) {
+            (Ok(config), _) => Ok(config),
+            (Err(err), Some(config)) => {
+                tracing::warn!(
+                    tenant = id,
+                    error = %err,
+                    "failed to refresh the tenant, using the stale configuration"
+                );
+                Ok(config)
+            }
+            (Err(err), None) => Err(err),
+        }
+    }
+}
+
+/// The identifier of the tenant of the request.
+///
+/// It is inserted into the request extensions by the
+/// [`TenantResolver`](crate::middleware::TenantResolver) middleware and does
+/// not depend on the type of the tenant configuration, so the other
+/// middlewares can use it to isolate the data of the tenants. For example,
+/// [`CrawlBudget`](crate::middleware::CrawlBudget) keeps separate budgets for
+/// each tenant, and `ServerSession` stores the sessions with the keys scoped
+/// by [`TenantId::scoped`].
+///
+/// # Errors
+///
+/// - [`TenantError`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TenantId(pub Arc<str>);
+
+impl TenantId {
+    /// Returns the identifier as a string slice.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `key` prefixed with the identifier of the tenant, which can be
+    /// used as the key of the shared storages.
+    pub fn scoped(&self, key: &str) -> String {
+        format!("{}:{}", self.0, key)
+    }
+}
+
+impl Display for TenantId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for TenantId {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req
+            .extensions()
+            .get::<TenantId>()
+            .cloned()
+            .ok_or(TenantError::Missing)?)
+    }
+}
+
+/// An extractor that extracts the tenant resolved by the
+/// [`TenantResolver`](crate::middleware::TenantResolver) middleware.
+///
+/// `T` is the [`TenantProvider::Config`] type of the provider.
+///
+/// # Errors
+///
+/// - [`TenantError`]
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use poem::{
+///     get, handler, middleware::TenantResolver, test::TestClient, web::Tenant, EndpointExt, Route,
+/// };
+///
+/// #[derive(Clone)]
+/// struct TenantConfig {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn index(tenant: Tenant<TenantConfig>) -> String {
+///     format!("{}: {}", tenant.id(), tenant.name)
+/// }
+///
+/// let tenants = HashMap::from([(
+///     "acme".to_string(),
+///     TenantConfig {
+///         name: "ACME Corporation".to_string(),
+///     },
+/// )]);
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .with(TenantResolver::new(tenants).subdomain("example.com"));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").header("host", "acme.example.com").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("acme: ACME Corporation").await;
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tenant<T> {
+    id: TenantId,
+    config: T,
+}
+
+impl<T> Tenant<T> {
+    pub(crate) fn new(id: TenantId, config: T) -> Self {
+        Self { id, config }
+    }
+
+    /// Returns the identifier of the tenant.
+    #[inline]
+    pub fn id(&self) -> &TenantId {
+        &self.id
+    }
+
+    /// Returns the configuration of the tenant.
+    #[inline]
+    pub fn config(&self) -> &T {
+        &self.config
+    }
+
+    /// Consumes this object and returns the configuration of the tenant.
+    #[inline]
+    pub fn into_config(self) -> T {
+        self.config
+    }
+}
+
+impl<T> std::ops::Deref for Tenant<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.config
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a, T: Clone + Send + Sync + 'static> FromRequest<'a> for Tenant<T> {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req
+            .extensions()
+            .get::<Tenant<T>>()
+            .cloned()
+            .ok_or(TenantError::Missing)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{error::InternalServerError, http::StatusCode};
+
+    #[derive(Default)]
+    struct CountingProvider {
+        calls: AtomicUsize,
+        fail: AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl TenantProvider for CountingProvider {
+        type Config = usize;
+
+        async fn get_tenant(&self, id: &str) -> Result<Option<Self::Config>> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(InternalServerError(std::io::Error::other("unavailable")));
+            }
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok((id == "acme").then_some(calls))
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_provider() {
+        let provider = Arc::new(CountingProvider::default());
+        let cached = CachedTenantProvider::new(provider.clone(), Duration::from_secs(60));
+
+        assert_eq!(cached.get_tenant("acme").await.unwrap(), Some(1));
+        assert_eq!(cached.get_tenant("acme").await.unwrap(), Some(1));
+        assert_eq!(cached.get_tenant("initech").await.unwrap(), None);
+        assert_eq!(cached.get_tenant("initech").await.unwrap(), None);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+
+        cached.invalidate("acme");
+        assert_eq!(cached.get_tenant("acme").await.unwrap(), Some(3));
+        assert_eq!(cached.refresh("acme").await.unwrap(), Some(4));
+        assert_eq!(cached.get_tenant("acme").await.unwrap(), Some(4));
+    }
+
+    #[tokio::test]
+    async fn stale_on_error() {
+        let provider = Arc::new(CountingProvider::default());
+        let cached = CachedTenantProvider::new(provider.clone(), Duration::ZERO);
+
+        assert_eq!(cached.get_tenant("acme").await.unwrap(), Some(1));
+        provider.fail.store(true, Ordering::SeqCst);
+        assert_eq!(cached.get_tenant("acme").await.unwrap(), Some(1));
+
+        cached.invalidate_all();
+        assert_eq!(
+            cached.get_tenant("acme").await.unwrap_err().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_tenants() {
+        let provider = Arc::new(CountingProvider::default());
+        let cached = CachedTenantProvider::new(provider.clone(), Duration::from_secs(60))
+            .negative_ttl(Duration::ZERO);
+
+        assert_eq!(cached.get_tenant("initech").await.unwrap(), None);
+        assert_eq!(cached.get_tenant("initech").await.unwrap(), None);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+        assert!(cached.inner.cache.read().is_empty());
+    }
+
+    #[tokio::test]
+    async fn max_entries() {
+        let provider = Arc::new(CountingProvider::default());
+        let cached =
+            CachedTenantProvider::new(provider.clone(), Duration::from_secs(60)).max_entries(100);
+
+        assert_eq!(cached.get_tenant("acme").await.unwrap(), Some(1));
+        for i in 0..1000 {
+            cached.get_tenant(&format!("tenant-{i}")).await.unwrap();
+            assert!(cached.inner.cache.read().len() <= 100);
+        }
+
+        // the unknown tenants are evicted first
+        assert_eq!(cached.get_tenant("acme").await.unwrap(), Some(1));
+    }
+}
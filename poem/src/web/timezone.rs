@@ -0,0 +1,297 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use percent_encoding::percent_decode_str;
+
+use crate::{
+    http::{header, header::HeaderName},
+    FromRequest, Request, RequestBody, Result,
+};
+
+/// The sources of the [`Timezone`] extractor.
+///
+/// Add it to the data of the endpoint to change the sources, otherwise the
+/// timezone is resolved from the `tz` query parameter, the `X-Timezone`
+/// header and the `tz` cookie, in that order, and defaults to UTC.
+#[cfg_attr(docsrs, doc(cfg(feature = "timezone")))]
+#[derive(Debug, Clone)]
+pub struct TimezoneConfig {
+    query: String,
+    header: HeaderName,
+    cookie: String,
+    default: Tz,
+}
+
+impl Default for TimezoneConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimezoneConfig {
+    /// Create a `TimezoneConfig` with the default sources.
+    pub fn new() -> Self {
+        Self {
+            query: "tz".to_string(),
+            header: HeaderName::from_static("x-timezone"),
+            cookie: "tz".to_string(),
+            default: Tz::UTC,
+        }
+    }
+
+    /// Sets the name of the query parameter, default to `tz`.
+    #[must_use]
+    pub fn query(self, name: impl Into<String>) -> Self {
+        Self {
+            query: name.into(),
+            ..self
+        }
+    }
+
+    /// Sets the name of the header, default to `X-Timezone`.
+    #[must_use]
+    pub fn header(self, name: HeaderName) -> Self {
+        Self {
+            header: name,
+            ..self
+        }
+    }
+
+    /// Sets the name of the cookie, default to `tz`.
+    #[must_use]
+    pub fn cookie(self, name: impl Into<String>) -> Self {
+        Self {
+            cookie: name.into(),
+            ..self
+        }
+    }
+
+    /// Sets the timezone used when the request does not specify a valid
+    /// timezone, default to UTC.
+    #[must_use]
+    pub fn default_timezone(self, tz: Tz) -> Self {
+        Self {
+            default: tz,
+            ..self
+        }
+    }
+
+    fn resolve(&self, req: &Request) -> Tz {
+        let from_query = || -> Option<Tz> {
+            serde_urlencoded::from_str::<Vec<(String, String)>>(req.uri().query()?)
+                .ok()?
+                .into_iter()
+                .find(|(name, _)| *name == self.query)
+                .and_then(|(_, value)| value.parse().ok())
+        };
+        let from_header = || -> Option<Tz> {
+            req.headers()
+                .get(&self.header)?
+                .to_str()
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        };
+        let from_cookie = || -> Option<Tz> {
+            req.headers()
+                .get_all(header::COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(';'))
+                .find_map(|pair| {
+                    let (name, value) = pair.trim().split_once('=')?;
+                    (name == self.cookie).then_some(value)
+                })
+                .and_then(|value| percent_decode_str(value).decode_utf8().ok()?.parse().ok())
+        };
+
+        from_query()
+            .or_else(from_header)
+            .or_else(from_cookie)
+            .unwrap_or(self.default)
+    }
+}
+
+/// An extractor for the timezone of the client, such as `Europe/Berlin`.
+///
+/// The timezone is resolved with the [`TimezoneConfig`] in the data of the
+/// endpoint. Browsers do not send their timezone, so it is usually stored in
+/// a cookie by a script:
+///
+/// ```js
+/// document.cookie = "tz=" + encodeURIComponent(
+///   Intl.DateTimeFormat().resolvedOptions().timeZone
+/// ) + "; path=/; SameSite=Lax";
+/// ```
+///
+/// The timestamps are converted to the timezone with [`Timezone::convert`],
+/// and can be formatted for the language of the client with
+/// [`Timezone::format_datetime`] and the `i18n` feature.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use poem::{handler, test::TestClient, web::Timezone};
+///
+/// #[handler]
+/// fn index(tz: Timezone) -> String {
+///     let published_at = Utc.with_ymd_and_hms(2024, 2, 29, 12, 0, 0).unwrap();
+///     tz.format(&published_at, "%Y-%m-%d %H:%M %Z")
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .header("cookie", "tz=Asia%2FTokyo")
+///     .send()
+///     .await;
+/// resp.assert_text("2024-02-29 21:00 JST").await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "timezone")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timezone(pub Tz);
+
+impl Timezone {
+    /// Returns the current time in the timezone.
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.0)
+    }
+
+    /// Converts the timestamp to the timezone.
+    pub fn convert<T: TimeZone>(&self, datetime: &DateTime<T>) -> DateTime<Tz> {
+        datetime.with_timezone(&self.0)
+    }
+
+    /// Converts the timestamp to the timezone and formats it with the
+    /// [`chrono` format string](chrono::format::strftime).
+    pub fn format<T: TimeZone>(&self, datetime: &DateTime<T>, fmt: &str) -> String {
+        self.convert(datetime).format(fmt).to_string()
+    }
+
+    /// Converts the timestamp to the timezone and formats it for the language
+    /// of the client.
+    ///
+    /// See also: [`Locale::format_datetime`](crate::i18n::Locale::format_datetime)
+    #[cfg(feature = "i18n")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "i18n")))]
+    pub fn format_datetime<T: TimeZone>(
+        &self,
+        datetime: &DateTime<T>,
+        locale: &crate::i18n::Locale,
+    ) -> String {
+        locale.format_datetime(&self.convert(datetime))
+    }
+}
+
+impl Display for Timezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0.name())
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for Timezone {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let tz = match req.data::<TimezoneConfig>() {
+            Some(config) => config.resolve(req),
+            None => TimezoneConfig::default().resolve(req),
+        };
+        Ok(Timezone(tz))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn resolve(config: Option<TimezoneConfig>, uri: &str, headers: &[(&str, &str)]) -> Tz {
+        let mut builder = Request::builder().uri_str(uri);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let mut req = builder.finish();
+        if let Some(config) = config {
+            req.extensions_mut().insert(config);
+        }
+        Timezone::from_request_without_body(&req).await.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn sources() {
+        assert_eq!(resolve(None, "/", &[]).await, Tz::UTC);
+        assert_eq!(
+            resolve(
+                None,
+                "/?tz=Europe%2FBerlin",
+                &[("x-timezone", "Asia/Tokyo")]
+            )
+            .await,
+            Tz::Europe__Berlin
+        );
+        assert_eq!(
+            resolve(
+                None,
+                "/",
+                &[
+                    ("x-timezone", "Asia/Tokyo"),
+                    ("cookie", "tz=Europe%2FBerlin")
+                ]
+            )
+            .await,
+            Tz::Asia__Tokyo
+        );
+        assert_eq!(
+            resolve(None, "/", &[("cookie", "a=1; tz=America%2FNew_York")]).await,
+            Tz::America__New_York
+        );
+
+        // invalid timezones are ignored
+        assert_eq!(
+            resolve(None, "/?tz=Mars%2FOlympus", &[("cookie", "tz=Asia/Tokyo")]).await,
+            Tz::Asia__Tokyo
+        );
+    }
+
+    #[tokio::test]
+    async fn config() {
+        let config = TimezoneConfig::new()
+            .query("zone")
+            .header(HeaderName::from_static("time-zone"))
+            .cookie("zone")
+            .default_timezone(Tz::Europe__London);
+        assert_eq!(
+            resolve(Some(config.clone()), "/?tz=Asia%2FTokyo", &[]).await,
+            Tz::Europe__London
+        );
+        assert_eq!(
+            resolve(Some(config.clone()), "/?zone=Asia%2FTokyo", &[]).await,
+            Tz::Asia__Tokyo
+        );
+        assert_eq!(
+            resolve(Some(config.clone()), "/", &[("time-zone", "Asia/Tokyo")]).await,
+            Tz::Asia__Tokyo
+        );
+        assert_eq!(
+            resolve(Some(config), "/", &[("cookie", "zone=Asia/Tokyo")]).await,
+            Tz::Asia__Tokyo
+        );
+    }
+
+    #[test]
+    fn convert() {
+        let tz = Timezone(Tz::America__New_York);
+        let datetime = Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            tz.format(&datetime, "%Y-%m-%d %H:%M %z"),
+            "2024-07-01 08:00 -0400"
+        );
+        assert_eq!(tz.convert(&datetime), datetime);
+        assert_eq!(tz.to_string(), "America/New_York");
+    }
+}
@@ -24,6 +24,7 @@ pub struct WebSocket {
     on_upgrade: OnUpgrade,
     protocols: Option<Box<[Cow<'static, str>]>>,
     sec_websocket_protocol: Option<HeaderValue>,
+    origin: Option<HeaderValue>,
 }
 
 impl WebSocket {
@@ -56,12 +57,14 @@ impl WebSocket {
             .ok_or(WebSocketError::InvalidProtocol)?;
 
         let sec_websocket_protocol = req.headers().get(header::SEC_WEBSOCKET_PROTOCOL).cloned();
+        let origin = req.headers().get(header::ORIGIN).cloned();
 
         Ok(Self {
             key,
             on_upgrade: req.take_upgrade()?,
             protocols: None,
             sec_websocket_protocol,
+            origin,
         })
     }
 }
@@ -110,6 +113,82 @@ impl WebSocket {
         self
     }
 
+    /// Returns the value of the `Origin` header.
+    pub fn origin(&self) -> Option<&str> {
+        self.origin.as_ref().and_then(|value| value.to_str().ok())
+    }
+
+    /// Rejects the upgrade with [`WebSocketError::OriginNotAllowed`] if the
+    /// `Origin` header is not in the specified list, the comparison is
+    /// case-insensitive.
+    ///
+    /// Browsers do not apply CORS to websocket connections, so without this
+    /// check any website can open a websocket with the cookies of the user.
+    /// Requests without the `Origin` header are not sent by browsers and are
+    /// accepted.
+    ///
+    /// ```
+    /// use poem::{get, handler, web::websocket::WebSocket, IntoResponse, Result, Route};
+    ///
+    /// #[handler]
+    /// async fn index(ws: WebSocket) -> Result<impl IntoResponse> {
+    ///     Ok(ws
+    ///         .allow_origins(["https://example.com"])?
+    ///         .on_upgrade(|socket| async move {
+    ///             // ...
+    ///         }))
+    /// }
+    ///
+    /// let app = Route::new().at("/", get(index));
+    /// ```
+    pub fn allow_origins<I, T>(self, origins: I) -> Result<Self, WebSocketError>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let mut origins = origins.into_iter();
+        self.check_origin(|origin| match origin {
+            Some(origin) => origins.any(|allowed| allowed.as_ref().eq_ignore_ascii_case(origin)),
+            None => true,
+        })
+    }
+
+    /// Rejects the upgrade with [`WebSocketError::OriginNotAllowed`] if the
+    /// function returns `false` for the value of the `Origin` header.
+    ///
+    /// ```
+    /// use poem::{get, handler, web::websocket::WebSocket, IntoResponse, Result, Route};
+    ///
+    /// #[handler]
+    /// async fn index(ws: WebSocket) -> Result<impl IntoResponse> {
+    ///     Ok(ws
+    ///         .check_origin(|origin| {
+    ///             origin.map_or(false, |origin| origin.ends_with(".example.com"))
+    ///         })?
+    ///         .on_upgrade(|socket| async move {
+    ///             // ...
+    ///         }))
+    /// }
+    ///
+    /// let app = Route::new().at("/", get(index));
+    /// ```
+    pub fn check_origin<F>(self, f: F) -> Result<Self, WebSocketError>
+    where
+        F: FnOnce(Option<&str>) -> bool,
+    {
+        let allowed = match &self.origin {
+            Some(origin) => match origin.to_str() {
+                Ok(origin) => f(Some(origin)),
+                Err(_) => false,
+            },
+            None => f(None),
+        };
+        if !allowed {
+            return Err(WebSocketError::OriginNotAllowed);
+        }
+        Ok(self)
+    }
+
     /// Finalize upgrading the connection and call the provided `callback` with
     /// the stream.
     ///
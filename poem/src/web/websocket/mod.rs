@@ -97,6 +97,54 @@ mod tests {
         handle.abort();
     }
 
+    #[tokio::test]
+    async fn test_allow_origins() {
+        #[handler(internal)]
+        async fn index(ws: WebSocket) -> crate::Result<impl IntoResponse> {
+            Ok(ws
+                .allow_origins(["https://example.com"])?
+                .on_upgrade(|_| async move {}))
+        }
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+
+        let handle = tokio::spawn(async move {
+            let _ = Server::new_with_acceptor(acceptor).run(index).await;
+        });
+
+        async fn connect(addr: SocketAddr, origin: Option<&str>) -> bool {
+            let mut builder = http::Request::builder()
+                .uri(format!("ws://{addr}"))
+                .header(header::SEC_WEBSOCKET_KEY, "test_key")
+                .header(header::UPGRADE, "websocket")
+                .header(header::HOST, "localhost")
+                .header(header::CONNECTION, "upgrade")
+                .header(header::SEC_WEBSOCKET_VERSION, "13");
+            if let Some(origin) = origin {
+                builder = builder.header(header::ORIGIN, origin);
+            }
+            tokio_tungstenite::connect_async(builder.body(()).unwrap())
+                .await
+                .is_ok()
+        }
+
+        assert!(connect(addr, None).await);
+        assert!(connect(addr, Some("https://example.com")).await);
+        assert!(connect(addr, Some("HTTPS://EXAMPLE.COM")).await);
+        assert!(!connect(addr, Some("https://evil.com")).await);
+
+        handle.abort();
+    }
+
     #[tokio::test]
     async fn test_websocket_echo() {
         #[handler(internal)]
@@ -134,27 +134,26 @@ fn is_xml_content_type(content_type: &str) -> bool {
             .map_or(false, |v| v == "xml")))
 }
 
+/// Serializes the value as XML, using `root` as the root element name if
+/// the value is not a struct.
+pub(crate) fn to_xml_string<T: Serialize>(value: &T) -> Result<String, quick_xml::DeError> {
+    match quick_xml::se::to_string(value) {
+        Err(quick_xml::DeError::Unsupported(_)) => {
+            quick_xml::se::to_string_with_root("root", value)
+        }
+        res => res,
+    }
+}
+
 impl<T: Serialize + Send> IntoResponse for Xml<T> {
     fn into_response(self) -> Response {
-        let data = match quick_xml::se::to_string(&self.0) {
+        let data = match to_xml_string(&self.0) {
             Ok(data) => data,
-            Err(err) => match err {
-                quick_xml::DeError::Unsupported(_) => {
-                    match quick_xml::se::to_string_with_root("root", &self.0) {
-                        Ok(data) => data,
-                        Err(err) => {
-                            return Response::builder()
-                                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                                .body(err.to_string());
-                        }
-                    }
-                }
-                _ => {
-                    return Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .body(err.to_string());
-                }
-            },
+            Err(err) => {
+                return Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(err.to_string())
+            }
         };
         Response::builder()
             .header(header::CONTENT_TYPE, "application/xml; charset=utf-8")